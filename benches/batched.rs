@@ -0,0 +1,68 @@
+use std::collections::HashMap;
+
+use bristol_circuit::gadgets;
+use criterion::{criterion_group, criterion_main, Criterion, Throughput};
+
+/// Instance `i` of each input, from a word per wire.
+fn instance(inputs: &HashMap<String, Vec<u64>>, i: usize) -> HashMap<String, Vec<bool>> {
+    inputs
+        .iter()
+        .map(|(name, words)| {
+            let bits = words.iter().map(|word| (word >> i) & 1 == 1).collect();
+            (name.clone(), bits)
+        })
+        .collect()
+}
+
+fn batched_vs_scalar(c: &mut Criterion) {
+    let circuit = gadgets::adder(64, true);
+    let word = |seed: u64| seed.wrapping_mul(0x9e37_79b9_7f4a_7c15);
+    let inputs = HashMap::from([
+        ("a".to_string(), (0..64).map(word).collect::<Vec<_>>()),
+        ("b".to_string(), (64..128).map(word).collect::<Vec<_>>()),
+    ]);
+    let instances = (0..64).map(|i| instance(&inputs, i)).collect::<Vec<_>>();
+
+    let mut group = c.benchmark_group("adder64_x64");
+    group.throughput(Throughput::Elements(64));
+
+    group.bench_function("eval_boolean", |b| {
+        b.iter(|| {
+            instances
+                .iter()
+                .map(|inputs| circuit.eval_boolean(inputs).unwrap())
+                .collect::<Vec<_>>()
+        })
+    });
+
+    group.bench_function("eval_boolean_batched", |b| {
+        b.iter(|| circuit.eval_boolean_batched(&inputs).unwrap())
+    });
+
+    // 4096 instances.
+    let words = 64;
+    let wide_inputs = inputs
+        .iter()
+        .map(|(name, values)| {
+            let words = values
+                .iter()
+                .flat_map(|&value| (0..words).map(move |k| value.rotate_left(k as u32)))
+                .collect::<Vec<_>>();
+            (name.clone(), words)
+        })
+        .collect::<HashMap<_, _>>();
+
+    group.throughput(Throughput::Elements(64 * words as u64));
+    group.bench_function("eval_boolean_batched_words_64", |b| {
+        b.iter(|| {
+            circuit
+                .eval_boolean_batched_words(&wide_inputs, words)
+                .unwrap()
+        })
+    });
+
+    group.finish();
+}
+
+criterion_group!(benches, batched_vs_scalar);
+criterion_main!(benches);
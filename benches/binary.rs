@@ -0,0 +1,63 @@
+use std::io::BufReader;
+
+use bristol_circuit::{BristolCircuit, CircuitBuilder};
+use criterion::{criterion_group, criterion_main, Criterion, Throughput};
+
+/// A million gates alternately combining the previous two wires, as in the `parse` benchmark.
+fn million_gates() -> BristolCircuit {
+    let mut b = CircuitBuilder::new();
+    let (mut x, mut y) = (b.input("a"), b.input("b"));
+
+    for i in 0..1_000_000 {
+        let op = if i % 2 == 0 { "AND" } else { "XOR" };
+        (x, y) = (y, b.gate(op, &[x, y]));
+    }
+
+    b.output("c", y);
+    b.build().unwrap()
+}
+
+fn binary(c: &mut Criterion) {
+    let circuit = million_gates();
+    let text = circuit.get_bristol_string().unwrap();
+    let bytes = circuit.to_binary_bytes().unwrap();
+
+    println!(
+        "{} gates: {} bytes of text, {} bytes of binary",
+        circuit.gates.len(),
+        text.len(),
+        bytes.len()
+    );
+
+    let mut group = c.benchmark_group("million_gates_binary");
+    group.throughput(Throughput::Elements(circuit.gates.len() as u64));
+    group.sample_size(10);
+
+    group.bench_function("read_binary", |b| {
+        b.iter_with_large_drop(|| BristolCircuit::read_binary(&mut bytes.as_slice()).unwrap())
+    });
+
+    // The text equivalent, for comparison. Both leave dropping the circuit out of the timing.
+    group.bench_function("read_info_and_bristol", |b| {
+        b.iter_with_large_drop(|| {
+            BristolCircuit::read_info_and_bristol(
+                &circuit.info,
+                &mut BufReader::new(text.as_bytes()),
+            )
+            .unwrap()
+        })
+    });
+
+    group.bench_function("write_binary", |b| {
+        b.iter(|| {
+            let mut out = Vec::new();
+            circuit.write_binary(&mut out).unwrap();
+            out
+        })
+    });
+
+    group.finish();
+}
+
+criterion_group!(benches, binary);
+criterion_main!(benches);
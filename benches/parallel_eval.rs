@@ -0,0 +1,113 @@
+use std::collections::HashMap;
+
+use bristol_circuit::{
+    BristolCircuit, BristolCircuitError, CircuitBuilder, Evaluator, ParallelEvaluator, WireId,
+};
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+
+const WIDTH: usize = 1 << 16;
+const DEPTH: usize = 8;
+
+/// `DEPTH` layers of `WIDTH` gates, each reading two pseudo-random wires of the layer before.
+fn wide_and_shallow() -> BristolCircuit {
+    let mut b = CircuitBuilder::new();
+    let mut layer = b.input_bus("x", WIDTH);
+    let mut state = 0x9e37_79b9_7f4a_7c15u64;
+
+    let mut pick = |layer: &[WireId]| {
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+        layer[state as usize % layer.len()]
+    };
+
+    for depth in 0..DEPTH {
+        let op = if depth % 2 == 0 { "AND" } else { "XOR" };
+        layer = (0..WIDTH)
+            .map(|_| {
+                let (x, y) = (pick(&layer), pick(&layer));
+                b.gate(op, &[x, y])
+            })
+            .collect();
+    }
+
+    b.output_bus("y", &layer);
+    b.build().unwrap()
+}
+
+struct Boolean;
+
+impl ParallelEvaluator<bool> for Boolean {
+    fn constant(&self, _name: &str, value: &str) -> Result<bool, BristolCircuitError> {
+        Ok(value == "1")
+    }
+
+    fn eval_gate(
+        &self,
+        op: &str,
+        inputs: &[bool],
+        _gate_index: usize,
+    ) -> Result<Vec<bool>, BristolCircuitError> {
+        Ok(vec![match op {
+            "AND" => inputs[0] & inputs[1],
+            _ => inputs[0] ^ inputs[1],
+        }])
+    }
+}
+
+/// The same, through the sequential driver.
+impl Evaluator<bool> for Boolean {
+    fn constant(&mut self, name: &str, value: &str) -> Result<bool, BristolCircuitError> {
+        ParallelEvaluator::constant(self, name, value)
+    }
+
+    fn eval_gate(
+        &mut self,
+        op: &str,
+        inputs: &[bool],
+        gate_index: usize,
+    ) -> Result<Vec<bool>, BristolCircuitError> {
+        ParallelEvaluator::eval_gate(self, op, inputs, gate_index)
+    }
+}
+
+fn parallel_eval(c: &mut Criterion) {
+    let circuit = wide_and_shallow();
+    let inputs = HashMap::from([(
+        "x".to_string(),
+        (0..WIDTH).map(|i| i % 3 == 0).collect::<Vec<_>>(),
+    )]);
+
+    let mut group = c.benchmark_group("wide_and_shallow");
+    group.throughput(Throughput::Elements(circuit.gates.len() as u64));
+    group.sample_size(10);
+
+    group.bench_function("eval_boolean", |b| {
+        b.iter(|| circuit.eval_boolean(&inputs).unwrap())
+    });
+
+    group.bench_function("eval_with", |b| {
+        b.iter(|| circuit.eval_with(&inputs, &mut Boolean).unwrap())
+    });
+
+    let max_threads = std::thread::available_parallelism().map_or(1, |n| n.get());
+    let thread_counts = [1, 2, 4, 8, 16]
+        .into_iter()
+        .filter(|&threads| threads <= max_threads.max(2));
+
+    for threads in thread_counts {
+        let pool = rayon::ThreadPoolBuilder::new()
+            .num_threads(threads)
+            .build()
+            .unwrap();
+
+        group.bench_with_input(BenchmarkId::new("threads", threads), &threads, |b, _| {
+            b.iter(|| pool.install(|| circuit.eval_parallel(&inputs, &Boolean).unwrap()))
+        });
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, parallel_eval);
+criterion_main!(benches);
@@ -0,0 +1,54 @@
+use bristol_circuit::{BristolCircuit, CircuitBuilder};
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+
+/// A million gates alternately combining the previous two wires, as in the `parse` benchmark.
+fn million_gates() -> BristolCircuit {
+    let mut b = CircuitBuilder::new();
+    let (mut x, mut y) = (b.input("a"), b.input("b"));
+
+    for i in 0..1_000_000 {
+        let op = if i % 2 == 0 { "AND" } else { "XOR" };
+        (x, y) = (y, b.gate(op, &[x, y]));
+    }
+
+    b.output("c", y);
+    b.build().unwrap()
+}
+
+fn parallel_parse(c: &mut Criterion) {
+    let circuit = million_gates();
+    let text = circuit.get_bristol_string().unwrap();
+
+    let mut group = c.benchmark_group("million_gates_parallel");
+    group.throughput(Throughput::Bytes(text.len() as u64));
+    group.sample_size(10);
+
+    group.bench_function("serial", |b| {
+        b.iter(|| BristolCircuit::from_info_and_bristol_string(&circuit.info, &text).unwrap())
+    });
+
+    let max_threads = std::thread::available_parallelism().map_or(1, |n| n.get());
+    let thread_counts = [1, 2, 4, 8, 16]
+        .into_iter()
+        .filter(|&threads| threads <= max_threads.max(2));
+
+    for threads in thread_counts {
+        let pool = rayon::ThreadPoolBuilder::new()
+            .num_threads(threads)
+            .build()
+            .unwrap();
+
+        group.bench_with_input(BenchmarkId::new("threads", threads), &threads, |b, _| {
+            b.iter(|| {
+                pool.install(|| {
+                    BristolCircuit::read_info_and_bristol_parallel(&circuit.info, &text).unwrap()
+                })
+            })
+        });
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, parallel_parse);
+criterion_main!(benches);
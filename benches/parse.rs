@@ -0,0 +1,97 @@
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    io::BufReader,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+use bristol_circuit::{BristolCircuit, BristolCircuitReader, CircuitBuilder};
+use criterion::{criterion_group, criterion_main, Criterion, Throughput};
+
+/// Counts allocations, so the benchmark can report them as well as time.
+struct CountingAlloc;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAlloc {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAlloc = CountingAlloc;
+
+/// A million gates alternately combining the previous two wires, so most indices are 6 or 7
+/// digits long as in real circuits.
+fn million_gates() -> BristolCircuit {
+    let mut b = CircuitBuilder::new();
+    let (mut x, mut y) = (b.input("a"), b.input("b"));
+
+    for i in 0..1_000_000 {
+        let op = if i % 2 == 0 { "AND" } else { "XOR" };
+        (x, y) = (y, b.gate(op, &[x, y]));
+    }
+
+    b.output("c", y);
+    b.build().unwrap()
+}
+
+fn parse(c: &mut Criterion) {
+    let circuit = million_gates();
+    let text = circuit.get_bristol_string().unwrap();
+
+    let before = ALLOCATIONS.load(Ordering::Relaxed);
+    let parsed =
+        BristolCircuit::read_info_and_bristol(&circuit.info, &mut BufReader::new(text.as_bytes()))
+            .unwrap();
+    let allocations = ALLOCATIONS.load(Ordering::Relaxed) - before;
+
+    // Gates with two inputs and one output store them inline, and the ops are shared.
+    println!(
+        "parsing {} gates made {} allocations, with {} bytes of op strings",
+        parsed.gates.len(),
+        allocations,
+        parsed.estimate_memory().op_strings_bytes
+    );
+
+    let mut group = c.benchmark_group("million_gates");
+    group.throughput(Throughput::Bytes(text.len() as u64));
+    group.sample_size(10);
+
+    group.bench_function("read_info_and_bristol", |b| {
+        b.iter(|| {
+            BristolCircuit::read_info_and_bristol(
+                &circuit.info,
+                &mut BufReader::new(text.as_bytes()),
+            )
+            .unwrap()
+        })
+    });
+
+    // Without storing the gates, so this is mostly tokenizing.
+    group.bench_function("circuit_reader", |b| {
+        b.iter(|| {
+            BristolCircuitReader::open(&circuit.info, BufReader::new(text.as_bytes()))
+                .unwrap()
+                .map(|gate| gate.unwrap().inputs.len())
+                .sum::<usize>()
+        })
+    });
+
+    group.bench_function("get_bristol_string", |b| {
+        b.iter(|| circuit.get_bristol_string().unwrap())
+    });
+
+    group.throughput(Throughput::Elements(circuit.gates.len() as u64));
+    group.bench_function("clone", |b| b.iter(|| circuit.clone()));
+
+    group.finish();
+}
+
+criterion_group!(benches, parse);
+criterion_main!(benches);
@@ -0,0 +1,42 @@
+use std::collections::HashMap;
+
+use bristol_circuit::gadgets;
+use criterion::{criterion_group, criterion_main, Criterion};
+
+fn bits(value: u64, width: usize) -> Vec<bool> {
+    (0..width).map(|i| (value >> i) & 1 == 1).collect()
+}
+
+fn prepared_vs_unprepared(c: &mut Criterion) {
+    let circuit = gadgets::adder(64, true);
+    let prepared = circuit.clone().prepare();
+
+    let inputs = HashMap::from([
+        ("a".to_string(), bits(0x0123_4567_89ab_cdef, 64)),
+        ("b".to_string(), bits(0xfedc_ba98_7654_3210, 64)),
+    ]);
+
+    let mut group = c.benchmark_group("adder64");
+
+    group.bench_function("eval_boolean", |b| {
+        b.iter(|| circuit.eval_boolean(&inputs).unwrap())
+    });
+
+    group.bench_function("prepared", |b| {
+        b.iter(|| prepared.eval_boolean(&inputs).unwrap())
+    });
+
+    let mut buffer = Vec::new();
+    group.bench_function("prepared_with_buffer", |b| {
+        b.iter(|| {
+            prepared
+                .eval_boolean_with_buffer(&inputs, &mut buffer)
+                .unwrap()
+        })
+    });
+
+    group.finish();
+}
+
+criterion_group!(benches, prepared_vs_unprepared);
+criterion_main!(benches);
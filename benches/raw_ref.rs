@@ -0,0 +1,80 @@
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+use bristol_circuit::{gadgets, RawBristolCircuit, RawBristolCircuitRef};
+use criterion::{criterion_group, criterion_main, Criterion};
+
+/// Counts allocated bytes, so the benchmark can report allocation as well as time.
+struct CountingAlloc;
+
+static ALLOCATED: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAlloc {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATED.fetch_add(layout.size(), Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAlloc = CountingAlloc;
+
+fn allocated_by<T>(f: impl FnOnce() -> T) -> usize {
+    let before = ALLOCATED.load(Ordering::Relaxed);
+    drop(f());
+    ALLOCATED.load(Ordering::Relaxed) - before
+}
+
+/// A large document, and the same document with its gate text on one line so that the JSON
+/// string has no escapes.
+fn documents() -> (Vec<u8>, Vec<u8>) {
+    let raw = gadgets::adder(4096, true).to_raw().unwrap();
+    let escaped = serde_json::to_vec(&raw).unwrap();
+
+    let unescaped = serde_json::to_vec(&RawBristolCircuit::new(
+        raw.bristol.replace('\n', " "),
+        raw.info,
+    ))
+    .unwrap();
+
+    (escaped, unescaped)
+}
+
+fn owned_vs_borrowed(c: &mut Criterion) {
+    let (escaped, unescaped) = documents();
+
+    for (name, doc) in [("escaped", &escaped), ("unescaped", &unescaped)] {
+        let owned = allocated_by(|| serde_json::from_slice::<RawBristolCircuit>(doc).unwrap());
+        let borrowed =
+            allocated_by(|| serde_json::from_slice::<RawBristolCircuitRef>(doc).unwrap());
+
+        println!(
+            "{} ({} bytes): owned allocates {} bytes, borrowed allocates {} bytes",
+            name,
+            doc.len(),
+            owned,
+            borrowed
+        );
+
+        let mut group = c.benchmark_group(format!("raw_{}", name));
+
+        group.bench_function("owned", |b| {
+            b.iter(|| serde_json::from_slice::<RawBristolCircuit>(doc).unwrap())
+        });
+
+        group.bench_function("borrowed", |b| {
+            b.iter(|| serde_json::from_slice::<RawBristolCircuitRef>(doc).unwrap())
+        });
+
+        group.finish();
+    }
+}
+
+criterion_group!(benches, owned_vs_borrowed);
+criterion_main!(benches);
@@ -0,0 +1,44 @@
+use std::io::BufReader;
+
+use bristol_circuit::{BristolCircuit, RandomCircuitConfig};
+use criterion::{criterion_group, criterion_main, Criterion};
+
+fn write_and_parse(c: &mut Criterion) {
+    let config = RandomCircuitConfig {
+        inputs: 64,
+        gates: 100_000,
+        ops: vec![
+            ("AND".to_string(), 1),
+            ("XOR".to_string(), 3),
+            ("INV".to_string(), 1),
+        ],
+        boolean: true,
+    };
+    let circuit = BristolCircuit::random_seeded(&config, 1).unwrap();
+    let text = circuit.get_bristol_string().unwrap();
+
+    let mut group = c.benchmark_group("random100k");
+
+    group.bench_function("write_bristol", |b| {
+        b.iter(|| {
+            let mut buffer = Vec::with_capacity(text.len());
+            circuit.write_bristol(&mut buffer).unwrap();
+            buffer
+        })
+    });
+
+    group.bench_function("read_info_and_bristol", |b| {
+        b.iter(|| {
+            BristolCircuit::read_info_and_bristol(
+                &circuit.info,
+                &mut BufReader::new(text.as_bytes()),
+            )
+            .unwrap()
+        })
+    });
+
+    group.finish();
+}
+
+criterion_group!(benches, write_and_parse);
+criterion_main!(benches);
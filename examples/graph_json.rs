@@ -0,0 +1,20 @@
+//! Writes the graph JSON of an 8-bit adder, for `graph_viewer.html`:
+//!
+//! ```sh
+//! cargo run --example graph_json > examples/graph.json
+//! ```
+
+use bristol_circuit::{gadgets, GraphJsonOptions};
+
+fn main() {
+    let circuit = gadgets::adder(8, true);
+
+    let json = circuit
+        .to_graph_json(&GraphJsonOptions {
+            levels: true,
+            ..Default::default()
+        })
+        .expect("adder graph serializes");
+
+    println!("{}", json);
+}
@@ -0,0 +1,28 @@
+#![no_main]
+
+use bristol_circuit::{BristolCircuit, CircuitInfo};
+use libfuzzer_sys::fuzz_target;
+
+// Inputs are the circuit info as JSON, a NUL byte, then the Bristol bytes. Without a NUL the
+// whole input is Bristol, read against info naming inputs a, b and output c.
+fuzz_target!(|data: &[u8]| {
+    let (info, bristol) = match data.iter().position(|&byte| byte == 0) {
+        Some(split) => match serde_json::from_slice::<CircuitInfo>(&data[..split]) {
+            Ok(info) => (info, &data[split + 1..]),
+            Err(_) => return,
+        },
+        None => (default_info(), data),
+    };
+
+    if let Ok(circuit) = BristolCircuit::parse_bytes(&info, bristol) {
+        circuit.get_bristol_string().unwrap();
+    }
+});
+
+fn default_info() -> CircuitInfo {
+    serde_json::from_str(
+        r#"{"input_name_to_wire_index": {"a": 0, "b": 1}, "constants": {},
+            "output_name_to_wire_index": {"c": 3}}"#,
+    )
+    .unwrap()
+}
@@ -0,0 +1,51 @@
+use serde::{Deserialize, Serialize};
+use strum::{Display, EnumIter, EnumString};
+
+/// The arithmetic ops used by circom-style arithmetic circuits.
+#[derive(
+    Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize, EnumString, Display, EnumIter,
+)]
+pub enum AGateType {
+    AAdd,
+    ADiv,
+    AEq,
+    AGEq,
+    AGt,
+    ALEq,
+    ALt,
+    AMul,
+    ANeq,
+    ASub,
+    AXor,
+    APow,
+    AIntDiv,
+    AMod,
+    AShiftL,
+    AShiftR,
+    ABoolOr,
+    ABoolAnd,
+    ABitOr,
+    ABitAnd,
+
+    /// `AMux sel a b` is `b + sel * (a - b)`: `a` when `sel` is 1 and `b` when it is 0, the
+    /// opposite of the boolean `MUX`.
+    AMux,
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use strum::IntoEnumIterator;
+
+    use super::*;
+
+    #[test]
+    fn test_round_trip() {
+        for op in AGateType::iter() {
+            assert_eq!(AGateType::from_str(&op.to_string()).unwrap(), op);
+        }
+
+        assert!(AGateType::from_str("XOR").is_err());
+    }
+}
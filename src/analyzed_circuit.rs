@@ -0,0 +1,352 @@
+use crate::{
+    bristol_circuit::BristolCircuit, bristol_circuit_error::BristolCircuitError, gate::Gate,
+    io_kind::IoKind, memory_estimate::MemoryEstimate, op_classification::OpClassification,
+};
+
+/// What an edit changed, which determines the cached analyses it invalidates.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EditKind {
+    /// Gates were added, removed or changed.
+    Gates,
+
+    /// Input, output or constant names changed, but not the gates.
+    Interface,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct CacheStats {
+    pub hits: usize,
+    pub misses: usize,
+}
+
+/// A circuit that caches analysis results between edits.
+///
+/// Every edit goes through this wrapper, which bumps a generation counter for the kind of edit.
+/// Analyses are recomputed on the next query only if an edit they depend on happened since they
+/// were cached. For example, renaming an output leaves the depth cached.
+#[derive(Clone, Debug)]
+pub struct AnalyzedCircuit {
+    circuit: BristolCircuit,
+    gates_generation: u64,
+    interface_generation: u64,
+    depth: Cached<usize>,
+    fan_out: Cached<Vec<usize>>,
+    classification: Cached<OpClassification>,
+    semantic_hash: Cached<u64>,
+    memory: Cached<MemoryEstimate>,
+    stats: CacheStats,
+}
+
+/// A value with the generations it was computed at.
+#[derive(Clone, Debug)]
+struct Cached<T>(Option<((u64, u64), T)>);
+
+impl<T> Default for Cached<T> {
+    fn default() -> Self {
+        Cached(None)
+    }
+}
+
+impl AnalyzedCircuit {
+    pub fn new(circuit: BristolCircuit) -> Self {
+        AnalyzedCircuit {
+            circuit,
+            gates_generation: 0,
+            interface_generation: 0,
+            depth: Cached::default(),
+            fan_out: Cached::default(),
+            classification: Cached::default(),
+            semantic_hash: Cached::default(),
+            memory: Cached::default(),
+            stats: CacheStats::default(),
+        }
+    }
+
+    pub fn circuit(&self) -> &BristolCircuit {
+        &self.circuit
+    }
+
+    pub fn into_circuit(self) -> BristolCircuit {
+        self.circuit
+    }
+
+    /// Counts every edit, of any kind.
+    pub fn generation(&self) -> u64 {
+        self.gates_generation + self.interface_generation
+    }
+
+    pub fn stats(&self) -> CacheStats {
+        self.stats
+    }
+
+    /// Drops every cached result.
+    pub fn invalidate(&mut self) {
+        self.depth = Cached::default();
+        self.fan_out = Cached::default();
+        self.classification = Cached::default();
+        self.semantic_hash = Cached::default();
+        self.memory = Cached::default();
+    }
+
+    /// Records an edit made outside the wrapper's own editing methods, e.g. via `circuit_mut`.
+    pub fn mark_edited(&mut self, kind: EditKind) {
+        match kind {
+            EditKind::Gates => self.gates_generation += 1,
+            EditKind::Interface => self.interface_generation += 1,
+        }
+    }
+
+    /// Direct access for edits the wrapper doesn't offer. Since their effect is unknown, this
+    /// counts as both kinds of edit.
+    pub fn circuit_mut(&mut self) -> &mut BristolCircuit {
+        self.mark_edited(EditKind::Gates);
+        self.mark_edited(EditKind::Interface);
+        &mut self.circuit
+    }
+
+    pub fn push_gate(&mut self, gate: Gate) {
+        let highest = gate.inputs.iter().chain(&gate.outputs).max();
+        self.circuit.wire_count = self.circuit.wire_count.max(highest.map_or(0, |w| w + 1));
+        self.circuit.gates.push(gate);
+        self.mark_edited(EditKind::Gates);
+    }
+
+    pub fn remove_gate(&mut self, index: usize) -> Result<Gate, BristolCircuitError> {
+        if index >= self.circuit.gates.len() {
+            return Err(BristolCircuitError::Inconsistency {
+                message: format!(
+                    "Gate {} is out of range ({} gates)",
+                    index,
+                    self.circuit.gates.len()
+                ),
+            });
+        }
+
+        self.mark_edited(EditKind::Gates);
+        Ok(self.circuit.gates.remove(index))
+    }
+
+    /// Renames a named input or output, with the checks of [`CircuitInfo::rename_input`] and
+    /// [`CircuitInfo::rename_output`].
+    ///
+    /// [`CircuitInfo::rename_input`]: crate::CircuitInfo::rename_input
+    /// [`CircuitInfo::rename_output`]: crate::CircuitInfo::rename_output
+    pub fn rename(
+        &mut self,
+        kind: IoKind,
+        from: &str,
+        to: &str,
+    ) -> Result<(), BristolCircuitError> {
+        let info = &mut self.circuit.info;
+
+        match kind {
+            IoKind::Input => info.rename_input(from, to)?,
+            IoKind::Output => info.rename_output(from, to)?,
+        }
+
+        self.mark_edited(EditKind::Interface);
+        Ok(())
+    }
+
+    /// The length of the longest chain of gates. Depends only on the gates.
+    pub fn depth(&mut self) -> usize {
+        let key = (self.gates_generation, 0);
+        let circuit = &self.circuit;
+
+        *lookup(&mut self.depth, key, &mut self.stats, || {
+            circuit.gate_depths().into_iter().max().unwrap_or(0)
+        })
+    }
+
+    /// How many gate inputs read `wire`. Depends only on the gates.
+    pub fn fan_out(&mut self, wire: usize) -> usize {
+        let key = (self.gates_generation, 0);
+        let circuit = &self.circuit;
+
+        let fan_out = lookup(&mut self.fan_out, key, &mut self.stats, || {
+            let mut fan_out = vec![0; circuit.wire_count.max(circuit.referenced_wire_count())];
+
+            for gate in &circuit.gates {
+                for &input in gate.input_wires() {
+                    fan_out[input] += 1;
+                }
+            }
+
+            fan_out
+        });
+
+        fan_out.get(wire).copied().unwrap_or(0)
+    }
+
+    /// Depends only on the gates.
+    pub fn classify_ops(&mut self) -> &OpClassification {
+        let key = (self.gates_generation, 0);
+        let circuit = &self.circuit;
+
+        lookup(&mut self.classification, key, &mut self.stats, || {
+            circuit.classify_ops()
+        })
+    }
+
+    pub fn semantic_hash(&mut self) -> u64 {
+        let key = (self.gates_generation, self.interface_generation);
+        let circuit = &self.circuit;
+
+        *lookup(&mut self.semantic_hash, key, &mut self.stats, || {
+            circuit.semantic_hash()
+        })
+    }
+
+    pub fn estimate_memory(&mut self) -> &MemoryEstimate {
+        let key = (self.gates_generation, self.interface_generation);
+        let circuit = &self.circuit;
+
+        lookup(&mut self.memory, key, &mut self.stats, || {
+            circuit.estimate_memory()
+        })
+    }
+}
+
+fn lookup<'a, T>(
+    cached: &'a mut Cached<T>,
+    key: (u64, u64),
+    stats: &mut CacheStats,
+    compute: impl FnOnce() -> T,
+) -> &'a T {
+    match &cached.0 {
+        Some((cached_key, _)) if *cached_key == key => stats.hits += 1,
+        _ => {
+            stats.misses += 1;
+            cached.0 = Some((key, compute()));
+        }
+    }
+
+    &cached.0.as_ref().expect("just cached").1
+}
+
+#[cfg(test)]
+mod tests {
+    use smallvec::smallvec;
+
+    use super::*;
+    use crate::{circuit_builder::CircuitBuilder, gadgets};
+
+    #[test]
+    fn test_cache_hits() {
+        let mut analyzed = AnalyzedCircuit::new(gadgets::equals(4));
+
+        assert_eq!(analyzed.depth(), 4);
+        assert_eq!(analyzed.depth(), 4);
+        assert!(analyzed.classify_ops().is_pure_boolean());
+        assert!(analyzed.classify_ops().is_pure_boolean());
+
+        assert_eq!(analyzed.stats(), CacheStats { hits: 2, misses: 2 });
+    }
+
+    #[test]
+    fn test_recomputes_after_gate_edit() {
+        let mut analyzed = AnalyzedCircuit::new(gadgets::equals(4));
+        let out = analyzed.circuit().info.output_name_to_wire_index["out"];
+        let hash = analyzed.semantic_hash();
+
+        assert_eq!(analyzed.depth(), 4);
+        assert_eq!(analyzed.fan_out(out), 0);
+
+        // Re-derive the output through an extra pair of inverters.
+        let last = analyzed
+            .remove_gate(analyzed.circuit().gates.len() - 1)
+            .unwrap();
+        let spare = analyzed.circuit().wire_count;
+        analyzed.push_gate(Gate {
+            outputs: smallvec![spare],
+            ..last
+        });
+        analyzed.push_gate(Gate {
+            inputs: smallvec![spare],
+            outputs: smallvec![spare + 1],
+            op: "INV".into(),
+        });
+        analyzed.push_gate(Gate {
+            inputs: smallvec![spare + 1],
+            outputs: smallvec![out],
+            op: "INV".into(),
+        });
+
+        assert_eq!(analyzed.generation(), 4);
+        assert_eq!(analyzed.depth(), 6);
+        assert_eq!(analyzed.fan_out(spare), 1);
+        assert_ne!(analyzed.semantic_hash(), hash);
+        assert_eq!(analyzed.stats(), CacheStats { hits: 0, misses: 6 });
+    }
+
+    #[test]
+    fn test_selective_invalidation() {
+        let mut analyzed = AnalyzedCircuit::new(gadgets::adder(4, false));
+        let depth = analyzed.depth();
+        let hash = analyzed.semantic_hash();
+
+        analyzed.rename(IoKind::Output, "sum", "total").unwrap();
+
+        // Renaming doesn't change the gates, so the depth is still cached.
+        assert_eq!(analyzed.depth(), depth);
+        assert_eq!(analyzed.stats(), CacheStats { hits: 1, misses: 2 });
+
+        assert_ne!(analyzed.semantic_hash(), hash);
+        assert_eq!(analyzed.stats(), CacheStats { hits: 1, misses: 3 });
+
+        analyzed.invalidate();
+        assert_eq!(analyzed.depth(), depth);
+        assert_eq!(analyzed.stats(), CacheStats { hits: 1, misses: 4 });
+    }
+
+    #[test]
+    fn test_edit_errors() {
+        let mut analyzed = AnalyzedCircuit::new(gadgets::adder(2, false));
+
+        let err = analyzed.rename(IoKind::Input, "a", "b").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Inconsistency: Can't rename input a to b, which is taken"
+        );
+
+        let err = analyzed.rename(IoKind::Output, "c", "d").unwrap_err();
+        assert_eq!(err.to_string(), "Inconsistency: No output named c");
+
+        assert!(analyzed.remove_gate(100).is_err());
+        assert_eq!(analyzed.generation(), 0);
+    }
+
+    #[test]
+    fn test_fan_out_skips_eq_literals() {
+        let circuit = BristolCircuit::from_bristol_string("1 2\n1 1\n1 1\n\n1 1 5 1 EQ\n").unwrap();
+        let mut analyzed = AnalyzedCircuit::new(circuit);
+
+        assert_eq!(analyzed.fan_out(5), 0);
+        assert_eq!(analyzed.fan_out(0), 0);
+    }
+
+    #[test]
+    fn test_rename_matches_circuit_info() {
+        let mut b = CircuitBuilder::new();
+        let x = b.input("x");
+        let one = b.constant("one", "1");
+        let sum = b.gate("AAdd", &[x, one]);
+        b.output("sum", sum);
+        let mut analyzed = AnalyzedCircuit::new(b.build().unwrap());
+
+        let err = analyzed.rename(IoKind::Input, "x", "one").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Inconsistency: Can't rename input x to one, which is taken"
+        );
+        assert_eq!(analyzed.generation(), 0);
+
+        analyzed.rename(IoKind::Input, "x", "x").unwrap();
+        analyzed.rename(IoKind::Output, "sum", "total").unwrap();
+        assert_eq!(
+            analyzed.circuit().info.output_name_to_wire_index["total"],
+            2
+        );
+        assert_eq!(analyzed.generation(), 2);
+    }
+}
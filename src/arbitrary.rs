@@ -0,0 +1,139 @@
+use proptest::{
+    arbitrary::{any, Arbitrary},
+    collection::vec,
+    sample::select,
+    strategy::{BoxedStrategy, Just, Strategy},
+};
+
+use smallvec::smallvec;
+
+use crate::{
+    bristol_circuit::BristolCircuit, circuit_info::CircuitInfo, gate::Gate,
+    random_circuit::RandomCircuitConfig,
+};
+
+const BOOLEAN_OPS: &[(&str, usize)] = &[("AND", 2), ("XOR", 2), ("INV", 1), ("MUX", 3)];
+const ARITHMETIC_OPS: &[(&str, usize)] = &[("AAdd", 2), ("AMul", 2), ("ASub", 2), ("AMux", 3)];
+
+/// A gate with a known single-output op and the right number of inputs. On its own a gate has no
+/// circuit to be consistent with, so its wires are any small indices.
+impl Arbitrary for Gate {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Gate>;
+
+    fn arbitrary_with(_: ()) -> Self::Strategy {
+        select([BOOLEAN_OPS, ARITHMETIC_OPS].concat())
+            .prop_flat_map(|(op, arity)| (Just(op), vec(0..1024usize, arity), 0..1024usize))
+            .prop_map(|(op, inputs, output)| Gate {
+                inputs: inputs.into(),
+                outputs: smallvec![output],
+                op: op.into(),
+            })
+            .boxed()
+    }
+}
+
+/// Circuits from [`BristolCircuit::random_seeded`]: single-assignment, in topological order, with every
+/// gate feeding an output, and always valid.
+///
+/// The seed doesn't shrink, and only the gate count does, so shrinking drops the trailing gates
+/// the seed generates (and with them the wires only they used) and keeps the rest as they were.
+impl Arbitrary for BristolCircuit {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<BristolCircuit>;
+
+    fn arbitrary_with(_: ()) -> Self::Strategy {
+        (
+            any::<u64>().no_shrink(),
+            (1..5usize).no_shrink(),
+            any::<bool>().no_shrink(),
+            0..64usize,
+        )
+            .prop_map(|(seed, inputs, boolean, gates)| {
+                let ops = if boolean { BOOLEAN_OPS } else { ARITHMETIC_OPS };
+                let config = RandomCircuitConfig {
+                    inputs,
+                    gates,
+                    ops: ops.iter().map(|&(op, _)| (op.to_string(), 1)).collect(),
+                    boolean,
+                };
+
+                BristolCircuit::random_seeded(&config, seed).expect("the config is usable")
+            })
+            .boxed()
+    }
+}
+
+/// The info of an arbitrary [`BristolCircuit`], so its names refer to distinct wires laid out as
+/// a circuit would lay them out.
+impl Arbitrary for CircuitInfo {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<CircuitInfo>;
+
+    fn arbitrary_with(_: ()) -> Self::Strategy {
+        any::<BristolCircuit>()
+            .prop_map(|circuit| circuit.info)
+            .boxed()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use proptest::prelude::*;
+
+    use super::*;
+    use crate::bristol_line::BristolLine;
+
+    proptest! {
+        #[test]
+        fn test_bristol_round_trip(circuit in any::<BristolCircuit>()) {
+            prop_assert!(circuit.validate().is_ok());
+
+            let text = circuit.get_bristol_string().unwrap();
+            let parsed = BristolCircuit::from_info_and_bristol_string(&circuit.info, &text).unwrap();
+            prop_assert_eq!(parsed, circuit);
+        }
+
+        #[test]
+        fn test_info_json_round_trip(info in any::<CircuitInfo>()) {
+            let json = serde_json::to_string(&info).unwrap();
+            prop_assert_eq!(serde_json::from_str::<CircuitInfo>(&json).unwrap(), info);
+        }
+
+        #[test]
+        fn test_gate_line_round_trip(gate in any::<Gate>()) {
+            let line = BristolLine::read(&mut Cursor::new(gate.to_string())).unwrap();
+            prop_assert_eq!(line.gate().unwrap(), gate);
+        }
+    }
+
+    #[test]
+    fn test_shrinking_keeps_a_prefix() {
+        use proptest::{strategy::ValueTree, test_runner::TestRunner};
+
+        let mut runner = TestRunner::deterministic();
+        let mut tree = any::<BristolCircuit>().new_tree(&mut runner).unwrap();
+
+        // Output buses may add trailing copies, and output wires are renumbered, so compare ops.
+        let ops = |circuit: &BristolCircuit| {
+            circuit
+                .gates
+                .iter()
+                .map(|gate| gate.op.clone())
+                .filter(|op| op != "EQW")
+                .collect::<Vec<_>>()
+        };
+        let mut previous = ops(&tree.current());
+
+        while tree.simplify() {
+            let current = tree.current();
+            current.validate().unwrap();
+
+            let current = ops(&current);
+            assert!(previous.starts_with(&current));
+            previous = current;
+        }
+    }
+}
@@ -0,0 +1,343 @@
+use std::{collections::HashMap, str::FromStr};
+
+use crate::{
+    a_gate_type::AGateType,
+    bristol_circuit::BristolCircuit,
+    bristol_circuit_error::BristolCircuitError,
+    eval::{constant_seeds, eval_wires, evaluation_error, GateSemantics},
+    op_classification::OpKind,
+};
+
+/// Arithmetic on `u64`, modulo `modulus` when given and wrapping otherwise. Comparisons and
+/// bitwise ops act on the values as stored (so reduced modulo `modulus`) and produce 0 or 1.
+struct U64Semantics {
+    modulus: Option<u64>,
+}
+
+impl U64Semantics {
+    fn reduce(&self, value: u128) -> u64 {
+        match self.modulus {
+            Some(p) => (value % u128::from(p)) as u64,
+            None => value as u64,
+        }
+    }
+
+    fn mul(&self, a: u64, b: u64) -> u64 {
+        self.reduce(u128::from(a) * u128::from(b))
+    }
+
+    fn pow(&self, mut base: u64, mut exponent: u64) -> u64 {
+        let mut result = self.reduce(1);
+
+        while exponent > 0 {
+            if exponent & 1 == 1 {
+                result = self.mul(result, base);
+            }
+
+            base = self.mul(base, base);
+            exponent >>= 1;
+        }
+
+        result
+    }
+}
+
+impl GateSemantics<u64> for U64Semantics {
+    fn eval_gate(
+        &mut self,
+        op: &str,
+        inputs: &[u64],
+        gate_index: usize,
+    ) -> Result<Vec<u64>, BristolCircuitError> {
+        let op_type = AGateType::from_str(op).map_err(|_| {
+            evaluation_error(format!(
+                "Gate {} has unsupported arithmetic op {}",
+                gate_index, op
+            ))
+        })?;
+
+        let expected = if op_type == AGateType::AMux { 3 } else { 2 };
+
+        if inputs.len() != expected {
+            return Err(evaluation_error(format!(
+                "Gate {} ({}) has {} inputs",
+                gate_index,
+                op,
+                inputs.len()
+            )));
+        }
+
+        let (a, b) = (inputs[0], inputs[1]);
+        let flag = |value: bool| u64::from(value);
+
+        let nonzero_divisor = || {
+            if b == 0 {
+                Err(evaluation_error(format!(
+                    "Gate {} ({}) divides by zero",
+                    gate_index, op
+                )))
+            } else {
+                Ok(b)
+            }
+        };
+
+        let sub = |a: u64, b: u64| match self.modulus {
+            Some(p) => self.reduce(u128::from(a) + u128::from(p) - u128::from(b)),
+            None => a.wrapping_sub(b),
+        };
+
+        use AGateType::*;
+
+        let result = match op_type {
+            AAdd => self.reduce(u128::from(a) + u128::from(b)),
+            ASub => sub(a, b),
+            AMul => self.mul(a, b),
+            ADiv => match self.modulus {
+                // Multiplying by the inverse, which needs `modulus` to be prime.
+                Some(p) => self.mul(a, self.pow(nonzero_divisor()?, p - 2)),
+                None => a / nonzero_divisor()?,
+            },
+            AIntDiv => a / nonzero_divisor()?,
+            AMod => a % nonzero_divisor()?,
+            APow => self.pow(a, b),
+            AEq => flag(a == b),
+            ANeq => flag(a != b),
+            ALt => flag(a < b),
+            ALEq => flag(a <= b),
+            AGt => flag(a > b),
+            AGEq => flag(a >= b),
+            AXor => self.reduce(u128::from(a ^ b)),
+            ABitOr => self.reduce(u128::from(a | b)),
+            ABitAnd => a & b,
+            ABoolOr => flag(a != 0 || b != 0),
+            ABoolAnd => flag(a != 0 && b != 0),
+            AShiftL => match self.modulus {
+                Some(_) => self.mul(a, self.pow(2, b)),
+                None => a.checked_shl(b.try_into().unwrap_or(u32::MAX)).unwrap_or(0),
+            },
+            AShiftR => a.checked_shr(b.try_into().unwrap_or(u32::MAX)).unwrap_or(0),
+            AMux => {
+                // b + sel * (a - b), matching `lower_mux`.
+                let (sel, a, b) = (inputs[0], inputs[1], inputs[2]);
+                self.reduce(u128::from(b) + u128::from(self.mul(sel, sub(a, b))))
+            }
+        };
+
+        Ok(vec![result])
+    }
+}
+
+impl BristolCircuit {
+    /// Evaluates an arithmetic circuit on `u64` values, modulo the prime `modulus` when given and
+    /// with wrapping arithmetic otherwise. Every named input and output is a single wire, and
+    /// constants are decimal or `0x` hex, as `ConstantInfo::as_u64` reads them. Without a
+    /// modulus `ADiv` is integer division, like `AIntDiv`.
+    pub fn eval_arithmetic(
+        &self,
+        inputs: &HashMap<String, u64>,
+        modulus: Option<u64>,
+    ) -> Result<HashMap<String, u64>, BristolCircuitError> {
+        self.classify_ops().require(OpKind::Arithmetic, self)?;
+
+        if modulus.is_some_and(|p| p < 2) {
+            return Err(evaluation_error(format!(
+                "Modulus {} is too small",
+                modulus.unwrap_or_default()
+            )));
+        }
+
+        let mut semantics = U64Semantics { modulus };
+
+        let mut seeds = self
+            .scalar_input_seeds(inputs)?
+            .into_iter()
+            .map(|(wire, &value)| (wire, semantics.reduce(u128::from(value))))
+            .collect::<Vec<_>>();
+
+        seeds.extend(
+            constant_seeds::<u64>(self)?
+                .into_iter()
+                .map(|(wire, value)| (wire, semantics.reduce(u128::from(value)))),
+        );
+
+        let wires = eval_wires(self, seeds, &mut semantics, &mut ())?;
+
+        self.scalar_outputs(&wires)
+    }
+
+    /// The wires of the named inputs with their values, for evaluators where every input is a
+    /// single wire.
+    pub(crate) fn scalar_input_seeds<'a, V>(
+        &self,
+        inputs: &'a HashMap<String, V>,
+    ) -> Result<Vec<(usize, &'a V)>, BristolCircuitError> {
+        let buses = self.input_buses();
+
+        if let Some(name) = inputs
+            .keys()
+            .find(|name| !buses.iter().any(|bus| bus.name == name.as_str()))
+        {
+            return Err(evaluation_error(format!("Unknown input {}", name)));
+        }
+
+        buses
+            .iter()
+            .map(|bus| {
+                if bus.width != 1 {
+                    return Err(evaluation_error(format!(
+                        "Input {} has width {}, but arithmetic evaluation needs single-wire inputs",
+                        bus.name, bus.width
+                    )));
+                }
+
+                let value = inputs.get(bus.name).ok_or_else(|| {
+                    evaluation_error(format!("Missing value for input {}", bus.name))
+                })?;
+
+                Ok((bus.wire, value))
+            })
+            .collect()
+    }
+
+    pub(crate) fn scalar_outputs<V: Clone>(
+        &self,
+        wires: &[Option<V>],
+    ) -> Result<HashMap<String, V>, BristolCircuitError> {
+        self.info
+            .output_name_to_wire_index
+            .iter()
+            .map(|(name, &wire)| {
+                let value = wires[wire].clone().ok_or_else(|| {
+                    evaluation_error(format!("Output {} wire {} is undefined", name, wire))
+                })?;
+
+                Ok((name.clone(), value))
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{circuit_builder::CircuitBuilder, gadgets};
+
+    fn binary(op: &str) -> BristolCircuit {
+        let mut b = CircuitBuilder::new();
+        let x = b.input("x");
+        let y = b.input("y");
+        let out = b.gate(op, &[x, y]);
+        b.output("out", out);
+        b.build().unwrap()
+    }
+
+    fn eval(op: &str, x: u64, y: u64, modulus: Option<u64>) -> Result<u64, BristolCircuitError> {
+        let inputs = HashMap::from([("x".to_string(), x), ("y".to_string(), y)]);
+
+        Ok(binary(op).eval_arithmetic(&inputs, modulus)?["out"])
+    }
+
+    #[test]
+    fn test_modular_ops() {
+        for (op, x, y, expected) in [
+            ("AAdd", 60, 50, 9),
+            ("ASub", 3, 5, 99),
+            ("AMul", 20, 10, 99),
+            ("ADiv", 1, 2, 51),
+            ("AIntDiv", 7, 2, 3),
+            ("AMod", 7, 4, 3),
+            ("APow", 2, 10, 14),
+            ("ALt", 3, 5, 1),
+            ("AGEq", 3, 5, 0),
+            ("AEq", 4, 4, 1),
+            ("ANeq", 4, 4, 0),
+            ("AXor", 6, 3, 5),
+            ("ABoolAnd", 6, 0, 0),
+            ("ABoolOr", 6, 0, 1),
+            ("AShiftL", 1, 7, 27),
+            ("AShiftR", 100, 2, 25),
+        ] {
+            assert_eq!(eval(op, x, y, Some(101)).unwrap(), expected, "{}", op);
+        }
+
+        // Inputs are reduced first.
+        assert_eq!(eval("AEq", 102, 1, Some(101)).unwrap(), 1);
+    }
+
+    #[test]
+    fn test_wrapping_ops() {
+        for (op, x, y, expected) in [
+            ("AAdd", u64::MAX, 2, 1),
+            ("ASub", 3, 5, u64::MAX - 1),
+            ("AMul", 1 << 32, 1 << 33, 0),
+            ("ADiv", 7, 2, 3),
+            ("APow", 3, 4, 81),
+            ("AShiftL", 1, 64, 0),
+            ("AGt", 5, 3, 1),
+        ] {
+            assert_eq!(eval(op, x, y, None).unwrap(), expected, "{}", op);
+        }
+    }
+
+    #[test]
+    fn test_matches_field_evaluator() {
+        let circuit = gadgets::horner(&[3, 0, 5, 2]);
+        let inputs = HashMap::from([("x".to_string(), 10)]);
+
+        assert_eq!(
+            circuit
+                .eval_arithmetic(&inputs, Some(1_000_000_007))
+                .unwrap()["out"],
+            2503
+        );
+        assert_eq!(circuit.eval_arithmetic(&inputs, None).unwrap()["out"], 2503);
+    }
+
+    #[test]
+    fn test_hex_constant() {
+        let mut b = CircuitBuilder::new();
+        let x = b.input("x");
+        let k = b.constant("k", "0xff");
+        let out = b.gate("AAdd", &[x, k]);
+        b.output("out", out);
+        let circuit = b.build().unwrap();
+
+        let inputs = HashMap::from([("x".to_string(), 1)]);
+        assert_eq!(circuit.eval_arithmetic(&inputs, None).unwrap()["out"], 256);
+        assert_eq!(
+            circuit.eval_arithmetic(&inputs, Some(101)).unwrap()["out"],
+            54
+        );
+    }
+
+    #[test]
+    fn test_errors() {
+        let err = eval("ADiv", 1, 0, Some(101)).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Evaluation error: Gate 0 (ADiv) divides by zero"
+        );
+
+        let err = eval("AMod", 1, 0, None).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Evaluation error: Gate 0 (AMod) divides by zero"
+        );
+
+        let mut b = CircuitBuilder::new();
+        let x = b.input("x");
+        let k = b.constant("k", "seven");
+        let out = b.gate("AAdd", &[x, k]);
+        b.output("out", out);
+        let circuit = b.build().unwrap();
+
+        let inputs = HashMap::from([("x".to_string(), 1)]);
+        let err = circuit.eval_arithmetic(&inputs, None).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Evaluation error: Constant k has non-numeric value \"seven\""
+        );
+
+        assert!(eval("AAdd", 1, 1, Some(1)).is_err());
+    }
+}
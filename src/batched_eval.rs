@@ -0,0 +1,395 @@
+use std::collections::HashMap;
+
+use crate::{
+    bool_eval::BoolOp,
+    bool_gate_type::BoolGateType,
+    bristol_circuit::BristolCircuit,
+    bristol_circuit_error::BristolCircuitError,
+    eval::evaluation_error,
+    gate::bool_literal,
+    lut::Lut,
+    op_classification::OpKind,
+    trace::{trace_progress, trace_span},
+};
+
+impl BristolCircuit {
+    /// Evaluates a boolean circuit on 64 sets of inputs at once: each wire holds a word whose
+    /// bit `i` is its value in instance `i`, so every gate is a few word operations. Otherwise
+    /// like [`BristolCircuit::eval_boolean`], with one word per wire of each input and output.
+    pub fn eval_boolean_batched(
+        &self,
+        inputs: &HashMap<String, Vec<u64>>,
+    ) -> Result<HashMap<String, Vec<u64>>, BristolCircuitError> {
+        self.eval_boolean_batched_words(inputs, 1)
+    }
+
+    /// Like [`BristolCircuit::eval_boolean_batched`], for `64 * words` instances: each wire
+    /// holds `words` consecutive words, so an input or output of width `w` has `w * words`,
+    /// and instance `i` is bit `i % 64` of each wire's word `i / 64`.
+    pub fn eval_boolean_batched_words(
+        &self,
+        inputs: &HashMap<String, Vec<u64>>,
+        words: usize,
+    ) -> Result<HashMap<String, Vec<u64>>, BristolCircuitError> {
+        let _span = trace_span!("eval_batched", gate_count = self.gates.len(), words = words);
+
+        self.classify_ops().require(OpKind::Boolean, self)?;
+
+        if words == 0 {
+            return Err(evaluation_error(
+                "Batches need at least one word per wire".into(),
+            ));
+        }
+
+        let mut wire_inputs = HashMap::new();
+
+        for (name, values) in inputs {
+            if values.len() % words != 0 {
+                return Err(evaluation_error(format!(
+                    "Input {} has {} words, which isn't {} per wire",
+                    name,
+                    values.len(),
+                    words
+                )));
+            }
+
+            wire_inputs.insert(name.clone(), values.chunks(words).collect::<Vec<_>>());
+        }
+
+        let seeds = self.bus_input_seeds(&wire_inputs)?;
+        let constants = self.boolean_constant_seeds()?;
+
+        let wire_count = self.wire_count.max(self.referenced_wire_count());
+        let mut wires = WordWires {
+            words,
+            values: vec![0; wire_count * words],
+            set: vec![false; wire_count],
+        };
+
+        for (wire, value) in seeds {
+            wires.set(wire).copy_from_slice(value);
+        }
+
+        for (wire, value) in constants {
+            wires.set(wire).fill(broadcast(value));
+        }
+
+        // Each gate's input words, then its output words, one wire after another.
+        let mut input_words = Vec::new();
+        let mut output_words = Vec::new();
+
+        for (gate_index, gate) in self.gates.iter().enumerate() {
+            input_words.clear();
+
+            for &wire in &gate.inputs {
+                if let Some(bit) = bool_literal(&gate.op, wire) {
+                    input_words.resize(input_words.len() + words, broadcast(bit));
+                    continue;
+                }
+
+                let value = wires.get(wire).ok_or_else(|| {
+                    evaluation_error(format!(
+                        "Gate {} ({}) reads undefined wire {}",
+                        gate_index, gate.op, wire
+                    ))
+                })?;
+                input_words.extend_from_slice(value);
+            }
+
+            let op = BoolOp::resolve(&gate.op, gate.inputs.len(), gate_index)?;
+
+            output_words.clear();
+            apply_words(&op, &input_words, words, &mut output_words);
+
+            let output_count = output_words.len() / words;
+            if output_count != gate.outputs.len() {
+                return Err(evaluation_error(format!(
+                    "Gate {} ({}) produced {} outputs but has {} output wires",
+                    gate_index,
+                    gate.op,
+                    output_count,
+                    gate.outputs.len()
+                )));
+            }
+
+            for (&wire, value) in gate.outputs.iter().zip(output_words.chunks(words)) {
+                wires.set(wire).copy_from_slice(value);
+            }
+
+            trace_progress!(gate_index + 1);
+        }
+
+        let values = (0..wire_count)
+            .map(|wire| wires.get(wire))
+            .collect::<Vec<_>>();
+
+        Ok(self
+            .bus_outputs(&values)?
+            .into_iter()
+            .map(|(name, value)| (name, value.concat()))
+            .collect())
+    }
+}
+
+/// Every wire's words, in one allocation.
+struct WordWires {
+    words: usize,
+    values: Vec<u64>,
+    set: Vec<bool>,
+}
+
+impl WordWires {
+    fn get(&self, wire: usize) -> Option<&[u64]> {
+        self.set[wire].then(|| &self.values[wire * self.words..(wire + 1) * self.words])
+    }
+
+    fn set(&mut self, wire: usize) -> &mut [u64] {
+        self.set[wire] = true;
+        &mut self.values[wire * self.words..(wire + 1) * self.words]
+    }
+}
+
+fn broadcast(bit: bool) -> u64 {
+    if bit {
+        u64::MAX
+    } else {
+        0
+    }
+}
+
+/// [`BoolOp::apply`] on `words` words per wire, appending the outputs' words to `outputs`.
+fn apply_words(op: &BoolOp, inputs: &[u64], words: usize, outputs: &mut Vec<u64>) {
+    use BoolGateType::*;
+
+    let op_type = match op {
+        BoolOp::Lut(lut) => {
+            outputs.extend((0..words).map(|k| lut_word(lut, inputs, words, k)));
+            return;
+        }
+        BoolOp::Gate(op_type) => op_type,
+    };
+
+    let input = |i: usize| &inputs[i * words..(i + 1) * words];
+    let mut binary = |f: fn(u64, u64) -> u64| {
+        outputs.extend(input(0).iter().zip(input(1)).map(|(&a, &b)| f(a, b)));
+    };
+
+    match op_type {
+        Xor => binary(|a, b| a ^ b),
+        And => binary(|a, b| a & b),
+        Or => binary(|a, b| a | b),
+        Nand => binary(|a, b| !(a & b)),
+        Nor => binary(|a, b| !(a | b)),
+        Xnor => binary(|a, b| !(a ^ b)),
+        Inv | Not => outputs.extend(input(0).iter().map(|&a| !a)),
+        Eq | Eqw | Buf => outputs.extend_from_slice(input(0)),
+        Mux => outputs.extend(
+            input(0)
+                .iter()
+                .zip(input(1).iter().zip(input(2)))
+                .map(|(&sel, (&a, &b))| (sel & b) | (!sel & a)),
+        ),
+        Mand => {
+            let pairs = inputs.len() / words / 2;
+
+            for i in 0..pairs {
+                outputs.extend(input(i).iter().zip(input(pairs + i)).map(|(&a, &b)| a & b));
+            }
+        }
+    }
+}
+
+/// Word `k` of a LUT's output: the OR of a minterm per set table entry for small tables, and
+/// a lookup per instance for larger ones.
+fn lut_word(lut: &Lut, inputs: &[u64], words: usize, k: usize) -> u64 {
+    let input = |j: usize| inputs[j * words + k];
+
+    if lut.table().len() > 64 {
+        return (0..64).fold(0, |word, bit| {
+            let index = (0..lut.input_count()).fold(0, |index, j| {
+                index | (((input(j) >> bit) & 1) as usize) << j
+            });
+
+            word | (u64::from(lut.table()[index]) << bit)
+        });
+    }
+
+    lut.table()
+        .iter()
+        .enumerate()
+        .filter(|&(_, &entry)| entry)
+        .fold(0, |word, (row, _)| {
+            word | (0..lut.input_count()).fold(u64::MAX, |term, j| {
+                term & if (row >> j) & 1 == 1 {
+                    input(j)
+                } else {
+                    !input(j)
+                }
+            })
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{circuit_builder::CircuitBuilder, gadgets};
+
+    /// Every op `eval_boolean` supports, and a constant. The second LUT is too big for minterms.
+    fn all_ops() -> BristolCircuit {
+        let mut b = CircuitBuilder::new();
+        let x = b.input_bus("x", 4);
+        let one = b.constant("one", "1");
+
+        let mut outputs = ["XOR", "AND", "OR", "NAND", "NOR", "XNOR"]
+            .into_iter()
+            .map(|op| b.gate(op, &[x[0], x[1]]))
+            .collect::<Vec<_>>();
+
+        outputs.push(b.gate("INV", &[x[2]]));
+        outputs.push(b.gate("NOT", &[x[3]]));
+        outputs.push(b.gate("BUF", &[one]));
+        outputs.push(b.gate("MUX", &[x[0], x[1], x[2]]));
+        outputs.push(b.gate("LUT[e8]", &[x[1], x[2], x[3]]));
+        outputs.push(b.gate(
+            "LUT[0123456789abcdeffedcba9876543210]",
+            &[x[0], x[1], x[2], x[3], outputs[0], x[2], one],
+        ));
+        b.output_bus("y", &outputs);
+
+        let mut circuit = b.build().unwrap();
+        let last = circuit.wire_count;
+        circuit.gates.push(crate::gate::Gate {
+            inputs: smallvec::smallvec![0, 1, 2, 3],
+            outputs: smallvec::smallvec![last, last + 1],
+            op: "MAND".into(),
+        });
+        circuit
+            .info
+            .output_name_to_wire_index
+            .insert("m".to_string(), last);
+        circuit.io_widths.1.push(2);
+        circuit.recompute_wire_count();
+        circuit
+    }
+
+    /// Instance `i` of each input wire, from a word per wire.
+    fn instance(inputs: &HashMap<String, Vec<u64>>, i: usize) -> HashMap<String, Vec<bool>> {
+        inputs
+            .iter()
+            .map(|(name, words)| {
+                let bits = words.iter().map(|word| (word >> i) & 1 == 1).collect();
+                (name.clone(), bits)
+            })
+            .collect()
+    }
+
+    fn check_against_scalar(circuit: &BristolCircuit, inputs: &HashMap<String, Vec<u64>>) {
+        let batched = circuit.eval_boolean_batched(inputs).unwrap();
+
+        for i in 0..64 {
+            let scalar = circuit.eval_boolean(&instance(inputs, i)).unwrap();
+            assert_eq!(instance(&batched, i), scalar, "instance {}", i);
+        }
+    }
+
+    #[test]
+    fn test_adder_matches_scalar() {
+        let circuit = gadgets::adder(8, true);
+        let word = |seed: u64| seed.wrapping_mul(0x9e37_79b9_7f4a_7c15);
+        let inputs = HashMap::from([
+            ("a".to_string(), (1..=8).map(word).collect()),
+            ("b".to_string(), (9..=16).map(word).collect()),
+        ]);
+
+        check_against_scalar(&circuit, &inputs);
+    }
+
+    #[test]
+    fn test_all_ops_match_scalar() {
+        // Every combination of the four inputs appears four times.
+        let inputs = HashMap::from([(
+            "x".to_string(),
+            (0..4)
+                .map(|j| (0..64).fold(0, |word, i| word | (((i >> j) & 1) << i)))
+                .collect(),
+        )]);
+
+        check_against_scalar(&all_ops(), &inputs);
+    }
+
+    #[test]
+    fn test_words() {
+        let circuit = gadgets::adder(4, false);
+        let a = (0..4 * 3)
+            .map(|i| 0x5555_0000_ffff_1234u64.rotate_left(i))
+            .collect::<Vec<_>>();
+        let b = (0..4 * 3)
+            .map(|i| 0x0f0f_ff00_1357_9bdfu64.rotate_right(i))
+            .collect::<Vec<_>>();
+
+        let batched = circuit
+            .eval_boolean_batched_words(
+                &HashMap::from([("a".to_string(), a.clone()), ("b".to_string(), b.clone())]),
+                3,
+            )
+            .unwrap();
+
+        // Word k of every wire is its own batch of 64.
+        for k in 0..3 {
+            let word = |values: &[u64]| values.iter().skip(k).step_by(3).copied().collect();
+            let single = circuit
+                .eval_boolean_batched(&HashMap::from([
+                    ("a".to_string(), word(&a)),
+                    ("b".to_string(), word(&b)),
+                ]))
+                .unwrap();
+
+            assert_eq!(single["sum"], word(&batched["sum"]));
+        }
+    }
+
+    #[test]
+    fn test_errors_match_scalar() {
+        let inputs = HashMap::from([("a".to_string(), vec![0; 4]), ("b".to_string(), vec![0; 4])]);
+        let scalar_inputs = instance(&inputs, 0);
+
+        let mut and = gadgets::adder(4, true);
+        and.gates[2].op = "AAdd".into();
+
+        let mut undefined = gadgets::adder(4, true);
+        undefined.gates[3].inputs[1] = undefined.wire_count;
+        undefined.recompute_wire_count();
+
+        for circuit in [and, undefined] {
+            assert_eq!(
+                circuit
+                    .eval_boolean_batched(&inputs)
+                    .unwrap_err()
+                    .to_string(),
+                circuit
+                    .eval_boolean(&scalar_inputs)
+                    .unwrap_err()
+                    .to_string()
+            );
+        }
+
+        let circuit = gadgets::adder(4, true);
+        let mut misaligned = inputs.clone();
+        misaligned.insert("b".to_string(), vec![0; 12]);
+        let err = circuit
+            .eval_boolean_batched_words(&misaligned, 3)
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Evaluation error: Input a has 4 words, which isn't 3 per wire"
+        );
+        assert!(circuit.eval_boolean_batched_words(&inputs, 0).is_err());
+        assert_eq!(
+            circuit
+                .eval_boolean_batched(&HashMap::new())
+                .unwrap_err()
+                .to_string(),
+            "Evaluation error: Missing value for input a"
+        );
+    }
+}
@@ -0,0 +1,458 @@
+//! A compact binary encoding of a whole circuit, several times faster to load than Bristol text.
+//!
+//! Integers are unsigned LEB128 varints: 7 bits per byte, least significant first, with the top
+//! bit set on every byte but the last. Strings are a varint byte length followed by UTF-8. In
+//! order:
+//!
+//! - The magic bytes `BRCB`, then a version byte, currently 1.
+//! - The wire count.
+//! - The header style, as a byte: 0 for `NivList`, 1 for `Legacy` and 2 for `PerPartyLines`.
+//! - The input widths, as a count followed by each width, then the output widths likewise.
+//! - The circuit info, as a string of compact JSON.
+//! - The op table: a count, then each distinct op as a string, in order of first use.
+//! - The gates: a count, then for each its op's index in the table, its input and output counts,
+//!   and its input wires followed by its output wires.
+//!
+//! Nothing may follow the gates. A file of another version is rejected with
+//! [`BristolCircuitError::UnsupportedVersion`], so the format can change without older builds
+//! misreading newer files.
+
+use std::{
+    collections::HashMap,
+    io::{Read, Write},
+};
+
+use smallvec::{Array, SmallVec};
+
+use crate::{
+    bristol_circuit::BristolCircuit,
+    bristol_circuit_error::BristolCircuitError,
+    circuit_header::CircuitHeader,
+    circuit_info::CircuitInfo,
+    gate::{Gate, GateInputs, GateOutputs},
+    header_style::HeaderStyle,
+    op_name::OpName,
+    trace::{trace_record, trace_span},
+};
+
+const MAGIC: &[u8; 4] = b"BRCB";
+const VERSION: u8 = 1;
+
+impl BristolCircuit {
+    pub fn write_binary<W: Write>(&self, w: &mut W) -> Result<(), BristolCircuitError> {
+        w.write_all(&self.to_binary_bytes()?)?;
+
+        Ok(())
+    }
+
+    pub fn to_binary_bytes(&self) -> Result<Vec<u8>, BristolCircuitError> {
+        let _span = trace_span!(
+            "write",
+            gate_count = self.gates.len(),
+            wire_count = self.wire_count
+        );
+
+        let mut out = Vec::with_capacity(64 + 8 * self.gates.len());
+        out.extend_from_slice(MAGIC);
+        out.push(VERSION);
+        push_varint(&mut out, self.wire_count);
+        out.push(match self.header_style {
+            HeaderStyle::NivList => 0,
+            HeaderStyle::Legacy => 1,
+            HeaderStyle::PerPartyLines => 2,
+        });
+
+        for widths in [&self.io_widths.0, &self.io_widths.1] {
+            push_varint(&mut out, widths.len());
+
+            for &width in widths {
+                push_varint(&mut out, width);
+            }
+        }
+
+        let info = serde_json::to_string(&self.info).map_err(|e| {
+            BristolCircuitError::InfoFormatError {
+                format: "JSON",
+                message: e.to_string(),
+            }
+        })?;
+        push_string(&mut out, &info);
+
+        let mut op_indices = HashMap::<&str, usize>::new();
+        let mut ops = Vec::new();
+
+        for gate in &self.gates {
+            op_indices.entry(gate.op.as_str()).or_insert_with(|| {
+                ops.push(gate.op.as_str());
+                ops.len() - 1
+            });
+        }
+
+        push_varint(&mut out, ops.len());
+
+        for op in ops {
+            push_string(&mut out, op);
+        }
+
+        push_varint(&mut out, self.gates.len());
+
+        for gate in &self.gates {
+            push_varint(&mut out, op_indices[gate.op.as_str()]);
+            push_varint(&mut out, gate.inputs.len());
+            push_varint(&mut out, gate.outputs.len());
+
+            for &wire in gate.inputs.iter().chain(&gate.outputs) {
+                push_varint(&mut out, wire);
+            }
+        }
+
+        Ok(out)
+    }
+
+    /// Reads a circuit that [`BristolCircuit::write_binary`] wrote, to the end of `r`.
+    pub fn read_binary<R: Read>(r: &mut R) -> Result<BristolCircuit, BristolCircuitError> {
+        let mut bytes = Vec::new();
+        r.read_to_end(&mut bytes)?;
+
+        BristolCircuit::from_binary_bytes(&bytes)
+    }
+
+    /// Parses the binary format. Like the text parsers this is safe on untrusted input: every
+    /// count is checked against the bytes left before anything is allocated for it, and the
+    /// header is checked against the info and the wires the gates reference.
+    pub fn from_binary_bytes(bytes: &[u8]) -> Result<BristolCircuit, BristolCircuitError> {
+        let span = trace_span!("parse"; gate_count, wire_count);
+
+        let mut r = BinaryReader { bytes, pos: 0 };
+
+        if r.take(MAGIC.len())? != MAGIC {
+            return Err(BristolCircuitError::ParsingError {
+                message: "Not a binary circuit (no BRCB magic bytes)".to_string(),
+            });
+        }
+
+        let version = r.byte()?;
+
+        if version != VERSION {
+            return Err(BristolCircuitError::UnsupportedVersion {
+                format: "binary circuit",
+                version: version.into(),
+                supported: VERSION.into(),
+            });
+        }
+
+        let wire_count = r.varint()?;
+        trace_record!(span, wire_count = wire_count);
+
+        let header_style = match r.byte()? {
+            0 => HeaderStyle::NivList,
+            1 => HeaderStyle::Legacy,
+            2 => HeaderStyle::PerPartyLines,
+            other => return Err(r.error(&format!("Unknown header style {}", other))),
+        };
+
+        let mut io_widths = (Vec::new(), Vec::new());
+
+        for widths in [&mut io_widths.0, &mut io_widths.1] {
+            for _ in 0..r.count()? {
+                widths.push(r.varint()?);
+            }
+        }
+
+        let info = CircuitInfo::from_json_str(r.string()?)?;
+
+        let ops = (0..r.count()?)
+            .map(|_| r.string().map(OpName::from))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let gate_count = r.count()?;
+        trace_record!(span, gate_count = gate_count);
+
+        let mut gates = Vec::with_capacity(gate_count);
+
+        for _ in 0..gate_count {
+            let op_index = r.varint()?;
+            let op = ops
+                .get(op_index)
+                .ok_or_else(|| r.error(&format!("Op index {} out of range", op_index)))?
+                .clone();
+
+            let input_count = r.count()?;
+            let output_count = r.count()?;
+
+            let (inputs, outputs) = match (input_count, output_count) {
+                // Nearly every gate, filled in directly.
+                (2, 1) => (
+                    GateInputs::from_buf([r.varint()?, r.varint()?]),
+                    GateOutputs::from_buf([r.varint()?]),
+                ),
+                _ => (r.wires(input_count)?, r.wires(output_count)?),
+            };
+
+            gates.push(Gate {
+                inputs,
+                outputs,
+                op,
+            });
+        }
+
+        if r.pos != bytes.len() {
+            return Err(r.error("Unexpected data after the gates"));
+        }
+
+        let header = CircuitHeader {
+            gate_count,
+            wire_count,
+            input_widths: io_widths.0,
+            output_widths: io_widths.1,
+            header_style,
+        };
+        header.validate_against(&info)?;
+
+        let circuit = BristolCircuit {
+            wire_count,
+            info,
+            io_widths: (header.input_widths, header.output_widths),
+            gates,
+            header_style,
+        };
+
+        let referenced = circuit.referenced_wire_count();
+
+        if referenced > wire_count {
+            return Err(BristolCircuitError::Inconsistency {
+                message: format!(
+                    "Header declares {} wires but {} are referenced",
+                    wire_count, referenced
+                ),
+            });
+        }
+
+        Ok(circuit)
+    }
+}
+
+fn push_varint(out: &mut Vec<u8>, mut n: usize) {
+    while n >= 0x80 {
+        out.push(n as u8 | 0x80);
+        n >>= 7;
+    }
+
+    out.push(n as u8);
+}
+
+fn push_string(out: &mut Vec<u8>, s: &str) {
+    push_varint(out, s.len());
+    out.extend_from_slice(s.as_bytes());
+}
+
+struct BinaryReader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> BinaryReader<'a> {
+    fn error(&self, message: &str) -> BristolCircuitError {
+        BristolCircuitError::ParsingError {
+            message: format!("{} at byte {} of the binary circuit", message, self.pos),
+        }
+    }
+
+    fn take(&mut self, len: usize) -> Result<&'a [u8], BristolCircuitError> {
+        let bytes = self
+            .bytes
+            .get(self.pos..self.pos.saturating_add(len))
+            .ok_or_else(|| self.error("Unexpected end"))?;
+        self.pos += len;
+
+        Ok(bytes)
+    }
+
+    fn byte(&mut self) -> Result<u8, BristolCircuitError> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn varint(&mut self) -> Result<usize, BristolCircuitError> {
+        // Most counts and op indices are a single byte.
+        if let Some(&byte) = self.bytes.get(self.pos).filter(|&&byte| byte < 0x80) {
+            self.pos += 1;
+            return Ok(usize::from(byte));
+        }
+
+        let mut n = 0usize;
+        let mut shift = 0;
+
+        loop {
+            let start = self.pos;
+            let byte = self.byte()?;
+            let bits = usize::from(byte & 0x7f);
+
+            if shift >= usize::BITS || (bits << shift) >> shift != bits {
+                self.pos = start;
+                return Err(self.error("Varint overflow"));
+            }
+
+            n |= bits << shift;
+
+            if byte & 0x80 == 0 {
+                return Ok(n);
+            }
+
+            shift += 7;
+        }
+    }
+
+    fn wires<A: Array<Item = usize>>(
+        &mut self,
+        count: usize,
+    ) -> Result<SmallVec<A>, BristolCircuitError> {
+        let mut wires = SmallVec::with_capacity(count);
+
+        for _ in 0..count {
+            wires.push(self.varint()?);
+        }
+
+        Ok(wires)
+    }
+
+    /// A varint counting items of at least a byte each, so no more than the bytes left.
+    fn count(&mut self) -> Result<usize, BristolCircuitError> {
+        let count = self.varint()?;
+
+        if count > self.bytes.len() - self.pos {
+            return Err(self.error(&format!("Count {} exceeds the bytes left", count)));
+        }
+
+        Ok(count)
+    }
+
+    fn string(&mut self) -> Result<&'a str, BristolCircuitError> {
+        let len = self.varint()?;
+        let start = self.pos;
+        let bytes = self.take(len)?;
+
+        std::str::from_utf8(bytes).map_err(|e| BristolCircuitError::InvalidUtf8 {
+            offset: start + e.valid_up_to(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use smallvec::smallvec;
+
+    use super::*;
+    use crate::{circuit_builder::CircuitBuilder, gadgets, party::PartyId};
+
+    /// `out = (a & b) ^ one`, plus a two-output gate.
+    fn sample() -> BristolCircuit {
+        let mut b = CircuitBuilder::new();
+        let x = b.input("a");
+        let y = b.input("b");
+        let one = b.constant("one", "1");
+        let and = b.gate("AND", &[x, y]);
+        let out = b.gate("XOR", &[and, one]);
+        b.output("out", out);
+
+        let mut circuit = b.build().unwrap();
+        let (out, a) = (4, 0);
+        assert_eq!(circuit.info.output_name_to_wire_index["out"], out);
+        assert_eq!(circuit.info.input_name_to_wire_index["a"], a);
+
+        circuit.gates.push(Gate {
+            inputs: smallvec![out, a],
+            outputs: smallvec![200, 300],
+            op: "MAND".into(),
+        });
+        circuit.wire_count = 301;
+        circuit
+    }
+
+    fn hex(bytes: &[u8]) -> String {
+        bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+    }
+
+    #[test]
+    fn test_golden_bytes() {
+        // Changing this breaks every binary file written so far; bump `VERSION` instead.
+        let bytes = sample().to_binary_bytes().unwrap();
+        let info = br#"{"input_name_to_wire_index":{"a":0,"b":1},"constants":{"one":{"value":"1","wire_index":2}},"output_name_to_wire_index":{"out":4}}"#;
+
+        let mut expected = b"BRCB\x01\xad\x02\x00\x02\x01\x01\x01\x01".to_vec();
+        expected.extend_from_slice(b"\x81\x01");
+        expected.extend_from_slice(info);
+        expected.extend_from_slice(b"\x03\x03AND\x03XOR\x04MAND\x03");
+        expected.extend_from_slice(b"\x00\x02\x01\x00\x01\x03");
+        expected.extend_from_slice(b"\x01\x02\x01\x03\x02\x04");
+        expected.extend_from_slice(b"\x02\x02\x02\x04\x00\xc8\x01\xac\x02");
+
+        assert_eq!(hex(&bytes), hex(&expected));
+        assert_eq!(BristolCircuit::from_binary_bytes(&bytes).unwrap(), sample());
+    }
+
+    #[test]
+    fn test_round_trips() {
+        let mut circuit = gadgets::adder(16, true);
+        circuit.header_style = HeaderStyle::PerPartyLines;
+        circuit
+            .info
+            .input_owners
+            .insert("a".to_string(), PartyId(1));
+
+        let mut bytes = Vec::new();
+        circuit.write_binary(&mut bytes).unwrap();
+        assert!(bytes.len() < circuit.get_bristol_string().unwrap().len() / 2);
+
+        let read = BristolCircuit::read_binary(&mut bytes.as_slice()).unwrap();
+        assert_eq!(read, circuit);
+        assert!(read.gates[0].op.ptr_eq(&read.gates[2].op));
+    }
+
+    #[test]
+    fn test_errors() {
+        let bytes = sample().to_binary_bytes().unwrap();
+        let err = |bytes: &[u8]| {
+            BristolCircuit::from_binary_bytes(bytes)
+                .unwrap_err()
+                .to_string()
+        };
+
+        let mut newer = bytes.clone();
+        newer[4] = 2;
+        assert_eq!(
+            err(&newer),
+            "Unsupported binary circuit version 2 (this build reads up to version 1)"
+        );
+
+        assert_eq!(
+            err(b"0 0"),
+            "Parsing error: Unexpected end at byte 0 of the binary circuit"
+        );
+        assert_eq!(
+            err(b"0 0\n0 0\n"),
+            "Parsing error: Not a binary circuit (no BRCB magic bytes)"
+        );
+
+        // Truncated anywhere, or with trailing bytes, is an error rather than a panic.
+        for len in 0..bytes.len() {
+            assert!(BristolCircuit::from_binary_bytes(&bytes[..len]).is_err());
+        }
+
+        let mut trailing = bytes.clone();
+        trailing.push(0);
+        assert!(err(&trailing).starts_with("Parsing error: Unexpected data after the gates"));
+
+        // A huge count is rejected before allocating for it.
+        let mut huge = b"BRCB\x01\x00\x00".to_vec();
+        huge.extend_from_slice(&[0xff, 0xff, 0xff, 0xff, 0x0f]);
+        assert_eq!(
+            err(&huge),
+            "Parsing error: Count 4294967295 exceeds the bytes left at byte 12 of the binary \
+             circuit"
+        );
+
+        let mut overflow = b"BRCB\x01".to_vec();
+        overflow.extend_from_slice(&[0xff; 11]);
+        assert!(err(&overflow).starts_with("Parsing error: Varint overflow"));
+    }
+}
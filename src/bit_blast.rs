@@ -0,0 +1,468 @@
+use std::{collections::HashMap, str::FromStr};
+
+use crate::{
+    a_gate_type::AGateType,
+    bristol_circuit::BristolCircuit,
+    bristol_circuit_error::BristolCircuitError,
+    circuit_builder::{CircuitBuilder, WireId},
+    gadgets::{add_bit, borrow_bit},
+    trace::trace_span,
+};
+
+impl BristolCircuit {
+    /// Converts an arithmetic circuit to a boolean one computing the same thing on unsigned
+    /// `bit_width`-bit integers, with wrapping arithmetic. Each wire becomes `bit_width` wires,
+    /// least significant bit first, so a named input or output of width `w` has width
+    /// `w * bit_width`. Constants become bits of the constants `bit_0` and `bit_1`.
+    ///
+    /// `AAdd` and `ASub` become ripple-carry adders and subtractors, `AMul` a shift-and-add
+    /// multiplier, comparisons borrow chains or equality trees, and the bitwise ops act on each
+    /// bit. Comparisons and `ABoolAnd`/`ABoolOr` give 0 or 1. Shifting by `bit_width` or more
+    /// gives 0. `ADiv`, `AIntDiv`, `AMod` and `APow` aren't supported, and neither are wire
+    /// groups. The gates must be topologically sorted.
+    pub fn bit_blast(&self, bit_width: usize) -> Result<BristolCircuit, BristolCircuitError> {
+        let _span = trace_span!("pass", name = "bit_blast", gate_count = self.gates.len());
+
+        let inconsistency = |message: String| BristolCircuitError::Inconsistency { message };
+
+        if bit_width == 0 {
+            return Err(inconsistency("Bit width must be at least 1".to_string()));
+        }
+
+        if let Some(name) = self.info.wire_groups.keys().min() {
+            return Err(inconsistency(format!(
+                "Wire group {} can't be bit-blasted",
+                name
+            )));
+        }
+
+        self.check_topological_order()?;
+
+        let mut blaster = Blaster {
+            b: CircuitBuilder::new(),
+            width: bit_width,
+            bits: [None, None],
+        };
+        let mut wires = HashMap::<usize, Vec<WireId>>::new();
+
+        for bus in self.input_buses() {
+            let bits = blaster.b.input_bus(bus.name, bus.width * bit_width);
+
+            for (i, chunk) in bits.chunks(bit_width).enumerate() {
+                wires.insert(bus.wire + i, chunk.to_vec());
+            }
+        }
+
+        let mut constants = self.info.constants.iter().collect::<Vec<_>>();
+        constants.sort_by_key(|(name, _)| name.as_str());
+
+        for (name, constant) in constants {
+            let value = u128::from_str(&constant.value).map_err(|_| {
+                inconsistency(format!(
+                    "Constant {} has non-numeric value \"{}\"",
+                    name, constant.value
+                ))
+            })?;
+
+            let bits = (0..bit_width)
+                .map(|i| blaster.bit(i < 128 && value >> i & 1 == 1))
+                .collect();
+            wires.insert(constant.wire_index, bits);
+        }
+
+        for (i, gate) in self.gates.iter().enumerate() {
+            let unsupported =
+                || inconsistency(format!("Gate {} ({}) can't be bit-blasted", i, gate.op));
+
+            let op = AGateType::from_str(&gate.op).map_err(|_| unsupported())?;
+            let arity = if op == AGateType::AMux { 3 } else { 2 };
+
+            if gate.inputs.len() != arity || gate.outputs.len() != 1 {
+                return Err(inconsistency(format!(
+                    "Gate {} ({}) has {} inputs and {} outputs",
+                    i,
+                    gate.op,
+                    gate.inputs.len(),
+                    gate.outputs.len()
+                )));
+            }
+
+            // Sorted, so every input is an input, a constant or an earlier gate's output.
+            let inputs = gate
+                .inputs
+                .iter()
+                .map(|wire| wires[wire].clone())
+                .collect::<Vec<_>>();
+            let (x, y) = (&inputs[0], &inputs[1]);
+
+            use AGateType::*;
+
+            let bits = match op {
+                AAdd => blaster.add(x, y),
+                ASub => blaster.sub(x, y),
+                AMul => blaster.mul(x, y),
+                AEq => blaster.flag(|bl| bl.equal(x, y)),
+                ANeq => blaster.flag(|bl| {
+                    let equal = bl.equal(x, y);
+                    bl.b.gate("INV", &[equal])
+                }),
+                ALt => blaster.flag(|bl| bl.less(x, y)),
+                AGt => blaster.flag(|bl| bl.less(y, x)),
+                ALEq => blaster.flag(|bl| {
+                    let greater = bl.less(y, x);
+                    bl.b.gate("INV", &[greater])
+                }),
+                AGEq => blaster.flag(|bl| {
+                    let less = bl.less(x, y);
+                    bl.b.gate("INV", &[less])
+                }),
+                AXor => blaster.bitwise("XOR", x, y),
+                ABitAnd => blaster.bitwise("AND", x, y),
+                ABitOr => blaster.bitwise("OR", x, y),
+                ABoolAnd => blaster.flag(|bl| {
+                    let (x, y) = (bl.any(x), bl.any(y));
+                    bl.b.gate("AND", &[x, y])
+                }),
+                ABoolOr => blaster.flag(|bl| {
+                    let (x, y) = (bl.any(x), bl.any(y));
+                    bl.b.gate("OR", &[x, y])
+                }),
+                AShiftL => blaster.shift(x, y, true),
+                AShiftR => blaster.shift(x, y, false),
+                AMux => {
+                    // b + sel * (a - b), as `eval_arithmetic` has it.
+                    let (sel, a, b) = (x, y, &inputs[2]);
+                    let difference = blaster.sub(a, b);
+                    let scaled = blaster.mul(sel, &difference);
+                    blaster.add(b, &scaled)
+                }
+                ADiv | AIntDiv | AMod | APow => return Err(unsupported()),
+            };
+
+            wires.insert(gate.outputs[0], bits);
+        }
+
+        for bus in self.output_buses() {
+            let mut bits = Vec::with_capacity(bus.width * bit_width);
+
+            for wire in bus.wire..bus.wire + bus.width {
+                bits.extend_from_slice(wires.get(&wire).ok_or_else(|| {
+                    inconsistency(format!(
+                        "Output {} reads wire {}, which nothing drives",
+                        bus.name, wire
+                    ))
+                })?);
+            }
+
+            blaster.b.output_bus(bus.name, &bits);
+        }
+
+        let mut result = blaster.b.build()?;
+        result.info.input_owners = self.info.input_owners.clone();
+        result.info.output_visibility = self.info.output_visibility.clone();
+
+        Ok(result)
+    }
+}
+
+/// Builds the boolean subcircuits, on values `width` bits wide.
+struct Blaster {
+    b: CircuitBuilder,
+    width: usize,
+
+    /// The `bit_0` and `bit_1` constants, once needed.
+    bits: [Option<WireId>; 2],
+}
+
+impl Blaster {
+    fn bit(&mut self, value: bool) -> WireId {
+        let value = u8::from(value);
+        let b = &mut self.b;
+
+        *self.bits[usize::from(value)]
+            .get_or_insert_with(|| b.constant(&format!("bit_{}", value), &value.to_string()))
+    }
+
+    /// A 0 or 1 value from its low bit.
+    fn flag(&mut self, low: impl FnOnce(&mut Self) -> WireId) -> Vec<WireId> {
+        let mut bits = vec![low(self)];
+
+        if self.width > 1 {
+            let zero = self.bit(false);
+            bits.resize(self.width, zero);
+        }
+
+        bits
+    }
+
+    fn bitwise(&mut self, op: &str, x: &[WireId], y: &[WireId]) -> Vec<WireId> {
+        x.iter()
+            .zip(y)
+            .map(|(&x, &y)| self.b.gate(op, &[x, y]))
+            .collect()
+    }
+
+    /// `x + y`, dropping the carry out.
+    fn add(&mut self, x: &[WireId], y: &[WireId]) -> Vec<WireId> {
+        let mut carry = None;
+
+        (0..x.len())
+            .map(|i| {
+                let (sum, next) = add_bit(&mut self.b, x[i], y[i], carry, i + 1 < x.len());
+                carry = next;
+                sum
+            })
+            .collect()
+    }
+
+    /// `x - y`, dropping the borrow out.
+    fn sub(&mut self, x: &[WireId], y: &[WireId]) -> Vec<WireId> {
+        let mut borrow = None;
+
+        (0..x.len())
+            .map(|i| {
+                let differ = self.b.gate("XOR", &[x[i], y[i]]);
+                let difference = match borrow {
+                    Some(borrow) => self.b.gate("XOR", &[differ, borrow]),
+                    None => differ,
+                };
+
+                if i + 1 < x.len() {
+                    borrow = Some(borrow_bit(&mut self.b, x[i], y[i], borrow));
+                }
+
+                difference
+            })
+            .collect()
+    }
+
+    /// `x * y`, adding `x << i` for each set bit `i` of `y` and keeping the low bits.
+    fn mul(&mut self, x: &[WireId], y: &[WireId]) -> Vec<WireId> {
+        let mut product = x
+            .iter()
+            .map(|&x| self.b.gate("AND", &[x, y[0]]))
+            .collect::<Vec<_>>();
+
+        for i in 1..y.len() {
+            let partial = x[..x.len() - i]
+                .iter()
+                .map(|&x| self.b.gate("AND", &[x, y[i]]))
+                .collect::<Vec<_>>();
+
+            let high = self.add(&product[i..], &partial);
+            product.splice(i.., high);
+        }
+
+        product
+    }
+
+    /// Whether `x < y`: the borrow out of `x - y`.
+    fn less(&mut self, x: &[WireId], y: &[WireId]) -> WireId {
+        let mut borrow = None;
+
+        for i in 0..x.len() {
+            borrow = Some(borrow_bit(&mut self.b, x[i], y[i], borrow));
+        }
+
+        borrow.expect("bit widths are at least 1")
+    }
+
+    fn equal(&mut self, x: &[WireId], y: &[WireId]) -> WireId {
+        let same = x
+            .iter()
+            .zip(y)
+            .map(|(&x, &y)| {
+                let differ = self.b.gate("XOR", &[x, y]);
+                self.b.gate("INV", &[differ])
+            })
+            .collect();
+
+        self.tree("AND", same)
+    }
+
+    /// Whether any of `x` is set.
+    fn any(&mut self, x: &[WireId]) -> WireId {
+        self.tree("OR", x.to_vec())
+    }
+
+    /// Combines `wires` with `op` in a balanced tree.
+    fn tree(&mut self, op: &str, mut wires: Vec<WireId>) -> WireId {
+        while wires.len() > 1 {
+            wires = wires
+                .chunks(2)
+                .map(|pair| match pair {
+                    [l, r] => self.b.gate(op, &[*l, *r]),
+                    [single] => *single,
+                    _ => unreachable!(),
+                })
+                .collect();
+        }
+
+        wires[0]
+    }
+
+    /// `x` shifted by `amount`: a barrel shifter with a stage per bit of `amount` below the
+    /// width, and zero if any higher bit is set.
+    fn shift(&mut self, x: &[WireId], amount: &[WireId], left: bool) -> Vec<WireId> {
+        let zero = self.bit(false);
+        let mut shifted = x.to_vec();
+        let mut overflow = Vec::new();
+
+        for (k, &bit) in amount.iter().enumerate() {
+            let Some(distance) = 1usize
+                .checked_shl(k as u32)
+                .filter(|&distance| distance < x.len())
+            else {
+                overflow.push(bit);
+                continue;
+            };
+
+            shifted = (0..x.len())
+                .map(|j| {
+                    let source = match left {
+                        true => j.checked_sub(distance),
+                        false => Some(j + distance).filter(|&source| source < x.len()),
+                    };
+                    let moved = source.map_or(zero, |source| shifted[source]);
+
+                    self.b.mux(bit, shifted[j], moved)
+                })
+                .collect();
+        }
+
+        if overflow.is_empty() {
+            return shifted;
+        }
+
+        let overflow = self.any(&overflow);
+        let keep = self.b.gate("INV", &[overflow]);
+
+        shifted
+            .into_iter()
+            .map(|bit| self.b.gate("AND", &[bit, keep]))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{io_encoding::BitOrder, split_mix::SplitMix64};
+
+    const OPS: &[&str] = &[
+        "AAdd", "ASub", "AMul", "AEq", "ANeq", "ALt", "AGt", "ALEq", "AGEq", "AXor", "ABitAnd",
+        "ABitOr", "ABoolAnd", "ABoolOr", "AShiftL", "AShiftR", "AMux",
+    ];
+
+    /// Random gates over two inputs and a constant, each reading two or three earlier wires.
+    fn random_circuit(rng: &mut SplitMix64, gates: usize, constant: u64) -> BristolCircuit {
+        let mut b = CircuitBuilder::new();
+        let mut wires = vec![
+            b.input("x"),
+            b.input("y"),
+            b.constant("c", &constant.to_string()),
+        ];
+
+        for _ in 0..gates {
+            let op = OPS[rng.next_u64() as usize % OPS.len()];
+            let arity = if op == "AMux" { 3 } else { 2 };
+            let inputs = (0..arity)
+                .map(|_| wires[rng.next_u64() as usize % wires.len()])
+                .collect::<Vec<_>>();
+            wires.push(b.gate(op, &inputs));
+        }
+
+        b.output("out", *wires.last().unwrap());
+        b.output("mid", wires[wires.len() / 2]);
+        b.build().unwrap()
+    }
+
+    #[test]
+    fn test_matches_arithmetic_eval() {
+        let mut rng = SplitMix64::new(7);
+
+        for bit_width in [1, 3, 8, 64] {
+            let mask = u64::MAX >> (64 - bit_width);
+            let modulus = (bit_width < 64).then(|| 1 << bit_width);
+
+            for _ in 0..10 {
+                let constant = rng.next_u64() & mask;
+                let circuit = random_circuit(&mut rng, 12, constant);
+                let blasted = circuit.bit_blast(bit_width).unwrap();
+                assert_eq!(blasted.io_widths.0, [bit_width, bit_width]);
+
+                for _ in 0..5 {
+                    let (x, y) = (rng.next_u64() & mask, rng.next_u64() & mask);
+                    // Small shift amounts too, not just ones that clear everything.
+                    let y = if rng.next_u64().is_multiple_of(2) {
+                        y % 8
+                    } else {
+                        y
+                    };
+
+                    let inputs = HashMap::from([("x".to_string(), x), ("y".to_string(), y)]);
+                    let expected = circuit.eval_arithmetic(&inputs, modulus).unwrap();
+
+                    let inputs = inputs
+                        .into_iter()
+                        .map(|(name, value)| (name, u128::from(value)))
+                        .collect();
+                    let actual = blasted
+                        .eval_boolean_ints(&inputs, BitOrder::LsbFirst)
+                        .unwrap();
+
+                    for (name, value) in expected {
+                        assert_eq!(actual[&name], u128::from(value), "{}", name);
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_unsupported() {
+        let mut b = CircuitBuilder::new();
+        let x = b.input("x");
+        let y = b.input("y");
+        let sum = b.gate("AAdd", &[x, y]);
+        let quotient = b.gate("ADiv", &[sum, y]);
+        b.output("out", quotient);
+        let circuit = b.build().unwrap();
+
+        let err = circuit.bit_blast(8).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Inconsistency: Gate 1 (ADiv) can't be bit-blasted"
+        );
+
+        let err = crate::gadgets::adder(2, true).bit_blast(8).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Inconsistency: Gate 0 (XOR) can't be bit-blasted"
+        );
+
+        assert!(circuit.bit_blast(0).is_err());
+    }
+
+    #[test]
+    fn test_undriven_wires() {
+        for (text, message) in [
+            (
+                "0 2\n1 1\n1 1\n",
+                "Output output0 reads wire 1, which nothing drives",
+            ),
+            (
+                "1 3\n1 1\n1 1\n\n2 1 0 1 2 AAdd\n",
+                "Gate 0 (AAdd) reads wire 1 before it is assigned",
+            ),
+        ] {
+            let circuit = BristolCircuit::from_bristol_string(text).unwrap();
+            circuit.validate().unwrap();
+
+            assert_eq!(
+                circuit.bit_blast(4).unwrap_err().to_string(),
+                format!("Inconsistency: {}", message)
+            );
+        }
+    }
+}
@@ -0,0 +1,271 @@
+use std::{collections::HashMap, str::FromStr};
+
+use crate::{
+    bool_gate_type::{Arity, BoolGateType},
+    bristol_circuit::BristolCircuit,
+    bristol_circuit_error::BristolCircuitError,
+    eval::{eval_wires, evaluation_error, EvalObserver, GateSemantics},
+    gate::bool_literal,
+    lut::Lut,
+    op_classification::OpKind,
+};
+
+struct BooleanSemantics;
+
+impl GateSemantics<bool> for BooleanSemantics {
+    fn literal(&mut self, op: &str, value: usize) -> Option<bool> {
+        bool_literal(op, value)
+    }
+
+    fn eval_gate(
+        &mut self,
+        op: &str,
+        inputs: &[bool],
+        gate_index: usize,
+    ) -> Result<Vec<bool>, BristolCircuitError> {
+        Ok(BoolOp::resolve(op, inputs.len(), gate_index)?.apply(inputs))
+    }
+}
+
+/// A boolean gate's op, parsed and checked against its input count.
+#[derive(Clone, Debug)]
+pub(crate) enum BoolOp {
+    Gate(BoolGateType),
+    Lut(Lut),
+}
+
+impl BoolOp {
+    pub fn resolve(
+        op: &str,
+        input_count: usize,
+        gate_index: usize,
+    ) -> Result<BoolOp, BristolCircuitError> {
+        if let Some(lut) = Lut::parse(op, input_count) {
+            return Ok(BoolOp::Lut(lut?));
+        }
+
+        let op_type = BoolGateType::from_str(op).map_err(|_| {
+            evaluation_error(format!(
+                "Gate {} has unsupported boolean op {}",
+                gate_index, op
+            ))
+        })?;
+
+        let expected = match op_type.arity() {
+            Arity::Fixed { inputs, .. } => inputs,
+            Arity::Variable => input_count - input_count % 2,
+        };
+
+        if input_count != expected {
+            return Err(evaluation_error(format!(
+                "Gate {} ({}) has {} inputs",
+                gate_index, op, input_count
+            )));
+        }
+
+        Ok(BoolOp::Gate(op_type))
+    }
+
+    pub fn apply(&self, inputs: &[bool]) -> Vec<bool> {
+        use BoolGateType::*;
+
+        let op_type = match self {
+            BoolOp::Lut(lut) => return vec![lut.eval(inputs)],
+            BoolOp::Gate(op_type) => op_type,
+        };
+
+        match op_type {
+            Xor => vec![inputs[0] ^ inputs[1]],
+            And => vec![inputs[0] & inputs[1]],
+            Or => vec![inputs[0] | inputs[1]],
+            Nand => vec![!(inputs[0] & inputs[1])],
+            Nor => vec![!(inputs[0] | inputs[1])],
+            Xnor => vec![!(inputs[0] ^ inputs[1])],
+            Inv | Not => vec![!inputs[0]],
+            Eq | Eqw | Buf => vec![inputs[0]],
+            Mux => vec![if inputs[0] { inputs[2] } else { inputs[1] }],
+            Mand => {
+                let (left, right) = inputs.split_at(inputs.len() / 2);
+                left.iter().zip(right).map(|(l, r)| l & r).collect()
+            }
+        }
+    }
+}
+
+impl BristolCircuit {
+    /// Evaluates a boolean circuit. Each named input and output is a bus of bits, with its width
+    /// taken from `io_widths`. Wire groups are evaluated as inputs and outputs too.
+    pub fn eval_boolean(
+        &self,
+        inputs: &HashMap<String, Vec<bool>>,
+    ) -> Result<HashMap<String, Vec<bool>>, BristolCircuitError> {
+        self.eval_boolean_observed(inputs, &mut ())
+    }
+
+    pub(crate) fn eval_boolean_observed(
+        &self,
+        inputs: &HashMap<String, Vec<bool>>,
+        observer: &mut impl EvalObserver<bool>,
+    ) -> Result<HashMap<String, Vec<bool>>, BristolCircuitError> {
+        let wires = self.eval_boolean_wires(inputs, observer)?;
+
+        self.bus_outputs(&wires)
+    }
+
+    /// Evaluates a boolean circuit, returning the value of every wire.
+    pub(crate) fn eval_boolean_wires(
+        &self,
+        inputs: &HashMap<String, Vec<bool>>,
+        observer: &mut impl EvalObserver<bool>,
+    ) -> Result<Vec<Option<bool>>, BristolCircuitError> {
+        self.classify_ops().require(OpKind::Boolean, self)?;
+
+        let mut seeds = self.bus_input_seeds(inputs)?;
+        seeds.extend(self.boolean_constant_seeds()?);
+
+        eval_wires(self, seeds, &mut BooleanSemantics, observer)
+    }
+
+    pub(crate) fn boolean_constant_seeds(&self) -> Result<Vec<(usize, bool)>, BristolCircuitError> {
+        let mut seeds = Vec::new();
+
+        for (name, constant) in &self.info.constants {
+            let value = match constant.value.as_str() {
+                "0" | "false" => false,
+                "1" | "true" => true,
+                _ => {
+                    return Err(evaluation_error(format!(
+                        "Constant {} has non-boolean value \"{}\"",
+                        name, constant.value
+                    )))
+                }
+            };
+
+            seeds.push((constant.wire_index, value));
+        }
+
+        Ok(seeds)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{circuit_info::ConstantInfo, gate::Gate};
+
+    fn gate(op: &str, inputs: &[usize], outputs: &[usize]) -> Gate {
+        Gate {
+            inputs: inputs.into(),
+            outputs: outputs.into(),
+            op: op.into(),
+        }
+    }
+
+    /// Inputs a (2 bits, wires 0-1) and b (2 bits, wires 2-3); output out (2 bits, wires 4-5).
+    fn two_bit_circuit(gates: Vec<Gate>) -> BristolCircuit {
+        let mut circuit = BristolCircuit {
+            wire_count: 0,
+            info: Default::default(),
+            io_widths: (vec![2, 2], vec![2]),
+            gates,
+            header_style: Default::default(),
+        };
+
+        circuit
+            .info
+            .input_name_to_wire_index
+            .extend([("a".to_string(), 0), ("b".to_string(), 2)]);
+        circuit
+            .info
+            .output_name_to_wire_index
+            .insert("out".to_string(), 4);
+        circuit.recompute_wire_count();
+        circuit
+    }
+
+    fn inputs(a: [bool; 2], b: [bool; 2]) -> HashMap<String, Vec<bool>> {
+        HashMap::from([("a".to_string(), a.to_vec()), ("b".to_string(), b.to_vec())])
+    }
+
+    #[test]
+    fn test_basic_ops() {
+        let circuit = two_bit_circuit(vec![
+            gate("XOR", &[0, 2], &[4]),
+            gate("NAND", &[1, 3], &[6]),
+            gate("INV", &[6], &[5]),
+        ]);
+
+        let outputs = circuit
+            .eval_boolean(&inputs([true, true], [false, true]))
+            .unwrap();
+        assert_eq!(outputs["out"], vec![true, true]);
+
+        let outputs = circuit
+            .eval_boolean(&inputs([true, false], [true, true]))
+            .unwrap();
+        assert_eq!(outputs["out"], vec![false, false]);
+    }
+
+    #[test]
+    fn test_mand_and_eq() {
+        let circuit = two_bit_circuit(vec![
+            gate("MAND", &[0, 1, 2, 3], &[6, 5]),
+            gate("EQ", &[1], &[7]),
+            gate("AND", &[6, 7], &[4]),
+        ]);
+
+        let outputs = circuit
+            .eval_boolean(&inputs([true, true], [true, false]))
+            .unwrap();
+        assert_eq!(outputs["out"], vec![true, false]);
+    }
+
+    #[test]
+    fn test_constants() {
+        let mut circuit =
+            two_bit_circuit(vec![gate("AND", &[0, 6], &[4]), gate("OR", &[1, 6], &[5])]);
+        circuit.info.constants.insert(
+            "one".to_string(),
+            ConstantInfo {
+                value: "1".to_string(),
+                wire_index: 6,
+            },
+        );
+
+        let outputs = circuit
+            .eval_boolean(&inputs([true, false], [false, false]))
+            .unwrap();
+        assert_eq!(outputs["out"], vec![true, true]);
+    }
+
+    #[test]
+    fn test_errors() {
+        let circuit = two_bit_circuit(vec![gate("XOR", &[0, 9], &[4]), gate("INV", &[1], &[5])]);
+
+        let err = circuit
+            .eval_boolean(&inputs([true, true], [false, true]))
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Evaluation error: Gate 0 (XOR) reads undefined wire 9"
+        );
+
+        let mut missing = inputs([true, true], [false, true]);
+        missing.remove("b");
+        let err = circuit.eval_boolean(&missing).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Evaluation error: Missing value for input b"
+        );
+
+        let mut wrong_width = inputs([true, true], [false, true]);
+        wrong_width.insert("a".to_string(), vec![true]);
+        assert!(circuit.eval_boolean(&wrong_width).is_err());
+
+        let arithmetic = two_bit_circuit(vec![gate("AAdd", &[0, 2], &[4])]);
+        assert!(matches!(
+            arithmetic.eval_boolean(&inputs([true, true], [false, true])),
+            Err(BristolCircuitError::Inconsistency { .. })
+        ));
+    }
+}
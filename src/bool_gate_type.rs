@@ -0,0 +1,127 @@
+use serde::{Deserialize, Serialize};
+use strum::{Display, EnumIter, EnumString};
+
+/// The ops of boolean Bristol circuits, spelled as in the Bristol Fashion format.
+#[derive(
+    Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize, EnumString, Display, EnumIter,
+)]
+#[strum(serialize_all = "UPPERCASE")]
+pub enum BoolGateType {
+    Xor,
+    And,
+    Inv,
+    Not,
+    Or,
+    Nand,
+    Nor,
+    Xnor,
+    Eq,
+    Eqw,
+    Mand,
+    Buf,
+
+    /// `MUX sel a b` is `b` when `sel` is set and `a` otherwise.
+    Mux,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Arity {
+    Fixed {
+        inputs: usize,
+        outputs: usize,
+    },
+
+    /// Any number of AND gates batched into one: `2n` inputs and `n` outputs.
+    Variable,
+}
+
+impl BoolGateType {
+    pub fn arity(&self) -> Arity {
+        use BoolGateType::*;
+
+        match self {
+            Xor | And | Or | Nand | Nor | Xnor => Arity::Fixed {
+                inputs: 2,
+                outputs: 1,
+            },
+            Inv | Not | Eq | Eqw | Buf => Arity::Fixed {
+                inputs: 1,
+                outputs: 1,
+            },
+            Mux => Arity::Fixed {
+                inputs: 3,
+                outputs: 1,
+            },
+            Mand => Arity::Variable,
+        }
+    }
+
+    /// Whether the gate is free under free-XOR garbling.
+    pub fn is_linear(&self) -> bool {
+        use BoolGateType::*;
+
+        match self {
+            Xor | Xnor | Inv | Not | Eq | Eqw | Buf => true,
+            And | Or | Nand | Nor | Mand | Mux => false,
+        }
+    }
+}
+
+impl Arity {
+    pub fn accepts(&self, inputs: usize, outputs: usize) -> bool {
+        match self {
+            Arity::Fixed {
+                inputs: i,
+                outputs: o,
+            } => *i == inputs && *o == outputs,
+            Arity::Variable => outputs > 0 && inputs == 2 * outputs,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use strum::IntoEnumIterator;
+
+    use super::*;
+
+    #[test]
+    fn test_round_trip() {
+        for op in BoolGateType::iter() {
+            assert_eq!(BoolGateType::from_str(&op.to_string()).unwrap(), op);
+        }
+
+        assert_eq!(BoolGateType::Eqw.to_string(), "EQW");
+        assert_eq!(BoolGateType::Mand.to_string(), "MAND");
+    }
+
+    #[test]
+    fn test_rejects_near_misses() {
+        for op in ["XOR2", "xor", "Xor", "AND ", "", "AAdd", "EQ W"] {
+            assert!(BoolGateType::from_str(op).is_err(), "{:?}", op);
+        }
+    }
+
+    #[test]
+    fn test_arity() {
+        assert!(BoolGateType::And.arity().accepts(2, 1));
+        assert!(!BoolGateType::And.arity().accepts(3, 1));
+        assert!(BoolGateType::Inv.arity().accepts(1, 1));
+        assert!(BoolGateType::Mux.arity().accepts(3, 1));
+        assert_eq!(BoolGateType::Mand.arity(), Arity::Variable);
+        assert!(BoolGateType::Mand.arity().accepts(6, 3));
+        assert!(!BoolGateType::Mand.arity().accepts(5, 3));
+    }
+
+    #[test]
+    fn test_is_linear() {
+        let nonlinear = BoolGateType::iter()
+            .filter(|op| !op.is_linear())
+            .collect::<Vec<_>>();
+
+        use BoolGateType::*;
+        assert_eq!(nonlinear, vec![And, Or, Nand, Nor, Mand, Mux]);
+    }
+}
@@ -1,9 +1,23 @@
-use crate::bristol_line::BristolLine;
-use crate::gate::Gate;
-use crate::raw_bristol_circuit::RawBristolCircuit;
+use crate::circuit_header::CircuitHeader;
+use crate::circuit_reader::BristolCircuitReader;
+use crate::circuit_writer::BristolWriter;
+use crate::compression::reject_gzip;
+use crate::gate::{takes_literals, Gate};
+use crate::header_style::HeaderStyle;
+use crate::line_reader::LineReader;
+use crate::parse_options::{ParseLimits, ParseOptions, Utf8Policy};
+use crate::parse_warning::ParseWarning;
+use crate::raw_bristol_circuit::{RawBristolCircuit, RawBristolCircuitRef};
+use crate::trace::{trace_record, trace_span};
+use crate::wire_group::WireSpan;
+use crate::write_options::WriteOptions;
 use crate::{bristol_circuit_error::BristolCircuitError, circuit_info::CircuitInfo};
 use serde::{Deserialize, Serialize};
-use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::fmt::{self, Display, Formatter};
+use std::io::{BufRead, BufReader, Write};
+use std::str::FromStr;
 
 #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub struct BristolCircuit {
@@ -11,6 +25,8 @@ pub struct BristolCircuit {
     pub info: CircuitInfo,
     pub io_widths: (Vec<usize>, Vec<usize>),
     pub gates: Vec<Gate>,
+    #[serde(default)]
+    pub header_style: HeaderStyle,
 }
 
 impl BristolCircuit {
@@ -18,22 +34,23 @@ impl BristolCircuit {
         BristolCircuit::from_info_and_bristol_string(&raw.info, &raw.bristol)
     }
 
+    pub fn from_raw_ref(raw: &RawBristolCircuitRef) -> Result<BristolCircuit, BristolCircuitError> {
+        BristolCircuit::from_info_and_bristol_string(&raw.info, &raw.bristol)
+    }
+
     pub fn to_raw(&self) -> Result<RawBristolCircuit, BristolCircuitError> {
-        Ok(RawBristolCircuit {
-            bristol: self.get_bristol_string()?,
-            info: self.info.clone(),
-        })
+        Ok(RawBristolCircuit::new(
+            self.get_bristol_string()?,
+            self.info.clone(),
+        ))
     }
 
     pub fn get_bristol_string(&self) -> Result<String, BristolCircuitError> {
-        let mut output = Vec::new();
-        let mut writer = BufWriter::new(&mut output);
-
-        self.write_bristol(&mut writer)?;
-        drop(writer);
+        let mut output = Vec::with_capacity(self.bristol_len_estimate());
+        self.write_bristol(&mut output)?;
 
-        String::from_utf8(output).map_err(|_| BristolCircuitError::ParsingError {
-            message: "Generated Bristol data was not valid utf8".into(),
+        String::from_utf8(output).map_err(|e| BristolCircuitError::InvalidUtf8 {
+            offset: e.utf8_error().valid_up_to(),
         })
     }
 
@@ -44,79 +61,435 @@ impl BristolCircuit {
         BristolCircuit::read_info_and_bristol(info, &mut BufReader::new(input.as_bytes()))
     }
 
-    pub fn write_bristol<W: Write>(&self, w: &mut W) -> Result<(), BristolCircuitError> {
-        writeln!(w, "{} {}", self.gates.len(), self.wire_count)?;
+    pub fn from_info_and_bristol_bytes(
+        info: &CircuitInfo,
+        bytes: &[u8],
+        policy: Utf8Policy,
+    ) -> Result<BristolCircuit, BristolCircuitError> {
+        BristolCircuit::from_info_and_bristol_bytes_with_options(
+            info,
+            bytes,
+            &ParseOptions {
+                utf8: policy,
+                ..Default::default()
+            },
+        )
+    }
+
+    pub fn from_info_and_bristol_bytes_with_options(
+        info: &CircuitInfo,
+        bytes: &[u8],
+        options: &ParseOptions,
+    ) -> Result<BristolCircuit, BristolCircuitError> {
+        reject_gzip(bytes)?;
 
-        let (input_widths, output_widths) = &self.io_widths;
+        let input = match options.utf8 {
+            Utf8Policy::Strict => Cow::Borrowed(std::str::from_utf8(bytes).map_err(|e| {
+                BristolCircuitError::InvalidUtf8 {
+                    offset: e.valid_up_to(),
+                }
+            })?),
+            Utf8Policy::Lossy => String::from_utf8_lossy(bytes),
+        };
 
-        write!(w, "{}", input_widths.len())?;
-        for width in input_widths {
-            write!(w, " {}", width)?;
-        }
-        writeln!(w)?;
+        BristolCircuit::from_info_and_bristol_string_with_options(info, &input, options)
+    }
+
+    /// Parses untrusted input, returning an error rather than panicking or allocating more than
+    /// the input justifies. Invalid UTF-8 is replaced, then rejected in whichever token it
+    /// appears. The circuit is validated, after checking that the header declares no more wires
+    /// than the input has bytes, which holds for any real circuit since each wire costs at least
+    /// a digit and a separator wherever it's used.
+    pub fn parse_bytes(
+        info: &CircuitInfo,
+        bytes: &[u8],
+    ) -> Result<BristolCircuit, BristolCircuitError> {
+        let circuit = BristolCircuit::from_info_and_bristol_bytes(info, bytes, Utf8Policy::Lossy)?;
 
-        write!(w, "{}", output_widths.len())?;
-        for width in output_widths {
-            write!(w, " {}", width)?;
+        if circuit.wire_count > bytes.len() {
+            return Err(BristolCircuitError::Inconsistency {
+                message: format!(
+                    "Header declares {} wires, more than the input's {} bytes could use",
+                    circuit.wire_count,
+                    bytes.len()
+                ),
+            });
         }
-        writeln!(w)?;
 
-        writeln!(w)?;
+        circuit.validate()?;
+
+        Ok(circuit)
+    }
+
+    pub fn write_bristol<W: Write>(&self, w: &mut W) -> Result<(), BristolCircuitError> {
+        self.write_bristol_with_options(w, &WriteOptions::default())
+    }
+
+    pub fn write_bristol_with_options<W: Write>(
+        &self,
+        w: &mut W,
+        options: &WriteOptions,
+    ) -> Result<(), BristolCircuitError> {
+        self.write_bristol_with_input_widths(w, &self.io_widths.0, options)
+    }
+
+    pub(crate) fn write_bristol_with_input_widths<W: Write>(
+        &self,
+        w: &mut W,
+        input_widths: &[usize],
+        options: &WriteOptions,
+    ) -> Result<(), BristolCircuitError> {
+        let _span = trace_span!(
+            "write",
+            gate_count = self.gates.len(),
+            wire_count = self.wire_count
+        );
+
+        let header = CircuitHeader {
+            gate_count: self.gates.len(),
+            wire_count: self.wire_count,
+            input_widths: input_widths.to_vec(),
+            output_widths: self.io_widths.1.clone(),
+            header_style: self.header_style,
+        };
+        let mut writer = BristolWriter::with_options(w, &header, options)?;
 
         for gate in &self.gates {
-            writeln!(w, "{}", gate)?;
+            writer.write_gate(gate)?;
         }
 
+        writer.finish()?;
+
         Ok(())
     }
 
+    /// Parses with [`ParseOptions::default`], which is strict: a header whose gate or wire count
+    /// disagrees with the gates is an error. Use [`ParseOptions::strict`] to accept it instead.
     pub fn read_info_and_bristol<R: BufRead>(
         info: &CircuitInfo,
         r: &mut R,
     ) -> Result<BristolCircuit, BristolCircuitError> {
-        let (gate_count, wire_count) = BristolLine::read(r)?.circuit_sizes()?;
+        BristolCircuit::read_info_and_bristol_with_options(info, r, &ParseOptions::default())
+    }
 
-        let input_widths = BristolLine::read(r)?.io_widths()?;
-        if input_widths.len() != info.input_name_to_wire_index.len() {
-            return Err(BristolCircuitError::Inconsistency {
-                message: "Input count mismatch".into(),
-            });
-        }
+    /// Parses untrusted input, failing with [`BristolCircuitError::LimitExceeded`] once it
+    /// declares or contains more than `limits` allows. [`ParseLimits::unlimited`] disables them.
+    pub fn read_info_and_bristol_with_limits<R: BufRead>(
+        info: &CircuitInfo,
+        r: &mut R,
+        limits: &ParseLimits,
+    ) -> Result<BristolCircuit, BristolCircuitError> {
+        BristolCircuit::read_info_and_bristol_with_options(
+            info,
+            r,
+            &ParseOptions {
+                limits: *limits,
+                ..Default::default()
+            },
+        )
+    }
 
-        let output_widths = BristolLine::read(r)?.io_widths()?;
-        if output_widths.len() != info.output_name_to_wire_index.len() {
-            return Err(BristolCircuitError::Inconsistency {
-                message: "Output count mismatch".into(),
-            });
-        }
+    pub fn from_info_and_bristol_string_with_options(
+        info: &CircuitInfo,
+        input: &str,
+        options: &ParseOptions,
+    ) -> Result<BristolCircuit, BristolCircuitError> {
+        BristolCircuit::read_info_and_bristol_with_options(
+            info,
+            &mut BufReader::new(input.as_bytes()),
+            options,
+        )
+    }
 
+    pub fn read_info_and_bristol_with_options<R: BufRead>(
+        info: &CircuitInfo,
+        r: &mut R,
+        options: &ParseOptions,
+    ) -> Result<BristolCircuit, BristolCircuitError> {
+        BristolCircuit::read_info_and_bristol_with_warnings(info, r, options)
+            .map(|(circuit, _)| circuit)
+    }
+
+    /// Parses a circuit, also returning the problems that were tolerated because
+    /// `options.strict` is disabled.
+    pub fn read_info_and_bristol_with_warnings<R: BufRead>(
+        info: &CircuitInfo,
+        r: &mut R,
+        options: &ParseOptions,
+    ) -> Result<(BristolCircuit, Vec<ParseWarning>), BristolCircuitError> {
+        let span = trace_span!("parse"; gate_count, wire_count);
+        let reader = BristolCircuitReader::open_with_options(info, r, options)?;
+        trace_record!(span, gate_count = reader.gate_count());
+        trace_record!(span, wire_count = reader.wire_count());
+
+        BristolCircuit::from_reader(info.clone(), reader, options)
+    }
+
+    /// Parses Bristol text that came without info, such as most published circuits, naming the
+    /// inputs and outputs as [`CircuitHeader::generated_info`] does. There are no constants.
+    /// [`BristolCircuit::with_renamed_io`] can give them better names.
+    pub fn from_bristol_string(input: &str) -> Result<BristolCircuit, BristolCircuitError> {
+        BristolCircuit::read_bristol(&mut BufReader::new(input.as_bytes()))
+    }
+
+    pub fn read_bristol<R: BufRead>(r: &mut R) -> Result<BristolCircuit, BristolCircuitError> {
+        let options = ParseOptions::default();
+        let span = trace_span!("parse"; gate_count, wire_count);
+        reject_gzip(r.fill_buf()?)?;
+
+        let mut lines = LineReader::new(r, &options);
+        let header = CircuitHeader::read(&mut lines, options.header_style, None)?;
+        trace_record!(span, gate_count = header.gate_count);
+        trace_record!(span, wire_count = header.wire_count);
+
+        let info = header.generated_info();
+        let reader = BristolCircuitReader::from_header(lines, header, &options);
+
+        BristolCircuit::from_reader(info, reader, &options).map(|(circuit, _)| circuit)
+    }
+
+    /// Collects the gates from `reader` and checks the wire count, as the last step of parsing.
+    pub(crate) fn from_reader<R: BufRead>(
+        info: CircuitInfo,
+        mut reader: BristolCircuitReader<R>,
+        options: &ParseOptions,
+    ) -> Result<(BristolCircuit, Vec<ParseWarning>), BristolCircuitError> {
+        let gates = reader.by_ref().collect::<Result<Vec<_>, _>>()?;
+        let (header, warnings) = reader.into_parts();
+
+        BristolCircuit::from_parts(info, header, gates, warnings, options)
+    }
+
+    /// Assembles the parsed parts and checks the wire count.
+    pub(crate) fn from_parts(
+        info: CircuitInfo,
+        header: CircuitHeader,
+        gates: Vec<Gate>,
+        mut warnings: Vec<ParseWarning>,
+        options: &ParseOptions,
+    ) -> Result<(BristolCircuit, Vec<ParseWarning>), BristolCircuitError> {
+        let CircuitHeader {
+            wire_count,
+            input_widths,
+            output_widths,
+            header_style,
+            ..
+        } = header;
         let io_widths = (input_widths, output_widths);
 
-        let mut gates = Vec::new();
-        for _ in 0..gate_count {
-            gates.push(BristolLine::read(r)?.gate()?);
+        let mut circuit = BristolCircuit {
+            wire_count,
+            info,
+            io_widths,
+            gates,
+            header_style,
+        };
+
+        // Gates may reference more wires than were declared, and validation allocates for them.
+        let referenced = circuit.referenced_wire_count();
+        ParseLimits::check("max_wires", options.limits.max_wires, referenced, || {
+            format!("Gates reference {} wires", referenced)
+        })?;
+
+        if options.validate {
+            circuit.validate()?;
         }
 
-        for line in r.lines() {
-            if !line?.trim().is_empty() {
-                return Err(BristolCircuitError::ParsingError {
-                    message: "Unexpected non-whitespace line after gates".into(),
+        if referenced > wire_count || (options.exact_wire_count && referenced != wire_count) {
+            if options.strict {
+                return Err(BristolCircuitError::Inconsistency {
+                    message: format!(
+                        "Header declares {} wires but {} are referenced",
+                        wire_count, referenced
+                    ),
                 });
             }
+
+            warnings.push(ParseWarning::WireCountMismatch {
+                declared: wire_count,
+                actual: referenced,
+            });
+            circuit.wire_count = referenced;
         }
 
-        Ok(BristolCircuit {
-            wire_count,
-            info: info.clone(),
-            io_widths,
-            gates,
-        })
+        Ok((circuit, warnings))
+    }
+
+    /// Sets `wire_count` to one more than the highest referenced wire (0 for an empty circuit),
+    /// returning the new value.
+    pub fn recompute_wire_count(&mut self) -> usize {
+        self.wire_count = self.referenced_wire_count();
+        self.wire_count
+    }
+
+    /// Panics unless `wire_count` is exactly the number of wires the circuit references.
+    pub fn assert_wire_count_tight(&self) {
+        let referenced = self.referenced_wire_count();
+
+        assert_eq!(
+            self.wire_count, referenced,
+            "wire_count is {} but {} wires are referenced",
+            self.wire_count, referenced
+        );
+    }
+
+    /// One more than the highest wire index used by a gate or by the circuit info (including the
+    /// full width of each input and output).
+    pub(crate) fn referenced_wire_count(&self) -> usize {
+        let gate_wires = self
+            .gates
+            .iter()
+            .flat_map(|gate| gate.input_wires().iter().chain(gate.outputs.iter()))
+            .map(|&wire| wire.saturating_add(1));
+
+        let io_wires = self
+            .input_buses()
+            .into_iter()
+            .chain(self.output_buses())
+            .map(|bus| bus.wire.saturating_add(bus.width.max(1)));
+
+        let constant_wires = self
+            .info
+            .constants
+            .values()
+            .map(|constant| constant.wire_index.saturating_add(1));
+
+        let group_wires = self
+            .info
+            .wire_groups
+            .values()
+            .flat_map(|group| group.span.wires())
+            .map(|wire| wire.saturating_add(1));
+
+        gate_wires
+            .chain(io_wires)
+            .chain(constant_wires)
+            .chain(group_wires)
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// Renumbers every wire reference: gates, named inputs and outputs, constants and wire groups.
+    /// `EQ` inputs are literal values rather than wires, so are left alone.
+    pub(crate) fn map_wires(&mut self, f: impl Fn(usize) -> usize) {
+        for gate in &mut self.gates {
+            if !takes_literals(&gate.op) {
+                for wire in gate.inputs.iter_mut() {
+                    *wire = f(*wire);
+                }
+            }
+
+            for wire in gate.outputs.iter_mut() {
+                *wire = f(*wire);
+            }
+        }
+
+        let info = &mut self.info;
+
+        for wire in info
+            .input_name_to_wire_index
+            .values_mut()
+            .chain(info.output_name_to_wire_index.values_mut())
+        {
+            *wire = f(*wire);
+        }
+
+        for constant in info.constants.values_mut() {
+            constant.wire_index = f(constant.wire_index);
+        }
+
+        for group in info.wire_groups.values_mut() {
+            group.span = WireSpan::from_wires(group.span.wires().into_iter().map(&f).collect());
+        }
+    }
+
+    /// Named inputs with their first wire and width, in wire order.
+    pub(crate) fn input_buses(&self) -> Vec<IoBus<'_>> {
+        io_buses(self.info.inputs(), &self.io_widths.0)
+    }
+
+    /// Named outputs with their first wire and width, in wire order.
+    pub(crate) fn output_buses(&self) -> Vec<IoBus<'_>> {
+        io_buses(self.info.outputs(), &self.io_widths.1)
+    }
+
+    /// Each named output's width, for [`BristolCircuit::set_output_widths`] to restore once a
+    /// pass has moved outputs past each other.
+    pub(crate) fn output_widths_by_name(&self) -> HashMap<String, usize> {
+        self.output_buses()
+            .into_iter()
+            .map(|bus| (bus.name.to_string(), bus.width))
+            .collect()
+    }
+
+    /// Lists the outputs' widths in their current wire order, since widths pair with names by
+    /// wire order.
+    pub(crate) fn set_output_widths(&mut self, widths: &HashMap<String, usize>) {
+        self.io_widths.1 = self
+            .info
+            .outputs()
+            .into_iter()
+            .map(|(name, _)| widths[name])
+            .collect();
+    }
+}
+
+/// The Bristol text, as [`BristolCircuit::write_bristol`] writes it, without the info. A header
+/// style that can't describe the circuit, like `Legacy` without exactly two inputs and one
+/// output, falls back to `NivList`, so formatting never fails.
+impl Display for BristolCircuit {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        let text = self.get_bristol_string().unwrap_or_else(|_| {
+            let niv_list = BristolCircuit {
+                header_style: HeaderStyle::NivList,
+                ..self.clone()
+            };
+
+            niv_list
+                .get_bristol_string()
+                .expect("a NivList header describes any circuit")
+        });
+
+        f.write_str(&text)
     }
 }
 
+/// Parses Bristol text that came without info, as [`BristolCircuit::from_bristol_string`] does.
+impl FromStr for BristolCircuit {
+    type Err = BristolCircuitError;
+
+    fn from_str(s: &str) -> Result<BristolCircuit, BristolCircuitError> {
+        BristolCircuit::from_bristol_string(s)
+    }
+}
+
+pub(crate) struct IoBus<'a> {
+    pub name: &'a str,
+    pub wire: usize,
+    pub width: usize,
+}
+
+/// Pairs names with widths: widths are listed in the order of the names' wire indices.
+fn io_buses<'a>(names: Vec<(&'a str, usize)>, widths: &[usize]) -> Vec<IoBus<'a>> {
+    names
+        .into_iter()
+        .enumerate()
+        .map(|(i, (name, wire))| IoBus {
+            name,
+            wire,
+            width: widths.get(i).copied().unwrap_or(1),
+        })
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
+    use smallvec::smallvec;
+
     use super::*;
+    use crate::{
+        bristol_line::BristolLine, circuit_builder::CircuitBuilder, parse_options::GateCountPolicy,
+    };
     use std::io::{BufReader, Cursor};
 
     // Helper function to create a sample BristolCircuit
@@ -133,20 +506,24 @@ mod tests {
                     .collect(),
                 constants: Default::default(),
                 output_name_to_wire_index: [("output0".to_string(), 3)].iter().cloned().collect(),
+                wire_groups: Default::default(),
+                input_owners: Default::default(),
+                output_visibility: Default::default(),
             },
             io_widths: (vec![1, 1], vec![1]),
             gates: vec![
                 Gate {
-                    inputs: vec![0, 1],
-                    outputs: vec![2],
-                    op: "AAdd".to_string(),
+                    inputs: smallvec![0, 1],
+                    outputs: smallvec![2],
+                    op: "AAdd".into(),
                 },
                 Gate {
-                    inputs: vec![2, 1],
-                    outputs: vec![3],
-                    op: "AMul".to_string(),
+                    inputs: smallvec![2, 1],
+                    outputs: smallvec![3],
+                    op: "AMul".into(),
                 },
             ],
+            header_style: HeaderStyle::NivList,
         }
     }
 
@@ -160,6 +537,18 @@ mod tests {
             + "\n"
     }
 
+    #[test]
+    fn test_builder_matches_sample() {
+        let mut b = CircuitBuilder::new();
+        let a = b.input("input0");
+        let x = b.input("input1");
+        let sum = b.gate("AAdd", &[a, x]);
+        let d = b.gate("AMul", &[sum, x]);
+        b.output("output0", d);
+
+        assert_eq!(b.build().unwrap(), create_sample_circuit());
+    }
+
     #[test]
     fn test_write_bristol() {
         assert_eq!(
@@ -194,6 +583,9 @@ mod tests {
                         .iter()
                         .cloned()
                         .collect(),
+                    wire_groups: Default::default(),
+                    input_owners: Default::default(),
+                    output_visibility: Default::default(),
                 },
                 "
                     2 4
@@ -209,18 +601,793 @@ mod tests {
         );
     }
 
+    const GATES: &str = "2 1 0 1 2 AAdd\n2 1 2 1 3 AMul\n";
+
+    #[test]
+    fn test_header_style_round_trips() {
+        let info = create_sample_circuit().info;
+
+        for (style, header) in [
+            (HeaderStyle::NivList, "2 4\n2 1 1\n1 1\n\n"),
+            (HeaderStyle::Legacy, "2 4\n1 1 1\n\n"),
+            (HeaderStyle::PerPartyLines, "2 4\n2\n1\n1\n1\n1\n\n"),
+        ] {
+            let fixture = format!("{}{}", header, GATES);
+
+            let detected = BristolCircuit::from_info_and_bristol_string(&info, &fixture).unwrap();
+            assert_eq!(detected.header_style, style);
+            assert_eq!(detected.io_widths, (vec![1, 1], vec![1]));
+            assert_eq!(detected.gates, create_sample_circuit().gates);
+            assert_eq!(detected.get_bristol_string().unwrap(), fixture);
+
+            let explicit = BristolCircuit::from_info_and_bristol_string_with_options(
+                &info,
+                &fixture,
+                &ParseOptions {
+                    header_style: Some(style),
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+            assert_eq!(explicit, detected);
+        }
+    }
+
+    #[test]
+    fn test_display_and_from_str() {
+        let circuit = crate::gadgets::adder(4, true);
+        let text = circuit.to_string();
+        assert_eq!(text, circuit.get_bristol_string().unwrap());
+
+        let parsed = text.parse::<BristolCircuit>().unwrap();
+        assert_eq!(parsed.gates, circuit.gates);
+        assert_eq!(parsed.io_widths, circuit.io_widths);
+        assert_eq!(parsed.wire_count, circuit.wire_count);
+        assert_eq!(parsed.to_string(), text);
+
+        // The generated names follow the Bristol layout of the header.
+        let inputs = &parsed.info.input_name_to_wire_index;
+        assert_eq!((inputs["input0"], inputs["input1"]), (0, 4));
+        let outputs = &parsed.info.output_name_to_wire_index;
+        let first_output = circuit.wire_count - 5;
+        assert_eq!(
+            (outputs["output0"], outputs["output1"]),
+            (first_output, first_output + 4)
+        );
+
+        for header in ["2 4\n1 1 1\n\n", "2 4\n2\n1\n1\n1\n1\n\n"] {
+            let fixture = format!("{}{}", header, GATES);
+            let parsed = fixture.parse::<BristolCircuit>().unwrap();
+            assert_eq!(parsed.gates, create_sample_circuit().gates);
+            assert_eq!(parsed.to_string(), fixture);
+        }
+
+        assert!("2 4\n2 1 1\n".parse::<BristolCircuit>().is_err());
+
+        // A legacy header can't describe one input, so the text falls back to NivList.
+        let mut unwritable = crate::gadgets::adder(1, false);
+        unwritable.header_style = HeaderStyle::Legacy;
+        unwritable.io_widths.0 = vec![2];
+        unwritable
+            .info
+            .input_name_to_wire_index
+            .retain(|name, _| name == "a");
+        assert!(unwritable.get_bristol_string().is_err());
+        assert!(unwritable.to_string().starts_with(&format!(
+            "{} {}\n1 2\n1 1\n\n",
+            unwritable.gates.len(),
+            unwritable.wire_count
+        )));
+    }
+
+    #[test]
+    fn test_header_style_lookahead() {
+        let info = create_sample_circuit().info;
+
+        // `2 1 1` is a valid NivList inputs line, but the next line is a gate, not outputs.
+        let circuit =
+            BristolCircuit::from_info_and_bristol_string(&info, &format!("2 4\n2 1 1\n{}", GATES))
+                .unwrap();
+        assert_eq!(circuit.header_style, HeaderStyle::Legacy);
+        assert_eq!(circuit.io_widths, (vec![2, 1], vec![1]));
+    }
+
+    #[test]
+    fn test_header_style_ambiguous() {
+        let err = BristolCircuit::from_info_and_bristol_string(
+            &create_sample_circuit().info,
+            "2 4\n2 1 1\nfoo bar\n",
+        )
+        .unwrap_err();
+
+        assert_eq!(
+            err.to_string(),
+            "Parsing error: Ambiguous io widths line 2 \"2 1 1\", could be: \
+             NivList (input widths [1, 1], followed by an output widths line); \
+             Legacy (input widths [2, 1], output widths [1])"
+        );
+    }
+
+    #[test]
+    fn test_comments() {
+        let info = create_sample_circuit().info;
+        let plain = format!("2 4\n2 1 1\n1 1\n\n{}", GATES);
+        let commented = "# generated by some tool\n\
+                         // another style\n\
+                         2 4 # sizes\n\
+                         2 1 1\n\
+                         1 1\n\
+                         \n\
+                         \t# the sum\n\
+                         2 1 0 1 2 AAdd # sum\n\
+                         2 1 2 1 3 AMul// product\n\
+                         # trailing comment\n";
+
+        let expected = BristolCircuit::from_info_and_bristol_string(&info, &plain).unwrap();
+
+        for streaming_threshold in [ParseOptions::DEFAULT_STREAMING_THRESHOLD, 3, 8] {
+            let options = ParseOptions {
+                streaming_threshold,
+                ..Default::default()
+            };
+
+            assert_eq!(
+                BristolCircuit::from_info_and_bristol_string_with_options(
+                    &info, commented, &options
+                )
+                .unwrap(),
+                expected,
+                "streaming threshold {}",
+                streaming_threshold
+            );
+        }
+    }
+
+    #[test]
+    fn test_write_header_comment() {
+        let circuit = create_sample_circuit();
+        let options = WriteOptions {
+            header_comment: Some("d = (a + b) * b\n\nsecond paragraph".to_string()),
+        };
+
+        let mut text = Vec::new();
+        circuit
+            .write_bristol_with_options(&mut text, &options)
+            .unwrap();
+        let text = String::from_utf8(text).unwrap();
+
+        assert!(
+            text.starts_with("# d = (a + b) * b\n#\n# second paragraph\n2 4\n"),
+            "{}",
+            text
+        );
+        assert_eq!(
+            BristolCircuit::from_info_and_bristol_string(&circuit.info, &text).unwrap(),
+            circuit
+        );
+    }
+
+    #[test]
+    fn test_error_line_number() {
+        // The header takes lines 1-3, then a blank line, so gate n is on line n + 4, except that
+        // another blank line before gate 10 pushes the later gates down by one.
+        let mut text = "20 22\n2 1 1\n1 1\n\n".to_string();
+
+        for i in 0..20 {
+            if i == 9 {
+                text.push('\n');
+            }
+
+            let input = if i == 16 {
+                "x".to_string()
+            } else {
+                i.to_string()
+            };
+            text.push_str(&format!("2 1 {} {} {} AAdd\n", input, i + 1, i + 2));
+        }
+
+        let info = create_sample_circuit().info;
+
+        let err = BristolCircuit::from_info_and_bristol_string(&info, &text).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Parsing error: Failed to convert at index 2 in line 22 \"2 1 x 17 18 AAdd\""
+        );
+
+        let streamed = ParseOptions {
+            streaming_threshold: 4,
+            ..Default::default()
+        };
+        let err =
+            BristolCircuit::from_info_and_bristol_string_with_options(&info, &text, &streamed)
+                .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Parsing error: Failed to convert gate input \"x\" in line 22"
+        );
+    }
+
+    #[test]
+    fn test_header_style_override() {
+        let err = BristolCircuit::from_info_and_bristol_string_with_options(
+            &create_sample_circuit().info,
+            &format!("2 4\n2 1 1\n1 1\n{}", GATES),
+            &ParseOptions {
+                header_style: Some(HeaderStyle::Legacy),
+                ..Default::default()
+            },
+        )
+        .unwrap_err();
+
+        assert!(err
+            .to_string()
+            .contains("Inconsistent part length for gate"));
+    }
+
+    fn parse_with(
+        src: &str,
+        options: &ParseOptions,
+    ) -> Result<(BristolCircuit, Vec<ParseWarning>), BristolCircuitError> {
+        BristolCircuit::read_info_and_bristol_with_warnings(
+            &create_sample_circuit().info,
+            &mut BufReader::new(src.as_bytes()),
+            options,
+        )
+    }
+
+    fn lenient() -> ParseOptions {
+        ParseOptions {
+            strict: false,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_under_declared_wire_count() {
+        let src = format!("2 3\n2 1 1\n1 1\n{}", GATES);
+
+        let err = parse_with(&src, &ParseOptions::default()).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Inconsistency: Header declares 3 wires but 4 are referenced"
+        );
+
+        let (circuit, warnings) = parse_with(&src, &lenient()).unwrap();
+        assert_eq!(circuit, create_sample_circuit());
+        assert_eq!(
+            warnings,
+            vec![ParseWarning::WireCountMismatch {
+                declared: 3,
+                actual: 4
+            }]
+        );
+    }
+
+    #[test]
+    fn test_default_entry_points_are_strict() {
+        let info = create_sample_circuit().info;
+        let wires = format!("2 3\n2 1 1\n1 1\n{}", GATES);
+        let gates = format!("3 4\n2 1 1\n1 1\n{}", GATES);
+
+        for (src, message) in [
+            (
+                &wires,
+                "Inconsistency: Header declares 3 wires but 4 are referenced",
+            ),
+            (
+                &gates,
+                "Inconsistency: Header declares 3 gates but 2 were found",
+            ),
+        ] {
+            let errors = [
+                BristolCircuit::read_info_and_bristol(&info, &mut src.as_bytes()).unwrap_err(),
+                BristolCircuit::from_info_and_bristol_string(&info, src).unwrap_err(),
+                BristolCircuit::read_info_and_bristol_with_limits(
+                    &info,
+                    &mut src.as_bytes(),
+                    &ParseLimits::default(),
+                )
+                .unwrap_err(),
+                BristolCircuit::from_bristol_string(src).unwrap_err(),
+            ];
+
+            for err in errors {
+                assert_eq!(err.to_string(), message);
+            }
+
+            let circuit =
+                BristolCircuit::from_info_and_bristol_string_with_options(&info, src, &lenient())
+                    .unwrap();
+            assert_eq!(circuit, create_sample_circuit());
+        }
+    }
+
+    #[test]
+    fn test_over_declared_wire_count() {
+        let src = format!("2 7\n2 1 1\n1 1\n{}", GATES);
+
+        let (circuit, warnings) = parse_with(&src, &ParseOptions::default()).unwrap();
+        assert_eq!(circuit.wire_count, 7);
+        assert!(warnings.is_empty());
+
+        let exact = ParseOptions {
+            exact_wire_count: true,
+            ..Default::default()
+        };
+
+        let err = parse_with(&src, &exact).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Inconsistency: Header declares 7 wires but 4 are referenced"
+        );
+
+        let (circuit, warnings) = parse_with(
+            &src,
+            &ParseOptions {
+                strict: false,
+                ..exact
+            },
+        )
+        .unwrap();
+        assert_eq!(circuit, create_sample_circuit());
+        assert_eq!(
+            warnings,
+            vec![ParseWarning::WireCountMismatch {
+                declared: 7,
+                actual: 4
+            }]
+        );
+    }
+
+    #[test]
+    fn test_over_declared_gate_count() {
+        let src = format!("3 4\n2 1 1\n1 1\n{}", GATES);
+
+        let err = parse_with(&src, &ParseOptions::default()).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Inconsistency: Header declares 3 gates but 2 were found"
+        );
+
+        let (circuit, warnings) = parse_with(&src, &lenient()).unwrap();
+        assert_eq!(circuit.gates, create_sample_circuit().gates);
+        assert_eq!(
+            warnings,
+            vec![ParseWarning::GateCountMismatch {
+                declared: 3,
+                actual: 2
+            }]
+        );
+    }
+
+    #[test]
+    fn test_under_declared_gate_count() {
+        let src = format!("1 4\n2 1 1\n1 1\n{}", GATES);
+        let no_trailing_check = ParseOptions {
+            check_trailing: false,
+            ..Default::default()
+        };
+
+        let err = parse_with(&src, &no_trailing_check).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Inconsistency: Header declares 1 gates but 2 were found"
+        );
+
+        let (circuit, warnings) = parse_with(
+            &src,
+            &ParseOptions {
+                strict: false,
+                ..no_trailing_check
+            },
+        )
+        .unwrap();
+        assert_eq!(circuit.gates, create_sample_circuit().gates);
+        assert_eq!(
+            warnings,
+            vec![ParseWarning::GateCountMismatch {
+                declared: 1,
+                actual: 2
+            }]
+        );
+    }
+
+    #[test]
+    fn test_gate_list_on_one_line() {
+        let src = "2 4\n2 1 1\n1 1\n2 1 0 1 2 AAdd 2 1 2 1 3 AMul\n";
+
+        for streaming_threshold in [4, ParseOptions::DEFAULT_STREAMING_THRESHOLD] {
+            let options = ParseOptions {
+                streaming_threshold,
+                ..Default::default()
+            };
+            let (circuit, _) = parse_with(src, &options).unwrap();
+            assert_eq!(circuit, create_sample_circuit());
+
+            let err = parse_with(&src.replacen("2 4", "1 4", 1), &options).unwrap_err();
+            assert_eq!(
+                err.to_string(),
+                "Inconsistency: Header declares 1 gates but 2 were found"
+            );
+        }
+    }
+
+    #[test]
+    fn test_trailing_check_disabled() {
+        let src = format!("2 4\n2 1 1\n1 1\n{}not a gate\n", GATES);
+
+        assert!(parse_with(&src, &ParseOptions::default()).is_err());
+
+        let (circuit, warnings) = parse_with(
+            &src,
+            &ParseOptions {
+                check_trailing: false,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        assert_eq!(circuit, create_sample_circuit());
+        assert!(warnings.is_empty());
+    }
+
+    #[test]
+    fn test_read_until_eof_placeholder_count() {
+        let options = ParseOptions {
+            gate_count: GateCountPolicy::ReadUntilEof,
+            ..Default::default()
+        };
+
+        let (circuit, warnings) =
+            parse_with(&format!("0 4\n2 1 1\n1 1\n{}\n", GATES), &options).unwrap();
+        assert_eq!(circuit, create_sample_circuit());
+        assert!(warnings.is_empty());
+        assert!(circuit.get_bristol_string().unwrap().starts_with("2 4\n"));
+    }
+
+    #[test]
+    fn test_read_until_terminator() {
+        let options = ParseOptions {
+            gate_count: GateCountPolicy::ReadUntilEof,
+            ..Default::default()
+        };
+
+        let src = format!("0 4\n2 1 1\n1 1\n{}.end\nanything goes here\n", GATES);
+        let (circuit, _) = parse_with(&src, &options).unwrap();
+        assert_eq!(circuit, create_sample_circuit());
+
+        let err = parse_with(&src, &ParseOptions::default()).unwrap_err();
+        assert!(err.to_string().contains("after gates"));
+    }
+
+    #[test]
+    fn test_exact_gate_count() {
+        let options = ParseOptions {
+            gate_count: GateCountPolicy::Exact(2),
+            ..Default::default()
+        };
+
+        let (circuit, _) = parse_with(&format!("9 4\n2 1 1\n1 1\n{}", GATES), &options).unwrap();
+        assert_eq!(circuit, create_sample_circuit());
+
+        let options = ParseOptions {
+            gate_count: GateCountPolicy::Exact(1),
+            ..Default::default()
+        };
+
+        let err = parse_with(&format!("2 4\n2 1 1\n1 1\n{}", GATES), &options).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Inconsistency: Gate count policy expects 1 gates but 2 were found (the header \
+             declares 2)"
+        );
+
+        let options = ParseOptions {
+            gate_count: GateCountPolicy::Exact(5),
+            limits: ParseLimits {
+                max_gates: Some(3),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        let err = parse_with(&format!("2 4\n2 1 1\n1 1\n{}", GATES), &options).unwrap_err();
+        assert!(
+            err.to_string()
+                .contains("Gate count policy expects 5 gates"),
+            "{}",
+            err
+        );
+    }
+
+    #[test]
+    fn test_recompute_wire_count() {
+        let mut circuit = create_sample_circuit();
+        circuit.wire_count = 100;
+
+        assert_eq!(circuit.recompute_wire_count(), 4);
+        assert_eq!(circuit.wire_count, 4);
+        circuit.assert_wire_count_tight();
+    }
+
+    #[test]
+    fn test_recompute_wire_count_empty() {
+        let mut circuit = BristolCircuit {
+            wire_count: 3,
+            info: CircuitInfo::default(),
+            io_widths: (vec![], vec![]),
+            gates: vec![],
+            header_style: HeaderStyle::NivList,
+        };
+
+        assert_eq!(circuit.recompute_wire_count(), 0);
+        circuit.assert_wire_count_tight();
+    }
+
+    #[test]
+    fn test_recompute_wire_count_info_only_wire() {
+        let mut circuit = create_sample_circuit();
+        circuit
+            .info
+            .output_name_to_wire_index
+            .insert("output0".to_string(), 9);
+
+        assert_eq!(circuit.recompute_wire_count(), 10);
+    }
+
+    #[test]
+    #[should_panic(expected = "wire_count is 5 but 4 wires are referenced")]
+    fn test_assert_wire_count_tight() {
+        let mut circuit = create_sample_circuit();
+        circuit.wire_count = 5;
+        circuit.assert_wire_count_tight();
+    }
+
+    fn with_invalid_byte(src: &str, marker: &str) -> Vec<u8> {
+        let position = src.find(marker).unwrap();
+        let mut bytes = src.as_bytes().to_vec();
+        bytes[position] = 0xff;
+        bytes
+    }
+
+    #[test]
+    fn test_bytes_strict_reports_offset() {
+        let src = format!("2 4\n2 1 1\n1 1\n{}", GATES);
+        let bytes = with_invalid_byte(&src, "AMul");
+
+        let err = BristolCircuit::from_info_and_bristol_bytes(
+            &create_sample_circuit().info,
+            &bytes,
+            Utf8Policy::Strict,
+        )
+        .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            format!("Invalid UTF-8 at byte offset {}", src.find("AMul").unwrap())
+        );
+
+        assert_eq!(
+            BristolCircuit::from_info_and_bristol_bytes(
+                &create_sample_circuit().info,
+                src.as_bytes(),
+                Utf8Policy::Strict
+            )
+            .unwrap(),
+            create_sample_circuit()
+        );
+    }
+
+    #[test]
+    fn test_bytes_lossy_rejects_corrupt_tokens() {
+        let src = format!("2 4\n2 1 1\n1 1\n{}", GATES);
+
+        for marker in ["AMul", "3 AMul", "4\n"] {
+            let err = BristolCircuit::from_info_and_bristol_bytes(
+                &create_sample_circuit().info,
+                &with_invalid_byte(&src, marker),
+                Utf8Policy::Lossy,
+            )
+            .unwrap_err();
+            assert!(err.to_string().contains("Invalid UTF-8"), "{}", err);
+        }
+    }
+
+    #[test]
+    fn test_bytes_lossy_tolerates_ignored_content() {
+        let src = format!("2 4\n2 1 1\n1 1\n{}trailing note: café\n", GATES);
+        let bytes = with_invalid_byte(&src, "é");
+
+        let options = ParseOptions {
+            check_trailing: false,
+            utf8: Utf8Policy::Lossy,
+            ..Default::default()
+        };
+
+        assert_eq!(
+            BristolCircuit::from_info_and_bristol_bytes_with_options(
+                &create_sample_circuit().info,
+                &bytes,
+                &options
+            )
+            .unwrap(),
+            create_sample_circuit()
+        );
+
+        assert!(BristolCircuit::from_info_and_bristol_bytes_with_options(
+            &create_sample_circuit().info,
+            &bytes,
+            &ParseOptions {
+                utf8: Utf8Policy::Strict,
+                ..options
+            }
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_parse_bytes() {
+        let info = create_sample_circuit().info;
+        let src = format!("2 4\n2 1 1\n1 1\n{}", GATES);
+        assert_eq!(
+            BristolCircuit::parse_bytes(&info, src.as_bytes()).unwrap(),
+            create_sample_circuit()
+        );
+
+        // Inputs that used to panic on overflow, or allocate by the declared wire count.
+        let max = usize::MAX;
+        for (src, message) in [
+            (
+                format!("2 4\n2 1 1\n1 1\n\n{} {} 0 1 2 AAdd\n", max, max),
+                "Inconsistent part length for gate",
+            ),
+            (
+                format!("2 4\n2 1 1\n1 1\n\n2 1 0 1 {} AAdd\n2 1 2 1 3 AMul\n", max),
+                "Gates reference",
+            ),
+            (
+                format!("2 4\n2 1 1\n1 {}\n{}", max, GATES),
+                "Gates reference",
+            ),
+            (format!("2 {}\n2 1 1\n1 1\n{}", max, GATES), "(max_wires is"),
+            (
+                format!("2 1000\n2 1 1\n1 1\n{}", GATES),
+                "more than the input's",
+            ),
+            (
+                format!("2 4\n{} 1 1\n1 1\n{}", max, GATES),
+                "Inconsistent part length",
+            ),
+        ] {
+            let err = BristolCircuit::parse_bytes(&info, src.as_bytes()).unwrap_err();
+            assert!(err.to_string().contains(message), "{}", err);
+        }
+
+        let err = BristolCircuit::parse_bytes(&info, &with_invalid_byte(&src, "AMul")).unwrap_err();
+        assert!(err.to_string().contains("Invalid UTF-8"), "{}", err);
+    }
+
+    #[test]
+    fn test_read_with_limits() {
+        let info = create_sample_circuit().info;
+        let read = |src: &str, limits: &ParseLimits| {
+            BristolCircuit::read_info_and_bristol_with_limits(&info, &mut src.as_bytes(), limits)
+        };
+        let src = format!("2 4\n2 1 1\n1 1\n{}", GATES);
+
+        let exact = ParseLimits {
+            max_gates: Some(2),
+            max_wires: Some(4),
+            max_line_bytes: Some(14),
+            max_io: Some(3),
+        };
+        assert_eq!(read(&src, &exact).unwrap(), create_sample_circuit());
+
+        let huge = format!("999999999999 999999999999\n2 1 1\n1 1\n{}", GATES);
+        for (limits, src, message) in [
+            (
+                ParseLimits::default(),
+                huge.as_str(),
+                "Limit exceeded: Header declares 999999999999 wires (max_wires is 1073741824)",
+            ),
+            (
+                ParseLimits {
+                    max_gates: Some(1),
+                    ..exact
+                },
+                &src,
+                "Limit exceeded: Header declares 2 gates (max_gates is 1)",
+            ),
+            (
+                ParseLimits {
+                    max_wires: Some(3),
+                    ..exact
+                },
+                &src,
+                "Limit exceeded: Header declares 4 wires (max_wires is 3)",
+            ),
+            (
+                ParseLimits {
+                    max_line_bytes: Some(13),
+                    ..exact
+                },
+                &src,
+                "Limit exceeded: Line 4 is too long (max_line_bytes is 13)",
+            ),
+            (
+                ParseLimits {
+                    max_io: Some(2),
+                    ..exact
+                },
+                &src,
+                "Limit exceeded: Header declares 3 inputs and outputs (max_io is 2)",
+            ),
+        ] {
+            let err = read(src, &limits).unwrap_err();
+            assert!(matches!(err, BristolCircuitError::LimitExceeded { .. }));
+            assert_eq!(err.to_string(), message);
+        }
+
+        // Undeclared gates and wires are counted as they're read.
+        let options = ParseOptions {
+            gate_count: GateCountPolicy::ReadUntilEof,
+            limits: ParseLimits {
+                max_gates: Some(1),
+                ..ParseLimits::default()
+            },
+            ..Default::default()
+        };
+        let err = parse_with(&src, &options).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Limit exceeded: Input has more than 1 gates (max_gates is 1)"
+        );
+
+        let lenient = ParseOptions {
+            strict: false,
+            limits: ParseLimits {
+                max_gates: Some(3),
+                ..exact
+            },
+            ..Default::default()
+        };
+        let err = parse_with(
+            &format!("3 4\n2 1 1\n1 1\n{}1 1 3 4 ANeg\n", GATES),
+            &lenient,
+        )
+        .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Limit exceeded: Gates reference 5 wires (max_wires is 4)"
+        );
+
+        assert!(read(&src, &ParseLimits::unlimited()).is_ok());
+    }
+
+    #[test]
+    fn test_read_bristol_truncated() {
+        let err = BristolCircuit::from_info_and_bristol_string(
+            &create_sample_circuit().info,
+            "2 4\n2 1 1\n1 1\n\n2 1 0 1 2 AAdd\n",
+        )
+        .unwrap_err();
+
+        assert_eq!(
+            err.to_string(),
+            "Inconsistency: Header declares 2 gates but 1 were found"
+        );
+    }
+
     #[test]
     fn test_bristol_line_read() {
         let input_data = "2 4\n";
         let mut reader = BufReader::new(Cursor::new(input_data));
 
         let bristol_line = BristolLine::read(&mut reader).unwrap();
-        assert_eq!(bristol_line.0, vec!["2", "4"]);
+        assert_eq!(bristol_line.tokens, vec!["2", "4"]);
     }
 
     #[test]
     fn test_bristol_line_circuit_sizes() {
-        let bristol_line = BristolLine(vec!["2".to_string(), "4".to_string()]);
+        let bristol_line = BristolLine::new(vec!["2".to_string(), "4".to_string()]);
         let (gate_count, wire_count) = bristol_line.circuit_sizes().unwrap();
         assert_eq!(gate_count, 2);
         assert_eq!(wire_count, 4);
@@ -228,14 +1395,15 @@ mod tests {
 
     #[test]
     fn test_bristol_line_io_count() {
-        let bristol_line = BristolLine(vec!["2".to_string(), "1".to_string(), "1".to_string()]);
+        let bristol_line =
+            BristolLine::new(vec!["2".to_string(), "1".to_string(), "1".to_string()]);
         let io_widths = bristol_line.io_widths().unwrap();
         assert_eq!(io_widths, vec![1, 1]);
     }
 
     #[test]
     fn test_bristol_line_gate() {
-        let bristol_line = BristolLine(vec![
+        let bristol_line = BristolLine::new(vec![
             "2".to_string(),
             "1".to_string(),
             "0".to_string(),
@@ -244,21 +1412,21 @@ mod tests {
             "AAdd".to_string(),
         ]);
         let gate = bristol_line.gate().unwrap();
-        assert_eq!(gate.inputs, vec![0, 1]);
-        assert_eq!(gate.outputs, vec![2]);
+        assert_eq!(gate.inputs[..], [0, 1]);
+        assert_eq!(gate.outputs[..], [2]);
         assert_eq!(gate.op, "AAdd");
     }
 
     #[test]
     fn test_bristol_line_get() {
-        let bristol_line = BristolLine(vec!["2".to_string(), "4".to_string()]);
+        let bristol_line = BristolLine::new(vec!["2".to_string(), "4".to_string()]);
         let value: usize = bristol_line.get(0).unwrap();
         assert_eq!(value, 2);
     }
 
     #[test]
     fn test_bristol_line_get_str() {
-        let bristol_line = BristolLine(vec!["2".to_string(), "4".to_string()]);
+        let bristol_line = BristolLine::new(vec!["2".to_string(), "4".to_string()]);
         let value = bristol_line.get_str(1).unwrap();
         assert_eq!(value, "4");
     }
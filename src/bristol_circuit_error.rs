@@ -1,3 +1,5 @@
+use std::path::{Path, PathBuf};
+
 use thiserror::Error;
 
 #[derive(Error, Debug)]
@@ -8,6 +10,47 @@ pub enum BristolCircuitError {
     ParseIntError(#[from] std::num::ParseIntError),
     #[error(transparent)]
     IOError(#[from] std::io::Error),
+    #[error("Invalid UTF-8 at byte offset {offset}")]
+    InvalidUtf8 { offset: usize },
     #[error("Inconsistency: {message}")]
     Inconsistency { message: String },
+    #[error("Invalid {format} circuit info: {message}")]
+    InfoFormatError {
+        format: &'static str,
+        message: String,
+    },
+    /// The input declared or contained more than the [`ParseLimits`](crate::ParseLimits) field
+    /// named `limit` allows.
+    #[error("Limit exceeded: {message} ({limit} is {max})")]
+    LimitExceeded {
+        limit: &'static str,
+        max: usize,
+        message: String,
+    },
+    /// A document was written in a version of `format` this build can't read.
+    #[error("Unsupported {format} version {version} (this build reads up to version {supported})")]
+    UnsupportedVersion {
+        format: &'static str,
+        version: u32,
+        supported: u32,
+    },
+    #[error("Evaluation error: {message}")]
+    EvaluationError { message: String },
+    /// Reading or writing `path` failed.
+    #[error("{}: {source}", path.display())]
+    FileError {
+        path: PathBuf,
+        source: Box<BristolCircuitError>,
+    },
+}
+
+/// Runs `f`, attributing any error to the file at `path`.
+pub(crate) fn with_path<T>(
+    path: &Path,
+    f: impl FnOnce() -> Result<T, BristolCircuitError>,
+) -> Result<T, BristolCircuitError> {
+    f().map_err(|source| BristolCircuitError::FileError {
+        path: path.to_path_buf(),
+        source: Box::new(source),
+    })
 }
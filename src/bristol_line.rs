@@ -1,107 +1,290 @@
-use std::{io::BufRead, str::FromStr};
+use std::str::FromStr;
 
-use crate::{bristol_circuit_error::BristolCircuitError, gate::Gate};
+use crate::{
+    bristol_circuit_error::BristolCircuitError,
+    gate::{validate_gate_shape, Gate},
+};
 
-pub struct BristolLine(pub Vec<String>);
+#[cfg(test)]
+use crate::{line_reader::LineReader, parse_options::ParseOptions};
+#[cfg(test)]
+use std::io::BufRead;
 
-impl BristolLine {
-    pub fn read(r: &mut impl BufRead) -> Result<Self, BristolCircuitError> {
-        loop {
-            let mut line = String::new();
-            r.read_line(&mut line)?;
-
-            let line = line.trim();
+#[derive(Debug)]
+pub struct BristolLine {
+    pub tokens: Vec<String>,
 
-            if line.is_empty() {
-                continue;
-            }
+    /// The 1-based line this was read from, which errors mention.
+    pub line_number: Option<usize>,
+}
 
-            return Ok(BristolLine(
-                line.split_whitespace()
-                    .map(|part| part.to_string())
-                    .collect(),
-            ));
+impl BristolLine {
+    pub fn new(tokens: Vec<String>) -> Self {
+        BristolLine {
+            tokens,
+            line_number: None,
         }
     }
 
+    #[cfg(test)]
+    pub fn read(r: &mut impl BufRead) -> Result<Self, BristolCircuitError> {
+        LineReader::new(r, &ParseOptions::default()).expect_line("line")
+    }
+
     pub fn circuit_sizes(&self) -> Result<(usize, usize), BristolCircuitError> {
+        self.expect_len(2, "circuit sizes")?;
+
         Ok((self.get(0)?, self.get(1)?))
     }
 
     pub fn io_widths(&self) -> Result<Vec<usize>, BristolCircuitError> {
         let count = self.get::<usize>(0)?;
+        self.expect_len(count.saturating_add(1), "io widths")?;
 
-        if self.0.len() != (count + 1) {
-            return Err(BristolCircuitError::ParsingError {
-                message: format!("Expected {} parts", count + 1),
-            });
-        }
+        self.get_range(1, count)
+    }
+
+    pub fn gate(&self) -> Result<Gate, BristolCircuitError> {
+        self.expect_len(self.gate_len()?, "gate")?;
+        self.leading_gate()
+    }
 
-        let mut res = Vec::<usize>::new();
+    /// The gate the line starts with, ignoring any tokens after it, which may be more gates.
+    pub fn leading_gate(&self) -> Result<Gate, BristolCircuitError> {
+        let len = self.gate_len()?;
 
-        for i in 1..self.0.len() {
-            res.push(self.get(i)?);
+        if self.len() < len {
+            self.expect_len(len, "gate")?;
         }
 
-        Ok(res)
+        let input_len = self.get::<usize>(0)?;
+        let output_len = self.get::<usize>(1)?;
+        let inputs = self.get_range(2, input_len)?;
+        let outputs = self.get_range(2 + input_len, output_len)?;
+        let op = self.get::<String>(input_len + output_len + 2)?;
+
+        validate_gate_shape(&op, input_len, output_len).map_err(|message| self.error(message))?;
+
+        Ok(Gate {
+            inputs,
+            outputs,
+            op: op.into(),
+        })
     }
 
-    pub fn gate(&self) -> Result<Gate, BristolCircuitError> {
+    /// The number of tokens in the gate the line starts with.
+    pub fn gate_len(&self) -> Result<usize, BristolCircuitError> {
         let input_len = self.get::<usize>(0)?;
         let output_len = self.get::<usize>(1)?;
 
-        let expected_part_len = input_len + output_len + 3;
+        // Saturating, so absurd counts fail the length check instead of overflowing.
+        Ok(input_len.saturating_add(output_len).saturating_add(3))
+    }
+
+    pub fn len(&self) -> usize {
+        self.tokens.len()
+    }
 
-        if self.0.len() != expected_part_len {
-            return Err(BristolCircuitError::ParsingError {
-                message: format!(
-                    "Inconsistent part length (actual: {}, expected: {})",
-                    self.0.len(),
-                    expected_part_len
-                ),
-            });
+    pub fn expect_len(&self, n: usize, context: &str) -> Result<(), BristolCircuitError> {
+        if self.len() != n {
+            return Err(self.error(format!(
+                "Inconsistent part length for {} (actual: {}, expected: {})",
+                context,
+                self.len(),
+                n
+            )));
         }
 
-        let mut inputs = Vec::<usize>::new();
+        Ok(())
+    }
+
+    pub fn get<T: FromStr>(&self, index: usize) -> Result<T, BristolCircuitError> {
+        let token = self
+            .tokens
+            .get(index)
+            .ok_or_else(|| self.error(format!("Index {} out of bounds", index)))?;
 
-        for i in 0..input_len {
-            inputs.push(self.get(i + 2)?);
+        // Lossy UTF-8 decoding leaves U+FFFD where the input was corrupt.
+        if token.contains(char::REPLACEMENT_CHARACTER) {
+            return Err(self.error(format!("Invalid UTF-8 in token at index {}", index)));
         }
 
-        let mut outputs = Vec::<usize>::new();
+        token
+            .parse::<T>()
+            .map_err(|_| self.error(format!("Failed to convert at index {}", index)))
+    }
 
-        for i in 0..output_len {
-            outputs.push(self.get(i + 2 + input_len)?);
-        }
+    pub fn get_range<T: FromStr, C: FromIterator<T>>(
+        &self,
+        start: usize,
+        len: usize,
+    ) -> Result<C, BristolCircuitError> {
+        let end = start.saturating_add(len);
 
-        let op = self.get::<String>(input_len + output_len + 2)?;
+        if end > self.len() {
+            return Err(self.error(format!(
+                "Range {}..{} out of bounds (length: {})",
+                start,
+                end,
+                self.len()
+            )));
+        }
 
-        Ok(Gate {
-            inputs,
-            outputs,
-            op,
-        })
+        self.iter_from(start).take(len).collect()
     }
 
-    pub fn get<T: FromStr>(&self, index: usize) -> Result<T, BristolCircuitError> {
-        self.0
-            .get(index)
-            .ok_or(BristolCircuitError::ParsingError {
-                message: format!("Index {} out of bounds", index),
-            })?
-            .parse::<T>()
-            .map_err(|_| BristolCircuitError::ParsingError {
-                message: format!("Failed to convert at index {}", index),
-            })
+    pub fn iter_from<T: FromStr>(
+        &self,
+        start: usize,
+    ) -> impl Iterator<Item = Result<T, BristolCircuitError>> + '_ {
+        (start..self.len().max(start)).map(|i| self.get(i))
     }
 
     #[cfg(test)]
     pub fn get_str(&self, index: usize) -> Result<&str, BristolCircuitError> {
-        self.0
+        self.tokens
             .get(index)
-            .ok_or(BristolCircuitError::ParsingError {
-                message: format!("Index {} out of bounds", index),
-            })
+            .ok_or_else(|| self.error(format!("Index {} out of bounds", index)))
             .map(|s| s.as_str())
     }
+
+    /// `line 17 "2 1 0 1 2 AND"` for error messages, leaving out the number if it's unknown.
+    pub fn describe(&self) -> String {
+        let text = self.tokens.join(" ");
+
+        match self.line_number {
+            Some(n) => format!("line {} \"{}\"", n, text),
+            None => format!("line \"{}\"", text),
+        }
+    }
+
+    fn error(&self, message: String) -> BristolCircuitError {
+        BristolCircuitError::ParsingError {
+            message: format!("{} in {}", message, self.describe()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn line(src: &str) -> BristolLine {
+        BristolLine::new(src.split_whitespace().map(str::to_string).collect())
+    }
+
+    fn message(err: BristolCircuitError) -> String {
+        match err {
+            BristolCircuitError::ParsingError { message } => message,
+            other => panic!("Unexpected error: {}", other),
+        }
+    }
+
+    #[test]
+    fn test_len() {
+        assert_eq!(line("2 1 0 1 2 AAdd").len(), 6);
+        assert_eq!(line("").len(), 0);
+    }
+
+    #[test]
+    fn test_expect_len() {
+        assert!(line("2 4").expect_len(2, "circuit sizes").is_ok());
+
+        let err = message(line("2 4 5").expect_len(2, "circuit sizes").unwrap_err());
+        assert_eq!(
+            err,
+            "Inconsistent part length for circuit sizes (actual: 3, expected: 2) \
+             in line \"2 4 5\""
+        );
+    }
+
+    #[test]
+    fn test_get_range() {
+        let bristol_line = line("2 1 0 1 2 AAdd");
+        assert_eq!(
+            bristol_line.get_range::<usize, Vec<_>>(2, 3).unwrap(),
+            vec![0, 1, 2]
+        );
+        assert_eq!(
+            bristol_line.get_range::<usize, Vec<_>>(6, 0).unwrap(),
+            Vec::<usize>::new()
+        );
+    }
+
+    #[test]
+    fn test_get_range_out_of_bounds() {
+        let err = message(
+            line("2 1 0 1")
+                .get_range::<usize, Vec<_>>(2, 3)
+                .unwrap_err(),
+        );
+        assert_eq!(
+            err,
+            "Range 2..5 out of bounds (length: 4) in line \"2 1 0 1\""
+        );
+    }
+
+    #[test]
+    fn test_get_range_parse_failure() {
+        let err = message(
+            line("2 1 0 x 2 AAdd")
+                .get_range::<usize, Vec<_>>(2, 3)
+                .unwrap_err(),
+        );
+        assert_eq!(
+            err,
+            "Failed to convert at index 3 in line \"2 1 0 x 2 AAdd\""
+        );
+    }
+
+    #[test]
+    fn test_iter_from() {
+        let bristol_line = line("3 7 8 9");
+        let values = bristol_line
+            .iter_from::<usize>(1)
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(values, vec![7, 8, 9]);
+
+        assert_eq!(bristol_line.iter_from::<usize>(4).count(), 0);
+        assert_eq!(bristol_line.iter_from::<usize>(10).count(), 0);
+    }
+
+    #[test]
+    fn test_iter_from_parse_failure() {
+        let bristol_line = line("3 7 x 9");
+        let values = bristol_line.iter_from::<usize>(1).collect::<Vec<_>>();
+        assert_eq!(values.len(), 3);
+        assert!(values[0].is_ok());
+        assert_eq!(
+            message(values.into_iter().nth(1).unwrap().unwrap_err()),
+            "Failed to convert at index 2 in line \"3 7 x 9\""
+        );
+    }
+
+    #[test]
+    fn test_get_out_of_bounds() {
+        let err = message(line("2").get::<usize>(1).unwrap_err());
+        assert_eq!(err, "Index 1 out of bounds in line \"2\"");
+    }
+
+    #[test]
+    fn test_gate_error_includes_line() {
+        let err = message(line("2 1 0 1 AAdd").gate().unwrap_err());
+        assert_eq!(
+            err,
+            "Inconsistent part length for gate (actual: 5, expected: 6) \
+             in line \"2 1 0 1 AAdd\""
+        );
+    }
+
+    #[test]
+    fn test_io_widths_error_includes_line() {
+        let err = message(line("3 1 1").io_widths().unwrap_err());
+        assert_eq!(
+            err,
+            "Inconsistent part length for io widths (actual: 3, expected: 4) \
+             in line \"3 1 1\""
+        );
+    }
 }
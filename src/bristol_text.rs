@@ -0,0 +1,132 @@
+use std::borrow::{Borrow, Cow};
+
+use serde::{de, ser, Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::{bristol_circuit::BristolCircuit, circuit_info::CircuitInfo};
+
+/// Serializes a `BristolCircuit` as `{"bristol": "<text>", "info": {...}}`, its gates as Bristol
+/// text rather than a JSON array of gate objects, which is much smaller.
+///
+/// Deserializing parses and validates the text, reporting problems as serde errors.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BristolTextCircuit<T>(pub T);
+
+#[derive(Serialize)]
+struct TextCircuit<'a> {
+    bristol: String,
+    info: &'a CircuitInfo,
+}
+
+#[derive(Deserialize)]
+struct ParsedTextCircuit<'a> {
+    #[serde(borrow)]
+    bristol: Cow<'a, str>,
+    info: CircuitInfo,
+}
+
+impl<T: Borrow<BristolCircuit>> Serialize for BristolTextCircuit<T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let circuit = self.0.borrow();
+
+        TextCircuit {
+            bristol: circuit
+                .get_bristol_string()
+                .map_err(|e| ser::Error::custom(format!("Can't write Bristol text: {}", e)))?,
+            info: &circuit.info,
+        }
+        .serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for BristolTextCircuit<BristolCircuit> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let parsed = ParsedTextCircuit::deserialize(deserializer)?;
+
+        let circuit = BristolCircuit::from_info_and_bristol_string(&parsed.info, &parsed.bristol)
+            .map_err(|e| de::Error::custom(format!("Invalid Bristol text: {}", e)))?;
+
+        circuit
+            .validate()
+            .map_err(|e| de::Error::custom(format!("Invalid circuit: {}", e)))?;
+
+        Ok(BristolTextCircuit(circuit))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gadgets;
+
+    #[test]
+    fn test_round_trips() {
+        let circuit = gadgets::adder(16, true);
+
+        let json = serde_json::to_value(BristolTextCircuit(&circuit)).unwrap();
+        assert_eq!(
+            json["bristol"].as_str().unwrap(),
+            circuit.get_bristol_string().unwrap()
+        );
+        assert_eq!(json["info"], serde_json::to_value(&circuit.info).unwrap());
+        assert_eq!(json.as_object().unwrap().len(), 2);
+
+        let text = json.to_string();
+        let verbose = serde_json::to_string(&circuit).unwrap();
+        assert!(
+            text.len() * 2 < verbose.len(),
+            "text {} vs verbose {}",
+            text.len(),
+            verbose.len()
+        );
+
+        let BristolTextCircuit(parsed) = serde_json::from_str(&text).unwrap();
+        assert_eq!(parsed, circuit);
+
+        // Usable as a field of a larger document.
+        #[derive(Serialize, Deserialize)]
+        struct Job {
+            id: u32,
+            circuit: BristolTextCircuit<BristolCircuit>,
+        }
+
+        let job = serde_json::to_string(&Job {
+            id: 7,
+            circuit: BristolTextCircuit(circuit.clone()),
+        })
+        .unwrap();
+        let job = serde_json::from_str::<Job>(&job).unwrap();
+        assert_eq!((job.id, job.circuit.0), (7, circuit));
+    }
+
+    #[test]
+    fn test_errors() {
+        let circuit = gadgets::adder(1, false);
+        let mut json = serde_json::to_value(BristolTextCircuit(&circuit)).unwrap();
+
+        json["bristol"] = "3 4\n2 1 1\n1 1\n\n2 1 0 1\n".into();
+        let err = serde_json::from_value::<BristolTextCircuit<BristolCircuit>>(json.clone())
+            .unwrap_err()
+            .to_string();
+        assert!(
+            err.starts_with("Invalid Bristol text: Parsing error: "),
+            "{}",
+            err
+        );
+
+        // Parses, but a gate overwrites input b.
+        json["bristol"] = "1 3\n2 1 1\n1 1\n\n2 1 0 2 1 XOR\n".into();
+        let err = serde_json::from_value::<BristolTextCircuit<BristolCircuit>>(json.clone())
+            .unwrap_err()
+            .to_string();
+        assert_eq!(
+            err,
+            "Invalid circuit: Inconsistency: Gate 0 (XOR) writes wire 1 of input b"
+        );
+
+        json.as_object_mut().unwrap().remove("info");
+        let err = serde_json::from_value::<BristolTextCircuit<BristolCircuit>>(json)
+            .unwrap_err()
+            .to_string();
+        assert_eq!(err, "missing field `info`");
+    }
+}
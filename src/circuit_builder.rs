@@ -0,0 +1,393 @@
+use std::{
+    collections::HashSet,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+use smallvec::smallvec;
+
+use crate::{
+    bristol_circuit::BristolCircuit,
+    bristol_circuit_error::BristolCircuitError,
+    circuit_info::{CircuitInfo, ConstantInfo},
+    gate::Gate,
+    header_style::HeaderStyle,
+    op_name::OpInterner,
+};
+
+static NEXT_BUILDER_ID: AtomicUsize = AtomicUsize::new(0);
+
+/// An opaque handle to a wire of the `CircuitBuilder` that created it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct WireId {
+    builder: usize,
+    index: usize,
+}
+
+/// Builds a `BristolCircuit` without manual wire bookkeeping.
+///
+/// Wires are numbered densely when the circuit is built: inputs first (in declaration order), then
+/// constants and intermediate gate outputs, with output wires last. Multi-bit inputs and outputs
+/// occupy contiguous wires, least significant bit first.
+#[derive(Debug)]
+pub struct CircuitBuilder {
+    id: usize,
+    wire_count: usize,
+    gate_outputs: HashSet<usize>,
+    inputs: Vec<(String, usize, usize)>,
+    constants: Vec<(String, String, usize)>,
+    gates: Vec<Gate>,
+    ops: OpInterner,
+    outputs: Vec<(String, Vec<usize>)>,
+    output_wires: HashSet<usize>,
+    copy_op: String,
+    errors: Vec<String>,
+}
+
+impl Default for CircuitBuilder {
+    fn default() -> Self {
+        CircuitBuilder::new()
+    }
+}
+
+impl CircuitBuilder {
+    pub fn new() -> Self {
+        CircuitBuilder {
+            id: NEXT_BUILDER_ID.fetch_add(1, Ordering::Relaxed),
+            wire_count: 0,
+            gate_outputs: HashSet::new(),
+            inputs: Vec::new(),
+            constants: Vec::new(),
+            gates: Vec::new(),
+            ops: OpInterner::default(),
+            outputs: Vec::new(),
+            output_wires: HashSet::new(),
+            copy_op: "EQW".to_string(),
+            errors: Vec::new(),
+        }
+    }
+
+    pub fn input(&mut self, name: &str) -> WireId {
+        self.input_bus(name, 1)[0]
+    }
+
+    /// Adds a `width`-bit input, returning its wires least significant bit first.
+    pub fn input_bus(&mut self, name: &str, width: usize) -> Vec<WireId> {
+        if width == 0 {
+            self.errors.push(format!("Input {} has width 0", name));
+        }
+
+        let wires = (0..width).map(|_| self.fresh_wire()).collect::<Vec<_>>();
+        let first = wires.first().map_or(self.wire_count, |wire| wire.index);
+        self.inputs.push((name.to_string(), first, width));
+
+        wires
+    }
+
+    pub fn constant(&mut self, name: &str, value: &str) -> WireId {
+        let wire = self.fresh_wire();
+        self.constants
+            .push((name.to_string(), value.to_string(), wire.index));
+        wire
+    }
+
+    /// Adds a single-output gate and returns its output wire.
+    pub fn gate(&mut self, op: &str, inputs: &[WireId]) -> WireId {
+        let inputs = inputs
+            .iter()
+            .map(|&wire| self.resolve(wire, &format!("an input of gate {}", op)))
+            .collect();
+
+        let output = self.fresh_wire();
+        self.gate_outputs.insert(output.index);
+
+        self.gates.push(Gate {
+            inputs,
+            outputs: smallvec![output.index],
+            op: self.ops.intern(op),
+        });
+
+        output
+    }
+
+    /// Adds a `MUX` gate, which produces `b` when `sel` is set and `a` otherwise. Note that the
+    /// arithmetic `AMux` selects the other way round.
+    pub fn mux(&mut self, sel: WireId, a: WireId, b: WireId) -> WireId {
+        self.gate("MUX", &[sel, a, b])
+    }
+
+    pub fn output(&mut self, name: &str, wire: WireId) {
+        let index = self.resolve(wire, &format!("output {}", name));
+        self.output_wires.insert(index);
+        self.outputs.push((name.to_string(), vec![index]));
+    }
+
+    /// Adds a multi-bit output from wires given least significant bit first.
+    ///
+    /// The bits of an output must occupy contiguous wires, so bits that aren't fresh gate outputs
+    /// (inputs, constants, or wires already used by an output) are routed through a copy gate.
+    pub fn output_bus(&mut self, name: &str, wires: &[WireId]) {
+        if wires.is_empty() {
+            self.errors.push(format!("Output {} has no wires", name));
+        }
+
+        let mut indices = Vec::with_capacity(wires.len());
+
+        for &wire in wires {
+            let mut index = self.resolve(wire, &format!("output {}", name));
+
+            if !self.gate_outputs.contains(&index) || self.output_wires.contains(&index) {
+                let copy_op = self.copy_op.clone();
+                index = self.gate(&copy_op, &[wire]).index;
+            }
+
+            self.output_wires.insert(index);
+            indices.push(index);
+        }
+
+        self.outputs.push((name.to_string(), indices));
+    }
+
+    /// Sets the op used for the copy gates `output_bus` inserts (`EQW` by default).
+    pub fn set_copy_op(&mut self, op: &str) {
+        self.copy_op = op.to_string();
+    }
+
+    pub fn gate_count(&self) -> usize {
+        self.gates.len()
+    }
+
+    pub fn build(self) -> Result<BristolCircuit, BristolCircuitError> {
+        let mut problems = self.errors.clone();
+
+        let mut seen = HashSet::new();
+        for name in self
+            .inputs
+            .iter()
+            .map(|(name, _, _)| name)
+            .chain(self.constants.iter().map(|(name, _, _)| name))
+        {
+            if !seen.insert(name) {
+                problems.push(format!("Name {} is registered twice", name));
+            }
+        }
+
+        let mut seen = HashSet::new();
+        for (name, _) in &self.outputs {
+            if !seen.insert(name) {
+                problems.push(format!("Output {} is registered twice", name));
+            }
+        }
+
+        if !problems.is_empty() {
+            return Err(BristolCircuitError::Inconsistency {
+                message: problems.join("; "),
+            });
+        }
+
+        let mapping = self.wire_mapping();
+        let remap = |wire: usize| mapping[wire];
+
+        let info = CircuitInfo {
+            input_name_to_wire_index: self
+                .inputs
+                .iter()
+                .map(|(name, wire, _)| (name.clone(), remap(*wire)))
+                .collect(),
+            constants: self
+                .constants
+                .iter()
+                .map(|(name, value, wire)| {
+                    (
+                        name.clone(),
+                        ConstantInfo {
+                            value: value.clone(),
+                            wire_index: remap(*wire),
+                        },
+                    )
+                })
+                .collect(),
+            output_name_to_wire_index: self
+                .outputs
+                .iter()
+                .map(|(name, wires)| (name.clone(), remap(wires[0])))
+                .collect(),
+            wire_groups: Default::default(),
+            input_owners: Default::default(),
+            output_visibility: Default::default(),
+        };
+
+        // Widths are listed in the order of the names' wire indices.
+        let mut outputs = self
+            .outputs
+            .iter()
+            .map(|(_, wires)| (remap(wires[0]), wires.len()))
+            .collect::<Vec<_>>();
+        outputs.sort();
+
+        let io_widths = (
+            self.inputs.iter().map(|(_, _, width)| *width).collect(),
+            outputs.into_iter().map(|(_, width)| width).collect(),
+        );
+
+        let gates = self
+            .gates
+            .into_iter()
+            .map(|gate| Gate {
+                inputs: gate.inputs.into_iter().map(remap).collect(),
+                outputs: gate.outputs.into_iter().map(remap).collect(),
+                op: gate.op,
+            })
+            .collect();
+
+        let mut circuit = BristolCircuit {
+            wire_count: 0,
+            info,
+            io_widths,
+            gates,
+            header_style: HeaderStyle::NivList,
+        };
+
+        circuit.recompute_wire_count();
+
+        Ok(circuit)
+    }
+
+    /// Maps builder wires to final wire indices, moving gate-produced output wires to the end.
+    fn wire_mapping(&self) -> Vec<usize> {
+        let mut output_wires = Vec::new();
+        let mut is_output = vec![false; self.wire_count];
+
+        for wire in self.outputs.iter().flat_map(|(_, wires)| wires) {
+            if self.gate_outputs.contains(wire) && !is_output[*wire] {
+                is_output[*wire] = true;
+                output_wires.push(*wire);
+            }
+        }
+
+        let mut mapping = vec![0; self.wire_count];
+        let internal_wires = (0..self.wire_count).filter(|&wire| !is_output[wire]);
+
+        for (new_index, old_index) in internal_wires.chain(output_wires).enumerate() {
+            mapping[old_index] = new_index;
+        }
+
+        mapping
+    }
+
+    fn fresh_wire(&mut self) -> WireId {
+        let wire = WireId {
+            builder: self.id,
+            index: self.wire_count,
+        };
+
+        self.wire_count += 1;
+        wire
+    }
+
+    fn resolve(&mut self, wire: WireId, usage: &str) -> usize {
+        if wire.builder != self.id {
+            self.errors.push(format!(
+                "Wire used as {} belongs to a different builder",
+                usage
+            ));
+        }
+
+        wire.index
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+
+    #[test]
+    fn test_buses() {
+        let mut b = CircuitBuilder::new();
+        let x = b.input_bus("x", 2);
+        let y = b.input("y");
+        let low = b.gate("AND", &[x[0], y]);
+        let high = b.gate("XOR", &[x[1], y]);
+        b.output("flag", low);
+        b.output_bus("out", &[low, high, y]);
+        let circuit = b.build().unwrap();
+
+        assert_eq!(circuit.info.input_name_to_wire_index["x"], 0);
+        assert_eq!(circuit.info.input_name_to_wire_index["y"], 2);
+        assert_eq!(circuit.io_widths, (vec![2, 1], vec![1, 3]));
+
+        // The AND output is already used by flag, so out gets copies of it and of input y.
+        assert_eq!(circuit.info.output_name_to_wire_index["flag"], 3);
+        assert_eq!(circuit.info.output_name_to_wire_index["out"], 4);
+        assert_eq!(
+            circuit
+                .gates
+                .iter()
+                .map(|gate| (
+                    gate.op.as_str(),
+                    gate.inputs.to_vec(),
+                    gate.outputs.to_vec()
+                ))
+                .collect::<Vec<_>>(),
+            vec![
+                ("AND", vec![0, 2], vec![3]),
+                ("XOR", vec![1, 2], vec![5]),
+                ("EQW", vec![3], vec![4]),
+                ("EQW", vec![2], vec![6]),
+            ]
+        );
+
+        let outputs = circuit
+            .eval_boolean(&HashMap::from([
+                ("x".to_string(), vec![true, false]),
+                ("y".to_string(), vec![true]),
+            ]))
+            .unwrap();
+        assert_eq!(outputs["out"], vec![true, true, true]);
+    }
+
+    #[test]
+    fn test_build_errors() {
+        let mut other = CircuitBuilder::new();
+        let foreign = other.input("z");
+
+        let mut b = CircuitBuilder::new();
+        let x = b.input("x");
+        b.constant("x", "1");
+        b.gate("AAdd", &[x, foreign]);
+        b.output("out", foreign);
+        b.output("out", x);
+
+        let err = b.build().unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Inconsistency: Wire used as an input of gate AAdd belongs to a different builder; \
+             Wire used as output out belongs to a different builder; Name x is registered twice; \
+             Output out is registered twice"
+        );
+    }
+
+    #[test]
+    fn test_copy_op() {
+        let mut b = CircuitBuilder::new();
+        b.set_copy_op("BUF");
+        let x = b.input("x");
+        b.output_bus("out", &[x]);
+
+        assert_eq!(b.build().unwrap().gates[0].op, "BUF");
+    }
+
+    #[test]
+    fn test_empty_buses() {
+        let mut b = CircuitBuilder::new();
+        b.input_bus("x", 0);
+        b.output_bus("out", &[]);
+
+        let err = b.build().unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Inconsistency: Input x has width 0; Output out has no wires"
+        );
+    }
+}
@@ -0,0 +1,192 @@
+use std::io::BufRead;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    bristol_circuit::BristolCircuit,
+    bristol_circuit_error::BristolCircuitError,
+    circuit_info::CircuitInfo,
+    header_style::{read_io_widths, HeaderStyle},
+    line_reader::LineReader,
+    parse_options::{ParseLimits, ParseOptions},
+};
+
+/// The circuit sizes and io widths declared at the top of a Bristol file.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CircuitHeader {
+    pub gate_count: usize,
+    pub wire_count: usize,
+    pub input_widths: Vec<usize>,
+    pub output_widths: Vec<usize>,
+    pub header_style: HeaderStyle,
+}
+
+impl CircuitHeader {
+    /// `counts` gives the expected number of inputs and outputs, if known, to help detect the
+    /// header style.
+    pub(crate) fn read<R: BufRead>(
+        lines: &mut LineReader<R>,
+        style: Option<HeaderStyle>,
+        counts: Option<(usize, usize)>,
+    ) -> Result<CircuitHeader, BristolCircuitError> {
+        let (gate_count, wire_count) = lines.expect_line("circuit sizes")?.circuit_sizes()?;
+        ParseLimits::check("max_wires", lines.limits().max_wires, wire_count, || {
+            format!("Header declares {} wires", wire_count)
+        })?;
+
+        let (header_style, input_widths, output_widths) = read_io_widths(lines, style, counts)?;
+        let io_count = input_widths.len() + output_widths.len();
+        ParseLimits::check("max_io", lines.limits().max_io, io_count, || {
+            format!("Header declares {} inputs and outputs", io_count)
+        })?;
+
+        Ok(CircuitHeader {
+            gate_count,
+            wire_count,
+            input_widths,
+            output_widths,
+            header_style,
+        })
+    }
+
+    /// Info for a file that came without any: inputs named `input0`, `input1`, ... on the first
+    /// wires in declaration order, and outputs named `output0`, `output1`, ... likewise on the
+    /// last wires, each starting where the one before ends.
+    pub fn generated_info(&self) -> CircuitInfo {
+        let output_width = self.output_widths.iter().sum::<usize>();
+        let named = |prefix: &str, widths: &[usize], mut wire: usize| {
+            widths
+                .iter()
+                .enumerate()
+                .map(|(i, &width)| {
+                    let entry = (format!("{}{}", prefix, i), wire);
+                    wire += width;
+                    entry
+                })
+                .collect()
+        };
+
+        CircuitInfo {
+            input_name_to_wire_index: named("input", &self.input_widths, 0),
+            output_name_to_wire_index: named(
+                "output",
+                &self.output_widths,
+                self.wire_count.saturating_sub(output_width),
+            ),
+            ..Default::default()
+        }
+    }
+
+    /// Checks that the header declares as many inputs and outputs as the info names.
+    pub fn validate_against(&self, info: &CircuitInfo) -> Result<(), BristolCircuitError> {
+        if self.input_widths.len() != info.input_name_to_wire_index.len() {
+            return Err(BristolCircuitError::Inconsistency {
+                message: "Input count mismatch".into(),
+            });
+        }
+
+        if self.output_widths.len() != info.output_name_to_wire_index.len() {
+            return Err(BristolCircuitError::Inconsistency {
+                message: "Output count mismatch".into(),
+            });
+        }
+
+        Ok(())
+    }
+}
+
+impl BristolCircuit {
+    /// Reads just the header, leaving the gates unread. The header style is auto-detected, which
+    /// may need to look at the first gate line.
+    pub fn read_header<R: BufRead>(r: &mut R) -> Result<CircuitHeader, BristolCircuitError> {
+        BristolCircuit::read_header_with_options(r, &ParseOptions::default())
+    }
+
+    pub fn read_header_with_options<R: BufRead>(
+        r: &mut R,
+        options: &ParseOptions,
+    ) -> Result<CircuitHeader, BristolCircuitError> {
+        CircuitHeader::read(&mut LineReader::new(r, options), options.header_style, None)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        collections::HashMap,
+        io::{BufReader, Read},
+    };
+
+    use super::*;
+
+    struct CountingReader<'a> {
+        inner: &'a [u8],
+        consumed: usize,
+    }
+
+    impl Read for CountingReader<'_> {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            let n = self.inner.read(buf)?;
+            self.consumed += n;
+            Ok(n)
+        }
+    }
+
+    #[test]
+    fn test_reads_only_the_header() {
+        let gate_count = 200_000;
+        let mut src = format!("{} {}\n2 1 1\n1 1\n\n", gate_count, gate_count + 2);
+        for i in 0..gate_count {
+            src.push_str(&format!("2 1 {} {} {} AAdd\n", i, i + 1, i + 2));
+        }
+
+        let mut counter = CountingReader {
+            inner: src.as_bytes(),
+            consumed: 0,
+        };
+        let header = BristolCircuit::read_header(&mut BufReader::new(&mut counter)).unwrap();
+
+        assert_eq!(
+            header,
+            CircuitHeader {
+                gate_count,
+                wire_count: gate_count + 2,
+                input_widths: vec![1, 1],
+                output_widths: vec![1],
+                header_style: HeaderStyle::NivList,
+            }
+        );
+
+        // A single buffer fill, out of several megabytes.
+        assert!(
+            counter.consumed <= 8 * 1024,
+            "read {} bytes",
+            counter.consumed
+        );
+        assert!(src.len() > 1_000_000);
+    }
+
+    #[test]
+    fn test_validate_against() {
+        let header =
+            BristolCircuit::read_header(&mut "1 3\n2 1 1\n1 1\n\n2 1 0 1 2 AAdd\n".as_bytes())
+                .unwrap();
+
+        let mut info = CircuitInfo {
+            input_name_to_wire_index: HashMap::from([("a".into(), 0), ("b".into(), 1)]),
+            output_name_to_wire_index: HashMap::from([("c".into(), 2)]),
+            ..Default::default()
+        };
+        assert!(header.validate_against(&info).is_ok());
+
+        info.output_name_to_wire_index.insert("d".into(), 3);
+        let err = header.validate_against(&info).unwrap_err();
+        assert_eq!(err.to_string(), "Inconsistency: Output count mismatch");
+    }
+
+    #[test]
+    fn test_malformed_header() {
+        let err = BristolCircuit::read_header(&mut "1\n".as_bytes()).unwrap_err();
+        assert!(matches!(err, BristolCircuitError::ParsingError { .. }));
+    }
+}
@@ -1,12 +1,51 @@
-use std::collections::HashMap;
+use std::{
+    collections::{BTreeMap, HashMap},
+    path::Path,
+};
 
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Serialize, Serializer};
 
+use crate::{
+    bristol_circuit_error::{with_path, BristolCircuitError},
+    party::PartyId,
+    visibility::Visibility,
+    wire_group::WireGroup,
+};
+
+/// Serializing is deterministic: inputs and outputs are written in wire order, and the other
+/// maps in name order, so the same info always gives the same document.
 #[derive(Default, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub struct CircuitInfo {
+    #[serde(serialize_with = "serialize_by_wire")]
     pub input_name_to_wire_index: HashMap<String, usize>,
+    #[serde(serialize_with = "serialize_by_name")]
     pub constants: HashMap<String, ConstantInfo>,
+    #[serde(serialize_with = "serialize_by_wire")]
     pub output_name_to_wire_index: HashMap<String, usize>,
+
+    /// Named inputs and outputs whose bits needn't occupy contiguous wires.
+    #[serde(
+        default,
+        skip_serializing_if = "HashMap::is_empty",
+        serialize_with = "serialize_by_name"
+    )]
+    pub wire_groups: HashMap<String, WireGroup>,
+
+    /// Which party supplies each input, for MPC. Inputs needn't all be annotated.
+    #[serde(
+        default,
+        skip_serializing_if = "HashMap::is_empty",
+        serialize_with = "serialize_by_name"
+    )]
+    pub input_owners: HashMap<String, PartyId>,
+
+    /// Who learns each output. Outputs without an annotation are public.
+    #[serde(
+        default,
+        skip_serializing_if = "HashMap::is_empty",
+        serialize_with = "serialize_by_name"
+    )]
+    pub output_visibility: HashMap<String, Visibility>,
 }
 
 #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
@@ -14,3 +53,318 @@ pub struct ConstantInfo {
     pub value: String,
     pub wire_index: usize,
 }
+
+impl CircuitInfo {
+    /// The named inputs and their first wires, sorted by wire index (then name).
+    pub fn inputs(&self) -> Vec<(&str, usize)> {
+        by_wire(&self.input_name_to_wire_index)
+    }
+
+    /// The named outputs and their first wires, sorted by wire index (then name).
+    pub fn outputs(&self) -> Vec<(&str, usize)> {
+        by_wire(&self.output_name_to_wire_index)
+    }
+
+    /// Loads an info document, choosing the format from the extension: `.toml` and
+    /// `.yaml`/`.yml` (e.g. `circuit.info.toml`) need the corresponding feature, anything else is
+    /// read as JSON.
+    pub fn from_file(path: &Path) -> Result<CircuitInfo, BristolCircuitError> {
+        with_path(path, || {
+            let text = std::fs::read_to_string(path)?;
+
+            match path.extension().and_then(|ext| ext.to_str()) {
+                Some("toml") => CircuitInfo::from_toml_str(&text),
+                Some("yaml" | "yml") => CircuitInfo::from_yaml_str(&text),
+                _ => CircuitInfo::from_json_str(&text),
+            }
+        })
+    }
+
+    /// Saves the info document, choosing the format from the extension like `from_file`.
+    pub fn to_file(&self, path: &Path) -> Result<(), BristolCircuitError> {
+        with_path(path, || {
+            let text = match path.extension().and_then(|ext| ext.to_str()) {
+                Some("toml") => self.to_toml_string()?,
+                Some("yaml" | "yml") => self.to_yaml_string()?,
+                _ => self.to_json_string()?,
+            };
+
+            Ok(std::fs::write(path, text)?)
+        })
+    }
+
+    pub fn from_json_str(text: &str) -> Result<CircuitInfo, BristolCircuitError> {
+        serde_json::from_str(text).map_err(|e| format_error("JSON", e))
+    }
+
+    pub fn to_json_string(&self) -> Result<String, BristolCircuitError> {
+        serde_json::to_string_pretty(self).map_err(|e| format_error("JSON", e))
+    }
+
+    pub fn from_toml_str(text: &str) -> Result<CircuitInfo, BristolCircuitError> {
+        #[cfg(feature = "toml")]
+        return toml::from_str(text).map_err(|e| format_error("TOML", e));
+
+        #[cfg(not(feature = "toml"))]
+        {
+            let _ = text;
+            Err(missing_feature("TOML", "toml"))
+        }
+    }
+
+    pub fn to_toml_string(&self) -> Result<String, BristolCircuitError> {
+        #[cfg(feature = "toml")]
+        return toml::to_string(self).map_err(|e| format_error("TOML", e));
+
+        #[cfg(not(feature = "toml"))]
+        Err(missing_feature("TOML", "toml"))
+    }
+
+    pub fn from_yaml_str(text: &str) -> Result<CircuitInfo, BristolCircuitError> {
+        #[cfg(feature = "yaml")]
+        return serde_yaml::from_str(text).map_err(|e| format_error("YAML", e));
+
+        #[cfg(not(feature = "yaml"))]
+        {
+            let _ = text;
+            Err(missing_feature("YAML", "yaml"))
+        }
+    }
+
+    pub fn to_yaml_string(&self) -> Result<String, BristolCircuitError> {
+        #[cfg(feature = "yaml")]
+        return serde_yaml::to_string(self).map_err(|e| format_error("YAML", e));
+
+        #[cfg(not(feature = "yaml"))]
+        Err(missing_feature("YAML", "yaml"))
+    }
+}
+
+fn by_wire(names: &HashMap<String, usize>) -> Vec<(&str, usize)> {
+    let mut names = names
+        .iter()
+        .map(|(name, &wire)| (name.as_str(), wire))
+        .collect::<Vec<_>>();
+    names.sort_by_key(|&(name, wire)| (wire, name));
+    names
+}
+
+fn serialize_by_wire<S: Serializer>(
+    names: &HashMap<String, usize>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    serializer.collect_map(by_wire(names))
+}
+
+fn serialize_by_name<S: Serializer, V: Serialize>(
+    map: &HashMap<String, V>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    serializer.collect_map(map.iter().collect::<BTreeMap<_, _>>())
+}
+
+fn format_error(format: &'static str, e: impl std::fmt::Display) -> BristolCircuitError {
+    BristolCircuitError::InfoFormatError {
+        format,
+        message: e.to_string().trim_end().to_string(),
+    }
+}
+
+#[cfg(any(not(feature = "toml"), not(feature = "yaml")))]
+fn missing_feature(format: &'static str, feature: &str) -> BristolCircuitError {
+    BristolCircuitError::InfoFormatError {
+        format,
+        message: format!("support requires the `{}` feature", feature),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_info() -> CircuitInfo {
+        CircuitInfo {
+            input_name_to_wire_index: [("a".to_string(), 0), ("b".to_string(), 1)]
+                .into_iter()
+                .collect(),
+            constants: [(
+                "two".to_string(),
+                ConstantInfo {
+                    value: "2".to_string(),
+                    wire_index: 2,
+                },
+            )]
+            .into_iter()
+            .collect(),
+            output_name_to_wire_index: [("c".to_string(), 4)].into_iter().collect(),
+            wire_groups: Default::default(),
+            input_owners: Default::default(),
+            output_visibility: Default::default(),
+        }
+    }
+
+    #[test]
+    fn test_json_round_trip() {
+        let info = sample_info();
+        assert_eq!(
+            CircuitInfo::from_json_str(&info.to_json_string().unwrap()).unwrap(),
+            info
+        );
+    }
+
+    /// Inputs `s`..`z` on wires 7..0, so wire order and name order disagree, inserted in the
+    /// given order.
+    fn reversed_names_info(names: impl Iterator<Item = char>) -> CircuitInfo {
+        let mut info = CircuitInfo::default();
+
+        for name in names {
+            let wire = (b'z' - name as u8) as usize;
+            info.input_name_to_wire_index.insert(name.to_string(), wire);
+            info.output_name_to_wire_index
+                .insert(format!("out_{}", name), 100 + wire);
+            info.constants.insert(
+                format!("k_{}", name),
+                ConstantInfo {
+                    value: wire.to_string(),
+                    wire_index: 50 + wire,
+                },
+            );
+        }
+
+        info
+    }
+
+    #[test]
+    fn test_deterministic_order() {
+        let forward = reversed_names_info('s'..='z');
+        let backward = reversed_names_info(('s'..='z').rev());
+
+        assert_eq!(
+            forward.inputs(),
+            vec![
+                ("z", 0),
+                ("y", 1),
+                ("x", 2),
+                ("w", 3),
+                ("v", 4),
+                ("u", 5),
+                ("t", 6),
+                ("s", 7)
+            ]
+        );
+        assert_eq!(forward.outputs()[0], ("out_z", 100));
+
+        let text = forward.to_json_string().unwrap();
+        assert_eq!(backward.to_json_string().unwrap(), text);
+        assert!(text.find("\"z\"").unwrap() < text.find("\"s\"").unwrap());
+        assert!(text.find("\"k_s\"").unwrap() < text.find("\"k_z\"").unwrap());
+
+        let reparsed = CircuitInfo::from_json_str(&text).unwrap();
+        assert_eq!(reparsed.to_json_string().unwrap(), text);
+    }
+
+    #[test]
+    fn test_json_error_position() {
+        let err = CircuitInfo::from_json_str("{\n  \"constants\": 3\n}").unwrap_err();
+        assert!(err.to_string().contains("line 2 column"), "{}", err);
+    }
+
+    #[cfg(feature = "toml")]
+    #[test]
+    fn test_toml_round_trip() {
+        let info = sample_info();
+        let text = info.to_toml_string().unwrap();
+        assert!(text.contains("[constants.two]"), "{}", text);
+        assert_eq!(CircuitInfo::from_toml_str(&text).unwrap(), info);
+    }
+
+    #[cfg(feature = "toml")]
+    #[test]
+    fn test_toml_with_comments() {
+        let text = r#"
+            # Hand-maintained info for the sample circuit
+            [input_name_to_wire_index]
+            a = 0 # left operand
+            b = 1
+
+            [constants.two]
+            value = "2"
+            wire_index = 2
+
+            [output_name_to_wire_index]
+            c = 4
+        "#;
+
+        assert_eq!(CircuitInfo::from_toml_str(text).unwrap(), sample_info());
+    }
+
+    #[cfg(feature = "toml")]
+    #[test]
+    fn test_toml_error_position() {
+        let err =
+            CircuitInfo::from_toml_str("[input_name_to_wire_index]\na = \"x\"\n").unwrap_err();
+        assert!(err.to_string().contains("line 2, column"), "{}", err);
+    }
+
+    #[cfg(feature = "yaml")]
+    #[test]
+    fn test_yaml_round_trip() {
+        let info = sample_info();
+        let text = info.to_yaml_string().unwrap();
+        assert_eq!(CircuitInfo::from_yaml_str(&text).unwrap(), info);
+    }
+
+    #[cfg(feature = "yaml")]
+    #[test]
+    fn test_yaml_with_comments() {
+        let text = "
+# Hand-maintained info for the sample circuit
+input_name_to_wire_index:
+  a: 0 # left operand
+  b: 1
+constants:
+  two:
+    value: \"2\"
+    wire_index: 2
+output_name_to_wire_index:
+  c: 4
+";
+
+        assert_eq!(CircuitInfo::from_yaml_str(text).unwrap(), sample_info());
+    }
+
+    #[cfg(feature = "yaml")]
+    #[test]
+    fn test_yaml_error_position() {
+        let err = CircuitInfo::from_yaml_str("input_name_to_wire_index:\n  a: x\n").unwrap_err();
+        assert!(err.to_string().contains("line 2 column"), "{}", err);
+    }
+
+    #[test]
+    fn test_from_file_sniffs_extension() {
+        let dir = std::env::temp_dir().join(format!("bristol-info-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let info = sample_info();
+        let json_path = dir.join("sample.info.json");
+        std::fs::write(&json_path, info.to_json_string().unwrap()).unwrap();
+        assert_eq!(CircuitInfo::from_file(&json_path).unwrap(), info);
+
+        let toml_path = dir.join("sample.info.toml");
+        std::fs::write(&toml_path, "[input_name_to_wire_index]\n").unwrap();
+        let result = CircuitInfo::from_file(&toml_path);
+
+        #[cfg(feature = "toml")]
+        assert!(result.is_err());
+        #[cfg(not(feature = "toml"))]
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            format!(
+                "{}: Invalid TOML circuit info: support requires the `toml` feature",
+                toml_path.display()
+            )
+        );
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
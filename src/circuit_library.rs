@@ -0,0 +1,343 @@
+use std::{
+    cell::{OnceCell, RefCell},
+    collections::BTreeMap,
+    fs::File,
+    io::{BufRead, BufReader, Read, Seek, SeekFrom, Write},
+    path::Path,
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    bristol_circuit::BristolCircuit, bristol_circuit_error::BristolCircuitError,
+    circuit_info::CircuitInfo,
+};
+
+/// Named circuits stored together in one archive file, loaded on first access.
+///
+/// The archive is a `BRISTOL-LIBRARY 1` line, a line giving the byte length of a JSON table of
+/// contents, the table itself, then each circuit's info JSON and Bristol text at the offsets the
+/// table records (relative to the end of the table).
+pub struct CircuitLibrary {
+    entries: BTreeMap<String, LibraryEntry>,
+    archive: Option<RefCell<Box<dyn ReadSeek>>>,
+}
+
+trait ReadSeek: Read + Seek {}
+
+impl<R: Read + Seek> ReadSeek for R {}
+
+struct LibraryEntry {
+    circuit: OnceCell<BristolCircuit>,
+    location: Option<EntryLocation>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+struct EntryLocation {
+    offset: u64,
+    info_length: u64,
+    bristol_length: u64,
+}
+
+#[derive(Serialize, Deserialize)]
+struct TableOfContents {
+    entries: BTreeMap<String, EntryLocation>,
+}
+
+const MAGIC: &str = "BRISTOL-LIBRARY 1";
+
+impl CircuitLibrary {
+    pub fn new() -> Self {
+        CircuitLibrary {
+            entries: BTreeMap::new(),
+            archive: None,
+        }
+    }
+
+    pub fn insert(
+        &mut self,
+        name: &str,
+        circuit: BristolCircuit,
+    ) -> Result<(), BristolCircuitError> {
+        if self.entries.contains_key(name) {
+            return Err(BristolCircuitError::Inconsistency {
+                message: format!("Circuit {} is already in the library", name),
+            });
+        }
+
+        self.entries.insert(
+            name.to_string(),
+            LibraryEntry {
+                circuit: OnceCell::from(circuit),
+                location: None,
+            },
+        );
+
+        Ok(())
+    }
+
+    /// The named circuit, parsing it from the archive if it hasn't been loaded yet.
+    pub fn get(&self, name: &str) -> Result<Option<&BristolCircuit>, BristolCircuitError> {
+        let Some(entry) = self.entries.get(name) else {
+            return Ok(None);
+        };
+
+        if let Some(circuit) = entry.circuit.get() {
+            return Ok(Some(circuit));
+        }
+
+        let (Some(location), Some(archive)) = (&entry.location, &self.archive) else {
+            unreachable!("unloaded entries come from an archive");
+        };
+
+        let circuit = read_entry(&mut **archive.borrow_mut(), location)?;
+
+        Ok(Some(entry.circuit.get_or_init(|| circuit)))
+    }
+
+    /// Entry names, in sorted order.
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.entries.keys().map(String::as_str)
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn write_archive(&self, path: &Path) -> Result<(), BristolCircuitError> {
+        let mut file = File::create(path)?;
+        self.write_archive_to(&mut file)?;
+        Ok(file.flush()?)
+    }
+
+    /// Writes every entry, loading any that haven't been yet.
+    pub fn write_archive_to<W: Write>(&self, w: &mut W) -> Result<(), BristolCircuitError> {
+        let mut entries = BTreeMap::new();
+        let mut data = Vec::new();
+
+        for name in self.names() {
+            let circuit = self.get(name)?.expect("name comes from the library");
+
+            let info = circuit.info.to_json_string()?;
+            let bristol = circuit.get_bristol_string()?;
+
+            entries.insert(
+                name.to_string(),
+                EntryLocation {
+                    offset: data.len() as u64,
+                    info_length: info.len() as u64,
+                    bristol_length: bristol.len() as u64,
+                },
+            );
+
+            data.extend_from_slice(info.as_bytes());
+            data.extend_from_slice(bristol.as_bytes());
+        }
+
+        let toc = serde_json::to_string(&TableOfContents { entries }).map_err(archive_error)?;
+
+        writeln!(w, "{}", MAGIC)?;
+        writeln!(w, "{}", toc.len())?;
+        w.write_all(toc.as_bytes())?;
+        w.write_all(&data)?;
+
+        Ok(())
+    }
+
+    pub fn open_archive(path: &Path) -> Result<CircuitLibrary, BristolCircuitError> {
+        CircuitLibrary::from_archive_reader(BufReader::new(File::open(path)?))
+    }
+
+    /// Reads just the table of contents; entries are parsed when first requested.
+    pub fn from_archive_reader<R: BufRead + Seek + 'static>(
+        mut r: R,
+    ) -> Result<CircuitLibrary, BristolCircuitError> {
+        let mut line = String::new();
+        r.read_line(&mut line)?;
+
+        if line.trim_end() != MAGIC {
+            return Err(BristolCircuitError::ParsingError {
+                message: "Not a circuit library archive".into(),
+            });
+        }
+
+        line.clear();
+        r.read_line(&mut line)?;
+        let toc_length =
+            line.trim_end()
+                .parse::<u64>()
+                .map_err(|_| BristolCircuitError::ParsingError {
+                    message: format!("Invalid table of contents length \"{}\"", line.trim_end()),
+                })?;
+
+        let mut toc = Vec::new();
+        (&mut r).take(toc_length).read_to_end(&mut toc)?;
+        let toc: TableOfContents = serde_json::from_slice(&toc).map_err(archive_error)?;
+
+        let data_start = r.stream_position()?;
+
+        let entries = toc
+            .entries
+            .into_iter()
+            .map(|(name, mut location)| {
+                location.offset += data_start;
+
+                (
+                    name,
+                    LibraryEntry {
+                        circuit: OnceCell::new(),
+                        location: Some(location),
+                    },
+                )
+            })
+            .collect();
+
+        Ok(CircuitLibrary {
+            entries,
+            archive: Some(RefCell::new(Box::new(r))),
+        })
+    }
+}
+
+impl Default for CircuitLibrary {
+    fn default() -> Self {
+        CircuitLibrary::new()
+    }
+}
+
+fn read_entry(
+    r: &mut dyn ReadSeek,
+    location: &EntryLocation,
+) -> Result<BristolCircuit, BristolCircuitError> {
+    r.seek(SeekFrom::Start(location.offset))?;
+
+    let mut info = String::new();
+    (&mut *r)
+        .take(location.info_length)
+        .read_to_string(&mut info)?;
+    let info = CircuitInfo::from_json_str(&info)?;
+
+    let mut bristol = BufReader::new(r.take(location.bristol_length));
+    BristolCircuit::read_info_and_bristol(&info, &mut bristol)
+}
+
+fn archive_error(e: serde_json::Error) -> BristolCircuitError {
+    BristolCircuitError::ParsingError {
+        message: format!("Invalid library table of contents: {}", e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::Cell, io::Cursor, rc::Rc};
+
+    use super::*;
+    use crate::gadgets;
+
+    struct CountingReader {
+        inner: Cursor<Vec<u8>>,
+        consumed: Rc<Cell<usize>>,
+    }
+
+    impl Read for CountingReader {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            let n = self.inner.read(buf)?;
+            self.consumed.set(self.consumed.get() + n);
+            Ok(n)
+        }
+    }
+
+    impl Seek for CountingReader {
+        fn seek(&mut self, pos: SeekFrom) -> std::io::Result<u64> {
+            self.inner.seek(pos)
+        }
+    }
+
+    fn sample_library() -> CircuitLibrary {
+        let mut library = CircuitLibrary::new();
+        library.insert("adder8", gadgets::adder(8, false)).unwrap();
+        library
+            .insert("adder16", gadgets::adder(16, false))
+            .unwrap();
+        library
+            .insert("adder32", gadgets::adder(32, false))
+            .unwrap();
+        library
+    }
+
+    #[test]
+    fn test_lazy_loading() {
+        let mut archive = Vec::new();
+        sample_library().write_archive_to(&mut archive).unwrap();
+
+        let consumed = Rc::new(Cell::new(0));
+        let reader = CountingReader {
+            inner: Cursor::new(archive.clone()),
+            consumed: consumed.clone(),
+        };
+        // An unbuffered reader, so only the bytes actually parsed are counted.
+        let library =
+            CircuitLibrary::from_archive_reader(BufReader::with_capacity(1, reader)).unwrap();
+
+        assert_eq!(
+            library.names().collect::<Vec<_>>(),
+            vec!["adder16", "adder32", "adder8"]
+        );
+        let toc_bytes = consumed.get();
+
+        let adder8 = library.get("adder8").unwrap().unwrap();
+        assert_eq!(adder8, &gadgets::adder(8, false));
+
+        let adder8_bytes = adder8.info.to_json_string().unwrap().len()
+            + adder8.get_bristol_string().unwrap().len();
+        assert_eq!(consumed.get() - toc_bytes, adder8_bytes);
+        assert!(toc_bytes + adder8_bytes < archive.len() / 2);
+
+        // Loaded entries are cached.
+        library.get("adder8").unwrap();
+        assert_eq!(consumed.get() - toc_bytes, adder8_bytes);
+
+        assert!(library.get("adder64").unwrap().is_none());
+    }
+
+    #[test]
+    fn test_file_round_trip() {
+        let library = sample_library();
+        let path = std::env::temp_dir().join(format!("library_{}.bristol-lib", std::process::id()));
+
+        library.write_archive(&path).unwrap();
+        let reopened = CircuitLibrary::open_archive(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(reopened.len(), 3);
+        for name in library.names() {
+            assert_eq!(reopened.get(name).unwrap(), library.get(name).unwrap());
+        }
+    }
+
+    #[test]
+    fn test_name_collision() {
+        let mut library = sample_library();
+        let err = library
+            .insert("adder8", gadgets::adder(4, false))
+            .unwrap_err();
+
+        assert_eq!(
+            err.to_string(),
+            "Inconsistency: Circuit adder8 is already in the library"
+        );
+        assert_eq!(library.len(), 3);
+    }
+
+    #[test]
+    fn test_rejects_other_files() {
+        let err = CircuitLibrary::from_archive_reader(Cursor::new(b"1 3\n2 1 1\n".to_vec()))
+            .err()
+            .unwrap();
+        assert!(err.to_string().contains("Not a circuit library archive"));
+    }
+}
@@ -0,0 +1,394 @@
+use std::io::BufRead;
+
+use crate::{
+    bristol_circuit_error::BristolCircuitError,
+    circuit_header::CircuitHeader,
+    circuit_info::CircuitInfo,
+    compression::reject_gzip,
+    gate::Gate,
+    header_style::HeaderStyle,
+    line_reader::{LineReader, Peeked},
+    parse_options::{GateCountPolicy, ParseLimits, ParseOptions},
+    parse_warning::ParseWarning,
+    trace::trace_progress,
+};
+
+/// Reads a circuit's gates one at a time, without storing them, for circuits too big to hold
+/// in memory. The header is read and checked against the info up front; iterating then yields
+/// each gate, and once the gates run out checks the rest of the input the same way
+/// [`BristolCircuit::read_info_and_bristol`](crate::BristolCircuit::read_info_and_bristol)
+/// does. Iteration stops after the first error.
+pub struct BristolCircuitReader<R> {
+    lines: LineReader<R>,
+    header: CircuitHeader,
+    options: ParseOptions,
+    gates_read: usize,
+    done: bool,
+    warnings: Vec<ParseWarning>,
+}
+
+impl<R: BufRead> BristolCircuitReader<R> {
+    pub fn open(info: &CircuitInfo, r: R) -> Result<Self, BristolCircuitError> {
+        BristolCircuitReader::open_with_options(info, r, &ParseOptions::default())
+    }
+
+    pub fn open_with_options(
+        info: &CircuitInfo,
+        mut r: R,
+        options: &ParseOptions,
+    ) -> Result<Self, BristolCircuitError> {
+        reject_gzip(r.fill_buf()?)?;
+
+        let mut lines = LineReader::new(r, options);
+        let header = read_checked_header(info, &mut lines, options)?;
+
+        Ok(BristolCircuitReader::from_header(lines, header, options))
+    }
+
+    /// Continues reading gates from `lines`, once `header` has been read from it.
+    pub(crate) fn from_header(
+        lines: LineReader<R>,
+        header: CircuitHeader,
+        options: &ParseOptions,
+    ) -> Self {
+        BristolCircuitReader {
+            lines,
+            header,
+            options: options.clone(),
+            gates_read: 0,
+            done: false,
+            warnings: Vec::new(),
+        }
+    }
+
+    pub fn header(&self) -> &CircuitHeader {
+        &self.header
+    }
+
+    /// The number of gates the header declares.
+    pub fn gate_count(&self) -> usize {
+        self.header.gate_count
+    }
+
+    /// The number of wires the header declares.
+    pub fn wire_count(&self) -> usize {
+        self.header.wire_count
+    }
+
+    /// The input and output widths.
+    pub fn io_widths(&self) -> (&[usize], &[usize]) {
+        (&self.header.input_widths, &self.header.output_widths)
+    }
+
+    pub fn header_style(&self) -> HeaderStyle {
+        self.header.header_style
+    }
+
+    /// Problems tolerated because `options.strict` is disabled, so far. A gate count mismatch
+    /// is only known once the gates run out.
+    pub fn warnings(&self) -> &[ParseWarning] {
+        &self.warnings
+    }
+
+    pub(crate) fn into_parts(self) -> (CircuitHeader, Vec<ParseWarning>) {
+        (self.header, self.warnings)
+    }
+
+    fn read_gate(&mut self) -> Result<Option<Gate>, BristolCircuitError> {
+        let declared_gate_count = match self.options.gate_count {
+            GateCountPolicy::TrustHeader => Some(self.header.gate_count),
+            GateCountPolicy::Exact(n) => Some(n),
+            GateCountPolicy::ReadUntilEof => None,
+        };
+
+        // What expects `gate_count` gates, for errors: the header, or an overriding policy.
+        let expectation = |gate_count: usize| match self.options.gate_count {
+            GateCountPolicy::Exact(_) => format!("Gate count policy expects {} gates", gate_count),
+            _ => format!("Header declares {} gates", gate_count),
+        };
+
+        if let Some(gate_count) = declared_gate_count {
+            ParseLimits::check(
+                "max_gates",
+                self.options.limits.max_gates,
+                gate_count,
+                || expectation(gate_count),
+            )?;
+        }
+
+        let Some(gate_count) = declared_gate_count else {
+            loop {
+                match self.lines.peek()? {
+                    Peeked::End => return Ok(None),
+                    Peeked::Line(line) if line.tokens == [GateCountPolicy::TERMINATOR] => {
+                        return Ok(None)
+                    }
+                    Peeked::Line(_) | Peeked::LongLine => {
+                        if let Some(gate) = self.lines.next_gate()? {
+                            return Ok(Some(gate));
+                        }
+                    }
+                }
+            }
+        };
+
+        if self.gates_read < gate_count {
+            if let Some(gate) = self.lines.next_gate()? {
+                return Ok(Some(gate));
+            }
+        }
+
+        // Lenient parsing reads the gates past the declared count too, and warns once they run
+        // out.
+        if !self.options.strict {
+            if let Some(gate) = next_trailing_gate(&mut self.lines, &self.options)? {
+                return Ok(Some(gate));
+            }
+
+            if self.gates_read != gate_count {
+                self.warnings.push(ParseWarning::GateCountMismatch {
+                    declared: gate_count,
+                    actual: self.gates_read,
+                });
+            }
+
+            return Ok(None);
+        }
+
+        let found_gate_count =
+            self.gates_read + count_trailing_gates(&mut self.lines, &self.options)?;
+
+        if found_gate_count != gate_count {
+            let header_note = match self.options.gate_count {
+                GateCountPolicy::Exact(_) => {
+                    format!(" (the header declares {})", self.header.gate_count)
+                }
+                _ => String::new(),
+            };
+
+            return Err(BristolCircuitError::Inconsistency {
+                message: format!(
+                    "{} but {} were found{}",
+                    expectation(gate_count),
+                    found_gate_count,
+                    header_note
+                ),
+            });
+        }
+
+        Ok(None)
+    }
+}
+
+impl<R: BufRead> Iterator for BristolCircuitReader<R> {
+    type Item = Result<Gate, BristolCircuitError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+
+        match self.read_gate() {
+            Ok(Some(gate)) => {
+                // Gates read until the end of the input aren't declared up front.
+                let read = self.gates_read + 1;
+
+                if let Err(e) =
+                    ParseLimits::check("max_gates", self.options.limits.max_gates, read, || {
+                        format!("Input has more than {} gates", read - 1)
+                    })
+                {
+                    self.done = true;
+                    return Some(Err(e));
+                }
+
+                if let Some(registry) = &self.options.op_registry {
+                    if let Err(mismatch) = registry.check(self.gates_read, &gate) {
+                        self.done = true;
+                        return Some(Err(self.lines.error(mismatch.to_string())));
+                    }
+                }
+
+                self.gates_read += 1;
+                trace_progress!(self.gates_read);
+                Some(Ok(gate))
+            }
+            Ok(None) => {
+                self.done = true;
+                None
+            }
+            Err(e) => {
+                self.done = true;
+                Some(Err(e))
+            }
+        }
+    }
+}
+
+/// Reads the header and checks it and `info` against each other.
+pub(crate) fn read_checked_header<R: BufRead>(
+    info: &CircuitInfo,
+    lines: &mut LineReader<R>,
+    options: &ParseOptions,
+) -> Result<CircuitHeader, BristolCircuitError> {
+    let header = CircuitHeader::read(
+        lines,
+        options.header_style,
+        Some((
+            info.input_name_to_wire_index.len(),
+            info.output_name_to_wire_index.len(),
+        )),
+    )?;
+    header.validate_against(info)?;
+    info.validate_input_owners()?;
+    info.validate_output_visibility()?;
+
+    Ok(header)
+}
+
+/// Consumes the rest of the input, counting lines that look like gates.
+fn count_trailing_gates<R: BufRead>(
+    lines: &mut LineReader<R>,
+    options: &ParseOptions,
+) -> Result<usize, BristolCircuitError> {
+    let mut count = 0;
+
+    while next_trailing_gate(lines, options)?.is_some() {
+        count += 1;
+    }
+
+    Ok(count)
+}
+
+/// Reads the next line that looks like a gate once the declared gates are read, skipping other
+/// lines unless `options.check_trailing` rejects them.
+fn next_trailing_gate<R: BufRead>(
+    lines: &mut LineReader<R>,
+    options: &ParseOptions,
+) -> Result<Option<Gate>, BristolCircuitError> {
+    loop {
+        match lines.peek()? {
+            Peeked::End => return Ok(None),
+            Peeked::LongLine => {
+                if let Some(gate) = lines.next_gate()? {
+                    return Ok(Some(gate));
+                }
+            }
+            Peeked::Line(line) => {
+                if line.leading_gate().is_ok() {
+                    return lines.next_gate();
+                }
+
+                if options.check_trailing {
+                    return Err(BristolCircuitError::ParsingError {
+                        message: "Unexpected non-whitespace line after gates".into(),
+                    });
+                }
+
+                lines.next_line()?;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gadgets;
+
+    #[test]
+    fn test_matches_read_info_and_bristol() {
+        let circuit = gadgets::adder(8, true);
+        let text = circuit.get_bristol_string().unwrap();
+
+        let mut reader = BristolCircuitReader::open(&circuit.info, text.as_bytes()).unwrap();
+        assert_eq!(reader.gate_count(), circuit.gates.len());
+        assert_eq!(reader.wire_count(), circuit.wire_count);
+        assert_eq!(
+            reader.io_widths(),
+            (&circuit.io_widths.0[..], &circuit.io_widths.1[..])
+        );
+
+        let gates = reader.by_ref().collect::<Result<Vec<_>, _>>().unwrap();
+        assert_eq!(gates, circuit.gates);
+        assert!(reader.next().is_none());
+    }
+
+    #[test]
+    fn test_errors_after_gates() {
+        let circuit = gadgets::adder(2, false);
+        let text = circuit.get_bristol_string().unwrap();
+
+        let trailing = format!("{}foo\n", text);
+        let results = BristolCircuitReader::open(&circuit.info, trailing.as_bytes())
+            .unwrap()
+            .collect::<Vec<_>>();
+        assert_eq!(results.len(), circuit.gates.len() + 1);
+        assert_eq!(
+            results.last().unwrap().as_ref().unwrap_err().to_string(),
+            "Parsing error: Unexpected non-whitespace line after gates"
+        );
+
+        let extra = format!("{}2 1 0 1 2 AND\n", text);
+        let err = BristolCircuitReader::open(&circuit.info, extra.as_bytes())
+            .unwrap()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            format!(
+                "Inconsistency: Header declares {} gates but {} were found",
+                circuit.gates.len(),
+                circuit.gates.len() + 1
+            )
+        );
+
+        let lenient = ParseOptions {
+            strict: false,
+            ..Default::default()
+        };
+        let mut reader =
+            BristolCircuitReader::open_with_options(&circuit.info, extra.as_bytes(), &lenient)
+                .unwrap();
+        assert_eq!(reader.by_ref().count(), circuit.gates.len() + 1);
+        assert_eq!(
+            reader.warnings(),
+            [ParseWarning::GateCountMismatch {
+                declared: circuit.gates.len(),
+                actual: circuit.gates.len() + 1,
+            }]
+        );
+    }
+
+    #[test]
+    fn test_gates_share_ops() {
+        let circuit = gadgets::adder(8, true);
+        let text = circuit.get_bristol_string().unwrap();
+
+        let gates = BristolCircuitReader::open(&circuit.info, text.as_bytes())
+            .unwrap()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+
+        for (i, gate) in gates.iter().enumerate() {
+            let first = gates.iter().find(|other| other.op == gate.op).unwrap();
+            assert!(gate.op.ptr_eq(&first.op), "gate {}", i);
+        }
+
+        let ops = gates.iter().map(|gate| gate.op.as_str());
+        let distinct = ops.collect::<std::collections::HashSet<_>>().len();
+        assert!(distinct > 1 && distinct < gates.len() / 4);
+        assert_eq!(
+            serde_json::to_string(&gates).unwrap(),
+            serde_json::to_string(&circuit.gates).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_header_error() {
+        let err = BristolCircuitReader::open(&CircuitInfo::default(), &b"1 3\n"[..])
+            .err()
+            .unwrap();
+        assert!(err.to_string().starts_with("Parsing error:"), "{}", err);
+    }
+}
@@ -0,0 +1,301 @@
+use std::io::Write;
+
+use crate::{
+    bristol_circuit::BristolCircuit, bristol_circuit_error::BristolCircuitError,
+    circuit_header::CircuitHeader, gate::Gate, header_style::write_io_widths,
+    memory_estimate::decimal_digits, write_options::WriteOptions,
+};
+
+/// Writes Bristol text a gate at a time, so gates can be generated and written without
+/// collecting them. The header is written up front, and `finish` checks that exactly the
+/// declared number of gates followed.
+pub struct BristolWriter<W: Write> {
+    w: W,
+    gate_count: usize,
+    gates_written: usize,
+
+    /// Each gate's line is formatted here and written whole.
+    line: Vec<u8>,
+}
+
+impl<W: Write> BristolWriter<W> {
+    pub fn new(w: W, header: &CircuitHeader) -> Result<Self, BristolCircuitError> {
+        BristolWriter::with_options(w, header, &WriteOptions::default())
+    }
+
+    pub fn with_options(
+        mut w: W,
+        header: &CircuitHeader,
+        options: &WriteOptions,
+    ) -> Result<Self, BristolCircuitError> {
+        if let Some(comment) = &options.header_comment {
+            for line in comment.lines() {
+                if line.is_empty() {
+                    writeln!(w, "#")?;
+                } else {
+                    writeln!(w, "# {}", line)?;
+                }
+            }
+        }
+
+        writeln!(w, "{} {}", header.gate_count, header.wire_count)?;
+
+        write_io_widths(
+            &mut w,
+            header.header_style,
+            &header.input_widths,
+            &header.output_widths,
+        )?;
+
+        writeln!(w)?;
+
+        Ok(BristolWriter {
+            w,
+            gate_count: header.gate_count,
+            gates_written: 0,
+            line: Vec::new(),
+        })
+    }
+
+    pub fn write_gate(&mut self, gate: &Gate) -> Result<(), BristolCircuitError> {
+        if self.gates_written == self.gate_count {
+            return Err(BristolCircuitError::Inconsistency {
+                message: format!(
+                    "Header declares {} gates but more were written",
+                    self.gate_count
+                ),
+            });
+        }
+
+        self.line.clear();
+        push_gate_line(&mut self.line, gate);
+        self.w.write_all(&self.line)?;
+        self.gates_written += 1;
+
+        Ok(())
+    }
+
+    /// Checks the gate count and flushes, returning the underlying writer.
+    pub fn finish(mut self) -> Result<W, BristolCircuitError> {
+        if self.gates_written != self.gate_count {
+            return Err(BristolCircuitError::Inconsistency {
+                message: format!(
+                    "Header declares {} gates but {} were written",
+                    self.gate_count, self.gates_written
+                ),
+            });
+        }
+
+        self.w.flush()?;
+
+        Ok(self.w)
+    }
+}
+
+/// Appends `gate`'s line: its `Display` form and a newline.
+pub(crate) fn push_gate_line(line: &mut Vec<u8>, gate: &Gate) {
+    push_usize(line, gate.inputs.len());
+    line.push(b' ');
+    push_usize(line, gate.outputs.len());
+
+    for &wire in gate.inputs.iter().chain(&gate.outputs) {
+        line.push(b' ');
+        push_usize(line, wire);
+    }
+
+    line.push(b' ');
+    line.extend_from_slice(gate.op.as_bytes());
+    line.push(b'\n');
+}
+
+/// Appends `n` in decimal, without going through `fmt`.
+fn push_usize(line: &mut Vec<u8>, mut n: usize) {
+    let mut digits = [0; 20];
+    let mut start = digits.len();
+
+    loop {
+        start -= 1;
+        digits[start] = b'0' + (n % 10) as u8;
+        n /= 10;
+
+        if n == 0 {
+            break;
+        }
+    }
+
+    line.extend_from_slice(&digits[start..]);
+}
+
+impl BristolCircuit {
+    /// About how many bytes [`BristolCircuit::write_bristol`] writes, for sizing buffers. It
+    /// counts each gate's line as if all its wires had as many digits as the highest wire, so
+    /// it's rarely much more than the actual length, and never less without a header comment.
+    pub fn bristol_len_estimate(&self) -> usize {
+        let wire_bytes = decimal_digits(self.wire_count.saturating_sub(1)) + 1;
+
+        let widths_bytes = |widths: &[usize]| {
+            decimal_digits(widths.len())
+                + widths
+                    .iter()
+                    .map(|&width| decimal_digits(width) + 1)
+                    .sum::<usize>()
+                + 1
+        };
+        let header_bytes = decimal_digits(self.gates.len())
+            + decimal_digits(self.wire_count)
+            + 2
+            + widths_bytes(&self.io_widths.0)
+            + widths_bytes(&self.io_widths.1)
+            + 1;
+
+        let gate_bytes = self
+            .gates
+            .iter()
+            .map(|gate| {
+                decimal_digits(gate.inputs.len())
+                    + decimal_digits(gate.outputs.len())
+                    + (gate.inputs.len() + gate.outputs.len()) * wire_bytes
+                    + gate.op.len()
+                    + 3
+            })
+            .sum::<usize>();
+
+        header_bytes + gate_bytes
+    }
+
+    /// Writes `header` then `gates` with a [`BristolWriter`], without collecting the gates.
+    pub fn write_bristol_streamed<W: Write, I: IntoIterator<Item = Gate>>(
+        header: &CircuitHeader,
+        gates: I,
+        w: &mut W,
+    ) -> Result<(), BristolCircuitError> {
+        let mut writer = BristolWriter::new(w, header)?;
+
+        for gate in gates {
+            writer.write_gate(&gate)?;
+        }
+
+        writer.finish()?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{gadgets, header_style::HeaderStyle};
+
+    fn header_of(circuit: &BristolCircuit) -> CircuitHeader {
+        CircuitHeader {
+            gate_count: circuit.gates.len(),
+            wire_count: circuit.wire_count,
+            input_widths: circuit.io_widths.0.clone(),
+            output_widths: circuit.io_widths.1.clone(),
+            header_style: HeaderStyle::NivList,
+        }
+    }
+
+    #[test]
+    fn test_matches_write_bristol() {
+        let circuit = gadgets::adder(4, true);
+
+        let mut text = Vec::new();
+        BristolCircuit::write_bristol_streamed(
+            &header_of(&circuit),
+            circuit.gates.iter().cloned(),
+            &mut text,
+        )
+        .unwrap();
+
+        assert_eq!(
+            String::from_utf8(text).unwrap(),
+            circuit.get_bristol_string().unwrap()
+        );
+    }
+
+    #[test]
+    fn test_gate_count_mismatch() {
+        let circuit = gadgets::adder(2, false);
+        let header = header_of(&circuit);
+
+        let err = BristolCircuit::write_bristol_streamed(
+            &header,
+            circuit.gates[1..].iter().cloned(),
+            &mut Vec::new(),
+        )
+        .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            format!(
+                "Inconsistency: Header declares {} gates but {} were written",
+                header.gate_count,
+                header.gate_count - 1
+            )
+        );
+
+        let mut writer = BristolWriter::new(Vec::new(), &header).unwrap();
+        for gate in &circuit.gates {
+            writer.write_gate(gate).unwrap();
+        }
+        let err = writer.write_gate(&circuit.gates[0]).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            format!(
+                "Inconsistency: Header declares {} gates but more were written",
+                header.gate_count
+            )
+        );
+    }
+
+    #[test]
+    fn test_gate_lines_match_display() {
+        use smallvec::smallvec;
+
+        let mut circuit = gadgets::adder(8, true);
+        circuit.gates.push(Gate {
+            inputs: smallvec![0, 1, 2, 3],
+            outputs: smallvec![4, 5],
+            op: "MAND".into(),
+        });
+        circuit.gates.push(Gate {
+            inputs: smallvec![usize::MAX, 10],
+            outputs: smallvec![0],
+            op: "LUT[e8]".into(),
+        });
+
+        let mut line = Vec::new();
+        for gate in &circuit.gates {
+            line.clear();
+            push_gate_line(&mut line, gate);
+            assert_eq!(
+                String::from_utf8(line.clone()).unwrap(),
+                format!("{}\n", gate)
+            );
+        }
+
+        circuit.wire_count = usize::MAX;
+        let text = circuit.get_bristol_string().unwrap();
+        let gate_lines = text
+            .lines()
+            .skip(text.lines().count() - circuit.gates.len());
+        for (written, gate) in gate_lines.zip(&circuit.gates) {
+            assert_eq!(written, gate.to_string());
+        }
+    }
+
+    #[test]
+    fn test_len_estimate() {
+        for circuit in [
+            gadgets::adder(1, false),
+            gadgets::adder(64, true),
+            gadgets::dot_product(8),
+        ] {
+            let len = circuit.get_bristol_string().unwrap().len();
+            let estimate = circuit.bristol_len_estimate();
+
+            assert!(estimate >= len, "{} < {}", estimate, len);
+            assert!(estimate < len + len / 4, "{} vs {}", estimate, len);
+        }
+    }
+}
@@ -0,0 +1,149 @@
+use std::collections::{HashMap, HashSet};
+
+use serde::Serialize;
+
+use crate::{
+    bristol_circuit::BristolCircuit,
+    gate::{Gate, GateInputs},
+    io_kind::IoKind,
+    op_properties::normalized_gate_key,
+    trace::trace_span,
+};
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize)]
+pub struct CommonSubexpressionReport {
+    pub gates_removed: usize,
+}
+
+impl BristolCircuit {
+    /// Removes gates that repeat an earlier gate's op and inputs (in either order, for
+    /// commutative ops), rewiring their consumers to the earlier gate's outputs, then compacts
+    /// the wires. Duplicates that write named outputs are kept, since outputs need their own
+    /// wires.
+    pub fn eliminate_common_subexpressions(&self) -> (BristolCircuit, CommonSubexpressionReport) {
+        let _span = trace_span!(
+            "pass",
+            name = "eliminate_common_subexpressions",
+            gate_count = self.gates.len()
+        );
+
+        let output_wires = self
+            .output_buses()
+            .into_iter()
+            .flat_map(|bus| bus.wire..bus.wire + bus.width)
+            .chain(
+                self.info
+                    .wire_groups
+                    .values()
+                    .filter(|group| group.direction == IoKind::Output)
+                    .flat_map(|group| group.span.wires()),
+            )
+            .collect::<HashSet<_>>();
+
+        let mut first = HashMap::<((String, Vec<usize>), usize), &[usize]>::new();
+        let mut replaced = HashMap::<usize, usize>::new();
+        let mut report = CommonSubexpressionReport::default();
+        let mut gates = Vec::with_capacity(self.gates.len());
+
+        for gate in &self.gates {
+            let inputs = gate
+                .inputs
+                .iter()
+                .map(|wire| replaced.get(wire).copied().unwrap_or(*wire))
+                .collect::<GateInputs>();
+            let key = (normalized_gate_key(&gate.op, &inputs), gate.outputs.len());
+
+            match first.get(&key) {
+                Some(outputs) if !gate.outputs.iter().any(|wire| output_wires.contains(wire)) => {
+                    replaced.extend(gate.outputs.iter().copied().zip(outputs.iter().copied()));
+                    report.gates_removed += 1;
+                    continue;
+                }
+                Some(_) => {}
+                None => {
+                    first.insert(key, &gate.outputs);
+                }
+            }
+
+            gates.push(Gate {
+                inputs,
+                ..gate.clone()
+            });
+        }
+
+        let mut result = self.clone();
+        result.gates = gates;
+
+        (result.compact_wires(), report)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::circuit_builder::CircuitBuilder;
+
+    /// `out = a * b + b * a - (a - b) - (b - a)`.
+    fn duplicated_multiply() -> BristolCircuit {
+        let mut b = CircuitBuilder::new();
+        let x = b.input("a");
+        let y = b.input("b");
+        let left = b.gate("AMul", &[x, y]);
+        let right = b.gate("AMul", &[y, x]);
+        let sum = b.gate("AAdd", &[left, right]);
+        let forward = b.gate("ASub", &[x, y]);
+        let backward = b.gate("ASub", &[y, x]);
+        let partial = b.gate("ASub", &[sum, forward]);
+        let out = b.gate("ASub", &[partial, backward]);
+        b.output("out", out);
+        b.build().unwrap()
+    }
+
+    #[test]
+    fn test_duplicate_multiply_removed() {
+        let circuit = duplicated_multiply();
+        let (result, report) = circuit.eliminate_common_subexpressions();
+
+        assert_eq!(report.gates_removed, 1);
+        assert_eq!(
+            result
+                .gates
+                .iter()
+                .map(|gate| gate.op.as_str())
+                .collect::<Vec<_>>(),
+            vec!["AMul", "AAdd", "ASub", "ASub", "ASub", "ASub"]
+        );
+        assert_eq!(result.gates[1].inputs[0], result.gates[1].inputs[1]);
+        assert_eq!(result.wire_count, circuit.wire_count - 1);
+        result.validate().unwrap();
+
+        for (a, b) in [(3, 5), (10, 2), (0, 7)] {
+            let inputs = HashMap::from([("a".to_string(), a), ("b".to_string(), b)]);
+            assert_eq!(
+                result.eval_arithmetic(&inputs, Some(101)).unwrap(),
+                circuit.eval_arithmetic(&inputs, Some(101)).unwrap()
+            );
+        }
+    }
+
+    #[test]
+    fn test_chained_duplicates_and_outputs() {
+        let mut b = CircuitBuilder::new();
+        let x = b.input("a");
+        let y = b.input("b");
+        let first = b.gate("AAdd", &[x, y]);
+        let second = b.gate("AAdd", &[x, y]);
+        let first_square = b.gate("AMul", &[first, first]);
+        let second_square = b.gate("AMul", &[second, second]);
+        b.output("first", first_square);
+        b.output("second", second_square);
+        let circuit = b.build().unwrap();
+
+        // The second sum goes, but the second square writes an output, so it stays.
+        let (result, report) = circuit.eliminate_common_subexpressions();
+        assert_eq!(report.gates_removed, 1);
+        assert_eq!(result.gates.len(), 3);
+        assert_eq!(result.gates[1].inputs, result.gates[2].inputs);
+        result.validate().unwrap();
+    }
+}
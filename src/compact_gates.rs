@@ -0,0 +1,185 @@
+//! A compact JSON form for gates, `["AAdd",[0,1],[2]]` instead of
+//! `{"inputs":[0,1],"outputs":[2],"op":"AAdd"}`, for use with `#[serde(with = "compact_gates")]`.
+//!
+//! Deserialization accepts both forms. `CompactJson` applies this to a whole `BristolCircuit`.
+
+use std::borrow::Borrow;
+
+use serde::{ser::SerializeSeq, Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::{
+    bristol_circuit::BristolCircuit,
+    circuit_info::CircuitInfo,
+    gate::{Gate, GateInputs, GateOutputs},
+    header_style::HeaderStyle,
+};
+
+pub fn serialize<S: Serializer>(gates: &[Gate], serializer: S) -> Result<S::Ok, S::Error> {
+    let mut seq = serializer.serialize_seq(Some(gates.len()))?;
+
+    for gate in gates {
+        seq.serialize_element(&(&gate.op, &gate.inputs, &gate.outputs))?;
+    }
+
+    seq.end()
+}
+
+pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<Gate>, D::Error> {
+    Ok(Vec::<AnyGate>::deserialize(deserializer)?
+        .into_iter()
+        .map(Gate::from)
+        .collect())
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum AnyGate {
+    Compact(String, GateInputs, GateOutputs),
+    Verbose(Gate),
+}
+
+impl From<AnyGate> for Gate {
+    fn from(gate: AnyGate) -> Gate {
+        match gate {
+            AnyGate::Compact(op, inputs, outputs) => Gate {
+                inputs,
+                outputs,
+                op: op.into(),
+            },
+            AnyGate::Verbose(gate) => gate,
+        }
+    }
+}
+
+/// Serializes a `BristolCircuit` with compact gates, omitting `io_widths` when there are none.
+///
+/// Deserializing accepts the output of either this or the plain `BristolCircuit` serialization.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CompactJson<T>(pub T);
+
+#[derive(Serialize)]
+struct CompactCircuit<'a> {
+    wire_count: usize,
+    info: &'a CircuitInfo,
+    #[serde(skip_serializing_if = "no_io_widths")]
+    io_widths: &'a (Vec<usize>, Vec<usize>),
+    #[serde(with = "self")]
+    gates: &'a [Gate],
+    header_style: HeaderStyle,
+}
+
+#[derive(Deserialize)]
+struct AnyCircuit {
+    wire_count: usize,
+    info: CircuitInfo,
+    #[serde(default)]
+    io_widths: (Vec<usize>, Vec<usize>),
+    #[serde(with = "self")]
+    gates: Vec<Gate>,
+    #[serde(default)]
+    header_style: HeaderStyle,
+}
+
+fn no_io_widths(io_widths: &&(Vec<usize>, Vec<usize>)) -> bool {
+    io_widths.0.is_empty() && io_widths.1.is_empty()
+}
+
+impl<T: Borrow<BristolCircuit>> Serialize for CompactJson<T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let circuit = self.0.borrow();
+
+        CompactCircuit {
+            wire_count: circuit.wire_count,
+            info: &circuit.info,
+            io_widths: &circuit.io_widths,
+            gates: &circuit.gates,
+            header_style: circuit.header_style,
+        }
+        .serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for CompactJson<BristolCircuit> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let circuit = AnyCircuit::deserialize(deserializer)?;
+
+        Ok(CompactJson(BristolCircuit {
+            wire_count: circuit.wire_count,
+            info: circuit.info,
+            io_widths: circuit.io_widths,
+            gates: circuit.gates,
+            header_style: circuit.header_style,
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use smallvec::smallvec;
+
+    use super::*;
+    use crate::gadgets;
+
+    #[test]
+    fn test_gate_form() {
+        let gate = Gate {
+            inputs: smallvec![0, 1],
+            outputs: smallvec![2],
+            op: "AAdd".into(),
+        };
+
+        #[derive(Serialize, Deserialize)]
+        struct Gates(#[serde(with = "super")] Vec<Gate>);
+
+        let json = serde_json::to_string(&Gates(vec![gate.clone()])).unwrap();
+        assert_eq!(json, r#"[["AAdd",[0,1],[2]]]"#);
+
+        let mixed = r#"[["AAdd",[0,1],[2]],{"inputs":[0,1],"outputs":[2],"op":"AAdd"}]"#;
+        let Gates(gates) = serde_json::from_str(mixed).unwrap();
+        assert_eq!(gates, vec![gate.clone(), gate]);
+    }
+
+    #[test]
+    fn test_round_trips() {
+        let circuit = gadgets::adder(16, true);
+
+        let compact = serde_json::to_string(&CompactJson(&circuit)).unwrap();
+        let verbose = serde_json::to_string(&circuit).unwrap();
+        assert!(
+            compact.len() * 2 < verbose.len(),
+            "compact {} vs verbose {}",
+            compact.len(),
+            verbose.len()
+        );
+
+        // compact -> struct -> verbose -> struct
+        let CompactJson(from_compact) = serde_json::from_str(&compact).unwrap();
+        assert_eq!(from_compact, circuit);
+        let reverbose = serde_json::to_string(&from_compact).unwrap();
+        assert_eq!(
+            serde_json::from_str::<BristolCircuit>(&reverbose).unwrap(),
+            circuit
+        );
+
+        // The compact reader accepts the verbose form too.
+        let CompactJson(from_verbose) = serde_json::from_str(&verbose).unwrap();
+        assert_eq!(from_verbose, circuit);
+    }
+
+    #[test]
+    fn test_empty_io_widths_omitted() {
+        let circuit = BristolCircuit {
+            wire_count: 0,
+            info: Default::default(),
+            io_widths: Default::default(),
+            gates: vec![],
+            header_style: Default::default(),
+        };
+
+        let json = serde_json::to_value(CompactJson(&circuit)).unwrap();
+        assert!(json.get("io_widths").is_none());
+
+        let CompactJson(parsed) = serde_json::from_value(json).unwrap();
+        assert_eq!(parsed, circuit);
+    }
+}
@@ -0,0 +1,128 @@
+use std::collections::{BTreeSet, HashMap};
+
+use crate::bristol_circuit::BristolCircuit;
+
+impl BristolCircuit {
+    /// Renumbers the wires densely, removing gaps. Wires keep their relative order (each gets its
+    /// rank among the referenced wires), so buses stay contiguous and a gap-free circuit is
+    /// unchanged apart from `wire_count`.
+    pub fn compact_wires(&self) -> BristolCircuit {
+        self.compact_wires_with_map().0
+    }
+
+    /// Like [`BristolCircuit::compact_wires`], also returning each referenced wire's new index.
+    pub fn compact_wires_with_map(&self) -> (BristolCircuit, HashMap<usize, usize>) {
+        let mapping = self
+            .used_wires()
+            .into_iter()
+            .enumerate()
+            .map(|(new, old)| (old, new))
+            .collect::<HashMap<_, _>>();
+
+        let mut result = self.clone();
+        result.map_wires(|wire| mapping[&wire]);
+        result.wire_count = mapping.len();
+
+        (result, mapping)
+    }
+
+    /// Every wire referenced by a gate or by the circuit info, including the full width of each
+    /// named input and output. `EQ` literals aren't wires, so don't count.
+    pub(crate) fn used_wires(&self) -> BTreeSet<usize> {
+        let mut wires = BTreeSet::new();
+
+        for gate in &self.gates {
+            wires.extend(gate.input_wires().iter().chain(&gate.outputs).copied());
+        }
+
+        for bus in self.input_buses().into_iter().chain(self.output_buses()) {
+            wires.extend(bus.wire..bus.wire + bus.width);
+        }
+
+        wires.extend(
+            self.info
+                .constants
+                .values()
+                .map(|constant| constant.wire_index),
+        );
+        wires.extend(
+            self.info
+                .wire_groups
+                .values()
+                .flat_map(|group| group.span.wires()),
+        );
+
+        wires
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{circuit_info::ConstantInfo, gadgets};
+
+    #[test]
+    fn test_closes_gaps() {
+        let circuit = gadgets::adder(2, true);
+        let mut gappy = circuit.clone();
+        gappy.map_wires(|wire| if wire < 4 { wire } else { wire + 96 });
+        gappy.info.constants.insert(
+            "one".to_string(),
+            ConstantInfo {
+                value: "1".to_string(),
+                wire_index: 300,
+            },
+        );
+        gappy.recompute_wire_count();
+        assert_eq!(gappy.wire_count, 301);
+
+        let (compacted, mapping) = gappy.compact_wires_with_map();
+        assert_eq!(compacted.wire_count, circuit.wire_count + 1);
+        compacted.assert_wire_count_tight();
+        assert_eq!(mapping[&100], 4);
+        assert_eq!(mapping[&300], circuit.wire_count);
+
+        let mut expected = circuit.clone();
+        expected.info.constants = compacted.info.constants.clone();
+        assert_eq!(compacted.gates, expected.gates);
+        assert_eq!(compacted.info, expected.info);
+        assert_eq!(
+            compacted.info.constants["one"].wire_index,
+            circuit.wire_count
+        );
+
+        let inputs = HashMap::from([
+            ("a".to_string(), vec![true, false]),
+            ("b".to_string(), vec![true, true]),
+        ]);
+        assert_eq!(
+            compacted.eval_boolean(&inputs).unwrap(),
+            circuit.eval_boolean(&inputs).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_keeps_eq_literals() {
+        let circuit = BristolCircuit::from_bristol_string("1 5\n0\n1 1\n\n1 1 1 4 EQ\n").unwrap();
+        assert_eq!(circuit.wire_count, 5);
+
+        let compacted = circuit.compact_wires();
+        assert_eq!(compacted.wire_count, 1);
+        assert_eq!(compacted.gates[0].to_string(), "1 1 1 0 EQ");
+        compacted.validate().unwrap();
+
+        let outputs = compacted.eval_boolean(&HashMap::new()).unwrap();
+        assert_eq!(outputs, circuit.eval_boolean(&HashMap::new()).unwrap());
+        assert_eq!(outputs["output0"], vec![true]);
+    }
+
+    #[test]
+    fn test_dense_circuit_is_unchanged() {
+        let circuit = gadgets::adder(4, false);
+        let (compacted, mapping) = circuit.compact_wires_with_map();
+
+        assert_eq!(compacted.gates, circuit.gates);
+        assert_eq!(compacted.wire_count, circuit.wire_count);
+        assert!(mapping.iter().all(|(old, new)| old == new));
+    }
+}
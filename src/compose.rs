@@ -0,0 +1,307 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::{
+    bristol_circuit::{BristolCircuit, IoBus},
+    bristol_circuit_error::BristolCircuitError,
+    circuit_info::CircuitInfo,
+};
+
+impl BristolCircuit {
+    /// Feeds outputs of this circuit into inputs of `other`. Each connection is an
+    /// `(output of self, input of other)` pair of names, whose widths must match.
+    ///
+    /// The composite's inputs are this circuit's inputs followed by `other`'s unconnected ones,
+    /// and its outputs are `other`'s outputs. This circuit's outputs are only kept as internal
+    /// wires. Inputs and constants that both circuits name the same way are renamed
+    /// `outer_<name>` and `inner_<name>`.
+    ///
+    /// Wires are numbered with the inputs first, then this circuit's other wires, then
+    /// `other`'s, so `other`'s outputs stay at the end.
+    pub fn compose(
+        &self,
+        other: &BristolCircuit,
+        connections: &[(&str, &str)],
+    ) -> Result<BristolCircuit, BristolCircuitError> {
+        let inconsistency = |message: String| BristolCircuitError::Inconsistency { message };
+
+        if !self.info.wire_groups.is_empty() || !other.info.wire_groups.is_empty() {
+            return Err(inconsistency(
+                "Circuits with wire groups can't be composed".to_string(),
+            ));
+        }
+
+        let outer_outputs = self.output_buses();
+        let inner_inputs = other.input_buses();
+
+        // The outer output feeding each connected inner input.
+        let mut feeds = HashMap::<&str, &IoBus>::new();
+
+        for &(output, input) in connections {
+            let source = find_bus(&outer_outputs, "output", output)?;
+            let target = find_bus(&inner_inputs, "input", input)?;
+
+            if source.width != target.width {
+                return Err(inconsistency(format!(
+                    "Output {} has width {} but input {} has width {}",
+                    output, source.width, input, target.width
+                )));
+            }
+
+            if feeds.insert(target.name, source).is_some() {
+                return Err(inconsistency(format!("Input {} is connected twice", input)));
+            }
+        }
+
+        let outer_inputs = self.input_buses();
+        let free_inputs = inner_inputs
+            .iter()
+            .filter(|bus| !feeds.contains_key(bus.name))
+            .collect::<Vec<_>>();
+
+        let outer_len = self.wire_count.max(self.referenced_wire_count());
+        let inner_len = other.wire_count.max(other.referenced_wire_count());
+        let mut outer_map = vec![None; outer_len];
+        let mut inner_map = vec![None; inner_len];
+        let mut next = 0;
+
+        let mut assign = |map: &mut Vec<Option<usize>>, wire: usize| {
+            if map[wire].is_none() {
+                map[wire] = Some(next);
+                next += 1;
+            }
+        };
+
+        for bus in &outer_inputs {
+            (bus.wire..bus.wire + bus.width).for_each(|wire| assign(&mut outer_map, wire));
+        }
+
+        for bus in &free_inputs {
+            (bus.wire..bus.wire + bus.width).for_each(|wire| assign(&mut inner_map, wire));
+        }
+
+        (0..outer_len).for_each(|wire| assign(&mut outer_map, wire));
+
+        for (input, source) in &feeds {
+            let target = inner_inputs.iter().find(|bus| bus.name == *input).unwrap();
+
+            for bit in 0..target.width {
+                inner_map[target.wire + bit] = outer_map[source.wire + bit];
+            }
+        }
+
+        (0..inner_len).for_each(|wire| assign(&mut inner_map, wire));
+
+        let mut outer = self.clone();
+        outer.map_wires(|wire| outer_map[wire].expect("every wire is mapped"));
+        let mut inner = other.clone();
+        inner.map_wires(|wire| inner_map[wire].expect("every wire is mapped"));
+
+        // Both circuits' inputs and constants share the composite's namespaces.
+        let outer_names = outer
+            .info
+            .input_name_to_wire_index
+            .keys()
+            .chain(outer.info.constants.keys())
+            .cloned()
+            .collect::<HashSet<_>>();
+        let inner_names = free_inputs
+            .iter()
+            .map(|bus| bus.name.to_string())
+            .chain(inner.info.constants.keys().cloned())
+            .collect::<HashSet<_>>();
+
+        let rename = |prefix: &str, name: &String| {
+            if outer_names.contains(name) && inner_names.contains(name) {
+                format!("{}_{}", prefix, name)
+            } else {
+                name.clone()
+            }
+        };
+
+        let mut info = CircuitInfo::default();
+
+        for (circuit, prefix, inputs) in [
+            (&outer, "outer", &outer_inputs.iter().collect::<Vec<_>>()),
+            (&inner, "inner", &free_inputs),
+        ] {
+            for bus in inputs {
+                let name = bus.name.to_string();
+                let renamed = rename(prefix, &name);
+
+                info.input_name_to_wire_index.insert(
+                    renamed.clone(),
+                    circuit.info.input_name_to_wire_index[&name],
+                );
+
+                if let Some(&owner) = circuit.info.input_owners.get(&name) {
+                    info.merge_input_owners(&HashMap::from([(renamed, owner)]))?;
+                }
+            }
+
+            for (name, constant) in &circuit.info.constants {
+                info.constants
+                    .insert(rename(prefix, name), constant.clone());
+            }
+        }
+
+        info.output_name_to_wire_index = inner.info.output_name_to_wire_index.clone();
+        info.merge_output_visibility(&inner.info.output_visibility)?;
+
+        let input_widths = outer_inputs
+            .iter()
+            .chain(free_inputs.iter().copied())
+            .map(|bus| bus.width)
+            .collect();
+
+        let mut gates = outer.gates;
+        gates.extend(inner.gates);
+
+        let mut composite = BristolCircuit {
+            wire_count: 0,
+            info,
+            io_widths: (input_widths, other.io_widths.1.clone()),
+            gates,
+            header_style: self.header_style,
+        };
+        composite.recompute_wire_count();
+
+        Ok(composite)
+    }
+}
+
+fn find_bus<'a, 'b>(
+    buses: &'b [IoBus<'a>],
+    kind: &str,
+    name: &str,
+) -> Result<&'b IoBus<'a>, BristolCircuitError> {
+    buses
+        .iter()
+        .find(|bus| bus.name == name)
+        .ok_or_else(|| BristolCircuitError::Inconsistency {
+            message: format!("No {} named {}", kind, name),
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{circuit_builder::CircuitBuilder, party::PartyId};
+
+    /// `sum = a + b` and `prod = a * b`.
+    fn sum_and_product() -> BristolCircuit {
+        let mut b = CircuitBuilder::new();
+        let x = b.input("a");
+        let y = b.input("b");
+        let sum = b.gate("AAdd", &[x, y]);
+        let prod = b.gate("AMul", &[x, y]);
+        b.output("sum", sum);
+        b.output("prod", prod);
+        b.build().unwrap()
+    }
+
+    /// `out = x * y - a`.
+    fn difference() -> BristolCircuit {
+        let mut b = CircuitBuilder::new();
+        let x = b.input("x");
+        let y = b.input("y");
+        let a = b.input("a");
+        let prod = b.gate("AMul", &[x, y]);
+        let out = b.gate("ASub", &[prod, a]);
+        b.output("out", out);
+        b.build().unwrap()
+    }
+
+    fn inputs(values: &[(&str, u64)]) -> HashMap<String, u64> {
+        values
+            .iter()
+            .map(|&(name, value)| (name.to_string(), value))
+            .collect()
+    }
+
+    #[test]
+    fn test_compose_matches_sequential() {
+        let outer = sum_and_product();
+        let inner = difference();
+        let composite = outer
+            .compose(&inner, &[("sum", "x"), ("prod", "y")])
+            .unwrap();
+
+        composite.validate().unwrap();
+        composite.check_topological_order().unwrap();
+        assert_eq!(composite.gates.len(), 4);
+        assert_eq!(
+            composite.info.inputs(),
+            vec![("outer_a", 0), ("b", 1), ("inner_a", 2)]
+        );
+
+        for (a, b, c) in [(3, 4, 5), (10, 0, 7), (1, 1, 100)] {
+            let first = outer
+                .eval_arithmetic(&inputs(&[("a", a), ("b", b)]), Some(101))
+                .unwrap();
+            let second = inner
+                .eval_arithmetic(
+                    &inputs(&[("x", first["sum"]), ("y", first["prod"]), ("a", c)]),
+                    Some(101),
+                )
+                .unwrap();
+
+            let combined = composite
+                .eval_arithmetic(
+                    &inputs(&[("outer_a", a), ("b", b), ("inner_a", c)]),
+                    Some(101),
+                )
+                .unwrap();
+            assert_eq!(combined, second);
+        }
+    }
+
+    #[test]
+    fn test_compose_errors() {
+        let outer = sum_and_product();
+        let inner = difference();
+        let compose = |connections: &[(&str, &str)]| {
+            outer.compose(&inner, connections).unwrap_err().to_string()
+        };
+
+        assert_eq!(
+            compose(&[("total", "x")]),
+            "Inconsistency: No output named total"
+        );
+        assert_eq!(compose(&[("sum", "z")]), "Inconsistency: No input named z");
+        assert_eq!(
+            compose(&[("sum", "x"), ("prod", "x")]),
+            "Inconsistency: Input x is connected twice"
+        );
+
+        let mut wide = CircuitBuilder::new();
+        let x = wide.input_bus("x", 2);
+        let y = wide.gate("XOR", &[x[0], x[1]]);
+        wide.output("y", y);
+        let err = outer
+            .compose(&wide.build().unwrap(), &[("sum", "x")])
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Inconsistency: Output sum has width 1 but input x has width 2"
+        );
+    }
+
+    #[test]
+    fn test_owner_conflicts() {
+        let mut outer = sum_and_product();
+        outer.info.input_owners.insert("a".to_string(), PartyId(0));
+
+        // Outer a becomes outer_a, which the inner circuit also has.
+        let mut inner = difference();
+        inner.info.rename_input("y", "outer_a").unwrap();
+        inner
+            .info
+            .input_owners
+            .insert("outer_a".to_string(), PartyId(1));
+        let err = outer.compose(&inner, &[("sum", "x")]).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Inconsistency: Input outer_a is owned by both party 0 and party 1"
+        );
+    }
+}
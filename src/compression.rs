@@ -0,0 +1,113 @@
+//! Gzip-compressed Bristol text, with the `compression` feature.
+
+#[cfg(feature = "compression")]
+use std::io::{BufRead, BufReader, Write};
+
+use crate::bristol_circuit_error::BristolCircuitError;
+#[cfg(feature = "compression")]
+use crate::{
+    bristol_circuit::BristolCircuit, circuit_info::CircuitInfo, parse_options::ParseOptions,
+};
+
+const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+
+/// Rejects input starting with the gzip magic bytes, which would otherwise fail as invalid
+/// UTF-8.
+pub(crate) fn reject_gzip(start: &[u8]) -> Result<(), BristolCircuitError> {
+    if !start.starts_with(&GZIP_MAGIC) {
+        return Ok(());
+    }
+
+    let hint = if cfg!(feature = "compression") {
+        "read it with read_info_and_bristol_gz"
+    } else {
+        "reading it needs the `compression` feature"
+    };
+
+    Err(BristolCircuitError::ParsingError {
+        message: format!("Input is gzip-compressed; {}", hint),
+    })
+}
+
+#[cfg(feature = "compression")]
+impl BristolCircuit {
+    /// Like `read_info_and_bristol`, decompressing the input if it starts with the gzip magic
+    /// bytes. Uncompressed input is read as is.
+    pub fn read_info_and_bristol_gz<R: BufRead>(
+        info: &CircuitInfo,
+        r: &mut R,
+    ) -> Result<BristolCircuit, BristolCircuitError> {
+        BristolCircuit::read_info_and_bristol_gz_with_options(info, r, &ParseOptions::default())
+    }
+
+    pub fn read_info_and_bristol_gz_with_options<R: BufRead>(
+        info: &CircuitInfo,
+        r: &mut R,
+        options: &ParseOptions,
+    ) -> Result<BristolCircuit, BristolCircuitError> {
+        if !r.fill_buf()?.starts_with(&GZIP_MAGIC) {
+            return BristolCircuit::read_info_and_bristol_with_options(info, r, options);
+        }
+
+        let mut decoder = BufReader::new(flate2::bufread::MultiGzDecoder::new(r));
+        BristolCircuit::read_info_and_bristol_with_options(info, &mut decoder, options)
+    }
+
+    /// Writes the Bristol text gzip-compressed, at `level` from 0 (none) to 9 (best).
+    pub fn write_bristol_gz<W: Write>(
+        &self,
+        w: &mut W,
+        level: u32,
+    ) -> Result<(), BristolCircuitError> {
+        let mut encoder = flate2::write::GzEncoder::new(w, flate2::Compression::new(level.min(9)));
+        self.write_bristol(&mut encoder)?;
+        encoder.finish()?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{bristol_circuit::BristolCircuit, gadgets};
+
+    #[cfg(feature = "compression")]
+    #[test]
+    fn test_gz_round_trip() {
+        let circuit = gadgets::adder(16, true);
+        let text = circuit.get_bristol_string().unwrap();
+
+        let mut compressed = Vec::new();
+        circuit.write_bristol_gz(&mut compressed, 9).unwrap();
+        assert!(compressed.len() < text.len() / 2);
+
+        let read = BristolCircuit::read_info_and_bristol_gz(&circuit.info, &mut &compressed[..]);
+        assert_eq!(read.unwrap(), circuit);
+
+        let plain = BristolCircuit::read_info_and_bristol_gz(&circuit.info, &mut text.as_bytes());
+        assert_eq!(plain.unwrap(), circuit);
+    }
+
+    #[test]
+    fn test_plain_reader_rejects_gzip() {
+        let circuit = gadgets::adder(2, false);
+        let gzipped = [0x1f, 0x8b, 0x08, 0x00, 0x00];
+
+        let err =
+            BristolCircuit::read_info_and_bristol(&circuit.info, &mut &gzipped[..]).unwrap_err();
+        assert!(
+            err.to_string()
+                .starts_with("Parsing error: Input is gzip-compressed;"),
+            "{}",
+            err
+        );
+
+        let err = BristolCircuit::from_info_and_bristol_bytes(
+            &circuit.info,
+            &gzipped,
+            Default::default(),
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("gzip-compressed"), "{}", err);
+    }
+}
@@ -0,0 +1,321 @@
+use std::{
+    collections::{HashMap, HashSet},
+    str::FromStr,
+};
+
+use crate::{
+    a_gate_type::AGateType, bristol_circuit::BristolCircuit,
+    bristol_circuit_error::BristolCircuitError, circuit_info::ConstantInfo, eval::evaluation_error,
+    io_kind::IoKind, trace::trace_span,
+};
+
+impl BristolCircuit {
+    /// Evaluates the gates whose inputs are all constants, replacing them with new constants
+    /// (named `folded_<wire>`) and dropping the gates and constants that are no longer needed.
+    ///
+    /// Folding understands the arithmetic ops and the boolean `AND`, `XOR` and `INV`/`NOT`, on
+    /// non-negative integers. Anything else (other ops, non-numeric constants, results that
+    /// would be negative, or an `ADiv` with a remainder, whose value depends on the field) is
+    /// left alone. A result too large for an `i128`, or a division by zero, is an error.
+    pub fn fold_constants(&self) -> Result<BristolCircuit, BristolCircuitError> {
+        let _span = trace_span!(
+            "pass",
+            name = "fold_constants",
+            gate_count = self.gates.len()
+        );
+
+        let mut known = HashMap::new();
+
+        for constant in self.info.constants.values() {
+            if let Some(value) = parse_constant(&constant.value) {
+                known.insert(constant.wire_index, value);
+            }
+        }
+
+        let mut folded = HashSet::new();
+
+        for (i, gate) in self.gates.iter().enumerate() {
+            let Some(inputs) = gate
+                .inputs
+                .iter()
+                .map(|wire| known.get(wire).copied())
+                .collect::<Option<Vec<_>>>()
+            else {
+                continue;
+            };
+
+            if gate.outputs.len() != 1 {
+                continue;
+            }
+
+            if let Some(value) = fold_gate(&gate.op, &inputs, i)? {
+                known.insert(gate.outputs[0], value);
+                folded.insert(i);
+            }
+        }
+
+        let mut needed = self
+            .output_buses()
+            .into_iter()
+            .flat_map(|bus| bus.wire..bus.wire + bus.width)
+            .chain(
+                self.info
+                    .wire_groups
+                    .values()
+                    .filter(|group| group.direction == IoKind::Output)
+                    .flat_map(|group| group.span.wires()),
+            )
+            .collect::<HashSet<_>>();
+
+        for (i, gate) in self.gates.iter().enumerate() {
+            if !folded.contains(&i) {
+                needed.extend(&gate.inputs);
+            }
+        }
+
+        let mut result = self.clone();
+
+        // Constants only the folded gates read are dropped.
+        let folded_reads = folded
+            .iter()
+            .flat_map(|&i| &self.gates[i].inputs)
+            .collect::<HashSet<_>>();
+        result.info.constants.retain(|_, constant| {
+            needed.contains(&constant.wire_index) || !folded_reads.contains(&constant.wire_index)
+        });
+
+        let mut folded = folded.into_iter().collect::<Vec<_>>();
+        folded.sort();
+
+        for &i in &folded {
+            let wire = self.gates[i].outputs[0];
+
+            if needed.contains(&wire) {
+                let mut name = format!("folded_{}", wire);
+
+                while result.info.constants.contains_key(&name) {
+                    name.push('_');
+                }
+
+                result.info.constants.insert(
+                    name,
+                    ConstantInfo {
+                        value: known[&wire].to_string(),
+                        wire_index: wire,
+                    },
+                );
+            }
+        }
+
+        result.gates = self
+            .gates
+            .iter()
+            .enumerate()
+            .filter(|(i, _)| folded.binary_search(i).is_err())
+            .map(|(_, gate)| gate.clone())
+            .collect();
+
+        Ok(result.prune_dead_gates())
+    }
+}
+
+fn parse_constant(value: &str) -> Option<i128> {
+    match value {
+        "true" => Some(1),
+        "false" => Some(0),
+        _ => i128::from_str(value).ok().filter(|&value| value >= 0),
+    }
+}
+
+/// The value of gate `gate_index`, or `None` if it can't be folded.
+fn fold_gate(
+    op: &str,
+    inputs: &[i128],
+    gate_index: usize,
+) -> Result<Option<i128>, BristolCircuitError> {
+    let overflow = || evaluation_error(format!("Gate {} ({}) overflows", gate_index, op));
+    let bit = |value: i128| (value == 0 || value == 1).then_some(value);
+
+    match (op, inputs) {
+        ("AND", &[a, b]) => return Ok(bit(a).zip(bit(b)).map(|(a, b)| a & b)),
+        ("XOR", &[a, b]) => return Ok(bit(a).zip(bit(b)).map(|(a, b)| a ^ b)),
+        ("INV" | "NOT", &[a]) => return Ok(bit(a).map(|a| 1 - a)),
+        _ => {}
+    }
+
+    let Ok(op_type) = AGateType::from_str(op) else {
+        return Ok(None);
+    };
+
+    use AGateType::*;
+
+    let (a, b) = match (op_type, inputs) {
+        (AMux, &[sel, a, b]) => {
+            return Ok(match sel {
+                0 => Some(b),
+                1 => Some(a),
+                _ => None,
+            })
+        }
+        (AMux, _) => return Ok(None),
+        (_, &[a, b]) => (a, b),
+        _ => return Ok(None),
+    };
+
+    let divisor = || {
+        if b == 0 {
+            Err(evaluation_error(format!(
+                "Gate {} ({}) divides by zero",
+                gate_index, op
+            )))
+        } else {
+            Ok(b)
+        }
+    };
+    let flag = |value: bool| Some(i128::from(value));
+
+    let value = match op_type {
+        AAdd => Some(a.checked_add(b).ok_or_else(overflow)?),
+        ASub => Some(a - b).filter(|&value| value >= 0),
+        AMul => Some(a.checked_mul(b).ok_or_else(overflow)?),
+        ADiv => {
+            let b = divisor()?;
+            (a % b == 0).then_some(a / b)
+        }
+        AIntDiv => Some(a / divisor()?),
+        AMod => Some(a % divisor()?),
+        APow => {
+            let exponent = u32::try_from(b).map_err(|_| overflow())?;
+            Some(a.checked_pow(exponent).ok_or_else(overflow)?)
+        }
+        AEq => flag(a == b),
+        ANeq => flag(a != b),
+        ALt => flag(a < b),
+        ALEq => flag(a <= b),
+        AGt => flag(a > b),
+        AGEq => flag(a >= b),
+        AXor => Some(a ^ b),
+        ABitOr => Some(a | b),
+        ABitAnd => Some(a & b),
+        ABoolOr => flag(a != 0 || b != 0),
+        ABoolAnd => flag(a != 0 && b != 0),
+        AShiftL => {
+            let shift = u32::try_from(b).map_err(|_| overflow())?;
+            let value = a.checked_shl(shift).ok_or_else(overflow)?;
+
+            if value >> shift != a {
+                return Err(overflow());
+            }
+
+            Some(value)
+        }
+        AShiftR => Some(
+            u32::try_from(b)
+                .ok()
+                .and_then(|b| a.checked_shr(b))
+                .unwrap_or(0),
+        ),
+        AMux => unreachable!("handled above"),
+    };
+
+    Ok(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::circuit_builder::CircuitBuilder;
+
+    /// `out = (two + three) * x`, with `op` in place of `AAdd`.
+    fn scaled(op: &str, two: &str, three: &str) -> BristolCircuit {
+        let mut b = CircuitBuilder::new();
+        let x = b.input("x");
+        let two = b.constant("two", two);
+        let three = b.constant("three", three);
+        let sum = b.gate(op, &[two, three]);
+        let out = b.gate("AMul", &[sum, x]);
+        b.output("out", out);
+        b.build().unwrap()
+    }
+
+    fn eval(circuit: &BristolCircuit, x: u64) -> u64 {
+        circuit
+            .eval_arithmetic(&HashMap::from([("x".to_string(), x)]), None)
+            .unwrap()["out"]
+    }
+
+    #[test]
+    fn test_fold_sum() {
+        let circuit = scaled("AAdd", "2", "3");
+        let folded = circuit.fold_constants().unwrap();
+
+        assert_eq!(folded.gates.len(), 1);
+        assert_eq!(folded.gates[0].op, "AMul");
+        assert_eq!(folded.info.constants.len(), 1);
+
+        let five = folded.info.constants.values().next().unwrap();
+        assert_eq!(five.value, "5");
+        assert_eq!(folded.gates[0].inputs[..], [five.wire_index, 0]);
+        assert_eq!(eval(&folded, 7), eval(&circuit, 7));
+        folded.validate().unwrap();
+    }
+
+    #[test]
+    fn test_blocked() {
+        for circuit in [
+            scaled("FOO", "2", "3"),
+            scaled("ASub", "2", "3"),
+            scaled("ADiv", "2", "3"),
+            scaled("AAdd", "2", "x"),
+        ] {
+            assert_eq!(circuit.fold_constants().unwrap(), circuit);
+        }
+
+        let folded = scaled("ADiv", "6", "3").fold_constants().unwrap();
+        assert_eq!(folded.gates.len(), 1);
+        assert_eq!(eval(&folded, 7), 14);
+    }
+
+    #[test]
+    fn test_overflow() {
+        let err = scaled("APow", "2", "127").fold_constants().unwrap_err();
+        assert_eq!(err.to_string(), "Evaluation error: Gate 0 (APow) overflows");
+
+        let err = scaled("AShiftL", "3", "126").fold_constants().unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Evaluation error: Gate 0 (AShiftL) overflows"
+        );
+
+        let err = scaled("AMod", "2", "0").fold_constants().unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Evaluation error: Gate 0 (AMod) divides by zero"
+        );
+    }
+
+    #[test]
+    fn test_fold_boolean() {
+        let mut b = CircuitBuilder::new();
+        let x = b.input("x");
+        let one = b.constant("one", "1");
+        let zero = b.constant("zero", "false");
+        let not_zero = b.gate("INV", &[zero]);
+        let both = b.gate("AND", &[one, not_zero]);
+        let out = b.gate("XOR", &[x, both]);
+        b.output("out", out);
+        let circuit = b.build().unwrap();
+
+        let folded = circuit.fold_constants().unwrap();
+        assert_eq!(folded.gates.len(), 1);
+        assert_eq!(folded.info.constants.values().next().unwrap().value, "1");
+
+        for x in [false, true] {
+            let inputs = HashMap::from([("x".to_string(), vec![x])]);
+            assert_eq!(
+                folded.eval_boolean(&inputs).unwrap(),
+                circuit.eval_boolean(&inputs).unwrap()
+            );
+        }
+    }
+}
@@ -0,0 +1,227 @@
+use std::{collections::HashMap, fmt::Display};
+
+#[cfg(feature = "bigint")]
+use num_bigint::BigUint;
+
+use crate::{
+    bristol_circuit_error::BristolCircuitError,
+    circuit_info::{CircuitInfo, ConstantInfo},
+};
+
+/// A numeric type constant values can be read as. Values are decimal or `0x` hex, optionally
+/// with a leading minus, like `42`, `0xff` or `-0x10`.
+pub trait ConstantValue: Sized {
+    /// The type's name, for errors.
+    const TYPE_NAME: &'static str;
+
+    /// The value with magnitude `digits` in `radix`, which are already checked to be valid
+    /// digits, or `None` if it's out of range.
+    fn from_digits(negative: bool, digits: &str, radix: u32) -> Option<Self>;
+}
+
+impl ConstantValue for u64 {
+    const TYPE_NAME: &'static str = "u64";
+
+    fn from_digits(negative: bool, digits: &str, radix: u32) -> Option<Self> {
+        let magnitude = u64::from_str_radix(digits, radix).ok()?;
+        (!negative || magnitude == 0).then_some(magnitude)
+    }
+}
+
+impl ConstantValue for i128 {
+    const TYPE_NAME: &'static str = "i128";
+
+    fn from_digits(negative: bool, digits: &str, radix: u32) -> Option<Self> {
+        let magnitude = u128::from_str_radix(digits, radix).ok()?;
+
+        match negative {
+            true => 0i128.checked_sub_unsigned(magnitude),
+            false => i128::try_from(magnitude).ok(),
+        }
+    }
+}
+
+#[cfg(feature = "bigint")]
+impl ConstantValue for BigUint {
+    const TYPE_NAME: &'static str = "BigUint";
+
+    fn from_digits(negative: bool, digits: &str, radix: u32) -> Option<Self> {
+        let magnitude = BigUint::parse_bytes(digits.as_bytes(), radix)?;
+        (!negative || magnitude == BigUint::default()).then_some(magnitude)
+    }
+}
+
+impl ConstantInfo {
+    pub fn from_u64(value: u64, wire_index: usize) -> ConstantInfo {
+        ConstantInfo {
+            value: value.to_string(),
+            wire_index,
+        }
+    }
+
+    pub fn from_i128(value: i128, wire_index: usize) -> ConstantInfo {
+        ConstantInfo {
+            value: value.to_string(),
+            wire_index,
+        }
+    }
+
+    #[cfg(feature = "bigint")]
+    pub fn from_biguint(value: &BigUint, wire_index: usize) -> ConstantInfo {
+        ConstantInfo {
+            value: value.to_string(),
+            wire_index,
+        }
+    }
+
+    pub fn as_u64(&self) -> Result<u64, BristolCircuitError> {
+        self.value_as()
+    }
+
+    pub fn as_i128(&self) -> Result<i128, BristolCircuitError> {
+        self.value_as()
+    }
+
+    #[cfg(feature = "bigint")]
+    pub fn as_biguint(&self) -> Result<BigUint, BristolCircuitError> {
+        self.value_as()
+    }
+
+    /// The value as any [`ConstantValue`], e.g. `constant.value_as::<u64>()`.
+    pub fn value_as<T: ConstantValue>(&self) -> Result<T, BristolCircuitError> {
+        self.parse(format_args!("Constant on wire {}", self.wire_index))
+    }
+
+    /// Parses the value, describing the constant as `label` in errors.
+    fn parse<T: ConstantValue>(&self, label: impl Display) -> Result<T, BristolCircuitError> {
+        let error = |problem: &str| BristolCircuitError::ParsingError {
+            message: format!("{} {}", label, problem),
+        };
+
+        let value = self.value.as_str();
+        let (negative, unsigned) = match value.strip_prefix('-') {
+            Some(rest) => (true, rest),
+            None => (false, value),
+        };
+
+        let (digits, radix) = match unsigned.strip_prefix("0x") {
+            Some(hex) => (hex, 16),
+            None => (unsigned, 10),
+        };
+
+        if digits.is_empty() || !digits.chars().all(|c| c.is_digit(radix)) {
+            return Err(error(&format!("has non-numeric value {:?}", value)));
+        }
+
+        T::from_digits(negative, digits, radix).ok_or_else(|| {
+            error(&format!(
+                "has value {} out of range for {}",
+                value,
+                T::TYPE_NAME
+            ))
+        })
+    }
+}
+
+impl CircuitInfo {
+    /// Every constant's value by name, as `T`. The first bad constant by name is reported.
+    pub fn constant_values<T: ConstantValue>(
+        &self,
+    ) -> Result<HashMap<String, T>, BristolCircuitError> {
+        let mut constants = self.constants.iter().collect::<Vec<_>>();
+        constants.sort_by_key(|&(name, _)| name);
+
+        constants
+            .into_iter()
+            .map(|(name, constant)| {
+                let value = constant.parse(format_args!("Constant {}", name))?;
+                Ok((name.clone(), value))
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn constant(value: &str) -> ConstantInfo {
+        ConstantInfo {
+            value: value.to_string(),
+            wire_index: 3,
+        }
+    }
+
+    #[test]
+    fn test_parses_decimal_and_hex() {
+        assert_eq!(constant("42").as_u64().unwrap(), 42);
+        assert_eq!(constant("0xff").as_u64().unwrap(), 255);
+        assert_eq!(constant("0xFfFf").as_u64().unwrap(), 0xffff);
+        assert_eq!(constant("-0").as_u64().unwrap(), 0);
+        assert_eq!(constant("-0x10").as_i128().unwrap(), -16);
+        assert_eq!(
+            constant(&i128::MIN.to_string()).as_i128().unwrap(),
+            i128::MIN
+        );
+
+        assert_eq!(ConstantInfo::from_u64(7, 3), constant("7"));
+        assert_eq!(ConstantInfo::from_i128(-7, 3).as_i128().unwrap(), -7);
+    }
+
+    #[test]
+    fn test_errors() {
+        let cases = [
+            ("seven", "has non-numeric value \"seven\""),
+            ("", "has non-numeric value \"\""),
+            ("0x", "has non-numeric value \"0x\""),
+            ("+5", "has non-numeric value \"+5\""),
+            ("0x1g", "has non-numeric value \"0x1g\""),
+            ("-1", "has value -1 out of range for u64"),
+            (
+                "0x10000000000000000",
+                "has value 0x10000000000000000 out of range for u64",
+            ),
+        ];
+
+        for (value, problem) in cases {
+            assert_eq!(
+                constant(value).as_u64().unwrap_err().to_string(),
+                format!("Parsing error: Constant on wire 3 {}", problem)
+            );
+        }
+
+        let err = constant(&u128::MAX.to_string()).as_i128().unwrap_err();
+        assert!(
+            err.to_string().ends_with("out of range for i128"),
+            "{}",
+            err
+        );
+
+        let info = CircuitInfo {
+            constants: HashMap::from([
+                ("one".to_string(), constant("1")),
+                ("big".to_string(), constant("0x1_0000")),
+            ]),
+            ..Default::default()
+        };
+        assert_eq!(
+            info.constant_values::<u64>().unwrap_err().to_string(),
+            "Parsing error: Constant big has non-numeric value \"0x1_0000\""
+        );
+    }
+
+    #[cfg(feature = "bigint")]
+    #[test]
+    fn test_biguint() {
+        let huge = constant("0x1000000000000000000000000000000000");
+        assert_eq!(huge.as_biguint().unwrap(), BigUint::from(1u32) << 132);
+        assert!(huge.as_i128().is_err());
+        assert_eq!(
+            ConstantInfo::from_biguint(&(BigUint::from(1u32) << 132), 3)
+                .as_biguint()
+                .unwrap(),
+            huge.as_biguint().unwrap()
+        );
+        assert!(constant("-3").as_biguint().is_err());
+    }
+}
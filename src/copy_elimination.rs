@@ -0,0 +1,372 @@
+use std::collections::HashMap;
+
+use serde::Serialize;
+
+use crate::{
+    bristol_circuit::BristolCircuit, gate::Gate, io_kind::IoKind, trace::trace_span,
+    wire_group::WireSpan,
+};
+
+/// What to do with a copy whose source and destination are both named outputs. Removing it would
+/// make two outputs share a wire.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum OutputCopies {
+    #[default]
+    Keep,
+
+    /// Remove the copy and point both output names at the same wire.
+    Alias,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize)]
+pub struct CopyEliminationReport {
+    pub removed: usize,
+
+    /// Copies that had to stay, either because they are load-bearing output copies or because
+    /// they write one bit of a multi-bit output.
+    pub kept: usize,
+}
+
+/// The ops [`BristolCircuit::propagate_copies`] treats as copies.
+pub const DEFAULT_COPY_OPS: &[&str] = &["EQW"];
+
+impl BristolCircuit {
+    /// Removes `EQW` gates, rewiring their consumers and outputs to the copied wire, then
+    /// compacts the wires.
+    pub fn propagate_copies(&self) -> BristolCircuit {
+        self.propagate_copies_with_ops(DEFAULT_COPY_OPS)
+    }
+
+    /// Like [`BristolCircuit::propagate_copies`], treating the gates with an op in `copy_ops` as
+    /// copies.
+    pub fn propagate_copies_with_ops(&self, copy_ops: &[&str]) -> BristolCircuit {
+        self.eliminate_copies(copy_ops).compact_wires()
+    }
+
+    /// Removes single-input single-output gates with an op in `copy_ops` (e.g. `EQW`, `BUF`) by
+    /// rewiring their consumers to the copied wire.
+    pub fn eliminate_copies(&self, copy_ops: &[&str]) -> BristolCircuit {
+        self.eliminate_copies_with_report(copy_ops, OutputCopies::Keep)
+            .0
+    }
+
+    pub fn eliminate_copies_with_report(
+        &self,
+        copy_ops: &[&str],
+        output_copies: OutputCopies,
+    ) -> (BristolCircuit, CopyEliminationReport) {
+        let _span = trace_span!(
+            "pass",
+            name = "eliminate_copies",
+            gate_count = self.gates.len()
+        );
+
+        let output_width_by_wire = self
+            .output_buses()
+            .into_iter()
+            .flat_map(|bus| (bus.wire..bus.wire + bus.width).map(move |wire| (wire, bus.width)))
+            .collect::<HashMap<_, _>>();
+
+        let mut sources = HashMap::<usize, usize>::new();
+        let mut report = CopyEliminationReport::default();
+        let mut kept_gates = Vec::with_capacity(self.gates.len());
+
+        for gate in &self.gates {
+            if !is_copy(gate, copy_ops) {
+                kept_gates.push(gate);
+                continue;
+            }
+
+            let source = resolve(&sources, gate.inputs[0]);
+            let destination = gate.outputs[0];
+
+            let removable = match output_width_by_wire.get(&destination) {
+                None => true,
+                Some(&width) if width > 1 => false,
+                Some(_) => {
+                    !output_width_by_wire.contains_key(&source)
+                        || output_copies == OutputCopies::Alias
+                }
+            };
+
+            if removable {
+                sources.insert(destination, source);
+                report.removed += 1;
+            } else {
+                kept_gates.push(gate);
+                report.kept += 1;
+            }
+        }
+
+        let mut result = self.clone();
+
+        result.gates = kept_gates
+            .into_iter()
+            .map(|gate| Gate {
+                inputs: gate
+                    .inputs
+                    .iter()
+                    .map(|&wire| resolve(&sources, wire))
+                    .collect(),
+                ..gate.clone()
+            })
+            .collect();
+
+        // Repointed outputs can move past others, so their widths are relisted in the new order.
+        let output_widths = self.output_widths_by_name();
+
+        for wire in result.info.output_name_to_wire_index.values_mut() {
+            *wire = resolve(&sources, *wire);
+        }
+
+        result.set_output_widths(&output_widths);
+
+        for constant in result.info.constants.values_mut() {
+            constant.wire_index = resolve(&sources, constant.wire_index);
+        }
+
+        for group in result.info.wire_groups.values_mut() {
+            if group.direction == IoKind::Output {
+                let wires = group.span.wires();
+                group.span = WireSpan::from_wires(
+                    wires
+                        .into_iter()
+                        .map(|wire| resolve(&sources, wire))
+                        .collect(),
+                );
+            }
+        }
+
+        result.recompute_wire_count();
+
+        (result, report)
+    }
+}
+
+fn is_copy(gate: &Gate, copy_ops: &[&str]) -> bool {
+    gate.inputs.len() == 1 && gate.outputs.len() == 1 && copy_ops.contains(&gate.op.as_str())
+}
+
+/// Follows chains of removed copies back to the original wire.
+fn resolve(sources: &HashMap<usize, usize>, mut wire: usize) -> usize {
+    while let Some(&source) = sources.get(&wire) {
+        wire = source;
+    }
+
+    wire
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+
+    const COPY_OPS: &[&str] = &["EQW", "BUF"];
+
+    fn gate(op: &str, inputs: &[usize], outputs: &[usize]) -> Gate {
+        Gate {
+            inputs: inputs.into(),
+            outputs: outputs.into(),
+            op: op.into(),
+        }
+    }
+
+    /// Single-bit inputs a (wire 0) and b (wire 1), with the given (name, wire, width) outputs.
+    fn circuit(gates: Vec<Gate>, outputs: &[(&str, usize, usize)]) -> BristolCircuit {
+        let mut circuit = BristolCircuit {
+            wire_count: 0,
+            info: Default::default(),
+            io_widths: (vec![1, 1], outputs.iter().map(|o| o.2).collect()),
+            gates,
+            header_style: Default::default(),
+        };
+
+        circuit
+            .info
+            .input_name_to_wire_index
+            .extend([("a".to_string(), 0), ("b".to_string(), 1)]);
+
+        for (name, wire, _) in outputs {
+            circuit
+                .info
+                .output_name_to_wire_index
+                .insert(name.to_string(), *wire);
+        }
+
+        circuit.recompute_wire_count();
+        circuit
+    }
+
+    fn assert_equivalent(left: &BristolCircuit, right: &BristolCircuit) {
+        for bits in 0..4 {
+            let inputs = HashMap::from([
+                ("a".to_string(), vec![bits & 1 != 0]),
+                ("b".to_string(), vec![bits & 2 != 0]),
+            ]);
+
+            assert_eq!(
+                left.eval_boolean(&inputs).unwrap(),
+                right.eval_boolean(&inputs).unwrap()
+            );
+        }
+    }
+
+    #[test]
+    fn test_copy_chain() {
+        let original = circuit(
+            vec![
+                gate("AND", &[0, 1], &[2]),
+                gate("EQW", &[2], &[3]),
+                gate("BUF", &[3], &[4]),
+                gate("XOR", &[4, 0], &[5]),
+            ],
+            &[("out", 5, 1)],
+        );
+
+        let (result, report) = original.eliminate_copies_with_report(COPY_OPS, OutputCopies::Keep);
+
+        assert_eq!(
+            report,
+            CopyEliminationReport {
+                removed: 2,
+                kept: 0
+            }
+        );
+        assert_eq!(
+            result.gates,
+            vec![gate("AND", &[0, 1], &[2]), gate("XOR", &[2, 0], &[5])]
+        );
+        assert_equivalent(&original, &result);
+    }
+
+    #[test]
+    fn test_output_copy_is_rewired() {
+        let original = circuit(
+            vec![gate("OR", &[0, 1], &[2]), gate("EQW", &[2], &[3])],
+            &[("out", 3, 1)],
+        );
+
+        let result = original.eliminate_copies(COPY_OPS);
+
+        assert_eq!(result.gates, vec![gate("OR", &[0, 1], &[2])]);
+        assert_eq!(result.info.output_name_to_wire_index["out"], 2);
+        assert_eq!(result.wire_count, 3);
+        assert_equivalent(&original, &result);
+    }
+
+    #[test]
+    fn test_repointed_output_moves_past_another() {
+        // x, a copy of input a, moves from after the 3-bit y to wire 0, before it.
+        let original = circuit(
+            vec![
+                gate("AND", &[0, 1], &[2]),
+                gate("XOR", &[0, 1], &[3]),
+                gate("OR", &[0, 1], &[4]),
+                gate("EQW", &[0], &[5]),
+            ],
+            &[("y", 2, 3), ("x", 5, 1)],
+        );
+
+        let result = original.eliminate_copies(COPY_OPS);
+        assert_eq!(result.info.output_name_to_wire_index["x"], 0);
+        assert_eq!(result.io_widths.1, vec![1, 3]);
+        assert_equivalent(&original, &result);
+    }
+
+    #[test]
+    fn test_load_bearing_output_copy() {
+        let original = circuit(
+            vec![gate("XOR", &[0, 1], &[2]), gate("EQW", &[2], &[3])],
+            &[("x", 2, 1), ("y", 3, 1)],
+        );
+
+        let (kept, report) = original.eliminate_copies_with_report(COPY_OPS, OutputCopies::Keep);
+        assert_eq!(kept, original);
+        assert_eq!(report.kept, 1);
+
+        let (aliased, report) =
+            original.eliminate_copies_with_report(COPY_OPS, OutputCopies::Alias);
+        assert_eq!(report.removed, 1);
+        assert_eq!(aliased.info.output_name_to_wire_index["y"], 2);
+        assert_equivalent(&original, &aliased);
+    }
+
+    #[test]
+    fn test_multi_bit_output_copy_is_kept() {
+        let original = circuit(
+            vec![
+                gate("AND", &[0, 1], &[2]),
+                gate("EQW", &[2], &[3]),
+                gate("INV", &[2], &[4]),
+            ],
+            &[("out", 3, 2)],
+        );
+
+        let (result, report) = original.eliminate_copies_with_report(COPY_OPS, OutputCopies::Alias);
+        assert_eq!(result, original);
+        assert_eq!(report.kept, 1);
+    }
+
+    #[test]
+    fn test_propagate_copies() {
+        let original = circuit(
+            vec![
+                gate("EQW", &[0], &[2]),
+                gate("EQW", &[2], &[3]),
+                gate("EQW", &[3], &[4]),
+                gate("AND", &[4, 1], &[5]),
+                gate("EQW", &[1], &[6]),
+            ],
+            &[("and", 5, 1), ("b", 6, 1)],
+        );
+
+        let result = original.propagate_copies();
+        assert_eq!(result.gates, vec![gate("AND", &[0, 1], &[2])]);
+        assert_eq!(result.wire_count, 3);
+        assert_eq!(result.info.output_name_to_wire_index["and"], 2);
+        assert_eq!(result.info.output_name_to_wire_index["b"], 1);
+        result.validate().unwrap();
+        assert_equivalent(&original, &result);
+
+        let identity = original.propagate_copies_with_ops(&["BUF"]);
+        assert_eq!(identity, original);
+    }
+
+    #[test]
+    fn test_propagate_copies_with_mixed_widths() {
+        // A 2-bit output, copies of its bits, a constant-1 `EQ` output, and a copied input that
+        // moves ahead of them all.
+        let original = circuit(
+            vec![
+                gate("AND", &[0, 1], &[2]),
+                gate("EQW", &[2], &[3]),
+                gate("XOR", &[0, 1], &[4]),
+                gate("EQW", &[4], &[5]),
+                gate("EQW", &[3], &[6]),
+                gate("EQW", &[5], &[7]),
+                gate("EQ", &[1], &[8]),
+                gate("EQW", &[8], &[9]),
+                gate("EQW", &[0], &[10]),
+            ],
+            &[("pair", 6, 2), ("one", 9, 1), ("a_copy", 10, 1)],
+        );
+
+        let result = original.propagate_copies();
+        result.validate().unwrap();
+        result.assert_wire_count_tight();
+        assert_eq!(result.info.output_name_to_wire_index["a_copy"], 0);
+        assert_equivalent(&original, &result);
+    }
+
+    #[test]
+    fn test_ignores_other_ops() {
+        let original = circuit(
+            vec![gate("INV", &[0], &[2]), gate("EQW", &[2], &[3])],
+            &[("out", 3, 1)],
+        );
+
+        let result = original.eliminate_copies(&["BUF"]);
+        assert_eq!(result, original);
+    }
+}
@@ -0,0 +1,218 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt::{self, Display, Formatter},
+};
+
+use serde::{Deserialize, Serialize};
+use strum::IntoEnumIterator;
+
+use crate::{
+    bool_gate_type::BoolGateType, bristol_circuit::BristolCircuit,
+    bristol_circuit_error::BristolCircuitError, gate::Gate, trace::trace_span,
+};
+
+/// What each op costs to evaluate under some protocol, for [`BristolCircuit::cost`].
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CostModel {
+    pub op_costs: HashMap<String, usize>,
+
+    /// The cost of ops not in `op_costs`.
+    pub default_cost: usize,
+}
+
+impl CostModel {
+    /// Garbling with free XOR: the linear boolean ops (`XOR`, `XNOR`, `INV`, `NOT`, `EQ`,
+    /// `EQW` and `BUF`) cost nothing, and every other op, like `AND`, costs 1.
+    pub fn garbled_free_xor() -> CostModel {
+        CostModel {
+            op_costs: BoolGateType::iter()
+                .map(|op| (op.to_string(), usize::from(!op.is_linear())))
+                .collect(),
+            default_cost: 1,
+        }
+    }
+
+    /// Arithmetic secret sharing: each `AMul` and `ADiv` costs 1, and everything else nothing.
+    pub fn arithmetic() -> CostModel {
+        CostModel {
+            op_costs: HashMap::from([("AMul".to_string(), 1), ("ADiv".to_string(), 1)]),
+            default_cost: 0,
+        }
+    }
+
+    /// What `gate` costs: its op's cost, once per output for `MAND`, which batches one `AND` per
+    /// output.
+    pub fn gate_cost(&self, gate: &Gate) -> usize {
+        let cost = self
+            .op_costs
+            .get(gate.op.as_str())
+            .copied()
+            .unwrap_or(self.default_cost);
+
+        match gate.op.as_str() {
+            "MAND" => cost.saturating_mul(gate.outputs.len()),
+            _ => cost,
+        }
+    }
+}
+
+/// One op's share of a [`CostReport`].
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize)]
+pub struct OpCost {
+    pub gates: usize,
+    pub cost: usize,
+}
+
+/// From [`BristolCircuit::cost`].
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize)]
+pub struct CostReport {
+    pub total: usize,
+
+    /// The gates using each op and what they cost in total.
+    pub by_op: BTreeMap<String, OpCost>,
+
+    /// The most any chain of gates, each reading an output of the one before, costs. Under
+    /// [`CostModel::garbled_free_xor`] this is the AND depth.
+    pub depth: usize,
+}
+
+impl BristolCircuit {
+    /// Totals what the gates cost under `model`, by op and along the most expensive path. Like
+    /// [`BristolCircuit::levelize`], gates don't need to be topologically sorted, but a
+    /// dependency cycle is an error.
+    pub fn cost(&self, model: &CostModel) -> Result<CostReport, BristolCircuitError> {
+        let _span = trace_span!("cost", gate_count = self.gates.len());
+
+        let mut report = CostReport::default();
+
+        for gate in &self.gates {
+            let cost = model.gate_cost(gate);
+            let op = report.by_op.entry(gate.op.to_string()).or_default();
+
+            op.gates += 1;
+            op.cost = op.cost.saturating_add(cost);
+            report.total = report.total.saturating_add(cost);
+        }
+
+        let levels = self.gate_levels(|gate| model.gate_cost(gate))?.levels;
+        report.depth = self
+            .gates
+            .iter()
+            .zip(levels)
+            .map(|(gate, level)| level.saturating_add(model.gate_cost(gate)))
+            .max()
+            .unwrap_or(0);
+
+        Ok(report)
+    }
+}
+
+impl Display for CostReport {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "cost {}, depth {}", self.total, self.depth)?;
+
+        for (op, cost) in &self.by_op {
+            write!(f, "\n  {}: {} gates, cost {}", op, cost.gates, cost.cost)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{circuit_builder::CircuitBuilder, gadgets};
+
+    #[test]
+    fn test_adder_free_xor() {
+        for bits in [1, 4, 16] {
+            let report = gadgets::adder(bits, true)
+                .cost(&CostModel::garbled_free_xor())
+                .unwrap();
+
+            // One AND per bit, all on the carry chain, and four XORs per full adder.
+            assert_eq!(report.total, bits);
+            assert_eq!(report.depth, bits);
+            assert_eq!(
+                report.by_op["AND"],
+                OpCost {
+                    gates: bits,
+                    cost: bits
+                }
+            );
+            assert_eq!(
+                report.by_op["XOR"],
+                OpCost {
+                    gates: 4 * bits - 3,
+                    cost: 0
+                }
+            );
+        }
+    }
+
+    #[test]
+    fn test_hand_counted() {
+        // (x & y) | !(x ^ y), then MAND of that and y with x and z.
+        let mut b = CircuitBuilder::new();
+        let x = b.input("x");
+        let y = b.input("y");
+        let z = b.input("z");
+        let and = b.gate("AND", &[x, y]);
+        let xor = b.gate("XOR", &[x, y]);
+        let inv = b.gate("INV", &[xor]);
+        let or = b.gate("OR", &[and, inv]);
+        let mand = b.gate("MAND", &[or, y, x, z]);
+        b.output("out", mand);
+
+        let mut circuit = b.build().unwrap();
+        let spare = circuit.wire_count;
+        circuit.wire_count += 1;
+        circuit.gates[4].outputs.push(spare);
+
+        let report = circuit.cost(&CostModel::garbled_free_xor()).unwrap();
+        assert_eq!(report.total, 4);
+        assert_eq!(report.by_op["MAND"], OpCost { gates: 1, cost: 2 });
+        assert_eq!(report.by_op["INV"], OpCost { gates: 1, cost: 0 });
+        // AND, OR, then the MAND's two.
+        assert_eq!(report.depth, 4);
+
+        assert_eq!(
+            report.to_string(),
+            "cost 4, depth 4\n  AND: 1 gates, cost 1\n  INV: 1 gates, cost 0\n  \
+             MAND: 1 gates, cost 2\n  OR: 1 gates, cost 1\n  XOR: 1 gates, cost 0"
+        );
+    }
+
+    #[test]
+    fn test_arithmetic() {
+        // ((x * y) + x) / (y - 1) * x
+        let mut b = CircuitBuilder::new();
+        let x = b.input("x");
+        let y = b.input("y");
+        let one = b.constant("one", "1");
+        let product = b.gate("AMul", &[x, y]);
+        let sum = b.gate("AAdd", &[product, x]);
+        let less = b.gate("ASub", &[y, one]);
+        let quotient = b.gate("ADiv", &[sum, less]);
+        let out = b.gate("AMul", &[quotient, x]);
+        b.output("out", out);
+
+        let circuit = b.build().unwrap();
+        let report = circuit.cost(&CostModel::arithmetic()).unwrap();
+        assert_eq!(report.total, 3);
+        assert_eq!(report.depth, 3);
+        assert_eq!(report.by_op["AAdd"], OpCost { gates: 1, cost: 0 });
+        assert_eq!(
+            report.depth,
+            circuit.multiplicative_depth(&["AMul", "ADiv"]).unwrap()
+        );
+
+        let json = serde_json::to_value(&report).unwrap();
+        assert_eq!(json["by_op"]["AMul"]["cost"], 2);
+
+        let mut cyclic = circuit;
+        cyclic.gates[0].inputs[0] = cyclic.gates[4].outputs[0];
+        assert!(cyclic.cost(&CostModel::arithmetic()).is_err());
+    }
+}
@@ -0,0 +1,189 @@
+use std::fmt::{self, Display, Formatter};
+
+use serde::Serialize;
+
+use crate::{
+    bristol_circuit::BristolCircuit, bristol_circuit_error::BristolCircuitError,
+    levelize::GateLevels, trace::trace_span,
+};
+
+/// A longest chain of gates, each reading an output of the one before.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize)]
+pub struct CriticalPath {
+    /// How many gates are on the path.
+    pub depth: usize,
+
+    /// The gates on the path, from the one reading only inputs and constants to the last.
+    pub gates: Vec<usize>,
+}
+
+/// From [`BristolCircuit::critical_path`].
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize)]
+pub struct CriticalPathReport {
+    /// The longest path ending at each named output, in wire order.
+    pub outputs: Vec<(String, CriticalPath)>,
+
+    /// The longest path anywhere in the circuit, whose depth is the one
+    /// [`BristolCircuit::stats`] gives.
+    pub overall: CriticalPath,
+}
+
+impl BristolCircuit {
+    /// Finds the longest path of gates ending at each named output, and overall. Where several
+    /// gates are as deep, the lowest gate index is followed, both at the end of a path and at
+    /// each step back. Like [`BristolCircuit::levelize`], gates don't need to be topologically
+    /// sorted, a dependency cycle is an error, and `EQ` inputs are literal values, not wires.
+    pub fn critical_path(&self) -> Result<CriticalPathReport, BristolCircuitError> {
+        let _span = trace_span!("critical_path", gate_count = self.gates.len());
+
+        let levels = self.gate_levels(|_| 1)?;
+
+        let outputs = self
+            .output_buses()
+            .into_iter()
+            .map(|bus| {
+                let gates = (bus.wire..bus.wire + bus.width)
+                    .filter_map(|wire| levels.producers.get(&wire).copied());
+
+                (
+                    bus.name.to_string(),
+                    path_to(self, &levels, deepest(&levels, gates)),
+                )
+            })
+            .collect();
+
+        Ok(CriticalPathReport {
+            outputs,
+            overall: path_to(self, &levels, deepest(&levels, 0..self.gates.len())),
+        })
+    }
+}
+
+/// The deepest of `gates`, the lowest index among equals.
+fn deepest(levels: &GateLevels, gates: impl Iterator<Item = usize>) -> Option<usize> {
+    gates.min_by_key(|&i| (std::cmp::Reverse(levels.levels[i]), i))
+}
+
+/// A longest path ending at `last`, from levels with unit weights.
+fn path_to(circuit: &BristolCircuit, levels: &GateLevels, last: Option<usize>) -> CriticalPath {
+    let mut gates = Vec::from_iter(last);
+
+    while let Some(&i) = gates.last() {
+        let Some(level) = levels.levels[i].checked_sub(1) else {
+            break;
+        };
+
+        // Some input's producer is exactly one level lower, or `i` would be lower itself.
+        let previous = circuit.gates[i]
+            .inputs
+            .iter()
+            .filter_map(|wire| levels.producers.get(wire).copied())
+            .filter(|&producer| levels.levels[producer] == level)
+            .min();
+
+        match previous {
+            Some(previous) => gates.push(previous),
+            None => break,
+        }
+    }
+
+    gates.reverse();
+
+    CriticalPath {
+        depth: gates.len(),
+        gates,
+    }
+}
+
+impl Display for CriticalPath {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "depth {} via gates {:?}", self.depth, self.gates)
+    }
+}
+
+impl Display for CriticalPathReport {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        for (name, path) in &self.outputs {
+            writeln!(f, "{}: {}", name, path)?;
+        }
+
+        write!(f, "critical path: {}", self.overall)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{circuit_builder::CircuitBuilder, gadgets};
+
+    #[test]
+    fn test_adder() {
+        let circuit = gadgets::adder(4, true);
+        let report = circuit.critical_path().unwrap();
+
+        let depth = circuit.stats().unwrap().depth;
+        assert_eq!(report.overall.depth, depth);
+        assert_eq!(report.overall.gates.len(), depth);
+        assert_eq!(report.outputs.len(), 2);
+        assert!(report
+            .outputs
+            .iter()
+            .any(|(_, path)| path == &report.overall));
+
+        // Each gate reads an output of the one before.
+        for pair in report.overall.gates.windows(2) {
+            let [a, b] = [pair[0], pair[1]].map(|i| &circuit.gates[i]);
+            assert!(a.outputs.iter().any(|wire| b.inputs.contains(wire)));
+        }
+    }
+
+    #[test]
+    fn test_per_output_and_ties() {
+        let mut b = CircuitBuilder::new();
+        let x = b.input("x");
+        let y = b.input("y");
+        let p = b.gate("AND", &[x, y]);
+        let q = b.gate("XOR", &[x, y]);
+        let r = b.gate("AND", &[q, p]);
+        let s = b.gate("INV", &[r]);
+        b.output("shallow", q);
+        b.output("deep", s);
+        b.output("copy", x);
+
+        let circuit = b.build().unwrap();
+        let report = circuit.critical_path().unwrap();
+
+        // r reads p and q equally deep, so p, the lower gate, is followed.
+        let by_name = |name: &str| &report.outputs.iter().find(|(n, _)| n == name).unwrap().1;
+        assert_eq!(by_name("shallow").gates, [1]);
+        assert_eq!(by_name("deep").gates, [0, 2, 3]);
+        assert_eq!(by_name("copy"), &CriticalPath::default());
+        assert_eq!(report.overall.gates, [0, 2, 3]);
+
+        let text = report.to_string();
+        assert!(
+            text.contains("deep: depth 3 via gates [0, 2, 3]\n"),
+            "{}",
+            text
+        );
+        assert!(text.ends_with("critical path: depth 3 via gates [0, 2, 3]"));
+
+        let json = serde_json::to_value(&report).unwrap();
+        assert_eq!(json["overall"]["depth"], 3);
+    }
+
+    #[test]
+    fn test_unsorted_and_cycle() {
+        let mut circuit = gadgets::adder(2, true);
+        let sorted = circuit.critical_path().unwrap().overall;
+
+        circuit.gates.reverse();
+        let reversed = circuit.critical_path().unwrap().overall;
+        assert_eq!(reversed.depth, sorted.depth);
+        assert!(reversed.gates.windows(2).all(|pair| pair[0] > pair[1]));
+
+        circuit.gates[0].inputs[0] = circuit.gates[1].outputs[0];
+        circuit.gates[1].inputs[0] = circuit.gates[0].outputs[0];
+        assert!(circuit.critical_path().is_err());
+    }
+}
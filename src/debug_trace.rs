@@ -0,0 +1,269 @@
+use std::{
+    collections::HashMap,
+    fmt::{self, Display, Formatter},
+};
+
+use crate::{
+    bristol_circuit::BristolCircuit, bristol_circuit_error::BristolCircuitError,
+    eval::EvalObserver, evaluator::Evaluator, gate::Gate,
+};
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct DebugTraceOptions {
+    /// Record every gate's input and output values, not just the final wire values.
+    pub record_gates: bool,
+
+    /// Names for wires, e.g. intermediate values. These take precedence over the names of
+    /// inputs, constants and outputs.
+    pub labels: HashMap<usize, String>,
+}
+
+/// Every wire's value after an evaluation, for debugging. Displays as text, one gate per line
+/// when gates were recorded and one wire per line otherwise.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DebugTrace<V> {
+    /// The value of each wire, or `None` for wires nothing set.
+    pub wires: Vec<Option<V>>,
+
+    /// One record per gate, when `record_gates` was set.
+    pub gates: Option<Vec<GateRecord<V>>>,
+
+    /// Wire names: the given labels, then named input, constant and output bits like `a[3]`.
+    pub labels: HashMap<usize, String>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct GateRecord<V> {
+    pub gate_index: usize,
+    pub op: String,
+
+    /// Each input wire with the value it had when the gate ran. `EQ` takes literals rather
+    /// than wires, so its input shows whatever the wire of that number held.
+    pub inputs: Vec<(usize, Option<V>)>,
+
+    pub outputs: Vec<(usize, Option<V>)>,
+}
+
+impl<V> DebugTrace<V> {
+    /// The label of `wire`, or `w<wire>` for unnamed wires.
+    pub fn label(&self, wire: usize) -> String {
+        self.labels
+            .get(&wire)
+            .cloned()
+            .unwrap_or_else(|| format!("w{}", wire))
+    }
+}
+
+impl<V: Display> Display for DebugTrace<V> {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        let value = |value: &Option<V>| match value {
+            Some(value) => value.to_string(),
+            None => "?".to_string(),
+        };
+
+        let Some(gates) = &self.gates else {
+            for (wire, wire_value) in self.wires.iter().enumerate() {
+                writeln!(f, "{} = {}", self.label(wire), value(wire_value))?;
+            }
+
+            return Ok(());
+        };
+
+        let wires = |wires: &[(usize, Option<V>)]| {
+            wires
+                .iter()
+                .map(|(wire, wire_value)| format!("{}={}", self.label(*wire), value(wire_value)))
+                .collect::<Vec<_>>()
+                .join(" ")
+        };
+
+        for record in gates {
+            writeln!(
+                f,
+                "#{} {} {} -> {}",
+                record.gate_index,
+                record.op,
+                wires(&record.inputs),
+                wires(&record.outputs)
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+struct GateRecorder<V> {
+    records: Option<Vec<GateRecord<V>>>,
+}
+
+impl<V: Clone> EvalObserver<V> for GateRecorder<V> {
+    fn after_gate(&mut self, gate_index: usize, gate: &Gate, wires: &[Option<V>]) {
+        if let Some(records) = &mut self.records {
+            let values = |list: &[usize]| {
+                list.iter()
+                    .map(|&wire| (wire, wires.get(wire).cloned().flatten()))
+                    .collect()
+            };
+
+            records.push(GateRecord {
+                gate_index,
+                op: gate.op.to_string(),
+                inputs: values(&gate.inputs),
+                outputs: values(&gate.outputs),
+            });
+        }
+    }
+}
+
+/// Named outputs, and the debug trace of the evaluation that produced them.
+type Debugged<V> = (HashMap<String, Vec<V>>, DebugTrace<V>);
+
+impl BristolCircuit {
+    /// Like [`BristolCircuit::eval_boolean`], also returning every wire's value.
+    pub fn eval_boolean_debug(
+        &self,
+        inputs: &HashMap<String, Vec<bool>>,
+        opts: &DebugTraceOptions,
+    ) -> Result<Debugged<bool>, BristolCircuitError> {
+        let mut recorder = GateRecorder::new(opts);
+        let wires = self.eval_boolean_wires(inputs, &mut recorder)?;
+
+        self.debugged(wires, recorder, opts)
+    }
+
+    /// Like [`BristolCircuit::eval_with`], also returning every wire's value.
+    pub fn eval_with_debug<V: Clone, E: Evaluator<V>>(
+        &self,
+        inputs: &HashMap<String, Vec<V>>,
+        evaluator: &mut E,
+        opts: &DebugTraceOptions,
+    ) -> Result<Debugged<V>, BristolCircuitError> {
+        let mut recorder = GateRecorder::new(opts);
+        let wires = self.eval_with_observed(inputs, evaluator, &mut recorder)?;
+
+        self.debugged(wires, recorder, opts)
+    }
+
+    fn debugged<V: Clone>(
+        &self,
+        wires: Vec<Option<V>>,
+        recorder: GateRecorder<V>,
+        opts: &DebugTraceOptions,
+    ) -> Result<Debugged<V>, BristolCircuitError> {
+        let mut labels = self.wire_labels();
+        labels.extend(
+            opts.labels
+                .iter()
+                .map(|(&wire, label)| (wire, label.clone())),
+        );
+
+        let outputs = self.bus_outputs(&wires)?;
+
+        Ok((
+            outputs,
+            DebugTrace {
+                wires,
+                gates: recorder.records,
+                labels,
+            },
+        ))
+    }
+
+    /// Labels for the named input, constant and output wires, like `a[3]` for bit 3 of `a` (or
+    /// just `a` when it's one bit). Inputs and constants take precedence over outputs.
+    pub(crate) fn wire_labels(&self) -> HashMap<usize, String> {
+        let mut labels = HashMap::new();
+
+        for bus in self.input_buses() {
+            for bit in 0..bus.width {
+                labels.insert(bus.wire + bit, bit_label(bus.name, bit, bus.width));
+            }
+        }
+
+        for (name, constant) in &self.info.constants {
+            labels.insert(constant.wire_index, name.to_string());
+        }
+
+        for bus in self.output_buses() {
+            for bit in 0..bus.width {
+                labels
+                    .entry(bus.wire + bit)
+                    .or_insert_with(|| bit_label(bus.name, bit, bus.width));
+            }
+        }
+
+        labels
+    }
+}
+
+impl<V> GateRecorder<V> {
+    fn new(opts: &DebugTraceOptions) -> Self {
+        GateRecorder {
+            records: opts.record_gates.then(Vec::new),
+        }
+    }
+}
+
+fn bit_label(name: &str, bit: usize, width: usize) -> String {
+    if width == 1 {
+        name.to_string()
+    } else {
+        format!("{}[{}]", name, bit)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gadgets;
+
+    fn inputs() -> HashMap<String, Vec<bool>> {
+        HashMap::from([
+            ("a".to_string(), vec![true, true]),
+            ("b".to_string(), vec![true, false]),
+        ])
+    }
+
+    #[test]
+    fn test_wire_values() {
+        let circuit = gadgets::adder(2, true);
+        let (outputs, trace) = circuit
+            .eval_boolean_debug(&inputs(), &Default::default())
+            .unwrap();
+
+        assert_eq!(outputs, circuit.eval_boolean(&inputs()).unwrap());
+        assert_eq!(trace.wires.len(), circuit.wire_count);
+        assert!(trace.wires.iter().all(Option::is_some));
+        assert!(trace.gates.is_none());
+
+        let text = trace.to_string();
+        assert_eq!(text.lines().count(), circuit.wire_count);
+        assert!(
+            text.starts_with("a[0] = true\na[1] = true\nb[0] = true\n"),
+            "{}",
+            text
+        );
+    }
+
+    #[test]
+    fn test_gate_records() {
+        let circuit = gadgets::adder(2, true);
+        let carry = circuit.gates[1].outputs[0];
+
+        let opts = DebugTraceOptions {
+            record_gates: true,
+            labels: HashMap::from([(carry, "c0".to_string())]),
+        };
+        let (_, trace) = circuit.eval_boolean_debug(&inputs(), &opts).unwrap();
+
+        let gates = trace.gates.as_ref().unwrap();
+        assert_eq!(gates.len(), circuit.gates.len());
+        assert_eq!(gates[0].inputs, vec![(0, Some(true)), (2, Some(true))]);
+
+        let text = trace.to_string();
+        let lines = text.lines().collect::<Vec<_>>();
+        assert_eq!(lines.len(), circuit.gates.len());
+        assert_eq!(lines[0], "#0 XOR a[0]=true b[0]=true -> sum[0]=false");
+        assert_eq!(lines[1], "#1 AND a[0]=true b[0]=true -> c0=true");
+    }
+}
@@ -0,0 +1,202 @@
+use std::{
+    collections::{BTreeSet, HashMap},
+    ops::Range,
+    rc::Rc,
+};
+
+use crate::{bristol_circuit::BristolCircuit, trace::trace_span, visit::Reachability};
+
+impl BristolCircuit {
+    /// The named inputs each named output depends on: those with a wire that a path of gates
+    /// leads from to one of the output's wires, or that is one of them. Every output is
+    /// listed, those depending on no input with an empty set. `EQ` inputs are literal values,
+    /// not wires.
+    pub fn input_dependencies(&self) -> HashMap<String, BTreeSet<String>> {
+        let sources = self
+            .input_buses()
+            .into_iter()
+            .map(|bus| (bus.name, bus.wire..bus.wire + bus.width))
+            .collect();
+
+        self.dependencies(sources)
+    }
+
+    /// Like [`BristolCircuit::input_dependencies`], but the constants each output depends on.
+    pub fn constant_dependencies(&self) -> HashMap<String, BTreeSet<String>> {
+        let mut sources = self
+            .info
+            .constants
+            .iter()
+            .map(|(name, constant)| (name.as_str(), constant.wire_index..constant.wire_index + 1))
+            .collect::<Vec<_>>();
+        sources.sort_by_key(|(name, _)| *name);
+
+        self.dependencies(sources)
+    }
+
+    /// Which of `sources` each output depends on. Within the outputs' cone of influence, the
+    /// gates are visited in topological order, each output wire sharing its inputs' set when
+    /// they're all the same one, so shared subgraphs are only merged once. With a dependency
+    /// cycle there's no such order, so each output's cone is walked on its own instead.
+    fn dependencies(
+        &self,
+        sources: Vec<(&str, Range<usize>)>,
+    ) -> HashMap<String, BTreeSet<String>> {
+        let _span = trace_span!("pass", name = "dependencies", gate_count = self.gates.len());
+
+        let outputs = self.output_buses();
+        let reachability = Reachability::new(self);
+
+        let names = |indices: &BTreeSet<usize>| {
+            indices
+                .iter()
+                .map(|&i| sources[i].0.to_string())
+                .collect::<BTreeSet<_>>()
+        };
+
+        let Ok(order) = self.topological_order() else {
+            return outputs
+                .iter()
+                .map(|bus| {
+                    let reached = reachability.backward(bus.wire..bus.wire + bus.width);
+                    let indices = (0..sources.len())
+                        .filter(|&i| {
+                            sources[i]
+                                .1
+                                .clone()
+                                .any(|wire| reached.wires.contains(&wire))
+                        })
+                        .collect();
+
+                    (bus.name.to_string(), names(&indices))
+                })
+                .collect();
+        };
+
+        let cone = reachability.backward(
+            outputs
+                .iter()
+                .flat_map(|bus| bus.wire..bus.wire + bus.width),
+        );
+
+        let mut wire_sources = HashMap::<usize, Rc<BTreeSet<usize>>>::new();
+
+        for (i, (_, wires)) in sources.iter().enumerate() {
+            for wire in wires.clone() {
+                wire_sources.insert(wire, Rc::new(BTreeSet::from([i])));
+            }
+        }
+
+        for i in order.into_iter().filter(|i| cone.gates.contains(i)) {
+            let gate = &self.gates[i];
+
+            let inputs = gate
+                .input_wires()
+                .iter()
+                .filter_map(|wire| wire_sources.get(wire))
+                .collect::<Vec<_>>();
+
+            let merged = match inputs.split_first() {
+                None => Rc::default(),
+                Some((first, rest)) if rest.iter().all(|set| Rc::ptr_eq(first, set)) => {
+                    Rc::clone(first)
+                }
+                Some(_) => Rc::new(inputs.iter().flat_map(|set| set.iter().copied()).collect()),
+            };
+
+            for &wire in &gate.outputs {
+                wire_sources.insert(wire, Rc::clone(&merged));
+            }
+        }
+
+        outputs
+            .iter()
+            .map(|bus| {
+                let indices = (bus.wire..bus.wire + bus.width)
+                    .filter_map(|wire| wire_sources.get(&wire))
+                    .flat_map(|set| set.iter().copied())
+                    .collect();
+
+                (bus.name.to_string(), names(&indices))
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{circuit_builder::CircuitBuilder, gadgets};
+
+    fn set(names: &[&str]) -> BTreeSet<String> {
+        names.iter().map(|name| name.to_string()).collect()
+    }
+
+    /// `a = (x & y) ^ one` and `b = !y`, with `z` unused.
+    fn split() -> BristolCircuit {
+        let mut b = CircuitBuilder::new();
+        let x = b.input("x");
+        let y = b.input("y");
+        b.input("z");
+        let one = b.constant("one", "1");
+        let shared = b.gate("AND", &[x, y]);
+        let a = b.gate("XOR", &[shared, one]);
+        let not_y = b.gate("INV", &[y]);
+        b.output("a", a);
+        b.output("b", not_y);
+        b.output("y_copy", y);
+        b.build().unwrap()
+    }
+
+    #[test]
+    fn test_outputs_depend_on_different_inputs() {
+        let circuit = split();
+
+        assert_eq!(
+            circuit.input_dependencies(),
+            HashMap::from([
+                ("a".to_string(), set(&["x", "y"])),
+                ("b".to_string(), set(&["y"])),
+                ("y_copy".to_string(), set(&["y"])),
+            ])
+        );
+        assert_eq!(
+            circuit.constant_dependencies(),
+            HashMap::from([
+                ("a".to_string(), set(&["one"])),
+                ("b".to_string(), set(&[])),
+                ("y_copy".to_string(), set(&[])),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_shared_subgraphs_and_cycles() {
+        // Every sum bit but the first depends on the carry chain, which all bits share.
+        let circuit = gadgets::adder(16, true);
+        let deps = circuit.input_dependencies();
+        assert_eq!(deps["sum"], set(&["a", "b"]));
+        assert_eq!(deps["carry_out"], set(&["a", "b"]));
+
+        // The cone-walking fallback agrees.
+        let mut cyclic = split();
+        let spare = cyclic.wire_count;
+        cyclic.wire_count += 2;
+        cyclic.gates.push(crate::Gate {
+            inputs: smallvec::smallvec![spare + 1],
+            outputs: smallvec::smallvec![spare],
+            op: "INV".into(),
+        });
+        cyclic.gates.push(crate::Gate {
+            inputs: smallvec::smallvec![spare],
+            outputs: smallvec::smallvec![spare + 1],
+            op: "INV".into(),
+        });
+        assert!(cyclic.topological_order().is_err());
+        assert_eq!(cyclic.input_dependencies(), split().input_dependencies());
+        assert_eq!(
+            cyclic.constant_dependencies(),
+            split().constant_dependencies()
+        );
+    }
+}
@@ -0,0 +1,868 @@
+use std::{
+    collections::{BTreeSet, HashMap},
+    fmt::{self, Display, Formatter},
+};
+
+use serde::Serialize;
+
+use crate::{
+    bristol_circuit::BristolCircuit,
+    circuit_info::{CircuitInfo, ConstantInfo},
+    gate::Gate,
+    io_kind::IoKind,
+};
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DiffOptions {
+    /// Compare circuits after renumbering wires canonically (inputs by name, then constants by
+    /// name, then gate outputs in order), so pure renumberings produce an empty diff.
+    pub canonicalize: bool,
+
+    /// Give up on the gate-level edit script once it needs more than this many insertions and
+    /// deletions, reporting only a summary.
+    pub max_edits: usize,
+}
+
+impl Default for DiffOptions {
+    fn default() -> Self {
+        DiffOptions {
+            canonicalize: false,
+            max_edits: 1000,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize)]
+pub struct CircuitDiff {
+    pub interface: Vec<InterfaceChange>,
+    pub constants: Vec<ConstantChange>,
+    pub gates: Vec<GateEdit>,
+
+    /// Set instead of `gates` when the edit script exceeded `DiffOptions::max_edits`.
+    pub gates_summary: Option<GateDiffSummary>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub enum InterfaceChange {
+    WireCountChanged {
+        left: usize,
+        right: usize,
+    },
+    WidthsChanged {
+        kind: IoKind,
+        left: Vec<usize>,
+        right: Vec<usize>,
+    },
+    Added {
+        kind: IoKind,
+        name: String,
+        wire: usize,
+    },
+    Removed {
+        kind: IoKind,
+        name: String,
+        wire: usize,
+    },
+    Moved {
+        kind: IoKind,
+        name: String,
+        left: usize,
+        right: usize,
+    },
+
+    /// A name that is gone, replaced by a new name on the same wire.
+    Renamed {
+        kind: IoKind,
+        left: String,
+        right: String,
+        wire: usize,
+    },
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub enum ConstantChange {
+    Added {
+        name: String,
+        constant: ConstantInfo,
+    },
+    Removed {
+        name: String,
+        constant: ConstantInfo,
+    },
+    Changed {
+        name: String,
+        left: ConstantInfo,
+        right: ConstantInfo,
+    },
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+pub enum GateField {
+    Op,
+    Inputs,
+    Outputs,
+}
+
+/// One step of the gate-level edit script. Indices refer to the (possibly canonicalized) gate
+/// lists of the left and right circuits.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub enum GateEdit {
+    Deleted {
+        left_index: usize,
+        gate: Gate,
+    },
+    Inserted {
+        right_index: usize,
+        gate: Gate,
+    },
+    Modified {
+        left_index: usize,
+        right_index: usize,
+        fields: Vec<GateField>,
+        left: Gate,
+        right: Gate,
+    },
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+pub struct GateDiffSummary {
+    pub left_gates: usize,
+    pub right_gates: usize,
+    pub positional_mismatches: usize,
+}
+
+impl CircuitDiff {
+    pub fn is_empty(&self) -> bool {
+        self.interface.is_empty()
+            && self.constants.is_empty()
+            && self.gates.is_empty()
+            && self.gates_summary.is_none()
+    }
+
+    /// Renders the diff as unified-diff-style text.
+    pub fn render(&self) -> String {
+        self.to_string()
+    }
+}
+
+impl BristolCircuit {
+    pub fn diff(&self, other: &BristolCircuit, opts: &DiffOptions) -> CircuitDiff {
+        let (left, right) = if opts.canonicalize {
+            (canonicalize(self), canonicalize(other))
+        } else {
+            (self.clone(), other.clone())
+        };
+
+        let mut diff = CircuitDiff {
+            interface: interface_changes(&left, &right),
+            constants: constant_changes(&left.info, &right.info),
+            ..Default::default()
+        };
+
+        match edit_script(&left.gates, &right.gates, opts.max_edits) {
+            Some(ops) => diff.gates = gate_edits(&left.gates, &right.gates, &ops),
+            None => {
+                diff.gates_summary = Some(GateDiffSummary {
+                    left_gates: left.gates.len(),
+                    right_gates: right.gates.len(),
+                    positional_mismatches: left
+                        .gates
+                        .iter()
+                        .zip(&right.gates)
+                        .filter(|(l, r)| l != r)
+                        .count()
+                        + left.gates.len().abs_diff(right.gates.len()),
+                })
+            }
+        }
+
+        diff
+    }
+}
+
+/// Maps each wire to its canonical index: inputs sorted by name, then constants sorted by name,
+/// then gate outputs in order of appearance, then any remaining wires in ascending order.
+pub(crate) fn canonical_wire_map(circuit: &BristolCircuit) -> HashMap<usize, usize> {
+    let mut mapping = HashMap::new();
+    let mut next = 0;
+    let mut assign = |wire: usize, mapping: &mut HashMap<usize, usize>| {
+        mapping.entry(wire).or_insert_with(|| {
+            next += 1;
+            next - 1
+        });
+    };
+
+    let mut inputs = circuit.input_buses();
+    inputs.sort_by_key(|bus| bus.name);
+
+    for bus in inputs {
+        for offset in 0..bus.width {
+            assign(bus.wire + offset, &mut mapping);
+        }
+    }
+
+    let mut constants = circuit.info.constants.iter().collect::<Vec<_>>();
+    constants.sort_by(|a, b| a.0.cmp(b.0));
+
+    for (_, constant) in constants {
+        assign(constant.wire_index, &mut mapping);
+    }
+
+    for gate in &circuit.gates {
+        for &wire in &gate.outputs {
+            assign(wire, &mut mapping);
+        }
+    }
+
+    let rest = circuit
+        .gates
+        .iter()
+        .flat_map(|gate| gate.inputs.iter().copied())
+        .chain(circuit.info.output_name_to_wire_index.values().copied())
+        .filter(|wire| !mapping.contains_key(wire))
+        .collect::<BTreeSet<_>>();
+
+    for wire in rest {
+        assign(wire, &mut mapping);
+    }
+
+    mapping
+}
+
+fn canonicalize(circuit: &BristolCircuit) -> BristolCircuit {
+    let mapping = canonical_wire_map(circuit);
+
+    let mut result = circuit.clone();
+    result.map_wires(|wire| mapping.get(&wire).copied().unwrap_or(wire));
+    result
+}
+
+fn interface_changes(left: &BristolCircuit, right: &BristolCircuit) -> Vec<InterfaceChange> {
+    let mut changes = Vec::new();
+
+    if left.wire_count != right.wire_count {
+        changes.push(InterfaceChange::WireCountChanged {
+            left: left.wire_count,
+            right: right.wire_count,
+        });
+    }
+
+    for (kind, l, r) in [
+        (IoKind::Input, &left.io_widths.0, &right.io_widths.0),
+        (IoKind::Output, &left.io_widths.1, &right.io_widths.1),
+    ] {
+        if l != r {
+            changes.push(InterfaceChange::WidthsChanged {
+                kind,
+                left: l.clone(),
+                right: r.clone(),
+            });
+        }
+    }
+
+    for (kind, l, r) in [
+        (
+            IoKind::Input,
+            &left.info.input_name_to_wire_index,
+            &right.info.input_name_to_wire_index,
+        ),
+        (
+            IoKind::Output,
+            &left.info.output_name_to_wire_index,
+            &right.info.output_name_to_wire_index,
+        ),
+    ] {
+        let names = l.keys().chain(r.keys()).collect::<BTreeSet<_>>();
+        let mut name_changes = Vec::new();
+
+        for name in names {
+            let name = name.clone();
+
+            match (l.get(&name), r.get(&name)) {
+                (Some(&wire), None) => {
+                    name_changes.push(InterfaceChange::Removed { kind, name, wire })
+                }
+                (None, Some(&wire)) => {
+                    name_changes.push(InterfaceChange::Added { kind, name, wire })
+                }
+                (Some(&left), Some(&right)) if left != right => {
+                    name_changes.push(InterfaceChange::Moved {
+                        kind,
+                        name,
+                        left,
+                        right,
+                    })
+                }
+                _ => {}
+            }
+        }
+
+        changes.extend(pair_renames(name_changes));
+    }
+
+    changes
+}
+
+/// Replaces each removed name with a [`InterfaceChange::Renamed`] when an added name took over its
+/// wire.
+fn pair_renames(changes: Vec<InterfaceChange>) -> Vec<InterfaceChange> {
+    // The added change each removed one pairs with, by index.
+    let mut renamed_to = HashMap::<usize, usize>::new();
+
+    for (i, change) in changes.iter().enumerate() {
+        if let InterfaceChange::Removed { wire, .. } = change {
+            let added = changes.iter().enumerate().position(|(j, change)| {
+                matches!(change, InterfaceChange::Added { wire: added, .. } if added == wire)
+                    && !renamed_to.values().any(|&k| k == j)
+            });
+
+            if let Some(j) = added {
+                renamed_to.insert(i, j);
+            }
+        }
+    }
+
+    changes
+        .iter()
+        .enumerate()
+        .filter(|(j, _)| !renamed_to.values().any(|k| k == j))
+        .map(|(i, change)| match (change, renamed_to.get(&i)) {
+            (InterfaceChange::Removed { kind, name, wire }, Some(&j)) => {
+                let InterfaceChange::Added { name: right, .. } = &changes[j] else {
+                    unreachable!("paired with an added name");
+                };
+
+                InterfaceChange::Renamed {
+                    kind: *kind,
+                    left: name.clone(),
+                    right: right.clone(),
+                    wire: *wire,
+                }
+            }
+            _ => change.clone(),
+        })
+        .collect()
+}
+
+fn constant_changes(left: &CircuitInfo, right: &CircuitInfo) -> Vec<ConstantChange> {
+    let names = left
+        .constants
+        .keys()
+        .chain(right.constants.keys())
+        .collect::<BTreeSet<_>>();
+
+    names
+        .into_iter()
+        .filter_map(|name| {
+            let name = name.clone();
+
+            match (left.constants.get(&name), right.constants.get(&name)) {
+                (Some(constant), None) => Some(ConstantChange::Removed {
+                    name,
+                    constant: constant.clone(),
+                }),
+                (None, Some(constant)) => Some(ConstantChange::Added {
+                    name,
+                    constant: constant.clone(),
+                }),
+                (Some(l), Some(r)) if l != r => Some(ConstantChange::Changed {
+                    name,
+                    left: l.clone(),
+                    right: r.clone(),
+                }),
+                _ => None,
+            }
+        })
+        .collect()
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum EditOp {
+    Equal,
+    Delete,
+    Insert,
+}
+
+/// Computes a shortest edit script between the gate lists (Myers' algorithm), or `None` if it
+/// needs more than `max_edits` insertions and deletions.
+fn edit_script(left: &[Gate], right: &[Gate], max_edits: usize) -> Option<Vec<EditOp>> {
+    let prefix = left.iter().zip(right).take_while(|(l, r)| l == r).count();
+
+    let suffix = left[prefix..]
+        .iter()
+        .rev()
+        .zip(right[prefix..].iter().rev())
+        .take_while(|(l, r)| l == r)
+        .count();
+
+    let a = &left[prefix..left.len() - suffix];
+    let b = &right[prefix..right.len() - suffix];
+
+    let mut ops = vec![EditOp::Equal; prefix];
+    ops.extend(myers(a, b, max_edits)?);
+    ops.extend(vec![EditOp::Equal; suffix]);
+
+    Some(ops)
+}
+
+fn myers(a: &[Gate], b: &[Gate], max_edits: usize) -> Option<Vec<EditOp>> {
+    let (n, m) = (a.len() as isize, b.len() as isize);
+    let max_d = (n + m).min(isize::try_from(max_edits).unwrap_or(isize::MAX));
+    let offset = max_d + 1;
+
+    let mut v = vec![0isize; 2 * offset as usize + 1];
+    let mut trace = Vec::new();
+
+    for d in 0..=max_d {
+        trace.push(v.clone());
+
+        for k in (-d..=d).step_by(2) {
+            let index = (k + offset) as usize;
+
+            let mut x = if k == -d || (k != d && v[index - 1] < v[index + 1]) {
+                v[index + 1]
+            } else {
+                v[index - 1] + 1
+            };
+
+            let mut y = x - k;
+
+            while x < n && y < m && a[x as usize] == b[y as usize] {
+                x += 1;
+                y += 1;
+            }
+
+            v[index] = x;
+
+            if x >= n && y >= m {
+                return Some(backtrack(&trace, n, m, offset));
+            }
+        }
+    }
+
+    None
+}
+
+fn backtrack(trace: &[Vec<isize>], n: isize, m: isize, offset: isize) -> Vec<EditOp> {
+    let mut ops = Vec::new();
+    let (mut x, mut y) = (n, m);
+
+    for (d, v) in trace.iter().enumerate().rev() {
+        let d = d as isize;
+        let k = x - y;
+
+        let prev_k =
+            if k == -d || (k != d && v[(k - 1 + offset) as usize] < v[(k + 1 + offset) as usize]) {
+                k + 1
+            } else {
+                k - 1
+            };
+
+        let prev_x = v[(prev_k + offset) as usize];
+        let prev_y = prev_x - prev_k;
+
+        while x > prev_x && y > prev_y {
+            ops.push(EditOp::Equal);
+            x -= 1;
+            y -= 1;
+        }
+
+        if d > 0 {
+            ops.push(if x == prev_x {
+                EditOp::Insert
+            } else {
+                EditOp::Delete
+            });
+        }
+
+        x = prev_x;
+        y = prev_y;
+    }
+
+    ops.reverse();
+    ops
+}
+
+/// Turns an edit script into gate edits, pairing up deletions and insertions between the same
+/// pair of matching gates as modifications.
+fn gate_edits(left: &[Gate], right: &[Gate], ops: &[EditOp]) -> Vec<GateEdit> {
+    let mut edits = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    let mut pos = 0;
+
+    while pos < ops.len() {
+        if ops[pos] == EditOp::Equal {
+            i += 1;
+            j += 1;
+            pos += 1;
+            continue;
+        }
+
+        let run_end = ops[pos..]
+            .iter()
+            .position(|op| *op == EditOp::Equal)
+            .map_or(ops.len(), |len| pos + len);
+
+        let deleted = ops[pos..run_end]
+            .iter()
+            .filter(|op| **op == EditOp::Delete)
+            .count();
+        let inserted = run_end - pos - deleted;
+
+        for k in 0..deleted.max(inserted) {
+            edits.push(match (k < deleted, k < inserted) {
+                (true, true) => GateEdit::Modified {
+                    left_index: i + k,
+                    right_index: j + k,
+                    fields: changed_fields(&left[i + k], &right[j + k]),
+                    left: left[i + k].clone(),
+                    right: right[j + k].clone(),
+                },
+                (true, false) => GateEdit::Deleted {
+                    left_index: i + k,
+                    gate: left[i + k].clone(),
+                },
+                _ => GateEdit::Inserted {
+                    right_index: j + k,
+                    gate: right[j + k].clone(),
+                },
+            });
+        }
+
+        i += deleted;
+        j += inserted;
+        pos = run_end;
+    }
+
+    edits
+}
+
+fn changed_fields(left: &Gate, right: &Gate) -> Vec<GateField> {
+    let mut fields = Vec::new();
+
+    if left.op != right.op {
+        fields.push(GateField::Op);
+    }
+
+    if left.inputs != right.inputs {
+        fields.push(GateField::Inputs);
+    }
+
+    if left.outputs != right.outputs {
+        fields.push(GateField::Outputs);
+    }
+
+    fields
+}
+
+impl Display for CircuitDiff {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        writeln!(f, "--- left")?;
+        writeln!(f, "+++ right")?;
+
+        if !self.interface.is_empty() {
+            writeln!(f, "@@ interface @@")?;
+        }
+
+        for change in &self.interface {
+            match change {
+                InterfaceChange::WireCountChanged { left, right } => {
+                    writeln!(f, "-wire_count {}", left)?;
+                    writeln!(f, "+wire_count {}", right)?;
+                }
+                InterfaceChange::WidthsChanged { kind, left, right } => {
+                    writeln!(f, "-{} widths {:?}", kind, left)?;
+                    writeln!(f, "+{} widths {:?}", kind, right)?;
+                }
+                InterfaceChange::Added { kind, name, wire } => {
+                    writeln!(f, "+{} {} {}", kind, name, wire)?;
+                }
+                InterfaceChange::Removed { kind, name, wire } => {
+                    writeln!(f, "-{} {} {}", kind, name, wire)?;
+                }
+                InterfaceChange::Moved {
+                    kind,
+                    name,
+                    left,
+                    right,
+                } => {
+                    writeln!(f, "-{} {} {}", kind, name, left)?;
+                    writeln!(f, "+{} {} {}", kind, name, right)?;
+                }
+                InterfaceChange::Renamed {
+                    kind,
+                    left,
+                    right,
+                    wire,
+                } => {
+                    writeln!(f, "-{} {} {}", kind, left, wire)?;
+                    writeln!(f, "+{} {} {}", kind, right, wire)?;
+                }
+            }
+        }
+
+        if !self.constants.is_empty() {
+            writeln!(f, "@@ constants @@")?;
+        }
+
+        for change in &self.constants {
+            match change {
+                ConstantChange::Added { name, constant } => {
+                    writeln!(
+                        f,
+                        "+const {} {} {}",
+                        name, constant.value, constant.wire_index
+                    )?;
+                }
+                ConstantChange::Removed { name, constant } => {
+                    writeln!(
+                        f,
+                        "-const {} {} {}",
+                        name, constant.value, constant.wire_index
+                    )?;
+                }
+                ConstantChange::Changed { name, left, right } => {
+                    writeln!(f, "-const {} {} {}", name, left.value, left.wire_index)?;
+                    writeln!(f, "+const {} {} {}", name, right.value, right.wire_index)?;
+                }
+            }
+        }
+
+        if !self.gates.is_empty() {
+            writeln!(f, "@@ gates @@")?;
+        }
+
+        for edit in &self.gates {
+            match edit {
+                GateEdit::Deleted { left_index, gate } => {
+                    writeln!(f, "-{}: {}", left_index, gate)?;
+                }
+                GateEdit::Inserted { right_index, gate } => {
+                    writeln!(f, "+{}: {}", right_index, gate)?;
+                }
+                GateEdit::Modified {
+                    left_index,
+                    right_index,
+                    left,
+                    right,
+                    ..
+                } => {
+                    writeln!(f, "-{}: {}", left_index, left)?;
+                    writeln!(f, "+{}: {}", right_index, right)?;
+                }
+            }
+        }
+
+        if let Some(summary) = &self.gates_summary {
+            writeln!(
+                f,
+                "@@ gates: too many differences ({} vs {} gates, {} positional mismatches) @@",
+                summary.left_gates, summary.right_gates, summary.positional_mismatches
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::circuit_builder::CircuitBuilder;
+
+    fn sample() -> BristolCircuit {
+        let mut b = CircuitBuilder::new();
+        let x = b.input("x");
+        let y = b.input("y");
+        let sum = b.gate("AAdd", &[x, y]);
+        let product = b.gate("AMul", &[sum, y]);
+        let diff = b.gate("ASub", &[product, x]);
+        b.output("result", diff);
+        b.build().unwrap()
+    }
+
+    fn gate(op: &str, inputs: &[usize], outputs: &[usize]) -> Gate {
+        Gate {
+            inputs: inputs.into(),
+            outputs: outputs.into(),
+            op: op.into(),
+        }
+    }
+
+    #[test]
+    fn test_identical() {
+        let diff = sample().diff(&sample(), &DiffOptions::default());
+        assert!(diff.is_empty());
+    }
+
+    #[test]
+    fn test_one_gate_changed() {
+        let left = sample();
+        let mut right = sample();
+        right.gates[1].op = "ADiv".into();
+
+        let diff = left.diff(&right, &DiffOptions::default());
+        assert_eq!(
+            diff.gates,
+            vec![GateEdit::Modified {
+                left_index: 1,
+                right_index: 1,
+                fields: vec![GateField::Op],
+                left: left.gates[1].clone(),
+                right: right.gates[1].clone(),
+            }]
+        );
+        assert!(diff.interface.is_empty());
+
+        assert_eq!(
+            diff.render(),
+            "--- left\n+++ right\n@@ gates @@\n-1: 2 1 2 1 3 AMul\n+1: 2 1 2 1 3 ADiv\n"
+        );
+    }
+
+    #[test]
+    fn test_one_gate_inserted() {
+        let left = sample();
+        let mut right = sample();
+        let inserted = gate("AXor", &[0, 1], &[5]);
+        right.gates.insert(1, inserted.clone());
+        right.wire_count = 6;
+
+        let diff = left.diff(&right, &DiffOptions::default());
+        assert_eq!(
+            diff.gates,
+            vec![GateEdit::Inserted {
+                right_index: 1,
+                gate: inserted,
+            }]
+        );
+        assert_eq!(
+            diff.interface,
+            vec![InterfaceChange::WireCountChanged { left: 5, right: 6 }]
+        );
+    }
+
+    #[test]
+    fn test_renumbered_wires() {
+        let left = sample();
+
+        // Same circuit with wires 2 and 3 swapped.
+        let mut right = sample();
+        right.gates = vec![
+            gate("AAdd", &[0, 1], &[3]),
+            gate("AMul", &[3, 1], &[2]),
+            gate("ASub", &[2, 0], &[4]),
+        ];
+
+        let diff = left.diff(&right, &DiffOptions::default());
+        assert_eq!(diff.gates.len(), 3);
+        assert!(diff
+            .gates
+            .iter()
+            .all(|edit| matches!(edit, GateEdit::Modified { .. })));
+
+        let canonical = left.diff(
+            &right,
+            &DiffOptions {
+                canonicalize: true,
+                ..Default::default()
+            },
+        );
+        assert!(canonical.is_empty(), "{}", canonical);
+    }
+
+    #[test]
+    fn test_interface_and_constant_changes() {
+        let left = sample();
+        let mut right = sample();
+        right.info.input_name_to_wire_index.remove("y");
+        right.info.input_name_to_wire_index.insert("z".into(), 1);
+        right.info.constants.insert(
+            "one".into(),
+            ConstantInfo {
+                value: "1".into(),
+                wire_index: 9,
+            },
+        );
+
+        let diff = left.diff(&right, &DiffOptions::default());
+        assert_eq!(
+            diff.interface,
+            vec![InterfaceChange::Renamed {
+                kind: IoKind::Input,
+                left: "y".into(),
+                right: "z".into(),
+                wire: 1
+            }]
+        );
+        assert_eq!(diff.constants.len(), 1);
+        assert!(serde_json::to_string(&diff).unwrap().contains("\"Added\""));
+
+        right.info.output_name_to_wire_index.remove("result");
+        right
+            .info
+            .output_name_to_wire_index
+            .insert("other".into(), 3);
+        let diff = left.diff(&right, &DiffOptions::default());
+        assert_eq!(
+            diff.interface[1..],
+            [
+                InterfaceChange::Added {
+                    kind: IoKind::Output,
+                    name: "other".into(),
+                    wire: 3
+                },
+                InterfaceChange::Removed {
+                    kind: IoKind::Output,
+                    name: "result".into(),
+                    wire: 4
+                },
+            ]
+        );
+        assert!(diff.render().contains("-input y 1\n+input z 1\n"));
+    }
+
+    #[test]
+    fn test_unlimited_edits() {
+        let unlimited = DiffOptions {
+            max_edits: usize::MAX,
+            ..Default::default()
+        };
+        assert!(sample().diff(&sample(), &unlimited).is_empty());
+
+        let mut right = sample();
+        right.gates[1].op = "ADiv".into();
+        let diff = sample().diff(&right, &unlimited);
+        assert_eq!(diff.gates_summary, None);
+        assert_eq!(
+            diff.gates,
+            sample().diff(&right, &DiffOptions::default()).gates
+        );
+    }
+
+    #[test]
+    fn test_edit_cap() {
+        let left = sample();
+        let mut right = sample();
+        for gate in &mut right.gates {
+            gate.op = "XOR".into();
+        }
+
+        let diff = left.diff(
+            &right,
+            &DiffOptions {
+                max_edits: 2,
+                ..Default::default()
+            },
+        );
+        assert!(diff.gates.is_empty());
+        assert_eq!(
+            diff.gates_summary,
+            Some(GateDiffSummary {
+                left_gates: 3,
+                right_gates: 3,
+                positional_mismatches: 3
+            })
+        );
+    }
+}
@@ -0,0 +1,206 @@
+use std::{collections::HashMap, fmt::Write};
+
+use crate::bristol_circuit::BristolCircuit;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DotOptions {
+    /// Render only the first this many gates. Edges from later gates are left out, and a
+    /// comment counts the gates omitted.
+    pub max_gates: Option<usize>,
+
+    /// Label each edge with its wire index.
+    pub wire_labels: bool,
+}
+
+impl Default for DotOptions {
+    fn default() -> Self {
+        DotOptions {
+            max_gates: Some(1000),
+            wire_labels: false,
+        }
+    }
+}
+
+impl BristolCircuit {
+    /// A Graphviz digraph, laid out left to right. Named inputs, constants and outputs are nodes
+    /// `input:<name>`, `const:<name>` and `output:<name>` with their own shapes and colors, each
+    /// gate is a node `gate:<index>` labelled with its op and index, and each edge is a wire.
+    pub fn to_dot(&self, opts: &DotOptions) -> String {
+        let shown = opts
+            .max_gates
+            .map_or(self.gates.len(), |max| max.min(self.gates.len()));
+
+        let mut sources = HashMap::<usize, String>::new();
+        let mut out = String::new();
+
+        writeln!(out, "digraph circuit {{").unwrap();
+        writeln!(out, "  rankdir=LR;").unwrap();
+
+        let input_buses = self.input_buses();
+        let output_buses = self.output_buses();
+
+        for bus in &input_buses {
+            let id = format!("input:{}", bus.name);
+            node(
+                &mut out,
+                &id,
+                bus.name,
+                "shape=invhouse, style=filled, fillcolor=lightblue",
+            );
+
+            for wire in bus.wire..bus.wire + bus.width {
+                sources.insert(wire, id.clone());
+            }
+        }
+
+        let mut constants = self.info.constants.iter().collect::<Vec<_>>();
+        constants.sort_by_key(|(name, _)| name.as_str());
+
+        for (name, constant) in constants {
+            let id = format!("const:{}", name);
+            let label = format!("{} = {}", name, constant.value);
+            node(
+                &mut out,
+                &id,
+                &label,
+                "shape=box, style=filled, fillcolor=lightgrey",
+            );
+            sources.insert(constant.wire_index, id);
+        }
+
+        let mut edges = Vec::new();
+
+        for (i, gate) in self.gates[..shown].iter().enumerate() {
+            let id = format!("gate:{}", i);
+            node(
+                &mut out,
+                &id,
+                &format!("{} #{}", gate.op, i),
+                "shape=ellipse",
+            );
+
+            for &wire in &gate.inputs {
+                if let Some(from) = sources.get(&wire) {
+                    edges.push((from.clone(), id.clone(), wire));
+                }
+            }
+
+            for &wire in &gate.outputs {
+                sources.insert(wire, id.clone());
+            }
+        }
+
+        for bus in &output_buses {
+            let id = format!("output:{}", bus.name);
+            node(
+                &mut out,
+                &id,
+                bus.name,
+                "shape=house, style=filled, fillcolor=lightgreen",
+            );
+
+            for wire in bus.wire..bus.wire + bus.width {
+                if let Some(from) = sources.get(&wire) {
+                    edges.push((from.clone(), id.clone(), wire));
+                }
+            }
+        }
+
+        for (from, to, wire) in edges {
+            write!(out, "  {} -> {}", quote(&from), quote(&to)).unwrap();
+
+            if opts.wire_labels {
+                write!(out, " [label=\"{}\"]", wire).unwrap();
+            }
+
+            writeln!(out, ";").unwrap();
+        }
+
+        if shown < self.gates.len() {
+            writeln!(out, "  // {} more gates omitted", self.gates.len() - shown).unwrap();
+        }
+
+        writeln!(out, "}}").unwrap();
+        out
+    }
+}
+
+fn node(out: &mut String, id: &str, label: &str, attributes: &str) {
+    writeln!(
+        out,
+        "  {} [label={}, {}];",
+        quote(id),
+        quote(label),
+        attributes
+    )
+    .unwrap();
+}
+
+fn quote(s: &str) -> String {
+    format!("\"{}\"", s.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::circuit_builder::CircuitBuilder;
+
+    /// `c = (a & b) ^ one`, then `d = !c`.
+    fn sample() -> BristolCircuit {
+        let mut b = CircuitBuilder::new();
+        let a = b.input("a");
+        let x = b.input("b");
+        let one = b.constant("one", "1");
+        let and = b.gate("AND", &[a, x]);
+        let c = b.gate("XOR", &[and, one]);
+        let d = b.gate("INV", &[c]);
+        b.output("c", c);
+        b.output("d", d);
+        b.build().unwrap()
+    }
+
+    #[test]
+    fn test_dot() {
+        let opts = DotOptions {
+            max_gates: None,
+            wire_labels: true,
+        };
+
+        assert_eq!(
+            sample().to_dot(&opts),
+            r#"digraph circuit {
+  rankdir=LR;
+  "input:a" [label="a", shape=invhouse, style=filled, fillcolor=lightblue];
+  "input:b" [label="b", shape=invhouse, style=filled, fillcolor=lightblue];
+  "const:one" [label="one = 1", shape=box, style=filled, fillcolor=lightgrey];
+  "gate:0" [label="AND #0", shape=ellipse];
+  "gate:1" [label="XOR #1", shape=ellipse];
+  "gate:2" [label="INV #2", shape=ellipse];
+  "output:c" [label="c", shape=house, style=filled, fillcolor=lightgreen];
+  "output:d" [label="d", shape=house, style=filled, fillcolor=lightgreen];
+  "input:a" -> "gate:0" [label="0"];
+  "input:b" -> "gate:0" [label="1"];
+  "gate:0" -> "gate:1" [label="3"];
+  "const:one" -> "gate:1" [label="2"];
+  "gate:1" -> "gate:2" [label="4"];
+  "gate:1" -> "output:c" [label="4"];
+  "gate:2" -> "output:d" [label="5"];
+}
+"#
+        );
+    }
+
+    #[test]
+    fn test_gate_cap() {
+        let opts = DotOptions {
+            max_gates: Some(1),
+            wire_labels: false,
+        };
+        let dot = sample().to_dot(&opts);
+
+        assert!(dot.contains("  \"input:a\" -> \"gate:0\";\n"));
+        assert!(!dot.contains("gate:1"));
+        assert!(!dot.contains("-> \"output:c\""));
+        assert!(dot.ends_with("  // 2 more gates omitted\n}\n"));
+    }
+}
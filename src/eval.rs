@@ -0,0 +1,185 @@
+use crate::{
+    bristol_circuit::BristolCircuit,
+    bristol_circuit_error::BristolCircuitError,
+    constant_value::ConstantValue,
+    gate::Gate,
+    trace::{trace_progress, trace_span},
+};
+
+/// The meaning of each op for some wire value type `V`.
+pub(crate) trait GateSemantics<V> {
+    /// Ops like `EQ` take literal values rather than wires as inputs; returns the value to use
+    /// for such an input.
+    fn literal(&mut self, _op: &str, _value: usize) -> Option<V> {
+        None
+    }
+
+    fn eval_gate(
+        &mut self,
+        op: &str,
+        inputs: &[V],
+        gate_index: usize,
+    ) -> Result<Vec<V>, BristolCircuitError>;
+}
+
+pub(crate) fn evaluation_error(message: String) -> BristolCircuitError {
+    BristolCircuitError::EvaluationError { message }
+}
+
+/// The constants' wires and values, with bad values reported as evaluation errors.
+pub(crate) fn constant_seeds<T: ConstantValue>(
+    circuit: &BristolCircuit,
+) -> Result<Vec<(usize, T)>, BristolCircuitError> {
+    let constants = &circuit.info.constants;
+
+    let values = circuit.info.constant_values::<T>().map_err(|e| match e {
+        BristolCircuitError::ParsingError { message } => evaluation_error(message),
+        e => e,
+    })?;
+
+    Ok(values
+        .into_iter()
+        .map(|(name, value)| (constants[&name].wire_index, value))
+        .collect())
+}
+
+/// Hooks into evaluation, e.g. for profiling or tracing. `after_gate` is called for every gate,
+/// once its outputs have been written to `wires`, so implementations should be cheap.
+pub(crate) trait EvalObserver<V> {
+    fn before_gates(&mut self, _wires: &[Option<V>]) {}
+
+    fn after_gate(&mut self, _gate_index: usize, _gate: &Gate, _wires: &[Option<V>]) {}
+}
+
+impl<V> EvalObserver<V> for () {}
+
+/// Runs the gates in order from the seeded wires, returning the value of every wire.
+pub(crate) fn eval_wires<V: Clone, S: GateSemantics<V>>(
+    circuit: &BristolCircuit,
+    seeds: impl IntoIterator<Item = (usize, V)>,
+    semantics: &mut S,
+    observer: &mut impl EvalObserver<V>,
+) -> Result<Vec<Option<V>>, BristolCircuitError> {
+    let wire_count = circuit.wire_count.max(circuit.referenced_wire_count());
+
+    let mut wires = Vec::new();
+    eval_wires_into(circuit, wire_count, seeds, semantics, observer, &mut wires)?;
+    Ok(wires)
+}
+
+/// Like `eval_wires`, but reuses `wires` for the values, which avoids an allocation when
+/// evaluating repeatedly. `wire_count` must cover every referenced wire.
+pub(crate) fn eval_wires_into<V: Clone, S: GateSemantics<V>>(
+    circuit: &BristolCircuit,
+    wire_count: usize,
+    seeds: impl IntoIterator<Item = (usize, V)>,
+    semantics: &mut S,
+    observer: &mut impl EvalObserver<V>,
+    wires: &mut Vec<Option<V>>,
+) -> Result<(), BristolCircuitError> {
+    let _span = trace_span!("eval", gate_count = circuit.gates.len());
+
+    wires.clear();
+    wires.resize(wire_count, None);
+
+    for (wire, value) in seeds {
+        wires[wire] = Some(value);
+    }
+
+    let mut inputs = Vec::new();
+    observer.before_gates(wires);
+
+    for (gate_index, gate) in circuit.gates.iter().enumerate() {
+        inputs.clear();
+
+        for &wire in &gate.inputs {
+            let value = match semantics.literal(&gate.op, wire) {
+                Some(value) => value,
+                None => wires[wire].clone().ok_or_else(|| {
+                    evaluation_error(format!(
+                        "Gate {} ({}) reads undefined wire {}",
+                        gate_index, gate.op, wire
+                    ))
+                })?,
+            };
+
+            inputs.push(value);
+        }
+
+        let outputs = semantics.eval_gate(&gate.op, &inputs, gate_index)?;
+
+        if outputs.len() != gate.outputs.len() {
+            return Err(evaluation_error(format!(
+                "Gate {} ({}) produced {} outputs but has {} output wires",
+                gate_index,
+                gate.op,
+                outputs.len(),
+                gate.outputs.len()
+            )));
+        }
+
+        for (&wire, value) in gate.outputs.iter().zip(outputs) {
+            wires[wire] = Some(value);
+        }
+
+        observer.after_gate(gate_index, gate, wires);
+        trace_progress!(gate_index + 1);
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+pub(crate) mod test_utils {
+    use std::collections::HashMap;
+
+    use super::*;
+
+    /// Wrapping u64 semantics for the basic arithmetic ops.
+    pub struct Wrapping;
+
+    impl GateSemantics<u64> for Wrapping {
+        fn eval_gate(
+            &mut self,
+            op: &str,
+            inputs: &[u64],
+            _gate_index: usize,
+        ) -> Result<Vec<u64>, BristolCircuitError> {
+            Ok(vec![match op {
+                "AAdd" => inputs[0].wrapping_add(inputs[1]),
+                "ASub" => inputs[0].wrapping_sub(inputs[1]),
+                "AMul" => inputs[0].wrapping_mul(inputs[1]),
+                "AMux" => {
+                    if inputs[0] == 1 {
+                        inputs[1]
+                    } else {
+                        inputs[2]
+                    }
+                }
+                _ => panic!("Unsupported op {}", op),
+            }])
+        }
+    }
+
+    /// Evaluates an arithmetic circuit with single-wire named inputs and outputs.
+    pub fn eval_wrapping(circuit: &BristolCircuit, inputs: &[(&str, u64)]) -> HashMap<String, u64> {
+        let info = &circuit.info;
+
+        let seeds = inputs
+            .iter()
+            .map(|&(name, value)| (info.input_name_to_wire_index[name], value))
+            .chain(
+                info.constants
+                    .values()
+                    .map(|constant| (constant.wire_index, constant.value.parse().unwrap())),
+            )
+            .collect::<Vec<_>>();
+
+        let wires = eval_wires(circuit, seeds, &mut Wrapping, &mut ()).unwrap();
+
+        info.output_name_to_wire_index
+            .iter()
+            .map(|(name, &wire)| (name.clone(), wires[wire].unwrap()))
+            .collect()
+    }
+}
@@ -0,0 +1,574 @@
+use std::{
+    collections::{HashMap, HashSet},
+    io::Write,
+};
+
+#[cfg(feature = "bigint")]
+use num_bigint::BigUint;
+
+use crate::{
+    bristol_circuit::BristolCircuit,
+    bristol_circuit_error::BristolCircuitError,
+    eval::{evaluation_error, EvalObserver},
+    gate::Gate,
+};
+
+/// How watched wires changed during one evaluation, from
+/// [`BristolCircuit::eval_boolean_traced`] or `eval_field_traced`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct EvalTrace<V> {
+    watched: Vec<WatchedWire<V>>,
+    changes: Vec<TraceChange<V>>,
+}
+
+/// A watched wire, named from the circuit info (e.g. `sum_3` for bit 3 of output `sum`), or
+/// `wire_<n>` for internal wires. Names are sanitized to be valid VCD identifiers.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct WatchedWire<V> {
+    pub wire: usize,
+    pub name: String,
+
+    /// The value before any gate ran, which is only set for inputs and constants.
+    pub initial: Option<V>,
+}
+
+/// A gate writing a watched wire.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TraceChange<V> {
+    pub gate_index: usize,
+
+    /// The gate's depth, as used by [`VcdTimeStep::Level`].
+    pub level: usize,
+
+    pub wire: usize,
+    pub value: V,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum VcdTimeStep {
+    /// One tick per gate, so gate `i` makes its changes at time `i + 1`.
+    #[default]
+    Gate,
+
+    /// One tick per level, so changes appear at the depth of the gate that made them.
+    Level,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct VcdOptions {
+    pub time_step: VcdTimeStep,
+
+    /// The width of the bit vector used for each arithmetic wire. Wider values are truncated to
+    /// their low bits. Boolean wires are always 1 bit.
+    pub arithmetic_width: usize,
+}
+
+impl Default for VcdOptions {
+    fn default() -> Self {
+        VcdOptions {
+            time_step: VcdTimeStep::Gate,
+            arithmetic_width: 64,
+        }
+    }
+}
+
+/// A wire value that can be written to a VCD file.
+pub trait VcdValue {
+    /// The variable width, given the configured width for arithmetic values.
+    fn vcd_width(arithmetic_width: usize) -> usize;
+
+    /// The value in binary, using at most `width` digits.
+    fn vcd_bits(&self, width: usize) -> String;
+}
+
+impl VcdValue for bool {
+    fn vcd_width(_arithmetic_width: usize) -> usize {
+        1
+    }
+
+    fn vcd_bits(&self, _width: usize) -> String {
+        if *self { "1" } else { "0" }.to_string()
+    }
+}
+
+#[cfg(feature = "bigint")]
+impl VcdValue for BigUint {
+    fn vcd_width(arithmetic_width: usize) -> usize {
+        arithmetic_width
+    }
+
+    fn vcd_bits(&self, width: usize) -> String {
+        let bits = self.to_str_radix(2);
+        let low = &bits[bits.len().saturating_sub(width)..];
+
+        match low.trim_start_matches('0') {
+            "" => "0".to_string(),
+            trimmed => trimmed.to_string(),
+        }
+    }
+}
+
+impl<V> EvalTrace<V> {
+    pub fn watched(&self) -> &[WatchedWire<V>] {
+        &self.watched
+    }
+
+    /// The changes to watched wires, in evaluation order.
+    pub fn changes(&self) -> &[TraceChange<V>] {
+        &self.changes
+    }
+}
+
+impl<V: VcdValue + PartialEq> EvalTrace<V> {
+    /// Writes the trace as a Value Change Dump, for waveform viewers like GTKWave. There is one
+    /// variable per watched wire, in a `circuit` scope, with inputs and constants set at time 0.
+    pub fn write_vcd<W: Write>(
+        &self,
+        w: &mut W,
+        opts: &VcdOptions,
+    ) -> Result<(), BristolCircuitError> {
+        let width = V::vcd_width(opts.arithmetic_width);
+
+        if width == 0 {
+            return Err(BristolCircuitError::Inconsistency {
+                message: "VCD arithmetic width must be positive".to_string(),
+            });
+        }
+
+        let codes = (0..self.watched.len()).map(id_code).collect::<Vec<_>>();
+        let slots = self
+            .watched
+            .iter()
+            .enumerate()
+            .map(|(i, watched)| (watched.wire, i))
+            .collect::<HashMap<_, _>>();
+
+        writeln!(w, "$version bristol-circuit $end")?;
+        writeln!(w, "$timescale 1ns $end")?;
+        writeln!(w, "$scope module circuit $end")?;
+
+        for (watched, code) in self.watched.iter().zip(&codes) {
+            writeln!(w, "$var wire {} {} {} $end", width, code, watched.name)?;
+        }
+
+        writeln!(w, "$upscope $end")?;
+        writeln!(w, "$enddefinitions $end")?;
+
+        let write_value = |w: &mut W, value: Option<&V>, code: &str| match (width, value) {
+            (1, Some(value)) => writeln!(w, "{}{}", value.vcd_bits(1), code),
+            (1, None) => writeln!(w, "x{}", code),
+            (_, Some(value)) => writeln!(w, "b{} {}", value.vcd_bits(width), code),
+            (_, None) => writeln!(w, "bx {}", code),
+        };
+
+        writeln!(w, "#0")?;
+        writeln!(w, "$dumpvars")?;
+
+        for (watched, code) in self.watched.iter().zip(&codes) {
+            write_value(w, watched.initial.as_ref(), code)?;
+        }
+
+        writeln!(w, "$end")?;
+
+        let time = |change: &TraceChange<V>| match opts.time_step {
+            VcdTimeStep::Gate => change.gate_index + 1,
+            VcdTimeStep::Level => change.level,
+        };
+
+        let mut changes = self.changes.iter().collect::<Vec<_>>();
+        changes.sort_by_key(|change| time(change));
+
+        let mut current = self
+            .watched
+            .iter()
+            .map(|watched| watched.initial.as_ref())
+            .collect::<Vec<_>>();
+        let mut last_time = 0;
+
+        for change in changes {
+            let slot = slots[&change.wire];
+
+            if current[slot] == Some(&change.value) {
+                continue;
+            }
+
+            if time(change) != last_time {
+                last_time = time(change);
+                writeln!(w, "#{}", last_time)?;
+            }
+
+            current[slot] = Some(&change.value);
+            write_value(w, Some(&change.value), &codes[slot])?;
+        }
+
+        Ok(())
+    }
+}
+
+/// VCD identifier codes: printable ASCII from `!` to `~`, as a little-endian base-94 number.
+fn id_code(mut index: usize) -> String {
+    let mut code = String::new();
+
+    loop {
+        code.push((b'!' + (index % 94) as u8) as char);
+        index /= 94;
+
+        if index == 0 {
+            return code;
+        }
+
+        index -= 1;
+    }
+}
+
+/// Named outputs, and the trace of the evaluation that produced them.
+type Traced<O, V> = (HashMap<String, O>, EvalTrace<V>);
+
+/// Records the watched wires' values as the gates run.
+struct TraceRecorder<V> {
+    trace: EvalTrace<V>,
+    watched: HashSet<usize>,
+    levels: Vec<usize>,
+}
+
+impl<V: Clone> EvalObserver<V> for TraceRecorder<V> {
+    fn before_gates(&mut self, wires: &[Option<V>]) {
+        for watched in &mut self.trace.watched {
+            watched.initial = wires[watched.wire].clone();
+        }
+    }
+
+    fn after_gate(&mut self, gate_index: usize, gate: &Gate, wires: &[Option<V>]) {
+        for &wire in &gate.outputs {
+            if self.watched.contains(&wire) {
+                if let Some(value) = &wires[wire] {
+                    self.trace.changes.push(TraceChange {
+                        gate_index,
+                        level: self.levels[gate_index],
+                        wire,
+                        value: value.clone(),
+                    });
+                }
+            }
+        }
+    }
+}
+
+impl BristolCircuit {
+    /// Like [`BristolCircuit::eval_boolean`], also recording how each wire in `watch` changes.
+    pub fn eval_boolean_traced(
+        &self,
+        inputs: &HashMap<String, Vec<bool>>,
+        watch: &[usize],
+    ) -> Result<Traced<Vec<bool>, bool>, BristolCircuitError> {
+        let mut recorder = self.trace_recorder(watch)?;
+        let outputs = self.eval_boolean_observed(inputs, &mut recorder)?;
+
+        Ok((outputs, recorder.trace))
+    }
+
+    /// Like `eval_field`, also recording how each wire in `watch` changes.
+    #[cfg(feature = "bigint")]
+    pub fn eval_field_traced(
+        &self,
+        inputs: &HashMap<String, BigUint>,
+        modulus: &BigUint,
+        watch: &[usize],
+    ) -> Result<Traced<BigUint, BigUint>, BristolCircuitError> {
+        use crate::{eval::eval_wires, field_eval::FieldSemantics, op_classification::OpKind};
+
+        self.classify_ops().require(OpKind::Arithmetic, self)?;
+
+        let mut recorder = self.trace_recorder(watch)?;
+        let seeds = self.field_seeds(inputs, modulus)?;
+        let wires = eval_wires(self, seeds, &mut FieldSemantics { modulus }, &mut recorder)?;
+
+        Ok((self.scalar_outputs(&wires)?, recorder.trace))
+    }
+
+    fn trace_recorder<V>(&self, watch: &[usize]) -> Result<TraceRecorder<V>, BristolCircuitError> {
+        let wire_count = self.wire_count.max(self.referenced_wire_count());
+
+        if let Some(wire) = watch.iter().find(|&&wire| wire >= wire_count) {
+            return Err(evaluation_error(format!(
+                "Watched wire {} doesn't exist ({} wires)",
+                wire, wire_count
+            )));
+        }
+
+        let mut wires = watch.to_vec();
+        wires.sort();
+        wires.dedup();
+
+        let names = self.wire_names();
+        let mut used = HashSet::new();
+
+        let watched = wires
+            .iter()
+            .map(|&wire| {
+                let mut name = names
+                    .get(&wire)
+                    .cloned()
+                    .unwrap_or_else(|| format!("wire_{}", wire));
+
+                if !used.insert(name.clone()) {
+                    name = format!("{}_{}", name, wire);
+                    used.insert(name.clone());
+                }
+
+                WatchedWire {
+                    wire,
+                    name,
+                    initial: None,
+                }
+            })
+            .collect();
+
+        Ok(TraceRecorder {
+            trace: EvalTrace {
+                watched,
+                changes: Vec::new(),
+            },
+            watched: wires.into_iter().collect(),
+            levels: self.gate_depths(),
+        })
+    }
+
+    /// Sanitized names for the named input, constant and output wires, preferring the first.
+    fn wire_names(&self) -> HashMap<usize, String> {
+        let mut names = HashMap::new();
+
+        for bus in self.input_buses() {
+            for bit in 0..bus.width {
+                names.insert(bus.wire + bit, vcd_name(bus.name, bit, bus.width));
+            }
+        }
+
+        for (name, constant) in &self.info.constants {
+            names
+                .entry(constant.wire_index)
+                .or_insert_with(|| vcd_name(name, 0, 1));
+        }
+
+        for bus in self.output_buses() {
+            for bit in 0..bus.width {
+                names
+                    .entry(bus.wire + bit)
+                    .or_insert_with(|| vcd_name(bus.name, bit, bus.width));
+            }
+        }
+
+        names
+    }
+}
+
+/// Replaces anything but ASCII letters, digits and `_`, and suffixes the bit for wide buses.
+fn vcd_name(name: &str, bit: usize, width: usize) -> String {
+    let mut sanitized = name
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect::<String>();
+
+    if !sanitized.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_') {
+        sanitized.insert(0, '_');
+    }
+
+    if width == 1 {
+        sanitized
+    } else {
+        format!("{}_{}", sanitized, bit)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::circuit_builder::CircuitBuilder;
+
+    /// `sum = a ^ b` as `(a NAND b) AND (a OR b)`, and `carry = a & b` as an inverted NAND, so
+    /// the gates span two levels.
+    fn half_adder() -> BristolCircuit {
+        let mut b = CircuitBuilder::new();
+        let a = b.input("a");
+        let x = b.input("b.in");
+        let nand = b.gate("NAND", &[a, x]);
+        let or = b.gate("OR", &[a, x]);
+        let sum = b.gate("AND", &[nand, or]);
+        let carry = b.gate("INV", &[nand]);
+        b.output("sum", sum);
+        b.output("carry", carry);
+        b.build().unwrap()
+    }
+
+    fn bits(a: bool, b: bool) -> HashMap<String, Vec<bool>> {
+        HashMap::from([("a".to_string(), vec![a]), ("b.in".to_string(), vec![b])])
+    }
+
+    fn vcd(trace: &EvalTrace<bool>, opts: &VcdOptions) -> String {
+        let mut out = Vec::new();
+        trace.write_vcd(&mut out, opts).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn test_golden_vcd() {
+        let circuit = half_adder();
+        let all = (0..circuit.wire_count).collect::<Vec<_>>();
+        let (outputs, trace) = circuit
+            .eval_boolean_traced(&bits(true, true), &all)
+            .unwrap();
+
+        assert_eq!(outputs["sum"], vec![false]);
+        assert_eq!(outputs["carry"], vec![true]);
+
+        let names = trace
+            .watched()
+            .iter()
+            .map(|watched| watched.name.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(names, vec!["a", "b_in", "wire_2", "wire_3", "sum", "carry"]);
+
+        assert_eq!(
+            vcd(&trace, &VcdOptions::default()),
+            "\
+$version bristol-circuit $end
+$timescale 1ns $end
+$scope module circuit $end
+$var wire 1 ! a $end
+$var wire 1 \" b_in $end
+$var wire 1 # wire_2 $end
+$var wire 1 $ wire_3 $end
+$var wire 1 % sum $end
+$var wire 1 & carry $end
+$upscope $end
+$enddefinitions $end
+#0
+$dumpvars
+1!
+1\"
+x#
+x$
+x%
+x&
+$end
+#1
+0#
+#2
+1$
+#3
+0%
+#4
+1&
+"
+        );
+
+        let by_level = vcd(
+            &trace,
+            &VcdOptions {
+                time_step: VcdTimeStep::Level,
+                ..Default::default()
+            },
+        );
+        assert!(
+            by_level.ends_with("#1\n0#\n1$\n#2\n0%\n1&\n"),
+            "{}",
+            by_level
+        );
+    }
+
+    #[test]
+    fn test_watch_set() {
+        let circuit = half_adder();
+        let (_, trace) = circuit
+            .eval_boolean_traced(&bits(true, false), &[5, 5])
+            .unwrap();
+
+        assert_eq!(trace.watched().len(), 1);
+        assert_eq!(trace.changes().len(), 1);
+        assert!(!vcd(&trace, &VcdOptions::default()).contains("sum"));
+
+        let err = circuit
+            .eval_boolean_traced(&bits(true, false), &[6])
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Evaluation error: Watched wire 6 doesn't exist (6 wires)"
+        );
+    }
+
+    #[test]
+    fn test_id_codes() {
+        assert_eq!(id_code(0), "!");
+        assert_eq!(id_code(93), "~");
+        assert_eq!(id_code(94), "!!");
+        assert_eq!(id_code(94 + 94 * 94), "!!!");
+    }
+
+    #[test]
+    fn test_vcd_crate_reads_output() {
+        use vcd::{Command, Parser, Value};
+
+        let circuit = crate::gadgets::adder(4, true);
+        let all = (0..circuit.wire_count).collect::<Vec<_>>();
+        let inputs = HashMap::from([
+            ("a".to_string(), vec![true, true, false, false]),
+            ("b".to_string(), vec![true, false, false, false]),
+        ]);
+        let (_, trace) = circuit.eval_boolean_traced(&inputs, &all).unwrap();
+
+        let text = vcd(&trace, &VcdOptions::default());
+        let mut parser = Parser::new(text.as_bytes());
+        let header = parser.parse_header().unwrap();
+
+        let sum = header.find_var(&["circuit", "sum_2"]).unwrap();
+        assert_eq!(sum.size, 1);
+
+        let last_sum = parser
+            .map(|command| command.unwrap())
+            .filter_map(|command| match command {
+                Command::ChangeScalar(code, value) if code == sum.code => Some(value),
+                _ => None,
+            })
+            .last();
+        assert_eq!(last_sum, Some(Value::V1));
+    }
+
+    #[cfg(feature = "bigint")]
+    #[test]
+    fn test_arithmetic_vectors() {
+        use vcd::{Command, Parser};
+
+        let mut b = CircuitBuilder::new();
+        let x = b.input("x");
+        let y = b.input("y");
+        let product = b.gate("AMul", &[x, y]);
+        b.output("product", product);
+        let circuit = b.build().unwrap();
+
+        let inputs = HashMap::from([
+            ("x".to_string(), BigUint::from(6u32)),
+            ("y".to_string(), BigUint::from(7u32)),
+        ]);
+        let (_, trace) = circuit
+            .eval_field_traced(&inputs, &BigUint::from(101u32), &[0, 1, 2])
+            .unwrap();
+
+        let opts = VcdOptions {
+            arithmetic_width: 4,
+            ..Default::default()
+        };
+        let mut out = Vec::new();
+        trace.write_vcd(&mut out, &opts).unwrap();
+        let text = String::from_utf8(out).unwrap();
+
+        assert!(text.contains("$var wire 4 # product $end"), "{}", text);
+        // 42 = 0b101010, truncated to its low 4 bits.
+        assert!(text.ends_with("#1\nb1010 #\n"), "{}", text);
+
+        let mut parser = Parser::new(text.as_bytes());
+        parser.parse_header().unwrap();
+        assert!(parser
+            .map(|command| command.unwrap())
+            .any(|command| matches!(command, Command::ChangeVector(..))));
+    }
+}
@@ -0,0 +1,323 @@
+use std::collections::HashMap;
+
+use crate::{
+    bristol_circuit::BristolCircuit,
+    bristol_circuit_error::BristolCircuitError,
+    eval::{eval_wires, evaluation_error, EvalObserver, GateSemantics},
+    io_kind::IoKind,
+};
+
+/// The meaning of each op for some wire value type `V`, for use with
+/// [`BristolCircuit::eval_with`]. This is how values like field elements or secret shares are
+/// plugged in.
+pub trait Evaluator<V> {
+    /// The value of a constant from `info.constants`.
+    fn constant(&mut self, name: &str, value: &str) -> Result<V, BristolCircuitError>;
+
+    /// Ops like `EQ` take literal values rather than wires as inputs; returns the value to use
+    /// for such an input, or `None` to read the wire as usual.
+    fn literal(&mut self, _op: &str, _value: usize) -> Option<V> {
+        None
+    }
+
+    /// Evaluates gate `gate_index`, returning one value per output wire.
+    fn eval_gate(
+        &mut self,
+        op: &str,
+        inputs: &[V],
+        gate_index: usize,
+    ) -> Result<Vec<V>, BristolCircuitError>;
+}
+
+struct EvaluatorSemantics<'a, E>(&'a mut E);
+
+impl<V, E: Evaluator<V>> GateSemantics<V> for EvaluatorSemantics<'_, E> {
+    fn literal(&mut self, op: &str, value: usize) -> Option<V> {
+        self.0.literal(op, value)
+    }
+
+    fn eval_gate(
+        &mut self,
+        op: &str,
+        inputs: &[V],
+        gate_index: usize,
+    ) -> Result<Vec<V>, BristolCircuitError> {
+        self.0.eval_gate(op, inputs, gate_index)
+    }
+}
+
+impl BristolCircuit {
+    /// Evaluates the gates in order with `evaluator`. Like [`BristolCircuit::eval_boolean`],
+    /// each named input and output (and wire group) is a bus with one value per wire. Reading a
+    /// wire that no input, constant or earlier gate has set is an error naming the gate.
+    pub fn eval_with<V: Clone, E: Evaluator<V>>(
+        &self,
+        inputs: &HashMap<String, Vec<V>>,
+        evaluator: &mut E,
+    ) -> Result<HashMap<String, Vec<V>>, BristolCircuitError> {
+        let wires = self.eval_with_observed(inputs, evaluator, &mut ())?;
+
+        self.bus_outputs(&wires)
+    }
+
+    /// Like `eval_with`, returning the value of every wire.
+    pub(crate) fn eval_with_observed<V: Clone, E: Evaluator<V>>(
+        &self,
+        inputs: &HashMap<String, Vec<V>>,
+        evaluator: &mut E,
+        observer: &mut impl EvalObserver<V>,
+    ) -> Result<Vec<Option<V>>, BristolCircuitError> {
+        let mut seeds = self.bus_input_seeds(inputs)?;
+
+        let mut constants = self.info.constants.iter().collect::<Vec<_>>();
+        constants.sort_by_key(|(name, _)| name.as_str());
+
+        for (name, constant) in constants {
+            seeds.push((
+                constant.wire_index,
+                evaluator.constant(name, &constant.value)?,
+            ));
+        }
+
+        eval_wires(self, seeds, &mut EvaluatorSemantics(evaluator), observer)
+    }
+
+    /// The wire values given by `inputs`, checked against the named inputs and input groups.
+    pub(crate) fn bus_input_seeds<V: Clone>(
+        &self,
+        inputs: &HashMap<String, Vec<V>>,
+    ) -> Result<Vec<(usize, V)>, BristolCircuitError> {
+        let buses = self.input_buses();
+
+        let input_groups = self
+            .info
+            .wire_groups
+            .iter()
+            .filter(|(_, group)| group.direction == IoKind::Input)
+            .collect::<Vec<_>>();
+
+        if let Some(name) = inputs.keys().find(|name| {
+            !buses.iter().any(|bus| bus.name == name.as_str())
+                && !input_groups.iter().any(|(group, _)| group == name)
+        }) {
+            return Err(evaluation_error(format!("Unknown input {}", name)));
+        }
+
+        let mut seeds = Vec::new();
+
+        for bus in &buses {
+            let bits = inputs
+                .get(bus.name)
+                .ok_or_else(|| evaluation_error(format!("Missing value for input {}", bus.name)))?;
+
+            if bits.len() != bus.width {
+                return Err(evaluation_error(format!(
+                    "Input {} has {} bits but its width is {}",
+                    bus.name,
+                    bits.len(),
+                    bus.width
+                )));
+            }
+
+            seeds.extend(
+                bits.iter()
+                    .cloned()
+                    .enumerate()
+                    .map(|(i, bit)| (bus.wire + i, bit)),
+            );
+        }
+
+        // Input groups are optional, and override the named inputs they overlap.
+        for (name, group) in input_groups {
+            if let Some(bits) = inputs.get(name) {
+                let wires = group.span.wires();
+
+                if bits.len() != wires.len() {
+                    return Err(evaluation_error(format!(
+                        "Input {} has {} bits but its width is {}",
+                        name,
+                        bits.len(),
+                        wires.len()
+                    )));
+                }
+
+                seeds.extend(wires.into_iter().zip(bits.iter().cloned()));
+            }
+        }
+
+        Ok(seeds)
+    }
+
+    /// The values of the named outputs and output groups.
+    pub(crate) fn bus_outputs<V: Clone>(
+        &self,
+        wires: &[Option<V>],
+    ) -> Result<HashMap<String, Vec<V>>, BristolCircuitError> {
+        let outputs = self
+            .output_buses()
+            .into_iter()
+            .map(|bus| {
+                (
+                    bus.name,
+                    (bus.wire..bus.wire + bus.width).collect::<Vec<_>>(),
+                )
+            })
+            .chain(
+                self.info
+                    .wire_groups
+                    .iter()
+                    .filter(|(_, group)| group.direction == IoKind::Output)
+                    .map(|(name, group)| (name.as_str(), group.span.wires())),
+            );
+
+        outputs
+            .map(|(name, output_wires)| {
+                let bits = output_wires
+                    .into_iter()
+                    .map(|wire| {
+                        wires[wire].clone().ok_or_else(|| {
+                            evaluation_error(format!("Output {} wire {} is undefined", name, wire))
+                        })
+                    })
+                    .collect::<Result<Vec<_>, _>>()?;
+
+                Ok((name.to_string(), bits))
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{circuit_builder::CircuitBuilder, gadgets};
+
+    /// Boolean ops on XOR shares: each value is a pair whose XOR is the actual bit. AND isn't
+    /// supported, since it needs interaction.
+    struct XorShares;
+
+    impl Evaluator<(bool, bool)> for XorShares {
+        fn constant(
+            &mut self,
+            _name: &str,
+            value: &str,
+        ) -> Result<(bool, bool), BristolCircuitError> {
+            Ok((value == "1", false))
+        }
+
+        fn eval_gate(
+            &mut self,
+            op: &str,
+            inputs: &[(bool, bool)],
+            gate_index: usize,
+        ) -> Result<Vec<(bool, bool)>, BristolCircuitError> {
+            match op {
+                "XOR" => Ok(vec![(inputs[0].0 ^ inputs[1].0, inputs[0].1 ^ inputs[1].1)]),
+                "INV" => Ok(vec![(!inputs[0].0, inputs[0].1)]),
+                _ => Err(evaluation_error(format!(
+                    "Gate {} ({}) can't be evaluated on shares",
+                    gate_index, op
+                ))),
+            }
+        }
+    }
+
+    fn parity_circuit() -> BristolCircuit {
+        let mut b = CircuitBuilder::new();
+        let x = b.input_bus("x", 3);
+        let one = b.constant("one", "1");
+        let a = b.gate("XOR", &[x[0], x[1]]);
+        let c = b.gate("XOR", &[a, x[2]]);
+        let d = b.gate("XOR", &[c, one]);
+        let e = b.gate("INV", &[d]);
+        b.output_bus("out", &[c, e]);
+        b.build().unwrap()
+    }
+
+    #[test]
+    fn test_shares() {
+        let circuit = parity_circuit();
+        let inputs = HashMap::from([(
+            "x".to_string(),
+            vec![(true, true), (false, true), (true, false)],
+        )]);
+
+        let outputs = circuit.eval_with(&inputs, &mut XorShares).unwrap();
+        let revealed = outputs["out"]
+            .iter()
+            .map(|(a, b)| a ^ b)
+            .collect::<Vec<_>>();
+
+        // x = [0, 1, 1], so the parity is 0.
+        assert_eq!(revealed, vec![false, false]);
+    }
+
+    #[test]
+    fn test_errors() {
+        let mut circuit = parity_circuit();
+        let inputs = HashMap::from([("x".to_string(), vec![(false, false); 3])]);
+
+        let mut and = circuit.clone();
+        and.gates[0].op = "AND".into();
+        let err = and.eval_with(&inputs, &mut XorShares).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Evaluation error: Gate 0 (AND) can't be evaluated on shares"
+        );
+
+        circuit.gates[1].inputs[1] = circuit.wire_count;
+        circuit.recompute_wire_count();
+        let err = circuit.eval_with(&inputs, &mut XorShares).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            format!(
+                "Evaluation error: Gate 1 (XOR) reads undefined wire {}",
+                circuit.wire_count - 1
+            )
+        );
+
+        let err = circuit
+            .eval_with(&HashMap::new(), &mut XorShares)
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Evaluation error: Missing value for input x"
+        );
+    }
+
+    /// Plain booleans through the generic driver agree with `eval_boolean`.
+    struct Plain;
+
+    impl Evaluator<bool> for Plain {
+        fn constant(&mut self, _name: &str, value: &str) -> Result<bool, BristolCircuitError> {
+            Ok(value == "1")
+        }
+
+        fn eval_gate(
+            &mut self,
+            op: &str,
+            inputs: &[bool],
+            _gate_index: usize,
+        ) -> Result<Vec<bool>, BristolCircuitError> {
+            Ok(vec![match op {
+                "XOR" => inputs[0] ^ inputs[1],
+                "AND" => inputs[0] & inputs[1],
+                _ => panic!("Unsupported op {}", op),
+            }])
+        }
+    }
+
+    #[test]
+    fn test_matches_eval_boolean() {
+        let circuit = gadgets::adder(4, true);
+        let inputs = HashMap::from([
+            ("a".to_string(), vec![true, false, true, true]),
+            ("b".to_string(), vec![true, true, false, true]),
+        ]);
+
+        assert_eq!(
+            circuit.eval_with(&inputs, &mut Plain).unwrap(),
+            circuit.eval_boolean(&inputs).unwrap()
+        );
+    }
+}
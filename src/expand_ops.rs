@@ -0,0 +1,276 @@
+use std::collections::HashMap;
+
+use crate::{
+    bristol_circuit::{BristolCircuit, IoBus},
+    bristol_circuit_error::BristolCircuitError,
+    circuit_info::ConstantInfo,
+    gate::Gate,
+    trace::trace_span,
+    wire_index::WireProducer,
+};
+
+/// How deeply templates may expand into other templated ops, to catch cyclic templates.
+const MAX_EXPANSION_DEPTH: usize = 32;
+
+impl BristolCircuit {
+    /// Replaces each gate whose op has a template with a copy of the template's gates. The
+    /// template's input wires, in wire order across its named inputs, become the gate's input
+    /// wires, and likewise for its outputs, so their counts must match. Its other wires become
+    /// fresh wires, and its constants are shared by every copy, named `<op>_<name>`.
+    ///
+    /// Templated ops in the copied gates are expanded in turn, up to 32 templates deep, so a
+    /// template defined in terms of itself is an error. Each template output must be set by one
+    /// of its gates, rather than being an input or constant.
+    pub fn expand_ops(
+        &self,
+        templates: &HashMap<String, BristolCircuit>,
+    ) -> Result<BristolCircuit, BristolCircuitError> {
+        let _span = trace_span!("pass", name = "expand_ops", gate_count = self.gates.len());
+
+        let mut result = self.clone();
+        let mut expander = Expander {
+            templates,
+            next_wire: self.wire_count.max(self.referenced_wire_count()),
+            gates: Vec::with_capacity(self.gates.len()),
+            constants: &mut result.info.constants,
+        };
+
+        for (i, gate) in self.gates.iter().enumerate() {
+            expander.expand(i, gate.clone(), 0)?;
+        }
+
+        let next_wire = expander.next_wire;
+        result.gates = expander.gates;
+        result.wire_count = next_wire;
+
+        Ok(result)
+    }
+}
+
+struct Expander<'a> {
+    templates: &'a HashMap<String, BristolCircuit>,
+    next_wire: usize,
+    gates: Vec<Gate>,
+    constants: &'a mut HashMap<String, ConstantInfo>,
+}
+
+impl Expander<'_> {
+    /// Adds `gate`, which is or came from gate `index` of the circuit, expanding it if it has a
+    /// template.
+    fn expand(
+        &mut self,
+        index: usize,
+        gate: Gate,
+        depth: usize,
+    ) -> Result<(), BristolCircuitError> {
+        let Some(template) = self.templates.get(gate.op.as_str()) else {
+            self.gates.push(gate);
+            return Ok(());
+        };
+
+        let inconsistency = |message: String| BristolCircuitError::Inconsistency { message };
+
+        if depth == MAX_EXPANSION_DEPTH {
+            return Err(inconsistency(format!(
+                "Gate {} ({}): templates nest more than {} deep, so may be cyclic",
+                index, gate.op, MAX_EXPANSION_DEPTH
+            )));
+        }
+
+        let bus_wires = |buses: Vec<IoBus>| {
+            buses
+                .into_iter()
+                .flat_map(|bus| bus.wire..bus.wire + bus.width)
+                .collect::<Vec<_>>()
+        };
+        let inputs = bus_wires(template.input_buses());
+        let outputs = bus_wires(template.output_buses());
+
+        if inputs.len() != gate.inputs.len() || outputs.len() != gate.outputs.len() {
+            return Err(inconsistency(format!(
+                "Gate {} ({}) has {} inputs and {} outputs, but its template has {} and {}",
+                index,
+                gate.op,
+                gate.inputs.len(),
+                gate.outputs.len(),
+                inputs.len(),
+                outputs.len()
+            )));
+        }
+
+        let index_of_template = template.build_wire_index();
+
+        if let Some(&wire) = outputs.iter().find(|&&wire| {
+            !matches!(
+                index_of_template.producer(wire),
+                Some(WireProducer::Gate(_))
+            )
+        }) {
+            return Err(inconsistency(format!(
+                "The {} template's output wire {} isn't set by one of its gates",
+                gate.op, wire
+            )));
+        }
+
+        let mut wires = inputs
+            .into_iter()
+            .zip(gate.inputs.iter().copied())
+            .chain(outputs.into_iter().zip(gate.outputs.iter().copied()))
+            .collect::<HashMap<_, _>>();
+
+        let mut constants = template.info.constants.iter().collect::<Vec<_>>();
+        constants.sort_by_key(|(name, _)| name.as_str());
+
+        for (name, constant) in constants {
+            let wire = self.constant(&format!("{}_{}", gate.op, name), &constant.value)?;
+            wires.insert(constant.wire_index, wire);
+        }
+
+        for inner in &template.gates {
+            let mut map = |wire: usize| {
+                *wires.entry(wire).or_insert_with(|| {
+                    self.next_wire += 1;
+                    self.next_wire - 1
+                })
+            };
+
+            let expanded = Gate {
+                inputs: inner.inputs.iter().map(|&wire| map(wire)).collect(),
+                outputs: inner.outputs.iter().map(|&wire| map(wire)).collect(),
+                op: inner.op.clone(),
+            };
+
+            self.expand(index, expanded, depth + 1)?;
+        }
+
+        Ok(())
+    }
+
+    /// The wire of the constant `name`, added with a fresh wire if the circuit doesn't have it.
+    fn constant(&mut self, name: &str, value: &str) -> Result<usize, BristolCircuitError> {
+        if let Some(existing) = self.constants.get(name) {
+            if existing.value != value {
+                return Err(BristolCircuitError::Inconsistency {
+                    message: format!(
+                        "Constant {} is already \"{}\", not \"{}\"",
+                        name, existing.value, value
+                    ),
+                });
+            }
+
+            return Ok(existing.wire_index);
+        }
+
+        let wire_index = self.next_wire;
+        self.next_wire += 1;
+        self.constants.insert(
+            name.to_string(),
+            ConstantInfo {
+                value: value.to_string(),
+                wire_index,
+            },
+        );
+
+        Ok(wire_index)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{circuit_builder::CircuitBuilder, gadgets};
+
+    /// `x << 1` as `x * two`.
+    fn double() -> BristolCircuit {
+        let mut b = CircuitBuilder::new();
+        let x = b.input("x");
+        let two = b.constant("two", "2");
+        let out = b.gate("AMul", &[x, two]);
+        b.output("out", out);
+        b.build().unwrap()
+    }
+
+    /// `a * a + b`, in terms of `DOUBLE` for the doubling of `b`: `a * a + 2b - b`.
+    fn square_plus() -> BristolCircuit {
+        let mut b = CircuitBuilder::new();
+        let x = b.input("a");
+        let y = b.input("b");
+        let square = b.gate("AMul", &[x, x]);
+        let doubled = b.gate("DOUBLE", &[y]);
+        let sum = b.gate("AAdd", &[square, doubled]);
+        let out = b.gate("ASub", &[sum, y]);
+        b.output("out", out);
+        b.build().unwrap()
+    }
+
+    #[test]
+    fn test_nested_templates() {
+        let mut b = CircuitBuilder::new();
+        let x = b.input("x");
+        let y = b.input("y");
+        let p = b.gate("SQUARE_PLUS", &[x, y]);
+        let q = b.gate("SQUARE_PLUS", &[p, x]);
+        let out = b.gate("DOUBLE", &[q]);
+        b.output("out", out);
+        let circuit = b.build().unwrap();
+
+        let templates = HashMap::from([
+            ("DOUBLE".to_string(), double()),
+            ("SQUARE_PLUS".to_string(), square_plus()),
+        ]);
+        let expanded = circuit.expand_ops(&templates).unwrap();
+
+        assert!(expanded.validate().is_ok());
+        assert!(expanded.is_topologically_sorted());
+        assert_eq!(expanded.gates.len(), 2 * 4 + 1);
+        assert_eq!(expanded.info.constants.len(), 1);
+        assert_eq!(expanded.info.constants["DOUBLE_two"].value, "2");
+
+        let (x, y) = (3u64, 5u64);
+        let p = x * x + y;
+        let q = p * p + x;
+        let inputs = HashMap::from([("x".to_string(), x), ("y".to_string(), y)]);
+        assert_eq!(
+            expanded.eval_arithmetic(&inputs, None).unwrap()["out"],
+            2 * q
+        );
+    }
+
+    #[test]
+    fn test_arity_mismatch() {
+        let mut circuit = gadgets::adder(2, false);
+        circuit.gates[2].op = "DOUBLE".into();
+
+        let templates = HashMap::from([("DOUBLE".to_string(), double())]);
+        let err = circuit.expand_ops(&templates).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Inconsistency: Gate 2 (DOUBLE) has 2 inputs and 1 outputs, but its template has 1 and 1"
+        );
+    }
+
+    #[test]
+    fn test_cyclic_templates() {
+        let mut b = CircuitBuilder::new();
+        let x = b.input("x");
+        let out = b.gate("LOOP", &[x]);
+        b.output("out", out);
+        let looping = b.build().unwrap();
+
+        let templates = HashMap::from([("LOOP".to_string(), looping.clone())]);
+        let err = looping.expand_ops(&templates).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Inconsistency: Gate 0 (LOOP): templates nest more than 32 deep, so may be cyclic"
+        );
+
+        // A template passing an input straight through can't be substituted.
+        let mut b = CircuitBuilder::new();
+        let x = b.input("x");
+        b.output("out", x);
+        let identity = b.build().unwrap();
+
+        let templates = HashMap::from([("LOOP".to_string(), identity)]);
+        assert!(looping.expand_ops(&templates).is_err());
+    }
+}
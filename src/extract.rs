@@ -0,0 +1,115 @@
+use crate::{
+    bristol_circuit::BristolCircuit, bristol_circuit_error::BristolCircuitError, io_kind::IoKind,
+    prune::UnusedInputs, visit::Reachability,
+};
+
+impl BristolCircuit {
+    /// The part of the circuit that computes `output_names`: only the gates those outputs depend
+    /// on, and the inputs and constants they read, with wires renumbered densely. Output wire
+    /// groups are dropped.
+    pub fn extract_subcircuit(
+        &self,
+        output_names: &[&str],
+    ) -> Result<BristolCircuit, BristolCircuitError> {
+        let buses = self.output_buses();
+
+        if let Some(name) = output_names
+            .iter()
+            .find(|name| !buses.iter().any(|bus| bus.name == **name))
+        {
+            return Err(BristolCircuitError::Inconsistency {
+                message: format!("No output named {}", name),
+            });
+        }
+
+        let kept = buses
+            .iter()
+            .filter(|bus| output_names.contains(&bus.name))
+            .collect::<Vec<_>>();
+
+        let mut restricted = self.clone();
+        let info = &mut restricted.info;
+        info.output_name_to_wire_index
+            .retain(|name, _| output_names.contains(&name.as_str()));
+        info.output_visibility
+            .retain(|name, _| output_names.contains(&name.as_str()));
+        info.wire_groups
+            .retain(|_, group| group.direction == IoKind::Input);
+        restricted.io_widths.1 = kept.iter().map(|bus| bus.width).collect();
+
+        let reached = Reachability::new(self)
+            .backward(kept.iter().flat_map(|bus| bus.wire..bus.wire + bus.width));
+        restricted
+            .info
+            .constants
+            .retain(|_, constant| reached.wires.contains(&constant.wire_index));
+
+        Ok(restricted
+            .prune_dead_gates_with_report(UnusedInputs::Drop)
+            .0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+    use crate::circuit_builder::CircuitBuilder;
+
+    /// `sum = a + b + one` and `square = c * c`, which share nothing.
+    fn two_branches() -> BristolCircuit {
+        let mut b = CircuitBuilder::new();
+        let a = b.input("a");
+        let x = b.input("b");
+        let c = b.input("c");
+        let one = b.constant("one", "1");
+        let partial = b.gate("AAdd", &[a, x]);
+        let square = b.gate("AMul", &[c, c]);
+        let sum = b.gate("AAdd", &[partial, one]);
+        b.output("sum", sum);
+        b.output("square", square);
+        b.build().unwrap()
+    }
+
+    #[test]
+    fn test_extract_one_branch() {
+        let circuit = two_branches();
+
+        let square = circuit.extract_subcircuit(&["square"]).unwrap();
+        assert_eq!(
+            square
+                .gates
+                .iter()
+                .map(|gate| gate.op.as_str())
+                .collect::<Vec<_>>(),
+            vec!["AMul"]
+        );
+        assert_eq!(square.info.inputs(), vec![("c", 0)]);
+        assert!(square.info.constants.is_empty());
+        assert_eq!(square.wire_count, 2);
+        assert_eq!(square.io_widths, (vec![1], vec![1]));
+        square.validate().unwrap();
+
+        let outputs = square
+            .eval_arithmetic(&HashMap::from([("c".to_string(), 7)]), None)
+            .unwrap();
+        assert_eq!(outputs, HashMap::from([("square".to_string(), 49)]));
+
+        let sum = circuit.extract_subcircuit(&["sum"]).unwrap();
+        assert_eq!(sum.gates.len(), 2);
+        assert_eq!(sum.info.inputs(), vec![("a", 0), ("b", 1)]);
+        assert_eq!(sum.info.constants["one"].wire_index, 2);
+
+        let both = circuit.extract_subcircuit(&["square", "sum"]).unwrap();
+        assert_eq!(both, circuit);
+    }
+
+    #[test]
+    fn test_unknown_output() {
+        let err = two_branches()
+            .extract_subcircuit(&["sum", "product"])
+            .unwrap_err();
+        assert_eq!(err.to_string(), "Inconsistency: No output named product");
+    }
+}
@@ -0,0 +1,242 @@
+use std::{collections::HashMap, str::FromStr};
+
+use num_bigint::BigUint;
+use num_traits::{ToPrimitive, Zero};
+
+use crate::{
+    a_gate_type::AGateType,
+    bristol_circuit::BristolCircuit,
+    bristol_circuit_error::BristolCircuitError,
+    eval::{constant_seeds, eval_wires, evaluation_error, GateSemantics},
+    op_classification::OpKind,
+};
+
+/// Arithmetic modulo a prime. Comparisons and bitwise ops act on the canonical representatives
+/// in `0..modulus` and produce 0 or 1.
+pub(crate) struct FieldSemantics<'a> {
+    pub modulus: &'a BigUint,
+}
+
+impl GateSemantics<BigUint> for FieldSemantics<'_> {
+    fn eval_gate(
+        &mut self,
+        op: &str,
+        inputs: &[BigUint],
+        gate_index: usize,
+    ) -> Result<Vec<BigUint>, BristolCircuitError> {
+        let p = self.modulus;
+        let op_type = AGateType::from_str(op).map_err(|_| {
+            evaluation_error(format!(
+                "Gate {} has unsupported arithmetic op {}",
+                gate_index, op
+            ))
+        })?;
+
+        let expected = if op_type == AGateType::AMux { 3 } else { 2 };
+
+        if inputs.len() != expected {
+            return Err(evaluation_error(format!(
+                "Gate {} ({}) has {} inputs",
+                gate_index,
+                op,
+                inputs.len()
+            )));
+        }
+
+        let (a, b) = (&inputs[0], &inputs[1]);
+        let flag = |value: bool| BigUint::from(u8::from(value));
+
+        let nonzero_divisor = || {
+            if b.is_zero() {
+                Err(evaluation_error(format!(
+                    "Gate {} ({}) divides by zero",
+                    gate_index, op
+                )))
+            } else {
+                Ok(b)
+            }
+        };
+
+        let shift = || {
+            b.to_usize().ok_or_else(|| {
+                evaluation_error(format!("Gate {} ({}) shifts by {}", gate_index, op, b))
+            })
+        };
+
+        use AGateType::*;
+
+        let result = match op_type {
+            AAdd => (a + b) % p,
+            ASub => (a + p - b) % p,
+            AMul => (a * b) % p,
+            ADiv => {
+                let inverse = nonzero_divisor()?.modpow(&(p - 2u32), p);
+                (a * inverse) % p
+            }
+            AIntDiv => a / nonzero_divisor()?,
+            AMod => a % nonzero_divisor()?,
+            APow => a.modpow(b, p),
+            AEq => flag(a == b),
+            ANeq => flag(a != b),
+            ALt => flag(a < b),
+            ALEq => flag(a <= b),
+            AGt => flag(a > b),
+            AGEq => flag(a >= b),
+            AXor => (a ^ b) % p,
+            ABitOr => (a | b) % p,
+            ABitAnd => a & b,
+            ABoolOr => flag(!a.is_zero() || !b.is_zero()),
+            ABoolAnd => flag(!a.is_zero() && !b.is_zero()),
+            AShiftL => (a << shift()?) % p,
+            AShiftR => a >> shift()?,
+            AMux => {
+                // b + sel * (a - b), matching `lower_mux`.
+                let (sel, a, b) = (&inputs[0], &inputs[1], &inputs[2]);
+                (b + sel * ((a + p - b) % p)) % p
+            }
+        };
+
+        Ok(vec![result])
+    }
+}
+
+impl BristolCircuit {
+    /// Evaluates an arithmetic circuit over the integers modulo the prime `modulus`. Every named
+    /// input and output is a single field element.
+    pub fn eval_field(
+        &self,
+        inputs: &HashMap<String, BigUint>,
+        modulus: &BigUint,
+    ) -> Result<HashMap<String, BigUint>, BristolCircuitError> {
+        self.classify_ops().require(OpKind::Arithmetic, self)?;
+
+        let seeds = self.field_seeds(inputs, modulus)?;
+        let wires = eval_wires(self, seeds, &mut FieldSemantics { modulus }, &mut ())?;
+
+        self.scalar_outputs(&wires)
+    }
+
+    /// Wire values for the named inputs and constants, reduced modulo `modulus`.
+    pub(crate) fn field_seeds<V: FieldValue>(
+        &self,
+        inputs: &HashMap<String, V>,
+        modulus: &BigUint,
+    ) -> Result<Vec<(usize, V)>, BristolCircuitError> {
+        let mut seeds = self
+            .scalar_input_seeds(inputs)?
+            .into_iter()
+            .map(|(wire, value)| (wire, value.reduce(modulus)))
+            .collect::<Vec<_>>();
+
+        seeds.extend(
+            constant_seeds::<BigUint>(self)?
+                .into_iter()
+                .map(|(wire, value)| (wire, V::from_constant(value % modulus))),
+        );
+
+        Ok(seeds)
+    }
+}
+
+/// A wire value built from a field element, for sharing input seeding between field evaluators.
+pub(crate) trait FieldValue {
+    fn reduce(&self, modulus: &BigUint) -> Self;
+    fn from_constant(value: BigUint) -> Self;
+}
+
+impl FieldValue for BigUint {
+    fn reduce(&self, modulus: &BigUint) -> Self {
+        self % modulus
+    }
+
+    fn from_constant(value: BigUint) -> Self {
+        value
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{circuit_builder::CircuitBuilder, gadgets};
+
+    fn p() -> BigUint {
+        BigUint::from(101u32)
+    }
+
+    fn binary(op: &str) -> BristolCircuit {
+        let mut b = CircuitBuilder::new();
+        let x = b.input("x");
+        let y = b.input("y");
+        let out = b.gate(op, &[x, y]);
+        b.output("out", out);
+        b.build().unwrap()
+    }
+
+    fn eval(circuit: &BristolCircuit, x: u32, y: u32) -> Result<u32, BristolCircuitError> {
+        let inputs = HashMap::from([
+            ("x".to_string(), BigUint::from(x)),
+            ("y".to_string(), BigUint::from(y)),
+        ]);
+
+        Ok(circuit.eval_field(&inputs, &p())?["out"].to_u32().unwrap())
+    }
+
+    #[test]
+    fn test_ops() {
+        for (op, x, y, expected) in [
+            ("AAdd", 60, 50, 9),
+            ("ASub", 3, 5, 99),
+            ("AMul", 20, 10, 99),
+            ("ADiv", 1, 2, 51),
+            ("AIntDiv", 7, 2, 3),
+            ("AMod", 7, 4, 3),
+            ("APow", 2, 10, 14),
+            ("ALt", 3, 5, 1),
+            ("AGEq", 3, 5, 0),
+            ("AEq", 4, 4, 1),
+            ("AXor", 6, 3, 5),
+            ("ABoolAnd", 6, 0, 0),
+            ("AShiftL", 1, 7, 27),
+        ] {
+            assert_eq!(eval(&binary(op), x, y).unwrap(), expected, "{}", op);
+        }
+    }
+
+    #[test]
+    fn test_division_by_zero() {
+        let err = eval(&binary("ADiv"), 1, 0).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Evaluation error: Gate 0 (ADiv) divides by zero"
+        );
+    }
+
+    #[test]
+    fn test_gadgets() {
+        let circuit = gadgets::horner(&[3, 0, 5, 2]);
+        let inputs = HashMap::from([("x".to_string(), BigUint::from(10u32))]);
+        let modulus = BigUint::from(1_000_000_007u32);
+
+        assert_eq!(
+            circuit.eval_field(&inputs, &modulus).unwrap()["out"],
+            BigUint::from(2503u32)
+        );
+    }
+
+    #[test]
+    fn test_bad_constant() {
+        let mut b = CircuitBuilder::new();
+        let x = b.input("x");
+        let k = b.constant("k", "seven");
+        let out = b.gate("AAdd", &[x, k]);
+        b.output("out", out);
+        let circuit = b.build().unwrap();
+
+        let inputs = HashMap::from([("x".to_string(), BigUint::from(1u32))]);
+        let err = circuit.eval_field(&inputs, &p()).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Evaluation error: Constant k has non-numeric value \"seven\""
+        );
+    }
+}
@@ -0,0 +1,138 @@
+use std::{
+    fs::File,
+    io::{BufReader, BufWriter},
+    path::Path,
+};
+
+use crate::{
+    bristol_circuit::BristolCircuit,
+    bristol_circuit_error::{with_path, BristolCircuitError},
+    circuit_info::CircuitInfo,
+    raw_bristol_circuit::RawBristolCircuit,
+};
+
+impl BristolCircuit {
+    /// Loads a circuit from a Bristol file and an info file, which is read with
+    /// [`CircuitInfo::from_file`] and so is JSON unless its extension says otherwise. Errors
+    /// name the file they came from.
+    pub fn from_files(
+        bristol_path: &Path,
+        info_path: &Path,
+    ) -> Result<BristolCircuit, BristolCircuitError> {
+        let info = CircuitInfo::from_file(info_path)?;
+
+        with_path(bristol_path, || {
+            let mut r = BufReader::new(File::open(bristol_path)?);
+            BristolCircuit::read_info_and_bristol(&info, &mut r)
+        })
+    }
+
+    /// Saves the circuit as a Bristol file and an info file, the reverse of `from_files`.
+    pub fn to_files(
+        &self,
+        bristol_path: &Path,
+        info_path: &Path,
+    ) -> Result<(), BristolCircuitError> {
+        with_path(bristol_path, || {
+            let mut w = BufWriter::new(File::create(bristol_path)?);
+            self.write_bristol(&mut w)
+        })?;
+
+        self.info.to_file(info_path)
+    }
+}
+
+impl RawBristolCircuit {
+    /// Loads a JSON document holding both the Bristol text and the info.
+    pub fn from_file(path: &Path) -> Result<RawBristolCircuit, BristolCircuitError> {
+        with_path(path, || {
+            RawBristolCircuit::from_json(&std::fs::read_to_string(path)?)
+        })
+    }
+
+    pub fn to_file(&self, path: &Path) -> Result<(), BristolCircuitError> {
+        with_path(path, || {
+            let w = BufWriter::new(File::create(path)?);
+
+            serde_json::to_writer_pretty(w, self).map_err(|e| BristolCircuitError::Inconsistency {
+                message: format!("Failed to serialize circuit: {}", e),
+            })
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::*;
+    use crate::gadgets;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("bristol-{}-{}", name, std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_file_round_trips() {
+        let dir = temp_dir("files");
+        let circuit = gadgets::adder(4, true);
+
+        let bristol_path = dir.join("adder.txt");
+        let info_path = dir.join("adder.info.json");
+        circuit.to_files(&bristol_path, &info_path).unwrap();
+        assert_eq!(
+            BristolCircuit::from_files(&bristol_path, &info_path).unwrap(),
+            circuit
+        );
+
+        let raw_path = dir.join("adder.json");
+        let raw = circuit.to_raw().unwrap();
+        raw.to_file(&raw_path).unwrap();
+        assert_eq!(RawBristolCircuit::from_file(&raw_path).unwrap(), raw);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_errors_name_the_file() {
+        let dir = temp_dir("file-errors");
+        let circuit = gadgets::adder(2, false);
+
+        let bristol_path = dir.join("adder.txt");
+        let info_path = dir.join("adder.info.json");
+        let missing = dir.join("missing.json");
+
+        let err = RawBristolCircuit::from_file(&missing).unwrap_err();
+        assert!(matches!(
+            &err,
+            BristolCircuitError::FileError { path, source }
+                if path == &missing && matches!(**source, BristolCircuitError::IOError(_))
+        ));
+        assert!(
+            err.to_string().starts_with(&missing.display().to_string()),
+            "{}",
+            err
+        );
+
+        circuit.to_files(&bristol_path, &info_path).unwrap();
+        std::fs::write(&bristol_path, "1 2\nnot a header\n").unwrap();
+        let err = BristolCircuit::from_files(&bristol_path, &info_path).unwrap_err();
+        assert!(
+            err.to_string()
+                .starts_with(&format!("{}: Parsing error", bristol_path.display())),
+            "{}",
+            err
+        );
+
+        let err = BristolCircuit::from_files(&bristol_path, &missing).unwrap_err();
+        assert!(
+            matches!(&err, BristolCircuitError::FileError { path, .. } if path == &missing),
+            "{}",
+            err
+        );
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
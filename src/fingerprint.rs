@@ -0,0 +1,143 @@
+use crate::{bristol_circuit::BristolCircuit, sha256::Sha256};
+
+impl BristolCircuit {
+    /// A SHA-256 hash of the circuit's content, for caching work keyed by the circuit. Equal
+    /// circuits have equal fingerprints however they were written out, and the encoding is fixed
+    /// so fingerprints stay the same across crate versions.
+    ///
+    /// The hashed bytes are, with integers as little-endian `u64`s and strings as their length
+    /// followed by their UTF-8 bytes:
+    ///
+    /// 1. the string `bristol-circuit fingerprint v1`,
+    /// 2. `wire_count`,
+    /// 3. the input widths and then the output widths, each as a count followed by the widths,
+    /// 4. the named inputs and then the named outputs, each as a count followed by
+    ///    `(name, wire, width)` sorted by name,
+    /// 5. the constants, as a count followed by `(name, value, wire)` sorted by name,
+    /// 6. the gates in order, as a count followed by each gate's op, its input wires and its
+    ///    output wires, each list prefixed by its length.
+    ///
+    /// Wire groups, visibility, owners and the header style aren't hashed.
+    pub fn fingerprint(&self) -> [u8; 32] {
+        let mut encoder = Encoder(Sha256::new());
+        encoder.str("bristol-circuit fingerprint v1");
+        encoder.usize(self.wire_count);
+        encoder.list(&self.io_widths.0);
+        encoder.list(&self.io_widths.1);
+
+        for mut buses in [self.input_buses(), self.output_buses()] {
+            buses.sort_by_key(|bus| bus.name);
+            encoder.usize(buses.len());
+
+            for bus in buses {
+                encoder.str(bus.name);
+                encoder.usize(bus.wire);
+                encoder.usize(bus.width);
+            }
+        }
+
+        let mut constants = self.info.constants.iter().collect::<Vec<_>>();
+        constants.sort_by_key(|(name, _)| *name);
+        encoder.usize(constants.len());
+
+        for (name, constant) in constants {
+            encoder.str(name);
+            encoder.str(&constant.value);
+            encoder.usize(constant.wire_index);
+        }
+
+        encoder.usize(self.gates.len());
+
+        for gate in &self.gates {
+            encoder.str(&gate.op);
+            encoder.list(&gate.inputs);
+            encoder.list(&gate.outputs);
+        }
+
+        encoder.0.finish()
+    }
+}
+
+struct Encoder(Sha256);
+
+impl Encoder {
+    fn usize(&mut self, value: usize) {
+        self.0.update(&(value as u64).to_le_bytes());
+    }
+
+    fn str(&mut self, s: &str) {
+        self.usize(s.len());
+        self.0.update(s.as_bytes());
+    }
+
+    fn list(&mut self, values: &[usize]) {
+        self.usize(values.len());
+        values.iter().for_each(|&value| self.usize(value));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::circuit_builder::CircuitBuilder;
+
+    /// `d = (a + b) * b`, with constant `one` added.
+    fn sample() -> BristolCircuit {
+        let mut b = CircuitBuilder::new();
+        let x = b.input("a");
+        let y = b.input("b");
+        let one = b.constant("one", "1");
+        let sum = b.gate("AAdd", &[x, y]);
+        let product = b.gate("AMul", &[sum, y]);
+        let d = b.gate("AAdd", &[product, one]);
+        b.output("d", d);
+        b.build().unwrap()
+    }
+
+    fn hex(bytes: &[u8]) -> String {
+        bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+    }
+
+    #[test]
+    fn test_golden_fingerprint() {
+        // Changing this value changes every cached fingerprint; bump the version string instead.
+        assert_eq!(
+            hex(&sample().fingerprint()),
+            "7953d1e23e65be29686dbf0375af8f682e0ed5335cbfad43ed7514a302ae972a"
+        );
+    }
+
+    #[test]
+    fn test_text_round_trip() {
+        let circuit = sample();
+        let reparsed = BristolCircuit::from_info_and_bristol_string(
+            &circuit.info,
+            &circuit.get_bristol_string().unwrap(),
+        )
+        .unwrap();
+
+        assert_eq!(reparsed, circuit);
+        assert_eq!(reparsed.fingerprint(), circuit.fingerprint());
+    }
+
+    #[test]
+    fn test_changes_are_detected() {
+        let circuit = sample();
+
+        let mut renamed = circuit.clone();
+        let wire = renamed.info.input_name_to_wire_index.remove("a").unwrap();
+        renamed
+            .info
+            .input_name_to_wire_index
+            .insert("x".into(), wire);
+        assert_ne!(renamed.fingerprint(), circuit.fingerprint());
+
+        let mut constant = circuit.clone();
+        constant.info.constants.get_mut("one").unwrap().value = "2".into();
+        assert_ne!(constant.fingerprint(), circuit.fingerprint());
+
+        let mut op = circuit.clone();
+        op.gates[1].op = "ASub".into();
+        assert_ne!(op.fingerprint(), circuit.fingerprint());
+    }
+}
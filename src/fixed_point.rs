@@ -0,0 +1,305 @@
+use std::collections::HashMap;
+
+use num_bigint::{BigInt, BigUint, Sign};
+use num_traits::{FromPrimitive, ToPrimitive};
+
+use crate::{
+    bristol_circuit::BristolCircuit,
+    bristol_circuit_error::BristolCircuitError,
+    eval::{eval_wires, evaluation_error, GateSemantics},
+    field_eval::{FieldSemantics, FieldValue},
+    op_classification::OpKind,
+};
+
+/// Encodes reals as field elements `round(value * 2^fractional_bits) mod modulus`, with the upper
+/// half of the field representing negative numbers.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FixedPointCodec {
+    pub fractional_bits: u32,
+    pub modulus: BigUint,
+}
+
+impl FixedPointCodec {
+    pub fn new(fractional_bits: u32, modulus: BigUint) -> Self {
+        FixedPointCodec {
+            fractional_bits,
+            modulus,
+        }
+    }
+
+    /// Panics if `value` is NaN or infinite.
+    pub fn encode(&self, value: f64) -> BigUint {
+        assert!(value.is_finite(), "Can't encode {} as fixed point", value);
+
+        let scaled = (value * 2f64.powi(self.fractional_bits as i32)).round();
+        let scaled = BigInt::from_f64(scaled).unwrap() % BigInt::from(self.modulus.clone());
+
+        match scaled.sign() {
+            Sign::Minus => &self.modulus - scaled.magnitude(),
+            _ => scaled.magnitude().clone(),
+        }
+    }
+
+    pub fn decode(&self, value: &BigUint) -> f64 {
+        self.decode_scaled(value, self.fractional_bits)
+    }
+
+    fn decode_scaled(&self, value: &BigUint, fractional_bits: u32) -> f64 {
+        let value = value % &self.modulus;
+
+        let signed = if value > &self.modulus >> 1 {
+            -(&self.modulus - value).to_f64().unwrap()
+        } else {
+            value.to_f64().unwrap()
+        };
+
+        signed / 2f64.powi(fractional_bits as i32)
+    }
+}
+
+/// A field element carrying `scale` fractional bits.
+#[derive(Clone, Debug)]
+struct Fixed {
+    value: BigUint,
+    scale: u32,
+}
+
+impl FieldValue for Fixed {
+    fn reduce(&self, modulus: &BigUint) -> Self {
+        Fixed {
+            value: &self.value % modulus,
+            scale: self.scale,
+        }
+    }
+
+    fn from_constant(value: BigUint) -> Self {
+        Fixed { value, scale: 0 }
+    }
+}
+
+struct FixedSemantics<'a> {
+    field: FieldSemantics<'a>,
+}
+
+impl GateSemantics<Fixed> for FixedSemantics<'_> {
+    fn eval_gate(
+        &mut self,
+        op: &str,
+        inputs: &[Fixed],
+        gate_index: usize,
+    ) -> Result<Vec<Fixed>, BristolCircuitError> {
+        let scales = inputs.iter().map(|input| input.scale).collect::<Vec<_>>();
+
+        let mismatch = || {
+            evaluation_error(format!(
+                "Gate {} ({}) combines values with different fixed-point scales {:?}",
+                gate_index, op, scales
+            ))
+        };
+
+        let scale = match (op, &scales[..]) {
+            ("AAdd" | "ASub", [a, b]) => Some(*a).filter(|_| a == b).ok_or_else(mismatch)?,
+            ("AEq" | "ANeq", [a, b]) => Some(0).filter(|_| a == b).ok_or_else(mismatch)?,
+            ("AMux", [0, a, b]) => Some(*a).filter(|_| a == b).ok_or_else(mismatch)?,
+            ("AMul", [a, b]) => {
+                let scale = a + b;
+
+                // Values need scale bits plus a sign bit to decode.
+                if u64::from(scale) + 1 >= self.field.modulus.bits() {
+                    return Err(evaluation_error(format!(
+                        "Gate {} (AMul) needs {} fractional bits, more than the modulus can hold",
+                        gate_index, scale
+                    )));
+                }
+
+                scale
+            }
+            _ => {
+                return Err(evaluation_error(format!(
+                    "Gate {} ({}) doesn't preserve the fixed-point encoding",
+                    gate_index, op
+                )))
+            }
+        };
+
+        let values = inputs
+            .iter()
+            .map(|input| input.value.clone())
+            .collect::<Vec<_>>();
+
+        Ok(self
+            .field
+            .eval_gate(op, &values, gate_index)?
+            .into_iter()
+            .map(|value| Fixed { value, scale })
+            .collect())
+    }
+}
+
+impl BristolCircuit {
+    /// Evaluates an arithmetic circuit on fixed-point reals.
+    ///
+    /// Inputs are encoded with `codec`. Constants are treated as integers. `AAdd`, `ASub` and
+    /// `AMux` preserve the encoding and need operands with the same scale. `AMul` adds its
+    /// operands' fractional bits, so `x * y` on two inputs carries `2 * fractional_bits`.
+    /// Each output is decoded with the scale it has. `AEq` and `ANeq` yield plain 0 or 1. Every
+    /// other op, and any product too fine for the modulus, is an error.
+    pub fn eval_fixed(
+        &self,
+        codec: &FixedPointCodec,
+        inputs: &HashMap<String, f64>,
+    ) -> Result<HashMap<String, f64>, BristolCircuitError> {
+        self.classify_ops().require(OpKind::Arithmetic, self)?;
+
+        let encoded = inputs
+            .iter()
+            .map(|(name, &value)| {
+                (
+                    name.clone(),
+                    Fixed {
+                        value: codec.encode(value),
+                        scale: codec.fractional_bits,
+                    },
+                )
+            })
+            .collect();
+
+        let modulus = &codec.modulus;
+        let seeds = self.field_seeds(&encoded, modulus)?;
+        let mut semantics = FixedSemantics {
+            field: FieldSemantics { modulus },
+        };
+        let wires = eval_wires(self, seeds, &mut semantics, &mut ())?;
+
+        Ok(self
+            .scalar_outputs(&wires)?
+            .into_iter()
+            .map(|(name, fixed)| (name, codec.decode_scaled(&fixed.value, fixed.scale)))
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::circuit_builder::CircuitBuilder;
+
+    fn bn254() -> BigUint {
+        "21888242871839275222246405745257275088548364400416034343698204186575808495617"
+            .parse()
+            .unwrap()
+    }
+
+    fn codec() -> FixedPointCodec {
+        FixedPointCodec::new(16, bn254())
+    }
+
+    fn eval(circuit: &BristolCircuit, codec: &FixedPointCodec, inputs: &[(&str, f64)]) -> f64 {
+        let inputs = inputs
+            .iter()
+            .map(|&(name, value)| (name.to_string(), value))
+            .collect();
+
+        circuit.eval_fixed(codec, &inputs).unwrap()["out"]
+    }
+
+    #[test]
+    fn test_encode_decode() {
+        let codec = codec();
+
+        for value in [0.0, 1.5, -1.5, 1234.0625, -0.0001220703125] {
+            assert_eq!(codec.decode(&codec.encode(value)), value);
+        }
+
+        assert_eq!(codec.encode(-1.0), bn254() - BigUint::from(1u32 << 16));
+
+        // Rounds to the nearest representable value.
+        let coarse = FixedPointCodec::new(4, bn254());
+        assert_eq!(coarse.decode(&coarse.encode(0.1)), 0.125);
+        assert_eq!(coarse.decode(&coarse.encode(-0.1)), -0.125);
+    }
+
+    #[test]
+    fn test_add_sub_identity() {
+        let mut b = CircuitBuilder::new();
+        let x = b.input("x");
+        let y = b.input("y");
+        let sum = b.gate("AAdd", &[x, y]);
+        let out = b.gate("ASub", &[sum, y]);
+        b.output("out", out);
+        let circuit = b.build().unwrap();
+
+        for (x, y) in [(1.5, -2.25), (-7.0, 0.5), (0.0, 100.125)] {
+            assert_eq!(eval(&circuit, &codec(), &[("x", x), ("y", y)]), x);
+        }
+    }
+
+    #[test]
+    fn test_products_track_scale() {
+        let mut b = CircuitBuilder::new();
+        let x = b.input("x");
+        let y = b.input("y");
+        let z = b.input("z");
+        let three = b.constant("three", "3");
+        let xy = b.gate("AMul", &[x, y]);
+        let xyz = b.gate("AMul", &[xy, z]);
+        let out = b.gate("AMul", &[xyz, three]);
+        b.output("out", out);
+        let circuit = b.build().unwrap();
+
+        assert_eq!(
+            eval(&circuit, &codec(), &[("x", 1.5), ("y", -2.25), ("z", 0.5)]),
+            1.5 * -2.25 * 0.5 * 3.0
+        );
+
+        // Three 20-bit operands need 60 fractional bits plus a sign bit.
+        let small = FixedPointCodec::new(20, (BigUint::from(1u32) << 61) - 1u32);
+        let inputs = HashMap::from([
+            ("x".to_string(), 1.0),
+            ("y".to_string(), 1.0),
+            ("z".to_string(), 1.0),
+        ]);
+        assert_eq!(
+            circuit.eval_fixed(&small, &inputs).unwrap_err().to_string(),
+            "Evaluation error: Gate 1 (AMul) needs 60 fractional bits, more than the modulus can \
+             hold"
+        );
+    }
+
+    #[test]
+    fn test_scale_mismatch() {
+        let mut b = CircuitBuilder::new();
+        let x = b.input("x");
+        let square = b.gate("AMul", &[x, x]);
+        let out = b.gate("AAdd", &[square, x]);
+        b.output("out", out);
+        let circuit = b.build().unwrap();
+
+        let err = circuit
+            .eval_fixed(&codec(), &HashMap::from([("x".to_string(), 2.0)]))
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Evaluation error: Gate 1 (AAdd) combines values with different fixed-point scales \
+             [32, 16]"
+        );
+    }
+
+    #[test]
+    fn test_unsupported_op() {
+        let mut b = CircuitBuilder::new();
+        let x = b.input("x");
+        let out = b.gate("ALt", &[x, x]);
+        b.output("out", out);
+
+        let err = b
+            .build()
+            .unwrap()
+            .eval_fixed(&codec(), &HashMap::from([("x".to_string(), 2.0)]))
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Evaluation error: Gate 0 (ALt) doesn't preserve the fixed-point encoding"
+        );
+    }
+}
@@ -0,0 +1,172 @@
+use crate::{
+    bristol_circuit::BristolCircuit,
+    circuit_builder::{CircuitBuilder, WireId},
+};
+
+/// A ripple-carry adder with inputs `a` and `b` and output `sum`, each `bits` wide, plus a 1-bit
+/// `carry_out` output if `with_carry_out` is set.
+///
+/// Each full adder uses one AND and four XORs: `sum = (a ^ c) ^ b` and
+/// `carry = ((a ^ c) & (b ^ c)) ^ c`. The first bit has no carry in, so it is a half adder (one
+/// XOR, plus one AND unless it is also the last bit and no carry out is wanted), and the last bit
+/// skips its three carry gates when there is no carry out. That makes `5 * bits - 3` gates with a
+/// carry out and `5 * bits - 6` without (1 when `bits` is 1).
+pub fn adder(bits: usize, with_carry_out: bool) -> BristolCircuit {
+    build_adder(bits, false, with_carry_out)
+}
+
+/// Like `adder`, with an extra 1-bit input `carry_in`. Every bit is a full adder, so this uses
+/// `5 * bits` gates with a carry out and `5 * bits - 3` without.
+pub fn adder_with_carry_in(bits: usize, with_carry_out: bool) -> BristolCircuit {
+    build_adder(bits, true, with_carry_out)
+}
+
+fn build_adder(bits: usize, with_carry_in: bool, with_carry_out: bool) -> BristolCircuit {
+    let mut b = CircuitBuilder::new();
+    let a = b.input_bus("a", bits);
+    let bb = b.input_bus("b", bits);
+    let mut carry = with_carry_in.then(|| b.input("carry_in"));
+
+    let mut sum = Vec::with_capacity(bits);
+
+    for i in 0..bits {
+        let needs_carry = i + 1 < bits || with_carry_out;
+        let (s, c) = add_bit(&mut b, a[i], bb[i], carry, needs_carry);
+        sum.push(s);
+        carry = c;
+    }
+
+    b.output_bus("sum", &sum);
+
+    if let Some(carry) = carry.filter(|_| with_carry_out) {
+        b.output("carry_out", carry);
+    }
+
+    b.build().expect("adder construction is valid")
+}
+
+/// Adds one bit position, returning the sum bit and (if requested) the carry out.
+pub(crate) fn add_bit(
+    b: &mut CircuitBuilder,
+    x: WireId,
+    y: WireId,
+    carry: Option<WireId>,
+    needs_carry: bool,
+) -> (WireId, Option<WireId>) {
+    let Some(c) = carry else {
+        let sum = b.gate("XOR", &[x, y]);
+        return (sum, needs_carry.then(|| b.gate("AND", &[x, y])));
+    };
+
+    let x_c = b.gate("XOR", &[x, c]);
+    let sum = b.gate("XOR", &[x_c, y]);
+
+    if !needs_carry {
+        return (sum, None);
+    }
+
+    let y_c = b.gate("XOR", &[y, c]);
+    let both = b.gate("AND", &[x_c, y_c]);
+    let carry = b.gate("XOR", &[both, c]);
+
+    (sum, Some(carry))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        gadgets::test_utils::{eval_ints, mask},
+        split_mix::SplitMix64,
+    };
+
+    fn check(
+        circuit: &BristolCircuit,
+        bits: usize,
+        carry_in: bool,
+        carry_out: bool,
+        rng: &mut SplitMix64,
+    ) {
+        let x = u128::from(rng.next_u64()) & mask(bits);
+        let y = u128::from(rng.next_u64()) & mask(bits);
+        let c = u128::from(carry_in && rng.next_u64() & 1 == 1);
+
+        let mut inputs = vec![("a", x), ("b", y)];
+        if carry_in {
+            inputs.push(("carry_in", c));
+        }
+
+        let outputs = eval_ints(circuit, &inputs);
+        let total = x + y + c;
+
+        assert_eq!(outputs["sum"], total & mask(bits), "{} + {} + {}", x, y, c);
+
+        if carry_out {
+            assert_eq!(outputs["carry_out"], total >> bits);
+        } else {
+            assert!(!outputs.contains_key("carry_out"));
+        }
+    }
+
+    #[test]
+    fn test_against_integer_addition() {
+        let mut rng = SplitMix64::new(7);
+
+        for bits in [1, 2, 3, 8, 17, 64] {
+            for (carry_in, carry_out) in
+                [(false, false), (false, true), (true, false), (true, true)]
+            {
+                let circuit = if carry_in {
+                    adder_with_carry_in(bits, carry_out)
+                } else {
+                    adder(bits, carry_out)
+                };
+
+                assert_eq!(
+                    circuit.io_widths.0,
+                    if carry_in {
+                        vec![bits, bits, 1]
+                    } else {
+                        vec![bits, bits]
+                    }
+                );
+                circuit.assert_wire_count_tight();
+
+                for _ in 0..50 {
+                    check(&circuit, bits, carry_in, carry_out, &mut rng);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_boundary_values() {
+        let circuit = adder(64, true);
+        let max = u128::from(u64::MAX);
+
+        let outputs = eval_ints(&circuit, &[("a", max), ("b", max)]);
+        assert_eq!(outputs["sum"], max - 1);
+        assert_eq!(outputs["carry_out"], 1);
+
+        let outputs = eval_ints(&circuit, &[("a", 0), ("b", 0)]);
+        assert_eq!(outputs["sum"], 0);
+        assert_eq!(outputs["carry_out"], 0);
+    }
+
+    #[test]
+    fn test_gate_counts() {
+        let and_count =
+            |circuit: &BristolCircuit| circuit.gates.iter().filter(|g| g.op == "AND").count();
+
+        for bits in [2, 8, 64] {
+            assert_eq!(adder(bits, true).gates.len(), 5 * bits - 3);
+            assert_eq!(adder(bits, false).gates.len(), 5 * bits - 6);
+            assert_eq!(adder_with_carry_in(bits, true).gates.len(), 5 * bits);
+            assert_eq!(adder_with_carry_in(bits, false).gates.len(), 5 * bits - 3);
+            assert_eq!(and_count(&adder(bits, true)), bits);
+        }
+
+        assert_eq!(adder(1, false).gates.len(), 1);
+        assert_eq!(adder(1, true).gates.len(), 2);
+    }
+}
@@ -0,0 +1,175 @@
+use std::fmt::Display;
+
+use crate::{
+    bristol_circuit::BristolCircuit,
+    circuit_builder::{CircuitBuilder, WireId},
+};
+
+/// Adds inputs `x0..x{n-1}` with a balanced tree of `AAdd` gates, output `out`.
+pub fn sum(n: usize) -> BristolCircuit {
+    assert!(n > 0, "sum needs at least one input");
+
+    let mut b = CircuitBuilder::new();
+    let terms = (0..n)
+        .map(|i| b.input(&format!("x{}", i)))
+        .collect::<Vec<_>>();
+
+    let total = sum_tree(&mut b, terms);
+    b.output("out", total);
+    b.build().expect("sum construction is valid")
+}
+
+/// Computes `a0 * b0 + ... + a{n-1} * b{n-1}` from inputs `a0..a{n-1}` and `b0..b{n-1}`, output
+/// `out`. The products are summed with a balanced tree.
+pub fn dot_product(n: usize) -> BristolCircuit {
+    assert!(n > 0, "dot_product needs at least one pair of inputs");
+
+    let mut b = CircuitBuilder::new();
+    let a = (0..n)
+        .map(|i| b.input(&format!("a{}", i)))
+        .collect::<Vec<_>>();
+    let bs = (0..n)
+        .map(|i| b.input(&format!("b{}", i)))
+        .collect::<Vec<_>>();
+
+    let products = a
+        .into_iter()
+        .zip(bs)
+        .map(|(x, y)| b.gate("AMul", &[x, y]))
+        .collect();
+
+    let total = sum_tree(&mut b, products);
+    b.output("out", total);
+    b.build().expect("dot product construction is valid")
+}
+
+/// Evaluates the polynomial `c0 + c1 * x + c2 * x^2 + ...` at input `x` by Horner's rule, output
+/// `out`. The coefficients (lowest degree first) become constants `c0..` in the circuit info.
+pub fn horner(coefficients: &[impl Display]) -> BristolCircuit {
+    assert!(
+        !coefficients.is_empty(),
+        "horner needs at least one coefficient"
+    );
+
+    let mut b = CircuitBuilder::new();
+    let x = b.input("x");
+    let constants = coefficients
+        .iter()
+        .enumerate()
+        .map(|(i, c)| b.constant(&format!("c{}", i), &c.to_string()))
+        .collect::<Vec<_>>();
+
+    let (&leading, rest) = constants.split_last().unwrap();
+    let mut acc = leading;
+
+    for &c in rest.iter().rev() {
+        let scaled = b.gate("AMul", &[acc, x]);
+        acc = b.gate("AAdd", &[scaled, c]);
+    }
+
+    b.output("out", acc);
+    b.build().expect("horner construction is valid")
+}
+
+fn sum_tree(b: &mut CircuitBuilder, mut terms: Vec<WireId>) -> WireId {
+    while terms.len() > 1 {
+        terms = terms
+            .chunks(2)
+            .map(|pair| match pair {
+                [l, r] => b.gate("AAdd", &[*l, *r]),
+                [single] => *single,
+                _ => unreachable!(),
+            })
+            .collect();
+    }
+
+    terms[0]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{eval::test_utils::eval_wrapping, split_mix::SplitMix64};
+
+    fn depth(circuit: &BristolCircuit) -> usize {
+        let mut depths = vec![0; circuit.wire_count];
+
+        for gate in &circuit.gates {
+            let d = gate.inputs.iter().map(|&w| depths[w]).max().unwrap_or(0) + 1;
+
+            for &w in &gate.outputs {
+                depths[w] = d;
+            }
+        }
+
+        depths.into_iter().max().unwrap_or(0)
+    }
+
+    #[test]
+    fn test_sum() {
+        let mut rng = SplitMix64::new(5);
+
+        for n in [1, 2, 3, 7, 64] {
+            let circuit = sum(n);
+            assert_eq!(circuit.gates.len(), n - 1);
+            assert_eq!(
+                depth(&circuit),
+                n.next_power_of_two().trailing_zeros() as usize
+            );
+
+            let values = (0..n).map(|_| rng.next_u64()).collect::<Vec<_>>();
+            let names = (0..n).map(|i| format!("x{}", i)).collect::<Vec<_>>();
+            let inputs = names
+                .iter()
+                .map(String::as_str)
+                .zip(values.iter().copied())
+                .collect::<Vec<_>>();
+
+            let expected = values.iter().fold(0u64, |acc, v| acc.wrapping_add(*v));
+            assert_eq!(eval_wrapping(&circuit, &inputs)["out"], expected);
+        }
+    }
+
+    #[test]
+    fn test_dot_product() {
+        let mut rng = SplitMix64::new(6);
+
+        for n in [1, 4, 9] {
+            let circuit = dot_product(n);
+            assert_eq!(circuit.gates.len(), 2 * n - 1);
+
+            let a = (0..n).map(|_| rng.next_u64() % 1000).collect::<Vec<_>>();
+            let b = (0..n).map(|_| rng.next_u64() % 1000).collect::<Vec<_>>();
+            let names = (0..n)
+                .flat_map(|i| [format!("a{}", i), format!("b{}", i)])
+                .collect::<Vec<_>>();
+            let inputs = names
+                .iter()
+                .map(String::as_str)
+                .zip((0..n).flat_map(|i| [a[i], b[i]]))
+                .collect::<Vec<_>>();
+
+            let expected = a.iter().zip(&b).map(|(x, y)| x * y).sum::<u64>();
+            assert_eq!(eval_wrapping(&circuit, &inputs)["out"], expected);
+        }
+    }
+
+    #[test]
+    fn test_horner() {
+        let coefficients = [3u64, 0, 5, 2];
+        let circuit = horner(&coefficients);
+
+        assert_eq!(circuit.info.constants.len(), 4);
+        assert_eq!(circuit.info.constants["c2"].value, "5");
+        assert_eq!(circuit.gates.len(), 6);
+
+        for x in [0u64, 1, 2, 10, 12345] {
+            let expected = 3 + 5 * x * x + 2 * x * x * x;
+            assert_eq!(eval_wrapping(&circuit, &[("x", x)])["out"], expected);
+        }
+
+        let constant = horner(&["42"]);
+        assert!(constant.gates.is_empty());
+        assert_eq!(eval_wrapping(&constant, &[("x", 7)])["out"], 42);
+    }
+}
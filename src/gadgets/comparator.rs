@@ -0,0 +1,197 @@
+use crate::{
+    bristol_circuit::BristolCircuit,
+    circuit_builder::{CircuitBuilder, WireId},
+};
+
+/// Unsigned `a < b` on `bits`-wide inputs `a` and `b`, with a 1-bit output `out`.
+///
+/// Computes the borrow out of `a - b` with one AND per bit.
+pub fn less_than(bits: usize) -> BristolCircuit {
+    comparator(bits, false)
+}
+
+/// Unsigned `a >= b`, the negation of `less_than`, with the same AND count.
+pub fn greater_equal(bits: usize) -> BristolCircuit {
+    comparator(bits, true)
+}
+
+/// `a == b` on `bits`-wide inputs `a` and `b`, with a 1-bit output `out`.
+///
+/// ANDs the per-bit equalities together in a balanced tree, using `bits - 1` ANDs.
+pub fn equals(bits: usize) -> BristolCircuit {
+    let mut b = CircuitBuilder::new();
+    let x = b.input_bus("a", bits);
+    let y = b.input_bus("b", bits);
+
+    let mut layer = x
+        .iter()
+        .zip(&y)
+        .map(|(&x, &y)| {
+            let differ = b.gate("XOR", &[x, y]);
+            b.gate("INV", &[differ])
+        })
+        .collect::<Vec<_>>();
+
+    while layer.len() > 1 {
+        layer = layer
+            .chunks(2)
+            .map(|pair| match pair {
+                [l, r] => b.gate("AND", &[*l, *r]),
+                [single] => *single,
+                _ => unreachable!(),
+            })
+            .collect();
+    }
+
+    b.output("out", layer[0]);
+    b.build().expect("equality construction is valid")
+}
+
+fn comparator(bits: usize, negate: bool) -> BristolCircuit {
+    let mut b = CircuitBuilder::new();
+    let x = b.input_bus("a", bits);
+    let y = b.input_bus("b", bits);
+
+    let mut borrow = None;
+
+    for i in 0..bits {
+        borrow = Some(borrow_bit(&mut b, x[i], y[i], borrow));
+    }
+
+    let lt = borrow.expect("comparators need at least one bit");
+    let out = if negate { b.gate("INV", &[lt]) } else { lt };
+
+    b.output("out", out);
+    b.build().expect("comparator construction is valid")
+}
+
+/// The borrow out of `x - y - borrow`, i.e. `majority(!x, y, borrow)`, with one AND.
+pub(crate) fn borrow_bit(
+    b: &mut CircuitBuilder,
+    x: WireId,
+    y: WireId,
+    borrow: Option<WireId>,
+) -> WireId {
+    let Some(c) = borrow else {
+        let not_x = b.gate("INV", &[x]);
+        return b.gate("AND", &[not_x, y]);
+    };
+
+    let x_c = b.gate("XOR", &[x, c]);
+    let not_x_c = b.gate("INV", &[x_c]);
+    let y_c = b.gate("XOR", &[y, c]);
+    let both = b.gate("AND", &[not_x_c, y_c]);
+    b.gate("XOR", &[both, c])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        gadgets::test_utils::{eval_ints, mask},
+        split_mix::SplitMix64,
+    };
+
+    const WIDTHS: [usize; 5] = [1, 2, 5, 32, 64];
+
+    fn and_count(circuit: &BristolCircuit) -> usize {
+        circuit.gates.iter().filter(|gate| gate.op == "AND").count()
+    }
+
+    fn check_all(bits: usize, pairs: &[(u128, u128)]) {
+        let lt = less_than(bits);
+        let ge = greater_equal(bits);
+        let eq = equals(bits);
+
+        for &(x, y) in pairs {
+            let inputs = [("a", x), ("b", y)];
+            assert_eq!(
+                eval_ints(&lt, &inputs)["out"],
+                u128::from(x < y),
+                "{} < {}",
+                x,
+                y
+            );
+            assert_eq!(
+                eval_ints(&ge, &inputs)["out"],
+                u128::from(x >= y),
+                "{} >= {}",
+                x,
+                y
+            );
+            assert_eq!(
+                eval_ints(&eq, &inputs)["out"],
+                u128::from(x == y),
+                "{} == {}",
+                x,
+                y
+            );
+        }
+    }
+
+    #[test]
+    fn test_boundaries() {
+        for bits in WIDTHS {
+            let max = mask(bits);
+            check_all(
+                bits,
+                &[
+                    (0, 0),
+                    (0, max),
+                    (max, 0),
+                    (max, max),
+                    (max - 1, max),
+                    (1, 0),
+                ],
+            );
+        }
+    }
+
+    #[test]
+    fn test_random() {
+        let mut rng = SplitMix64::new(3);
+
+        for bits in WIDTHS {
+            let pairs = (0..40)
+                .map(|i| {
+                    let x = u128::from(rng.next_u64()) & mask(bits);
+                    // Make some pairs equal or nearly equal to exercise the low bits.
+                    let y = match i % 4 {
+                        0 => x,
+                        1 => x ^ 1,
+                        _ => u128::from(rng.next_u64()) & mask(bits),
+                    };
+                    (x, y)
+                })
+                .collect::<Vec<_>>();
+
+            check_all(bits, &pairs);
+        }
+    }
+
+    #[test]
+    fn test_unsigned_semantics() {
+        // With 4 bits, 0b1000 is 8, not -8.
+        let outputs = eval_ints(&less_than(4), &[("a", 0b0001), ("b", 0b1000)]);
+        assert_eq!(outputs["out"], 1);
+    }
+
+    #[test]
+    fn test_and_counts() {
+        for bits in WIDTHS {
+            assert_eq!(and_count(&less_than(bits)), bits);
+            assert_eq!(and_count(&greater_equal(bits)), bits);
+            assert_eq!(and_count(&equals(bits)), bits - 1);
+        }
+    }
+
+    #[test]
+    fn test_interface() {
+        for circuit in [less_than(8), greater_equal(8), equals(8)] {
+            assert_eq!(circuit.io_widths, (vec![8, 8], vec![1]));
+            assert_eq!(circuit.info.input_name_to_wire_index["a"], 0);
+            assert_eq!(circuit.info.input_name_to_wire_index["b"], 8);
+            assert!(circuit.info.output_name_to_wire_index.contains_key("out"));
+        }
+    }
+}
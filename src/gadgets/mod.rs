@@ -0,0 +1,63 @@
+//! Generators for commonly needed circuits, built with `CircuitBuilder`.
+//!
+//! Multi-bit inputs and outputs are buses listed least significant bit first. Binary gadgets take
+//! inputs `a` and `b`; single-result gadgets name their output `out`.
+
+mod adder;
+mod arithmetic;
+mod comparator;
+mod mux_tree;
+
+pub use adder::{adder, adder_with_carry_in};
+pub use arithmetic::{dot_product, horner, sum};
+pub use comparator::{equals, greater_equal, less_than};
+pub use mux_tree::{mux_tree, one_hot};
+
+pub(crate) use adder::add_bit;
+pub(crate) use comparator::borrow_bit;
+
+#[cfg(test)]
+pub(crate) mod test_utils {
+    use std::collections::HashMap;
+
+    use crate::bristol_circuit::BristolCircuit;
+
+    pub fn to_bits(value: u128, width: usize) -> Vec<bool> {
+        (0..width).map(|i| value >> i & 1 == 1).collect()
+    }
+
+    pub fn from_bits(bits: &[bool]) -> u128 {
+        bits.iter()
+            .enumerate()
+            .fold(0, |value, (i, &bit)| value | (u128::from(bit) << i))
+    }
+
+    /// Evaluates a boolean circuit on integer-valued buses.
+    pub fn eval_ints(circuit: &BristolCircuit, inputs: &[(&str, u128)]) -> HashMap<String, u128> {
+        let widths = circuit
+            .input_buses()
+            .into_iter()
+            .map(|bus| (bus.name.to_string(), bus.width))
+            .collect::<HashMap<_, _>>();
+
+        let inputs = inputs
+            .iter()
+            .map(|&(name, value)| (name.to_string(), to_bits(value, widths[name])))
+            .collect();
+
+        circuit
+            .eval_boolean(&inputs)
+            .unwrap()
+            .into_iter()
+            .map(|(name, bits)| (name, from_bits(&bits)))
+            .collect()
+    }
+
+    pub fn mask(width: usize) -> u128 {
+        if width >= 128 {
+            u128::MAX
+        } else {
+            (1 << width) - 1
+        }
+    }
+}
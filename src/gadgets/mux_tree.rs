@@ -0,0 +1,191 @@
+use crate::{
+    bristol_circuit::BristolCircuit,
+    circuit_builder::{CircuitBuilder, WireId},
+};
+
+/// Selects one of `n_choices` values: inputs `sel` (`ceil(log2(n_choices))` bits) and
+/// `in0..in{n_choices - 1}` (`width` bits each), output `out` (`width` bits).
+///
+/// A `sel` value of `n_choices` or more selects `in0`. With a single choice there is no `sel`
+/// input and `out` is a copy of `in0`.
+///
+/// Each level of the tree picks between pairs with `a ^ (sel & (a ^ b))`, so only XOR and AND
+/// gates are used.
+pub fn mux_tree(n_choices: usize, width: usize) -> BristolCircuit {
+    assert!(n_choices > 0, "mux_tree needs at least one choice");
+
+    let mut b = CircuitBuilder::new();
+    let sel_bits = select_width(n_choices);
+    let sel = if sel_bits > 0 {
+        b.input_bus("sel", sel_bits)
+    } else {
+        Vec::new()
+    };
+
+    let inputs = (0..n_choices)
+        .map(|i| b.input_bus(&format!("in{}", i), width))
+        .collect::<Vec<_>>();
+
+    // Padding with in0 is what makes out-of-range selects pick it.
+    let mut layer = (0..1 << sel_bits)
+        .map(|i| inputs.get(i).unwrap_or(&inputs[0]).clone())
+        .collect::<Vec<_>>();
+
+    for &s in &sel {
+        layer = layer
+            .chunks(2)
+            .map(|pair| select(&mut b, s, &pair[0], &pair[1]))
+            .collect();
+    }
+
+    b.output_bus("out", &layer[0]);
+    b.build().expect("mux tree construction is valid")
+}
+
+/// Decodes an index into a one-hot vector: input `sel` (`ceil(log2(n))` bits), output `out`
+/// (`n` bits) with only bit `sel` set. A `sel` value of `n` or more sets no bits.
+///
+/// `n` must be at least 2.
+pub fn one_hot(n: usize) -> BristolCircuit {
+    assert!(n >= 2, "one_hot needs at least two outputs");
+
+    let mut b = CircuitBuilder::new();
+    let sel = b.input_bus("sel", select_width(n));
+
+    // After processing bit j, matches[i] is set when the low j + 1 bits of sel equal i.
+    let not_first = b.gate("INV", &[sel[0]]);
+    let mut matches = vec![not_first, sel[0]];
+
+    for (j, &s) in sel.iter().enumerate().skip(1) {
+        let not_s = b.gate("INV", &[s]);
+
+        matches = (0..n.min(2 << j))
+            .map(|i| {
+                let bit = if i >> j & 1 == 1 { s } else { not_s };
+                b.gate("AND", &[matches[i % (1 << j)], bit])
+            })
+            .collect();
+    }
+
+    matches.truncate(n);
+
+    b.output_bus("out", &matches);
+    b.build().expect("one-hot construction is valid")
+}
+
+fn select_width(n: usize) -> usize {
+    n.next_power_of_two().trailing_zeros() as usize
+}
+
+fn select(b: &mut CircuitBuilder, sel: WireId, a: &[WireId], other: &[WireId]) -> Vec<WireId> {
+    if a == other {
+        return a.to_vec();
+    }
+
+    a.iter()
+        .zip(other)
+        .map(|(&x, &y)| {
+            let differ = b.gate("XOR", &[x, y]);
+            let chosen = b.gate("AND", &[sel, differ]);
+            b.gate("XOR", &[x, chosen])
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        gadgets::test_utils::{eval_ints, mask},
+        split_mix::SplitMix64,
+    };
+
+    fn eval_mux(circuit: &BristolCircuit, sel: u128, values: &[u128]) -> u128 {
+        let names = (0..values.len())
+            .map(|i| format!("in{}", i))
+            .collect::<Vec<_>>();
+
+        let mut inputs = names
+            .iter()
+            .zip(values)
+            .map(|(name, &value)| (name.as_str(), value))
+            .collect::<Vec<_>>();
+
+        if circuit.info.input_name_to_wire_index.contains_key("sel") {
+            inputs.push(("sel", sel));
+        }
+
+        eval_ints(circuit, &inputs)["out"]
+    }
+
+    #[test]
+    fn test_mux_tree_every_select() {
+        let width = 3;
+
+        for n in 1..=6 {
+            let circuit = mux_tree(n, width);
+            let values = (0..n as u128)
+                .map(|i| (i * 5 + 1) & mask(width))
+                .collect::<Vec<_>>();
+            let sel_width = select_width(n);
+
+            for sel in 0..1 << sel_width {
+                let expected = values.get(sel as usize).copied().unwrap_or(values[0]);
+                assert_eq!(
+                    eval_mux(&circuit, sel, &values),
+                    expected,
+                    "n {} sel {}",
+                    n,
+                    sel
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_mux_tree_random() {
+        let mut rng = SplitMix64::new(11);
+
+        for (n, width) in [(13, 8), (32, 16), (5, 64)] {
+            let circuit = mux_tree(n, width);
+            assert_eq!(circuit.io_widths.1, vec![width]);
+
+            for _ in 0..20 {
+                let values = (0..n)
+                    .map(|_| u128::from(rng.next_u64()) & mask(width))
+                    .collect::<Vec<_>>();
+                let sel = u128::from(rng.next_u64()) & mask(select_width(n));
+
+                let expected = values.get(sel as usize).copied().unwrap_or(values[0]);
+                assert_eq!(eval_mux(&circuit, sel, &values), expected);
+            }
+        }
+    }
+
+    #[test]
+    fn test_mux_tree_gate_set() {
+        assert!(mux_tree(7, 4)
+            .gates
+            .iter()
+            .all(|gate| gate.op == "XOR" || gate.op == "AND"));
+    }
+
+    #[test]
+    fn test_one_hot() {
+        for n in 2..=9 {
+            let circuit = one_hot(n);
+            assert_eq!(circuit.io_widths, (vec![select_width(n)], vec![n]));
+
+            for sel in 0..1u128 << select_width(n) {
+                let expected = if sel < n as u128 { 1 << sel } else { 0 };
+                assert_eq!(
+                    eval_ints(&circuit, &[("sel", sel)])["out"],
+                    expected,
+                    "n {} sel {}",
+                    n,
+                    sel
+                );
+            }
+        }
+    }
+}
@@ -1,14 +1,87 @@
 use core::fmt;
-use std::fmt::{Display, Formatter};
+use std::{
+    fmt::{Display, Formatter},
+    str::FromStr,
+};
 
 use serde::{Deserialize, Serialize};
+use smallvec::{smallvec, SmallVec};
+
+use crate::{
+    bristol_circuit_error::BristolCircuitError,
+    bristol_line::BristolLine,
+    lut::{validate_lut_gate, Lut},
+    op_name::OpName,
+};
 
 /// Represents a circuit gate, with a left-hand input, right-hand input, and output node identifiers.
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
 pub struct Gate {
-    pub inputs: Vec<usize>,
-    pub outputs: Vec<usize>,
-    pub op: String,
+    pub inputs: GateInputs,
+    pub outputs: GateOutputs,
+    pub op: OpName,
+}
+
+/// A gate's input wires. Nearly every gate has at most two, which are stored inline, so most
+/// gates need no allocation of their own. Build one from a `Vec` or slice with `.into()`.
+pub type GateInputs = SmallVec<[usize; 2]>;
+
+/// A gate's output wires, stored inline for the usual single output.
+pub type GateOutputs = SmallVec<[usize; 1]>;
+
+impl Gate {
+    pub fn new_lut(inputs: Vec<usize>, output: usize, lut: &Lut) -> Gate {
+        Gate {
+            inputs: inputs.into(),
+            outputs: smallvec![output],
+            op: lut.op().into(),
+        }
+    }
+
+    /// The truth table of a LUT gate, or `None` for other ops.
+    pub fn lut(&self) -> Option<Result<Lut, BristolCircuitError>> {
+        Lut::parse(&self.op, self.inputs.len())
+    }
+
+    /// The wires the gate reads: its inputs, unless they are literal values.
+    pub(crate) fn input_wires(&self) -> &[usize] {
+        match takes_literals(&self.op) {
+            true => &[],
+            false => &self.inputs,
+        }
+    }
+}
+
+/// Whether gates with `op` take literal values rather than wires as inputs, as `EQ` does.
+pub(crate) fn takes_literals(op: &str) -> bool {
+    op == "EQ"
+}
+
+/// The boolean an input `value` of an `op` gate stands for, if it's a literal: anything but 0 is
+/// true.
+pub(crate) fn bool_literal(op: &str, value: usize) -> Option<bool> {
+    takes_literals(op).then_some(value != 0)
+}
+
+/// Checks the shapes of gates whose op determines their input and output counts: LUTs, `MUX` and
+/// `AMux`, which select between two values, and `MAND`, which ANDs the first half of its inputs
+/// pairwise with the second half, so has two inputs per output.
+pub(crate) fn validate_gate_shape(op: &str, inputs: usize, outputs: usize) -> Result<(), String> {
+    if op == "MAND" && (outputs == 0 || inputs != 2 * outputs) {
+        return Err(format!(
+            "MAND gate needs two inputs per output, got {} inputs and {} outputs",
+            inputs, outputs
+        ));
+    }
+
+    if (op == "MUX" || op == "AMux") && (inputs, outputs) != (3, 1) {
+        return Err(format!(
+            "{} gate needs 3 inputs and 1 output, got {} inputs and {} outputs",
+            op, inputs, outputs
+        ));
+    }
+
+    validate_lut_gate(op, inputs, outputs)
 }
 
 impl Display for Gate {
@@ -27,3 +100,105 @@ impl Display for Gate {
         write!(f, " {}", self.op)
     }
 }
+
+impl FromStr for Gate {
+    type Err = BristolCircuitError;
+
+    /// Parses a gate line such as `2 1 0 1 2 AAdd`, which must have nothing after the op.
+    fn from_str(s: &str) -> Result<Gate, BristolCircuitError> {
+        BristolLine::new(s.split_whitespace().map(str::to_string).collect()).gate()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+    use crate::{bristol_circuit::BristolCircuit, circuit_info::CircuitInfo};
+
+    /// A Bristol Fashion 2-bit adder of `a` (wires 0-1) and `b` (wires 2-3), least significant
+    /// bit first, using `MAND` for both bits' ANDs. The sum is on the last two wires.
+    const FASHION_ADDER: &str = "\
+4 9
+2 2 2
+1 2
+
+4 2 0 1 2 3 4 5 MAND
+2 1 1 3 6 XOR
+2 1 0 2 7 XOR
+2 1 6 4 8 XOR
+";
+
+    fn adder_info() -> CircuitInfo {
+        CircuitInfo {
+            input_name_to_wire_index: HashMap::from([("a".to_string(), 0), ("b".to_string(), 2)]),
+            output_name_to_wire_index: HashMap::from([("sum".to_string(), 7)]),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_bristol_fashion_adder() {
+        let circuit =
+            BristolCircuit::from_info_and_bristol_string(&adder_info(), FASHION_ADDER).unwrap();
+        assert_eq!(circuit.gates[0].inputs[..], [0, 1, 2, 3]);
+        assert_eq!(circuit.gates[0].outputs[..], [4, 5]);
+        circuit.validate().unwrap();
+
+        for (a, b) in [(0, 0), (1, 1), (2, 1), (3, 3)] {
+            let bits = |n: usize| vec![n & 1 == 1, n & 2 == 2];
+            let outputs = circuit
+                .eval_boolean(&HashMap::from([
+                    ("a".to_string(), bits(a)),
+                    ("b".to_string(), bits(b)),
+                ]))
+                .unwrap();
+
+            assert_eq!(outputs["sum"], bits((a + b) % 4), "{} + {}", a, b);
+        }
+
+        assert_eq!(circuit.get_bristol_string().unwrap(), FASHION_ADDER);
+    }
+
+    #[test]
+    fn test_from_str() {
+        let gate = "2 1 0 1 2 AAdd".parse::<Gate>().unwrap();
+        assert_eq!(gate.inputs[..], [0, 1]);
+        assert_eq!(gate.outputs[..], [2]);
+        assert_eq!(gate.op, "AAdd");
+        assert_eq!(gate.to_string().parse::<Gate>().unwrap(), gate);
+
+        let err = "2 1 0 1 2 AAdd 7".parse::<Gate>().unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Parsing error: Inconsistent part length for gate (actual: 7, expected: 6) in \
+             line \"2 1 0 1 2 AAdd 7\""
+        );
+
+        assert!("2 1 0 1 AAdd".parse::<Gate>().is_err());
+        assert!("".parse::<Gate>().is_err());
+        assert!("3 2 0 1 2 4 5 MAND".parse::<Gate>().is_err());
+    }
+
+    #[test]
+    fn test_mand_shape() {
+        let bad = FASHION_ADDER.replace("4 2 0 1 2 3 4 5 MAND", "3 2 0 1 2 4 5 MAND");
+        let err = BristolCircuit::from_info_and_bristol_string(&adder_info(), &bad).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Parsing error: MAND gate needs two inputs per output, got 3 inputs and 2 outputs \
+             in line 5 \"3 2 0 1 2 4 5 MAND\""
+        );
+
+        let mut circuit =
+            BristolCircuit::from_info_and_bristol_string(&adder_info(), FASHION_ADDER).unwrap();
+        circuit.gates[0].outputs.pop();
+        let err = circuit.validate().unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Inconsistency: Gate 0: MAND gate needs two inputs per output, got 4 inputs and 1 \
+             outputs"
+        );
+    }
+}
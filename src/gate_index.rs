@@ -0,0 +1,330 @@
+use std::{
+    io::{BufRead, Read, Seek, SeekFrom},
+    ops::Range,
+    path::Path,
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    bristol_circuit_error::{with_path, BristolCircuitError},
+    bristol_line::BristolLine,
+    circuit_header::CircuitHeader,
+    gate::Gate,
+    header_style::HeaderStyle,
+    line_reader::{strip_comment, LineReader},
+    parse_options::{GateCountPolicy, ParseOptions},
+    semantic_hash::Fnv,
+};
+
+/// Byte offsets of every `interval`-th gate line of a Bristol file, for reading slices of the
+/// gates without parsing the ones before them.
+///
+/// Blank lines and `#` comment lines between gates are skipped. Each gate must be on its own line.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GateIndex {
+    pub interval: usize,
+    pub gate_count: usize,
+
+    /// The byte offset of gate `i * interval`, for each `i`.
+    pub checkpoints: Vec<u64>,
+
+    /// The indexed file's length and a hash of its first `PREFIX_BYTES` bytes, to detect that it
+    /// has changed.
+    pub content_length: u64,
+    pub prefix_hash: u64,
+}
+
+impl GateIndex {
+    pub const DEFAULT_INTERVAL: usize = 1024;
+    pub const PREFIX_BYTES: u64 = 64 * 1024;
+
+    pub fn build<R: BufRead + Seek>(r: &mut R) -> Result<GateIndex, BristolCircuitError> {
+        GateIndex::build_with_interval(r, GateIndex::DEFAULT_INTERVAL)
+    }
+
+    pub fn build_with_interval<R: BufRead + Seek>(
+        r: &mut R,
+        interval: usize,
+    ) -> Result<GateIndex, BristolCircuitError> {
+        if interval == 0 {
+            return Err(BristolCircuitError::Inconsistency {
+                message: "Gate index interval must be positive".into(),
+            });
+        }
+
+        let (content_length, prefix_hash) = fingerprint(r)?;
+
+        r.seek(SeekFrom::Start(0))?;
+        let header = CircuitHeader::read(
+            &mut LineReader::new(&mut *r, &ParseOptions::default()),
+            None,
+            None,
+        )?;
+
+        // The line reader may have read ahead, so find where the gates start by skipping the
+        // header's lines again.
+        r.seek(SeekFrom::Start(0))?;
+        let mut lines = OffsetLines { r, offset: 0 };
+        for _ in 0..header_line_count(&header) {
+            lines.next_content_line()?;
+        }
+
+        let mut checkpoints = Vec::new();
+        let mut gate_count = 0;
+
+        while let Some((offset, line)) = lines.next_content_line()? {
+            if line.trim() == GateCountPolicy::TERMINATOR {
+                break;
+            }
+
+            if gate_count % interval == 0 {
+                checkpoints.push(offset);
+            }
+
+            gate_count += 1;
+        }
+
+        Ok(GateIndex {
+            interval,
+            gate_count,
+            checkpoints,
+            content_length,
+            prefix_hash,
+        })
+    }
+
+    /// Reads the gates in `range`, seeking to the nearest checkpoint before it.
+    pub fn read_gates<R: BufRead + Seek>(
+        &self,
+        r: &mut R,
+        range: Range<usize>,
+    ) -> Result<Vec<Gate>, BristolCircuitError> {
+        if fingerprint(r)? != (self.content_length, self.prefix_hash) {
+            return Err(BristolCircuitError::Inconsistency {
+                message: "The indexed file has changed since the gate index was built".into(),
+            });
+        }
+
+        if range.end > self.gate_count {
+            return Err(BristolCircuitError::Inconsistency {
+                message: format!(
+                    "Gate range {}..{} is out of bounds ({} gates)",
+                    range.start, range.end, self.gate_count
+                ),
+            });
+        }
+
+        if range.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let checkpoint = range.start / self.interval;
+        r.seek(SeekFrom::Start(self.checkpoints[checkpoint]))?;
+
+        let mut lines = OffsetLines {
+            r,
+            offset: self.checkpoints[checkpoint],
+        };
+        let mut gates = Vec::with_capacity(range.len());
+
+        for i in checkpoint * self.interval..range.end {
+            let (_, line) =
+                lines
+                    .next_content_line()?
+                    .ok_or_else(|| BristolCircuitError::ParsingError {
+                        message: format!("Unexpected end of input while reading gate {}", i),
+                    })?;
+
+            if i >= range.start {
+                gates.push(
+                    BristolLine::new(line.split_whitespace().map(str::to_string).collect())
+                        .gate()?,
+                );
+            }
+        }
+
+        Ok(gates)
+    }
+
+    pub fn from_file(path: &Path) -> Result<GateIndex, BristolCircuitError> {
+        with_path(path, || {
+            GateIndex::from_json_str(&std::fs::read_to_string(path)?)
+        })
+    }
+
+    pub fn to_file(&self, path: &Path) -> Result<(), BristolCircuitError> {
+        with_path(path, || Ok(std::fs::write(path, self.to_json_string()?)?))
+    }
+
+    pub fn from_json_str(text: &str) -> Result<GateIndex, BristolCircuitError> {
+        serde_json::from_str(text).map_err(|e| BristolCircuitError::ParsingError {
+            message: format!("Invalid gate index: {}", e),
+        })
+    }
+
+    pub fn to_json_string(&self) -> Result<String, BristolCircuitError> {
+        serde_json::to_string(self).map_err(|e| BristolCircuitError::ParsingError {
+            message: format!("Invalid gate index: {}", e),
+        })
+    }
+}
+
+fn header_line_count(header: &CircuitHeader) -> usize {
+    1 + match header.header_style {
+        HeaderStyle::NivList => 2,
+        HeaderStyle::Legacy => 1,
+        HeaderStyle::PerPartyLines => 2 + header.input_widths.len() + header.output_widths.len(),
+    }
+}
+
+fn fingerprint<R: Read + Seek>(r: &mut R) -> Result<(u64, u64), BristolCircuitError> {
+    let content_length = r.seek(SeekFrom::End(0))?;
+    r.seek(SeekFrom::Start(0))?;
+
+    let mut prefix = Vec::new();
+    r.take(GateIndex::PREFIX_BYTES).read_to_end(&mut prefix)?;
+
+    Ok((content_length, Fnv::new().bytes(&prefix).finish()))
+}
+
+struct OffsetLines<'a, R> {
+    r: &'a mut R,
+    offset: u64,
+}
+
+impl<R: BufRead> OffsetLines<'_, R> {
+    /// The next line that isn't blank or only a comment, with its byte offset. Comments are
+    /// stripped.
+    fn next_content_line(&mut self) -> Result<Option<(u64, String)>, BristolCircuitError> {
+        let mut buf = Vec::new();
+
+        loop {
+            buf.clear();
+            let offset = self.offset;
+            let n = self.r.read_until(b'\n', &mut buf)?;
+
+            if n == 0 {
+                return Ok(None);
+            }
+
+            self.offset += n as u64;
+
+            let line =
+                std::str::from_utf8(&buf).map_err(|_| BristolCircuitError::ParsingError {
+                    message: "Line is not valid utf8".into(),
+                })?;
+            let trimmed = strip_comment(line).trim();
+
+            if !trimmed.is_empty() {
+                return Ok(Some((offset, trimmed.to_string())));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use super::*;
+    use crate::split_mix::SplitMix64;
+
+    fn generated_file(gate_count: usize) -> String {
+        let mut src = format!("{} {}\n2 1 1\n1 1\n\n", gate_count, gate_count + 2);
+
+        for i in 0..gate_count {
+            if i % 97 == 0 {
+                src.push_str("# checkpoint comment\n\n");
+            }
+
+            let op = if i % 3 == 0 { "AMul" } else { "AAdd" };
+            src.push_str(&format!("2 1 {} {} {} {}\n", i, i + 1, i + 2, op));
+        }
+
+        src
+    }
+
+    fn sequential_gates(src: &str) -> Vec<Gate> {
+        let mut r = Cursor::new(src.as_bytes());
+        let mut lines = LineReader::new(&mut r, &ParseOptions::default());
+        CircuitHeader::read(&mut lines, None, None).unwrap();
+
+        let mut gates = Vec::new();
+        while let Some(line) = lines.next_line().unwrap() {
+            gates.push(line.gate().unwrap());
+        }
+
+        gates
+    }
+
+    #[test]
+    fn test_random_slices_match_sequential_parse() {
+        let src = generated_file(5000);
+        let all = sequential_gates(&src);
+        let mut r = Cursor::new(src.as_bytes());
+
+        let index = GateIndex::build_with_interval(&mut r, 64).unwrap();
+        assert_eq!(index.gate_count, 5000);
+        assert_eq!(index.checkpoints.len(), 5000usize.div_ceil(64));
+
+        let mut rng = SplitMix64::new(3);
+        for _ in 0..50 {
+            let start = (rng.next_u64() % 5000) as usize;
+            let len = (rng.next_u64() % 200) as usize;
+            let range = start..(start + len).min(5000);
+
+            assert_eq!(
+                index.read_gates(&mut r, range.clone()).unwrap(),
+                all[range.clone()],
+                "{:?}",
+                range
+            );
+        }
+
+        assert_eq!(index.read_gates(&mut r, 4990..5000).unwrap(), all[4990..]);
+        assert!(index.read_gates(&mut r, 10..10).unwrap().is_empty());
+        assert!(index.read_gates(&mut r, 4990..5001).is_err());
+    }
+
+    #[test]
+    fn test_sidecar_round_trip() {
+        let src = generated_file(300);
+        let index = GateIndex::build(&mut Cursor::new(src.as_bytes())).unwrap();
+
+        let path = std::env::temp_dir().join(format!("gate_index_{}.json", std::process::id()));
+        index.to_file(&path).unwrap();
+        let loaded = GateIndex::from_file(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(loaded, index);
+        assert_eq!(index.checkpoints.len(), 1);
+    }
+
+    #[test]
+    fn test_detects_changed_file() {
+        let src = generated_file(300);
+        let index = GateIndex::build(&mut Cursor::new(src.as_bytes())).unwrap();
+
+        let edited = src.replacen("AMul", "AAdd", 1);
+        let err = index
+            .read_gates(&mut Cursor::new(edited.as_bytes()), 0..10)
+            .unwrap_err();
+        assert!(err.to_string().contains("has changed"));
+
+        let extended = format!("{}\n", src);
+        assert!(index
+            .read_gates(&mut Cursor::new(extended.as_bytes()), 0..10)
+            .is_err());
+    }
+
+    #[test]
+    fn test_header_styles() {
+        let src = "2 4\n2\n1\n1\n1\n1\n2 1 0 1 2 AAdd\n2 1 2 1 3 AMul\n";
+        let mut r = Cursor::new(src.as_bytes());
+        let index = GateIndex::build(&mut r).unwrap();
+
+        assert_eq!(index.gate_count, 2);
+        assert_eq!(index.read_gates(&mut r, 1..2).unwrap()[0].op, "AMul");
+    }
+}
@@ -0,0 +1,160 @@
+use std::{
+    convert::Infallible,
+    fmt::{self, Display, Formatter},
+    str::FromStr,
+};
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::{
+    a_gate_type::AGateType,
+    bool_gate_type::BoolGateType,
+    gate::{Gate, GateInputs, GateOutputs},
+};
+
+/// A gate's op, parsed. Ops that aren't arithmetic or boolean, including LUT ops, are kept
+/// verbatim as `Custom`, so every op round-trips through `FromStr` and `Display` unchanged.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum GateOp {
+    Arithmetic(AGateType),
+    Boolean(BoolGateType),
+    Custom(String),
+}
+
+impl FromStr for GateOp {
+    type Err = Infallible;
+
+    fn from_str(op: &str) -> Result<Self, Self::Err> {
+        Ok(if let Ok(op) = AGateType::from_str(op) {
+            GateOp::Arithmetic(op)
+        } else if let Ok(op) = BoolGateType::from_str(op) {
+            GateOp::Boolean(op)
+        } else {
+            GateOp::Custom(op.to_string())
+        })
+    }
+}
+
+impl Display for GateOp {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            GateOp::Arithmetic(op) => write!(f, "{}", op),
+            GateOp::Boolean(op) => write!(f, "{}", op),
+            GateOp::Custom(op) => write!(f, "{}", op),
+        }
+    }
+}
+
+impl From<&str> for GateOp {
+    fn from(op: &str) -> Self {
+        match GateOp::from_str(op) {
+            Ok(op) => op,
+            Err(never) => match never {},
+        }
+    }
+}
+
+/// Serialized as the op's text, like `Gate::op`.
+impl Serialize for GateOp {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for GateOp {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Ok(GateOp::from(String::deserialize(deserializer)?.as_str()))
+    }
+}
+
+/// A [`Gate`] with its op parsed.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TypedGate {
+    pub inputs: GateInputs,
+    pub outputs: GateOutputs,
+    pub op: GateOp,
+}
+
+impl Gate {
+    pub fn typed_op(&self) -> GateOp {
+        GateOp::from(self.op.as_str())
+    }
+}
+
+impl From<Gate> for TypedGate {
+    fn from(gate: Gate) -> Self {
+        TypedGate {
+            op: gate.typed_op(),
+            inputs: gate.inputs,
+            outputs: gate.outputs,
+        }
+    }
+}
+
+impl From<TypedGate> for Gate {
+    fn from(gate: TypedGate) -> Self {
+        Gate {
+            inputs: gate.inputs,
+            outputs: gate.outputs,
+            op: gate.op.to_string().into(),
+        }
+    }
+}
+
+impl Display for TypedGate {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "{}", Gate::from(self.clone()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use smallvec::smallvec;
+    use strum::IntoEnumIterator;
+
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(GateOp::from("AAdd"), GateOp::Arithmetic(AGateType::AAdd));
+        assert_eq!(GateOp::from("MAND"), GateOp::Boolean(BoolGateType::Mand));
+        assert_eq!(GateOp::from("AAdd "), GateOp::Custom("AAdd ".to_string()));
+        assert_eq!(GateOp::from("xor"), GateOp::Custom("xor".to_string()));
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let ops = AGateType::iter()
+            .map(|op| op.to_string())
+            .chain(BoolGateType::iter().map(|op| op.to_string()))
+            .chain(["LUT[e8]".to_string(), "FOO".to_string()]);
+
+        for op in ops {
+            assert_eq!(GateOp::from(op.as_str()).to_string(), op);
+        }
+    }
+
+    #[test]
+    fn test_typed_gate() {
+        let gate = Gate {
+            inputs: smallvec![0, 1],
+            outputs: smallvec![2],
+            op: "CUSTOM_OP".into(),
+        };
+
+        let typed = TypedGate::from(gate.clone());
+        assert_eq!(typed.op, GateOp::Custom("CUSTOM_OP".to_string()));
+        assert_eq!(typed.to_string(), "2 1 0 1 2 CUSTOM_OP");
+        assert_eq!(Gate::from(typed.clone()), gate);
+
+        let json = serde_json::to_string(&typed).unwrap();
+        assert_eq!(json, r#"{"inputs":[0,1],"outputs":[2],"op":"CUSTOM_OP"}"#);
+        assert_eq!(serde_json::from_str::<TypedGate>(&json).unwrap(), typed);
+
+        let and = TypedGate::from(Gate {
+            op: "AND".into(),
+            ..gate
+        });
+        assert_eq!(and.op, GateOp::Boolean(BoolGateType::And));
+    }
+}
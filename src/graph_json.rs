@@ -0,0 +1,377 @@
+use std::collections::{BTreeSet, HashMap, VecDeque};
+
+use serde::Serialize;
+
+use crate::{bristol_circuit::BristolCircuit, bristol_circuit_error::BristolCircuitError};
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct GraphJsonOptions {
+    /// Give each node a `level`: 0 for inputs and constants, the gate depth for gates, and one
+    /// more than the deepest gate for outputs.
+    pub levels: bool,
+
+    /// Only show the cone of influence of this output.
+    pub cone: Option<String>,
+
+    /// Stop adding gates once the graph has this many nodes, keeping the gates closest to the
+    /// outputs. `omitted_gates` in the document counts the rest.
+    pub max_nodes: Option<usize>,
+}
+
+#[derive(Serialize)]
+struct GraphDocument {
+    nodes: Vec<GraphNode>,
+    edges: Vec<GraphEdge>,
+    #[serde(skip_serializing_if = "is_zero")]
+    omitted_gates: usize,
+}
+
+#[derive(Serialize)]
+struct GraphNode {
+    id: String,
+    kind: NodeKind,
+    label: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    op: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    level: Option<usize>,
+}
+
+#[derive(Clone, Copy, Serialize)]
+#[serde(rename_all = "lowercase")]
+enum NodeKind {
+    Input,
+    Gate,
+    Output,
+    Const,
+}
+
+#[derive(Serialize)]
+struct GraphEdge {
+    from: String,
+    to: String,
+    wire: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    label: Option<String>,
+}
+
+fn is_zero(n: &usize) -> bool {
+    *n == 0
+}
+
+impl BristolCircuit {
+    /// A node/edge document for graph viewers:
+    ///
+    /// ```json
+    /// {
+    ///   "nodes": [{"id": "gate:0", "kind": "gate", "label": "AND", "op": "AND", "level": 1}],
+    ///   "edges": [{"from": "input:a", "to": "gate:0", "wire": 0, "label": "a[0]"}],
+    ///   "omitted_gates": 0
+    /// }
+    /// ```
+    ///
+    /// There is one node per named input, constant and output (`input:<name>`, `const:<name>`,
+    /// `output:<name>`) and one per gate (`gate:<index>`). Nodes are listed as inputs, constants,
+    /// gates, then outputs. Each edge is one wire, labelled when the wire is a named input, constant or output
+    /// bit. `level` and `omitted_gates` are only present when requested or non-zero.
+    pub fn to_graph_json(&self, opts: &GraphJsonOptions) -> Result<String, BristolCircuitError> {
+        let wire_labels = self.wire_labels();
+        let mut sources = HashMap::<usize, String>::new();
+
+        let input_buses = self.input_buses();
+        let output_buses = self.output_buses();
+
+        for bus in &input_buses {
+            for bit in 0..bus.width {
+                sources.insert(bus.wire + bit, format!("input:{}", bus.name));
+            }
+        }
+
+        let mut constants = self.info.constants.iter().collect::<Vec<_>>();
+        constants.sort_by_key(|(name, _)| name.as_str());
+
+        for (name, constant) in &constants {
+            sources.insert(constant.wire_index, format!("const:{}", name));
+        }
+
+        let producers = self
+            .gates
+            .iter()
+            .enumerate()
+            .flat_map(|(i, gate)| gate.outputs.iter().map(move |&wire| (wire, i)))
+            .collect::<HashMap<_, _>>();
+
+        for (&wire, &gate) in &producers {
+            sources.insert(wire, format!("gate:{}", gate));
+        }
+
+        let shown_outputs = match &opts.cone {
+            None => output_buses.iter().collect::<Vec<_>>(),
+            Some(name) => {
+                let bus = output_buses
+                    .iter()
+                    .find(|bus| bus.name == name.as_str())
+                    .ok_or_else(|| BristolCircuitError::Inconsistency {
+                        message: format!("No output named {}", name),
+                    })?;
+
+                vec![bus]
+            }
+        };
+
+        // Walk back from the outputs, so a node cap keeps the gates nearest them.
+        let mut gate_budget = opts.max_nodes.map(|max| {
+            max.saturating_sub(input_buses.len() + constants.len() + shown_outputs.len())
+        });
+        let mut gates = BTreeSet::new();
+        let mut omitted = BTreeSet::new();
+        let mut queue = shown_outputs
+            .iter()
+            .flat_map(|bus| bus.wire..bus.wire + bus.width)
+            .filter_map(|wire| producers.get(&wire).copied())
+            .collect::<VecDeque<_>>();
+
+        while let Some(gate) = queue.pop_front() {
+            if gates.contains(&gate) || omitted.contains(&gate) {
+                continue;
+            }
+
+            if gate_budget == Some(0) {
+                omitted.insert(gate);
+            } else {
+                gates.insert(gate);
+                gate_budget = gate_budget.map(|budget| budget - 1);
+            }
+
+            queue.extend(
+                self.gates[gate]
+                    .inputs
+                    .iter()
+                    .filter_map(|wire| producers.get(wire).copied()),
+            );
+        }
+
+        let depths = opts.levels.then(|| self.gate_depths());
+        let output_level = depths
+            .as_ref()
+            .map(|depths| gates.iter().map(|&g| depths[g]).max().unwrap_or(0) + 1);
+
+        let mut edges = Vec::new();
+        let mut used_sources = BTreeSet::new();
+
+        let mut edge = |wire: usize, to: String, edges: &mut Vec<GraphEdge>| {
+            if let Some(from) = sources.get(&wire) {
+                let shown = match from.strip_prefix("gate:") {
+                    Some(gate) => gates.contains(&gate.parse::<usize>().expect("gate id")),
+                    None => true,
+                };
+
+                if shown {
+                    used_sources.insert(from.clone());
+                    edges.push(GraphEdge {
+                        from: from.clone(),
+                        to,
+                        wire,
+                        label: wire_labels.get(&wire).cloned(),
+                    });
+                }
+            }
+        };
+
+        for &gate in &gates {
+            for &wire in &self.gates[gate].inputs {
+                edge(wire, format!("gate:{}", gate), &mut edges);
+            }
+        }
+
+        for bus in &shown_outputs {
+            for wire in bus.wire..bus.wire + bus.width {
+                edge(wire, format!("output:{}", bus.name), &mut edges);
+            }
+        }
+
+        let level = |level: usize| opts.levels.then_some(level);
+        let restricted = opts.cone.is_some();
+        let mut nodes = Vec::new();
+
+        for bus in &input_buses {
+            let id = format!("input:{}", bus.name);
+
+            if !restricted || used_sources.contains(&id) {
+                nodes.push(GraphNode {
+                    id,
+                    kind: NodeKind::Input,
+                    label: bus.name.to_string(),
+                    op: None,
+                    level: level(0),
+                });
+            }
+        }
+
+        for (name, constant) in &constants {
+            let id = format!("const:{}", name);
+
+            if !restricted || used_sources.contains(&id) {
+                nodes.push(GraphNode {
+                    id,
+                    kind: NodeKind::Const,
+                    label: format!("{} = {}", name, constant.value),
+                    op: None,
+                    level: level(0),
+                });
+            }
+        }
+
+        for &gate in &gates {
+            let op = &self.gates[gate].op;
+
+            nodes.push(GraphNode {
+                id: format!("gate:{}", gate),
+                kind: NodeKind::Gate,
+                label: op.to_string(),
+                op: Some(op.to_string()),
+                level: depths.as_ref().map(|depths| depths[gate]),
+            });
+        }
+
+        for bus in &shown_outputs {
+            nodes.push(GraphNode {
+                id: format!("output:{}", bus.name),
+                kind: NodeKind::Output,
+                label: bus.name.to_string(),
+                op: None,
+                level: output_level,
+            });
+        }
+
+        let document = GraphDocument {
+            nodes,
+            edges,
+            omitted_gates: omitted.len(),
+        };
+
+        serde_json::to_string(&document).map_err(|e| BristolCircuitError::Inconsistency {
+            message: format!("Failed to serialize graph: {}", e),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{json, Value};
+
+    use super::*;
+    use crate::{circuit_builder::CircuitBuilder, gadgets};
+
+    fn graph(circuit: &BristolCircuit, opts: &GraphJsonOptions) -> Value {
+        serde_json::from_str(&circuit.to_graph_json(opts).unwrap()).unwrap()
+    }
+
+    /// `out = (a & b) ^ one` and `copy = a`, where `copy` shares `a`'s wire.
+    fn small_circuit() -> BristolCircuit {
+        let mut b = CircuitBuilder::new();
+        let x = b.input("a");
+        let y = b.input("b");
+        let one = b.constant("one", "1");
+        let and = b.gate("AND", &[x, y]);
+        let out = b.gate("XOR", &[and, one]);
+        b.output("out", out);
+        b.output("copy", x);
+        b.build().unwrap()
+    }
+
+    #[test]
+    fn test_golden() {
+        let circuit = small_circuit();
+        let wire = |name: &str| circuit.info.input_name_to_wire_index[name];
+        let one = circuit.info.constants["one"].wire_index;
+        let and = circuit.gates[0].outputs[0];
+        let out = circuit.info.output_name_to_wire_index["out"];
+
+        let expected = json!({
+            "nodes": [
+                {"id": "input:a", "kind": "input", "label": "a", "level": 0},
+                {"id": "input:b", "kind": "input", "label": "b", "level": 0},
+                {"id": "const:one", "kind": "const", "label": "one = 1", "level": 0},
+                {"id": "gate:0", "kind": "gate", "label": "AND", "op": "AND", "level": 1},
+                {"id": "gate:1", "kind": "gate", "label": "XOR", "op": "XOR", "level": 2},
+                {"id": "output:copy", "kind": "output", "label": "copy", "level": 3},
+                {"id": "output:out", "kind": "output", "label": "out", "level": 3},
+            ],
+            "edges": [
+                {"from": "input:a", "to": "gate:0", "wire": wire("a"), "label": "a"},
+                {"from": "input:b", "to": "gate:0", "wire": wire("b"), "label": "b"},
+                {"from": "gate:0", "to": "gate:1", "wire": and},
+                {"from": "const:one", "to": "gate:1", "wire": one, "label": "one"},
+                {"from": "input:a", "to": "output:copy", "wire": wire("a"), "label": "a"},
+                {"from": "gate:1", "to": "output:out", "wire": out, "label": "out"},
+            ],
+        });
+
+        assert_eq!(
+            graph(
+                &circuit,
+                &GraphJsonOptions {
+                    levels: true,
+                    ..Default::default()
+                }
+            ),
+            expected
+        );
+    }
+
+    #[test]
+    fn test_cone() {
+        let circuit = small_circuit();
+        let document = graph(
+            &circuit,
+            &GraphJsonOptions {
+                cone: Some("copy".into()),
+                ..Default::default()
+            },
+        );
+
+        let ids = document["nodes"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|node| node["id"].as_str().unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(ids, vec!["input:a", "output:copy"]);
+        assert!(document["nodes"][0].get("level").is_none());
+
+        let err = circuit
+            .to_graph_json(&GraphJsonOptions {
+                cone: Some("missing".into()),
+                ..Default::default()
+            })
+            .unwrap_err();
+        assert_eq!(err.to_string(), "Inconsistency: No output named missing");
+    }
+
+    #[test]
+    fn test_node_cap() {
+        let circuit = gadgets::adder(32, false);
+        let document = graph(
+            &circuit,
+            &GraphJsonOptions {
+                max_nodes: Some(20),
+                ..Default::default()
+            },
+        );
+
+        let nodes = document["nodes"].as_array().unwrap();
+        assert_eq!(nodes.len(), 20);
+        assert_eq!(
+            document["omitted_gates"].as_u64().unwrap() as usize,
+            circuit.gates.len() - 17
+        );
+
+        // Every edge connects shown nodes.
+        for edge in document["edges"].as_array().unwrap() {
+            for end in ["from", "to"] {
+                assert!(nodes.iter().any(|node| node["id"] == edge[end]));
+            }
+        }
+    }
+}
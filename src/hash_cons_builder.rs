@@ -0,0 +1,188 @@
+use std::collections::HashMap;
+
+use crate::{
+    bristol_circuit::BristolCircuit,
+    bristol_circuit_error::BristolCircuitError,
+    circuit_builder::{CircuitBuilder, WireId},
+    op_properties::normalized_gate_key,
+};
+
+/// A `CircuitBuilder` that reuses existing gates instead of emitting duplicates.
+///
+/// Gates are keyed by op and inputs (sorted for commutative ops), so building an expression with
+/// repeated subterms produces each distinct subterm once.
+#[derive(Debug, Default)]
+pub struct HashConsBuilder {
+    builder: CircuitBuilder,
+    table: HashMap<(String, Vec<WireId>), WireId>,
+    gates_requested: usize,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct HashConsStats {
+    pub gates_requested: usize,
+    pub gates_emitted: usize,
+}
+
+impl HashConsBuilder {
+    pub fn new() -> Self {
+        HashConsBuilder::default()
+    }
+
+    pub fn input(&mut self, name: &str) -> WireId {
+        self.builder.input(name)
+    }
+
+    pub fn constant(&mut self, name: &str, value: &str) -> WireId {
+        self.builder.constant(name, value)
+    }
+
+    pub fn gate(&mut self, op: &str, inputs: &[WireId]) -> WireId {
+        self.gates_requested += 1;
+
+        let key = normalized_gate_key(op, inputs);
+
+        if let Some(&wire) = self.table.get(&key) {
+            return wire;
+        }
+
+        let wire = self.builder.gate(op, inputs);
+        self.table.insert(key, wire);
+        wire
+    }
+
+    pub fn input_bus(&mut self, name: &str, width: usize) -> Vec<WireId> {
+        self.builder.input_bus(name, width)
+    }
+
+    pub fn output(&mut self, name: &str, wire: WireId) {
+        self.builder.output(name, wire);
+    }
+
+    /// Adds a multi-bit output. Copy gates it inserts are never deduplicated.
+    pub fn output_bus(&mut self, name: &str, wires: &[WireId]) {
+        self.builder.output_bus(name, wires);
+    }
+
+    pub fn stats(&self) -> HashConsStats {
+        HashConsStats {
+            gates_requested: self.gates_requested,
+            gates_emitted: self.table.len(),
+        }
+    }
+
+    /// The underlying builder, for operations that shouldn't be deduplicated.
+    pub fn builder_mut(&mut self) -> &mut CircuitBuilder {
+        &mut self.builder
+    }
+
+    pub fn into_builder(self) -> CircuitBuilder {
+        self.builder
+    }
+
+    pub fn build(self) -> Result<BristolCircuit, BristolCircuitError> {
+        self.builder.build()
+    }
+}
+
+impl From<CircuitBuilder> for HashConsBuilder {
+    fn from(builder: CircuitBuilder) -> Self {
+        HashConsBuilder {
+            builder,
+            ..Default::default()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use smallvec::smallvec;
+
+    use super::*;
+    use crate::gate::Gate;
+
+    #[test]
+    fn test_hash_cons_repeated_subterms() {
+        let mut b = HashConsBuilder::new();
+        let x = b.input("x");
+        let y = b.input("y");
+
+        // ((x + y) * (y + x)) + ((x + y) * (x + y))
+        let left_sum = b.gate("AAdd", &[x, y]);
+        let right_sum = b.gate("AAdd", &[y, x]);
+        let left = b.gate("AMul", &[left_sum, right_sum]);
+        let sum = b.gate("AAdd", &[x, y]);
+        let right = b.gate("AMul", &[sum, sum]);
+        let result = b.gate("AAdd", &[left, right]);
+        b.output("result", result);
+
+        assert_eq!(
+            b.stats(),
+            HashConsStats {
+                gates_requested: 6,
+                gates_emitted: 3,
+            }
+        );
+
+        let circuit = b.build().unwrap();
+        assert_eq!(circuit.wire_count, 5);
+        assert_eq!(
+            circuit.gates,
+            vec![
+                Gate {
+                    inputs: smallvec![0, 1],
+                    outputs: smallvec![2],
+                    op: "AAdd".into(),
+                },
+                Gate {
+                    inputs: smallvec![2, 2],
+                    outputs: smallvec![3],
+                    op: "AMul".into(),
+                },
+                Gate {
+                    inputs: smallvec![3, 3],
+                    outputs: smallvec![4],
+                    op: "AAdd".into(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_hash_cons_respects_non_commutative_ops() {
+        let mut b = HashConsBuilder::new();
+        let x = b.input("x");
+        let y = b.input("y");
+
+        let d1 = b.gate("ASub", &[x, y]);
+        let d2 = b.gate("ASub", &[y, x]);
+        let d3 = b.gate("ASub", &[x, y]);
+
+        assert_ne!(d1, d2);
+        assert_eq!(d1, d3);
+        assert_eq!(b.stats().gates_emitted, 2);
+    }
+
+    #[test]
+    fn test_hash_cons_interoperates_with_builder() {
+        let mut plain = CircuitBuilder::new();
+        let x = plain.input("x");
+        let first = plain.gate("AMul", &[x, x]);
+
+        let mut b = HashConsBuilder::from(plain);
+        let square = b.gate("AMul", &[x, x]);
+        assert_ne!(
+            square, first,
+            "gates emitted before wrapping are not indexed"
+        );
+
+        let again = b.gate("AMul", &[x, x]);
+        assert_eq!(again, square);
+
+        let duplicate = b.builder_mut().gate("AMul", &[x, x]);
+        assert_ne!(duplicate, square);
+
+        b.output("square", again);
+        assert_eq!(b.into_builder().gate_count(), 3);
+    }
+}
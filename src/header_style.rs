@@ -0,0 +1,232 @@
+use std::io::{BufRead, Write};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    bristol_circuit_error::BristolCircuitError,
+    bristol_line::BristolLine,
+    line_reader::{LineReader, Peeked},
+    parse_options::ParseLimits,
+};
+
+/// The shape of the input/output declaration lines that follow the circuit sizes line.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum HeaderStyle {
+    /// `niv w_1 ... w_niv` followed by `nov w_1 ... w_nov` (Bristol Fashion).
+    #[default]
+    NivList,
+
+    /// A single `in_1 in_2 out` line giving two input widths and one output width.
+    Legacy,
+
+    /// `niv` on its own line followed by one line per input width, then the same for outputs.
+    PerPartyLines,
+}
+
+impl HeaderStyle {
+    fn describe(&self, first: &BristolLine) -> String {
+        match self {
+            HeaderStyle::NivList => format!(
+                "NivList (input widths {:?}, followed by an output widths line)",
+                first
+                    .get_range::<usize, Vec<_>>(1, first.len() - 1)
+                    .unwrap_or_default()
+            ),
+            HeaderStyle::Legacy => format!(
+                "Legacy (input widths {:?}, output widths {:?})",
+                first.get_range::<usize, Vec<_>>(0, 2).unwrap_or_default(),
+                first.get_range::<usize, Vec<_>>(2, 1).unwrap_or_default()
+            ),
+            HeaderStyle::PerPartyLines => format!(
+                "PerPartyLines ({} input width lines)",
+                first.get::<usize>(0).unwrap_or_default()
+            ),
+        }
+    }
+}
+
+/// Reads the io width declarations, auto-detecting the header style when `style` is `None`.
+///
+/// `counts` gives the expected number of inputs and outputs (if known) and is used to rule out
+/// interpretations during auto-detection.
+pub(crate) fn read_io_widths<R: BufRead>(
+    lines: &mut LineReader<R>,
+    style: Option<HeaderStyle>,
+    counts: Option<(usize, usize)>,
+) -> Result<(HeaderStyle, Vec<usize>, Vec<usize>), BristolCircuitError> {
+    let first = lines.expect_line("input widths")?;
+
+    let style = match style {
+        Some(style) => style,
+        None => detect(&first, lines, counts)?,
+    };
+
+    let (input_widths, output_widths) = match style {
+        HeaderStyle::NivList => (
+            first.io_widths()?,
+            lines.expect_line("output widths")?.io_widths()?,
+        ),
+        HeaderStyle::Legacy => {
+            first.expect_len(3, "legacy io widths")?;
+            (first.get_range(0, 2)?, first.get_range(2, 1)?)
+        }
+        HeaderStyle::PerPartyLines => {
+            let input_widths = read_width_lines(lines, &first, "input")?;
+            let output_count_line = lines.expect_line("output count")?;
+            let output_widths = read_width_lines(lines, &output_count_line, "output")?;
+
+            (input_widths, output_widths)
+        }
+    };
+
+    Ok((style, input_widths, output_widths))
+}
+
+pub(crate) fn write_io_widths<W: Write>(
+    w: &mut W,
+    style: HeaderStyle,
+    input_widths: &[usize],
+    output_widths: &[usize],
+) -> Result<(), BristolCircuitError> {
+    match style {
+        HeaderStyle::NivList => {
+            for widths in [input_widths, output_widths] {
+                write!(w, "{}", widths.len())?;
+                for width in widths {
+                    write!(w, " {}", width)?;
+                }
+                writeln!(w)?;
+            }
+        }
+        HeaderStyle::Legacy => {
+            if input_widths.len() != 2 || output_widths.len() != 1 {
+                return Err(BristolCircuitError::Inconsistency {
+                    message: format!(
+                        "Legacy header requires 2 inputs and 1 output (found {} and {})",
+                        input_widths.len(),
+                        output_widths.len()
+                    ),
+                });
+            }
+
+            writeln!(
+                w,
+                "{} {} {}",
+                input_widths[0], input_widths[1], output_widths[0]
+            )?;
+        }
+        HeaderStyle::PerPartyLines => {
+            for widths in [input_widths, output_widths] {
+                writeln!(w, "{}", widths.len())?;
+                for width in widths {
+                    writeln!(w, "{}", width)?;
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn read_width_lines<R: BufRead>(
+    lines: &mut LineReader<R>,
+    count_line: &BristolLine,
+    kind: &str,
+) -> Result<Vec<usize>, BristolCircuitError> {
+    count_line.expect_len(1, &format!("{} count", kind))?;
+    let count = count_line.get::<usize>(0)?;
+
+    // Each width is a line of its own, so check the count before reading them.
+    ParseLimits::check("max_io", lines.limits().max_io, count, || {
+        format!("Header declares {} {}s", count, kind)
+    })?;
+
+    let mut widths = Vec::new();
+
+    for _ in 0..count {
+        let line = lines.expect_line(&format!("{} width", kind))?;
+        line.expect_len(1, &format!("{} width", kind))?;
+        widths.push(line.get(0)?);
+    }
+
+    Ok(widths)
+}
+
+fn detect<R: BufRead>(
+    first: &BristolLine,
+    lines: &mut LineReader<R>,
+    counts: Option<(usize, usize)>,
+) -> Result<HeaderStyle, BristolCircuitError> {
+    let count = first.get::<usize>(0)?;
+    let mut candidates = Vec::new();
+
+    if first.len() - 1 == count {
+        candidates.push(HeaderStyle::NivList);
+    }
+
+    if first.len() == 3 {
+        candidates.push(HeaderStyle::Legacy);
+    }
+
+    if first.len() == 1 && count > 0 {
+        candidates.push(HeaderStyle::PerPartyLines);
+    }
+
+    if let Some((input_count, output_count)) = counts {
+        candidates.retain(|style| match style {
+            HeaderStyle::NivList | HeaderStyle::PerPartyLines => count == input_count,
+            HeaderStyle::Legacy => input_count == 2 && output_count == 1,
+        });
+    }
+
+    if candidates.len() > 1 {
+        // Only NivList and Legacy can overlap (`2 a b`). The next line tells them apart: it
+        // declares the outputs for NivList but is already the first gate for Legacy.
+        let next_is_legacy = match lines.peek()? {
+            Peeked::Line(line) if is_io_widths_shaped(line) => Some(false),
+            Peeked::Line(line) if is_gate_shaped(line) => Some(true),
+            Peeked::Line(_) => None,
+            Peeked::LongLine | Peeked::End => Some(true),
+        };
+
+        match next_is_legacy {
+            Some(true) => candidates.retain(|style| *style == HeaderStyle::Legacy),
+            Some(false) => candidates.retain(|style| *style != HeaderStyle::Legacy),
+            None => {}
+        }
+    }
+
+    match candidates.as_slice() {
+        [style] => Ok(*style),
+        [] => Err(BristolCircuitError::ParsingError {
+            message: format!("Unrecognized io widths {}", first.describe()),
+        }),
+        _ => Err(BristolCircuitError::ParsingError {
+            message: format!(
+                "Ambiguous io widths {}, could be: {}",
+                first.describe(),
+                candidates
+                    .iter()
+                    .map(|style| style.describe(first))
+                    .collect::<Vec<_>>()
+                    .join("; ")
+            ),
+        }),
+    }
+}
+
+fn is_io_widths_shaped(line: &BristolLine) -> bool {
+    line.get::<usize>(0)
+        .is_ok_and(|count| line.len() - 1 == count)
+        && line.iter_from::<usize>(1).all(|width| width.is_ok())
+}
+
+fn is_gate_shaped(line: &BristolLine) -> bool {
+    match (line.get::<usize>(0), line.get::<usize>(1)) {
+        (Ok(input_len), Ok(output_len)) => input_len
+            .checked_add(output_len)
+            .and_then(|len| len.checked_add(3))
+            .is_some_and(|len| len == line.len()),
+        _ => false,
+    }
+}
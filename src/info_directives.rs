@@ -0,0 +1,376 @@
+use std::{
+    collections::HashMap,
+    io::{BufRead, Read, Write},
+};
+
+use serde::{de::DeserializeOwned, Serialize};
+
+use crate::{
+    bristol_circuit::BristolCircuit,
+    bristol_circuit_error::BristolCircuitError,
+    circuit_info::{CircuitInfo, ConstantInfo},
+    parse_options::ParseOptions,
+    party::PartyId,
+    write_options::WriteOptions,
+};
+
+impl BristolCircuit {
+    /// Writes Bristol text preceded by comment directives holding the info, so the one file
+    /// describes the whole circuit and [`BristolCircuit::read_bristol_with_info`] can read it
+    /// back. Plain Bristol readers skip the directives as comments:
+    ///
+    /// ```text
+    /// # @info
+    /// # @input a 0
+    /// # @const two 2 4
+    /// # @output result 7
+    /// ```
+    ///
+    /// Wire groups, input owners and output visibility have `@group`, `@owner` and `@visibility`
+    /// directives. Names and constant values can't contain whitespace.
+    pub fn write_bristol_with_info<W: Write>(&self, w: &mut W) -> Result<(), BristolCircuitError> {
+        let info = &self.info;
+        let mut lines = vec!["# @info".to_string()];
+
+        for (name, wire) in info.inputs() {
+            lines.push(format!("# @input {} {}", token("input", name)?, wire));
+        }
+
+        for (name, constant) in by_name(&info.constants) {
+            lines.push(format!(
+                "# @const {} {} {}",
+                token("constant", name)?,
+                token("constant value", &constant.value)?,
+                constant.wire_index
+            ));
+        }
+
+        for (name, wire) in info.outputs() {
+            lines.push(format!("# @output {} {}", token("output", name)?, wire));
+        }
+
+        for (name, group) in by_name(&info.wire_groups) {
+            lines.push(format!(
+                "# @group {} {}",
+                token("wire group", name)?,
+                to_json(group)?
+            ));
+        }
+
+        for (name, party) in by_name(&info.input_owners) {
+            lines.push(format!("# @owner {} {}", token("input", name)?, party.0));
+        }
+
+        for (name, visibility) in by_name(&info.output_visibility) {
+            lines.push(format!(
+                "# @visibility {} {}",
+                token("output", name)?,
+                to_json(visibility)?
+            ));
+        }
+
+        for line in lines {
+            writeln!(w, "{}", line)?;
+        }
+
+        self.write_bristol_with_options(w, &WriteOptions::default())
+    }
+
+    /// Reads what [`BristolCircuit::write_bristol_with_info`] writes, taking the info from the
+    /// directives before the header. Without any, the info has to come from elsewhere, through
+    /// [`BristolCircuit::read_info_and_bristol`].
+    pub fn read_bristol_with_info<R: BufRead>(
+        r: &mut R,
+    ) -> Result<BristolCircuit, BristolCircuitError> {
+        BristolCircuit::read_bristol_with_info_and_options(r, &ParseOptions::default())
+    }
+
+    pub fn read_bristol_with_info_and_options<R: BufRead>(
+        r: &mut R,
+        options: &ParseOptions,
+    ) -> Result<BristolCircuit, BristolCircuitError> {
+        let mut info = CircuitInfo::default();
+        let mut found_directive = false;
+
+        // The lines before the header, which are given back to the parser afterwards so line
+        // numbers still count them.
+        let mut leading = Vec::new();
+        let mut line = String::new();
+        let mut line_number = 0;
+
+        loop {
+            line.clear();
+            line_number += 1;
+
+            if r.read_line(&mut line)? == 0 {
+                break;
+            }
+
+            leading.extend_from_slice(line.as_bytes());
+
+            let trimmed = line.trim();
+
+            if let Some(directive) = trimmed.strip_prefix("# @") {
+                found_directive = true;
+                read_directive(&mut info, directive).map_err(|message| {
+                    BristolCircuitError::ParsingError {
+                        message: format!("{} in line {}", message, line_number),
+                    }
+                })?;
+            } else if !(trimmed.is_empty() || trimmed.starts_with('#') || trimmed.starts_with("//"))
+            {
+                break;
+            }
+        }
+
+        if !found_directive {
+            return Err(BristolCircuitError::ParsingError {
+                message: "No info directives (like `# @input a 0`) before the header, so the \
+                          circuit info must be given separately"
+                    .to_string(),
+            });
+        }
+
+        BristolCircuit::read_info_and_bristol_with_options(
+            &info,
+            &mut leading.as_slice().chain(r),
+            options,
+        )
+    }
+}
+
+/// Adds what `directive`, the text after `# @`, says to `info`.
+fn read_directive(info: &mut CircuitInfo, directive: &str) -> Result<(), String> {
+    let (kind, rest) = directive
+        .split_once(char::is_whitespace)
+        .unwrap_or((directive, ""));
+    let args = rest.split_whitespace().collect::<Vec<_>>();
+
+    let expect_args = |count: usize, usage: &str| {
+        if args.len() == count {
+            Ok(())
+        } else {
+            Err(format!("Expected `# @{} {}`", kind, usage))
+        }
+    };
+
+    let wire = |arg: &str| {
+        arg.parse::<usize>()
+            .map_err(|_| format!("Invalid wire index {:?}", arg))
+    };
+
+    // The name, and the JSON after it, which may contain spaces.
+    let json_arg = || {
+        let rest = rest.trim_start();
+        let (name, json) = rest.split_once(char::is_whitespace).unwrap_or((rest, ""));
+
+        match name.is_empty() || json.trim().is_empty() {
+            true => Err(format!("Expected `# @{} NAME JSON`", kind)),
+            false => Ok((name, json.trim())),
+        }
+    };
+
+    match kind {
+        "info" => expect_args(0, ""),
+        "input" => {
+            expect_args(2, "NAME WIRE")?;
+            insert(
+                &mut info.input_name_to_wire_index,
+                "Input",
+                args[0],
+                wire(args[1])?,
+            )
+        }
+        "output" => {
+            expect_args(2, "NAME WIRE")?;
+            insert(
+                &mut info.output_name_to_wire_index,
+                "Output",
+                args[0],
+                wire(args[1])?,
+            )
+        }
+        "const" => {
+            expect_args(3, "NAME VALUE WIRE")?;
+            let constant = ConstantInfo {
+                value: args[1].to_string(),
+                wire_index: wire(args[2])?,
+            };
+            insert(&mut info.constants, "Constant", args[0], constant)
+        }
+        "group" => {
+            let (name, json) = json_arg()?;
+            insert(&mut info.wire_groups, "Wire group", name, from_json(json)?)
+        }
+        "owner" => {
+            expect_args(2, "INPUT PARTY")?;
+            let party = args[1]
+                .parse()
+                .map_err(|_| format!("Invalid party {:?}", args[1]))?;
+            insert(&mut info.input_owners, "Owner of", args[0], PartyId(party))
+        }
+        "visibility" => {
+            let (name, json) = json_arg()?;
+            insert(
+                &mut info.output_visibility,
+                "Visibility of",
+                name,
+                from_json(json)?,
+            )
+        }
+        _ => Err(format!("Unknown directive @{}", kind)),
+    }
+}
+
+fn insert<T>(map: &mut HashMap<String, T>, what: &str, name: &str, value: T) -> Result<(), String> {
+    match map.insert(name.to_string(), value) {
+        None => Ok(()),
+        Some(_) => Err(format!("{} {} is declared twice", what, name)),
+    }
+}
+
+fn by_name<T>(map: &HashMap<String, T>) -> Vec<(&str, &T)> {
+    let mut entries = map
+        .iter()
+        .map(|(name, value)| (name.as_str(), value))
+        .collect::<Vec<_>>();
+    entries.sort_by_key(|&(name, _)| name);
+    entries
+}
+
+/// `text`, if it can be written as one token of a directive.
+fn token<'a>(what: &str, text: &'a str) -> Result<&'a str, BristolCircuitError> {
+    if text.is_empty() || text.contains(char::is_whitespace) {
+        return Err(BristolCircuitError::Inconsistency {
+            message: format!("Can't write {} {:?} in an info directive", what, text),
+        });
+    }
+
+    Ok(text)
+}
+
+fn to_json(value: &impl Serialize) -> Result<String, BristolCircuitError> {
+    serde_json::to_string(value).map_err(|e| BristolCircuitError::InfoFormatError {
+        format: "JSON",
+        message: e.to_string(),
+    })
+}
+
+fn from_json<T: DeserializeOwned>(json: &str) -> Result<T, String> {
+    serde_json::from_str(json).map_err(|e| format!("Invalid JSON ({})", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        circuit_builder::CircuitBuilder,
+        gadgets,
+        io_kind::IoKind,
+        visibility::Visibility,
+        wire_group::{WireGroup, WireSpan},
+    };
+
+    fn round_trip(circuit: &BristolCircuit) -> String {
+        let mut text = Vec::new();
+        circuit.write_bristol_with_info(&mut text).unwrap();
+
+        let read = BristolCircuit::read_bristol_with_info(&mut text.as_slice()).unwrap();
+        assert_eq!(&read, circuit);
+
+        String::from_utf8(text).unwrap()
+    }
+
+    #[test]
+    fn test_round_trips() {
+        let mut b = CircuitBuilder::new();
+        let x = b.input("x");
+        let two = b.constant("two", "2");
+        let product = b.gate("AMul", &[x, two]);
+        b.output("result", product);
+        let circuit = b.build().unwrap();
+
+        let text = round_trip(&circuit);
+        assert!(
+            text.starts_with("# @info\n# @input x 0\n# @const two 2 1\n# @output result 2\n1 3\n")
+        );
+
+        // Plain readers skip the directives.
+        assert_eq!(
+            BristolCircuit::from_info_and_bristol_string(&circuit.info, &text).unwrap(),
+            circuit
+        );
+
+        let mut annotated = gadgets::adder(4, true);
+        let info = &mut annotated.info;
+        info.input_owners.insert("a".to_string(), PartyId(0));
+        info.input_owners.insert("b".to_string(), PartyId(1));
+        info.output_visibility
+            .insert("sum".to_string(), Visibility::Party(PartyId(1)));
+        info.output_visibility
+            .insert("carry_out".to_string(), Visibility::Hidden);
+        info.wire_groups.insert(
+            "pair".to_string(),
+            WireGroup {
+                direction: IoKind::Output,
+                span: WireSpan::Wires { wires: vec![3, 1] },
+            },
+        );
+
+        let text = round_trip(&annotated);
+        assert!(text.contains("# @owner b 1\n"));
+        assert!(text.contains("# @group pair {\"direction\":\"Output\",\"wires\":[3,1]}\n"));
+
+        // Comments may be mixed in.
+        let text = format!("// comment\n\n{}", round_trip(&circuit));
+        assert_eq!(
+            BristolCircuit::read_bristol_with_info(&mut text.as_bytes()).unwrap(),
+            circuit
+        );
+    }
+
+    #[test]
+    fn test_errors() {
+        let plain = gadgets::adder(2, false).get_bristol_string().unwrap();
+        let err = BristolCircuit::read_bristol_with_info(&mut plain.as_bytes()).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Parsing error: No info directives (like `# @input a 0`) before the header, so the \
+             circuit info must be given separately"
+        );
+
+        let cases = [
+            (
+                "# @info\n# @input a\n",
+                "Expected `# @input NAME WIRE` in line 2",
+            ),
+            ("# @input a x\n", "Invalid wire index \"x\" in line 1"),
+            (
+                "# @input a 0\n# @input a 1\n",
+                "Input a is declared twice in line 2",
+            ),
+            ("# @frob\n", "Unknown directive @frob in line 1"),
+            ("# @group g\n", "Expected `# @group NAME JSON` in line 1"),
+        ];
+
+        for (text, message) in cases {
+            let err = BristolCircuit::read_bristol_with_info(&mut text.as_bytes()).unwrap_err();
+            assert_eq!(err.to_string(), format!("Parsing error: {}", message));
+        }
+
+        // Errors in the Bristol text count the directive lines.
+        let err = BristolCircuit::read_bristol_with_info(
+            &mut "# @input x 0\n# @output y 2\n1 3\n1 2\n1 1\n\n2 1 0 1 2 XOR x\n".as_bytes(),
+        )
+        .unwrap_err();
+        assert!(err.to_string().ends_with("in line 7"), "{}", err);
+
+        let mut circuit = gadgets::adder(1, false);
+        let wire = circuit.info.input_name_to_wire_index.remove("a").unwrap();
+        circuit
+            .info
+            .input_name_to_wire_index
+            .insert("a b".to_string(), wire);
+        assert!(circuit.write_bristol_with_info(&mut Vec::new()).is_err());
+    }
+}
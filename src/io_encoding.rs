@@ -0,0 +1,245 @@
+use std::collections::HashMap;
+
+use crate::{
+    bristol_circuit::{BristolCircuit, IoBus},
+    bristol_circuit_error::BristolCircuitError,
+    eval::evaluation_error,
+};
+
+/// Which end of a named input or output its first wire holds.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum BitOrder {
+    /// The first wire is the least significant bit, as in this crate's gadgets.
+    #[default]
+    LsbFirst,
+
+    MsbFirst,
+}
+
+impl BitOrder {
+    /// The bit of a `width`-bit value that the `i`th wire holds.
+    fn bit(self, i: usize, width: usize) -> usize {
+        match self {
+            BitOrder::LsbFirst => i,
+            BitOrder::MsbFirst => width - 1 - i,
+        }
+    }
+}
+
+impl BristolCircuit {
+    pub fn encode_inputs(
+        &self,
+        values: &HashMap<String, u128>,
+    ) -> Result<Vec<bool>, BristolCircuitError> {
+        self.encode_inputs_with_order(values, BitOrder::LsbFirst)
+    }
+
+    /// The wire values setting each named input to its value in `values`, with widths from
+    /// `io_widths`. Wires that aren't inputs are false. Every named input must be given, and
+    /// must fit in its width.
+    pub fn encode_inputs_with_order(
+        &self,
+        values: &HashMap<String, u128>,
+        order: BitOrder,
+    ) -> Result<Vec<bool>, BristolCircuitError> {
+        let buses = self.input_buses();
+
+        if let Some(name) = values
+            .keys()
+            .find(|name| !buses.iter().any(|bus| bus.name == name.as_str()))
+        {
+            return Err(evaluation_error(format!("Unknown input {}", name)));
+        }
+
+        let mut wires = vec![false; self.wire_count.max(self.referenced_wire_count())];
+
+        for bus in &buses {
+            let value = *values
+                .get(bus.name)
+                .ok_or_else(|| evaluation_error(format!("Missing value for input {}", bus.name)))?;
+
+            if bus.width < 128 && value >> bus.width != 0 {
+                return Err(evaluation_error(format!(
+                    "Input {} is {}, which doesn't fit in {} bits",
+                    bus.name, value, bus.width
+                )));
+            }
+
+            for i in 0..bus.width {
+                let bit = order.bit(i, bus.width);
+                wires[bus.wire + i] = bit < 128 && value >> bit & 1 == 1;
+            }
+        }
+
+        Ok(wires)
+    }
+
+    pub fn decode_outputs(&self, wires: &[bool]) -> HashMap<String, u128> {
+        self.decode_outputs_with_order(wires, BitOrder::LsbFirst)
+    }
+
+    /// The value of each named output in `wires`, such as from evaluating the gates on
+    /// [`BristolCircuit::encode_inputs`]. Wires past the end of `wires` are read as false, and
+    /// bits above the 128th are dropped.
+    pub fn decode_outputs_with_order(
+        &self,
+        wires: &[bool],
+        order: BitOrder,
+    ) -> HashMap<String, u128> {
+        self.output_buses()
+            .into_iter()
+            .map(|bus| {
+                let bits = (bus.wire..bus.wire + bus.width)
+                    .map(|wire| wires.get(wire).copied().unwrap_or(false));
+
+                (bus.name.to_string(), bits_value(&bus, bits, order))
+            })
+            .collect()
+    }
+
+    /// Runs [`BristolCircuit::eval_boolean`] on integer-valued named inputs and outputs, encoded
+    /// as [`BristolCircuit::encode_inputs_with_order`] does.
+    pub fn eval_boolean_ints(
+        &self,
+        values: &HashMap<String, u128>,
+        order: BitOrder,
+    ) -> Result<HashMap<String, u128>, BristolCircuitError> {
+        let wires = self.encode_inputs_with_order(values, order)?;
+
+        let inputs = self
+            .input_buses()
+            .into_iter()
+            .map(|bus| {
+                let bits = wires[bus.wire..bus.wire + bus.width].to_vec();
+                (bus.name.to_string(), bits)
+            })
+            .collect();
+
+        let outputs = self.eval_boolean(&inputs)?;
+
+        Ok(self
+            .output_buses()
+            .into_iter()
+            .map(|bus| {
+                let bits = outputs[bus.name].iter().copied();
+                (bus.name.to_string(), bits_value(&bus, bits, order))
+            })
+            .collect())
+    }
+}
+
+/// The value `bits` hold, read from `bus`'s wires in order.
+fn bits_value(bus: &IoBus, bits: impl Iterator<Item = bool>, order: BitOrder) -> u128 {
+    bits.enumerate()
+        .map(|(i, set)| (order.bit(i, bus.width), set))
+        .filter(|&(bit, set)| set && bit < 128)
+        .fold(0, |value, (bit, _)| value | 1 << bit)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{circuit_builder::CircuitBuilder, gadgets};
+
+    fn values(pairs: &[(&str, u128)]) -> HashMap<String, u128> {
+        pairs
+            .iter()
+            .map(|&(name, value)| (name.to_string(), value))
+            .collect()
+    }
+
+    #[test]
+    fn test_adder_round_trip() {
+        let circuit = gadgets::adder(8, true);
+        let inputs = values(&[("a", 200), ("b", 100)]);
+
+        let wires = circuit.encode_inputs(&inputs).unwrap();
+        assert_eq!(wires.len(), circuit.wire_count);
+        assert_eq!(&wires[..8], gadgets::test_utils::to_bits(200, 8));
+
+        let outputs = circuit
+            .eval_boolean_ints(&inputs, BitOrder::LsbFirst)
+            .unwrap();
+        assert_eq!(outputs, values(&[("sum", 44), ("carry_out", 1)]));
+
+        // Decoding the evaluated wires gives the same.
+        let bits = HashMap::from([
+            ("a".to_string(), wires[..8].to_vec()),
+            ("b".to_string(), wires[8..16].to_vec()),
+        ]);
+        let all_wires = circuit
+            .eval_boolean_wires(&bits, &mut ())
+            .unwrap()
+            .into_iter()
+            .map(Option::unwrap_or_default)
+            .collect::<Vec<_>>();
+        assert_eq!(circuit.decode_outputs(&all_wires), outputs);
+    }
+
+    #[test]
+    fn test_msb_first() {
+        // Passes a 4-bit input straight through, and outputs its top bit.
+        let mut b = CircuitBuilder::new();
+        let x = b.input_bus("x", 4);
+        b.output_bus("y", &x);
+        b.output("top", x[0]);
+        let circuit = b.build().unwrap();
+
+        let inputs = values(&[("x", 0b0011)]);
+        let wires = circuit
+            .encode_inputs_with_order(&inputs, BitOrder::MsbFirst)
+            .unwrap();
+        assert_eq!(&wires[..4], [false, false, true, true]);
+
+        let bits = HashMap::from([("x".to_string(), wires[..4].to_vec())]);
+        let wires = circuit
+            .eval_boolean_wires(&bits, &mut ())
+            .unwrap()
+            .into_iter()
+            .map(Option::unwrap_or_default)
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            circuit.decode_outputs_with_order(&wires, BitOrder::MsbFirst),
+            values(&[("y", 0b0011), ("top", 0)])
+        );
+        assert_eq!(
+            circuit.decode_outputs(&wires),
+            values(&[("y", 0b1100), ("top", 0)])
+        );
+        assert_eq!(
+            circuit
+                .eval_boolean_ints(&values(&[("x", 0b1000)]), BitOrder::MsbFirst)
+                .unwrap(),
+            values(&[("y", 0b1000), ("top", 1)])
+        );
+    }
+
+    #[test]
+    fn test_errors() {
+        let circuit = gadgets::adder(8, false);
+
+        let err = circuit
+            .encode_inputs(&values(&[("a", 256), ("b", 0)]))
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Evaluation error: Input a is 256, which doesn't fit in 8 bits"
+        );
+
+        let err = circuit.encode_inputs(&values(&[("a", 1)])).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Evaluation error: Missing value for input b"
+        );
+
+        let err = circuit
+            .encode_inputs(&values(&[("a", 1), ("b", 2), ("c", 3)]))
+            .unwrap_err();
+        assert_eq!(err.to_string(), "Evaluation error: Unknown input c");
+
+        assert!(circuit
+            .encode_inputs(&values(&[("a", 255), ("b", 255)]))
+            .is_ok());
+    }
+}
@@ -0,0 +1,18 @@
+use std::fmt::{self, Display, Formatter};
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum IoKind {
+    Input,
+    Output,
+}
+
+impl Display for IoKind {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            IoKind::Input => write!(f, "input"),
+            IoKind::Output => write!(f, "output"),
+        }
+    }
+}
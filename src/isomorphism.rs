@@ -0,0 +1,192 @@
+use std::collections::HashMap;
+
+use crate::{
+    bristol_circuit::BristolCircuit, bristol_circuit_error::BristolCircuitError,
+    gate::takes_literals,
+};
+
+impl BristolCircuit {
+    /// Looks for a renumbering of this circuit's wires that turns it into `other`, returning the
+    /// mapping from this circuit's wires to `other`'s, or `None` if there isn't one.
+    ///
+    /// Inputs, outputs and constants are matched by name, and gates pairwise in order, with
+    /// their inputs in the same positions. Each wire is mapped the first time it's seen, which
+    /// only finds the renumbering of single-assignment circuits in topological order, so other
+    /// circuits are an error.
+    pub fn is_isomorphic(
+        &self,
+        other: &BristolCircuit,
+    ) -> Result<Option<HashMap<usize, usize>>, BristolCircuitError> {
+        for circuit in [self, other] {
+            circuit.check_single_assignment()?;
+            circuit.check_topological_order()?;
+        }
+
+        let mut matcher = Matcher::default();
+        let (inputs, other_inputs) = (self.input_buses(), other.input_buses());
+
+        if self.io_widths != other.io_widths
+            || self.gates.len() != other.gates.len()
+            || inputs.len() != other_inputs.len()
+            || self.info.constants.len() != other.info.constants.len()
+        {
+            return Ok(None);
+        }
+
+        for bus in &inputs {
+            let Some(other_bus) = other_inputs.iter().find(|other| other.name == bus.name) else {
+                return Ok(None);
+            };
+
+            if bus.width != other_bus.width
+                || !(0..bus.width).all(|bit| matcher.unify(bus.wire + bit, other_bus.wire + bit))
+            {
+                return Ok(None);
+            }
+        }
+
+        for (name, constant) in &self.info.constants {
+            match other.info.constants.get(name) {
+                Some(other_constant)
+                    if other_constant.value == constant.value
+                        && matcher.unify(constant.wire_index, other_constant.wire_index) => {}
+                _ => return Ok(None),
+            }
+        }
+
+        for (gate, other_gate) in self.gates.iter().zip(&other.gates) {
+            if gate.op != other_gate.op
+                || gate.inputs.len() != other_gate.inputs.len()
+                || gate.outputs.len() != other_gate.outputs.len()
+            {
+                return Ok(None);
+            }
+
+            // `EQ` inputs are literal values, not wires.
+            let inputs_match = if takes_literals(&gate.op) {
+                gate.inputs == other_gate.inputs
+            } else {
+                gate.inputs
+                    .iter()
+                    .zip(&other_gate.inputs)
+                    .all(|(&wire, &other_wire)| matcher.unify(wire, other_wire))
+            };
+
+            if !inputs_match
+                || !gate
+                    .outputs
+                    .iter()
+                    .zip(&other_gate.outputs)
+                    .all(|(&wire, &other_wire)| matcher.unify(wire, other_wire))
+            {
+                return Ok(None);
+            }
+        }
+
+        let (outputs, other_outputs) = (self.output_buses(), other.output_buses());
+
+        if outputs.len() != other_outputs.len() {
+            return Ok(None);
+        }
+
+        for bus in &outputs {
+            let Some(other_bus) = other_outputs.iter().find(|other| other.name == bus.name) else {
+                return Ok(None);
+            };
+
+            if bus.width != other_bus.width
+                || !(0..bus.width).all(|bit| matcher.unify(bus.wire + bit, other_bus.wire + bit))
+            {
+                return Ok(None);
+            }
+        }
+
+        Ok(Some(matcher.forward))
+    }
+}
+
+/// A partial bijection between the wires of two circuits.
+#[derive(Default)]
+struct Matcher {
+    forward: HashMap<usize, usize>,
+    backward: HashMap<usize, usize>,
+}
+
+impl Matcher {
+    /// Maps `wire` to `other`, returning whether that's consistent with the mapping so far.
+    fn unify(&mut self, wire: usize, other: usize) -> bool {
+        match (self.forward.get(&wire), self.backward.get(&other)) {
+            (None, None) => {
+                self.forward.insert(wire, other);
+                self.backward.insert(other, wire);
+                true
+            }
+            (Some(&mapped), _) => mapped == other,
+            (None, Some(_)) => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::gadgets;
+
+    #[test]
+    fn test_renumbered_matches() {
+        let circuit = gadgets::adder(4, true);
+        let wire_count = circuit.wire_count;
+
+        // Moves the 9 input wires after the others, keeping each bus contiguous.
+        let rotate = |wire: usize| (wire + wire_count - 9) % wire_count;
+        let mut rotated = circuit.clone();
+        rotated.map_wires(rotate);
+        assert_ne!(rotated, circuit);
+
+        let mapping = circuit.is_isomorphic(&rotated).unwrap().unwrap();
+        assert_eq!(mapping.len(), wire_count);
+        assert!(mapping.iter().all(|(&wire, &other)| other == rotate(wire)));
+
+        let identity = circuit.is_isomorphic(&circuit).unwrap().unwrap();
+        assert!(identity.iter().all(|(wire, other)| wire == other));
+    }
+
+    #[test]
+    fn test_changed_op_does_not_match() {
+        let circuit = gadgets::adder(4, true);
+        let mut changed = circuit.clone();
+        changed.gates[3].op = if changed.gates[3].op == "AND" {
+            "XOR".into()
+        } else {
+            "AND".into()
+        };
+
+        assert_eq!(circuit.is_isomorphic(&changed).unwrap(), None);
+
+        let mut renamed = circuit.clone();
+        let wire = renamed
+            .info
+            .output_name_to_wire_index
+            .remove("sum")
+            .unwrap();
+        renamed
+            .info
+            .output_name_to_wire_index
+            .insert("total".into(), wire);
+        assert_eq!(circuit.is_isomorphic(&renamed).unwrap(), None);
+    }
+
+    #[test]
+    fn test_requires_single_assignment() {
+        let circuit = gadgets::adder(2, false);
+        let mut reassigned = circuit.clone();
+        let wire = reassigned.gates[0].outputs[0];
+        reassigned.gates[1].outputs[0] = wire;
+
+        let err = circuit.is_isomorphic(&reassigned).unwrap_err();
+        assert!(
+            err.to_string().contains("which gate 0 already wrote"),
+            "{}",
+            err
+        );
+    }
+}
@@ -0,0 +1,151 @@
+//! The original Bristol format of the classic AES and SHA circuits: a `gates wires` line, then
+//! one `in_1 in_2 out` line, with the inputs on the first wires and the output on the last.
+
+use std::{
+    collections::HashMap,
+    io::{BufRead, Write},
+};
+
+use crate::{
+    bristol_circuit::BristolCircuit, bristol_circuit_error::BristolCircuitError,
+    circuit_header::CircuitHeader, circuit_info::CircuitInfo, circuit_reader::BristolCircuitReader,
+    circuit_writer::BristolWriter, header_style::HeaderStyle, line_reader::LineReader,
+    parse_options::ParseOptions,
+};
+
+impl BristolCircuit {
+    /// Parses a legacy Bristol circuit, naming its inputs `input0` and `input1` and its output
+    /// `output0`.
+    pub fn from_legacy_bristol<R: BufRead>(r: R) -> Result<BristolCircuit, BristolCircuitError> {
+        let options = ParseOptions {
+            header_style: Some(HeaderStyle::Legacy),
+            ..Default::default()
+        };
+
+        let mut lines = LineReader::new(r, &options);
+        let header = CircuitHeader::read(&mut lines, options.header_style, Some((2, 1)))?;
+        let info = legacy_info(&header)?;
+
+        let reader = BristolCircuitReader::from_header(lines, header, &options);
+        let (circuit, _) = BristolCircuit::from_reader(info, reader, &options)?;
+
+        Ok(circuit)
+    }
+
+    /// Writes the circuit in the legacy format, which needs exactly two inputs, occupying the
+    /// first wires in order, and one output occupying the last wires.
+    pub fn write_legacy_bristol<W: Write>(&self, w: &mut W) -> Result<(), BristolCircuitError> {
+        let inputs = self.input_buses();
+        let outputs = self.output_buses();
+
+        let layout_ok = match (inputs.as_slice(), outputs.as_slice()) {
+            ([a, b], [out]) => {
+                a.wire == 0 && b.wire == a.width && out.wire + out.width == self.wire_count
+            }
+            _ => false,
+        };
+
+        if !layout_ok {
+            return Err(BristolCircuitError::Inconsistency {
+                message: "Legacy Bristol needs two inputs from wire 0 and one output on the last \
+                          wires"
+                    .into(),
+            });
+        }
+
+        let header = CircuitHeader {
+            gate_count: self.gates.len(),
+            wire_count: self.wire_count,
+            input_widths: self.io_widths.0.clone(),
+            output_widths: self.io_widths.1.clone(),
+            header_style: HeaderStyle::Legacy,
+        };
+
+        let mut writer = BristolWriter::new(w, &header)?;
+
+        for gate in &self.gates {
+            writer.write_gate(gate)?;
+        }
+
+        writer.finish()?;
+
+        Ok(())
+    }
+}
+
+fn legacy_info(header: &CircuitHeader) -> Result<CircuitInfo, BristolCircuitError> {
+    let input_wires = header.input_widths.iter().sum::<usize>();
+    let output_width = header.output_widths[0];
+
+    let output_wire = header
+        .wire_count
+        .checked_sub(output_width)
+        .filter(|&wire| wire >= input_wires)
+        .ok_or_else(|| BristolCircuitError::Inconsistency {
+            message: format!(
+                "{} wires can't hold {} input and {} output wires",
+                header.wire_count, input_wires, output_width
+            ),
+        })?;
+
+    Ok(CircuitInfo {
+        input_name_to_wire_index: HashMap::from([
+            ("input0".to_string(), 0),
+            ("input1".to_string(), header.input_widths[0]),
+        ]),
+        output_name_to_wire_index: HashMap::from([("output0".to_string(), output_wire)]),
+        ..Default::default()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 2-bit `a + b` mod 4, with `a` on wires 0-1 and `b` on wires 2-3, least significant bit
+    /// first, and the sum on the last two wires.
+    const ADDER: &str =
+        "4 8\n2 2 2\n\n2 1 0 2 6 XOR\n2 1 0 2 4 AND\n2 1 1 3 5 XOR\n2 1 4 5 7 XOR\n";
+
+    #[test]
+    fn test_parse_and_evaluate() {
+        let circuit = BristolCircuit::from_legacy_bristol(ADDER.as_bytes()).unwrap();
+
+        assert_eq!(circuit.info.inputs(), vec![("input0", 0), ("input1", 2)]);
+        assert_eq!(circuit.info.outputs(), vec![("output0", 6)]);
+        assert_eq!(circuit.header_style, HeaderStyle::Legacy);
+
+        // 3 + 2 = 1 (mod 4)
+        let outputs = circuit
+            .eval_boolean(&HashMap::from([
+                ("input0".to_string(), vec![true, true]),
+                ("input1".to_string(), vec![false, true]),
+            ]))
+            .unwrap();
+        assert_eq!(outputs["output0"], vec![true, false]);
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let circuit = BristolCircuit::from_legacy_bristol(ADDER.as_bytes()).unwrap();
+
+        let mut text = Vec::new();
+        circuit.write_legacy_bristol(&mut text).unwrap();
+        assert_eq!(String::from_utf8(text).unwrap(), ADDER);
+    }
+
+    #[test]
+    fn test_errors() {
+        let err = BristolCircuit::from_legacy_bristol("1 3\n2 2 1\n\n1 1 0 2 INV\n".as_bytes())
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Inconsistency: 3 wires can't hold 4 input and 1 output wires"
+        );
+
+        let mut circuit = BristolCircuit::from_legacy_bristol(ADDER.as_bytes()).unwrap();
+        circuit.wire_count += 1;
+        let err = circuit.write_legacy_bristol(&mut Vec::new()).unwrap_err();
+        assert!(err.to_string().contains("Legacy Bristol needs"), "{}", err);
+    }
+}
@@ -0,0 +1,197 @@
+use std::collections::{BTreeMap, HashMap};
+
+use crate::{
+    bristol_circuit::BristolCircuit, bristol_circuit_error::BristolCircuitError, gate::Gate,
+    trace::trace_span,
+};
+
+impl BristolCircuit {
+    /// Groups the gate indices into layers, each gate as early as it can go (ASAP): the first
+    /// layer only reads inputs, constants and wires no gate produces, and every later gate reads
+    /// at least one wire from the layer before. Gates don't need to be topologically sorted, but
+    /// a dependency cycle is an error naming the gates on it. `EQ` inputs are literal values,
+    /// not wires.
+    pub fn levelize(&self) -> Result<Vec<Vec<usize>>, BristolCircuitError> {
+        self.levelize_weighted(|_| 1)
+    }
+
+    /// Like [`BristolCircuit::levelize`], but a gate's layer is the most its inputs cost to
+    /// compute, a producer costing `weight` on top of its own layer. Weighting XOR as 0 and AND
+    /// as 1 gives layers by AND depth. A zero-weight gate shares its layer with the gates
+    /// reading it, so within a layer gates are listed in dependency order. Layers no gate is in
+    /// are left out.
+    pub fn levelize_weighted(
+        &self,
+        weight: impl Fn(&Gate) -> usize,
+    ) -> Result<Vec<Vec<usize>>, BristolCircuitError> {
+        let _span = trace_span!("pass", name = "levelize", gate_count = self.gates.len());
+
+        let GateLevels { order, levels, .. } = self.gate_levels(weight)?;
+        let mut layers = BTreeMap::<usize, Vec<usize>>::new();
+
+        for i in order {
+            layers.entry(levels[i]).or_default().push(i);
+        }
+
+        Ok(layers.into_values().collect())
+    }
+
+    /// Each gate's level as [`BristolCircuit::levelize_weighted`] assigns them.
+    pub(crate) fn gate_levels(
+        &self,
+        weight: impl Fn(&Gate) -> usize,
+    ) -> Result<GateLevels, BristolCircuitError> {
+        let order = self.topological_order()?;
+
+        let producers = self
+            .gates
+            .iter()
+            .enumerate()
+            .flat_map(|(i, gate)| gate.outputs.iter().map(move |&wire| (wire, i)))
+            .collect::<HashMap<_, _>>();
+
+        let mut levels = vec![0usize; self.gates.len()];
+
+        for &i in &order {
+            let gate = &self.gates[i];
+
+            levels[i] = gate
+                .input_wires()
+                .iter()
+                .filter_map(|wire| producers.get(wire))
+                .map(|&producer| levels[producer].saturating_add(weight(&self.gates[producer])))
+                .max()
+                .unwrap_or(0);
+        }
+
+        Ok(GateLevels {
+            order,
+            levels,
+            producers,
+        })
+    }
+
+    /// The circuit with its gates laid out layer by layer, as [`BristolCircuit::levelize`]
+    /// groups them.
+    pub fn reorder_by_levels(&self) -> Result<BristolCircuit, BristolCircuitError> {
+        let layers = self.levelize()?;
+
+        let mut result = self.clone();
+        result.gates = layers
+            .into_iter()
+            .flatten()
+            .map(|i| self.gates[i].clone())
+            .collect();
+
+        Ok(result)
+    }
+}
+
+/// From [`BristolCircuit::gate_levels`].
+pub(crate) struct GateLevels {
+    /// The gates in topological order.
+    pub order: Vec<usize>,
+
+    /// The level of each gate.
+    pub levels: Vec<usize>,
+
+    /// The gate producing each wire gates produce.
+    pub producers: HashMap<usize, usize>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{circuit_builder::CircuitBuilder, gadgets};
+
+    fn and_weight(gate: &Gate) -> usize {
+        usize::from(gate.op == "AND")
+    }
+
+    #[test]
+    fn test_adder_layers() {
+        // Each carry takes an XOR, an AND and another XOR after the one before, of which only
+        // the AND counts when weighted.
+        for bits in [2, 4, 16] {
+            let circuit = gadgets::adder(bits, true);
+
+            let layers = circuit.levelize().unwrap();
+            assert_eq!(layers.len(), 3 * bits - 2);
+            assert_eq!(layers.len(), circuit.stats().unwrap().depth);
+            assert_eq!(layers.concat().len(), circuit.gates.len());
+
+            let weighted = circuit.levelize_weighted(and_weight).unwrap();
+            assert_eq!(weighted.len(), bits + 1);
+        }
+    }
+
+    #[test]
+    fn test_layers_only_read_earlier_layers() {
+        let circuit = gadgets::adder(8, true);
+        let layers = circuit.levelize().unwrap();
+
+        let mut layer_of = HashMap::new();
+        for (k, layer) in layers.iter().enumerate() {
+            for &i in layer {
+                for &wire in &circuit.gates[i].outputs {
+                    layer_of.insert(wire, k);
+                }
+            }
+        }
+
+        for (k, layer) in layers.iter().enumerate() {
+            for &i in layer {
+                let inputs = circuit.gates[i].inputs.iter();
+                let read = inputs.filter_map(|wire| layer_of.get(wire)).copied();
+
+                assert!(read.clone().all(|j| j < k));
+                assert!(k == 0 || read.max() == Some(k - 1));
+            }
+        }
+
+        let reordered = circuit.reorder_by_levels().unwrap();
+        assert!(reordered.is_topologically_sorted());
+        assert_eq!(
+            reordered.levelize().unwrap().concat(),
+            (0..circuit.gates.len()).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn test_multi_output_and_unsorted() {
+        // A two-output SPLIT, an AND of its first output and XOR of the AND and its second.
+        let mut b = CircuitBuilder::new();
+        let x = b.input("x");
+        let y = b.input("y");
+        let s0 = b.gate("SPLIT", &[x]);
+        let s1 = b.gate("SPLIT", &[x]);
+        let a = b.gate("AND", &[s0, y]);
+        let c = b.gate("XOR", &[s1, a]);
+        let d = b.gate("XOR", &[x, y]);
+        b.output("c", c);
+        b.output("d", d);
+
+        let mut circuit = b.build().unwrap();
+        let second = circuit.gates.remove(1);
+        circuit.gates[0].outputs.extend(second.outputs);
+        circuit.gates.reverse();
+
+        // The gates are now XOR(x, y), XOR(s1, a), AND(s0, y) and SPLIT.
+        assert_eq!(circuit.levelize().unwrap(), [vec![0, 3], vec![2], vec![1]]);
+        assert_eq!(
+            circuit.levelize_weighted(and_weight).unwrap(),
+            [vec![0, 3, 2], vec![1]]
+        );
+    }
+
+    #[test]
+    fn test_cycle() {
+        let mut circuit = gadgets::adder(2, false);
+        circuit.gates[0].inputs[0] = circuit.gates[1].outputs[0];
+        circuit.gates[1].inputs[0] = circuit.gates[0].outputs[0];
+
+        let err = circuit.levelize().unwrap_err();
+        assert!(err.to_string().contains("Dependency cycle"), "{}", err);
+        assert!(circuit.reorder_by_levels().is_err());
+    }
+}
@@ -1,12 +1,158 @@
+mod a_gate_type;
+mod analyzed_circuit;
+#[cfg(feature = "arbitrary")]
+mod arbitrary;
+mod arithmetic_eval;
+mod batched_eval;
+mod binary_format;
+mod bit_blast;
+mod bool_eval;
+mod bool_gate_type;
 mod bristol_circuit;
 mod bristol_circuit_error;
 mod bristol_line;
+mod bristol_text;
+mod circuit_builder;
+mod circuit_header;
 mod circuit_info;
+mod circuit_library;
+mod circuit_reader;
+mod circuit_writer;
+mod common_subexpressions;
+pub mod compact_gates;
+mod compact_wires;
+mod compose;
+mod compression;
+mod constant_folding;
+mod constant_value;
+mod copy_elimination;
+mod cost_model;
+mod critical_path;
+mod debug_trace;
+mod dependencies;
+mod diff;
+mod dot;
+mod eval;
+mod eval_trace;
+mod evaluator;
+mod expand_ops;
+mod extract;
+#[cfg(feature = "bigint")]
+mod field_eval;
+mod files;
+mod fingerprint;
+#[cfg(feature = "bigint")]
+mod fixed_point;
+pub mod gadgets;
 mod gate;
+mod gate_index;
+mod gate_op;
+mod graph_json;
+mod hash_cons_builder;
+mod header_style;
+mod info_directives;
+mod io_encoding;
+mod io_kind;
+mod isomorphism;
+mod legacy_bristol;
+mod levelize;
+mod line_reader;
+mod lut;
+mod memory_estimate;
+mod multiplicative_depth;
+mod mux;
+mod op_classification;
+mod op_name;
+mod op_properties;
+mod op_registry;
+mod parallel_eval;
+mod parallel_merge;
+#[cfg(feature = "rayon")]
+mod parallel_parse;
+mod parse_options;
+mod parse_warning;
+mod party;
+mod prepared_circuit;
+mod profile;
+mod prune;
+#[cfg(feature = "testing")]
+mod random_circuit;
 mod raw_bristol_circuit;
+mod rename_io;
+mod semantic_hash;
+mod sha256;
+mod shuffle;
+mod split_mix;
+mod stats;
+mod topological_sort;
+mod trace;
+mod validate;
+mod validation_issue;
+mod visibility;
+pub mod visit;
+mod wire_expr;
+mod wire_group;
+mod wire_index;
+mod wire_layout;
+mod write_options;
 
+pub use a_gate_type::AGateType;
+pub use analyzed_circuit::{AnalyzedCircuit, CacheStats, EditKind};
+pub use bool_gate_type::{Arity, BoolGateType};
 pub use bristol_circuit::BristolCircuit;
 pub use bristol_circuit_error::BristolCircuitError;
+pub use bristol_text::BristolTextCircuit;
+pub use circuit_builder::{CircuitBuilder, WireId};
+pub use circuit_header::CircuitHeader;
 pub use circuit_info::{CircuitInfo, ConstantInfo};
-pub use gate::Gate;
-pub use raw_bristol_circuit::RawBristolCircuit;
+pub use circuit_library::CircuitLibrary;
+pub use circuit_reader::BristolCircuitReader;
+pub use circuit_writer::BristolWriter;
+pub use common_subexpressions::CommonSubexpressionReport;
+pub use compact_gates::CompactJson;
+pub use constant_value::ConstantValue;
+pub use copy_elimination::{CopyEliminationReport, OutputCopies, DEFAULT_COPY_OPS};
+pub use cost_model::{CostModel, CostReport, OpCost};
+pub use critical_path::{CriticalPath, CriticalPathReport};
+pub use debug_trace::{DebugTrace, DebugTraceOptions, GateRecord};
+pub use diff::{
+    CircuitDiff, ConstantChange, DiffOptions, GateDiffSummary, GateEdit, GateField, InterfaceChange,
+};
+pub use dot::DotOptions;
+pub use eval_trace::{EvalTrace, TraceChange, VcdOptions, VcdTimeStep, VcdValue, WatchedWire};
+pub use evaluator::Evaluator;
+#[cfg(feature = "bigint")]
+pub use fixed_point::FixedPointCodec;
+pub use gate::{Gate, GateInputs, GateOutputs};
+pub use gate_index::GateIndex;
+pub use gate_op::{GateOp, TypedGate};
+pub use graph_json::GraphJsonOptions;
+pub use hash_cons_builder::{HashConsBuilder, HashConsStats};
+pub use header_style::HeaderStyle;
+pub use io_encoding::BitOrder;
+pub use io_kind::IoKind;
+pub use lut::Lut;
+pub use memory_estimate::MemoryEstimate;
+pub use multiplicative_depth::MULTIPLICATIVE_OPS;
+pub use op_classification::{OpBucket, OpClassification, OpKind};
+pub use op_name::OpName;
+pub use op_registry::{OpMismatch, OpRegistry, UnknownOpPolicy};
+pub use parallel_eval::ParallelEvaluator;
+pub use parallel_merge::ParallelMergeOptions;
+pub use parse_options::{GateCountPolicy, ParseLimits, ParseOptions, Utf8Policy};
+pub use parse_warning::ParseWarning;
+pub use party::PartyId;
+pub use prepared_circuit::PreparedCircuit;
+pub use profile::{ProfileBucket, ProfileBuckets, ProfileOptions, ProfileReport};
+pub use prune::{PruneReport, UnusedInputs};
+#[cfg(feature = "testing")]
+pub use random_circuit::RandomCircuitConfig;
+pub use raw_bristol_circuit::{RawBristolCircuit, RawBristolCircuitRef, UnknownFields};
+pub use shuffle::WirePermutation;
+pub use stats::CircuitStats;
+pub use validation_issue::{IssueKind, Severity, ValidationIssue};
+pub use visibility::Visibility;
+pub use wire_expr::{CircuitContext, WireExpr};
+pub use wire_group::{GroupLayout, WireGroup, WireSpan};
+pub use wire_index::{WireIndex, WireProducer};
+pub use write_options::WriteOptions;
@@ -0,0 +1,817 @@
+use std::{
+    collections::VecDeque,
+    io::{BufRead, Read},
+};
+
+use crate::{
+    bristol_circuit_error::BristolCircuitError,
+    bristol_line::BristolLine,
+    gate::{validate_gate_shape, Gate, GateInputs, GateOutputs},
+    op_name::OpInterner,
+    parse_options::{ParseLimits, ParseOptions},
+};
+
+/// Reads Bristol text line by line while bounding the memory used for any single line.
+///
+/// Comments, from `#` or `//` to the end of the line, are skipped, so comment-only lines count as
+/// blank.
+///
+/// A line may hold any number of gates, one after another; some generators write the whole gate
+/// list on one line. Gate lines longer than the streaming threshold are never materialized: their
+/// tokens are pulled from the underlying reader one at a time, so memory use is proportional to
+/// the gate being built rather than to the line.
+///
+/// Gates with the same op share it, so each distinct op is allocated once.
+pub struct LineReader<R> {
+    r: R,
+    ops: OpInterner,
+    buf: Vec<u8>,
+    limits: ParseLimits,
+    max_line_bytes: usize,
+    streaming_threshold: usize,
+    peeked: Option<BristolLine>,
+    long_line: Option<LongLine>,
+
+    /// The 1-based number of the physical line last read, counting blank lines.
+    line_number: usize,
+}
+
+/// The next piece of content, as returned by `LineReader::peek`.
+pub enum Peeked<'a> {
+    Line(&'a BristolLine),
+    /// A line too long to buffer. Only gates may be long, and they are read via `next_gate`.
+    LongLine,
+    End,
+}
+
+/// State for a physical line that is being consumed as a token stream.
+struct LongLine {
+    pending: VecDeque<String>,
+    partial: Vec<u8>,
+    line_bytes: usize,
+    ended: bool,
+}
+
+enum RawLine {
+    Complete,
+    Truncated,
+    Eof,
+}
+
+impl<R: BufRead> LineReader<R> {
+    pub fn new(r: R, options: &ParseOptions) -> Self {
+        LineReader {
+            r,
+            ops: OpInterner::default(),
+            buf: Vec::new(),
+            limits: options.limits,
+            max_line_bytes: options.limits.max_line_bytes.unwrap_or(usize::MAX).max(1),
+            streaming_threshold: options.streaming_threshold.max(1),
+            peeked: None,
+            long_line: None,
+            line_number: 0,
+        }
+    }
+
+    pub fn limits(&self) -> &ParseLimits {
+        &self.limits
+    }
+
+    #[cfg(feature = "rayon")]
+    /// Numbers lines as if `r` started after line `line_number` of a larger input.
+    pub(crate) fn starting_after_line(mut self, line_number: usize) -> Self {
+        self.line_number = line_number;
+        self
+    }
+
+    #[cfg(feature = "rayon")]
+    /// The 1-based number of the first line not yet consumed, including one only peeked at.
+    pub(crate) fn next_unread_line(&self) -> usize {
+        if self.peeked.is_some() || self.long_line.is_some() {
+            self.line_number
+        } else {
+            self.line_number + 1
+        }
+    }
+
+    /// Reads the next non-blank line, or `None` at the end of the input.
+    pub fn next_line(&mut self) -> Result<Option<BristolLine>, BristolCircuitError> {
+        if let Some(line) = self.peeked.take() {
+            return Ok(Some(line));
+        }
+
+        if let Some(token) = self.next_stream_token()? {
+            return Err(self.error(format!("Unexpected token \"{}\" after gate", token)));
+        }
+
+        loop {
+            match self.read_raw(self.max_line_bytes)? {
+                RawLine::Eof => return Ok(None),
+                RawLine::Truncated => return Err(self.line_too_long()),
+                RawLine::Complete => {
+                    let line = self.tokenize()?;
+
+                    if line.len() != 0 {
+                        return Ok(Some(line));
+                    }
+                }
+            }
+        }
+    }
+
+    /// Like `next_line`, but treats the end of the input as an error.
+    pub fn expect_line(&mut self, context: &str) -> Result<BristolLine, BristolCircuitError> {
+        self.next_line()?
+            .ok_or_else(|| BristolCircuitError::ParsingError {
+                message: format!("Unexpected end of input while reading {}", context),
+            })
+    }
+
+    /// Reads the next gate, or `None` at the end of the input.
+    pub fn next_gate(&mut self) -> Result<Option<Gate>, BristolCircuitError> {
+        if let Some(line) = self.peeked.take() {
+            return self.first_gate(line).map(Some);
+        }
+
+        if self.long_line.is_some() {
+            if let Some(gate) = self.stream_gate()? {
+                return Ok(Some(gate));
+            }
+        }
+
+        let limit = self.streaming_threshold.min(self.max_line_bytes);
+
+        loop {
+            match self.read_raw(limit)? {
+                RawLine::Eof => return Ok(None),
+                RawLine::Complete => {
+                    if let Some(gate) = parse_gate(&self.buf, &mut self.ops) {
+                        return Ok(Some(gate));
+                    }
+
+                    // Blank, commented or not a gate, which the general path handles.
+                    let line = self.tokenize()?;
+
+                    if line.len() != 0 {
+                        return self.first_gate(line).map(Some);
+                    }
+                }
+                RawLine::Truncated => {
+                    if limit == self.max_line_bytes {
+                        return Err(self.line_too_long());
+                    }
+
+                    self.start_long_line()?;
+
+                    // Otherwise the line was only a comment.
+                    if let Some(gate) = self.stream_gate()? {
+                        return Ok(Some(gate));
+                    }
+                }
+            }
+        }
+    }
+
+    /// Looks at the next non-blank line without consuming it.
+    pub fn peek(&mut self) -> Result<Peeked<'_>, BristolCircuitError> {
+        if self.peeked.is_none() && self.long_line.is_none() {
+            let limit = self.streaming_threshold.min(self.max_line_bytes);
+
+            loop {
+                match self.read_raw(limit)? {
+                    RawLine::Eof => break,
+                    RawLine::Complete => {
+                        let line = self.tokenize()?;
+
+                        if line.len() != 0 {
+                            self.peeked = Some(line);
+                            break;
+                        }
+                    }
+                    RawLine::Truncated => {
+                        if limit == self.max_line_bytes {
+                            return Err(self.line_too_long());
+                        }
+
+                        self.start_long_line()?;
+                        break;
+                    }
+                }
+            }
+        }
+
+        Ok(match (&self.peeked, &self.long_line) {
+            (Some(line), _) => Peeked::Line(line),
+            (None, Some(_)) => Peeked::LongLine,
+            (None, None) => Peeked::End,
+        })
+    }
+
+    #[cfg(test)]
+    pub fn buffer_capacity(&self) -> usize {
+        self.buf.capacity()
+    }
+
+    /// Reads bytes up to the next newline (which is consumed but not stored) into `self.buf`,
+    /// stopping early once more than `limit` bytes have been buffered.
+    fn read_raw(&mut self, limit: usize) -> Result<RawLine, BristolCircuitError> {
+        self.buf.clear();
+
+        // One byte over the limit tells a line that's too long from one that just fits.
+        let max = limit.saturating_add(1) as u64;
+
+        if (&mut self.r).take(max).read_until(b'\n', &mut self.buf)? == 0 {
+            return Ok(RawLine::Eof);
+        }
+
+        self.line_number += 1;
+
+        if self.buf.last() == Some(&b'\n') {
+            self.buf.pop();
+        } else if self.buf.len() > limit {
+            return Ok(RawLine::Truncated);
+        }
+
+        Ok(RawLine::Complete)
+    }
+
+    fn tokenize(&self) -> Result<BristolLine, BristolCircuitError> {
+        let line = std::str::from_utf8(&self.buf).map_err(|_| self.invalid_utf8())?;
+
+        Ok(BristolLine {
+            tokens: strip_comment(line)
+                .split_whitespace()
+                .map(|part| part.to_string())
+                .collect(),
+            line_number: Some(self.line_number),
+        })
+    }
+
+    fn start_long_line(&mut self) -> Result<(), BristolCircuitError> {
+        let split = self
+            .buf
+            .iter()
+            .rposition(|b| b.is_ascii_whitespace())
+            .map_or(0, |i| i + 1);
+
+        let complete = std::str::from_utf8(&self.buf[..split]).map_err(|_| self.invalid_utf8())?;
+
+        self.long_line = Some(LongLine {
+            pending: complete.split_whitespace().map(str::to_string).collect(),
+            partial: self.buf[split..].to_vec(),
+            line_bytes: self.buf.len(),
+            ended: false,
+        });
+
+        self.buf.clear();
+        self.buf.shrink_to(self.streaming_threshold);
+
+        Ok(())
+    }
+
+    /// The next token of the long line, or `None` once it ends, which a comment does.
+    fn next_stream_token(&mut self) -> Result<Option<String>, BristolCircuitError> {
+        let Some(token) = self.next_raw_stream_token()? else {
+            return Ok(None);
+        };
+
+        let content = strip_comment(&token);
+
+        if content.len() == token.len() {
+            return Ok(Some(token));
+        }
+
+        let content = content.to_string();
+        self.skip_rest_of_long_line()?;
+
+        Ok((!content.is_empty()).then_some(content))
+    }
+
+    fn next_raw_stream_token(&mut self) -> Result<Option<String>, BristolCircuitError> {
+        let Some(long_line) = &mut self.long_line else {
+            return Ok(None);
+        };
+
+        if let Some(token) = long_line.pending.pop_front() {
+            return Ok(Some(token));
+        }
+
+        let mut token = std::mem::take(&mut long_line.partial);
+
+        while !long_line.ended {
+            let available = self.r.fill_buf()?;
+
+            if available.is_empty() {
+                long_line.ended = true;
+                break;
+            }
+
+            let mut consumed = 0;
+            let mut token_done = false;
+
+            for &b in available {
+                if b == b'\n' {
+                    self.r.consume(consumed + 1);
+                    long_line.ended = true;
+                    break;
+                }
+
+                consumed += 1;
+
+                if b.is_ascii_whitespace() {
+                    if !token.is_empty() {
+                        token_done = true;
+                        break;
+                    }
+                } else {
+                    token.push(b);
+                }
+            }
+
+            if !long_line.ended {
+                self.r.consume(consumed);
+            }
+
+            long_line.line_bytes += consumed;
+
+            if long_line.line_bytes > self.max_line_bytes {
+                return Err(self.line_too_long());
+            }
+
+            if token_done {
+                break;
+            }
+        }
+
+        if long_line.ended && long_line.pending.is_empty() && token.is_empty() {
+            self.long_line = None;
+        }
+
+        if token.is_empty() {
+            return Ok(None);
+        }
+
+        String::from_utf8(token)
+            .map(Some)
+            .map_err(|_| self.invalid_utf8())
+    }
+
+    fn stream_token<T: std::str::FromStr>(
+        &mut self,
+        context: &str,
+    ) -> Result<T, BristolCircuitError> {
+        let token = self.next_stream_token()?.ok_or_else(|| {
+            self.error(format!(
+                "Unexpected end of line while reading gate {}",
+                context
+            ))
+        })?;
+
+        self.parse_stream_token(token, context)
+    }
+
+    fn parse_stream_token<T: std::str::FromStr>(
+        &self,
+        token: String,
+        context: &str,
+    ) -> Result<T, BristolCircuitError> {
+        if token.contains(char::REPLACEMENT_CHARACTER) {
+            return Err(self.error(format!("Invalid UTF-8 in gate {}", context)));
+        }
+
+        token
+            .parse::<T>()
+            .map_err(|_| self.error(format!("Failed to convert gate {} \"{}\"", context, token)))
+    }
+
+    /// Discards the rest of the long line, e.g. a comment. Comments aren't buffered, so they
+    /// don't count towards `max_line_bytes`.
+    fn skip_rest_of_long_line(&mut self) -> Result<(), BristolCircuitError> {
+        if let Some(long_line) = &mut self.long_line {
+            long_line.pending.clear();
+            long_line.partial.clear();
+
+            while !long_line.ended {
+                let available = self.r.fill_buf()?;
+
+                if available.is_empty() {
+                    break;
+                }
+
+                match available.iter().position(|&b| b == b'\n') {
+                    Some(i) => {
+                        self.r.consume(i + 1);
+                        long_line.ended = true;
+                    }
+                    None => {
+                        let n = available.len();
+                        self.r.consume(n);
+                    }
+                }
+            }
+        }
+
+        self.long_line = None;
+
+        Ok(())
+    }
+
+    /// Reads a gate from the long line, or `None` if nothing but a comment was left on it.
+    fn stream_gate(&mut self) -> Result<Option<Gate>, BristolCircuitError> {
+        let input_len = match self.next_stream_token()? {
+            Some(token) => self.parse_stream_token::<usize>(token, "input count")?,
+            None => return Ok(None),
+        };
+        let output_len = self.stream_token::<usize>("output count")?;
+
+        let mut inputs = GateInputs::new();
+        for _ in 0..input_len {
+            inputs.push(self.stream_token("input")?);
+        }
+
+        let mut outputs = GateOutputs::new();
+        for _ in 0..output_len {
+            outputs.push(self.stream_token("output")?);
+        }
+
+        let op = self.stream_token::<String>("op")?;
+
+        validate_gate_shape(&op, input_len, output_len).map_err(|message| self.error(message))?;
+        self.finish_long_line_if_done()?;
+
+        Ok(Some(Gate {
+            inputs,
+            outputs,
+            op: self.ops.intern(&op),
+        }))
+    }
+
+    /// Ends the long line once nothing but whitespace or a comment is left on it, so that `peek`
+    /// sees the line after it.
+    fn finish_long_line_if_done(&mut self) -> Result<(), BristolCircuitError> {
+        if let Some(token) = self.next_stream_token()? {
+            self.queue_tokens([token].into());
+        }
+
+        Ok(())
+    }
+
+    /// The gate `line` starts with. Any tokens after it are queued, to be read as more gates.
+    fn first_gate(&mut self, mut line: BristolLine) -> Result<Gate, BristolCircuitError> {
+        let gate = line.leading_gate()?;
+        let rest = line.tokens.split_off(line.gate_len()?);
+
+        if !rest.is_empty() {
+            self.queue_tokens(rest.into());
+        }
+
+        Ok(self.intern(gate))
+    }
+
+    /// Puts `tokens` before the rest of the current line, starting a long line for them if
+    /// there is none.
+    fn queue_tokens(&mut self, mut tokens: VecDeque<String>) {
+        let long_line = self.long_line.get_or_insert_with(|| LongLine {
+            pending: VecDeque::new(),
+            partial: Vec::new(),
+            line_bytes: 0,
+            ended: true,
+        });
+
+        tokens.append(&mut long_line.pending);
+        long_line.pending = tokens;
+    }
+
+    fn intern(&mut self, mut gate: Gate) -> Gate {
+        gate.op = self.ops.intern(&gate.op);
+        gate
+    }
+
+    fn line_too_long(&self) -> BristolCircuitError {
+        BristolCircuitError::LimitExceeded {
+            limit: "max_line_bytes",
+            max: self.max_line_bytes,
+            message: format!("Line {} is too long", self.line_number),
+        }
+    }
+
+    fn invalid_utf8(&self) -> BristolCircuitError {
+        BristolCircuitError::ParsingError {
+            message: format!("Line {} is not valid utf8", self.line_number),
+        }
+    }
+
+    /// An error about the current line, which is too long to quote.
+    pub fn error(&self, message: String) -> BristolCircuitError {
+        BristolCircuitError::ParsingError {
+            message: format!("{} in line {}", message, self.line_number),
+        }
+    }
+}
+
+/// Parses a plain gate line by slicing it, so only the gate itself is allocated. `None` for
+/// anything else, including comments and non-ASCII text, which [`BristolLine::gate`] handles the
+/// same way it always has.
+fn parse_gate(line: &[u8], ops: &mut OpInterner) -> Option<Gate> {
+    let mut tokens = line
+        .split(|b| b.is_ascii_whitespace())
+        .filter(|token| !token.is_empty());
+    let input_len = parse_usize(tokens.next()?)?;
+    let output_len = parse_usize(tokens.next()?)?;
+
+    // Collecting grows the lists as tokens are found, so absurd counts don't allocate.
+    let inputs = (0..input_len)
+        .map(|_| parse_usize(tokens.next()?))
+        .collect::<Option<GateInputs>>()?;
+    let outputs = (0..output_len)
+        .map(|_| parse_usize(tokens.next()?))
+        .collect::<Option<GateOutputs>>()?;
+    let op = tokens.next()?;
+
+    if tokens.next().is_some()
+        || !op
+            .iter()
+            .all(|&b| b.is_ascii_graphic() && b != b'#' && b != b'/')
+    {
+        return None;
+    }
+
+    let op = std::str::from_utf8(op).ok()?;
+    validate_gate_shape(op, input_len, output_len).ok()?;
+
+    Some(Gate {
+        inputs,
+        outputs,
+        op: ops.intern(op),
+    })
+}
+
+/// A token of decimal digits, or `None` for anything else, including overflow.
+fn parse_usize(token: &[u8]) -> Option<usize> {
+    token.iter().try_fold(0usize, |n, &b| {
+        if !b.is_ascii_digit() {
+            return None;
+        }
+
+        n.checked_mul(10)?.checked_add(usize::from(b - b'0'))
+    })
+}
+
+/// `line` up to the start of any `#` or `//` comment.
+pub(crate) fn strip_comment(line: &str) -> &str {
+    let bytes = line.as_bytes();
+    let end = (0..bytes.len())
+        .find(|&i| bytes[i] == b'#' || (bytes[i] == b'/' && bytes.get(i + 1) == Some(&b'/')))
+        .unwrap_or(line.len());
+
+    &line[..end]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn options(max_line_bytes: Option<usize>, streaming_threshold: usize) -> ParseOptions {
+        ParseOptions {
+            limits: ParseLimits {
+                max_line_bytes,
+                ..Default::default()
+            },
+            streaming_threshold,
+            ..Default::default()
+        }
+    }
+
+    fn read_gates(input: &str, options: &ParseOptions) -> Result<Vec<Gate>, BristolCircuitError> {
+        let mut reader = LineReader::new(input.as_bytes(), options);
+        let mut gates = Vec::new();
+
+        while let Some(gate) = reader.next_gate()? {
+            gates.push(gate);
+        }
+
+        Ok(gates)
+    }
+
+    #[test]
+    fn test_streaming_matches_buffered() {
+        let input = "2 1 0 1 2 AAdd\n\n  2 1 2 1 3 AMul  \n1 1 3 4 ANeg\n";
+
+        let buffered = read_gates(input, &ParseOptions::default()).unwrap();
+        assert_eq!(buffered.len(), 3);
+
+        for threshold in 1..20 {
+            assert_eq!(
+                read_gates(input, &options(None, threshold)).unwrap(),
+                buffered
+            );
+        }
+    }
+
+    #[test]
+    fn test_streamed_comments() {
+        let input = "2 1 0 1 2 AAdd # add\n\
+                     # a comment line that is longer than the threshold\n\
+                     2 1 2 1 3 AMul  \n\
+                     1 1 3 4 ANeg//negate\n";
+
+        for threshold in 1..40 {
+            let gates = read_gates(input, &options(None, threshold)).unwrap();
+            let ops = gates
+                .iter()
+                .map(|gate| gate.op.as_str())
+                .collect::<Vec<_>>();
+            assert_eq!(ops, vec!["AAdd", "AMul", "ANeg"], "threshold {}", threshold);
+        }
+    }
+
+    #[test]
+    fn test_gates_share_a_line() {
+        let input = "2 1 0 1 2 AAdd 2 1 2 1 3 AMul # comment\n1 1 3 4 ANeg  1 1 4 5 ANeg\n";
+        let ops = |gates: Vec<Gate>| {
+            gates
+                .iter()
+                .map(|gate| gate.op.to_string())
+                .collect::<Vec<_>>()
+        };
+
+        let buffered = read_gates(input, &ParseOptions::default()).unwrap();
+        assert_eq!(ops(buffered.clone()), ["AAdd", "AMul", "ANeg", "ANeg"]);
+        assert_eq!(buffered[1].inputs[..], [2, 1]);
+
+        for threshold in 1..60 {
+            assert_eq!(
+                read_gates(input, &options(None, threshold)).unwrap(),
+                buffered,
+                "threshold {}",
+                threshold
+            );
+        }
+
+        for threshold in [4, 100] {
+            let err = read_gates("2 1 0 1 2 AAdd 2 1 2\n", &options(None, threshold)).unwrap_err();
+            assert_eq!(
+                err.to_string(),
+                "Parsing error: Unexpected end of line while reading gate input in line 1"
+            );
+        }
+    }
+
+    #[test]
+    fn test_max_line_bytes() {
+        let input = "2 1 0 1 2 AAdd\n";
+
+        assert!(read_gates(input, &options(Some(14), 4)).is_ok());
+
+        let err = read_gates(input, &options(Some(13), 4)).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Limit exceeded: Line 1 is too long (max_line_bytes is 13)"
+        );
+
+        let err = LineReader::new(input.as_bytes(), &options(Some(5), 4))
+            .next_line()
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Limit exceeded: Line 1 is too long (max_line_bytes is 5)"
+        );
+    }
+
+    #[test]
+    fn test_plain_gates_match_general_path() {
+        for line in [
+            "2 1 0 1 2 AAdd",
+            "  2\t1 0 1 2 AAdd\r",
+            "+2 1 0 1 2 AAdd",
+            "2 1 0 1 2 AAdd # comment",
+            "2 1 0 1 2 AAdd//comment",
+            "2 1 0 1 2 A/B",
+            "2 1 0 1 2 X\u{b}Y",
+            "2 1 0 1 2 X\u{a0}Y",
+            "2 1 0 1 2 AAdd extra",
+            "2 1 0 1",
+            "18446744073709551615 1 0 1 2 AAdd",
+            "1 1 00 1 MUX",
+            "3 2 0 1 2 3 4 MAND",
+            "",
+        ] {
+            let general = BristolLine::new(
+                strip_comment(line)
+                    .split_whitespace()
+                    .map(str::to_string)
+                    .collect(),
+            )
+            .gate()
+            .ok();
+
+            if let Some(gate) = parse_gate(line.as_bytes(), &mut OpInterner::default()) {
+                assert_eq!(Some(gate), general, "{:?}", line);
+            }
+
+            assert_eq!(
+                read_gates(&format!("{}\n", line), &ParseOptions::default()).ok(),
+                general
+                    .map(|gate| vec![gate])
+                    .or(line.is_empty().then(Vec::new)),
+                "{:?}",
+                line
+            );
+        }
+
+        assert!(parse_gate(b"2 1 0 1 2 AAdd", &mut OpInterner::default()).is_some());
+    }
+
+    #[test]
+    fn test_truncated_long_gate() {
+        let err = read_gates("3 1 0 1", &options(None, 2)).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Parsing error: Unexpected end of line while reading gate input in line 1"
+        );
+    }
+
+    #[test]
+    fn test_ten_megabyte_gate_list_on_one_line() {
+        let mut input = String::new();
+        let mut expected_count = 0;
+
+        while input.len() < 10 * 1024 * 1024 {
+            let i = expected_count;
+            input.push_str(&format!("2 1 {} {} {} XOR ", i, i + 1, i + 2));
+            expected_count += 1;
+        }
+        input.push('\n');
+
+        let options = ParseOptions::default();
+        let mut reader = LineReader::new(input.as_bytes(), &options);
+        let mut count = 0;
+
+        while let Some(gate) = reader.next_gate().unwrap() {
+            assert_eq!(gate.inputs[..], [count, count + 1]);
+            count += 1;
+        }
+
+        assert_eq!(count, expected_count);
+        assert!(reader.buffer_capacity() <= options.streaming_threshold);
+    }
+
+    #[test]
+    fn test_ten_megabyte_gate_on_one_line() {
+        // A MAND gate whose wire lists fill 10 MiB.
+        let operands = 10 * 1024 * 1024 / (3 * 8);
+        let mut input = format!("{} {}", 2 * operands, operands);
+
+        for i in 0..(3 * operands) {
+            input.push_str(&format!(" {:7}", 1_000_000 + i));
+        }
+
+        input.push_str(" MAND\n2 1 0 1 2 AND\n");
+        assert!(input.len() >= 10 * 1024 * 1024);
+
+        let options = ParseOptions::default();
+        let mut reader = LineReader::new(input.as_bytes(), &options);
+
+        let gate = reader.next_gate().unwrap().unwrap();
+        assert_eq!(gate.inputs.len(), 2 * operands);
+        assert_eq!(gate.outputs[operands - 1], 1_000_000 + 3 * operands - 1);
+        assert!(reader.buffer_capacity() <= options.streaming_threshold);
+
+        assert_eq!(reader.next_gate().unwrap().unwrap().op, "AND");
+        assert!(reader.next_gate().unwrap().is_none());
+    }
+
+    #[test]
+    fn test_wide_mand_gate() {
+        let operands = 100_000;
+        let mut input = format!("{} {}", 2 * operands, operands);
+
+        for i in 0..(3 * operands) {
+            input.push_str(&format!(" {}", i));
+        }
+
+        input.push_str(" MAND\n2 1 0 1 2 AND\n");
+
+        let gates = read_gates(&input, &ParseOptions::default()).unwrap();
+        assert_eq!(gates.len(), 2);
+        assert_eq!(gates[0].inputs.len(), 2 * operands);
+        assert_eq!(gates[0].outputs.len(), operands);
+        assert_eq!(gates[0].op, "MAND");
+        assert_eq!(gates[1].op, "AND");
+    }
+
+    #[test]
+    fn test_peek() {
+        let input = "1 2\n\n1 1 0 1 AXor\n";
+        let mut reader = LineReader::new(input.as_bytes(), &options(None, 6));
+
+        assert!(matches!(reader.peek().unwrap(), Peeked::Line(line) if line.len() == 2));
+        assert_eq!(reader.next_line().unwrap().unwrap().tokens, vec!["1", "2"]);
+
+        assert!(matches!(reader.peek().unwrap(), Peeked::LongLine));
+        assert_eq!(reader.next_gate().unwrap().unwrap().op, "AXor");
+
+        assert!(matches!(reader.peek().unwrap(), Peeked::End));
+        assert!(reader.next_line().unwrap().is_none());
+    }
+}
@@ -0,0 +1,435 @@
+use smallvec::smallvec;
+
+use crate::{
+    bristol_circuit::BristolCircuit, bristol_circuit_error::BristolCircuitError, gate::Gate,
+    trace::trace_span,
+};
+
+/// Inputs beyond this would need truth tables too large to be practical.
+const MAX_LUT_INPUTS: usize = 32;
+
+/// A lookup-table gate with `k` inputs, written as `LUT[<hex truth table>]`.
+///
+/// The table is a hex number whose bit `i` is the output when the inputs, read as a binary number
+/// with the first input as the least significant bit, equal `i`. It has exactly `2^k / 4` digits
+/// (one digit when `k < 2`), so a 3-input majority gate is `LUT[e8]`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Lut {
+    input_count: usize,
+    table: Vec<bool>,
+}
+
+impl Lut {
+    pub const PREFIX: &'static str = "LUT[";
+
+    pub fn new(input_count: usize, table: Vec<bool>) -> Result<Lut, BristolCircuitError> {
+        check_input_count(input_count)
+            .map_err(|message| BristolCircuitError::Inconsistency { message })?;
+
+        if table.len() != 1 << input_count {
+            return Err(BristolCircuitError::Inconsistency {
+                message: format!(
+                    "LUT with {} inputs needs a table of {} entries, got {}",
+                    input_count,
+                    1usize << input_count,
+                    table.len()
+                ),
+            });
+        }
+
+        Ok(Lut { input_count, table })
+    }
+
+    pub fn from_fn(
+        input_count: usize,
+        f: impl Fn(usize) -> bool,
+    ) -> Result<Lut, BristolCircuitError> {
+        check_input_count(input_count)
+            .map_err(|message| BristolCircuitError::Inconsistency { message })?;
+
+        Lut::new(input_count, (0..1 << input_count).map(f).collect())
+    }
+
+    /// Parses `op` as a LUT with `input_count` inputs, or returns `None` if it isn't a LUT op.
+    pub fn parse(op: &str, input_count: usize) -> Option<Result<Lut, BristolCircuitError>> {
+        if !is_lut_op(op) {
+            return None;
+        }
+
+        Some(
+            parse_table(op, input_count)
+                .map(|table| Lut { input_count, table })
+                .map_err(|message| BristolCircuitError::ParsingError { message }),
+        )
+    }
+
+    pub fn input_count(&self) -> usize {
+        self.input_count
+    }
+
+    pub fn table(&self) -> &[bool] {
+        &self.table
+    }
+
+    pub fn eval(&self, inputs: &[bool]) -> bool {
+        let index = inputs
+            .iter()
+            .enumerate()
+            .fold(0, |index, (i, &bit)| index | (usize::from(bit) << i));
+
+        self.table[index]
+    }
+
+    /// The op string encoding this table.
+    pub fn op(&self) -> String {
+        let digits = self
+            .table
+            .chunks(4)
+            .rev()
+            .map(|chunk| {
+                let digit = chunk
+                    .iter()
+                    .enumerate()
+                    .fold(0, |digit, (i, &bit)| digit | (u32::from(bit) << i));
+
+                char::from_digit(digit, 16).unwrap()
+            })
+            .collect::<String>();
+
+        format!("{}{}]", Lut::PREFIX, digits)
+    }
+}
+
+pub(crate) fn is_lut_op(op: &str) -> bool {
+    op.starts_with(Lut::PREFIX)
+}
+
+/// Checks a LUT gate's shape, returning a message describing the problem if it's invalid. Gates
+/// with other ops are accepted.
+pub(crate) fn validate_lut_gate(op: &str, inputs: usize, outputs: usize) -> Result<(), String> {
+    if !is_lut_op(op) {
+        return Ok(());
+    }
+
+    if outputs != 1 {
+        return Err(format!("LUT gate must have 1 output, got {}", outputs));
+    }
+
+    parse_table(op, inputs).map(|_| ())
+}
+
+fn check_input_count(input_count: usize) -> Result<(), String> {
+    if input_count == 0 || input_count > MAX_LUT_INPUTS {
+        return Err(format!(
+            "LUT gates need between 1 and {} inputs, got {}",
+            MAX_LUT_INPUTS, input_count
+        ));
+    }
+
+    Ok(())
+}
+
+fn parse_table(op: &str, input_count: usize) -> Result<Vec<bool>, String> {
+    check_input_count(input_count)?;
+
+    let hex = op
+        .strip_prefix(Lut::PREFIX)
+        .and_then(|rest| rest.strip_suffix(']'))
+        .ok_or_else(|| format!("Malformed LUT op \"{}\"", op))?;
+
+    let entries = 1usize << input_count;
+    let expected_digits = entries.div_ceil(4);
+
+    if hex.len() != expected_digits {
+        return Err(format!(
+            "LUT op \"{}\" has {} hex digits but {} inputs need {}",
+            op,
+            hex.len(),
+            input_count,
+            expected_digits
+        ));
+    }
+
+    let mut table = Vec::with_capacity(expected_digits * 4);
+
+    for c in hex.chars().rev() {
+        let digit = c
+            .to_digit(16)
+            .ok_or_else(|| format!("Invalid hex digit '{}' in LUT op \"{}\"", c, op))?;
+
+        table.extend((0..4).map(|i| digit & (1 << i) != 0));
+    }
+
+    if table[entries..].iter().any(|&bit| bit) {
+        return Err(format!(
+            "LUT op \"{}\" sets entries beyond the {} a {}-input table has",
+            op, entries, input_count
+        ));
+    }
+
+    table.truncate(entries);
+
+    Ok(table)
+}
+
+impl BristolCircuit {
+    /// Replaces each LUT gate with an equivalent network of AND, XOR and INV gates, built by
+    /// Shannon expansion on the LUT's inputs.
+    pub fn lower_luts(&self) -> Result<BristolCircuit, BristolCircuitError> {
+        let _span = trace_span!("pass", name = "lower_luts", gate_count = self.gates.len());
+
+        let mut lowering = Lowering {
+            gates: Vec::with_capacity(self.gates.len()),
+            next_wire: self.wire_count.max(self.referenced_wire_count()),
+        };
+
+        for gate in &self.gates {
+            match gate.lut() {
+                Some(lut) => lowering.lower(&lut?, &gate.inputs, gate.outputs[0]),
+                None => lowering.gates.push(gate.clone()),
+            }
+        }
+
+        Ok(BristolCircuit {
+            wire_count: lowering.next_wire,
+            gates: lowering.gates,
+            ..self.clone()
+        })
+    }
+}
+
+#[derive(Clone, Copy)]
+enum Node {
+    Const(bool),
+    Wire(usize),
+}
+
+struct Lowering {
+    gates: Vec<Gate>,
+    next_wire: usize,
+}
+
+impl Lowering {
+    fn lower(&mut self, lut: &Lut, inputs: &[usize], output: usize) {
+        let start = self.gates.len();
+
+        let result = match self.expand(&lut.table, inputs) {
+            Node::Wire(wire) if self.gates.len() > start => wire,
+            Node::Wire(wire) => {
+                let inverted = self.emit("INV", &[wire]);
+                self.emit("INV", &[inverted])
+            }
+            Node::Const(value) => {
+                let zero = self.emit("XOR", &[inputs[0], inputs[0]]);
+
+                if value {
+                    self.emit("INV", &[zero])
+                } else {
+                    zero
+                }
+            }
+        };
+
+        // The last gate emitted computes the result: have it write the LUT's output wire directly.
+        let last = self.gates.last_mut().unwrap();
+        debug_assert_eq!(last.outputs[..], [result]);
+        last.outputs[0] = output;
+
+        if result + 1 == self.next_wire {
+            self.next_wire -= 1;
+        }
+    }
+
+    /// Builds `table` (indexed by `vars`, first var least significant) by splitting on the last
+    /// var: `f = f0 ^ (x & (f0 ^ f1))`, with shortcuts when either half is constant.
+    fn expand(&mut self, table: &[bool], vars: &[usize]) -> Node {
+        if table.iter().all(|&bit| bit == table[0]) {
+            return Node::Const(table[0]);
+        }
+
+        let (&x, rest) = vars.split_last().unwrap();
+        let (lo, hi) = table.split_at(table.len() / 2);
+
+        if lo == hi {
+            return self.expand(lo, rest);
+        }
+
+        let f0 = self.expand(lo, rest);
+        let f1 = self.expand(hi, rest);
+
+        Node::Wire(match (f0, f1) {
+            (Node::Const(_), Node::Const(true)) => x,
+            (Node::Const(_), Node::Const(false)) => self.emit("INV", &[x]),
+            (Node::Const(false), Node::Wire(w1)) => self.emit("AND", &[x, w1]),
+            (Node::Wire(w0), Node::Const(false)) => {
+                let not_x = self.emit("INV", &[x]);
+                self.emit("AND", &[not_x, w0])
+            }
+            (Node::Const(true), Node::Wire(w1)) => {
+                let not_w1 = self.emit("INV", &[w1]);
+                let both = self.emit("AND", &[x, not_w1]);
+                self.emit("INV", &[both])
+            }
+            (Node::Wire(w0), Node::Const(true)) => {
+                let not_x = self.emit("INV", &[x]);
+                let not_w0 = self.emit("INV", &[w0]);
+                let neither = self.emit("AND", &[not_x, not_w0]);
+                self.emit("INV", &[neither])
+            }
+            (Node::Wire(w0), Node::Wire(w1)) => {
+                let differ = self.emit("XOR", &[w0, w1]);
+                let select = self.emit("AND", &[x, differ]);
+                self.emit("XOR", &[w0, select])
+            }
+        })
+    }
+
+    fn emit(&mut self, op: &str, inputs: &[usize]) -> usize {
+        let output = self.next_wire;
+        self.next_wire += 1;
+
+        self.gates.push(Gate {
+            inputs: inputs.into(),
+            outputs: smallvec![output],
+            op: op.into(),
+        });
+
+        output
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+    use crate::circuit_builder::CircuitBuilder;
+
+    fn lut_circuit(op: &str, input_count: usize) -> BristolCircuit {
+        let mut b = CircuitBuilder::new();
+        let inputs = (0..input_count)
+            .map(|i| b.input(&format!("x{}", i)))
+            .collect::<Vec<_>>();
+        let out = b.gate(op, &inputs);
+        b.output("out", out);
+        b.build().unwrap()
+    }
+
+    fn eval(circuit: &BristolCircuit, bits: usize, input_count: usize) -> bool {
+        let inputs = (0..input_count)
+            .map(|i| (format!("x{}", i), vec![bits & (1 << i) != 0]))
+            .collect::<HashMap<_, _>>();
+
+        circuit.eval_boolean(&inputs).unwrap()["out"][0]
+    }
+
+    fn majority() -> Lut {
+        Lut::from_fn(3, |i| i.count_ones() >= 2).unwrap()
+    }
+
+    #[test]
+    fn test_op_round_trip() {
+        assert_eq!(majority().op(), "LUT[e8]");
+        assert_eq!(Lut::parse("LUT[e8]", 3).unwrap().unwrap(), majority());
+
+        let not = Lut::new(1, vec![true, false]).unwrap();
+        assert_eq!(not.op(), "LUT[1]");
+        assert_eq!(Lut::parse("LUT[1]", 1).unwrap().unwrap(), not);
+
+        let wide = Lut::from_fn(6, |i| i % 3 == 0).unwrap();
+        assert_eq!(Lut::parse(&wide.op(), 6).unwrap().unwrap(), wide);
+
+        assert!(Lut::parse("AND", 2).is_none());
+    }
+
+    #[test]
+    fn test_table_length_validation() {
+        for (op, inputs) in [
+            ("LUT[e8]", 2),
+            ("LUT[e8]", 4),
+            ("LUT[e]", 1),
+            ("LUT[5]", 1),
+            ("LUT[zz]", 3),
+            ("LUT[e8", 3),
+            ("LUT[1]", 0),
+        ] {
+            assert!(
+                Lut::parse(op, inputs).unwrap().is_err(),
+                "{} with {} inputs",
+                op,
+                inputs
+            );
+        }
+
+        assert!(Lut::new(2, vec![true; 3]).is_err());
+    }
+
+    #[test]
+    fn test_parse_rejects_bad_lut() {
+        let circuit = lut_circuit("LUT[e8]", 3);
+        let bristol = circuit.get_bristol_string().unwrap();
+        let parse = |op: &str| {
+            BristolCircuit::from_info_and_bristol_string(
+                &circuit.info,
+                &bristol.replace("LUT[e8]", op),
+            )
+        };
+
+        assert_eq!(parse("LUT[e8]").unwrap(), circuit);
+
+        let err = parse("LUT[e]").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Parsing error: LUT op \"LUT[e]\" has 1 hex digits but 3 inputs need 2 \
+             in line 5 \"3 1 0 1 2 3 LUT[e]\""
+        );
+
+        assert!(parse("LUT[e8e8]").is_err());
+    }
+
+    #[test]
+    fn test_majority_evaluates() {
+        let circuit = lut_circuit("LUT[e8]", 3);
+
+        for bits in 0..8 {
+            assert_eq!(
+                eval(&circuit, bits, 3),
+                bits.count_ones() >= 2,
+                "{:03b}",
+                bits
+            );
+        }
+    }
+
+    #[test]
+    fn test_majority_lowers_to_equivalent_network() {
+        let circuit = lut_circuit("LUT[e8]", 3);
+        let lowered = circuit.lower_luts().unwrap();
+
+        assert!(lowered
+            .gates
+            .iter()
+            .all(|gate| ["AND", "XOR", "INV"].contains(&gate.op.as_str())));
+        lowered.assert_wire_count_tight();
+
+        for bits in 0..8 {
+            assert_eq!(eval(&lowered, bits, 3), eval(&circuit, bits, 3));
+        }
+    }
+
+    #[test]
+    fn test_all_two_input_luts_lower_correctly() {
+        for table in 0..16usize {
+            let lut = Lut::from_fn(2, |i| table & (1 << i) != 0).unwrap();
+            let circuit = lut_circuit(&lut.op(), 2);
+            let lowered = circuit.lower_luts().unwrap();
+
+            for bits in 0..4 {
+                assert_eq!(
+                    eval(&lowered, bits, 2),
+                    lut.eval(&[bits & 1 != 0, bits & 2 != 0])
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,368 @@
+use std::{
+    collections::{HashMap, HashSet},
+    io::{self, Write},
+    mem::size_of,
+};
+
+use serde::Serialize;
+
+use crate::{
+    bristol_circuit::BristolCircuit,
+    circuit_info::{CircuitInfo, ConstantInfo},
+    gate::Gate,
+    wire_group::{WireGroup, WireSpan},
+};
+
+/// Sizes, in bytes, of a circuit in memory, during evaluation and when serialized.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct MemoryEstimate {
+    /// The `Gate` structs themselves, by the capacity of the gate list.
+    pub gates_bytes: usize,
+
+    /// Heap storage of the gates' input and output lists, which only gates with more than two
+    /// inputs or more than one output need.
+    pub gate_wires_bytes: usize,
+
+    /// Heap storage of the gates' op strings, counting each string that gates share once.
+    pub op_strings_bytes: usize,
+
+    /// The info maps, including their keys and constant values.
+    pub info_bytes: usize,
+
+    pub wire_count: usize,
+
+    /// The most wires holding values at once when each is freed after its last use, which is what
+    /// an evaluator reusing dead wires' slots needs.
+    pub peak_live_wires: usize,
+
+    /// The Bristol text from `write_bristol`.
+    pub bristol_text_bytes: usize,
+
+    /// The info document as JSON.
+    pub info_json_bytes: usize,
+
+    /// A compact binary encoding: varint counts and wire indices, with ops stored once in a
+    /// table and referenced by index.
+    pub binary_bytes: usize,
+
+    /// Whether this came from `estimate_from_header` rather than a parsed circuit.
+    pub from_header: bool,
+}
+
+impl MemoryEstimate {
+    /// The whole parsed representation.
+    pub fn parsed_bytes(&self) -> usize {
+        size_of::<BristolCircuit>()
+            + self.gates_bytes
+            + self.gate_wires_bytes
+            + self.op_strings_bytes
+            + self.info_bytes
+    }
+
+    /// What an evaluator storing one value per wire needs.
+    pub fn evaluator_bytes(&self, value_bytes: usize) -> usize {
+        self.wire_count * value_bytes
+    }
+
+    /// What an evaluator reusing dead wires' slots needs.
+    pub fn reduced_evaluator_bytes(&self, value_bytes: usize) -> usize {
+        self.peak_live_wires * value_bytes
+    }
+
+    /// A rough estimate for a circuit of 2-input, 1-output gates with short ops like `AAdd` or
+    /// `XOR`, from just the header's counts. Interface and info sizes are unknown and left out,
+    /// and every wire is assumed live at once.
+    pub fn estimate_from_header(gate_count: usize, wire_count: usize) -> MemoryEstimate {
+        const OP_BYTES: usize = 4;
+        const DISTINCT_OPS: usize = 4;
+
+        let index_digits = decimal_digits(wire_count.saturating_sub(1));
+
+        // "2 1 a b c OP\n"
+        let gate_line_bytes = 4 + 3 * (index_digits + 1) + OP_BYTES + 1;
+        let header_bytes = decimal_digits(gate_count) + decimal_digits(wire_count) + 3;
+
+        MemoryEstimate {
+            gates_bytes: gate_count * size_of::<Gate>(),
+            gate_wires_bytes: 0,
+            op_strings_bytes: DISTINCT_OPS * (2 * size_of::<usize>() + OP_BYTES),
+            info_bytes: 0,
+            wire_count,
+            peak_live_wires: wire_count,
+            bristol_text_bytes: header_bytes + gate_count * gate_line_bytes,
+            info_json_bytes: 0,
+            binary_bytes: gate_count * (3 + 3 * varint_len(wire_count.saturating_sub(1))),
+            from_header: true,
+        }
+    }
+}
+
+impl BristolCircuit {
+    /// Measures the circuit's footprint from the actual lengths and capacities of its parts.
+    pub fn estimate_memory(&self) -> MemoryEstimate {
+        let gate_wires_bytes = self
+            .gates
+            .iter()
+            .map(|gate| {
+                let spilled = |spilled: bool, capacity: usize| if spilled { capacity } else { 0 };
+
+                (spilled(gate.inputs.spilled(), gate.inputs.capacity())
+                    + spilled(gate.outputs.spilled(), gate.outputs.capacity()))
+                    * size_of::<usize>()
+            })
+            .sum();
+
+        let mut shared_ops = HashSet::new();
+        let op_strings_bytes = self
+            .gates
+            .iter()
+            .filter(|gate| shared_ops.insert(gate.op.shared_ptr()))
+            .map(|gate| gate.op.heap_bytes())
+            .sum();
+
+        let mut bristol_text = ByteCounter(0);
+        let bristol_text_bytes = match self.write_bristol(&mut bristol_text) {
+            Ok(()) => bristol_text.0,
+            Err(_) => 0,
+        };
+
+        let mut info_json = ByteCounter(0);
+        let info_json_bytes = match serde_json::to_writer(&mut info_json, &self.info) {
+            Ok(()) => info_json.0,
+            Err(_) => 0,
+        };
+
+        MemoryEstimate {
+            gates_bytes: self.gates.capacity() * size_of::<Gate>(),
+            gate_wires_bytes,
+            op_strings_bytes,
+            info_bytes: info_bytes(&self.info),
+            wire_count: self.wire_count,
+            peak_live_wires: self.peak_live_wires(),
+            bristol_text_bytes,
+            info_json_bytes,
+            binary_bytes: self.binary_bytes() + info_json_bytes,
+            from_header: false,
+        }
+    }
+
+    fn peak_live_wires(&self) -> usize {
+        let mut last_use = HashMap::<usize, usize>::new();
+
+        for (i, gate) in self.gates.iter().enumerate() {
+            for &wire in &gate.inputs {
+                last_use.insert(wire, i);
+            }
+        }
+
+        let outputs = self
+            .output_buses()
+            .into_iter()
+            .flat_map(|bus| bus.wire..bus.wire + bus.width)
+            .chain(
+                self.info
+                    .wire_groups
+                    .values()
+                    .flat_map(|group| group.span.wires()),
+            )
+            .collect::<HashSet<_>>();
+
+        let mut freed_after = vec![0usize; self.gates.len()];
+        for (&wire, &i) in &last_use {
+            if !outputs.contains(&wire) {
+                freed_after[i] += 1;
+            }
+        }
+
+        let mut live = self
+            .input_buses()
+            .iter()
+            .map(|bus| bus.width)
+            .sum::<usize>()
+            + self.info.constants.len();
+        let mut peak = live;
+
+        for (gate, freed) in self.gates.iter().zip(freed_after) {
+            live += gate.outputs.len();
+            peak = peak.max(live);
+            live = live.saturating_sub(freed);
+        }
+
+        peak
+    }
+
+    fn binary_bytes(&self) -> usize {
+        let mut op_ids = HashMap::<&str, usize>::new();
+        let mut table_bytes = 0;
+
+        for gate in &self.gates {
+            let next = op_ids.len();
+            op_ids.entry(&gate.op).or_insert_with(|| {
+                table_bytes += varint_len(gate.op.len()) + gate.op.len();
+                next
+            });
+        }
+
+        let gate_bytes = self
+            .gates
+            .iter()
+            .map(|gate| {
+                varint_len(gate.inputs.len())
+                    + varint_len(gate.outputs.len())
+                    + gate
+                        .inputs
+                        .iter()
+                        .chain(&gate.outputs)
+                        .map(|&wire| varint_len(wire))
+                        .sum::<usize>()
+                    + varint_len(op_ids[gate.op.as_str()])
+            })
+            .sum::<usize>();
+
+        let header_bytes = varint_len(self.gates.len()) + varint_len(self.wire_count);
+
+        header_bytes + varint_len(op_ids.len()) + table_bytes + gate_bytes
+    }
+}
+
+fn info_bytes(info: &CircuitInfo) -> usize {
+    let names = |map: &HashMap<String, usize>| {
+        map.capacity() * map_entry_bytes::<String, usize>()
+            + map.keys().map(String::capacity).sum::<usize>()
+    };
+
+    let constants = info.constants.capacity() * map_entry_bytes::<String, ConstantInfo>()
+        + info
+            .constants
+            .iter()
+            .map(|(name, constant)| name.capacity() + constant.value.capacity())
+            .sum::<usize>();
+
+    let groups = info.wire_groups.capacity() * map_entry_bytes::<String, WireGroup>()
+        + info
+            .wire_groups
+            .iter()
+            .map(|(name, group)| {
+                name.capacity()
+                    + match &group.span {
+                        WireSpan::Range { .. } => 0,
+                        WireSpan::Wires { wires } => wires.capacity() * size_of::<usize>(),
+                    }
+            })
+            .sum::<usize>();
+
+    names(&info.input_name_to_wire_index)
+        + names(&info.output_name_to_wire_index)
+        + constants
+        + groups
+}
+
+/// A hash map slot: the key, the value and a control byte.
+fn map_entry_bytes<K, V>() -> usize {
+    size_of::<(K, V)>() + 1
+}
+
+pub(crate) fn decimal_digits(n: usize) -> usize {
+    n.checked_ilog10().map_or(1, |log| log as usize + 1)
+}
+
+/// LEB128 length: 7 bits per byte.
+fn varint_len(n: usize) -> usize {
+    n.checked_ilog2().map_or(1, |log| log as usize / 7 + 1)
+}
+
+struct ByteCounter(usize);
+
+impl Write for ByteCounter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0 += buf.len();
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gadgets;
+
+    #[test]
+    fn test_matches_measured_sizes() {
+        let circuit = gadgets::adder(16, true);
+        let estimate = circuit.estimate_memory();
+
+        assert_eq!(
+            estimate.gates_bytes,
+            circuit.gates.capacity() * size_of::<Gate>()
+        );
+
+        // Two inputs and one output fit inline.
+        assert_eq!(estimate.gate_wires_bytes, 0);
+
+        let mut wide = circuit.clone();
+        wide.gates[0].inputs.extend([0, 1]);
+        assert_eq!(
+            wide.estimate_memory().gate_wires_bytes,
+            wide.gates[0].inputs.capacity() * size_of::<usize>()
+        );
+
+        assert_eq!(
+            estimate.bristol_text_bytes,
+            circuit.get_bristol_string().unwrap().len()
+        );
+        assert_eq!(
+            estimate.info_json_bytes,
+            serde_json::to_string(&circuit.info).unwrap().len()
+        );
+
+        assert!(estimate.binary_bytes < estimate.bristol_text_bytes + estimate.info_json_bytes);
+        assert!(estimate.parsed_bytes() > estimate.gates_bytes + estimate.gate_wires_bytes);
+        assert_eq!(estimate.evaluator_bytes(8), circuit.wire_count * 8);
+    }
+
+    #[test]
+    fn test_peak_live_wires() {
+        // Input bits are freed as the carry ripples past them, so the peak is the seeded inputs
+        // plus a few temporaries.
+        let circuit = gadgets::adder(16, true);
+        let estimate = circuit.estimate_memory();
+
+        assert!(estimate.peak_live_wires < circuit.wire_count);
+        assert!(estimate.peak_live_wires >= 32);
+        assert!(estimate.reduced_evaluator_bytes(1) < estimate.evaluator_bytes(1));
+    }
+
+    #[test]
+    fn test_estimate_from_header() {
+        let circuit = gadgets::adder(64, true);
+        let measured = circuit.estimate_memory();
+        let estimate =
+            MemoryEstimate::estimate_from_header(circuit.gates.len(), circuit.wire_count);
+
+        assert!(estimate.from_header);
+        // The builder's gate list has spare capacity.
+        assert!(estimate.gates_bytes <= measured.gates_bytes);
+        assert_eq!(estimate.wire_count, circuit.wire_count);
+
+        let ratio = estimate.bristol_text_bytes as f64 / measured.bristol_text_bytes as f64;
+        assert!((0.8..1.25).contains(&ratio), "text ratio {}", ratio);
+
+        let ratio = estimate.binary_bytes as f64
+            / (measured.binary_bytes - measured.info_json_bytes) as f64;
+        assert!((0.8..1.25).contains(&ratio), "binary ratio {}", ratio);
+    }
+
+    #[test]
+    fn test_digit_counts() {
+        assert_eq!(decimal_digits(0), 1);
+        assert_eq!(decimal_digits(9), 1);
+        assert_eq!(decimal_digits(10), 2);
+        assert_eq!(varint_len(0), 1);
+        assert_eq!(varint_len(127), 1);
+        assert_eq!(varint_len(128), 2);
+        assert_eq!(varint_len(16_384), 3);
+    }
+}
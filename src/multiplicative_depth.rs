@@ -0,0 +1,105 @@
+use std::collections::HashMap;
+
+use crate::{bristol_circuit::BristolCircuit, bristol_circuit_error::BristolCircuitError};
+
+/// The ops [`BristolCircuit::arithmetic_multiplicative_depth`] counts.
+pub const MULTIPLICATIVE_OPS: &[&str] = &["AMul", "ADiv", "APow"];
+
+impl BristolCircuit {
+    /// The longest chain of gates, counting only gates whose op is in `expensive_ops`; other
+    /// gates pass on the deepest of their inputs. The gates must be topologically sorted, as
+    /// [`BristolCircuit::check_topological_order`] checks.
+    pub fn multiplicative_depth(
+        &self,
+        expensive_ops: &[&str],
+    ) -> Result<usize, BristolCircuitError> {
+        self.check_topological_order()?;
+
+        let mut wire_depths = HashMap::<usize, usize>::new();
+        let mut max_depth = 0;
+
+        for gate in &self.gates {
+            let input_depth = gate
+                .input_wires()
+                .iter()
+                .filter_map(|wire| wire_depths.get(wire))
+                .max()
+                .copied()
+                .unwrap_or(0);
+
+            let depth = input_depth + usize::from(expensive_ops.contains(&gate.op.as_str()));
+            max_depth = max_depth.max(depth);
+
+            for &wire in &gate.outputs {
+                wire_depths.insert(wire, depth);
+            }
+        }
+
+        Ok(max_depth)
+    }
+
+    /// [`BristolCircuit::multiplicative_depth`] counting [`MULTIPLICATIVE_OPS`].
+    pub fn arithmetic_multiplicative_depth(&self) -> Result<usize, BristolCircuitError> {
+        self.multiplicative_depth(MULTIPLICATIVE_OPS)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::circuit_builder::CircuitBuilder;
+
+    /// `x*x` and `x+x` both read `x` and are joined by a multiplication, then added to `x*x`.
+    fn diamond() -> BristolCircuit {
+        let mut b = CircuitBuilder::new();
+        let x = b.input("x");
+        let square = b.gate("AMul", &[x, x]);
+        let double = b.gate("AAdd", &[x, x]);
+        let product = b.gate("AMul", &[square, double]);
+        let sum = b.gate("AAdd", &[product, square]);
+        b.output("y", sum);
+        b.build().unwrap()
+    }
+
+    #[test]
+    fn test_diamond() {
+        let circuit = diamond();
+
+        assert_eq!(circuit.arithmetic_multiplicative_depth().unwrap(), 2);
+        assert_eq!(circuit.multiplicative_depth(&["AAdd"]).unwrap(), 2);
+        assert_eq!(circuit.multiplicative_depth(&["AMul", "AAdd"]).unwrap(), 3);
+        assert_eq!(circuit.multiplicative_depth(&[]).unwrap(), 0);
+    }
+
+    #[test]
+    fn test_div_pow_and_free_chains() {
+        let mut b = CircuitBuilder::new();
+        let x = b.input("x");
+        let y = b.input("y");
+        let two = b.constant("two", "2");
+
+        // A long chain of additions is free.
+        let mut sum = x;
+        for _ in 0..10 {
+            sum = b.gate("AAdd", &[sum, y]);
+        }
+
+        let power = b.gate("APow", &[sum, two]);
+        let quotient = b.gate("ADiv", &[power, y]);
+        let difference = b.gate("ASub", &[quotient, x]);
+        b.output("z", difference);
+        b.output("s", sum);
+        let circuit = b.build().unwrap();
+
+        assert_eq!(circuit.arithmetic_multiplicative_depth().unwrap(), 2);
+    }
+
+    #[test]
+    fn test_wrong_order() {
+        let mut circuit = diamond();
+        circuit.gates.swap(0, 2);
+
+        let err = circuit.arithmetic_multiplicative_depth().unwrap_err();
+        assert!(err.to_string().contains("before it is assigned"), "{}", err);
+    }
+}
@@ -0,0 +1,198 @@
+use smallvec::smallvec;
+
+use crate::{
+    bristol_circuit::BristolCircuit,
+    bristol_circuit_error::BristolCircuitError,
+    gate::{validate_gate_shape, Gate},
+    trace::trace_span,
+};
+
+impl BristolCircuit {
+    /// Rewrites each `MUX sel a b` gate as `a ^ (sel & (a ^ b))` and each `AMux sel a b` gate as
+    /// `b + sel * (a - b)`, using fresh wires for the intermediate values.
+    ///
+    /// The two select opposite operands: `MUX` gives `b` when `sel` is set, as
+    /// [`CircuitBuilder::mux`](crate::CircuitBuilder::mux) does, while `AMux` gives `a` when `sel`
+    /// is 1. Fails on a `MUX` or `AMux` gate without 3 inputs and 1 output.
+    pub fn lower_mux(&self) -> Result<BristolCircuit, BristolCircuitError> {
+        let _span = trace_span!("pass", name = "lower_mux", gate_count = self.gates.len());
+
+        let mut next_wire = self.wire_count.max(self.referenced_wire_count());
+        let mut gates = Vec::with_capacity(self.gates.len());
+
+        for (i, gate) in self.gates.iter().enumerate() {
+            let ops = match gate.op.as_str() {
+                "MUX" => ["XOR", "AND", "XOR"],
+                "AMux" => ["ASub", "AMul", "AAdd"],
+                _ => {
+                    gates.push(gate.clone());
+                    continue;
+                }
+            };
+
+            validate_gate_shape(&gate.op, gate.inputs.len(), gate.outputs.len()).map_err(
+                |message| BristolCircuitError::Inconsistency {
+                    message: format!("Gate {}: {}", i, message),
+                },
+            )?;
+            let [sel, a, b] = gate.inputs[..] else {
+                unreachable!("checked above")
+            };
+
+            let difference = next_wire;
+            let scaled = next_wire + 1;
+            next_wire += 2;
+
+            let (base, other) = if gate.op == "MUX" { (a, b) } else { (b, a) };
+
+            gates.push(binary_gate(ops[0], other, base, difference));
+            gates.push(binary_gate(ops[1], sel, difference, scaled));
+            gates.push(binary_gate(ops[2], base, scaled, gate.outputs[0]));
+        }
+
+        Ok(BristolCircuit {
+            wire_count: next_wire,
+            gates,
+            ..self.clone()
+        })
+    }
+}
+
+fn binary_gate(op: &str, left: usize, right: usize, output: usize) -> Gate {
+    Gate {
+        inputs: smallvec![left, right],
+        outputs: smallvec![output],
+        op: op.into(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+    use crate::{circuit_builder::CircuitBuilder, eval::test_utils::eval_wrapping};
+
+    fn mux_circuit(op: &str) -> BristolCircuit {
+        let mut b = CircuitBuilder::new();
+        let sel = b.input("sel");
+        let x = b.input("a");
+        let y = b.input("b");
+        let out = b.gate(op, &[sel, x, y]);
+        b.output("out", out);
+        b.build().unwrap()
+    }
+
+    #[test]
+    fn test_boolean_mux() {
+        let mut b = CircuitBuilder::new();
+        let sel = b.input("sel");
+        let x = b.input("a");
+        let y = b.input("b");
+        let out = b.mux(sel, x, y);
+        b.output("out", out);
+        let circuit = b.build().unwrap();
+        assert_eq!(circuit, mux_circuit("MUX"));
+
+        let lowered = circuit.lower_mux().unwrap();
+        assert!(lowered
+            .gates
+            .iter()
+            .all(|gate| gate.op == "XOR" || gate.op == "AND"));
+        lowered.assert_wire_count_tight();
+
+        for bits in 0..8 {
+            let [sel, a, b] = [bits & 1 != 0, bits & 2 != 0, bits & 4 != 0];
+            let inputs = HashMap::from([
+                ("sel".to_string(), vec![sel]),
+                ("a".to_string(), vec![a]),
+                ("b".to_string(), vec![b]),
+            ]);
+
+            let expected = if sel { b } else { a };
+            assert_eq!(
+                circuit.eval_boolean(&inputs).unwrap()["out"],
+                vec![expected]
+            );
+            assert_eq!(
+                lowered.eval_boolean(&inputs).unwrap()["out"],
+                vec![expected]
+            );
+        }
+    }
+
+    fn eval_arithmetic(circuit: &BristolCircuit, sel: u64, a: u64, b: u64) -> u64 {
+        eval_wrapping(circuit, &[("sel", sel), ("a", a), ("b", b)])["out"]
+    }
+
+    #[test]
+    fn test_arithmetic_mux() {
+        let circuit = mux_circuit("AMux");
+        let lowered = circuit.lower_mux().unwrap();
+
+        assert_eq!(
+            lowered
+                .gates
+                .iter()
+                .map(|gate| gate.op.as_str())
+                .collect::<Vec<_>>(),
+            vec!["ASub", "AMul", "AAdd"]
+        );
+
+        for sel in [0, 1] {
+            for (a, b) in [(7, 3), (3, 7), (0, u64::MAX)] {
+                let expected = if sel == 1 { a } else { b };
+                assert_eq!(eval_arithmetic(&circuit, sel, a, b), expected);
+                assert_eq!(eval_arithmetic(&lowered, sel, a, b), expected);
+            }
+        }
+    }
+
+    #[test]
+    fn test_other_gates_untouched() {
+        let mut circuit = mux_circuit("MUX");
+        circuit.gates[0].op = "XOR".into();
+        circuit.gates[0].inputs.pop();
+
+        assert_eq!(circuit.lower_mux().unwrap(), circuit);
+    }
+
+    #[test]
+    fn test_mux_and_amux_select_opposite_operands() {
+        let boolean = mux_circuit("MUX");
+        let inputs = HashMap::from([
+            ("sel".to_string(), vec![true]),
+            ("a".to_string(), vec![false]),
+            ("b".to_string(), vec![true]),
+        ]);
+        assert_eq!(boolean.eval_boolean(&inputs).unwrap()["out"], vec![true]);
+
+        let arithmetic = mux_circuit("AMux");
+        assert_eq!(eval_arithmetic(&arithmetic, 1, 0, 1), 0);
+    }
+
+    #[test]
+    fn test_bad_shapes() {
+        let text = "1 4\n3 1 1 1\n1 1\n\n3 0 0 1 2 MUX\n";
+        let err = BristolCircuit::from_bristol_string(text).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Parsing error: MUX gate needs 3 inputs and 1 output, got 3 inputs and 0 outputs in \
+             line 5 \"3 0 0 1 2 MUX\""
+        );
+
+        for (op, inputs, outputs) in [("MUX", 2, 1), ("AMux", 3, 0), ("AMux", 4, 1)] {
+            let mut circuit = mux_circuit(op);
+            circuit.gates[0].inputs.resize(inputs, 0);
+            circuit.gates[0].outputs.resize(outputs, 3);
+
+            let message = format!(
+                "Inconsistency: Gate 0: {} gate needs 3 inputs and 1 output, got {} inputs and {} \
+                 outputs",
+                op, inputs, outputs
+            );
+            assert_eq!(circuit.validate().unwrap_err().to_string(), message);
+            assert_eq!(circuit.lower_mux().unwrap_err().to_string(), message);
+        }
+    }
+}
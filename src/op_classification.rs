@@ -0,0 +1,233 @@
+use std::{ops::ControlFlow, str::FromStr};
+
+use serde::Serialize;
+
+use crate::{
+    a_gate_type::AGateType,
+    bool_gate_type::BoolGateType,
+    bristol_circuit::BristolCircuit,
+    bristol_circuit_error::BristolCircuitError,
+    gate::Gate,
+    lut::is_lut_op,
+    visit::{walk, CircuitVisitor},
+};
+
+/// How many gate indices are kept as examples per bucket.
+const MAX_EXAMPLES: usize = 5;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+pub enum OpKind {
+    Arithmetic,
+    Boolean,
+    Unknown,
+}
+
+impl OpKind {
+    pub fn of(op: &str) -> OpKind {
+        if AGateType::from_str(op).is_ok() {
+            OpKind::Arithmetic
+        } else if BoolGateType::from_str(op).is_ok() || is_lut_op(op) {
+            OpKind::Boolean
+        } else {
+            OpKind::Unknown
+        }
+    }
+
+    fn describe(&self) -> &'static str {
+        match self {
+            OpKind::Arithmetic => "arithmetic",
+            OpKind::Boolean => "boolean",
+            OpKind::Unknown => "unknown",
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize)]
+pub struct OpBucket {
+    pub count: usize,
+
+    /// Indices of the first few gates in this bucket.
+    pub examples: Vec<usize>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize)]
+pub struct OpClassification {
+    pub arithmetic: OpBucket,
+    pub boolean: OpBucket,
+    pub unknown: OpBucket,
+}
+
+impl OpClassification {
+    pub fn bucket(&self, kind: OpKind) -> &OpBucket {
+        match kind {
+            OpKind::Arithmetic => &self.arithmetic,
+            OpKind::Boolean => &self.boolean,
+            OpKind::Unknown => &self.unknown,
+        }
+    }
+
+    pub fn is_pure_arithmetic(&self) -> bool {
+        self.boolean.count == 0 && self.unknown.count == 0
+    }
+
+    pub fn is_pure_boolean(&self) -> bool {
+        self.arithmetic.count == 0 && self.unknown.count == 0
+    }
+
+    pub fn is_mixed(&self) -> bool {
+        self.arithmetic.count > 0 && self.boolean.count > 0
+    }
+
+    /// Fails with a message naming the offending gates unless every gate is of `kind`.
+    pub fn require(
+        &self,
+        kind: OpKind,
+        circuit: &BristolCircuit,
+    ) -> Result<(), BristolCircuitError> {
+        let offending = [OpKind::Arithmetic, OpKind::Boolean, OpKind::Unknown]
+            .into_iter()
+            .filter(|other| *other != kind && self.bucket(*other).count > 0)
+            .map(|other| {
+                let bucket = self.bucket(other);
+                let examples = bucket
+                    .examples
+                    .iter()
+                    .map(|&i| format!("gate {} ({})", i, circuit.gates[i].op))
+                    .collect::<Vec<_>>()
+                    .join(", ");
+
+                format!(
+                    "{} {} gates, e.g. {}",
+                    bucket.count,
+                    other.describe(),
+                    examples
+                )
+            })
+            .collect::<Vec<_>>();
+
+        if offending.is_empty() {
+            return Ok(());
+        }
+
+        Err(BristolCircuitError::Inconsistency {
+            message: format!(
+                "Expected a pure {} circuit but found {}",
+                kind.describe(),
+                offending.join("; ")
+            ),
+        })
+    }
+}
+
+impl BristolCircuit {
+    pub fn classify_ops(&self) -> OpClassification {
+        let mut classifier = Classifier(OpClassification::default());
+        let _ = walk(self, &mut classifier);
+
+        classifier.0
+    }
+}
+
+struct Classifier(OpClassification);
+
+impl CircuitVisitor for Classifier {
+    fn visit_gate(&mut self, index: usize, gate: &Gate) -> ControlFlow<()> {
+        let classification = &mut self.0;
+
+        let bucket = match OpKind::of(&gate.op) {
+            OpKind::Arithmetic => &mut classification.arithmetic,
+            OpKind::Boolean => &mut classification.boolean,
+            OpKind::Unknown => &mut classification.unknown,
+        };
+
+        bucket.count += 1;
+
+        if bucket.examples.len() < MAX_EXAMPLES {
+            bucket.examples.push(index);
+        }
+
+        ControlFlow::Continue(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use smallvec::smallvec;
+
+    use super::*;
+
+    fn circuit_with_ops(ops: &[&str]) -> BristolCircuit {
+        let mut circuit = BristolCircuit {
+            wire_count: 0,
+            info: Default::default(),
+            io_widths: (vec![], vec![]),
+            gates: ops
+                .iter()
+                .enumerate()
+                .map(|(i, op)| Gate {
+                    inputs: smallvec![0, 1],
+                    outputs: smallvec![i + 2],
+                    op: (*op).into(),
+                })
+                .collect(),
+            header_style: Default::default(),
+        };
+        circuit.recompute_wire_count();
+        circuit
+    }
+
+    #[test]
+    fn test_mixed_circuit() {
+        let circuit = circuit_with_ops(&["AAdd", "XOR", "AMul", "FOO", "AND"]);
+        let classification = circuit.classify_ops();
+
+        assert_eq!(
+            classification,
+            OpClassification {
+                arithmetic: OpBucket {
+                    count: 2,
+                    examples: vec![0, 2]
+                },
+                boolean: OpBucket {
+                    count: 2,
+                    examples: vec![1, 4]
+                },
+                unknown: OpBucket {
+                    count: 1,
+                    examples: vec![3]
+                },
+            }
+        );
+
+        assert!(classification.is_mixed());
+        assert!(!classification.is_pure_arithmetic());
+        assert!(!classification.is_pure_boolean());
+
+        let err = classification
+            .require(OpKind::Arithmetic, &circuit)
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Inconsistency: Expected a pure arithmetic circuit but found 2 boolean gates, \
+             e.g. gate 1 (XOR), gate 4 (AND); 1 unknown gates, e.g. gate 3 (FOO)"
+        );
+    }
+
+    #[test]
+    fn test_pure_circuits() {
+        let arithmetic = circuit_with_ops(&["AAdd", "AMul"]).classify_ops();
+        assert!(arithmetic.is_pure_arithmetic());
+        assert!(!arithmetic.is_pure_boolean());
+
+        let boolean = circuit_with_ops(&["AND", "XOR", "INV"]).classify_ops();
+        assert!(boolean.is_pure_boolean());
+        assert!(!boolean.is_mixed());
+    }
+
+    #[test]
+    fn test_examples_are_capped() {
+        let classification = circuit_with_ops(&["XOR"; 20]).classify_ops();
+        assert_eq!(classification.boolean.count, 20);
+        assert_eq!(classification.boolean.examples, vec![0, 1, 2, 3, 4]);
+    }
+}
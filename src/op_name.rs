@@ -0,0 +1,162 @@
+use std::{
+    borrow::Borrow,
+    collections::HashSet,
+    fmt::{self, Debug, Display, Formatter},
+    ops::Deref,
+    sync::Arc,
+};
+
+use serde::{Deserialize, Serialize};
+
+/// A gate's op. Circuits have millions of gates but only a handful of distinct ops, so gates
+/// share their op's text instead of each owning a copy: cloning is a reference count increment,
+/// and parsing allocates each distinct op once. It reads as a `str` and serializes as a string.
+#[derive(Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct OpName(Arc<str>);
+
+impl OpName {
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// Whether both refer to the same shared text, as ops interned together do.
+    pub fn ptr_eq(&self, other: &OpName) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+
+    /// Identifies the shared text, so it can be counted once however many gates use it.
+    pub(crate) fn shared_ptr(&self) -> *const u8 {
+        self.0.as_ptr()
+    }
+
+    /// The heap storage of the shared text, including its reference counts.
+    pub(crate) fn heap_bytes(&self) -> usize {
+        2 * std::mem::size_of::<usize>() + self.0.len()
+    }
+}
+
+impl Deref for OpName {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl AsRef<str> for OpName {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl Borrow<str> for OpName {
+    fn borrow(&self) -> &str {
+        &self.0
+    }
+}
+
+impl Display for OpName {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl Debug for OpName {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        Debug::fmt(&*self.0, f)
+    }
+}
+
+impl From<&str> for OpName {
+    fn from(op: &str) -> Self {
+        OpName(op.into())
+    }
+}
+
+impl From<String> for OpName {
+    fn from(op: String) -> Self {
+        OpName(op.into())
+    }
+}
+
+impl From<&String> for OpName {
+    fn from(op: &String) -> Self {
+        OpName(op.as_str().into())
+    }
+}
+
+impl From<OpName> for String {
+    fn from(op: OpName) -> Self {
+        op.0.to_string()
+    }
+}
+
+impl PartialEq<str> for OpName {
+    fn eq(&self, other: &str) -> bool {
+        &*self.0 == other
+    }
+}
+
+impl PartialEq<&str> for OpName {
+    fn eq(&self, other: &&str) -> bool {
+        &*self.0 == *other
+    }
+}
+
+impl PartialEq<String> for OpName {
+    fn eq(&self, other: &String) -> bool {
+        *self.0 == **other
+    }
+}
+
+impl PartialEq<OpName> for &str {
+    fn eq(&self, other: &OpName) -> bool {
+        *self == &*other.0
+    }
+}
+
+/// Hands out one shared [`OpName`] per distinct op.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct OpInterner(HashSet<OpName>);
+
+impl OpInterner {
+    pub fn intern(&mut self, op: &str) -> OpName {
+        if let Some(interned) = self.0.get(op) {
+            return interned.clone();
+        }
+
+        let interned = OpName::from(op);
+        self.0.insert(interned.clone());
+
+        interned
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_intern() {
+        let mut interner = OpInterner::default();
+        let and = interner.intern("AND");
+
+        assert!(interner.intern("AND").ptr_eq(&and));
+        assert!(!interner.intern("XOR").ptr_eq(&and));
+        assert!(!OpName::from("AND").ptr_eq(&and));
+        assert_eq!(OpName::from("AND"), and);
+    }
+
+    #[test]
+    fn test_reads_as_a_string() {
+        let op = OpName::from("AAdd");
+
+        assert_eq!(op, "AAdd");
+        assert_eq!("AAdd", op);
+        assert_eq!(format!("{} {:?}", op, op), "AAdd \"AAdd\"");
+        assert_eq!(serde_json::to_string(&op).unwrap(), "\"AAdd\"");
+        assert_eq!(serde_json::from_str::<OpName>("\"AAdd\"").unwrap(), op);
+        assert!(op.starts_with('A'));
+    }
+}
@@ -0,0 +1,30 @@
+/// Ops whose result doesn't depend on the order of their inputs.
+const COMMUTATIVE_OPS: &[&str] = &[
+    "AAdd", "AMul", "AEq", "ANeq", "AXor", "ABoolOr", "ABoolAnd", "ABitOr", "ABitAnd", "AND",
+    "XOR", "OR", "NAND", "NOR", "XNOR",
+];
+
+pub fn is_commutative(op: &str) -> bool {
+    COMMUTATIVE_OPS.contains(&op)
+}
+
+/// Maps alternative spellings of the same op to one name, for comparing gates.
+pub fn canonical_op(op: &str) -> &str {
+    match op {
+        "NOT" => "INV",
+        _ => op,
+    }
+}
+
+/// A key identifying gates that compute the same value: the canonical op and its inputs, sorted
+/// when the op is commutative.
+pub fn normalized_gate_key<T: Ord + Clone>(op: &str, inputs: &[T]) -> (String, Vec<T>) {
+    let op = canonical_op(op);
+    let mut inputs = inputs.to_vec();
+
+    if is_commutative(op) {
+        inputs.sort();
+    }
+
+    (op.to_string(), inputs)
+}
@@ -0,0 +1,239 @@
+use std::{
+    collections::HashMap,
+    fmt::{self, Display, Formatter},
+};
+
+use strum::IntoEnumIterator;
+
+use crate::{
+    a_gate_type::AGateType,
+    bool_gate_type::{Arity, BoolGateType},
+    bristol_circuit::BristolCircuit,
+    gate::Gate,
+    lut::is_lut_op,
+};
+
+/// The input and output counts each op expects, for checking gates beyond what the format
+/// itself requires. LUT ops are always accepted here, since their shape is checked at parse time.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct OpRegistry {
+    arities: HashMap<String, Arity>,
+
+    /// What to do with gates whose op isn't registered.
+    pub unknown_ops: UnknownOpPolicy,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum UnknownOpPolicy {
+    /// Accept gates with unregistered ops, whatever their shape.
+    #[default]
+    Allow,
+
+    /// Report gates with unregistered ops.
+    Reject,
+}
+
+/// A gate that doesn't match its op's registered arity, or whose op isn't registered.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct OpMismatch {
+    pub gate_index: usize,
+    pub op: String,
+    pub inputs: usize,
+    pub outputs: usize,
+
+    /// The registered arity, or `None` if the op is unknown.
+    pub expected: Option<Arity>,
+}
+
+impl OpRegistry {
+    /// A registry with no ops, to be filled with `register`.
+    pub fn empty() -> Self {
+        OpRegistry {
+            arities: HashMap::new(),
+            unknown_ops: UnknownOpPolicy::default(),
+        }
+    }
+
+    /// Adds `op`, replacing any arity it already had.
+    pub fn register(&mut self, op: impl Into<String>, arity: Arity) -> &mut Self {
+        self.arities.insert(op.into(), arity);
+        self
+    }
+
+    pub fn arity(&self, op: &str) -> Option<Arity> {
+        self.arities.get(op).copied()
+    }
+
+    pub(crate) fn check(&self, gate_index: usize, gate: &Gate) -> Result<(), OpMismatch> {
+        let (inputs, outputs) = (gate.inputs.len(), gate.outputs.len());
+
+        let accepted = match self.arity(&gate.op) {
+            Some(arity) => arity.accepts(inputs, outputs),
+            None => is_lut_op(&gate.op) || self.unknown_ops == UnknownOpPolicy::Allow,
+        };
+
+        if accepted {
+            return Ok(());
+        }
+
+        Err(OpMismatch {
+            gate_index,
+            op: gate.op.to_string(),
+            inputs,
+            outputs,
+            expected: self.arity(&gate.op),
+        })
+    }
+}
+
+/// The arithmetic ops, binary apart from the 3-input `AMux`, and every boolean op.
+impl Default for OpRegistry {
+    fn default() -> Self {
+        let mut registry = OpRegistry::empty();
+
+        for op in AGateType::iter() {
+            let inputs = if op == AGateType::AMux { 3 } else { 2 };
+            registry.register(op.to_string(), Arity::Fixed { inputs, outputs: 1 });
+        }
+
+        for op in BoolGateType::iter() {
+            registry.register(op.to_string(), op.arity());
+        }
+
+        registry
+    }
+}
+
+impl Display for OpMismatch {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(
+            f,
+            "Gate {} ({}) has {} inputs and {} outputs",
+            self.gate_index, self.op, self.inputs, self.outputs
+        )?;
+
+        match self.expected {
+            None => write!(f, ", but {} is not a known op", self.op),
+            Some(Arity::Fixed { inputs, outputs }) => write!(
+                f,
+                ", but {} takes {} inputs and {} outputs",
+                self.op, inputs, outputs
+            ),
+            Some(Arity::Variable) => write!(f, ", but {} takes two inputs per output", self.op),
+        }
+    }
+}
+
+impl BristolCircuit {
+    /// Checks every gate against `registry`, returning all the mismatches in gate order.
+    pub fn validate_ops(&self, registry: &OpRegistry) -> Vec<OpMismatch> {
+        self.gates
+            .iter()
+            .enumerate()
+            .filter_map(|(i, gate)| registry.check(i, gate).err())
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{circuit_info::CircuitInfo, parse_options::ParseOptions};
+
+    /// Inputs `a` and `b` on wires 0 and 1, and output `c` on wire 3.
+    fn info() -> CircuitInfo {
+        CircuitInfo {
+            input_name_to_wire_index: [("a".to_string(), 0), ("b".to_string(), 1)].into(),
+            output_name_to_wire_index: [("c".to_string(), 3)].into(),
+            ..Default::default()
+        }
+    }
+
+    fn circuit(gates: &str) -> BristolCircuit {
+        BristolCircuit::from_info_and_bristol_string(
+            &info(),
+            &format!("{} 4\n2 1 1\n1 1\n\n{}", gates.lines().count(), gates),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_validate_ops() {
+        let circuit = circuit("3 1 0 1 0 2 AAdd\n1 1 2 3 FOO\n");
+        let mut registry = OpRegistry::default();
+
+        let mismatches = circuit.validate_ops(&registry);
+        assert_eq!(
+            mismatches
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>(),
+            vec!["Gate 0 (AAdd) has 3 inputs and 1 outputs, but AAdd takes 2 inputs and 1 outputs"]
+        );
+
+        registry.unknown_ops = UnknownOpPolicy::Reject;
+        let mismatches = circuit.validate_ops(&registry);
+        assert_eq!(mismatches.len(), 2);
+        assert_eq!(
+            mismatches[1].to_string(),
+            "Gate 1 (FOO) has 1 inputs and 1 outputs, but FOO is not a known op"
+        );
+
+        registry.register(
+            "FOO",
+            Arity::Fixed {
+                inputs: 1,
+                outputs: 1,
+            },
+        );
+        registry.register(
+            "AAdd",
+            Arity::Fixed {
+                inputs: 3,
+                outputs: 1,
+            },
+        );
+        assert_eq!(circuit.validate_ops(&registry), vec![]);
+    }
+
+    #[test]
+    fn test_seeded_ops() {
+        let registry = OpRegistry::default();
+        assert!(registry.arity("AMul").unwrap().accepts(2, 1));
+        assert!(registry.arity("AMux").unwrap().accepts(3, 1));
+        assert!(registry.arity("INV").unwrap().accepts(1, 1));
+        assert_eq!(registry.arity("MAND"), Some(Arity::Variable));
+        assert_eq!(registry.arity("LUT[6]"), None);
+
+        let lut = circuit("2 1 0 1 3 LUT[6]\n");
+        let strict = OpRegistry {
+            unknown_ops: UnknownOpPolicy::Reject,
+            ..Default::default()
+        };
+        assert_eq!(lut.validate_ops(&strict), vec![]);
+    }
+
+    #[test]
+    fn test_parse_with_registry() {
+        let info = info();
+        let text = "2 4\n2 1 1\n1 1\n\n2 1 0 1 2 AMul\n3 1 0 1 2 3 AAdd\n";
+
+        assert!(BristolCircuit::from_info_and_bristol_string(&info, text).is_ok());
+
+        let options = ParseOptions {
+            op_registry: Some(OpRegistry::default()),
+            ..Default::default()
+        };
+        let err = BristolCircuit::read_info_and_bristol_with_options(
+            &info,
+            &mut text.as_bytes(),
+            &options,
+        )
+        .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Parsing error: Gate 1 (AAdd) has 3 inputs and 1 outputs, but AAdd takes 2 inputs \
+             and 1 outputs in line 6"
+        );
+    }
+}
@@ -0,0 +1,327 @@
+use std::collections::HashMap;
+
+use crate::{
+    bristol_circuit::BristolCircuit, bristol_circuit_error::BristolCircuitError,
+    evaluator::Evaluator,
+};
+
+/// Like [`Evaluator`], but evaluating a gate only reads `self`, so gates can be evaluated on
+/// several threads at once, for [`BristolCircuit::eval_parallel`].
+pub trait ParallelEvaluator<V>: Sync {
+    /// The value of a constant from `info.constants`.
+    fn constant(&self, name: &str, value: &str) -> Result<V, BristolCircuitError>;
+
+    /// Ops like `EQ` take literal values rather than wires as inputs; returns the value to use
+    /// for such an input, or `None` to read the wire as usual.
+    fn literal(&self, _op: &str, _value: usize) -> Option<V> {
+        None
+    }
+
+    /// Evaluates gate `gate_index`, returning one value per output wire.
+    fn eval_gate(
+        &self,
+        op: &str,
+        inputs: &[V],
+        gate_index: usize,
+    ) -> Result<Vec<V>, BristolCircuitError>;
+}
+
+/// Runs a [`ParallelEvaluator`] through the sequential driver.
+struct Sequential<'a, E>(&'a E);
+
+impl<V, E: ParallelEvaluator<V>> Evaluator<V> for Sequential<'_, E> {
+    fn constant(&mut self, name: &str, value: &str) -> Result<V, BristolCircuitError> {
+        self.0.constant(name, value)
+    }
+
+    fn literal(&mut self, op: &str, value: usize) -> Option<V> {
+        self.0.literal(op, value)
+    }
+
+    fn eval_gate(
+        &mut self,
+        op: &str,
+        inputs: &[V],
+        gate_index: usize,
+    ) -> Result<Vec<V>, BristolCircuitError> {
+        self.0.eval_gate(op, inputs, gate_index)
+    }
+}
+
+impl BristolCircuit {
+    /// Like [`BristolCircuit::eval_with`], but with the `rayon` feature the gates are grouped
+    /// into layers that only read earlier layers, and each layer's gates are evaluated in
+    /// parallel. The results, and any error, are the same as evaluating the gates in order,
+    /// which is what happens without the feature, and for circuits that write a wire twice or
+    /// read one before it's set.
+    pub fn eval_parallel<V: Clone + Send + Sync, E: ParallelEvaluator<V>>(
+        &self,
+        inputs: &HashMap<String, Vec<V>>,
+        evaluator: &E,
+    ) -> Result<HashMap<String, Vec<V>>, BristolCircuitError> {
+        #[cfg(feature = "rayon")]
+        if let Some(outputs) = layered::eval_layers(self, inputs, evaluator) {
+            return Ok(outputs);
+        }
+
+        self.eval_with(inputs, &mut Sequential(evaluator))
+    }
+}
+
+#[cfg(feature = "rayon")]
+mod layered {
+    use rayon::prelude::*;
+    use smallvec::SmallVec;
+
+    use super::*;
+    use crate::trace::trace_span;
+
+    /// Fewer gates than this aren't worth a task of their own.
+    const MIN_GATES_PER_TASK: usize = 256;
+
+    /// Evaluates layer by layer, or `None` if that might not match evaluating the gates in
+    /// order, including when anything fails, so the sequential driver can report the error.
+    pub(super) fn eval_layers<V: Clone + Send + Sync, E: ParallelEvaluator<V>>(
+        circuit: &BristolCircuit,
+        inputs: &HashMap<String, Vec<V>>,
+        evaluator: &E,
+    ) -> Option<HashMap<String, Vec<V>>> {
+        let _span = trace_span!("eval_parallel", gate_count = circuit.gates.len());
+
+        let mut seeds = circuit.bus_input_seeds(inputs).ok()?;
+
+        for (name, constant) in &circuit.info.constants {
+            seeds.push((
+                constant.wire_index,
+                evaluator.constant(name, &constant.value).ok()?,
+            ));
+        }
+
+        let wire_count = circuit.wire_count.max(circuit.referenced_wire_count());
+        let mut wires = vec![None; wire_count];
+
+        for (wire, value) in seeds {
+            wires[wire] = Some(value);
+        }
+
+        let layers = layers(circuit, &wires, evaluator)?;
+        let mut outputs = Vec::new();
+
+        for layer in layers {
+            layer
+                .par_iter()
+                .with_min_len(MIN_GATES_PER_TASK)
+                .map(|&i| {
+                    let gate = &circuit.gates[i];
+                    let inputs = gate
+                        .inputs
+                        .iter()
+                        .map(|&wire| {
+                            evaluator
+                                .literal(&gate.op, wire)
+                                .or_else(|| wires[wire].clone())
+                        })
+                        .collect::<Option<SmallVec<[V; 2]>>>()?;
+
+                    let outputs = evaluator.eval_gate(&gate.op, &inputs, i).ok()?;
+
+                    // Moved out so the list is freed straight away, like the sequential
+                    // driver does, rather than a layer's worth being held at once.
+                    (outputs.len() == gate.outputs.len())
+                        .then(|| outputs.into_iter().collect::<SmallVec<[V; 1]>>())
+                })
+                .collect_into_vec(&mut outputs);
+
+            for (&i, values) in layer.iter().zip(outputs.drain(..)) {
+                for (&wire, value) in circuit.gates[i].outputs.iter().zip(values?) {
+                    wires[wire] = Some(value);
+                }
+            }
+        }
+
+        circuit.bus_outputs(&wires).ok()
+    }
+
+    /// The gates grouped so each layer only reads wires set by earlier layers or seeded, or
+    /// `None` unless every wire is set once, before any gate reads it.
+    pub(super) fn layers<V, E: ParallelEvaluator<V>>(
+        circuit: &BristolCircuit,
+        seeded: &[Option<V>],
+        evaluator: &E,
+    ) -> Option<Vec<Vec<usize>>> {
+        const UNSET: usize = usize::MAX;
+
+        // For each wire, the first layer a gate reading it can be in.
+        let mut ready = seeded
+            .iter()
+            .map(|value| if value.is_some() { 0 } else { UNSET })
+            .collect::<Vec<_>>();
+        let mut layers = Vec::<Vec<usize>>::new();
+
+        for (i, gate) in circuit.gates.iter().enumerate() {
+            let mut layer = 0;
+
+            for &wire in &gate.inputs {
+                if evaluator.literal(&gate.op, wire).is_none() {
+                    if ready[wire] == UNSET {
+                        return None;
+                    }
+
+                    layer = layer.max(ready[wire]);
+                }
+            }
+
+            for &wire in &gate.outputs {
+                if std::mem::replace(&mut ready[wire], layer + 1) != UNSET {
+                    return None;
+                }
+            }
+
+            if layer == layers.len() {
+                layers.push(Vec::new());
+            }
+            layers[layer].push(i);
+        }
+
+        Some(layers)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        circuit_builder::CircuitBuilder, eval::evaluation_error, gadgets, gate::bool_literal,
+    };
+
+    /// Plain booleans, with `EQ` setting its output to the literal it's given.
+    struct Plain;
+
+    impl ParallelEvaluator<bool> for Plain {
+        fn constant(&self, _name: &str, value: &str) -> Result<bool, BristolCircuitError> {
+            Ok(value == "1")
+        }
+
+        fn literal(&self, op: &str, value: usize) -> Option<bool> {
+            bool_literal(op, value)
+        }
+
+        fn eval_gate(
+            &self,
+            op: &str,
+            inputs: &[bool],
+            gate_index: usize,
+        ) -> Result<Vec<bool>, BristolCircuitError> {
+            Ok(vec![match op {
+                "XOR" => inputs[0] ^ inputs[1],
+                "AND" => inputs[0] & inputs[1],
+                "INV" => !inputs[0],
+                "EQ" => inputs[0],
+                _ => {
+                    return Err(evaluation_error(format!(
+                        "Gate {} ({}) is not supported",
+                        gate_index, op
+                    )))
+                }
+            }])
+        }
+    }
+
+    fn adder_inputs() -> HashMap<String, Vec<bool>> {
+        HashMap::from([
+            ("a".to_string(), gadgets::test_utils::to_bits(0xb5, 8)),
+            ("b".to_string(), gadgets::test_utils::to_bits(0x6e, 8)),
+        ])
+    }
+
+    #[test]
+    fn test_matches_eval_boolean() {
+        let circuit = gadgets::adder(8, true);
+        let inputs = adder_inputs();
+
+        assert_eq!(
+            circuit.eval_parallel(&inputs, &Plain).unwrap(),
+            circuit.eval_boolean(&inputs).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_literals_and_constants() {
+        let mut b = CircuitBuilder::new();
+        let x = b.input("x");
+        let one = b.constant("one", "1");
+        let t = b.gate("EQ", &[]);
+        let y = b.gate("XOR", &[x, one]);
+        let z = b.gate("AND", &[y, t]);
+        b.output("z", z);
+
+        let mut circuit = b.build().unwrap();
+        // EQ's input is the literal 1, which isn't a wire this circuit sets.
+        circuit.gates[0].inputs = smallvec::smallvec![1];
+
+        let inputs = HashMap::from([("x".to_string(), vec![false])]);
+        assert_eq!(
+            circuit.eval_parallel(&inputs, &Plain).unwrap(),
+            circuit.eval_with(&inputs, &mut Sequential(&Plain)).unwrap()
+        );
+        assert_eq!(circuit.eval_parallel(&inputs, &Plain).unwrap()["z"], [true]);
+
+        // Literals other than 0 and 1 mean what they do to `eval_boolean`.
+        circuit.gates[0].inputs = smallvec::smallvec![2];
+        assert_eq!(
+            circuit.eval_parallel(&inputs, &Plain).unwrap(),
+            circuit.eval_boolean(&inputs).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_errors_match_sequential() {
+        let inputs = adder_inputs();
+
+        // Both the first gate and a later one fail, in different layers.
+        let mut unsupported = gadgets::adder(8, true);
+        let last = unsupported.gates.len() - 1;
+        unsupported.gates[last].op = "OR".into();
+        unsupported.gates[3].op = "OR".into();
+
+        // Reads a wire a later gate sets.
+        let mut unsorted = gadgets::adder(8, true);
+        unsorted.gates.swap(0, 5);
+
+        // Sets an input wire.
+        let mut overwrites = gadgets::adder(8, true);
+        overwrites.gates[3].outputs[0] = 0;
+
+        for circuit in [unsupported, unsorted, overwrites] {
+            assert_eq!(
+                format!("{:?}", circuit.eval_parallel(&inputs, &Plain)),
+                format!("{:?}", circuit.eval_with(&inputs, &mut Sequential(&Plain)))
+            );
+        }
+
+        let err = gadgets::adder(8, true)
+            .eval_parallel(&HashMap::new(), &Plain)
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Evaluation error: Missing value for input a"
+        );
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn test_layers() {
+        let circuit = gadgets::adder(8, true);
+        let seeded = vec![Some(false); 16]
+            .into_iter()
+            .chain(std::iter::repeat(None))
+            .take(circuit.wire_count)
+            .collect::<Vec<_>>();
+
+        // The same layers as levelizing, since the adder is sorted and sets each wire once.
+        assert_eq!(
+            layered::layers(&circuit, &seeded, &Plain).unwrap(),
+            circuit.levelize().unwrap()
+        );
+    }
+}
@@ -0,0 +1,286 @@
+use std::collections::HashMap;
+
+use crate::{
+    bristol_circuit::BristolCircuit, bristol_circuit_error::BristolCircuitError,
+    circuit_info::CircuitInfo,
+};
+
+#[derive(Clone, Copy, Debug)]
+pub struct ParallelMergeOptions {
+    /// The name of input, output or wire group `name` of copy `index`. `name_<index>` by default.
+    pub rename: fn(name: &str, index: usize) -> String,
+
+    /// Give copies one wire for each constant they share, by name and value. Shared constants
+    /// keep their names; otherwise constants are renamed like inputs.
+    pub share_constants: bool,
+}
+
+impl Default for ParallelMergeOptions {
+    fn default() -> Self {
+        ParallelMergeOptions {
+            rename: |name, index| format!("{}_{}", name, index),
+            share_constants: true,
+        }
+    }
+}
+
+impl BristolCircuit {
+    /// Places circuits side by side: each circuit's wires are offset past the previous ones,
+    /// its gates appended and its inputs and outputs renamed `name_<index>`.
+    pub fn parallel_merge(circuits: &[&BristolCircuit]) -> BristolCircuit {
+        BristolCircuit::parallel_merge_with_options(circuits, &ParallelMergeOptions::default())
+            .expect("the default renaming gives each copy its own names")
+    }
+
+    /// `n` copies of this circuit side by side, as by [`BristolCircuit::parallel_merge`].
+    pub fn replicate(&self, n: usize) -> BristolCircuit {
+        BristolCircuit::parallel_merge(&vec![self; n])
+    }
+
+    /// Like [`BristolCircuit::parallel_merge`], with custom renaming. Fails if copies that
+    /// `options.rename` gives the same input or output name disagree about its owner or
+    /// visibility.
+    pub fn parallel_merge_with_options(
+        circuits: &[&BristolCircuit],
+        options: &ParallelMergeOptions,
+    ) -> Result<BristolCircuit, BristolCircuitError> {
+        let rename = options.rename;
+        let mut merged = BristolCircuit {
+            wire_count: 0,
+            info: CircuitInfo::default(),
+            io_widths: (Vec::new(), Vec::new()),
+            gates: Vec::new(),
+            header_style: circuits
+                .first()
+                .map(|circuit| circuit.header_style)
+                .unwrap_or_default(),
+        };
+
+        let mut offset = 0;
+        let mut shared_wires = false;
+
+        for (index, circuit) in circuits.iter().enumerate() {
+            let mut copy = (*circuit).clone();
+            copy.map_wires(|wire| wire + offset);
+            offset += circuit.wire_count.max(circuit.referenced_wire_count());
+
+            let info = &mut merged.info;
+            let mut replaced = HashMap::new();
+
+            let mut constants = std::mem::take(&mut copy.info.constants)
+                .into_iter()
+                .collect::<Vec<_>>();
+            constants.sort_by(|(a, _), (b, _)| a.cmp(b));
+
+            for (name, constant) in constants {
+                if options.share_constants {
+                    match info.constants.get(&name) {
+                        None => {
+                            info.constants.insert(name, constant);
+                            continue;
+                        }
+                        Some(existing) if existing.value == constant.value => {
+                            replaced.insert(constant.wire_index, existing.wire_index);
+                            continue;
+                        }
+                        Some(_) => {}
+                    }
+                }
+
+                info.constants.insert(rename(&name, index), constant);
+            }
+
+            shared_wires |= !replaced.is_empty();
+
+            for mut gate in copy.gates {
+                for wire in &mut gate.inputs {
+                    *wire = replaced.get(wire).copied().unwrap_or(*wire);
+                }
+
+                merged.gates.push(gate);
+            }
+
+            let copy_info = copy.info;
+            rename_into(
+                &mut info.input_name_to_wire_index,
+                copy_info.input_name_to_wire_index,
+                rename,
+                index,
+            );
+            rename_into(
+                &mut info.output_name_to_wire_index,
+                copy_info.output_name_to_wire_index,
+                rename,
+                index,
+            );
+            rename_into(&mut info.wire_groups, copy_info.wire_groups, rename, index);
+            info.merge_input_owners(&renamed(copy_info.input_owners, rename, index))?;
+            info.merge_output_visibility(&renamed(copy_info.output_visibility, rename, index))?;
+
+            merged.io_widths.0.extend(&circuit.io_widths.0);
+            merged.io_widths.1.extend(&circuit.io_widths.1);
+        }
+
+        merged.wire_count = offset;
+
+        // Shared constants leave their copies' wires unused.
+        if shared_wires {
+            merged = merged.compact_wires();
+        }
+
+        Ok(merged)
+    }
+}
+
+fn rename_into<V>(
+    merged: &mut HashMap<String, V>,
+    names: HashMap<String, V>,
+    rename: fn(&str, usize) -> String,
+    index: usize,
+) {
+    merged.extend(renamed(names, rename, index));
+}
+
+fn renamed<V>(
+    names: HashMap<String, V>,
+    rename: fn(&str, usize) -> String,
+    index: usize,
+) -> HashMap<String, V> {
+    names
+        .into_iter()
+        .map(|(name, value)| (rename(&name, index), value))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{circuit_builder::CircuitBuilder, party::PartyId};
+
+    /// `d = (a + b) * b`, with `k` added when `constant` is given.
+    fn sample(constant: Option<&str>) -> BristolCircuit {
+        let mut b = CircuitBuilder::new();
+        let x = b.input("a");
+        let y = b.input("b");
+        let sum = b.gate("AAdd", &[x, y]);
+        let mut d = b.gate("AMul", &[sum, y]);
+
+        if let Some(value) = constant {
+            let k = b.constant("k", value);
+            d = b.gate("AAdd", &[d, k]);
+        }
+
+        b.output("d", d);
+        b.build().unwrap()
+    }
+
+    fn eval(circuit: &BristolCircuit, inputs: &[(&str, u64)]) -> HashMap<String, u64> {
+        let inputs = inputs
+            .iter()
+            .map(|&(name, value)| (name.to_string(), value))
+            .collect();
+
+        circuit.eval_arithmetic(&inputs, None).unwrap()
+    }
+
+    #[test]
+    fn test_replicate() {
+        let copies = sample(None).replicate(3);
+
+        assert_eq!(copies.gates.len(), 6);
+        assert_eq!(copies.wire_count, 12);
+        assert_eq!(copies.io_widths, (vec![1; 6], vec![1; 3]));
+        copies.validate().unwrap();
+
+        let outputs = eval(
+            &copies,
+            &[
+                ("a_0", 1),
+                ("b_0", 2),
+                ("a_1", 3),
+                ("b_1", 4),
+                ("a_2", 5),
+                ("b_2", 6),
+            ],
+        );
+        assert_eq!(
+            outputs,
+            HashMap::from([
+                ("d_0".to_string(), 6),
+                ("d_1".to_string(), 28),
+                ("d_2".to_string(), 66)
+            ])
+        );
+    }
+
+    #[test]
+    fn test_constants() {
+        let one = sample(Some("1"));
+        let two = sample(Some("2"));
+
+        let merged = BristolCircuit::parallel_merge(&[&one, &one, &two]);
+        assert_eq!(merged.wire_count, 17);
+        assert_eq!(
+            merged
+                .info
+                .constants
+                .iter()
+                .map(|(name, constant)| (name.as_str(), constant.value.as_str()))
+                .collect::<HashMap<_, _>>(),
+            HashMap::from([("k", "1"), ("k_2", "2")])
+        );
+        merged.validate().unwrap();
+
+        let outputs = eval(
+            &merged,
+            &[
+                ("a_0", 1),
+                ("b_0", 1),
+                ("a_1", 1),
+                ("b_1", 1),
+                ("a_2", 1),
+                ("b_2", 1),
+            ],
+        );
+        assert_eq!((outputs["d_0"], outputs["d_1"], outputs["d_2"]), (3, 3, 4));
+
+        let separate = BristolCircuit::parallel_merge_with_options(
+            &[&one, &one],
+            &ParallelMergeOptions {
+                rename: |name, index| format!("copy{}.{}", index, name),
+                share_constants: false,
+            },
+        )
+        .unwrap();
+        assert_eq!(separate.wire_count, 12);
+        assert_eq!(separate.info.constants.len(), 2);
+        assert!(separate.info.constants.contains_key("copy1.k"));
+        assert_eq!(separate.info.output_name_to_wire_index["copy0.d"], 5);
+    }
+
+    #[test]
+    fn test_owner_conflicts() {
+        let mut first = sample(None);
+        first.info.input_owners.insert("a".to_string(), PartyId(0));
+        let mut second = first.clone();
+
+        let keep_names = ParallelMergeOptions {
+            rename: |name, _| name.to_string(),
+            share_constants: false,
+        };
+        let merged =
+            BristolCircuit::parallel_merge_with_options(&[&first, &second], &keep_names).unwrap();
+        assert_eq!(merged.info.input_owners["a"], PartyId(0));
+
+        second.info.input_owners.insert("a".to_string(), PartyId(1));
+        let err = BristolCircuit::parallel_merge_with_options(&[&first, &second], &keep_names)
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Inconsistency: Input a is owned by both party 0 and party 1"
+        );
+
+        let merged = BristolCircuit::parallel_merge(&[&first, &second]);
+        assert_eq!(merged.info.input_owners["a_1"], PartyId(1));
+    }
+}
@@ -0,0 +1,349 @@
+use rayon::prelude::*;
+
+use crate::{
+    bristol_circuit::BristolCircuit,
+    bristol_circuit_error::BristolCircuitError,
+    circuit_info::CircuitInfo,
+    circuit_reader::read_checked_header,
+    compression::reject_gzip,
+    gate::Gate,
+    line_reader::LineReader,
+    parse_options::{GateCountPolicy, ParseLimits, ParseOptions},
+    parse_warning::ParseWarning,
+    trace::{trace_record, trace_span},
+};
+
+/// Chunks smaller than this aren't worth a task of their own.
+const MIN_CHUNK_BYTES: usize = 64 * 1024;
+
+/// More chunks than threads, so a thread that finishes early can pick up another.
+const CHUNKS_PER_THREAD: usize = 4;
+
+impl BristolCircuit {
+    pub fn read_info_and_bristol_parallel(
+        info: &CircuitInfo,
+        input: &str,
+    ) -> Result<BristolCircuit, BristolCircuitError> {
+        BristolCircuit::read_info_and_bristol_parallel_with_options(
+            info,
+            input,
+            &ParseOptions::default(),
+        )
+    }
+
+    /// Like [`BristolCircuit::read_info_and_bristol_with_options`], but parses the gates on
+    /// the rayon thread pool: the header is read first, then the rest of the input is split on
+    /// line boundaries and the pieces parsed in parallel. The result is the same, except that
+    /// gate errors also give the gate's index. Reading until a terminator, checking against an
+    /// op registry, and anything after the declared gates are handled by the serial parser.
+    pub fn read_info_and_bristol_parallel_with_options(
+        info: &CircuitInfo,
+        input: &str,
+        options: &ParseOptions,
+    ) -> Result<BristolCircuit, BristolCircuitError> {
+        let serial =
+            || BristolCircuit::from_info_and_bristol_string_with_options(info, input, options);
+
+        let gate_count = match options.gate_count {
+            _ if options.op_registry.is_some() => return serial(),
+            GateCountPolicy::ReadUntilEof => return serial(),
+            GateCountPolicy::TrustHeader => None,
+            GateCountPolicy::Exact(n) => Some(n),
+        };
+
+        let span = trace_span!("parse_parallel"; gate_count, wire_count);
+        reject_gzip(input.as_bytes())?;
+
+        let mut lines = LineReader::new(input.as_bytes(), options);
+        let header = read_checked_header(info, &mut lines, options)?;
+        let gate_count = gate_count.unwrap_or(header.gate_count);
+        trace_record!(span, gate_count = gate_count);
+        trace_record!(span, wire_count = header.wire_count);
+
+        ParseLimits::check("max_gates", options.limits.max_gates, gate_count, || {
+            format!("Header declares {} gates", gate_count)
+        })?;
+
+        let header_lines = lines.next_unread_line() - 1;
+        let gates_start = line_start(input.as_bytes(), header_lines);
+
+        let chunk_count = rayon::current_num_threads() * CHUNKS_PER_THREAD;
+        let gates = match parse_gates(input.as_bytes(), gates_start, chunk_count, options) {
+            Ok(gates) => gates,
+            Err(GateError { index, error }) if index < gate_count => {
+                return Err(match error {
+                    BristolCircuitError::ParsingError { message } => {
+                        BristolCircuitError::ParsingError {
+                            message: format!("Gate {}: {}", index, message),
+                        }
+                    }
+                    error => error,
+                })
+            }
+            // The serial parser decides what may follow the gates.
+            Err(_) => return serial(),
+        };
+
+        // The serial parser reads and limits the gates past the declared count.
+        if !options.strict && gates.len() > gate_count {
+            return serial();
+        }
+
+        let mut warnings = Vec::new();
+
+        if gates.len() != gate_count {
+            if options.strict {
+                return Err(BristolCircuitError::Inconsistency {
+                    message: format!(
+                        "Header declares {} gates but {} were found",
+                        gate_count,
+                        gates.len()
+                    ),
+                });
+            }
+
+            warnings.push(ParseWarning::GateCountMismatch {
+                declared: gate_count,
+                actual: gates.len(),
+            });
+        }
+
+        BristolCircuit::from_parts(info.clone(), header, gates, warnings, options)
+            .map(|(circuit, _)| circuit)
+    }
+}
+
+/// A gate that failed to parse, by its index among all the gates.
+struct GateError {
+    index: usize,
+    error: BristolCircuitError,
+}
+
+/// Parses every line of `input` from `start` as a gate, in about `chunk_count` pieces.
+fn parse_gates(
+    input: &[u8],
+    start: usize,
+    chunk_count: usize,
+    options: &ParseOptions,
+) -> Result<Vec<Gate>, GateError> {
+    let bounds = chunk_bounds(input, start, chunk_count);
+
+    let chunks = bounds
+        .par_windows(2)
+        .map(|bounds| parse_chunk(&input[bounds[0]..bounds[1]], 0, options))
+        .collect::<Vec<_>>();
+
+    let mut gates = Vec::with_capacity(
+        chunks
+            .iter()
+            .map(|chunk| chunk.as_ref().map_or(0, Vec::len))
+            .sum(),
+    );
+    for (chunk, bounds) in chunks.into_iter().zip(bounds.windows(2)) {
+        match chunk {
+            Ok(chunk) => gates.extend(chunk),
+            Err((parsed, error)) => {
+                // Parse it again numbering its lines correctly, which only failures need.
+                let first_line = input[..bounds[0]].iter().filter(|&&b| b == b'\n').count();
+                let chunk = &input[bounds[0]..bounds[1]];
+                let error = parse_chunk(chunk, first_line, options)
+                    .err()
+                    .map_or(error, |(_, error)| error);
+
+                return Err(GateError {
+                    index: gates.len() + parsed,
+                    error,
+                });
+            }
+        }
+    }
+
+    Ok(gates)
+}
+
+fn parse_chunk(
+    chunk: &[u8],
+    first_line: usize,
+    options: &ParseOptions,
+) -> Result<Vec<Gate>, (usize, BristolCircuitError)> {
+    let mut lines = LineReader::new(chunk, options).starting_after_line(first_line);
+    let mut gates = Vec::new();
+
+    loop {
+        match lines.next_gate() {
+            Ok(Some(gate)) => gates.push(gate),
+            Ok(None) => return Ok(gates),
+            Err(error) => return Err((gates.len(), error)),
+        }
+    }
+}
+
+/// The byte offsets splitting `input[start..]` into up to `chunk_count` pieces, each ending
+/// just after a newline (or at the end of the input).
+fn chunk_bounds(input: &[u8], start: usize, chunk_count: usize) -> Vec<usize> {
+    let chunk_len = ((input.len() - start) / chunk_count.max(1)).max(MIN_CHUNK_BYTES);
+    let mut bounds = vec![start];
+
+    while let Some(&last) = bounds.last().filter(|&&last| last < input.len()) {
+        let target = last.saturating_add(chunk_len).min(input.len());
+        let end = match input[target..].iter().position(|&b| b == b'\n') {
+            Some(newline) => target + newline + 1,
+            None => input.len(),
+        };
+
+        bounds.push(end);
+    }
+
+    bounds
+}
+
+/// The offset of the start of the line after the first `lines` newlines.
+fn line_start(input: &[u8], lines: usize) -> usize {
+    if lines == 0 {
+        return 0;
+    }
+
+    input
+        .iter()
+        .enumerate()
+        .filter(|&(_, &b)| b == b'\n')
+        .nth(lines - 1)
+        .map_or(input.len(), |(i, _)| i + 1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{gadgets, CircuitBuilder};
+
+    /// Gates alternately combining the previous two wires.
+    fn many_gates(n: usize) -> BristolCircuit {
+        let mut b = CircuitBuilder::new();
+        let (mut x, mut y) = (b.input("a"), b.input("b"));
+
+        for i in 0..n {
+            let op = if i % 2 == 0 { "AND" } else { "XOR" };
+            (x, y) = (y, b.gate(op, &[x, y]));
+        }
+
+        b.output("c", y);
+        b.build().unwrap()
+    }
+
+    #[test]
+    fn test_matches_serial() {
+        let circuit = many_gates(100_000);
+        let text = circuit.get_bristol_string().unwrap();
+
+        let serial = BristolCircuit::from_info_and_bristol_string(&circuit.info, &text).unwrap();
+        let parallel =
+            BristolCircuit::read_info_and_bristol_parallel(&circuit.info, &text).unwrap();
+        assert_eq!(parallel, serial);
+
+        // However it's split.
+        let start = line_start(text.as_bytes(), 3);
+        for chunk_count in [1, 7, 1000] {
+            let gates = parse_gates(
+                text.as_bytes(),
+                start,
+                chunk_count,
+                &ParseOptions::default(),
+            )
+            .ok()
+            .unwrap();
+            assert_eq!(gates, serial.gates);
+        }
+    }
+
+    #[test]
+    fn test_chunk_bounds() {
+        let input = "a\n".repeat(MIN_CHUNK_BYTES);
+        let bounds = chunk_bounds(input.as_bytes(), 2, 4);
+
+        assert_eq!(bounds.first(), Some(&2));
+        assert_eq!(bounds.last(), Some(&input.len()));
+        assert_eq!(bounds.len(), 3);
+        assert!(bounds[1..]
+            .iter()
+            .all(|&end| input.as_bytes()[end - 1] == b'\n'));
+
+        assert_eq!(chunk_bounds(b"", 0, 4), [0]);
+        assert_eq!(chunk_bounds(b"1 2", 0, 4), [0, 3]);
+        assert_eq!(line_start(b"1 2\n3\n", 1), 4);
+        assert_eq!(line_start(b"1 2\n3", 2), 5);
+    }
+
+    #[test]
+    fn test_errors_give_gate_and_line() {
+        let circuit = many_gates(50_000);
+        let mut text = circuit.get_bristol_string().unwrap();
+
+        let bad_gate = 40_000;
+        let line = circuit.gates[bad_gate].to_string();
+        let start = text.find(&format!("\n{}\n", line)).unwrap() + 1;
+        let line_number = text[..start].matches('\n').count() + 1;
+        text.replace_range(start..start + line.len(), "2 1 0 1 AND");
+
+        let serial =
+            BristolCircuit::from_info_and_bristol_string(&circuit.info, &text).unwrap_err();
+        let parallel =
+            BristolCircuit::read_info_and_bristol_parallel(&circuit.info, &text).unwrap_err();
+        assert_eq!(
+            parallel.to_string(),
+            serial.to_string().replace(
+                "Parsing error: ",
+                &format!("Parsing error: Gate {}: ", bad_gate)
+            )
+        );
+        assert!(parallel
+            .to_string()
+            .contains(&format!("line {} ", line_number)));
+    }
+
+    #[test]
+    fn test_gate_count_mismatch() {
+        let circuit = gadgets::adder(8, true);
+        let text = circuit.get_bristol_string().unwrap();
+        let extra = format!("{}{}\n", text, circuit.gates[0]);
+        let trailing = format!("{}not a gate\n", text);
+
+        let err =
+            BristolCircuit::read_info_and_bristol_parallel(&circuit.info, &extra).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            format!(
+                "Inconsistency: Header declares {} gates but {} were found",
+                circuit.gates.len(),
+                circuit.gates.len() + 1
+            )
+        );
+
+        let lenient = ParseOptions {
+            strict: false,
+            check_trailing: false,
+            ..Default::default()
+        };
+        let mut with_extra = circuit.clone();
+        with_extra.gates.push(circuit.gates[0].clone());
+        for (input, expected) in [(&extra, &with_extra), (&trailing, &circuit)] {
+            assert_eq!(
+                &BristolCircuit::read_info_and_bristol_parallel_with_options(
+                    &circuit.info,
+                    input,
+                    &lenient
+                )
+                .unwrap(),
+                expected
+            );
+        }
+
+        assert_eq!(
+            BristolCircuit::read_info_and_bristol_parallel(&circuit.info, &trailing)
+                .unwrap_err()
+                .to_string(),
+            BristolCircuit::from_info_and_bristol_string(&circuit.info, &trailing)
+                .unwrap_err()
+                .to_string()
+        );
+    }
+}
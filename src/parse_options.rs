@@ -0,0 +1,162 @@
+use crate::{
+    bristol_circuit_error::BristolCircuitError, header_style::HeaderStyle, op_registry::OpRegistry,
+};
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParseOptions {
+    /// Caps on what the input may declare or contain.
+    pub limits: ParseLimits,
+
+    /// Gate lines longer than this are consumed token by token instead of being buffered whole.
+    /// Either way a line may hold several gates, so this only affects memory use.
+    pub streaming_threshold: usize,
+
+    /// The io widths declaration style to expect. `None` auto-detects it.
+    pub header_style: Option<HeaderStyle>,
+
+    /// Reject circuits whose declared gate or wire counts disagree with their content. When
+    /// disabled, each mismatch is reported as a [`ParseWarning`](crate::ParseWarning) instead:
+    /// every gate in the input is read, however many are declared, and the wire count is set to
+    /// the wires the gates use.
+    ///
+    /// On by default, including for the entry points that take no options, such as
+    /// [`BristolCircuit::read_info_and_bristol`](crate::BristolCircuit::read_info_and_bristol).
+    /// Those used to accept a header declaring fewer wires than the gates reference, or more
+    /// gates than are present; disable this to keep accepting such input.
+    pub strict: bool,
+
+    /// Require the declared wire count to be exactly the number of referenced wires, rather than
+    /// an upper bound (some tools over-declare).
+    pub exact_wire_count: bool,
+
+    /// Reject non-whitespace content after the declared gates.
+    pub check_trailing: bool,
+
+    /// How many gate lines to read.
+    pub gate_count: GateCountPolicy,
+
+    /// How invalid UTF-8 is handled by the byte-oriented entry points.
+    pub utf8: Utf8Policy,
+
+    /// Run [`BristolCircuit::validate`](crate::BristolCircuit::validate) on the parsed circuit,
+    /// before the wire count is checked or corrected, so out of range wires are reported by gate.
+    pub validate: bool,
+
+    /// Fail on the first gate that doesn't match its op's arity in this registry.
+    pub op_registry: Option<OpRegistry>,
+}
+
+/// Caps on the sizes an input may declare or contain, so untrusted input can't make parsing, or
+/// whatever runs the circuit afterwards, allocate without bound. Declared sizes are checked
+/// before anything is allocated for them. `None` disables a limit.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ParseLimits {
+    /// Maximum number of gates, declared or read.
+    pub max_gates: Option<usize>,
+
+    /// Maximum number of wires, declared or referenced.
+    pub max_wires: Option<usize>,
+
+    /// Maximum number of bytes in a single physical line.
+    pub max_line_bytes: Option<usize>,
+
+    /// Maximum number of inputs plus outputs.
+    pub max_io: Option<usize>,
+}
+
+impl ParseLimits {
+    pub const DEFAULT_MAX_GATES: usize = 1 << 30;
+    pub const DEFAULT_MAX_WIRES: usize = 1 << 30;
+    pub const DEFAULT_MAX_LINE_BYTES: usize = 256 * 1024 * 1024;
+    pub const DEFAULT_MAX_IO: usize = 1 << 20;
+
+    /// No limits, for trusted input.
+    pub fn unlimited() -> Self {
+        ParseLimits {
+            max_gates: None,
+            max_wires: None,
+            max_line_bytes: None,
+            max_io: None,
+        }
+    }
+
+    /// Fails if `actual` is over `max`, which is the limit named `limit`, describing `actual`
+    /// with `what`.
+    pub(crate) fn check(
+        limit: &'static str,
+        max: Option<usize>,
+        actual: usize,
+        what: impl FnOnce() -> String,
+    ) -> Result<(), BristolCircuitError> {
+        match max {
+            Some(max) if actual > max => Err(BristolCircuitError::LimitExceeded {
+                limit,
+                max,
+                message: what(),
+            }),
+            _ => Ok(()),
+        }
+    }
+}
+
+impl Default for ParseLimits {
+    fn default() -> Self {
+        ParseLimits {
+            max_gates: Some(ParseLimits::DEFAULT_MAX_GATES),
+            max_wires: Some(ParseLimits::DEFAULT_MAX_WIRES),
+            max_line_bytes: Some(ParseLimits::DEFAULT_MAX_LINE_BYTES),
+            max_io: Some(ParseLimits::DEFAULT_MAX_IO),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Utf8Policy {
+    /// Reject input that is not valid UTF-8, reporting the byte offset of the first bad sequence.
+    #[default]
+    Strict,
+
+    /// Replace invalid sequences with U+FFFD. Parsing only fails if a replacement ends up in a
+    /// token that is actually used (a number or an op), so corruption in ignored content is
+    /// tolerated.
+    Lossy,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum GateCountPolicy {
+    /// Read exactly the number of gates declared in the header.
+    #[default]
+    TrustHeader,
+
+    /// Ignore the declared number and read gates until the input ends or a terminator line
+    /// (`.end`) is reached. Anything after the terminator is ignored.
+    ReadUntilEof,
+
+    /// Read exactly this many gates, regardless of the header.
+    Exact(usize),
+}
+
+impl GateCountPolicy {
+    pub const TERMINATOR: &'static str = ".end";
+}
+
+impl ParseOptions {
+    pub const DEFAULT_STREAMING_THRESHOLD: usize = 64 * 1024;
+}
+
+impl Default for ParseOptions {
+    fn default() -> Self {
+        ParseOptions {
+            limits: ParseLimits::default(),
+            streaming_threshold: ParseOptions::DEFAULT_STREAMING_THRESHOLD,
+            header_style: None,
+            strict: true,
+            exact_wire_count: false,
+            check_trailing: true,
+            gate_count: GateCountPolicy::TrustHeader,
+            utf8: Utf8Policy::Strict,
+            validate: false,
+            op_registry: None,
+        }
+    }
+}
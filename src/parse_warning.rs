@@ -0,0 +1,25 @@
+use std::fmt::{self, Display, Formatter};
+
+/// A problem tolerated by lenient parsing.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ParseWarning {
+    GateCountMismatch { declared: usize, actual: usize },
+    WireCountMismatch { declared: usize, actual: usize },
+}
+
+impl Display for ParseWarning {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            ParseWarning::GateCountMismatch { declared, actual } => write!(
+                f,
+                "Header declares {} gates but {} were found",
+                declared, actual
+            ),
+            ParseWarning::WireCountMismatch { declared, actual } => write!(
+                f,
+                "Header declares {} wires but {} are referenced",
+                declared, actual
+            ),
+        }
+    }
+}
@@ -0,0 +1,258 @@
+use std::{
+    collections::HashMap,
+    fmt::{self, Display, Formatter},
+    io::Write,
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    bristol_circuit::BristolCircuit, bristol_circuit_error::BristolCircuitError,
+    circuit_info::CircuitInfo, write_options::WriteOptions,
+};
+
+/// A participant in an MPC protocol, numbered from 0.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct PartyId(pub u32);
+
+impl Display for PartyId {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "party {}", self.0)
+    }
+}
+
+impl CircuitInfo {
+    /// The inputs `party` supplies, in wire order.
+    pub fn inputs_of_party(&self, party: PartyId) -> Vec<&str> {
+        let mut inputs = self
+            .input_owners
+            .iter()
+            .filter(|(_, &owner)| owner == party)
+            .filter_map(|(name, _)| {
+                self.input_name_to_wire_index
+                    .get(name)
+                    .map(|&wire| (wire, name.as_str()))
+            })
+            .collect::<Vec<_>>();
+        inputs.sort();
+
+        inputs.into_iter().map(|(_, name)| name).collect()
+    }
+
+    /// Checks that every owner annotation names an input.
+    pub fn validate_input_owners(&self) -> Result<(), BristolCircuitError> {
+        let mut unknown = self
+            .input_owners
+            .keys()
+            .filter(|name| !self.input_name_to_wire_index.contains_key(*name))
+            .collect::<Vec<_>>();
+        unknown.sort();
+
+        match unknown.first() {
+            None => Ok(()),
+            Some(name) => Err(BristolCircuitError::Inconsistency {
+                message: format!("Owner annotation for unknown input {}", name),
+            }),
+        }
+    }
+
+    /// Adds `owners` to this circuit's annotations, e.g. when combining circuits. An input can't
+    /// be owned by two parties.
+    pub fn merge_input_owners(
+        &mut self,
+        owners: &HashMap<String, PartyId>,
+    ) -> Result<(), BristolCircuitError> {
+        let mut names = owners.keys().collect::<Vec<_>>();
+        names.sort();
+
+        for name in &names {
+            match self.input_owners.get(*name) {
+                Some(&existing) if existing != owners[*name] => {
+                    return Err(BristolCircuitError::Inconsistency {
+                        message: format!(
+                            "Input {} is owned by both {} and {}",
+                            name, existing, owners[*name]
+                        ),
+                    });
+                }
+                _ => {}
+            }
+        }
+
+        for name in names {
+            self.input_owners.insert(name.clone(), owners[name]);
+        }
+
+        Ok(())
+    }
+}
+
+impl BristolCircuit {
+    /// The total input width of each party, indexed by party number, as used by headers that
+    /// declare one input per party.
+    ///
+    /// Every input must have an owner, and each party's inputs must come after the previous
+    /// party's in wire order, so that each party's wires are contiguous.
+    pub fn party_input_widths(&self) -> Result<Vec<usize>, BristolCircuitError> {
+        let mut widths = Vec::<usize>::new();
+        let mut current = None;
+
+        for bus in self.input_buses() {
+            let owner = *self.info.input_owners.get(bus.name).ok_or_else(|| {
+                BristolCircuitError::Inconsistency {
+                    message: format!("Input {} has no owner", bus.name),
+                }
+            })?;
+
+            if current.is_some_and(|current| owner < current) {
+                return Err(BristolCircuitError::Inconsistency {
+                    message: format!(
+                        "Input {} of {} comes after inputs of a later party",
+                        bus.name, owner
+                    ),
+                });
+            }
+
+            current = Some(owner);
+
+            let party = owner.0 as usize;
+            if widths.len() <= party {
+                widths.resize(party + 1, 0);
+            }
+            widths[party] += bus.width;
+        }
+
+        Ok(widths)
+    }
+
+    /// Writes the circuit with one input width per party in the header, instead of one per
+    /// named input. With `HeaderStyle::Legacy` this gives the classic two-party header.
+    pub fn write_bristol_by_party<W: Write>(&self, w: &mut W) -> Result<(), BristolCircuitError> {
+        self.write_bristol_with_input_widths(
+            w,
+            &self.party_input_widths()?,
+            &WriteOptions::default(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{circuit_builder::CircuitBuilder, header_style::HeaderStyle};
+
+    /// Party 0 supplies `a` (4 bits) and `c` (1 bit), party 1 supplies `b` (4 bits).
+    fn two_party_circuit() -> BristolCircuit {
+        let mut b = CircuitBuilder::new();
+        let a = b.input_bus("a", 4);
+        let c = b.input("c");
+        let x = b.input_bus("b", 4);
+
+        let bits = a
+            .iter()
+            .zip(&x)
+            .map(|(&a, &x)| {
+                let sum = b.gate("XOR", &[a, x]);
+                b.gate("AND", &[sum, c])
+            })
+            .collect::<Vec<_>>();
+        b.output_bus("out", &bits);
+
+        let mut circuit = b.build().unwrap();
+        circuit.info.input_owners = HashMap::from([
+            ("a".to_string(), PartyId(0)),
+            ("c".to_string(), PartyId(0)),
+            ("b".to_string(), PartyId(1)),
+        ]);
+        circuit
+    }
+
+    #[test]
+    fn test_party_header() {
+        let mut circuit = two_party_circuit();
+        assert_eq!(circuit.party_input_widths().unwrap(), vec![5, 4]);
+
+        circuit.header_style = HeaderStyle::Legacy;
+
+        let mut out = Vec::new();
+        circuit.write_bristol_by_party(&mut out).unwrap();
+        let text = String::from_utf8(out).unwrap();
+
+        let header = text.lines().take(2).collect::<Vec<_>>();
+        assert_eq!(header[1], "5 4 4");
+
+        circuit.header_style = HeaderStyle::NivList;
+        let mut out = Vec::new();
+        circuit.write_bristol_by_party(&mut out).unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap().lines().nth(1),
+            Some("2 5 4")
+        );
+    }
+
+    #[test]
+    fn test_inputs_of_party() {
+        let info = two_party_circuit().info;
+
+        assert_eq!(info.inputs_of_party(PartyId(0)), vec!["a", "c"]);
+        assert_eq!(info.inputs_of_party(PartyId(1)), vec!["b"]);
+        assert!(info.inputs_of_party(PartyId(2)).is_empty());
+    }
+
+    #[test]
+    fn test_party_order() {
+        let mut circuit = two_party_circuit();
+        circuit
+            .info
+            .input_owners
+            .insert("c".to_string(), PartyId(1));
+        circuit
+            .info
+            .input_owners
+            .insert("b".to_string(), PartyId(0));
+
+        let err = circuit.party_input_widths().unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Inconsistency: Input b of party 0 comes after inputs of a later party"
+        );
+
+        circuit.info.input_owners.remove("a");
+        let err = circuit.party_input_widths().unwrap_err();
+        assert_eq!(err.to_string(), "Inconsistency: Input a has no owner");
+    }
+
+    #[test]
+    fn test_validation_and_merge() {
+        let mut info = two_party_circuit().info;
+        assert!(info.validate_input_owners().is_ok());
+
+        info.merge_input_owners(&HashMap::from([("a".to_string(), PartyId(0))]))
+            .unwrap();
+
+        let err = info
+            .merge_input_owners(&HashMap::from([("b".to_string(), PartyId(0))]))
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Inconsistency: Input b is owned by both party 1 and party 0"
+        );
+
+        info.input_owners.insert("z".to_string(), PartyId(1));
+        let err = info.validate_input_owners().unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Inconsistency: Owner annotation for unknown input z"
+        );
+    }
+
+    #[test]
+    fn test_json_shape() {
+        let info = two_party_circuit().info;
+        let json = serde_json::to_value(&info).unwrap();
+
+        assert_eq!(json["input_owners"]["b"], 1);
+        assert_eq!(CircuitInfo::from_json_str(&json.to_string()).unwrap(), info);
+    }
+}
@@ -0,0 +1,291 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, OnceLock},
+};
+
+use crate::{
+    bool_eval::BoolOp,
+    bristol_circuit::BristolCircuit,
+    bristol_circuit_error::BristolCircuitError,
+    eval::{eval_wires_into, GateSemantics},
+    gate::bool_literal,
+    op_classification::OpKind,
+};
+
+/// An immutable circuit shared between threads, with the lookups evaluation and analyses need
+/// computed once, on first use.
+///
+/// Evaluating a prepared circuit only allocates the wire values, and not even that when a buffer
+/// is passed in (e.g. one taken from a pool).
+#[derive(Debug)]
+pub struct PreparedCircuit {
+    circuit: BristolCircuit,
+    wire_count: usize,
+    producers: OnceLock<Vec<Option<usize>>>,
+    consumers: OnceLock<Vec<Vec<usize>>>,
+    levels: OnceLock<Vec<Vec<usize>>>,
+    signature: OnceLock<u64>,
+    bool_ops: OnceLock<Vec<BoolOp>>,
+    bool_constants: OnceLock<Vec<(usize, bool)>>,
+}
+
+impl BristolCircuit {
+    pub fn prepare(self) -> Arc<PreparedCircuit> {
+        Arc::new(PreparedCircuit {
+            wire_count: self.wire_count.max(self.referenced_wire_count()),
+            circuit: self,
+            producers: OnceLock::new(),
+            consumers: OnceLock::new(),
+            levels: OnceLock::new(),
+            signature: OnceLock::new(),
+            bool_ops: OnceLock::new(),
+            bool_constants: OnceLock::new(),
+        })
+    }
+}
+
+impl PreparedCircuit {
+    pub fn circuit(&self) -> &BristolCircuit {
+        &self.circuit
+    }
+
+    /// The gate writing `wire`, if any.
+    pub fn producer(&self, wire: usize) -> Option<usize> {
+        let producers = self.producers.get_or_init(|| {
+            let mut producers = vec![None; self.wire_count];
+
+            for (i, gate) in self.circuit.gates.iter().enumerate() {
+                for &wire in &gate.outputs {
+                    producers[wire] = Some(i);
+                }
+            }
+
+            producers
+        });
+
+        producers.get(wire).copied().flatten()
+    }
+
+    /// The gates reading `wire`, in order.
+    pub fn consumers(&self, wire: usize) -> &[usize] {
+        let consumers = self.consumers.get_or_init(|| {
+            let mut consumers = vec![Vec::new(); self.wire_count];
+
+            for (i, gate) in self.circuit.gates.iter().enumerate() {
+                for &wire in &gate.inputs {
+                    if consumers[wire].last() != Some(&i) {
+                        consumers[wire].push(i);
+                    }
+                }
+            }
+
+            consumers
+        });
+
+        consumers.get(wire).map_or(&[], Vec::as_slice)
+    }
+
+    /// Gate indices grouped by depth: level `k` holds the gates whose deepest input comes from
+    /// level `k - 1` (or from inputs and constants, for level 0).
+    pub fn levels(&self) -> &[Vec<usize>] {
+        self.levels.get_or_init(|| {
+            let mut levels = Vec::<Vec<usize>>::new();
+
+            for (i, depth) in self.circuit.gate_depths().into_iter().enumerate() {
+                if levels.len() < depth {
+                    levels.resize(depth, Vec::new());
+                }
+
+                levels[depth - 1].push(i);
+            }
+
+            levels
+        })
+    }
+
+    /// The length of the longest chain of gates.
+    pub fn depth(&self) -> usize {
+        self.levels().len()
+    }
+
+    /// The circuit's `semantic_hash`.
+    pub fn signature(&self) -> u64 {
+        *self.signature.get_or_init(|| self.circuit.semantic_hash())
+    }
+
+    /// Evaluates a boolean circuit like `BristolCircuit::eval_boolean`.
+    pub fn eval_boolean(
+        &self,
+        inputs: &HashMap<String, Vec<bool>>,
+    ) -> Result<HashMap<String, Vec<bool>>, BristolCircuitError> {
+        self.eval_boolean_with_buffer(inputs, &mut Vec::new())
+    }
+
+    /// Like `eval_boolean`, storing wire values in `buffer` so its allocation can be reused.
+    pub fn eval_boolean_with_buffer(
+        &self,
+        inputs: &HashMap<String, Vec<bool>>,
+        buffer: &mut Vec<Option<bool>>,
+    ) -> Result<HashMap<String, Vec<bool>>, BristolCircuitError> {
+        let ops = self.bool_ops()?;
+        let constants = self.bool_constants()?;
+        let seeds = self.circuit.bus_input_seeds(inputs)?;
+
+        eval_wires_into(
+            &self.circuit,
+            self.wire_count,
+            constants.iter().copied().chain(seeds),
+            &mut PreparedBoolean { ops },
+            &mut (),
+            buffer,
+        )?;
+
+        self.circuit.bus_outputs(buffer)
+    }
+
+    fn bool_ops(&self) -> Result<&[BoolOp], BristolCircuitError> {
+        if let Some(ops) = self.bool_ops.get() {
+            return Ok(ops);
+        }
+
+        // Errors aren't cached, which is fine since they're returned on every call anyway.
+        let circuit = &self.circuit;
+        circuit.classify_ops().require(OpKind::Boolean, circuit)?;
+
+        let ops = circuit
+            .gates
+            .iter()
+            .enumerate()
+            .map(|(i, gate)| BoolOp::resolve(&gate.op, gate.inputs.len(), i))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(self.bool_ops.get_or_init(|| ops))
+    }
+
+    fn bool_constants(&self) -> Result<&[(usize, bool)], BristolCircuitError> {
+        if let Some(constants) = self.bool_constants.get() {
+            return Ok(constants);
+        }
+
+        let constants = self.circuit.boolean_constant_seeds()?;
+
+        Ok(self.bool_constants.get_or_init(|| constants))
+    }
+}
+
+struct PreparedBoolean<'a> {
+    ops: &'a [BoolOp],
+}
+
+impl GateSemantics<bool> for PreparedBoolean<'_> {
+    fn literal(&mut self, op: &str, value: usize) -> Option<bool> {
+        bool_literal(op, value)
+    }
+
+    fn eval_gate(
+        &mut self,
+        _op: &str,
+        inputs: &[bool],
+        gate_index: usize,
+    ) -> Result<Vec<bool>, BristolCircuitError> {
+        Ok(self.ops[gate_index].apply(inputs))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::thread;
+
+    use super::*;
+    use crate::gadgets::{
+        self,
+        test_utils::{from_bits, to_bits},
+    };
+
+    fn adder_inputs(a: u128, b: u128) -> HashMap<String, Vec<bool>> {
+        HashMap::from([
+            ("a".to_string(), to_bits(a, 32)),
+            ("b".to_string(), to_bits(b, 32)),
+        ])
+    }
+
+    #[test]
+    fn test_concurrent_evaluation() {
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<PreparedCircuit>();
+
+        let prepared = gadgets::adder(32, true).prepare();
+
+        let handles = (0..8u128)
+            .map(|t| {
+                let prepared = Arc::clone(&prepared);
+
+                thread::spawn(move || {
+                    let mut buffer = Vec::new();
+
+                    for i in 0..50u128 {
+                        let (a, b) = (t * 1_000_003 + i, i * 7_919 + t);
+                        let outputs = prepared
+                            .eval_boolean_with_buffer(&adder_inputs(a, b), &mut buffer)
+                            .unwrap();
+
+                        let carry = from_bits(&outputs["carry_out"]);
+                        assert_eq!(from_bits(&outputs["sum"]) + (carry << 32), a + b);
+                    }
+                })
+            })
+            .collect::<Vec<_>>();
+
+        for handle in handles {
+            handle.join().unwrap();
+        }
+    }
+
+    #[test]
+    fn test_matches_unprepared() {
+        let circuit = gadgets::mux_tree(4, 3);
+        let prepared = circuit.clone().prepare();
+
+        for sel in 0..4 {
+            let mut inputs = HashMap::from([("sel".to_string(), to_bits(sel, 2))]);
+            for i in 0..4 {
+                inputs.insert(format!("in{}", i), to_bits(i as u128 + 3, 3));
+            }
+
+            assert_eq!(
+                prepared.eval_boolean(&inputs).unwrap(),
+                circuit.eval_boolean(&inputs).unwrap()
+            );
+        }
+
+        let err = prepared.eval_boolean(&HashMap::new()).unwrap_err();
+        assert!(err.to_string().contains("Missing value for input"));
+    }
+
+    #[test]
+    fn test_artifacts() {
+        let circuit = gadgets::equals(4);
+        let prepared = circuit.clone().prepare();
+
+        // 4 XORs, 4 INVs, then a 2-level AND tree.
+        assert_eq!(prepared.depth(), 4);
+        assert_eq!(prepared.levels()[0].len(), 4);
+        assert_eq!(prepared.signature(), circuit.semantic_hash());
+
+        let a0 = circuit.info.input_name_to_wire_index["a"];
+        assert_eq!(prepared.producer(a0), None);
+        assert_eq!(prepared.consumers(a0), &[0]);
+
+        let out = circuit.info.output_name_to_wire_index["out"];
+        assert_eq!(prepared.producer(out), Some(circuit.gates.len() - 1));
+        assert!(prepared.consumers(out).is_empty());
+    }
+
+    #[test]
+    fn test_non_boolean_circuit() {
+        let prepared = gadgets::sum(2).prepare();
+        let err = prepared.eval_boolean(&HashMap::new()).unwrap_err();
+
+        assert!(err.to_string().contains("Expected a pure boolean circuit"));
+    }
+}
@@ -0,0 +1,469 @@
+use std::{
+    collections::HashMap,
+    fmt::{self, Display, Formatter},
+    time::{Duration, Instant},
+};
+
+use serde::Serialize;
+
+use crate::{
+    bristol_circuit::BristolCircuit, bristol_circuit_error::BristolCircuitError,
+    eval::EvalObserver, gate::Gate,
+};
+
+/// How to split the gate list into profiled regions.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ProfileBuckets {
+    /// Consecutive runs of this many gates.
+    Fixed(usize),
+
+    /// Consecutive runs of gates at the same depth, which matches levels exactly when the gates
+    /// are listed level by level.
+    Levels,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ProfileOptions {
+    pub buckets: ProfileBuckets,
+
+    /// How many of the slowest buckets to list in `ProfileReport::hottest`.
+    pub top_k: usize,
+}
+
+impl Default for ProfileOptions {
+    fn default() -> Self {
+        ProfileOptions {
+            buckets: ProfileBuckets::Fixed(10_000),
+            top_k: 10,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct ProfileBucket {
+    /// Gate indices `start..end`.
+    pub start: usize,
+    pub end: usize,
+
+    /// The depth shared by the bucket's gates, when bucketing by level.
+    pub level: Option<usize>,
+
+    pub elapsed: Duration,
+
+    /// The most common ops in the bucket, with their counts, most common first.
+    pub dominant_ops: Vec<(String, usize)>,
+
+    /// Named outputs with at least one wire produced in the bucket.
+    pub outputs: Vec<String>,
+}
+
+impl ProfileBucket {
+    pub fn gate_count(&self) -> usize {
+        self.end - self.start
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct ProfileReport {
+    /// Wall time of the whole evaluation, including seeding inputs and collecting outputs.
+    pub total_elapsed: Duration,
+
+    /// Every bucket, in gate order.
+    pub buckets: Vec<ProfileBucket>,
+
+    /// Indices into `buckets` of the slowest buckets, slowest first.
+    pub hottest: Vec<usize>,
+}
+
+impl ProfileReport {
+    pub fn gate_count(&self) -> usize {
+        self.buckets.iter().map(ProfileBucket::gate_count).sum()
+    }
+
+    /// Time spent running gates, which excludes setup outside the gate loop.
+    pub fn gates_elapsed(&self) -> Duration {
+        self.buckets.iter().map(|bucket| bucket.elapsed).sum()
+    }
+
+    pub fn hottest_buckets(&self) -> impl Iterator<Item = &ProfileBucket> {
+        self.hottest.iter().map(|&i| &self.buckets[i])
+    }
+}
+
+const DOMINANT_OPS: usize = 3;
+
+impl BristolCircuit {
+    /// Evaluates a boolean circuit like `eval_boolean`, also timing each region of the gate list.
+    ///
+    /// Takes one timestamp per bucket, so the overhead doesn't grow with the bucket size.
+    pub fn eval_boolean_profiled(
+        &self,
+        inputs: &HashMap<String, Vec<bool>>,
+        opts: &ProfileOptions,
+    ) -> Result<(HashMap<String, Vec<bool>>, ProfileReport), BristolCircuitError> {
+        let (ranges, levels) = self.bucket_ranges(opts.buckets)?;
+
+        let mut profiler = Profiler {
+            ends: ranges.iter().map(|&(_, end)| end).collect(),
+            next: 0,
+            start: None,
+            laps: Vec::with_capacity(ranges.len()),
+        };
+
+        let started = Instant::now();
+        let outputs = self.eval_boolean_observed(inputs, &mut profiler)?;
+        let total_elapsed = started.elapsed();
+
+        let mut previous = profiler.start.unwrap_or(started);
+        let elapsed = profiler
+            .laps
+            .iter()
+            .map(|&lap| {
+                let elapsed = lap - previous;
+                previous = lap;
+                elapsed
+            })
+            .collect::<Vec<_>>();
+
+        let output_gates = self.output_gate_indices();
+
+        let buckets = ranges
+            .into_iter()
+            .zip(elapsed)
+            .zip(levels)
+            .map(|(((start, end), elapsed), level)| ProfileBucket {
+                start,
+                end,
+                level,
+                elapsed,
+                dominant_ops: self.dominant_ops(start, end),
+                outputs: output_gates
+                    .iter()
+                    .filter(|(_, gates)| gates.iter().any(|gate| (start..end).contains(gate)))
+                    .map(|(name, _)| name.clone())
+                    .collect(),
+            })
+            .collect::<Vec<_>>();
+
+        let mut hottest = (0..buckets.len()).collect::<Vec<_>>();
+        hottest.sort_by(|&a, &b| buckets[b].elapsed.cmp(&buckets[a].elapsed).then(a.cmp(&b)));
+        hottest.truncate(opts.top_k);
+
+        Ok((
+            outputs,
+            ProfileReport {
+                total_elapsed,
+                buckets,
+                hottest,
+            },
+        ))
+    }
+
+    /// The `start..end` gate ranges of each bucket, with the level of each when bucketing by level.
+    #[allow(clippy::type_complexity)]
+    fn bucket_ranges(
+        &self,
+        buckets: ProfileBuckets,
+    ) -> Result<(Vec<(usize, usize)>, Vec<Option<usize>>), BristolCircuitError> {
+        let gate_count = self.gates.len();
+
+        match buckets {
+            ProfileBuckets::Fixed(0) => Err(BristolCircuitError::Inconsistency {
+                message: "Profile bucket size must be positive".to_string(),
+            }),
+            ProfileBuckets::Fixed(size) => {
+                let ranges = (0..gate_count)
+                    .step_by(size)
+                    .map(|start| (start, (start + size).min(gate_count)))
+                    .collect::<Vec<_>>();
+                let levels = vec![None; ranges.len()];
+
+                Ok((ranges, levels))
+            }
+            ProfileBuckets::Levels => {
+                let depths = self.gate_depths();
+                let mut ranges = Vec::<(usize, usize)>::new();
+                let mut levels = Vec::new();
+
+                for (i, &depth) in depths.iter().enumerate() {
+                    match ranges.last_mut() {
+                        Some((_, end)) if levels.last() == Some(&Some(depth)) => *end = i + 1,
+                        _ => {
+                            ranges.push((i, i + 1));
+                            levels.push(Some(depth));
+                        }
+                    }
+                }
+
+                Ok((ranges, levels))
+            }
+        }
+    }
+
+    /// The depth of each gate: 1 plus the deepest gate it reads from, where inputs, constants and
+    /// wires not yet produced have depth 0.
+    pub(crate) fn gate_depths(&self) -> Vec<usize> {
+        let mut wire_depths = HashMap::<usize, usize>::new();
+
+        self.gates
+            .iter()
+            .map(|gate| {
+                let depth = 1 + gate
+                    .inputs
+                    .iter()
+                    .filter_map(|wire| wire_depths.get(wire))
+                    .max()
+                    .copied()
+                    .unwrap_or(0);
+
+                for &wire in &gate.outputs {
+                    wire_depths.insert(wire, depth);
+                }
+
+                depth
+            })
+            .collect()
+    }
+
+    fn dominant_ops(&self, start: usize, end: usize) -> Vec<(String, usize)> {
+        let mut counts = HashMap::<&str, usize>::new();
+
+        for gate in &self.gates[start..end] {
+            *counts.entry(&gate.op).or_default() += 1;
+        }
+
+        let mut counts = counts
+            .into_iter()
+            .map(|(op, count)| (op.to_string(), count))
+            .collect::<Vec<_>>();
+        counts.sort_by(|(a_op, a), (b_op, b)| b.cmp(a).then(a_op.cmp(b_op)));
+        counts.truncate(DOMINANT_OPS);
+
+        counts
+    }
+
+    /// Each named output with the indices of the gates producing its wires, sorted by name.
+    fn output_gate_indices(&self) -> Vec<(String, Vec<usize>)> {
+        let producers = self
+            .gates
+            .iter()
+            .enumerate()
+            .flat_map(|(i, gate)| gate.outputs.iter().map(move |&wire| (wire, i)))
+            .collect::<HashMap<_, _>>();
+
+        let mut outputs = self
+            .output_buses()
+            .into_iter()
+            .map(|bus| {
+                let gates = (bus.wire..bus.wire + bus.width)
+                    .filter_map(|wire| producers.get(&wire).copied())
+                    .collect();
+
+                (bus.name.to_string(), gates)
+            })
+            .collect::<Vec<_>>();
+        outputs.sort();
+
+        outputs
+    }
+}
+
+struct Profiler {
+    ends: Vec<usize>,
+    next: usize,
+    start: Option<Instant>,
+    laps: Vec<Instant>,
+}
+
+impl<V> EvalObserver<V> for Profiler {
+    fn before_gates(&mut self, _wires: &[Option<V>]) {
+        self.start = Some(Instant::now());
+    }
+
+    fn after_gate(&mut self, gate_index: usize, _gate: &Gate, _wires: &[Option<V>]) {
+        if self.ends.get(self.next) == Some(&(gate_index + 1)) {
+            self.laps.push(Instant::now());
+            self.next += 1;
+        }
+    }
+}
+
+impl Display for ProfileReport {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        let gates_elapsed = self.gates_elapsed();
+
+        writeln!(
+            f,
+            "{} gates in {} buckets, {:?} total ({:?} in gates)",
+            self.gate_count(),
+            self.buckets.len(),
+            self.total_elapsed,
+            gates_elapsed
+        )?;
+        writeln!(
+            f,
+            "{:>4}  {:<15}  {:>5}  {:>12}  {:>6}  {:<30}  outputs",
+            "rank", "gates", "level", "time", "share", "ops"
+        )?;
+
+        for (rank, bucket) in self.hottest_buckets().enumerate() {
+            let share = if gates_elapsed.is_zero() {
+                0.0
+            } else {
+                100.0 * bucket.elapsed.as_secs_f64() / gates_elapsed.as_secs_f64()
+            };
+
+            let ops = bucket
+                .dominant_ops
+                .iter()
+                .map(|(op, count)| format!("{} {}", op, count))
+                .collect::<Vec<_>>()
+                .join(", ");
+
+            writeln!(
+                f,
+                "{:>4}  {:<15}  {:>5}  {:>12}  {:>5.1}%  {:<30}  {}",
+                rank + 1,
+                format!("{}..{}", bucket.start, bucket.end),
+                bucket
+                    .level
+                    .map_or("-".to_string(), |level| level.to_string()),
+                format!("{:?}", bucket.elapsed),
+                share,
+                ops,
+                bucket.outputs.join(", ")
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gadgets::{self, test_utils::to_bits};
+
+    fn adder_inputs(a: u128, b: u128, bits: usize) -> HashMap<String, Vec<bool>> {
+        HashMap::from([
+            ("a".to_string(), to_bits(a, bits)),
+            ("b".to_string(), to_bits(b, bits)),
+        ])
+    }
+
+    #[test]
+    fn test_fixed_buckets() {
+        let circuit = gadgets::adder(32, true);
+        let inputs = adder_inputs(123_456, 654_321, 32);
+
+        let started = Instant::now();
+        let (outputs, report) = circuit
+            .eval_boolean_profiled(
+                &inputs,
+                &ProfileOptions {
+                    buckets: ProfileBuckets::Fixed(50),
+                    top_k: 2,
+                },
+            )
+            .unwrap();
+        let wall = started.elapsed();
+
+        assert_eq!(outputs, circuit.eval_boolean(&inputs).unwrap());
+
+        // Buckets partition the gate list in order.
+        assert_eq!(report.buckets.len(), circuit.gates.len().div_ceil(50));
+        assert_eq!(report.buckets[0].start, 0);
+        for pair in report.buckets.windows(2) {
+            assert_eq!(pair[0].end, pair[1].start);
+            assert_eq!(pair[0].gate_count(), 50);
+        }
+        assert_eq!(report.gate_count(), circuit.gates.len());
+
+        // Bucket times are nested within the whole run.
+        assert!(report.gates_elapsed() <= report.total_elapsed);
+        assert!(report.total_elapsed <= wall);
+
+        assert_eq!(report.hottest.len(), 2);
+        let hottest = report.hottest_buckets().collect::<Vec<_>>();
+        assert!(hottest[0].elapsed >= hottest[1].elapsed);
+        assert!(report
+            .buckets
+            .iter()
+            .all(|bucket| bucket.elapsed <= hottest[0].elapsed));
+
+        // The last sum bit and the carry out come from the final bucket.
+        let last = report.buckets.last().unwrap();
+        assert_eq!(last.outputs, vec!["carry_out", "sum"]);
+        assert_eq!(last.dominant_ops[0].0, "XOR");
+    }
+
+    #[test]
+    fn test_level_buckets() {
+        let circuit = gadgets::equals(8);
+        let inputs = HashMap::from([
+            ("a".to_string(), to_bits(0x5a, 8)),
+            ("b".to_string(), to_bits(0x5a, 8)),
+        ]);
+
+        let (outputs, report) = circuit
+            .eval_boolean_profiled(
+                &inputs,
+                &ProfileOptions {
+                    buckets: ProfileBuckets::Levels,
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+
+        assert_eq!(outputs["out"], vec![true]);
+
+        // Each XOR is followed by its INV, then the AND tree is emitted layer by layer.
+        let levels = report
+            .buckets
+            .iter()
+            .map(|bucket| (bucket.level.unwrap(), bucket.gate_count()))
+            .collect::<Vec<_>>();
+        assert_eq!(&levels[levels.len() - 3..], &[(3, 4), (4, 2), (5, 1)]);
+        assert_eq!(report.gate_count(), circuit.gates.len());
+        assert_eq!(report.buckets.last().unwrap().outputs, vec!["out"]);
+    }
+
+    #[test]
+    fn test_report_table() {
+        let circuit = gadgets::adder(4, false);
+        let (_, report) = circuit
+            .eval_boolean_profiled(
+                &adder_inputs(3, 5, 4),
+                &ProfileOptions {
+                    buckets: ProfileBuckets::Fixed(100),
+                    top_k: 5,
+                },
+            )
+            .unwrap();
+
+        let table = report.to_string();
+        let lines = table.lines().collect::<Vec<_>>();
+
+        assert!(lines[0].starts_with("14 gates in 1 buckets"));
+        assert!(lines[1].starts_with("rank  gates"));
+        assert!(lines[2].contains("0..14"));
+        assert!(lines[2].contains("XOR 11, AND 3"));
+        assert!(lines[2].ends_with("sum"));
+    }
+
+    #[test]
+    fn test_zero_bucket_size() {
+        let circuit = gadgets::adder(2, false);
+        let err = circuit
+            .eval_boolean_profiled(
+                &adder_inputs(1, 1, 2),
+                &ProfileOptions {
+                    buckets: ProfileBuckets::Fixed(0),
+                    top_k: 1,
+                },
+            )
+            .unwrap_err();
+
+        assert!(err.to_string().contains("bucket size must be positive"));
+    }
+}
@@ -0,0 +1,176 @@
+use serde::Serialize;
+
+use crate::{bristol_circuit::BristolCircuit, trace::trace_span, visit::Reachability};
+
+/// What to do with named inputs that no output depends on.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum UnusedInputs {
+    #[default]
+    Keep,
+
+    /// Remove them from the info and `io_widths`.
+    Drop,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize)]
+pub struct PruneReport {
+    pub removed_gates: usize,
+
+    /// Dropped inputs, in wire order. Always empty with `UnusedInputs::Keep`.
+    pub removed_inputs: Vec<String>,
+
+    /// How much `wire_count` shrank by.
+    pub removed_wires: usize,
+}
+
+impl BristolCircuit {
+    /// Removes the gates that no named output (or output group) depends on, and renumbers the
+    /// remaining wires densely in their original order. Inputs and constants are kept.
+    pub fn prune_dead_gates(&self) -> BristolCircuit {
+        self.prune_dead_gates_with_report(UnusedInputs::Keep).0
+    }
+
+    pub fn prune_dead_gates_with_report(
+        &self,
+        unused_inputs: UnusedInputs,
+    ) -> (BristolCircuit, PruneReport) {
+        let _span = trace_span!(
+            "pass",
+            name = "prune_dead_gates",
+            gate_count = self.gates.len()
+        );
+
+        let reachability = Reachability::new(self);
+        let reached = reachability.backward(reachability.index().output_wires());
+
+        let mut result = self.clone();
+        let mut report = PruneReport {
+            removed_gates: self.gates.len() - reached.gates.len(),
+            ..Default::default()
+        };
+
+        result.gates = reached
+            .gates
+            .iter()
+            .map(|&i| self.gates[i].clone())
+            .collect();
+
+        if unused_inputs == UnusedInputs::Drop {
+            let mut kept_widths = Vec::new();
+
+            for bus in self.input_buses() {
+                if (bus.wire..bus.wire + bus.width).any(|wire| reached.wires.contains(&wire)) {
+                    kept_widths.push(bus.width);
+                } else {
+                    result.info.input_name_to_wire_index.remove(bus.name);
+                    result.info.input_owners.remove(bus.name);
+                    report.removed_inputs.push(bus.name.to_string());
+                }
+            }
+
+            result.io_widths.0 = kept_widths;
+        }
+
+        // Compacting drops the wires that only the removed gates and inputs used.
+        let result = result.compact_wires();
+        report.removed_wires = self.wire_count.saturating_sub(result.wire_count);
+
+        (result, report)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+    use crate::circuit_builder::CircuitBuilder;
+
+    /// `out = (a & b) ^ (a | b)` takes 4 gates. Another 6 gates, including everything reading
+    /// `c`, reach no output.
+    fn circuit_with_dead_gates() -> BristolCircuit {
+        let mut b = CircuitBuilder::new();
+        let a = b.input("a");
+        let x = b.input("b");
+        let c = b.input("c");
+
+        let and = b.gate("AND", &[a, x]);
+        let dead_1 = b.gate("XOR", &[a, c]);
+        let or = b.gate("OR", &[a, x]);
+        let dead_2 = b.gate("AND", &[dead_1, and]);
+        let xor = b.gate("XOR", &[and, or]);
+        let dead_3 = b.gate("INV", &[dead_2]);
+        let dead_4 = b.gate("OR", &[dead_3, c]);
+        let dead_5 = b.gate("AND", &[dead_4, xor]);
+        b.gate("XOR", &[dead_5, dead_1]);
+        let out = b.gate("INV", &[xor]);
+        b.output("out", out);
+
+        b.build().unwrap()
+    }
+
+    fn eval(circuit: &BristolCircuit, bits: u8) -> Vec<bool> {
+        let mut inputs = HashMap::new();
+
+        for (i, name) in ["a", "b", "c"].iter().enumerate() {
+            if circuit.info.input_name_to_wire_index.contains_key(*name) {
+                inputs.insert(name.to_string(), vec![bits >> i & 1 == 1]);
+            }
+        }
+
+        circuit
+            .eval_boolean(&inputs)
+            .unwrap()
+            .remove("out")
+            .unwrap()
+    }
+
+    #[test]
+    fn test_prune() {
+        let circuit = circuit_with_dead_gates();
+        assert_eq!(circuit.gates.len(), 10);
+
+        let (pruned, report) = circuit.prune_dead_gates_with_report(UnusedInputs::Keep);
+        assert_eq!(pruned.gates.len(), 4);
+        assert_eq!(report.removed_gates, 6);
+        assert_eq!(pruned.wire_count, 7);
+        assert_eq!(report.removed_wires, circuit.wire_count - 7);
+        pruned.assert_wire_count_tight();
+        assert!(pruned.validate().is_ok());
+
+        for bits in 0..8 {
+            assert_eq!(eval(&pruned, bits), eval(&circuit, bits));
+        }
+
+        let text = pruned.get_bristol_string().unwrap();
+        let reparsed = BristolCircuit::from_info_and_bristol_string(&pruned.info, &text).unwrap();
+        assert_eq!(reparsed.gates, pruned.gates);
+        assert_eq!(reparsed.wire_count, pruned.wire_count);
+        assert_eq!(reparsed.io_widths, pruned.io_widths);
+    }
+
+    #[test]
+    fn test_drop_unused_inputs() {
+        let circuit = circuit_with_dead_gates();
+        let (pruned, report) = circuit.prune_dead_gates_with_report(UnusedInputs::Drop);
+
+        assert_eq!(report.removed_inputs, vec!["c"]);
+        assert_eq!(pruned.io_widths.0, vec![1, 1]);
+        assert_eq!(pruned.wire_count, 6);
+        assert!(!pruned.info.input_name_to_wire_index.contains_key("c"));
+        pruned.assert_wire_count_tight();
+
+        for bits in 0..4 {
+            assert_eq!(eval(&pruned, bits), eval(&circuit, bits));
+        }
+    }
+
+    #[test]
+    fn test_nothing_to_prune() {
+        let circuit = crate::gadgets::adder(4, true);
+        let pruned = circuit.prune_dead_gates();
+
+        assert_eq!(pruned.gates, circuit.gates);
+        assert_eq!(pruned.info, circuit.info);
+    }
+}
@@ -0,0 +1,254 @@
+use rand::{rngs::Xoshiro256PlusPlus, Rng, SeedableRng};
+
+use crate::{
+    bool_gate_type::Arity,
+    bristol_circuit::BristolCircuit,
+    bristol_circuit_error::BristolCircuitError,
+    circuit_builder::{CircuitBuilder, WireId},
+    gate::takes_literals,
+    op_registry::OpRegistry,
+};
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RandomCircuitConfig {
+    /// The number of named inputs.
+    pub inputs: usize,
+
+    /// The number of gates, not counting any copies needed to lay out boolean output buses.
+    pub gates: usize,
+
+    /// Ops to choose from with their relative weights. Each must be a single-output op with a
+    /// fixed arity in [`OpRegistry::default`], other than `EQ`, whose input is a literal.
+    pub ops: Vec<(String, u32)>,
+
+    /// Make inputs and outputs multi-bit buses of 1 to 8 wires, as in boolean circuits, instead of
+    /// one wire each.
+    pub boolean: bool,
+}
+
+impl Default for RandomCircuitConfig {
+    fn default() -> Self {
+        RandomCircuitConfig {
+            inputs: 4,
+            gates: 16,
+            ops: vec![
+                ("AAdd".to_string(), 2),
+                ("AMul".to_string(), 2),
+                ("ASub".to_string(), 1),
+            ],
+            boolean: false,
+        }
+    }
+}
+
+impl BristolCircuit {
+    /// A pseudorandom circuit drawn from `rng`, for fuzzing and benchmarks.
+    ///
+    /// Each gate reads uniformly chosen inputs or earlier gate outputs, so the circuit is in
+    /// topological order and single-assignment. The gate outputs nothing else reads become the
+    /// outputs, so every gate contributes to one, and the result always passes validation.
+    ///
+    /// Fails if `config` has gates but no inputs, or no usable ops.
+    pub fn random<R: Rng>(
+        config: &RandomCircuitConfig,
+        rng: &mut R,
+    ) -> Result<BristolCircuit, BristolCircuitError> {
+        let inconsistency = |message: String| BristolCircuitError::Inconsistency { message };
+
+        let registry = OpRegistry::default();
+        let ops = config
+            .ops
+            .iter()
+            .map(|(op, weight)| match registry.arity(op) {
+                Some(Arity::Fixed { inputs, outputs: 1 }) if !takes_literals(op) => {
+                    Ok((op, inputs, *weight))
+                }
+                _ => Err(inconsistency(format!(
+                    "{} isn't a fixed-arity single-output op",
+                    op
+                ))),
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        let total_weight = ops
+            .iter()
+            .map(|&(_, _, weight)| u64::from(weight))
+            .sum::<u64>();
+
+        if config.gates > 0 && (config.inputs == 0 || total_weight == 0) {
+            return Err(inconsistency(
+                "Random circuits with gates need inputs and ops".to_string(),
+            ));
+        }
+
+        let width = |rng: &mut R| {
+            if config.boolean {
+                1 + (rng.next_u64() % 8) as usize
+            } else {
+                1
+            }
+        };
+
+        let mut b = CircuitBuilder::new();
+        let mut wires = Vec::<WireId>::new();
+
+        for i in 0..config.inputs {
+            let width = width(rng);
+            wires.extend(b.input_bus(&format!("input_{}", i), width));
+        }
+
+        let first_gate_wire = wires.len();
+        let mut read = Vec::new();
+
+        for _ in 0..config.gates {
+            let mut choice = rng.next_u64() % total_weight;
+            let &(op, arity, _) = ops
+                .iter()
+                .find(|&&(_, _, weight)| {
+                    let found = choice < u64::from(weight);
+                    choice = choice.saturating_sub(u64::from(weight));
+                    found
+                })
+                .expect("choice is below the total weight");
+
+            let inputs = (0..arity)
+                .map(|_| (rng.next_u64() % wires.len() as u64) as usize)
+                .collect::<Vec<_>>();
+
+            for &input in &inputs {
+                if input >= first_gate_wire {
+                    read.resize(read.len().max(input - first_gate_wire + 1), false);
+                    read[input - first_gate_wire] = true;
+                }
+            }
+
+            let inputs = inputs.into_iter().map(|i| wires[i]).collect::<Vec<_>>();
+            wires.push(b.gate(op, &inputs));
+        }
+
+        let sinks = wires[first_gate_wire..]
+            .iter()
+            .enumerate()
+            .filter(|&(i, _)| !read.get(i).copied().unwrap_or(false))
+            .map(|(_, &wire)| wire)
+            .collect::<Vec<_>>();
+
+        let mut start = 0;
+
+        for output in 0.. {
+            if start == sinks.len() {
+                break;
+            }
+
+            let end = (start + width(rng)).min(sinks.len());
+            b.output_bus(&format!("output_{}", output), &sinks[start..end]);
+            start = end;
+        }
+
+        b.build()
+    }
+
+    /// [`BristolCircuit::random`] with a [`Xoshiro256PlusPlus`] generator seeded from `seed`, so
+    /// the same seed gives the same circuit on every platform.
+    pub fn random_seeded(
+        config: &RandomCircuitConfig,
+        seed: u64,
+    ) -> Result<BristolCircuit, BristolCircuitError> {
+        BristolCircuit::random(config, &mut Xoshiro256PlusPlus::seed_from_u64(seed))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn boolean_config() -> RandomCircuitConfig {
+        RandomCircuitConfig {
+            inputs: 3,
+            gates: 200,
+            ops: vec![
+                ("AND".to_string(), 1),
+                ("XOR".to_string(), 3),
+                ("INV".to_string(), 1),
+                ("MUX".to_string(), 1),
+            ],
+            boolean: true,
+        }
+    }
+
+    #[test]
+    fn test_deterministic() {
+        let config = boolean_config();
+
+        let seeded = |seed| BristolCircuit::random_seeded(&config, seed).unwrap();
+        assert_eq!(seeded(7), seeded(7));
+        assert_ne!(seeded(7), seeded(8));
+
+        let mut rng = Xoshiro256PlusPlus::seed_from_u64(7);
+        assert_eq!(
+            BristolCircuit::random(&config, &mut rng).unwrap(),
+            seeded(7)
+        );
+        assert_ne!(
+            BristolCircuit::random(&config, &mut rng).unwrap(),
+            seeded(7)
+        );
+    }
+
+    #[test]
+    fn test_bad_configs() {
+        let random = |config: RandomCircuitConfig| {
+            BristolCircuit::random_seeded(&config, 0)
+                .unwrap_err()
+                .to_string()
+        };
+
+        assert_eq!(
+            random(RandomCircuitConfig {
+                ops: vec![("EQ".to_string(), 1)],
+                ..Default::default()
+            }),
+            "Inconsistency: EQ isn't a fixed-arity single-output op"
+        );
+        assert_eq!(
+            random(RandomCircuitConfig {
+                inputs: 0,
+                ..Default::default()
+            }),
+            "Inconsistency: Random circuits with gates need inputs and ops"
+        );
+        assert_eq!(
+            random(RandomCircuitConfig {
+                ops: vec![("AAdd".to_string(), 0)],
+                ..Default::default()
+            }),
+            "Inconsistency: Random circuits with gates need inputs and ops"
+        );
+
+        let empty = RandomCircuitConfig {
+            inputs: 0,
+            gates: 0,
+            ..Default::default()
+        };
+        assert!(BristolCircuit::random_seeded(&empty, 0)
+            .unwrap()
+            .gates
+            .is_empty());
+    }
+
+    #[test]
+    fn test_valid() {
+        for seed in 0..20 {
+            for config in [RandomCircuitConfig::default(), boolean_config()] {
+                let circuit = BristolCircuit::random_seeded(&config, seed).unwrap();
+
+                circuit.validate().unwrap();
+                circuit.check_topological_order().unwrap();
+                assert_eq!(circuit.input_buses().len(), config.inputs);
+                assert!(circuit.gates.len() >= config.gates);
+
+                // Every gate feeds an output.
+                assert_eq!(circuit.prune_dead_gates(), circuit);
+            }
+        }
+    }
+}
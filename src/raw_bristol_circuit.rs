@@ -1,9 +1,330 @@
+use std::borrow::Cow;
+
 use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
 
-use crate::circuit_info::CircuitInfo;
+use crate::{bristol_circuit_error::BristolCircuitError, circuit_info::CircuitInfo};
 
+/// Bristol text and its info, as one document. As JSON, the `version` field says which version
+/// of this schema the document follows; [`RawBristolCircuit::from_json`] migrates older ones.
 #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub struct RawBristolCircuit {
+    /// Documents written before the field existed are version 1.
+    #[serde(default = "current_version")]
+    pub version: u32,
     pub bristol: String,
     pub info: CircuitInfo,
 }
+
+/// What [`RawBristolCircuit::from_json_with_policy`] does with fields the schema doesn't have,
+/// such as those added by a newer writer.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum UnknownFields {
+    #[default]
+    Ignore,
+    Reject,
+}
+
+const FIELDS: &[&str] = &["version", "bristol", "info"];
+
+const INFO_FIELDS: &[&str] = &[
+    "input_name_to_wire_index",
+    "constants",
+    "output_name_to_wire_index",
+    "wire_groups",
+    "input_owners",
+    "output_visibility",
+];
+
+const CONSTANT_FIELDS: &[&str] = &["value", "wire_index"];
+
+fn current_version() -> u32 {
+    RawBristolCircuit::VERSION
+}
+
+impl RawBristolCircuit {
+    /// The schema version this build writes.
+    pub const VERSION: u32 = 1;
+
+    pub fn new(bristol: String, info: CircuitInfo) -> RawBristolCircuit {
+        RawBristolCircuit {
+            version: RawBristolCircuit::VERSION,
+            bristol,
+            info,
+        }
+    }
+
+    /// Reads a JSON document of this or any earlier version, ignoring unknown fields.
+    pub fn from_json(text: &str) -> Result<RawBristolCircuit, BristolCircuitError> {
+        RawBristolCircuit::from_json_with_policy(text, UnknownFields::Ignore)
+    }
+
+    /// Like [`RawBristolCircuit::from_json`], rejecting fields the document, its info or its
+    /// constants don't have in this version.
+    pub fn from_json_strict(text: &str) -> Result<RawBristolCircuit, BristolCircuitError> {
+        RawBristolCircuit::from_json_with_policy(text, UnknownFields::Reject)
+    }
+
+    /// Reads a JSON document, migrating it to the current version first:
+    ///
+    /// - Version 0 had no constants, so its info gets an empty `constants`.
+    pub fn from_json_with_policy(
+        text: &str,
+        unknown_fields: UnknownFields,
+    ) -> Result<RawBristolCircuit, BristolCircuitError> {
+        let mut document = serde_json::from_str::<Value>(text).map_err(invalid_json)?;
+
+        let Some(fields) = document.as_object_mut() else {
+            return Err(invalid_json("expected an object"));
+        };
+
+        let version = match fields.get("version") {
+            None => 1,
+            Some(version) => version
+                .as_u64()
+                .and_then(|version| u32::try_from(version).ok())
+                .ok_or_else(|| invalid_json(format!("invalid version {}", version)))?,
+        };
+
+        if version > RawBristolCircuit::VERSION {
+            return Err(BristolCircuitError::UnsupportedVersion {
+                format: "raw circuit JSON",
+                version,
+                supported: RawBristolCircuit::VERSION,
+            });
+        }
+
+        if version == 0 {
+            if let Some(Value::Object(info)) = fields.get_mut("info") {
+                info.entry("constants")
+                    .or_insert_with(|| Value::Object(Map::new()));
+            }
+        }
+
+        fields.insert("version".to_string(), RawBristolCircuit::VERSION.into());
+
+        if unknown_fields == UnknownFields::Reject {
+            reject_unknown_fields(fields)?;
+        }
+
+        serde_json::from_value(document).map_err(invalid_json)
+    }
+
+    /// Pretty-printed JSON of the current version.
+    pub fn to_json(&self) -> Result<String, BristolCircuitError> {
+        serde_json::to_string_pretty(self).map_err(|e| BristolCircuitError::Inconsistency {
+            message: format!("Failed to serialize circuit: {}", e),
+        })
+    }
+}
+
+fn invalid_json(e: impl std::fmt::Display) -> BristolCircuitError {
+    BristolCircuitError::ParsingError {
+        message: format!("Invalid JSON: {}", e),
+    }
+}
+
+fn reject_unknown_fields(fields: &Map<String, Value>) -> Result<(), BristolCircuitError> {
+    check_fields(fields, FIELDS, "")?;
+
+    let Some(Value::Object(info)) = fields.get("info") else {
+        return Ok(());
+    };
+
+    check_fields(info, INFO_FIELDS, "info.")?;
+
+    if let Some(Value::Object(constants)) = info.get("constants") {
+        for (name, constant) in constants {
+            if let Value::Object(constant) = constant {
+                check_fields(
+                    constant,
+                    CONSTANT_FIELDS,
+                    &format!("info.constants.{}.", name),
+                )?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn check_fields(
+    fields: &Map<String, Value>,
+    known: &[&str],
+    path: &str,
+) -> Result<(), BristolCircuitError> {
+    match fields.keys().find(|key| !known.contains(&key.as_str())) {
+        None => Ok(()),
+        Some(key) => Err(BristolCircuitError::ParsingError {
+            message: format!("Unknown field {}{}", path, key),
+        }),
+    }
+}
+
+/// A [`RawBristolCircuit`] whose gate text can borrow from the buffer it was deserialized from,
+/// e.g. with `serde_json::from_slice` on a memory-mapped file.
+///
+/// serde_json can only borrow strings with no escape sequences. Bristol text spans several
+/// lines, so in a JSON document it normally contains `\n` escapes and is decoded into
+/// `Cow::Owned`; the borrowed path applies to formats and documents that keep the text
+/// unescaped.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RawBristolCircuitRef<'a> {
+    #[serde(default = "current_version")]
+    pub version: u32,
+    #[serde(borrow)]
+    pub bristol: Cow<'a, str>,
+    pub info: CircuitInfo,
+}
+
+impl RawBristolCircuitRef<'_> {
+    pub fn into_owned(self) -> RawBristolCircuit {
+        RawBristolCircuit {
+            version: self.version,
+            bristol: self.bristol.into_owned(),
+            info: self.info,
+        }
+    }
+}
+
+impl<'a> From<&'a RawBristolCircuit> for RawBristolCircuitRef<'a> {
+    fn from(raw: &'a RawBristolCircuit) -> Self {
+        RawBristolCircuitRef {
+            version: raw.version,
+            bristol: Cow::Borrowed(&raw.bristol),
+            info: raw.info.clone(),
+        }
+    }
+}
+
+impl From<RawBristolCircuitRef<'_>> for RawBristolCircuit {
+    fn from(raw: RawBristolCircuitRef<'_>) -> Self {
+        raw.into_owned()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{bristol_circuit::BristolCircuit, gadgets};
+
+    /// A circuit of one `AAdd` gate with a constant, to pin the JSON field names.
+    const GOLDEN: &str = r#"{
+  "version": 1,
+  "bristol": "1 3\n1 1\n1 1\n\n2 1 0 1 2 AAdd\n",
+  "info": {
+    "input_name_to_wire_index": {
+      "x": 0
+    },
+    "constants": {
+      "one": {
+        "value": "1",
+        "wire_index": 1
+      }
+    },
+    "output_name_to_wire_index": {
+      "y": 2
+    }
+  }
+}"#;
+
+    fn golden() -> RawBristolCircuit {
+        let mut b = crate::CircuitBuilder::new();
+        let x = b.input("x");
+        let one = b.constant("one", "1");
+        let y = b.gate("AAdd", &[x, one]);
+        b.output("y", y);
+        b.build().unwrap().to_raw().unwrap()
+    }
+
+    #[test]
+    fn test_golden_json() {
+        let raw = golden();
+        assert_eq!(raw.to_json().unwrap(), GOLDEN);
+        assert_eq!(RawBristolCircuit::from_json_strict(GOLDEN).unwrap(), raw);
+
+        // Without a version, a document is version 1.
+        let unversioned = GOLDEN.replace("\"version\": 1,", "");
+        assert_eq!(RawBristolCircuit::from_json(&unversioned).unwrap(), raw);
+        assert_eq!(
+            serde_json::from_str::<RawBristolCircuit>(&unversioned).unwrap(),
+            raw
+        );
+    }
+
+    #[test]
+    fn test_migrates_version_0() {
+        let v0 = r#"{"version":0,"bristol":"1 3\n2 1 1\n1 1\n\n2 1 0 1 2 AAdd\n","info":{"input_name_to_wire_index":{"a":0,"b":1},"output_name_to_wire_index":{"c":2}}}"#;
+
+        let raw = RawBristolCircuit::from_json_strict(v0).unwrap();
+        assert_eq!(raw.version, RawBristolCircuit::VERSION);
+        assert!(raw.info.constants.is_empty());
+        assert_eq!(BristolCircuit::from_raw(&raw).unwrap().gates.len(), 1);
+
+        // Version 1 requires them.
+        let v1 = v0.replace("\"version\":0", "\"version\":1");
+        assert!(RawBristolCircuit::from_json(&v1).is_err());
+    }
+
+    #[test]
+    fn test_unknown_fields_and_versions() {
+        let extra = GOLDEN.replace(
+            "\"wire_index\": 1",
+            "\"wire_index\": 1, \"comment\": \"unity\"",
+        );
+        assert_eq!(RawBristolCircuit::from_json(&extra).unwrap(), golden());
+        assert_eq!(
+            RawBristolCircuit::from_json_strict(&extra)
+                .unwrap_err()
+                .to_string(),
+            "Parsing error: Unknown field info.constants.one.comment"
+        );
+
+        let extra = GOLDEN.replace("\"version\": 1,", "\"version\": 1, \"checksum\": 7,");
+        assert_eq!(
+            RawBristolCircuit::from_json_with_policy(&extra, UnknownFields::Reject)
+                .unwrap_err()
+                .to_string(),
+            "Parsing error: Unknown field checksum"
+        );
+
+        let newer = GOLDEN.replace("\"version\": 1", "\"version\": 2");
+        assert_eq!(
+            RawBristolCircuit::from_json(&newer)
+                .unwrap_err()
+                .to_string(),
+            "Unsupported raw circuit JSON version 2 (this build reads up to version 1)"
+        );
+
+        assert!(RawBristolCircuit::from_json("[]").is_err());
+        assert!(RawBristolCircuit::from_json(r#"{"version":"1"}"#).is_err());
+    }
+
+    #[test]
+    fn test_unescaped_text_is_borrowed() {
+        let json = br#"{"bristol":"0 0","info":{"input_name_to_wire_index":{},"constants":{},"output_name_to_wire_index":{}}}"#;
+
+        let raw: RawBristolCircuitRef = serde_json::from_slice(json).unwrap();
+        assert!(matches!(raw.bristol, Cow::Borrowed("0 0")));
+    }
+
+    #[test]
+    fn test_escaped_text_is_owned() {
+        let circuit = gadgets::adder(4, false);
+        let json = serde_json::to_vec(&circuit.to_raw().unwrap()).unwrap();
+
+        let raw: RawBristolCircuitRef = serde_json::from_slice(&json).unwrap();
+        assert!(matches!(raw.bristol, Cow::Owned(_)));
+
+        let parsed = BristolCircuit::from_raw_ref(&raw).unwrap();
+        assert_eq!(parsed.gates, circuit.gates);
+        assert_eq!(parsed.info, circuit.info);
+
+        let owned = RawBristolCircuit::from(raw);
+        assert_eq!(owned, circuit.to_raw().unwrap());
+        assert_eq!(
+            BristolCircuit::from_raw(&owned).unwrap().gates,
+            circuit.gates
+        );
+    }
+}
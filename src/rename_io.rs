@@ -0,0 +1,271 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    hash::Hash,
+};
+
+use crate::{
+    bristol_circuit::BristolCircuit, bristol_circuit_error::BristolCircuitError,
+    circuit_info::CircuitInfo,
+};
+
+impl CircuitInfo {
+    /// Renames an input, and its owner entry if it has one. The new name can't be another
+    /// input's or a constant's.
+    pub fn rename_input(&mut self, old: &str, new: &str) -> Result<(), BristolCircuitError> {
+        if !self.input_name_to_wire_index.contains_key(old) {
+            return Err(inconsistency(format!("No input named {}", old)));
+        }
+
+        if old != new
+            && (self.input_name_to_wire_index.contains_key(new) || self.constants.contains_key(new))
+        {
+            return Err(inconsistency(format!(
+                "Can't rename input {} to {}, which is taken",
+                old, new
+            )));
+        }
+
+        move_entry(&mut self.input_name_to_wire_index, old, new);
+        move_entry(&mut self.input_owners, old, new);
+
+        Ok(())
+    }
+
+    /// Renames an output, and its visibility entry if it has one. The new name can't be another
+    /// output's.
+    pub fn rename_output(&mut self, old: &str, new: &str) -> Result<(), BristolCircuitError> {
+        if !self.output_name_to_wire_index.contains_key(old) {
+            return Err(inconsistency(format!("No output named {}", old)));
+        }
+
+        if old != new && self.output_name_to_wire_index.contains_key(new) {
+            return Err(inconsistency(format!(
+                "Can't rename output {} to {}, which is taken",
+                old, new
+            )));
+        }
+
+        move_entry(&mut self.output_name_to_wire_index, old, new);
+        move_entry(&mut self.output_visibility, old, new);
+
+        Ok(())
+    }
+}
+
+impl BristolCircuit {
+    /// Renames inputs, outputs and constants all at once, so names can be swapped. Since inputs
+    /// and outputs are named separately, a name that is both an input's and an output's renames
+    /// both. Input owners and output visibility follow their input or output; wire groups keep
+    /// their names.
+    ///
+    /// Every old name must exist, and no two inputs or constants, or two outputs, may end up with
+    /// the same name. Otherwise nothing is renamed, and the error lists every problem.
+    pub fn rename_io(
+        &mut self,
+        mapping: &HashMap<String, String>,
+    ) -> Result<(), BristolCircuitError> {
+        let info = &mut self.info;
+        let mut problems = Vec::new();
+
+        let mut olds = mapping.keys().collect::<Vec<_>>();
+        olds.sort();
+
+        for old in olds {
+            if !info.input_name_to_wire_index.contains_key(old)
+                && !info.output_name_to_wire_index.contains_key(old)
+                && !info.constants.contains_key(old)
+            {
+                problems.push(format!("No input, output or constant named {}", old));
+            }
+        }
+
+        let rename = |name: &String| mapping.get(name).unwrap_or(name).clone();
+
+        let sources = info
+            .input_name_to_wire_index
+            .keys()
+            .chain(info.constants.keys());
+
+        for (kind, names) in [
+            ("Inputs or constants", sources.collect::<Vec<_>>()),
+            ("Outputs", info.output_name_to_wire_index.keys().collect()),
+        ] {
+            let mut renamed = BTreeMap::<String, Vec<&str>>::new();
+
+            for name in names {
+                renamed.entry(rename(name)).or_default().push(name);
+            }
+
+            for (new, mut olds) in renamed.into_iter().filter(|(_, olds)| olds.len() > 1) {
+                olds.sort();
+                problems.push(format!(
+                    "{} {} would all be named {}",
+                    kind,
+                    olds.join(", "),
+                    new
+                ));
+            }
+        }
+
+        if !problems.is_empty() {
+            return Err(inconsistency(problems.join("; ")));
+        }
+
+        rename_keys(&mut info.input_name_to_wire_index, rename);
+        rename_keys(&mut info.output_name_to_wire_index, rename);
+        rename_keys(&mut info.constants, rename);
+        rename_keys(&mut info.input_owners, rename);
+        rename_keys(&mut info.output_visibility, rename);
+
+        Ok(())
+    }
+
+    /// Applies `(old, new)` renames in order, each as [`BristolCircuit::rename_io`] does, e.g. to
+    /// replace the `input0`, `output0`, ... names of [`BristolCircuit::from_bristol_string`].
+    pub fn with_renamed_io(
+        mut self,
+        renames: &[(&str, &str)],
+    ) -> Result<BristolCircuit, BristolCircuitError> {
+        for &(old, new) in renames {
+            self.rename_io(&HashMap::from([(old.to_string(), new.to_string())]))?;
+        }
+
+        Ok(self)
+    }
+}
+
+fn inconsistency(message: String) -> BristolCircuitError {
+    BristolCircuitError::Inconsistency { message }
+}
+
+fn move_entry<T>(map: &mut HashMap<String, T>, old: &str, new: &str) {
+    if let Some(value) = map.remove(old) {
+        map.insert(new.to_string(), value);
+    }
+}
+
+fn rename_keys<K: Eq + Hash, T>(map: &mut HashMap<K, T>, rename: impl Fn(&K) -> K) {
+    *map = map
+        .drain()
+        .map(|(key, value)| (rename(&key), value))
+        .collect();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{circuit_builder::CircuitBuilder, gadgets, party::PartyId, visibility::Visibility};
+
+    fn mapping(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+        pairs
+            .iter()
+            .map(|&(old, new)| (old.to_string(), new.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_names_a_bare_circuit() {
+        let adder = gadgets::adder(4, true);
+        let text = adder.get_bristol_string().unwrap();
+
+        let bare = BristolCircuit::from_bristol_string(&text).unwrap();
+        assert!(bare.info.constants.is_empty());
+        assert_eq!(bare.gates, adder.gates);
+
+        let named = bare
+            .with_renamed_io(&[
+                ("input0", "a"),
+                ("input1", "b"),
+                ("output0", "sum"),
+                ("output1", "carry_out"),
+            ])
+            .unwrap();
+        assert_eq!(named, adder);
+
+        assert_eq!(
+            BristolCircuit::read_bristol(&mut text.as_bytes()).unwrap(),
+            text.parse().unwrap()
+        );
+    }
+
+    #[test]
+    fn test_rename_input_and_output() {
+        let mut info = gadgets::adder(2, false).info;
+        info.input_owners.insert("a".to_string(), PartyId(1));
+        info.output_visibility
+            .insert("sum".to_string(), Visibility::Hidden);
+
+        info.rename_input("a", "x").unwrap();
+        info.rename_output("sum", "total").unwrap();
+        assert_eq!(info.input_name_to_wire_index["x"], 0);
+        assert_eq!(info.input_owners["x"], PartyId(1));
+        assert_eq!(info.output_visibility["total"], Visibility::Hidden);
+
+        let err = info.rename_input("x", "b").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Inconsistency: Can't rename input x to b, which is taken"
+        );
+        assert!(info.rename_input("a", "y").is_err());
+        assert!(info.rename_output("x", "y").is_err());
+    }
+
+    #[test]
+    fn test_rename_io() {
+        // `x` is both an input and an output, and `one` a constant.
+        let mut b = CircuitBuilder::new();
+        let x = b.input("x");
+        let y = b.input("y");
+        let one = b.constant("one", "1");
+        let sum = b.gate("AAdd", &[x, one]);
+        let product = b.gate("AMul", &[sum, y]);
+        b.output("x", product);
+        b.output("y", sum);
+        let circuit = b.build().unwrap();
+
+        // Swapping x and y, in the inputs and the outputs.
+        let mut renamed = circuit.clone();
+        renamed
+            .rename_io(&mapping(&[("x", "y"), ("y", "x"), ("one", "unit")]))
+            .unwrap();
+        let info = &renamed.info;
+        assert_eq!(
+            info.input_name_to_wire_index["y"],
+            circuit.info.input_name_to_wire_index["x"]
+        );
+        assert_eq!(
+            info.output_name_to_wire_index["x"],
+            circuit.info.output_name_to_wire_index["y"]
+        );
+        assert_eq!(info.constants["unit"], circuit.info.constants["one"]);
+
+        let inputs = HashMap::from([("x".to_string(), 3), ("y".to_string(), 5)]);
+        let swapped = HashMap::from([("y".to_string(), 3), ("x".to_string(), 5)]);
+        let outputs = circuit.eval_arithmetic(&inputs, None).unwrap();
+        let renamed_outputs = renamed.eval_arithmetic(&swapped, None).unwrap();
+        assert_eq!(
+            (renamed_outputs["x"], renamed_outputs["y"]),
+            (outputs["y"], outputs["x"])
+        );
+
+        // Every problem is listed, and nothing is renamed.
+        let mut unchanged = circuit.clone();
+        let err = unchanged
+            .rename_io(&mapping(&[("x", "one"), ("z", "w"), ("y", "one")]))
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Inconsistency: No input, output or constant named z; Inputs or constants one, x, y \
+             would all be named one; Outputs x, y would all be named one"
+        );
+        assert_eq!(unchanged, circuit);
+
+        let err = circuit
+            .with_renamed_io(&[("x", "t"), ("q", "r")])
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Inconsistency: No input, output or constant named q"
+        );
+    }
+}
@@ -0,0 +1,194 @@
+use std::{collections::HashMap, ops::ControlFlow};
+
+use crate::{
+    bristol_circuit::BristolCircuit,
+    circuit_info::ConstantInfo,
+    gate::Gate,
+    op_properties::normalized_gate_key,
+    visit::{walk, CircuitVisitor},
+};
+
+const FNV_OFFSET: u64 = 0xcbf2_9ce4_8422_2325;
+const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+
+/// FNV-1a, which is stable across platforms and Rust versions, unlike `DefaultHasher`.
+#[derive(Clone, Copy)]
+pub(crate) struct Fnv(u64);
+
+impl Fnv {
+    pub fn new() -> Self {
+        Fnv(FNV_OFFSET)
+    }
+
+    pub fn bytes(mut self, bytes: &[u8]) -> Self {
+        for &byte in bytes {
+            self.0 ^= u64::from(byte);
+            self.0 = self.0.wrapping_mul(FNV_PRIME);
+        }
+
+        self
+    }
+
+    /// Hashes a length-prefixed string, so adjacent strings can't run together.
+    pub fn str(self, s: &str) -> Self {
+        self.u64(s.len() as u64).bytes(s.as_bytes())
+    }
+
+    pub fn u64(self, value: u64) -> Self {
+        self.bytes(&value.to_le_bytes())
+    }
+
+    pub fn finish(self) -> u64 {
+        self.0
+    }
+}
+
+impl BristolCircuit {
+    /// A hash of what the circuit computes structurally, independent of wire numbering and of
+    /// the order of independent gates.
+    ///
+    /// Each wire is hashed from how it is produced: inputs by name and bit, constants by name and
+    /// value, and gate outputs by op and the hashes of their inputs (sorted for commutative ops).
+    /// The result combines the named outputs' wire hashes. Unused gates don't contribute.
+    pub fn semantic_hash(&self) -> u64 {
+        let mut hasher = SemanticHasher::default();
+        let _ = walk(self, &mut hasher);
+
+        hasher.outputs.sort();
+        hasher
+            .outputs
+            .into_iter()
+            .fold(Fnv::new().str("circuit"), |hash, (_, output)| {
+                hash.u64(output)
+            })
+            .finish()
+    }
+}
+
+#[derive(Default)]
+struct SemanticHasher {
+    wire_hashes: HashMap<usize, u64>,
+    outputs: Vec<(String, u64)>,
+}
+
+impl CircuitVisitor for SemanticHasher {
+    fn visit_input(&mut self, name: &str, wire: usize, width: usize) -> ControlFlow<()> {
+        for bit in 0..width {
+            let hash = Fnv::new().str("input").str(name).u64(bit as u64);
+            self.wire_hashes.insert(wire + bit, hash.finish());
+        }
+
+        ControlFlow::Continue(())
+    }
+
+    fn visit_constant(&mut self, name: &str, constant: &ConstantInfo) -> ControlFlow<()> {
+        let hash = Fnv::new().str("constant").str(name).str(&constant.value);
+        self.wire_hashes.insert(constant.wire_index, hash.finish());
+
+        ControlFlow::Continue(())
+    }
+
+    fn visit_gate(&mut self, _index: usize, gate: &Gate) -> ControlFlow<()> {
+        let inputs = gate
+            .inputs
+            .iter()
+            .map(|wire| {
+                self.wire_hashes
+                    .get(wire)
+                    .copied()
+                    .unwrap_or_else(|| Fnv::new().str("undefined").finish())
+            })
+            .collect::<Vec<_>>();
+
+        let (op, inputs) = normalized_gate_key(&gate.op, &inputs);
+        let mut hash = Fnv::new().str(&op).u64(inputs.len() as u64);
+
+        for input in inputs {
+            hash = hash.u64(input);
+        }
+
+        for (i, &wire) in gate.outputs.iter().enumerate() {
+            self.wire_hashes.insert(wire, hash.u64(i as u64).finish());
+        }
+
+        ControlFlow::Continue(())
+    }
+
+    fn visit_output(&mut self, name: &str, wire: usize, width: usize) -> ControlFlow<()> {
+        let mut hash = Fnv::new().str(name).u64(width as u64);
+
+        for wire in wire..wire + width {
+            hash = hash.u64(self.wire_hashes.get(&wire).copied().unwrap_or(0));
+        }
+
+        self.outputs.push((name.to_string(), hash.finish()));
+
+        ControlFlow::Continue(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gadgets;
+
+    #[test]
+    fn test_fnv_reference() {
+        assert_eq!(Fnv::new().finish(), 0xcbf2_9ce4_8422_2325);
+        assert_eq!(Fnv::new().bytes(b"a").finish(), 0xaf63_dc4c_8601_ec8c);
+    }
+
+    #[test]
+    fn test_golden_values() {
+        assert_eq!(
+            gadgets::adder(4, true).semantic_hash(),
+            0x6ed8_4bc4_8d50_451e
+        );
+        assert_eq!(
+            gadgets::adder(8, false).semantic_hash(),
+            0x9587_051a_7f46_facc
+        );
+    }
+
+    #[test]
+    fn test_invariant_under_renumbering() {
+        let circuit = gadgets::adder(4, true);
+
+        let mut shifted = circuit.clone();
+        shifted.map_wires(|wire| wire + 10);
+        shifted.recompute_wire_count();
+
+        assert_eq!(circuit.semantic_hash(), shifted.semantic_hash());
+    }
+
+    #[test]
+    fn test_commutative_inputs() {
+        let mut circuit = gadgets::adder(2, false);
+        let hash = circuit.semantic_hash();
+
+        circuit.gates[0].inputs.reverse();
+        assert_eq!(circuit.semantic_hash(), hash);
+    }
+
+    #[test]
+    fn test_sensitive_to_structure() {
+        let circuit = gadgets::adder(2, false);
+        let hash = circuit.semantic_hash();
+
+        let mut changed_op = circuit.clone();
+        changed_op.gates[0].op = "AND".into();
+        assert_ne!(changed_op.semantic_hash(), hash);
+
+        let mut renamed = circuit.clone();
+        let wire = renamed
+            .info
+            .output_name_to_wire_index
+            .remove("sum")
+            .unwrap();
+        renamed
+            .info
+            .output_name_to_wire_index
+            .insert("total".to_string(), wire);
+        assert_ne!(renamed.semantic_hash(), hash);
+    }
+}
@@ -0,0 +1,252 @@
+use std::collections::{HashMap, HashSet};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    bristol_circuit::BristolCircuit, gate::Gate, split_mix::SplitMix64, trace::trace_span,
+};
+
+/// A renumbering of wires, mapping each original wire index to its new index.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WirePermutation {
+    forward: Vec<usize>,
+}
+
+impl WirePermutation {
+    /// The new index of `original`.
+    pub fn apply(&self, original: usize) -> usize {
+        self.forward[original]
+    }
+
+    /// The original index of the wire now numbered `shuffled`.
+    pub fn invert(&self, shuffled: usize) -> usize {
+        self.forward
+            .iter()
+            .position(|&wire| wire == shuffled)
+            .expect("wire is outside the permutation")
+    }
+
+    pub fn inverse(&self) -> WirePermutation {
+        let mut backward = vec![0; self.forward.len()];
+
+        for (original, &shuffled) in self.forward.iter().enumerate() {
+            backward[shuffled] = original;
+        }
+
+        WirePermutation { forward: backward }
+    }
+
+    pub fn len(&self) -> usize {
+        self.forward.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.forward.is_empty()
+    }
+}
+
+impl BristolCircuit {
+    /// Pseudorandomly renumbers internal wires and reorders independent gates, deterministically
+    /// for a given `seed`.
+    ///
+    /// Wires of named inputs, outputs and wire groups keep their indices, so the interface is
+    /// unchanged. Internal wires and constants are permuted among themselves. Gates are emitted in
+    /// a random topological order, so the result evaluates identically.
+    pub fn shuffle(&self, seed: u64) -> (BristolCircuit, WirePermutation) {
+        let _span = trace_span!("pass", name = "shuffle", gate_count = self.gates.len());
+
+        let mut rng = SplitMix64::new(seed);
+        let wire_count = self.wire_count.max(self.referenced_wire_count());
+
+        let fixed = self
+            .input_buses()
+            .into_iter()
+            .chain(self.output_buses())
+            .flat_map(|bus| bus.wire..bus.wire + bus.width)
+            .chain(
+                self.info
+                    .wire_groups
+                    .values()
+                    .flat_map(|group| group.span.wires()),
+            )
+            .collect::<HashSet<_>>();
+
+        let movable = (0..wire_count)
+            .filter(|wire| !fixed.contains(wire))
+            .collect::<Vec<_>>();
+
+        let mut targets = movable.clone();
+        shuffle_in_place(&mut targets, &mut rng);
+
+        let mut forward = (0..wire_count).collect::<Vec<_>>();
+        for (&from, &to) in movable.iter().zip(&targets) {
+            forward[from] = to;
+        }
+
+        let permutation = WirePermutation { forward };
+
+        let mut result = self.clone();
+        result.gates = random_topological_order(&self.gates, &mut rng);
+
+        // `map_wires` leaves `EQ` literals alone, which the permutation would otherwise rewrite.
+        result.map_wires(|wire| permutation.apply(wire));
+
+        (result, permutation)
+    }
+}
+
+fn shuffle_in_place<T>(items: &mut [T], rng: &mut SplitMix64) {
+    for i in (1..items.len()).rev() {
+        let j = (rng.next_u64() % (i as u64 + 1)) as usize;
+        items.swap(i, j);
+    }
+}
+
+/// Kahn's algorithm, picking uniformly among the ready gates at each step. Gates reading wires no
+/// gate produces have no dependency on them.
+fn random_topological_order(gates: &[Gate], rng: &mut SplitMix64) -> Vec<Gate> {
+    let producers = gates
+        .iter()
+        .enumerate()
+        .flat_map(|(i, gate)| gate.outputs.iter().map(move |&wire| (wire, i)))
+        .collect::<HashMap<_, _>>();
+
+    let mut pending = vec![0; gates.len()];
+    let mut consumers = vec![Vec::new(); gates.len()];
+
+    for (i, gate) in gates.iter().enumerate() {
+        let dependencies = gate
+            .input_wires()
+            .iter()
+            .filter_map(|wire| producers.get(wire))
+            .filter(|&&producer| producer != i)
+            .collect::<HashSet<_>>();
+
+        pending[i] = dependencies.len();
+
+        for &producer in dependencies {
+            consumers[producer].push(i);
+        }
+    }
+
+    let mut ready = (0..gates.len())
+        .filter(|&i| pending[i] == 0)
+        .collect::<Vec<_>>();
+    let mut order = Vec::with_capacity(gates.len());
+
+    while !ready.is_empty() {
+        let i = ready.swap_remove((rng.next_u64() % ready.len() as u64) as usize);
+        order.push(gates[i].clone());
+
+        for &consumer in &consumers[i] {
+            pending[consumer] -= 1;
+
+            if pending[consumer] == 0 {
+                ready.push(consumer);
+            }
+        }
+    }
+
+    // Gates on a cycle can't be ordered; keep them, in their original order, at the end.
+    if order.len() < gates.len() {
+        order.extend(
+            (0..gates.len())
+                .filter(|&i| pending[i] > 0)
+                .map(|i| gates[i].clone()),
+        );
+    }
+
+    order
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gadgets::{self, test_utils::eval_ints};
+
+    #[test]
+    fn test_shuffle_preserves_behavior() {
+        let circuit = gadgets::adder(8, true);
+        let (shuffled, permutation) = circuit.shuffle(42);
+
+        assert_ne!(shuffled.gates, circuit.gates);
+        assert_eq!(shuffled.info, circuit.info);
+        assert_eq!(shuffled.semantic_hash(), circuit.semantic_hash());
+
+        for (a, b) in [(0, 0), (255, 1), (100, 200), (77, 91)] {
+            let inputs = [("a", a), ("b", b)];
+            assert_eq!(eval_ints(&shuffled, &inputs), eval_ints(&circuit, &inputs));
+        }
+
+        // Every original gate is present, renumbered by the permutation.
+        let mut renumbered = circuit
+            .gates
+            .iter()
+            .map(|gate| {
+                let mut gate = gate.clone();
+                for wire in gate.inputs.iter_mut().chain(gate.outputs.iter_mut()) {
+                    *wire = permutation.apply(*wire);
+                }
+                gate.to_string()
+            })
+            .collect::<Vec<_>>();
+        let mut actual = shuffled
+            .gates
+            .iter()
+            .map(Gate::to_string)
+            .collect::<Vec<_>>();
+        renumbered.sort();
+        actual.sort();
+        assert_eq!(renumbered, actual);
+    }
+
+    #[test]
+    fn test_eq_literals_survive() {
+        // Wires 0 and 1 are internal, so move, but the `EQ` literals 0 and 1 mustn't.
+        let circuit = BristolCircuit::from_bristol_string(
+            "3 4\n0\n1 1\n\n1 1 1 0 EQ\n1 1 0 1 EQ\n2 1 0 1 3 XOR\n",
+        )
+        .unwrap();
+        let expected = circuit.eval_boolean(&HashMap::new()).unwrap();
+        assert_eq!(expected["output0"], vec![true]);
+
+        for seed in 0..16 {
+            let (shuffled, _) = circuit.shuffle(seed);
+            assert_eq!(shuffled.eval_boolean(&HashMap::new()).unwrap(), expected);
+
+            let mut literals = shuffled
+                .gates
+                .iter()
+                .filter(|gate| gate.op == "EQ")
+                .map(|gate| gate.inputs[0])
+                .collect::<Vec<_>>();
+            literals.sort();
+            assert_eq!(literals, vec![0, 1]);
+        }
+    }
+
+    #[test]
+    fn test_seed_reproducibility() {
+        let circuit = gadgets::mux_tree(5, 3);
+
+        assert_eq!(circuit.shuffle(7), circuit.shuffle(7));
+        assert_ne!(circuit.shuffle(7).0, circuit.shuffle(8).0);
+    }
+
+    #[test]
+    fn test_permutation_inverse() {
+        let circuit = gadgets::equals(6);
+        let (_, permutation) = circuit.shuffle(1);
+        let inverse = permutation.inverse();
+
+        for wire in 0..permutation.len() {
+            assert_eq!(inverse.apply(permutation.apply(wire)), wire);
+            assert_eq!(permutation.invert(permutation.apply(wire)), wire);
+        }
+
+        // Interface wires keep their indices.
+        for &wire in circuit.info.input_name_to_wire_index.values() {
+            assert_eq!(permutation.apply(wire), wire);
+        }
+    }
+}
@@ -0,0 +1,200 @@
+use std::collections::{HashMap, VecDeque};
+
+use serde::Serialize;
+
+use crate::{bristol_circuit::BristolCircuit, bristol_circuit_error::BristolCircuitError};
+
+/// A summary of a circuit's size and shape.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize)]
+pub struct CircuitStats {
+    pub gate_count: usize,
+    pub wire_count: usize,
+
+    /// How many gates use each op.
+    pub op_counts: HashMap<String, usize>,
+
+    pub input_count: usize,
+
+    /// The total width of the named inputs, in wires.
+    pub input_width: usize,
+
+    pub output_count: usize,
+
+    /// The total width of the named outputs, in wires.
+    pub output_width: usize,
+
+    /// The most gate inputs reading any one wire.
+    pub max_fan_out: usize,
+
+    /// The length of the longest chain of gates, each reading an output of the one before.
+    pub depth: usize,
+}
+
+impl BristolCircuit {
+    /// Gathers [`CircuitStats`]. The depth follows wires rather than gate order, so gates don't
+    /// need to be topologically sorted, but gates reading each other's outputs in a cycle are an
+    /// error.
+    pub fn stats(&self) -> Result<CircuitStats, BristolCircuitError> {
+        let mut op_counts = HashMap::<String, usize>::new();
+        let mut fan_out = HashMap::<usize, usize>::new();
+
+        for gate in &self.gates {
+            *op_counts.entry(gate.op.to_string()).or_default() += 1;
+
+            for &wire in gate.input_wires() {
+                *fan_out.entry(wire).or_default() += 1;
+            }
+        }
+
+        let input_buses = self.input_buses();
+        let output_buses = self.output_buses();
+
+        Ok(CircuitStats {
+            gate_count: self.gates.len(),
+            wire_count: self.wire_count,
+            op_counts,
+            input_count: input_buses.len(),
+            input_width: input_buses.iter().map(|bus| bus.width).sum(),
+            output_count: output_buses.len(),
+            output_width: output_buses.iter().map(|bus| bus.width).sum(),
+            max_fan_out: fan_out.into_values().max().unwrap_or(0),
+            depth: self.unordered_gate_depths()?.into_iter().max().unwrap_or(0),
+        })
+    }
+
+    /// Like `gate_depths`, but a gate's depth counts every gate producing a wire it reads,
+    /// wherever that gate is in the list.
+    fn unordered_gate_depths(&self) -> Result<Vec<usize>, BristolCircuitError> {
+        let mut producers = HashMap::<usize, Vec<usize>>::new();
+
+        for (i, gate) in self.gates.iter().enumerate() {
+            for &wire in &gate.outputs {
+                producers.entry(wire).or_default().push(i);
+            }
+        }
+
+        // For each gate, the gates reading its outputs, and how many producers it waits on.
+        let mut readers = vec![Vec::new(); self.gates.len()];
+        let mut waiting = vec![0usize; self.gates.len()];
+
+        for (i, gate) in self.gates.iter().enumerate() {
+            for wire in gate.input_wires() {
+                for &producer in producers.get(wire).into_iter().flatten() {
+                    readers[producer].push(i);
+                    waiting[i] += 1;
+                }
+            }
+        }
+
+        let mut depths = vec![1; self.gates.len()];
+        let mut ready = (0..self.gates.len())
+            .filter(|&i| waiting[i] == 0)
+            .collect::<VecDeque<_>>();
+        let mut done = 0;
+
+        while let Some(i) = ready.pop_front() {
+            done += 1;
+
+            for j in std::mem::take(&mut readers[i]) {
+                depths[j] = depths[j].max(depths[i] + 1);
+                waiting[j] -= 1;
+
+                if waiting[j] == 0 {
+                    ready.push_back(j);
+                }
+            }
+        }
+
+        if done < self.gates.len() {
+            let i = (0..self.gates.len()).find(|&i| waiting[i] > 0).unwrap();
+
+            return Err(BristolCircuitError::Inconsistency {
+                message: format!("Gate {} ({}) is part of a cycle", i, self.gates[i].op),
+            });
+        }
+
+        Ok(depths)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{circuit_builder::CircuitBuilder, gadgets};
+
+    #[test]
+    fn test_adder_stats() {
+        let circuit = gadgets::adder(4, true);
+        let stats = circuit.stats().unwrap();
+
+        assert_eq!(stats.gate_count, circuit.gates.len());
+        assert_eq!(stats.wire_count, circuit.wire_count);
+        assert_eq!(stats.op_counts.values().sum::<usize>(), circuit.gates.len());
+        assert_eq!((stats.input_count, stats.input_width), (2, 8));
+        assert_eq!((stats.output_count, stats.output_width), (2, 5));
+        assert_eq!(
+            stats.depth,
+            circuit.gate_depths().into_iter().max().unwrap()
+        );
+
+        let json = serde_json::to_value(&stats).unwrap();
+        assert_eq!(json["depth"], stats.depth);
+    }
+
+    /// A two-output `SPLIT` gate feeding an `AND`, which feeds an `XOR` with the other output.
+    fn multi_output() -> BristolCircuit {
+        let mut b = CircuitBuilder::new();
+        let x = b.input("x");
+        let y = b.input("y");
+        let s0 = b.gate("SPLIT", &[x]);
+        let s1 = b.gate("SPLIT", &[x]);
+        let a = b.gate("AND", &[s0, y]);
+        let c = b.gate("XOR", &[s1, a]);
+        b.output("a", a);
+        b.output("c", c);
+
+        let mut circuit = b.build().unwrap();
+        let second = circuit.gates.remove(1);
+        circuit.gates[0].outputs.extend(second.outputs);
+        circuit
+    }
+
+    #[test]
+    fn test_multi_output_and_fan_out() {
+        let stats = multi_output().stats().unwrap();
+
+        assert_eq!(stats.depth, 3);
+        assert_eq!(stats.op_counts["SPLIT"], 1);
+        assert_eq!(stats.max_fan_out, 1);
+        assert_eq!((stats.output_count, stats.output_width), (2, 2));
+    }
+
+    #[test]
+    fn test_unsorted_and_cycle() {
+        let mut circuit = multi_output();
+        circuit.gates.reverse();
+        assert_eq!(circuit.stats().unwrap().depth, 3);
+
+        // SPLIT reads the XOR's output, which depends on SPLIT.
+        circuit.gates[2].inputs[0] = circuit.gates[0].outputs[0];
+        let err = circuit.stats().unwrap_err();
+        assert!(err.to_string().contains("is part of a cycle"), "{}", err);
+    }
+
+    #[test]
+    fn test_eq_literals_are_not_wires() {
+        // Gate 0 would read its own output if its literal were a wire.
+        let circuit =
+            BristolCircuit::from_bristol_string("2 3\n1 1\n2 1 1\n\n1 1 1 1 EQ\n1 1 0 2 EQW\n")
+                .unwrap();
+        assert_eq!(circuit.stats().unwrap().depth, 1);
+
+        let circuit = BristolCircuit::from_bristol_string(
+            "3 4\n1 1\n1 1\n\n1 1 0 1 EQ\n1 1 0 2 EQ\n2 1 0 1 3 AND\n",
+        )
+        .unwrap();
+        let stats = circuit.stats().unwrap();
+        assert_eq!(stats.max_fan_out, 1);
+        assert_eq!(stats.depth, 2);
+    }
+}
@@ -0,0 +1,195 @@
+use std::{
+    cmp::Reverse,
+    collections::{BinaryHeap, HashMap, HashSet},
+};
+
+use crate::{
+    bristol_circuit::BristolCircuit, bristol_circuit_error::BristolCircuitError,
+    gate::takes_literals, trace::trace_span,
+};
+
+impl BristolCircuit {
+    /// Reorders the gates so each one comes after the gates producing its inputs, using Kahn's
+    /// algorithm. Among the gates that are ready at each step, the one earliest in the original
+    /// order goes first, so an already sorted circuit is unchanged.
+    ///
+    /// Inputs, constants and wires no gate produces are sources. `EQ` inputs are literal values,
+    /// not wires. A dependency cycle is an error naming the gates on it.
+    pub fn topological_sort(&self) -> Result<BristolCircuit, BristolCircuitError> {
+        let _span = trace_span!(
+            "pass",
+            name = "topological_sort",
+            gate_count = self.gates.len()
+        );
+
+        let order = self.topological_order()?;
+
+        let mut result = self.clone();
+        result.gates = order.into_iter().map(|i| self.gates[i].clone()).collect();
+
+        Ok(result)
+    }
+
+    /// The gate indices in the order [`BristolCircuit::topological_sort`] puts them.
+    pub(crate) fn topological_order(&self) -> Result<Vec<usize>, BristolCircuitError> {
+        let producers = self
+            .gates
+            .iter()
+            .enumerate()
+            .flat_map(|(i, gate)| gate.outputs.iter().map(move |&wire| (wire, i)))
+            .collect::<HashMap<_, _>>();
+
+        let mut pending = vec![0; self.gates.len()];
+        let mut consumers = vec![Vec::new(); self.gates.len()];
+
+        for (i, gate) in self.gates.iter().enumerate() {
+            if takes_literals(&gate.op) {
+                continue;
+            }
+
+            let dependencies = gate
+                .inputs
+                .iter()
+                .filter_map(|wire| producers.get(wire).copied())
+                .collect::<HashSet<_>>();
+
+            pending[i] = dependencies.len();
+
+            for producer in dependencies {
+                consumers[producer].push(i);
+            }
+        }
+
+        let mut ready = (0..self.gates.len())
+            .filter(|&i| pending[i] == 0)
+            .map(Reverse)
+            .collect::<BinaryHeap<_>>();
+        let mut order = Vec::with_capacity(self.gates.len());
+
+        while let Some(Reverse(i)) = ready.pop() {
+            order.push(i);
+
+            for &consumer in &consumers[i] {
+                pending[consumer] -= 1;
+
+                if pending[consumer] == 0 {
+                    ready.push(Reverse(consumer));
+                }
+            }
+        }
+
+        if order.len() < self.gates.len() {
+            let cycle = cycle_gates(&pending, &consumers)
+                .iter()
+                .map(usize::to_string)
+                .collect::<Vec<_>>();
+
+            return Err(BristolCircuitError::Inconsistency {
+                message: format!("Dependency cycle among gates {}", cycle.join(", ")),
+            });
+        }
+
+        Ok(order)
+    }
+}
+
+/// The unordered gates that are on a cycle, rather than only downstream of one: those left after
+/// repeatedly dropping unordered gates no other unordered gate reads.
+fn cycle_gates(pending: &[usize], consumers: &[Vec<usize>]) -> Vec<usize> {
+    let mut remaining = (0..pending.len())
+        .filter(|&i| pending[i] > 0)
+        .collect::<HashSet<_>>();
+
+    loop {
+        let sinks = remaining
+            .iter()
+            .copied()
+            .filter(|&i| !consumers[i].iter().any(|c| remaining.contains(c)))
+            .collect::<Vec<_>>();
+
+        if sinks.is_empty() {
+            break;
+        }
+
+        for i in sinks {
+            remaining.remove(&i);
+        }
+    }
+
+    let mut cycle = remaining.into_iter().collect::<Vec<_>>();
+    cycle.sort();
+    cycle
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{gadgets, gate::Gate};
+
+    fn gate(op: &str, inputs: &[usize], outputs: &[usize]) -> Gate {
+        Gate {
+            inputs: inputs.into(),
+            outputs: outputs.into(),
+            op: op.into(),
+        }
+    }
+
+    fn circuit(gates: Vec<Gate>) -> BristolCircuit {
+        let mut circuit = BristolCircuit {
+            wire_count: 0,
+            info: Default::default(),
+            io_widths: (vec![1, 1], vec![1]),
+            gates,
+            header_style: Default::default(),
+        };
+        circuit.recompute_wire_count();
+        circuit
+    }
+
+    #[test]
+    fn test_sorted_is_unchanged() {
+        let adder = gadgets::adder(4, true);
+        assert!(adder.is_topologically_sorted());
+        assert_eq!(adder.topological_sort().unwrap(), adder);
+    }
+
+    #[test]
+    fn test_sort_with_ties() {
+        // Gates 2 and 3 only need the inputs; gate 0 needs both of them, and gate 1 needs gate 0.
+        let unsorted = circuit(vec![
+            gate("AND", &[3, 4], &[5]),
+            gate("INV", &[5], &[6]),
+            gate("XOR", &[0, 1], &[3]),
+            gate("MAND", &[0, 1, 1, 0], &[4, 7]),
+        ]);
+        assert!(!unsorted.is_topologically_sorted());
+
+        let sorted = unsorted.topological_sort().unwrap();
+        assert!(sorted.is_topologically_sorted());
+        assert_eq!(
+            sorted.gates,
+            vec![
+                unsorted.gates[2].clone(),
+                unsorted.gates[3].clone(),
+                unsorted.gates[0].clone(),
+                unsorted.gates[1].clone(),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_cycle() {
+        let cyclic = circuit(vec![
+            gate("XOR", &[0, 1], &[2]),
+            gate("AND", &[2, 4], &[3]),
+            gate("INV", &[3], &[4]),
+            gate("INV", &[4], &[5]),
+        ]);
+
+        let err = cyclic.topological_sort().unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Inconsistency: Dependency cycle among gates 1, 2"
+        );
+    }
+}
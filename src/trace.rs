@@ -0,0 +1,193 @@
+//! Spans and events emitted with the `tracing` feature, which compile to nothing without it.
+//!
+//! Span names and fields, all at `INFO`:
+//! - `parse`: `gate_count` and `wire_count`, as declared by the header.
+//! - `write`: `gate_count`, `wire_count`.
+//! - `eval`: `gate_count`.
+//! - `pass`: `name` (e.g. `eliminate_copies`, `lower_luts`), `gate_count` of the input circuit.
+//!
+//! Parsing and evaluation also emit a `DEBUG` `progress` event with a `gates` field every
+//! `PROGRESS_INTERVAL` gates.
+
+#[cfg(feature = "tracing")]
+pub(crate) const PROGRESS_INTERVAL: usize = 1_000_000;
+
+/// Stands in for an entered span when tracing is disabled.
+#[cfg(not(feature = "tracing"))]
+pub(crate) struct NoSpan;
+
+/// Enters a span until the returned guard is dropped. Fields listed after `;` start empty and
+/// are filled in with `trace_record!`.
+macro_rules! trace_span {
+    ($name:literal $(, $field:ident = $value:expr)* $(; $($empty:ident),+)?) => {{
+        #[cfg(feature = "tracing")]
+        {
+            tracing::info_span!(
+                $name
+                $(, $field = $value)*
+                $($(, $empty = tracing::field::Empty)+)?
+            )
+            .entered()
+        }
+
+        #[cfg(not(feature = "tracing"))]
+        {
+            $(let _ = &$value;)*
+            $crate::trace::NoSpan
+        }
+    }};
+}
+
+macro_rules! trace_record {
+    ($span:expr, $field:ident = $value:expr) => {{
+        #[cfg(feature = "tracing")]
+        $span.record(stringify!($field), $value);
+
+        #[cfg(not(feature = "tracing"))]
+        let _ = (&$span, &$value);
+    }};
+}
+
+/// Emits a `progress` event when `gates` is a multiple of `PROGRESS_INTERVAL`.
+macro_rules! trace_progress {
+    ($gates:expr) => {{
+        let gates: usize = $gates;
+
+        #[cfg(feature = "tracing")]
+        if gates % $crate::trace::PROGRESS_INTERVAL == 0 && gates > 0 {
+            tracing::debug!(gates, "progress");
+        }
+
+        #[cfg(not(feature = "tracing"))]
+        let _ = gates;
+    }};
+}
+
+pub(crate) use trace_progress;
+pub(crate) use trace_record;
+pub(crate) use trace_span;
+
+#[cfg(all(test, feature = "tracing"))]
+mod tests {
+    use std::{
+        collections::HashMap,
+        sync::{Arc, Mutex},
+    };
+
+    use tracing::{
+        field::{Field, Visit},
+        span::{Attributes, Id, Record},
+        Event, Metadata, Subscriber,
+    };
+
+    use crate::{bristol_circuit::BristolCircuit, copy_elimination::OutputCopies, gadgets};
+
+    #[derive(Default)]
+    struct Collected {
+        /// Span names with their fields, in creation order.
+        spans: Vec<(String, HashMap<String, String>)>,
+        events: Vec<HashMap<String, String>>,
+    }
+
+    struct Collector {
+        collected: Arc<Mutex<Collected>>,
+    }
+
+    struct Fields<'a>(&'a mut HashMap<String, String>);
+
+    impl Visit for Fields<'_> {
+        fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+            self.0
+                .insert(field.name().to_string(), format!("{:?}", value));
+        }
+    }
+
+    impl Subscriber for Collector {
+        fn enabled(&self, _: &Metadata<'_>) -> bool {
+            true
+        }
+
+        fn new_span(&self, span: &Attributes<'_>) -> Id {
+            let mut fields = HashMap::new();
+            span.record(&mut Fields(&mut fields));
+
+            let mut collected = self.collected.lock().unwrap();
+            collected
+                .spans
+                .push((span.metadata().name().to_string(), fields));
+
+            // Ids are 1-based indices into `spans`.
+            Id::from_u64(collected.spans.len() as u64)
+        }
+
+        fn record(&self, span: &Id, values: &Record<'_>) {
+            let mut collected = self.collected.lock().unwrap();
+            let (_, fields) = &mut collected.spans[span.into_u64() as usize - 1];
+            values.record(&mut Fields(fields));
+        }
+
+        fn record_follows_from(&self, _: &Id, _: &Id) {}
+
+        fn event(&self, event: &Event<'_>) {
+            let mut fields = HashMap::new();
+            event.record(&mut Fields(&mut fields));
+            self.collected.lock().unwrap().events.push(fields);
+        }
+
+        fn enter(&self, _: &Id) {}
+
+        fn exit(&self, _: &Id) {}
+    }
+
+    #[test]
+    fn test_spans() {
+        let circuit = gadgets::adder(4, false);
+
+        let collected = Arc::new(Mutex::new(Collected::default()));
+        let collector = Collector {
+            collected: collected.clone(),
+        };
+
+        tracing::subscriber::with_default(collector, || {
+            let bristol = circuit.get_bristol_string().unwrap();
+            let parsed =
+                BristolCircuit::from_info_and_bristol_string(&circuit.info, &bristol).unwrap();
+            parsed.eliminate_copies_with_report(&["EQW"], OutputCopies::Keep);
+
+            let inputs = HashMap::from([
+                ("a".to_string(), vec![true; 4]),
+                ("b".to_string(), vec![false; 4]),
+            ]);
+            parsed.eval_boolean(&inputs).unwrap();
+        });
+
+        let collected = collected.lock().unwrap();
+        let spans = collected
+            .spans
+            .iter()
+            .map(|(name, fields)| {
+                let mut fields = fields.iter().collect::<Vec<_>>();
+                fields.sort();
+                let fields = fields
+                    .into_iter()
+                    .map(|(k, v)| format!("{}={}", k, v))
+                    .collect::<Vec<_>>()
+                    .join(" ");
+                format!("{} {}", name, fields)
+            })
+            .collect::<Vec<_>>();
+
+        let gates = circuit.gates.len();
+        let wires = circuit.wire_count;
+        assert_eq!(
+            spans,
+            vec![
+                format!("write gate_count={} wire_count={}", gates, wires),
+                format!("parse gate_count={} wire_count={}", gates, wires),
+                format!("pass gate_count={} name=\"eliminate_copies\"", gates),
+                format!("eval gate_count={}", gates),
+            ]
+        );
+        assert!(collected.events.is_empty());
+    }
+}
@@ -0,0 +1,400 @@
+use crate::{
+    bristol_circuit::BristolCircuit,
+    bristol_circuit_error::BristolCircuitError,
+    gate::{takes_literals, validate_gate_shape},
+    io_kind::IoKind,
+    trace::trace_span,
+    wire_index::WireProducer,
+};
+
+impl BristolCircuit {
+    /// Checks that every wire the circuit refers to is below `wire_count`: gate inputs and
+    /// outputs, the full width of each named input and output, constants and wire groups. Gates
+    /// whose op fixes their shape, like `MAND` and LUTs, must have it. Then runs
+    /// [`BristolCircuit::check_single_assignment`].
+    pub fn validate(&self) -> Result<(), BristolCircuitError> {
+        let _span = trace_span!("validate", gate_count = self.gates.len());
+
+        let wire_count = self.wire_count;
+        let out_of_range = |what: String, wire: usize| BristolCircuitError::Inconsistency {
+            message: format!(
+                "{} wire {} is out of range ({} wires)",
+                what, wire, wire_count
+            ),
+        };
+
+        for (i, gate) in self.gates.iter().enumerate() {
+            validate_gate_shape(&gate.op, gate.inputs.len(), gate.outputs.len()).map_err(
+                |message| BristolCircuitError::Inconsistency {
+                    message: format!("Gate {}: {}", i, message),
+                },
+            )?;
+
+            for (role, wires) in [("input", gate.input_wires()), ("output", &gate.outputs[..])] {
+                if let Some(&wire) = wires.iter().find(|&&wire| wire >= wire_count) {
+                    return Err(out_of_range(
+                        format!("Gate {} ({}) {}", i, gate.op, role),
+                        wire,
+                    ));
+                }
+            }
+        }
+
+        for (kind, buses) in [
+            ("Input", self.input_buses()),
+            ("Output", self.output_buses()),
+        ] {
+            for bus in buses {
+                let end = bus.wire.saturating_add(bus.width.max(1));
+
+                if end > wire_count {
+                    return Err(out_of_range(format!("{} {}", kind, bus.name), end - 1));
+                }
+            }
+        }
+
+        let mut constants = self.info.constants.iter().collect::<Vec<_>>();
+        constants.sort_by_key(|(name, _)| name.as_str());
+
+        for (name, constant) in constants {
+            if constant.wire_index >= wire_count {
+                return Err(out_of_range(
+                    format!("Constant {}", name),
+                    constant.wire_index,
+                ));
+            }
+        }
+
+        let mut groups = self.info.wire_groups.iter().collect::<Vec<_>>();
+        groups.sort_by_key(|(name, _)| name.as_str());
+
+        for (name, group) in groups {
+            if let Some(wire) = group
+                .span
+                .wires()
+                .into_iter()
+                .find(|&wire| wire >= wire_count)
+            {
+                return Err(out_of_range(format!("Wire group {}", name), wire));
+            }
+        }
+
+        self.check_single_assignment()
+    }
+
+    /// Checks that each wire is assigned once: named inputs and constants are assigned before
+    /// the gates run, and every gate output must be a wire nothing else has assigned.
+    pub fn check_single_assignment(&self) -> Result<(), BristolCircuitError> {
+        let Some((i, wire, producer)) = self.build_wire_index().first_reassignment() else {
+            return Ok(());
+        };
+
+        let op = &self.gates[i].op;
+        let message = match producer {
+            WireProducer::Gate(j) if j == i => {
+                format!("Gate {} ({}) lists output wire {} twice", i, op, wire)
+            }
+            WireProducer::Gate(j) => format!(
+                "Gate {} ({}) writes wire {}, which gate {} already wrote",
+                i, op, wire, j
+            ),
+            WireProducer::Input(name) => {
+                format!("Gate {} ({}) writes wire {} of input {}", i, op, wire, name)
+            }
+            WireProducer::Constant(name) => format!(
+                "Gate {} ({}) writes wire {} of constant {}",
+                i, op, wire, name
+            ),
+        };
+
+        Err(BristolCircuitError::Inconsistency { message })
+    }
+}
+
+impl BristolCircuit {
+    /// Checks that every gate input is a named input, a constant, or an output of an earlier gate,
+    /// so the gates can be evaluated in order. When the info names no inputs, the first
+    /// `sum(input widths)` wires are taken to be the inputs. `EQ` inputs are literal values,
+    /// not wires, and aren't checked.
+    pub fn check_topological_order(&self) -> Result<(), BristolCircuitError> {
+        let Some((i, wire)) = self.first_unordered_read() else {
+            return Ok(());
+        };
+
+        let gate = &self.gates[i];
+        let later_writer = match self.build_wire_index().producer(wire) {
+            Some(WireProducer::Gate(j)) => format!(", which gate {} assigns", j),
+            _ => String::new(),
+        };
+
+        Err(BristolCircuitError::Inconsistency {
+            message: format!(
+                "Gate {} ({}) reads wire {} before it is assigned{}",
+                i, gate.op, wire, later_writer
+            ),
+        })
+    }
+
+    /// Whether [`BristolCircuit::check_topological_order`] would succeed, without building an
+    /// error.
+    pub fn is_topologically_sorted(&self) -> bool {
+        self.first_unordered_read().is_none()
+    }
+
+    /// The first gate, and its input wire, that reads a wire nothing has assigned yet.
+    fn first_unordered_read(&self) -> Option<(usize, usize)> {
+        let mut assigned = vec![false; self.referenced_wire_count()];
+
+        if self.info.input_name_to_wire_index.is_empty() {
+            let input_wires = self.io_widths.0.iter().sum::<usize>().min(assigned.len());
+            assigned[..input_wires].fill(true);
+        }
+
+        for bus in self.input_buses() {
+            assigned[bus.wire..bus.wire + bus.width].fill(true);
+        }
+
+        for group in self.info.wire_groups.values() {
+            if group.direction == IoKind::Input {
+                for wire in group.span.wires() {
+                    assigned[wire] = true;
+                }
+            }
+        }
+
+        for constant in self.info.constants.values() {
+            assigned[constant.wire_index] = true;
+        }
+
+        for (i, gate) in self.gates.iter().enumerate() {
+            if !takes_literals(&gate.op) {
+                if let Some(&wire) = gate.inputs.iter().find(|&&wire| !assigned[wire]) {
+                    return Some((i, wire));
+                }
+            }
+
+            for &wire in &gate.outputs {
+                assigned[wire] = true;
+            }
+        }
+
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{circuit_info::ConstantInfo, gadgets, parse_options::ParseOptions};
+
+    #[test]
+    fn test_gate_wires_at_boundary() {
+        let mut circuit = gadgets::adder(2, false);
+        circuit.assert_wire_count_tight();
+        assert!(circuit.validate().is_ok());
+
+        let last = circuit.gates.len() - 1;
+        let max = circuit.wire_count - 1;
+
+        circuit.gates[last].outputs[0] = max;
+        assert!(circuit.validate().is_ok());
+
+        circuit.gates[last].outputs[0] = max + 1;
+        let err = circuit.validate().unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            format!(
+                "Inconsistency: Gate {} ({}) output wire {} is out of range ({} wires)",
+                last,
+                circuit.gates[last].op,
+                max + 1,
+                max + 1
+            )
+        );
+
+        circuit.gates[last].outputs[0] = max;
+        circuit.gates[0].inputs[1] = max + 1;
+        let err = circuit.validate().unwrap_err();
+        assert!(err
+            .to_string()
+            .starts_with("Inconsistency: Gate 0 (XOR) input wire"));
+    }
+
+    #[test]
+    fn test_info_wires() {
+        let circuit = gadgets::adder(2, false);
+        let wire_count = circuit.wire_count;
+
+        let mut short = circuit.clone();
+        short.wire_count = 3;
+        let err = short.validate().unwrap_err();
+        assert!(
+            err.to_string().contains("is out of range (3 wires)"),
+            "{}",
+            err
+        );
+
+        let mut bad_input = circuit.clone();
+        bad_input
+            .info
+            .input_name_to_wire_index
+            .insert("b".to_string(), wire_count - 1);
+        let err = bad_input.validate().unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            format!(
+                "Inconsistency: Input b wire {} is out of range ({} wires)",
+                wire_count, wire_count
+            )
+        );
+
+        let mut bad_constant = circuit.clone();
+        bad_constant.info.constants.insert(
+            "one".to_string(),
+            ConstantInfo {
+                value: "1".to_string(),
+                wire_index: wire_count,
+            },
+        );
+        let err = bad_constant.validate().unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            format!(
+                "Inconsistency: Constant one wire {} is out of range ({} wires)",
+                wire_count, wire_count
+            )
+        );
+    }
+
+    #[test]
+    fn test_single_assignment() {
+        let circuit = gadgets::adder(2, true);
+        assert!(circuit.check_single_assignment().is_ok());
+
+        let mut rewritten = circuit.clone();
+        rewritten.gates[3].outputs[0] = rewritten.gates[1].outputs[0];
+        let err = rewritten.validate().unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            format!(
+                "Inconsistency: Gate 3 ({}) writes wire {}, which gate 1 already wrote",
+                rewritten.gates[3].op, rewritten.gates[1].outputs[0]
+            )
+        );
+
+        let mut writes_input = circuit.clone();
+        writes_input.gates[0].outputs[0] = 1;
+        let err = writes_input.check_single_assignment().unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Inconsistency: Gate 0 (XOR) writes wire 1 of input a"
+        );
+
+        let mut writes_constant = circuit.clone();
+        writes_constant.info.constants.insert(
+            "zero".to_string(),
+            ConstantInfo {
+                value: "0".to_string(),
+                wire_index: circuit.gates[2].outputs[0],
+            },
+        );
+        let err = writes_constant.check_single_assignment().unwrap_err();
+        assert!(err.to_string().ends_with("of constant zero"), "{}", err);
+    }
+
+    #[test]
+    fn test_duplicate_outputs_in_one_gate() {
+        let mut circuit = gadgets::adder(2, true);
+        let wire = circuit.gates[0].outputs[0];
+        circuit.gates[0].outputs.push(wire);
+
+        let err = circuit.check_single_assignment().unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            format!(
+                "Inconsistency: Gate 0 (XOR) lists output wire {} twice",
+                wire
+            )
+        );
+    }
+
+    #[test]
+    fn test_topological_order() {
+        let circuit = gadgets::adder(4, true);
+        assert!(circuit.is_topologically_sorted());
+        assert!(circuit.check_topological_order().is_ok());
+
+        // Move the first gate that reads another gate's output ahead of its producer.
+        let mut swapped = circuit.clone();
+        let (producer, reader) = (0..swapped.gates.len())
+            .flat_map(|r| (0..r).map(move |p| (p, r)))
+            .find(|&(p, r)| {
+                swapped.gates[r]
+                    .inputs
+                    .iter()
+                    .any(|wire| swapped.gates[p].outputs.contains(wire))
+            })
+            .unwrap();
+        swapped.gates.swap(producer, reader);
+
+        assert!(!swapped.is_topologically_sorted());
+        let err = swapped.check_topological_order().unwrap_err().to_string();
+        assert!(
+            err.starts_with(&format!("Inconsistency: Gate {} (", producer)),
+            "{}",
+            err
+        );
+        assert!(
+            err.ends_with(&format!("which gate {} assigns", reader)),
+            "{}",
+            err
+        );
+
+        let mut undefined = circuit.clone();
+        undefined.gates[2].inputs[0] = undefined.wire_count;
+        undefined.recompute_wire_count();
+        let err = undefined.check_topological_order().unwrap_err();
+        assert!(
+            err.to_string().ends_with("before it is assigned"),
+            "{}",
+            err
+        );
+    }
+
+    #[test]
+    fn test_topological_order_by_io_widths() {
+        let mut circuit = gadgets::adder(2, false);
+        circuit.info.input_name_to_wire_index.clear();
+        assert!(circuit.is_topologically_sorted());
+
+        circuit.io_widths.0 = vec![2, 1];
+        assert!(!circuit.is_topologically_sorted());
+    }
+
+    #[test]
+    fn test_validate_on_parse() {
+        let info = gadgets::adder(1, false).info;
+        let text = "1 3\n2 1 1\n1 1\n\n2 1 0 1 5 XOR\n";
+
+        let options = ParseOptions {
+            validate: true,
+            strict: false,
+            ..Default::default()
+        };
+        let err = BristolCircuit::from_info_and_bristol_string_with_options(&info, text, &options)
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Inconsistency: Gate 0 (XOR) output wire 5 is out of range (3 wires)"
+        );
+
+        let lenient = ParseOptions {
+            strict: false,
+            ..Default::default()
+        };
+        let circuit =
+            BristolCircuit::from_info_and_bristol_string_with_options(&info, text, &lenient)
+                .unwrap();
+        assert_eq!(circuit.wire_count, 6);
+    }
+}
@@ -0,0 +1,373 @@
+use std::fmt::{self, Display, Formatter};
+
+use serde::Serialize;
+
+use crate::{
+    bristol_circuit::BristolCircuit,
+    gate::{takes_literals, validate_gate_shape},
+    io_kind::IoKind,
+    op_registry::{OpRegistry, UnknownOpPolicy},
+    wire_index::WireProducer,
+};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+pub enum IssueKind {
+    /// A gate, input, output, constant or wire group refers to a wire at or above `wire_count`.
+    OutOfRangeWire,
+
+    /// A gate writes a wire that an input, constant or gate (possibly itself) already assigned.
+    MultipleAssignment,
+
+    /// A gate reads a wire before anything assigns it.
+    UseBeforeDefine,
+
+    /// An output wire is never assigned.
+    UndefinedOutput,
+
+    /// A gate's input and output counts don't fit its op.
+    ArityMismatch,
+
+    /// A gate's op is neither arithmetic, boolean nor a LUT.
+    UnknownOp,
+
+    /// No gate or output reads any of an input's wires.
+    UnusedInput,
+
+    /// No gate or output reads any of a gate's output wires.
+    UnusedGateOutput,
+}
+
+/// A problem found by [`BristolCircuit::validate_all`].
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct ValidationIssue {
+    pub severity: Severity,
+    pub kind: IssueKind,
+    pub gate_index: Option<usize>,
+    pub message: String,
+}
+
+impl Display for ValidationIssue {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        let severity = match self.severity {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+        };
+
+        write!(f, "{}: {}", severity, self.message)
+    }
+}
+
+impl BristolCircuit {
+    /// Like [`BristolCircuit::validate`] and [`BristolCircuit::check_topological_order`], but
+    /// reports every problem instead of stopping at the first, along with warnings for unknown
+    /// ops and unused inputs and gates.
+    ///
+    /// Issues are listed in a fixed order: gate issues in gate order, then out of range inputs
+    /// and outputs in wire order, constants and wire groups by name, then unassigned output
+    /// wires, unused inputs and unused gates.
+    pub fn validate_all(&self) -> Vec<ValidationIssue> {
+        let mut issues = Vec::new();
+        let mut issue = |severity, kind, gate_index, message| {
+            issues.push(ValidationIssue {
+                severity,
+                kind,
+                gate_index,
+                message,
+            })
+        };
+
+        let wire_count = self.wire_count;
+        let out_of_range = |what: String, wire: usize| {
+            format!(
+                "{} wire {} is out of range ({} wires)",
+                what, wire, wire_count
+            )
+        };
+
+        let len = wire_count.max(self.referenced_wire_count());
+        let mut writers = vec![None; len];
+        let mut read = vec![false; len];
+
+        let input_buses = self.input_buses();
+        let output_buses = self.output_buses();
+
+        if self.info.input_name_to_wire_index.is_empty() {
+            let input_wires = self.io_widths.0.iter().sum::<usize>().min(len);
+            writers[..input_wires].fill(Some(WireProducer::Input("")));
+        }
+
+        for bus in &input_buses {
+            writers[bus.wire..bus.wire + bus.width].fill(Some(WireProducer::Input(bus.name)));
+        }
+
+        let mut groups = self.info.wire_groups.iter().collect::<Vec<_>>();
+        groups.sort_by_key(|(name, _)| name.as_str());
+
+        for (name, group) in &groups {
+            for wire in group.span.wires() {
+                match group.direction {
+                    IoKind::Input => writers[wire] = Some(WireProducer::Input(name)),
+                    IoKind::Output => read[wire] = true,
+                }
+            }
+        }
+
+        let mut constants = self.info.constants.iter().collect::<Vec<_>>();
+        constants.sort_by_key(|(name, _)| name.as_str());
+
+        for (name, constant) in &constants {
+            writers[constant.wire_index] = Some(WireProducer::Constant(name));
+        }
+
+        for bus in &output_buses {
+            read[bus.wire..bus.wire + bus.width].fill(true);
+        }
+
+        let mut registry = OpRegistry::default();
+        registry.unknown_ops = UnknownOpPolicy::Reject;
+
+        for (i, gate) in self.gates.iter().enumerate() {
+            let gate_issue = |kind, message| (Severity::Error, kind, Some(i), message);
+            let mut gate_issues = Vec::new();
+
+            if let Err(message) =
+                validate_gate_shape(&gate.op, gate.inputs.len(), gate.outputs.len())
+            {
+                gate_issues.push(gate_issue(
+                    IssueKind::ArityMismatch,
+                    format!("Gate {} ({}): {}", i, gate.op, message),
+                ));
+            } else if let Err(mismatch) = registry.check(i, gate) {
+                gate_issues.push(match mismatch.expected {
+                    Some(_) => gate_issue(IssueKind::ArityMismatch, mismatch.to_string()),
+                    None => (
+                        Severity::Warning,
+                        IssueKind::UnknownOp,
+                        Some(i),
+                        format!("Gate {} uses unknown op {}", i, gate.op),
+                    ),
+                });
+            }
+
+            for (role, wires) in [("input", gate.input_wires()), ("output", &gate.outputs[..])] {
+                for &wire in wires.iter().filter(|&&wire| wire >= wire_count) {
+                    gate_issues.push(gate_issue(
+                        IssueKind::OutOfRangeWire,
+                        out_of_range(format!("Gate {} ({}) {}", i, gate.op, role), wire),
+                    ));
+                }
+            }
+
+            if !takes_literals(&gate.op) {
+                for &wire in &gate.inputs {
+                    read[wire] = true;
+
+                    if writers[wire].is_none() {
+                        gate_issues.push(gate_issue(
+                            IssueKind::UseBeforeDefine,
+                            format!(
+                                "Gate {} ({}) reads wire {} before it is assigned",
+                                i, gate.op, wire
+                            ),
+                        ));
+                    }
+                }
+            }
+
+            for &wire in &gate.outputs {
+                let message = match writers[wire] {
+                    None => {
+                        writers[wire] = Some(WireProducer::Gate(i));
+                        continue;
+                    }
+                    Some(WireProducer::Gate(j)) if j == i => {
+                        format!("Gate {} ({}) lists output wire {} twice", i, gate.op, wire)
+                    }
+                    Some(WireProducer::Gate(j)) => format!(
+                        "Gate {} ({}) writes wire {}, which gate {} already wrote",
+                        i, gate.op, wire, j
+                    ),
+                    Some(WireProducer::Input(name)) => format!(
+                        "Gate {} ({}) writes wire {} of input {}",
+                        i, gate.op, wire, name
+                    ),
+                    Some(WireProducer::Constant(name)) => format!(
+                        "Gate {} ({}) writes wire {} of constant {}",
+                        i, gate.op, wire, name
+                    ),
+                };
+
+                gate_issues.push(gate_issue(IssueKind::MultipleAssignment, message));
+            }
+
+            for (severity, kind, gate_index, message) in gate_issues {
+                issue(severity, kind, gate_index, message);
+            }
+        }
+
+        for (kind, buses) in [("Input", &input_buses), ("Output", &output_buses)] {
+            for bus in buses {
+                let end = bus.wire + bus.width.max(1);
+
+                if end > wire_count {
+                    issue(
+                        Severity::Error,
+                        IssueKind::OutOfRangeWire,
+                        None,
+                        out_of_range(format!("{} {}", kind, bus.name), end - 1),
+                    );
+                }
+            }
+        }
+
+        for (name, constant) in &constants {
+            if constant.wire_index >= wire_count {
+                issue(
+                    Severity::Error,
+                    IssueKind::OutOfRangeWire,
+                    None,
+                    out_of_range(format!("Constant {}", name), constant.wire_index),
+                );
+            }
+        }
+
+        for (name, group) in &groups {
+            if let Some(wire) = group
+                .span
+                .wires()
+                .into_iter()
+                .find(|&wire| wire >= wire_count)
+            {
+                issue(
+                    Severity::Error,
+                    IssueKind::OutOfRangeWire,
+                    None,
+                    out_of_range(format!("Wire group {}", name), wire),
+                );
+            }
+        }
+
+        for bus in &output_buses {
+            for wire in (bus.wire..bus.wire + bus.width).filter(|&wire| writers[wire].is_none()) {
+                issue(
+                    Severity::Error,
+                    IssueKind::UndefinedOutput,
+                    None,
+                    format!("Output {} wire {} is never assigned", bus.name, wire),
+                );
+            }
+        }
+
+        for bus in &input_buses {
+            if bus.width > 0 && !read[bus.wire..bus.wire + bus.width].contains(&true) {
+                issue(
+                    Severity::Warning,
+                    IssueKind::UnusedInput,
+                    None,
+                    format!("Input {} is never read", bus.name),
+                );
+            }
+        }
+
+        for (i, gate) in self.gates.iter().enumerate() {
+            if !gate.outputs.is_empty() && !gate.outputs.iter().any(|&wire| read[wire]) {
+                issue(
+                    Severity::Warning,
+                    IssueKind::UnusedGateOutput,
+                    Some(i),
+                    format!("Gate {} ({}) has no used outputs", i, gate.op),
+                );
+            }
+        }
+
+        issues
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{circuit_builder::CircuitBuilder, gadgets, gate::Gate};
+
+    fn gate(op: &str, inputs: &[usize], outputs: &[usize]) -> Gate {
+        Gate {
+            inputs: inputs.into(),
+            outputs: outputs.into(),
+            op: op.into(),
+        }
+    }
+
+    #[test]
+    fn test_clean_circuit() {
+        assert_eq!(gadgets::adder(4, true).validate_all(), vec![]);
+    }
+
+    #[test]
+    fn test_snapshot() {
+        let mut b = CircuitBuilder::new();
+        let x = b.input("a");
+        let y = b.input("b");
+        b.input("unused");
+        let and = b.gate("AND", &[x, y]);
+        b.output("c", and);
+        let mut circuit = b.build().unwrap();
+
+        let wire_count = circuit.wire_count;
+        circuit.gates.extend([
+            gate("AAdd", &[0, 1, 0], &[wire_count]),
+            gate("FOO", &[wire_count + 5], &[0]),
+            gate("XOR", &[0, 1], &[wire_count + 1]),
+        ]);
+        circuit
+            .gates
+            .insert(0, gate("INV", &[wire_count + 1], &[3]));
+        circuit.recompute_wire_count();
+        circuit.wire_count -= 1;
+
+        let issues = circuit
+            .validate_all()
+            .iter()
+            .map(|issue| format!("{:?} {:?} {}", issue.kind, issue.gate_index, issue))
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            issues,
+            vec![
+                "UseBeforeDefine Some(0) error: Gate 0 (INV) reads wire 5 before it is assigned",
+                "MultipleAssignment Some(1) error: Gate 1 (AND) writes wire 3, which gate 0 \
+                 already wrote",
+                "ArityMismatch Some(2) error: Gate 2 (AAdd) has 3 inputs and 1 outputs, but AAdd \
+                 takes 2 inputs and 1 outputs",
+                "UnknownOp Some(3) warning: Gate 3 uses unknown op FOO",
+                "OutOfRangeWire Some(3) error: Gate 3 (FOO) input wire 9 is out of range (9 \
+                 wires)",
+                "UseBeforeDefine Some(3) error: Gate 3 (FOO) reads wire 9 before it is assigned",
+                "MultipleAssignment Some(3) error: Gate 3 (FOO) writes wire 0 of input a",
+                "UnusedInput None warning: Input unused is never read",
+                "UnusedGateOutput Some(2) warning: Gate 2 (AAdd) has no used outputs",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_undefined_output() {
+        let mut circuit = gadgets::adder(2, false);
+        let last = circuit.gates.len() - 1;
+        circuit.gates.remove(last);
+
+        let issues = circuit.validate_all();
+        let errors = issues
+            .iter()
+            .filter(|issue| issue.severity == Severity::Error)
+            .collect::<Vec<_>>();
+
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].kind, IssueKind::UndefinedOutput);
+    }
+}
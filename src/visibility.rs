@@ -0,0 +1,244 @@
+use std::{
+    collections::HashMap,
+    fmt::{self, Display, Formatter},
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    bristol_circuit::BristolCircuit, bristol_circuit_error::BristolCircuitError,
+    circuit_info::CircuitInfo, io_kind::IoKind, party::PartyId,
+};
+
+/// Who learns an output's value once the circuit has been evaluated, in MPC/ZK settings.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Visibility {
+    /// Revealed to every party.
+    #[default]
+    Public,
+
+    /// Revealed only to one party.
+    Party(PartyId),
+
+    /// Not revealed, e.g. kept as a commitment.
+    Hidden,
+}
+
+impl Visibility {
+    /// Whether `viewer` learns outputs with this visibility. `None` is an observer that isn't
+    /// one of the parties, who only learns public outputs.
+    pub fn is_visible_to(&self, viewer: Option<PartyId>) -> bool {
+        match self {
+            Visibility::Public => true,
+            Visibility::Party(party) => viewer == Some(*party),
+            Visibility::Hidden => false,
+        }
+    }
+}
+
+impl Display for Visibility {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            Visibility::Public => write!(f, "public"),
+            Visibility::Party(party) => write!(f, "visible to {}", party),
+            Visibility::Hidden => write!(f, "hidden"),
+        }
+    }
+}
+
+impl CircuitInfo {
+    /// The visibility of an output. Outputs without an annotation are public.
+    pub fn visibility_of(&self, output: &str) -> Visibility {
+        self.output_visibility
+            .get(output)
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// The named outputs with the given visibility, in wire order.
+    pub fn outputs_with_visibility(&self, visibility: Visibility) -> Vec<&str> {
+        let mut outputs = self
+            .output_name_to_wire_index
+            .iter()
+            .filter(|(name, _)| self.visibility_of(name) == visibility)
+            .map(|(name, &wire)| (wire, name.as_str()))
+            .collect::<Vec<_>>();
+        outputs.sort();
+
+        outputs.into_iter().map(|(_, name)| name).collect()
+    }
+
+    /// Checks that every visibility annotation names an output or output group.
+    pub fn validate_output_visibility(&self) -> Result<(), BristolCircuitError> {
+        let mut unknown = self
+            .output_visibility
+            .keys()
+            .filter(|name| {
+                !self.output_name_to_wire_index.contains_key(*name)
+                    && self
+                        .wire_groups
+                        .get(*name)
+                        .is_none_or(|group| group.direction != IoKind::Output)
+            })
+            .collect::<Vec<_>>();
+        unknown.sort();
+
+        match unknown.first() {
+            None => Ok(()),
+            Some(name) => Err(BristolCircuitError::Inconsistency {
+                message: format!("Visibility annotation for unknown output {}", name),
+            }),
+        }
+    }
+
+    /// Adds `visibility` to this circuit's annotations, e.g. when combining circuits. An output
+    /// can't be given two different visibilities.
+    pub fn merge_output_visibility(
+        &mut self,
+        visibility: &HashMap<String, Visibility>,
+    ) -> Result<(), BristolCircuitError> {
+        let mut names = visibility.keys().collect::<Vec<_>>();
+        names.sort();
+
+        for name in &names {
+            let existing = self.visibility_of(name);
+
+            if self.output_visibility.contains_key(*name) && existing != visibility[*name] {
+                return Err(BristolCircuitError::Inconsistency {
+                    message: format!(
+                        "Output {} is both {} and {}",
+                        name, existing, visibility[*name]
+                    ),
+                });
+            }
+        }
+
+        for name in names {
+            self.output_visibility
+                .insert(name.clone(), visibility[name]);
+        }
+
+        Ok(())
+    }
+}
+
+impl BristolCircuit {
+    /// Keeps only the evaluated outputs that `viewer` learns, as given by
+    /// [`Visibility::is_visible_to`]. Works with the results of any of the evaluators.
+    pub fn outputs_visible_to<T>(
+        &self,
+        outputs: HashMap<String, T>,
+        viewer: Option<PartyId>,
+    ) -> HashMap<String, T> {
+        outputs
+            .into_iter()
+            .filter(|(name, _)| self.info.visibility_of(name).is_visible_to(viewer))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{analyzed_circuit::AnalyzedCircuit, circuit_builder::CircuitBuilder};
+
+    /// `sum` is public, `carry` goes to party 1, `debug` is hidden.
+    fn annotated_circuit() -> BristolCircuit {
+        let mut b = CircuitBuilder::new();
+        let x = b.input("x");
+        let y = b.input("y");
+        let sum = b.gate("XOR", &[x, y]);
+        let carry = b.gate("AND", &[x, y]);
+        let debug = b.gate("OR", &[x, y]);
+        b.output("sum", sum);
+        b.output("carry", carry);
+        b.output("debug", debug);
+
+        let mut circuit = b.build().unwrap();
+        circuit.info.output_visibility = HashMap::from([
+            ("carry".to_string(), Visibility::Party(PartyId(1))),
+            ("debug".to_string(), Visibility::Hidden),
+        ]);
+        circuit
+    }
+
+    #[test]
+    fn test_outputs_visible_to() {
+        let circuit = annotated_circuit();
+        let inputs = HashMap::from([("x".to_string(), vec![true]), ("y".to_string(), vec![true])]);
+        let outputs = circuit.eval_boolean(&inputs).unwrap();
+
+        let names = |viewer| {
+            let mut names = circuit
+                .outputs_visible_to(outputs.clone(), viewer)
+                .into_keys()
+                .collect::<Vec<_>>();
+            names.sort();
+            names
+        };
+
+        assert_eq!(names(None), vec!["sum"]);
+        assert_eq!(names(Some(PartyId(0))), vec!["sum"]);
+        assert_eq!(names(Some(PartyId(1))), vec!["carry", "sum"]);
+    }
+
+    #[test]
+    fn test_outputs_with_visibility() {
+        let info = annotated_circuit().info;
+
+        assert_eq!(info.visibility_of("sum"), Visibility::Public);
+        assert_eq!(
+            info.outputs_with_visibility(Visibility::Public),
+            vec!["sum"]
+        );
+        assert_eq!(
+            info.outputs_with_visibility(Visibility::Hidden),
+            vec!["debug"]
+        );
+    }
+
+    #[test]
+    fn test_rename_keeps_visibility() {
+        let mut analyzed = AnalyzedCircuit::new(annotated_circuit());
+        analyzed.rename(IoKind::Output, "debug", "trace").unwrap();
+
+        let info = &analyzed.circuit().info;
+        assert_eq!(info.visibility_of("trace"), Visibility::Hidden);
+        assert!(!info.output_visibility.contains_key("debug"));
+        assert!(info.validate_output_visibility().is_ok());
+    }
+
+    #[test]
+    fn test_validation_and_merge() {
+        let mut info = annotated_circuit().info;
+
+        info.merge_output_visibility(&HashMap::from([("sum".to_string(), Visibility::Hidden)]))
+            .unwrap();
+
+        let err = info
+            .merge_output_visibility(&HashMap::from([("debug".to_string(), Visibility::Public)]))
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Inconsistency: Output debug is both hidden and public"
+        );
+
+        info.output_visibility
+            .insert("x".to_string(), Visibility::Public);
+        let err = info.validate_output_visibility().unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Inconsistency: Visibility annotation for unknown output x"
+        );
+    }
+
+    #[test]
+    fn test_json_shape() {
+        let info = annotated_circuit().info;
+        let json = serde_json::to_value(&info).unwrap();
+
+        assert_eq!(json["output_visibility"]["carry"]["Party"], 1);
+        assert_eq!(json["output_visibility"]["debug"], "Hidden");
+        assert_eq!(CircuitInfo::from_json_str(&json.to_string()).unwrap(), info);
+    }
+}
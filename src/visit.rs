@@ -0,0 +1,369 @@
+//! A visitor over a circuit's inputs, constants, gates and outputs, with drivers for the whole
+//! circuit and for the part of it reachable from one input or output.
+
+use std::{
+    collections::{BTreeSet, HashSet},
+    ops::ControlFlow,
+};
+
+use crate::{
+    bristol_circuit::{BristolCircuit, IoBus},
+    bristol_circuit_error::BristolCircuitError,
+    circuit_info::ConstantInfo,
+    gate::Gate,
+    wire_index::{WireIndex, WireProducer},
+};
+
+/// Callbacks for each part of a circuit. Return `ControlFlow::Break` to stop the walk early.
+pub trait CircuitVisitor {
+    /// A named input occupying `width` wires from `wire`.
+    fn visit_input(&mut self, _name: &str, _wire: usize, _width: usize) -> ControlFlow<()> {
+        ControlFlow::Continue(())
+    }
+
+    fn visit_constant(&mut self, _name: &str, _constant: &ConstantInfo) -> ControlFlow<()> {
+        ControlFlow::Continue(())
+    }
+
+    fn visit_gate(&mut self, _index: usize, _gate: &Gate) -> ControlFlow<()> {
+        ControlFlow::Continue(())
+    }
+
+    /// A named output occupying `width` wires from `wire`.
+    fn visit_output(&mut self, _name: &str, _wire: usize, _width: usize) -> ControlFlow<()> {
+        ControlFlow::Continue(())
+    }
+}
+
+/// Visits inputs and outputs in wire order, constants by name, then gates in circuit order,
+/// before the outputs.
+pub fn walk<V: CircuitVisitor + ?Sized>(
+    circuit: &BristolCircuit,
+    visitor: &mut V,
+) -> ControlFlow<()> {
+    walk_selected(circuit, visitor, None, |_| true)
+}
+
+/// Visits only what `output` depends on: the gates found by walking back from its wires, and
+/// the inputs and constants they reach. Everything is visited in the same order as [`walk`].
+pub fn walk_cone_of_output<V: CircuitVisitor + ?Sized>(
+    circuit: &BristolCircuit,
+    output: &str,
+    visitor: &mut V,
+) -> Result<ControlFlow<()>, BristolCircuitError> {
+    let bus = find_bus(circuit.output_buses(), "output", output)?;
+    let reached = Reachability::new(circuit).backward(bus.wire..bus.wire + bus.width);
+
+    Ok(walk_selected(circuit, visitor, Some(&reached), |bus| {
+        bus.name == output
+    }))
+}
+
+/// Visits only what `input` affects: the gates found by walking forward from its wires, and the
+/// outputs they reach. Everything is visited in the same order as [`walk`].
+pub fn walk_forward_from_input<V: CircuitVisitor + ?Sized>(
+    circuit: &BristolCircuit,
+    input: &str,
+    visitor: &mut V,
+) -> Result<ControlFlow<()>, BristolCircuitError> {
+    let bus = find_bus(circuit.input_buses(), "input", input)?;
+    let reached = Reachability::new(circuit).forward(bus.wire..bus.wire + bus.width);
+
+    Ok(walk_selected(circuit, visitor, Some(&reached), |bus| {
+        reached.touches(bus)
+    }))
+}
+
+fn find_bus<'a>(
+    buses: Vec<IoBus<'a>>,
+    kind: &str,
+    name: &str,
+) -> Result<IoBus<'a>, BristolCircuitError> {
+    buses
+        .into_iter()
+        .find(|bus| bus.name == name)
+        .ok_or_else(|| BristolCircuitError::Inconsistency {
+            message: format!("No {} named {}", kind, name),
+        })
+}
+
+/// The gates and wires found by a traversal.
+pub(crate) struct Reached {
+    pub gates: BTreeSet<usize>,
+    pub wires: HashSet<usize>,
+}
+
+impl Reached {
+    fn touches(&self, bus: &IoBus) -> bool {
+        (bus.wire..bus.wire + bus.width).any(|wire| self.wires.contains(&wire))
+    }
+}
+
+/// Traversals in either direction over a [`WireIndex`].
+pub(crate) struct Reachability<'a> {
+    index: WireIndex<'a>,
+}
+
+impl<'a> Reachability<'a> {
+    pub fn new(circuit: &'a BristolCircuit) -> Self {
+        Reachability {
+            index: circuit.build_wire_index(),
+        }
+    }
+
+    pub fn index(&self) -> &WireIndex<'a> {
+        &self.index
+    }
+
+    pub fn backward(&self, wires: impl IntoIterator<Item = usize>) -> Reached {
+        self.traverse(
+            wires,
+            |wire| match self.index.producer(wire) {
+                Some(WireProducer::Gate(i)) => vec![i],
+                _ => Vec::new(),
+            },
+            |gate| gate.input_wires(),
+        )
+    }
+
+    pub fn forward(&self, wires: impl IntoIterator<Item = usize>) -> Reached {
+        self.traverse(
+            wires,
+            |wire| self.index.consumers(wire).to_vec(),
+            |gate| &gate.outputs,
+        )
+    }
+
+    /// An iterative worklist traversal: from each wire, `next_gates` gives the gates to visit
+    /// and `next_wires` the wires each of those leads to.
+    fn traverse(
+        &self,
+        wires: impl IntoIterator<Item = usize>,
+        next_gates: impl Fn(usize) -> Vec<usize>,
+        next_wires: impl Fn(&'a Gate) -> &'a [usize],
+    ) -> Reached {
+        let mut reached = Reached {
+            gates: BTreeSet::new(),
+            wires: HashSet::new(),
+        };
+        let mut stack = wires.into_iter().collect::<Vec<_>>();
+
+        while let Some(wire) = stack.pop() {
+            if !reached.wires.insert(wire) {
+                continue;
+            }
+
+            for gate in next_gates(wire) {
+                if reached.gates.insert(gate) {
+                    stack.extend(next_wires(&self.index.circuit().gates[gate]));
+                }
+            }
+        }
+
+        reached
+    }
+}
+
+/// Visits everything, or what `reached` covers. Outputs are further filtered by `show_output`.
+fn walk_selected<V: CircuitVisitor + ?Sized>(
+    circuit: &BristolCircuit,
+    visitor: &mut V,
+    reached: Option<&Reached>,
+    show_output: impl Fn(&IoBus) -> bool,
+) -> ControlFlow<()> {
+    for bus in circuit.input_buses() {
+        if reached.is_none_or(|reached| reached.touches(&bus)) {
+            visitor.visit_input(bus.name, bus.wire, bus.width)?;
+        }
+    }
+
+    let mut constants = circuit.info.constants.iter().collect::<Vec<_>>();
+    constants.sort_by_key(|(name, _)| name.as_str());
+
+    for (name, constant) in constants {
+        if reached.is_none_or(|reached| reached.wires.contains(&constant.wire_index)) {
+            visitor.visit_constant(name, constant)?;
+        }
+    }
+
+    match reached {
+        None => {
+            for (i, gate) in circuit.gates.iter().enumerate() {
+                visitor.visit_gate(i, gate)?;
+            }
+        }
+        Some(reached) => {
+            for &i in &reached.gates {
+                visitor.visit_gate(i, &circuit.gates[i])?;
+            }
+        }
+    }
+
+    for bus in circuit.output_buses() {
+        if show_output(&bus) {
+            visitor.visit_output(bus.name, bus.wire, bus.width)?;
+        }
+    }
+
+    ControlFlow::Continue(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{circuit_builder::CircuitBuilder, gadgets};
+
+    /// Records what was visited, stopping after `limit` gates if given.
+    #[derive(Default)]
+    struct Recorder {
+        items: Vec<String>,
+        limit: Option<usize>,
+    }
+
+    impl CircuitVisitor for Recorder {
+        fn visit_input(&mut self, name: &str, _wire: usize, _width: usize) -> ControlFlow<()> {
+            self.items.push(format!("input:{}", name));
+            ControlFlow::Continue(())
+        }
+
+        fn visit_constant(&mut self, name: &str, _constant: &ConstantInfo) -> ControlFlow<()> {
+            self.items.push(format!("const:{}", name));
+            ControlFlow::Continue(())
+        }
+
+        fn visit_gate(&mut self, index: usize, _gate: &Gate) -> ControlFlow<()> {
+            self.items.push(format!("gate:{}", index));
+
+            match self.limit {
+                Some(limit)
+                    if self.items.iter().filter(|i| i.starts_with("gate")).count() >= limit =>
+                {
+                    ControlFlow::Break(())
+                }
+                _ => ControlFlow::Continue(()),
+            }
+        }
+
+        fn visit_output(&mut self, name: &str, _wire: usize, _width: usize) -> ControlFlow<()> {
+            self.items.push(format!("output:{}", name));
+            ControlFlow::Continue(())
+        }
+    }
+
+    /// `left = a & b` and `right = !c`, which `both` combines.
+    fn two_cones() -> BristolCircuit {
+        let mut b = CircuitBuilder::new();
+        let a = b.input("a");
+        let x = b.input("b");
+        let c = b.input("c");
+        let and = b.gate("AND", &[a, x]);
+        let not = b.gate("INV", &[c]);
+        let both = b.gate("XOR", &[and, not]);
+        b.output("left", and);
+        b.output("right", not);
+        b.output("both", both);
+        b.build().unwrap()
+    }
+
+    fn record(f: impl FnOnce(&mut Recorder) -> ControlFlow<()>) -> Vec<String> {
+        let mut recorder = Recorder::default();
+        assert_eq!(f(&mut recorder), ControlFlow::Continue(()));
+        recorder.items
+    }
+
+    #[test]
+    fn test_walk() {
+        let circuit = two_cones();
+        let items = record(|r| walk(&circuit, r));
+
+        assert_eq!(
+            items,
+            vec![
+                "input:a",
+                "input:b",
+                "input:c",
+                "gate:0",
+                "gate:1",
+                "gate:2",
+                "output:left",
+                "output:right",
+                "output:both"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_cone_and_forward() {
+        let circuit = two_cones();
+
+        let cone = record(|r| walk_cone_of_output(&circuit, "left", r).unwrap());
+        assert_eq!(cone, vec!["input:a", "input:b", "gate:0", "output:left"]);
+
+        let forward = record(|r| walk_forward_from_input(&circuit, "c", r).unwrap());
+        assert_eq!(
+            forward,
+            vec!["input:c", "gate:1", "gate:2", "output:right", "output:both"]
+        );
+
+        let mut recorder = Recorder::default();
+        let err = walk_cone_of_output(&circuit, "missing", &mut recorder).unwrap_err();
+        assert_eq!(err.to_string(), "Inconsistency: No output named missing");
+    }
+
+    #[test]
+    fn test_cone_skips_eq_literals() {
+        let circuit =
+            BristolCircuit::from_bristol_string("2 3\n1 1\n2 1 1\n\n1 1 0 1 EQ\n1 1 0 2 EQW\n")
+                .unwrap();
+        assert!(circuit.input_dependencies()["output0"].is_empty());
+
+        let cone = record(|r| walk_cone_of_output(&circuit, "output0", r).unwrap());
+        assert_eq!(cone, vec!["gate:0", "output:output0"]);
+
+        let extracted = circuit.extract_subcircuit(&["output0"]).unwrap();
+        assert!(extracted.info.input_name_to_wire_index.is_empty());
+    }
+
+    #[test]
+    fn test_early_exit() {
+        let circuit = gadgets::adder(8, true);
+        let mut recorder = Recorder {
+            limit: Some(3),
+            ..Default::default()
+        };
+
+        assert_eq!(walk(&circuit, &mut recorder), ControlFlow::Break(()));
+        assert_eq!(
+            recorder.items,
+            vec!["input:a", "input:b", "gate:0", "gate:1", "gate:2"]
+        );
+    }
+
+    #[test]
+    fn test_deep_cone_is_iterative() {
+        let mut b = CircuitBuilder::new();
+        let mut wire = b.input("x");
+
+        for _ in 0..50_000 {
+            wire = b.gate("INV", &[wire]);
+        }
+        b.output("y", wire);
+        let circuit = b.build().unwrap();
+
+        let mut count = GateCount(0);
+        assert_eq!(
+            walk_cone_of_output(&circuit, "y", &mut count).unwrap(),
+            ControlFlow::Continue(())
+        );
+        assert_eq!(count.0, 50_000);
+    }
+
+    struct GateCount(usize);
+
+    impl CircuitVisitor for GateCount {
+        fn visit_gate(&mut self, _index: usize, _gate: &Gate) -> ControlFlow<()> {
+            self.0 += 1;
+            ControlFlow::Continue(())
+        }
+    }
+}
@@ -0,0 +1,209 @@
+use std::{
+    cell::RefCell,
+    ops::{Add, BitAnd, BitOr, BitXor, Div, Mul, Rem, Shl, Shr, Sub},
+};
+
+use crate::{
+    bristol_circuit::BristolCircuit,
+    bristol_circuit_error::BristolCircuitError,
+    circuit_builder::{CircuitBuilder, WireId},
+};
+
+/// The circuit that [`WireExpr`]s are built into. Gates are emitted in the order the expressions
+/// are evaluated, so the same Rust code always gives the same circuit.
+#[derive(Debug, Default)]
+pub struct CircuitContext {
+    builder: RefCell<CircuitBuilder>,
+}
+
+/// An arithmetic value in a [`CircuitContext`]: a handle to the wire holding it. Operators emit
+/// one gate each, so an expression that is used twice reads the same wire rather than being
+/// rebuilt: `(a + b) * b` is one `AAdd` and one `AMul`, both reading `b`'s wire.
+#[derive(Clone, Copy, Debug)]
+pub struct WireExpr<'a> {
+    ctx: &'a CircuitContext,
+    wire: WireId,
+}
+
+impl CircuitContext {
+    pub fn new() -> Self {
+        CircuitContext::default()
+    }
+
+    pub fn input(&self, name: &str) -> WireExpr<'_> {
+        self.expr(self.builder.borrow_mut().input(name))
+    }
+
+    pub fn constant(&self, name: &str, value: &str) -> WireExpr<'_> {
+        self.expr(self.builder.borrow_mut().constant(name, value))
+    }
+
+    /// Emits a gate with any op, for those without an operator or method. Like
+    /// [`CircuitBuilder`], expressions from another context are reported when the circuit is
+    /// built.
+    pub fn gate<'a>(&'a self, op: &str, inputs: &[WireExpr<'a>]) -> WireExpr<'a> {
+        let wires = inputs.iter().map(|expr| expr.wire).collect::<Vec<_>>();
+
+        self.expr(self.builder.borrow_mut().gate(op, &wires))
+    }
+
+    pub fn output(&self, name: &str, expr: WireExpr) {
+        self.builder.borrow_mut().output(name, expr.wire);
+    }
+
+    /// Builds the circuit, as [`CircuitBuilder::build`] does.
+    pub fn finish(self) -> Result<BristolCircuit, BristolCircuitError> {
+        self.builder.into_inner().build()
+    }
+
+    fn expr(&self, wire: WireId) -> WireExpr<'_> {
+        WireExpr { ctx: self, wire }
+    }
+}
+
+impl<'a> WireExpr<'a> {
+    fn binary(self, op: &str, rhs: WireExpr<'a>) -> WireExpr<'a> {
+        self.ctx.gate(op, &[self, rhs])
+    }
+
+    /// `AEq`: 1 if the values are equal, 0 otherwise.
+    pub fn equals(self, rhs: WireExpr<'a>) -> WireExpr<'a> {
+        self.binary("AEq", rhs)
+    }
+
+    /// `ANeq`: 1 if the values differ, 0 otherwise.
+    pub fn not_equals(self, rhs: WireExpr<'a>) -> WireExpr<'a> {
+        self.binary("ANeq", rhs)
+    }
+
+    /// `ALt`
+    pub fn lt(self, rhs: WireExpr<'a>) -> WireExpr<'a> {
+        self.binary("ALt", rhs)
+    }
+
+    /// `ALEq`
+    pub fn le(self, rhs: WireExpr<'a>) -> WireExpr<'a> {
+        self.binary("ALEq", rhs)
+    }
+
+    /// `AGt`
+    pub fn gt(self, rhs: WireExpr<'a>) -> WireExpr<'a> {
+        self.binary("AGt", rhs)
+    }
+
+    /// `AGEq`
+    pub fn ge(self, rhs: WireExpr<'a>) -> WireExpr<'a> {
+        self.binary("AGEq", rhs)
+    }
+
+    /// `AIntDiv`, as opposed to `/`, which is field division (`ADiv`).
+    pub fn int_div(self, rhs: WireExpr<'a>) -> WireExpr<'a> {
+        self.binary("AIntDiv", rhs)
+    }
+
+    /// `APow`
+    pub fn pow(self, rhs: WireExpr<'a>) -> WireExpr<'a> {
+        self.binary("APow", rhs)
+    }
+
+    /// `AMux`: `a` when `self` is 1 and `b` when it is 0.
+    pub fn select(self, a: WireExpr<'a>, b: WireExpr<'a>) -> WireExpr<'a> {
+        self.ctx.gate("AMux", &[self, a, b])
+    }
+}
+
+macro_rules! binary_operator {
+    ($trait:ident, $method:ident, $op:literal) => {
+        impl<'a> $trait for WireExpr<'a> {
+            type Output = WireExpr<'a>;
+
+            fn $method(self, rhs: WireExpr<'a>) -> WireExpr<'a> {
+                self.binary($op, rhs)
+            }
+        }
+    };
+}
+
+binary_operator!(Add, add, "AAdd");
+binary_operator!(Sub, sub, "ASub");
+binary_operator!(Mul, mul, "AMul");
+binary_operator!(Div, div, "ADiv");
+binary_operator!(Rem, rem, "AMod");
+binary_operator!(BitXor, bitxor, "AXor");
+binary_operator!(BitAnd, bitand, "ABitAnd");
+binary_operator!(BitOr, bitor, "ABitOr");
+binary_operator!(Shl, shl, "AShiftL");
+binary_operator!(Shr, shr, "AShiftR");
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+
+    #[test]
+    fn test_matches_builder() {
+        let ctx = CircuitContext::new();
+        let a = ctx.input("a");
+        let b = ctx.input("b");
+        ctx.output("d", (a + b) * b);
+
+        let mut builder = CircuitBuilder::new();
+        let x = builder.input("a");
+        let y = builder.input("b");
+        let sum = builder.gate("AAdd", &[x, y]);
+        let d = builder.gate("AMul", &[sum, y]);
+        builder.output("d", d);
+
+        assert_eq!(ctx.finish().unwrap(), builder.build().unwrap());
+    }
+
+    #[test]
+    fn test_reuse_and_comparisons() {
+        let ctx = CircuitContext::new();
+        let a = ctx.input("a");
+        let b = ctx.input("b");
+        let three = ctx.constant("three", "3");
+
+        let square = a * a;
+        let sum = square + square;
+        ctx.output("sum", sum);
+        ctx.output("less", sum.lt(b));
+        ctx.output("picked", a.equals(three).select(b, a ^ b));
+
+        let circuit = ctx.finish().unwrap();
+        assert_eq!(
+            circuit
+                .gates
+                .iter()
+                .map(|gate| gate.op.as_str())
+                .collect::<Vec<_>>(),
+            vec!["AMul", "AAdd", "ALt", "AEq", "AXor", "AMux"]
+        );
+
+        let outputs = circuit
+            .eval_arithmetic(
+                &HashMap::from([("a".to_string(), 3), ("b".to_string(), 20)]),
+                None,
+            )
+            .unwrap();
+        assert_eq!(outputs["sum"], 18);
+        assert_eq!(outputs["less"], 1);
+        assert_eq!(outputs["picked"], 20);
+    }
+
+    #[test]
+    fn test_foreign_expression() {
+        let other = CircuitContext::new();
+        let foreign = other.input("x");
+
+        let ctx = CircuitContext::new();
+        let a = ctx.input("a");
+        ctx.output("out", a + foreign);
+
+        assert_eq!(
+            ctx.finish().unwrap_err().to_string(),
+            "Inconsistency: Wire used as an input of gate AAdd belongs to a different builder"
+        );
+    }
+}
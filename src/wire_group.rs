@@ -0,0 +1,391 @@
+use std::collections::{HashMap, HashSet};
+
+use serde::{Deserialize, Serialize};
+
+use smallvec::smallvec;
+
+use crate::{
+    bristol_circuit::{BristolCircuit, IoBus},
+    bristol_circuit_error::BristolCircuitError,
+    gate::Gate,
+    io_kind::IoKind,
+    trace::trace_span,
+};
+
+/// A named input or output made of arbitrary wires, listed least significant bit first.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WireGroup {
+    pub direction: IoKind,
+    #[serde(flatten)]
+    pub span: WireSpan,
+}
+
+/// The wires of a group: either `{"start": s, "width": w}` or an explicit `{"wires": [...]}`.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum WireSpan {
+    Range { start: usize, width: usize },
+    Wires { wires: Vec<usize> },
+}
+
+impl WireSpan {
+    /// The most compact span for these wires.
+    pub fn from_wires(wires: Vec<usize>) -> WireSpan {
+        let contiguous = wires.windows(2).all(|pair| pair[1] == pair[0] + 1);
+
+        match (contiguous, wires.first()) {
+            (true, Some(&start)) => WireSpan::Range {
+                start,
+                width: wires.len(),
+            },
+            _ => WireSpan::Wires { wires },
+        }
+    }
+
+    pub fn wires(&self) -> Vec<usize> {
+        match self {
+            WireSpan::Range { start, width } => (*start..start + width).collect(),
+            WireSpan::Wires { wires } => wires.clone(),
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        match self {
+            WireSpan::Range { width, .. } => *width,
+            WireSpan::Wires { wires } => wires.len(),
+        }
+    }
+}
+
+/// How `materialize_groups` turns wire groups into ordinary contiguous inputs and outputs.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GroupLayout {
+    /// Copy each output group's bits onto fresh contiguous wires with `EQW` gates. Input groups
+    /// can't be copied, so they must already be contiguous.
+    Copies,
+
+    /// Renumber wires so each group's bits become contiguous, with inputs first and outputs last.
+    Renumber,
+}
+
+impl BristolCircuit {
+    /// Names `wires` as a multi-bit input or output, without requiring them to be contiguous.
+    pub fn group_wires(
+        &mut self,
+        name: &str,
+        wires: &[usize],
+        direction: IoKind,
+    ) -> Result<(), BristolCircuitError> {
+        let info = &self.info;
+
+        if info.input_name_to_wire_index.contains_key(name)
+            || info.output_name_to_wire_index.contains_key(name)
+            || info.constants.contains_key(name)
+            || info.wire_groups.contains_key(name)
+        {
+            return Err(inconsistency(format!("Name {} is already in use", name)));
+        }
+
+        if wires.is_empty() {
+            return Err(inconsistency(format!("Wire group {} has no wires", name)));
+        }
+
+        let mut seen = HashSet::new();
+
+        if let Some(wire) = wires.iter().find(|&&wire| !seen.insert(wire)) {
+            return Err(inconsistency(format!(
+                "Wire group {} lists wire {} twice",
+                name, wire
+            )));
+        }
+
+        self.info.wire_groups.insert(
+            name.to_string(),
+            WireGroup {
+                direction,
+                span: WireSpan::from_wires(wires.to_vec()),
+            },
+        );
+
+        Ok(())
+    }
+
+    /// Turns every wire group into an ordinary named input or output with a width in `io_widths`,
+    /// so the circuit can be written as Bristol text without losing them.
+    pub fn materialize_groups(
+        &self,
+        layout: GroupLayout,
+    ) -> Result<BristolCircuit, BristolCircuitError> {
+        let _span = trace_span!(
+            "pass",
+            name = "materialize_groups",
+            gate_count = self.gates.len()
+        );
+
+        let mut result = self.clone();
+
+        if layout == GroupLayout::Renumber {
+            result.renumber_for_groups()?;
+        }
+
+        let mut groups = result.info.wire_groups.drain().collect::<Vec<_>>();
+        groups.sort_by(|a, b| a.0.cmp(&b.0));
+
+        let mut next_wire = result.wire_count.max(result.referenced_wire_count());
+
+        for (name, group) in groups {
+            let wires = match (&group.span, group.direction) {
+                (WireSpan::Wires { wires }, IoKind::Output) => {
+                    let start = next_wire;
+                    next_wire += wires.len();
+
+                    result
+                        .gates
+                        .extend(wires.iter().enumerate().map(|(i, &wire)| Gate {
+                            inputs: smallvec![wire],
+                            outputs: smallvec![start + i],
+                            op: "EQW".into(),
+                        }));
+
+                    (start, wires.len())
+                }
+                (WireSpan::Range { start, width }, _) => (*start, *width),
+                (WireSpan::Wires { .. }, IoKind::Input) => {
+                    return Err(inconsistency(format!(
+                        "Input group {} isn't contiguous, so it can only be materialized by \
+                         renumbering",
+                        name
+                    )));
+                }
+            };
+
+            result.insert_named_io(group.direction, name, wires.0, wires.1);
+        }
+
+        result.recompute_wire_count();
+
+        Ok(result)
+    }
+
+    /// Adds a named input or output, keeping `io_widths` in the order of the names' wires.
+    fn insert_named_io(&mut self, kind: IoKind, name: String, wire: usize, width: usize) {
+        let (name_to_wire, widths) = match kind {
+            IoKind::Input => (
+                &mut self.info.input_name_to_wire_index,
+                &mut self.io_widths.0,
+            ),
+            IoKind::Output => (
+                &mut self.info.output_name_to_wire_index,
+                &mut self.io_widths.1,
+            ),
+        };
+
+        let position = name_to_wire.values().filter(|&&other| other < wire).count();
+        widths.insert(position.min(widths.len()), width);
+        name_to_wire.insert(name, wire);
+    }
+
+    fn renumber_for_groups(&mut self) -> Result<(), BristolCircuitError> {
+        let mut groups = self.info.wire_groups.iter().collect::<Vec<_>>();
+        groups.sort_by(|a, b| a.0.cmp(b.0));
+
+        let mut claimed = HashMap::<usize, &str>::new();
+
+        for bus in self.input_buses().into_iter().chain(self.output_buses()) {
+            for wire in bus.wire..bus.wire + bus.width {
+                claimed.entry(wire).or_insert(bus.name);
+            }
+        }
+
+        for (name, group) in &groups {
+            for wire in group.span.wires() {
+                if let Some(other) = claimed.insert(wire, name) {
+                    return Err(inconsistency(format!(
+                        "Wire group {} shares wire {} with {}, so it can't be renumbered",
+                        name, wire, other
+                    )));
+                }
+            }
+        }
+
+        let bus_wires = |buses: Vec<IoBus>| {
+            buses
+                .into_iter()
+                .flat_map(|bus| bus.wire..bus.wire + bus.width)
+                .collect::<Vec<_>>()
+        };
+
+        let group_wires = |direction: IoKind| {
+            groups
+                .iter()
+                .filter(|(_, group)| group.direction == direction)
+                .flat_map(|(_, group)| group.span.wires())
+                .collect::<Vec<_>>()
+        };
+
+        let first = bus_wires(self.input_buses())
+            .into_iter()
+            .chain(group_wires(IoKind::Input))
+            .collect::<Vec<_>>();
+
+        let last = bus_wires(self.output_buses())
+            .into_iter()
+            .chain(group_wires(IoKind::Output))
+            .collect::<Vec<_>>();
+
+        let edge = first.iter().chain(&last).copied().collect::<HashSet<_>>();
+        let middle = (0..self.wire_count.max(self.referenced_wire_count()))
+            .filter(|wire| !edge.contains(wire));
+
+        let mut mapping = HashMap::new();
+
+        for wire in first.into_iter().chain(middle).chain(last) {
+            let next = mapping.len();
+            mapping.entry(wire).or_insert(next);
+        }
+
+        self.map_wires(|wire| mapping[&wire]);
+
+        Ok(())
+    }
+}
+
+fn inconsistency(message: String) -> BristolCircuitError {
+    BristolCircuitError::Inconsistency { message }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+    use crate::{
+        circuit_info::CircuitInfo,
+        gadgets::test_utils::{from_bits, to_bits},
+    };
+
+    /// Inverts the bits of a 4-bit input `a` (wires 0-3) onto scattered wires 7, 4, 6 and 5, and
+    /// exposes them as the output group `out`.
+    fn scattered() -> BristolCircuit {
+        let mut circuit = BristolCircuit {
+            wire_count: 8,
+            info: Default::default(),
+            io_widths: (vec![4], vec![]),
+            gates: [(0, 7), (1, 4), (2, 6), (3, 5)]
+                .into_iter()
+                .map(|(input, output)| Gate {
+                    inputs: smallvec![input],
+                    outputs: smallvec![output],
+                    op: "INV".into(),
+                })
+                .collect(),
+            header_style: Default::default(),
+        };
+
+        circuit
+            .info
+            .input_name_to_wire_index
+            .insert("a".to_string(), 0);
+        circuit
+            .group_wires("out", &[7, 4, 6, 5], IoKind::Output)
+            .unwrap();
+
+        circuit
+    }
+
+    fn eval_out(circuit: &BristolCircuit, a: u128) -> u128 {
+        let inputs = HashMap::from([("a".to_string(), to_bits(a, 4))]);
+        from_bits(&circuit.eval_boolean(&inputs).unwrap()["out"])
+    }
+
+    #[test]
+    fn test_scattered_output_decodes() {
+        let circuit = scattered();
+
+        for a in 0..16 {
+            assert_eq!(eval_out(&circuit, a), !a & 0xf);
+        }
+    }
+
+    #[test]
+    fn test_materialize() {
+        let circuit = scattered();
+
+        for layout in [GroupLayout::Copies, GroupLayout::Renumber] {
+            let materialized = circuit.materialize_groups(layout).unwrap();
+
+            assert!(materialized.info.wire_groups.is_empty());
+            assert_eq!(materialized.io_widths, (vec![4], vec![4]));
+            materialized.assert_wire_count_tight();
+
+            let reparsed = BristolCircuit::from_info_and_bristol_string(
+                &materialized.info,
+                &materialized.get_bristol_string().unwrap(),
+            )
+            .unwrap();
+            assert_eq!(reparsed, materialized);
+
+            for a in 0..16 {
+                assert_eq!(
+                    eval_out(&materialized, a),
+                    eval_out(&circuit, a),
+                    "{:?}",
+                    layout
+                );
+            }
+        }
+
+        let renumbered = circuit.materialize_groups(GroupLayout::Renumber).unwrap();
+        assert_eq!(renumbered.info.output_name_to_wire_index["out"], 4);
+        assert_eq!(renumbered.gates.len(), 4);
+    }
+
+    #[test]
+    fn test_serde_forms() {
+        let info = CircuitInfo::from_json_str(
+            r#"{
+                "input_name_to_wire_index": {},
+                "constants": {},
+                "output_name_to_wire_index": {},
+                "wire_groups": {
+                    "x": {"direction": "Input", "start": 0, "width": 3},
+                    "y": {"direction": "Output", "wires": [9, 4, 6]}
+                }
+            }"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            info.wire_groups["x"].span,
+            WireSpan::Range { start: 0, width: 3 }
+        );
+        assert_eq!(info.wire_groups["y"].span.wires(), vec![9, 4, 6]);
+        assert_eq!(
+            CircuitInfo::from_json_str(&info.to_json_string().unwrap()).unwrap(),
+            info
+        );
+
+        // Documents without groups keep their existing shape.
+        assert!(!CircuitInfo::default()
+            .to_json_string()
+            .unwrap()
+            .contains("wire_groups"));
+    }
+
+    #[test]
+    fn test_group_validation() {
+        let mut circuit = scattered();
+
+        assert!(circuit.group_wires("a", &[1], IoKind::Input).is_err());
+        assert!(circuit.group_wires("empty", &[], IoKind::Input).is_err());
+        assert!(circuit
+            .group_wires("twice", &[4, 4], IoKind::Output)
+            .is_err());
+
+        circuit
+            .group_wires("overlap", &[1, 3], IoKind::Input)
+            .unwrap();
+        assert!(circuit.materialize_groups(GroupLayout::Renumber).is_err());
+        assert!(circuit.materialize_groups(GroupLayout::Copies).is_err());
+    }
+}
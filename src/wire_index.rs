@@ -0,0 +1,230 @@
+use smallvec::SmallVec;
+
+use crate::{
+    bristol_circuit::BristolCircuit, gate::takes_literals, io_kind::IoKind, trace::trace_span,
+};
+
+/// What assigns a wire.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum WireProducer<'a> {
+    /// A gate, by index.
+    Gate(usize),
+
+    /// A named input or input wire group.
+    Input(&'a str),
+
+    Constant(&'a str),
+}
+
+/// Which gates produce and consume each wire of a circuit, from
+/// [`BristolCircuit::build_wire_index`].
+#[derive(Clone, Debug)]
+pub struct WireIndex<'a> {
+    circuit: &'a BristolCircuit,
+    producers: Vec<Option<WireProducer<'a>>>,
+    consumers: Vec<SmallVec<[usize; 2]>>,
+    outputs: Vec<bool>,
+
+    /// The first gate output that writes an already assigned wire, with what assigned it.
+    reassigned: Option<(usize, usize, WireProducer<'a>)>,
+}
+
+impl BristolCircuit {
+    /// Indexes each wire's producer and consumers in one pass over the gates. Inputs, input wire
+    /// groups and constants are assigned before the gates, so a gate writing one of their wires
+    /// doesn't become its producer, nor does a gate writing a wire an earlier gate wrote. `EQ`
+    /// inputs are literal values, not wires, so don't make `EQ` gates consumers.
+    pub fn build_wire_index(&self) -> WireIndex<'_> {
+        let _span = trace_span!("wire_index", gate_count = self.gates.len());
+
+        let len = self.wire_count.max(self.referenced_wire_count());
+        let mut producers = vec![None; len];
+        let mut consumers = vec![SmallVec::new(); len];
+        let mut outputs = vec![false; len];
+        let mut reassigned = None;
+
+        for bus in self.input_buses() {
+            producers[bus.wire..bus.wire + bus.width].fill(Some(WireProducer::Input(bus.name)));
+        }
+
+        let mut groups = self.info.wire_groups.iter().collect::<Vec<_>>();
+        groups.sort_by_key(|(name, _)| name.as_str());
+
+        for (name, group) in groups {
+            for wire in group.span.wires() {
+                match group.direction {
+                    IoKind::Input => {
+                        producers[wire].get_or_insert(WireProducer::Input(name));
+                    }
+                    IoKind::Output => outputs[wire] = true,
+                }
+            }
+        }
+
+        for (name, constant) in &self.info.constants {
+            producers[constant.wire_index] = Some(WireProducer::Constant(name));
+        }
+
+        for bus in self.output_buses() {
+            outputs[bus.wire..bus.wire + bus.width].fill(true);
+        }
+
+        for (i, gate) in self.gates.iter().enumerate() {
+            if !takes_literals(&gate.op) {
+                for &wire in &gate.inputs {
+                    consumers[wire].push(i);
+                }
+            }
+
+            for &wire in &gate.outputs {
+                match producers[wire] {
+                    None => producers[wire] = Some(WireProducer::Gate(i)),
+                    Some(producer) => {
+                        reassigned.get_or_insert((i, wire, producer));
+                    }
+                }
+            }
+        }
+
+        WireIndex {
+            circuit: self,
+            producers,
+            consumers,
+            outputs,
+            reassigned,
+        }
+    }
+}
+
+impl<'a> WireIndex<'a> {
+    pub fn circuit(&self) -> &'a BristolCircuit {
+        self.circuit
+    }
+
+    /// What assigns `wire`, or `None` if nothing does.
+    pub fn producer(&self, wire: usize) -> Option<WireProducer<'a>> {
+        self.producers.get(wire).copied().flatten()
+    }
+
+    /// The gates reading `wire`, in order. A gate reading it twice is listed twice.
+    pub fn consumers(&self, wire: usize) -> &[usize] {
+        self.consumers.get(wire).map_or(&[], |gates| gates)
+    }
+
+    pub fn fan_out(&self, wire: usize) -> usize {
+        self.consumers(wire).len()
+    }
+
+    /// Whether `wire` belongs to a named output or output wire group.
+    pub fn is_output(&self, wire: usize) -> bool {
+        self.outputs.get(wire).copied().unwrap_or(false)
+    }
+
+    /// The wires nothing assigns, in order.
+    pub fn undriven_wires(&self) -> impl Iterator<Item = usize> + '_ {
+        (0..self.producers.len()).filter(|&wire| self.producers[wire].is_none())
+    }
+
+    /// The wires that are assigned but that no gate reads and no output includes, in order.
+    pub fn unconsumed_wires(&self) -> impl Iterator<Item = usize> + '_ {
+        (0..self.producers.len()).filter(|&wire| {
+            self.producers[wire].is_some() && self.consumers[wire].is_empty() && !self.outputs[wire]
+        })
+    }
+
+    /// The wires of the named outputs and output wire groups, in order.
+    pub(crate) fn output_wires(&self) -> impl Iterator<Item = usize> + '_ {
+        (0..self.outputs.len()).filter(|&wire| self.outputs[wire])
+    }
+
+    /// The first gate, in order, with an output wire that was already assigned: the gate, the
+    /// wire and what assigned it first.
+    pub(crate) fn first_reassignment(&self) -> Option<(usize, usize, WireProducer<'a>)> {
+        self.reassigned
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{circuit_builder::CircuitBuilder, gadgets};
+
+    #[test]
+    fn test_multiple_consumers() {
+        let mut b = CircuitBuilder::new();
+        let x = b.input("x");
+        let y = b.input("y");
+        let one = b.constant("one", "1");
+        let a = b.gate("AND", &[x, y]);
+        let c = b.gate("XOR", &[a, x]);
+        let d = b.gate("XOR", &[a, a]);
+        b.output("c", c);
+        b.output("d", d);
+        b.gate("INV", &[one]);
+        let circuit = b.build().unwrap();
+
+        let info = &circuit.info;
+        let x = info.input_name_to_wire_index["x"];
+        let one = info.constants["one"].wire_index;
+        let [a, c, d, inv] = [0, 1, 2, 3].map(|i| circuit.gates[i].outputs[0]);
+        assert_eq!(info.output_name_to_wire_index["d"], d);
+
+        let index = circuit.build_wire_index();
+        assert_eq!(index.producer(x), Some(WireProducer::Input("x")));
+        assert_eq!(index.producer(one), Some(WireProducer::Constant("one")));
+        assert_eq!(index.producer(a), Some(WireProducer::Gate(0)));
+        assert_eq!(index.consumers(a), [1, 2, 2]);
+        assert_eq!(index.fan_out(a), 3);
+        assert_eq!(index.consumers(x), [0, 1]);
+        assert!(index.consumers(c).is_empty());
+        assert!(index.is_output(c) && !index.is_output(a));
+
+        // Only the INV's output is assigned but unused.
+        assert_eq!(index.unconsumed_wires().collect::<Vec<_>>(), [inv]);
+        assert_eq!(index.undriven_wires().count(), 0);
+        assert_eq!(index.first_reassignment(), None);
+    }
+
+    #[test]
+    fn test_input_that_is_an_output() {
+        let mut b = CircuitBuilder::new();
+        let x = b.input("x");
+        let y = b.input("y");
+        let z = b.gate("AND", &[x, y]);
+        b.output("x_out", x);
+        b.output("z", z);
+        let circuit = b.build().unwrap();
+
+        let x = circuit.info.input_name_to_wire_index["x"];
+        let z = circuit.gates[0].outputs[0];
+
+        let index = circuit.build_wire_index();
+        assert_eq!(index.producer(x), Some(WireProducer::Input("x")));
+        assert!(index.is_output(x));
+        assert_eq!(index.consumers(x), [0]);
+        assert_eq!(index.unconsumed_wires().count(), 0);
+        assert_eq!(index.output_wires().collect::<Vec<_>>(), [x, z]);
+    }
+
+    #[test]
+    fn test_undriven_and_reassigned() {
+        let mut circuit = gadgets::adder(2, true);
+        let spare = circuit.wire_count;
+        circuit.wire_count += 1;
+        circuit.gates[0].inputs[0] = spare;
+        let overwritten = std::mem::replace(&mut circuit.gates[2].outputs[0], 1);
+
+        let index = circuit.build_wire_index();
+        assert_eq!(
+            index.undriven_wires().collect::<Vec<_>>(),
+            [overwritten, spare]
+        );
+        assert_eq!(index.producer(spare), None);
+        assert_eq!(index.producer(100), None);
+        assert!(index.consumers(100).is_empty());
+        assert_eq!(
+            index.first_reassignment(),
+            Some((2, 1, WireProducer::Input("a")))
+        );
+    }
+}
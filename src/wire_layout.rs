@@ -0,0 +1,241 @@
+use std::collections::{HashMap, HashSet};
+
+use smallvec::smallvec;
+
+use crate::{
+    bristol_circuit::BristolCircuit, bristol_circuit_error::BristolCircuitError, gate::Gate,
+    trace::trace_span, wire_index::WireProducer,
+};
+
+impl BristolCircuit {
+    /// Renumbers the wires to the usual Bristol layout: the named inputs first, from wire 0 in
+    /// wire order, then the constants by name, then the other wires in their current order, and
+    /// the named outputs last, in wire order, ending at `wire_count`. Unused wires are dropped.
+    ///
+    /// An output wire that is an input or constant, or that an earlier output already uses,
+    /// gets an `EQW` copy gate after the others, so every output has wires of its own.
+    pub fn normalize_wire_layout(&self) -> BristolCircuit {
+        let _span = trace_span!(
+            "pass",
+            name = "normalize_wire_layout",
+            gate_count = self.gates.len()
+        );
+
+        let index = self.build_wire_index();
+        let mut mapping = HashMap::<usize, usize>::new();
+        let mut next = 0;
+
+        let mut constants = self.info.constants.iter().collect::<Vec<_>>();
+        constants.sort_by_key(|(name, _)| name.as_str());
+
+        let leading = self
+            .input_buses()
+            .into_iter()
+            .flat_map(|bus| bus.wire..bus.wire + bus.width)
+            .chain(constants.iter().map(|(_, constant)| constant.wire_index))
+            .collect::<Vec<_>>();
+
+        for wire in leading {
+            mapping.entry(wire).or_insert_with(|| {
+                next += 1;
+                next - 1
+            });
+        }
+
+        // Each output wire, and whether it needs a copy rather than being moved.
+        let outputs = self.output_buses();
+        let mut moved = HashSet::new();
+        let slots = outputs
+            .iter()
+            .flat_map(|bus| bus.wire..bus.wire + bus.width)
+            .map(|wire| {
+                let fixed = matches!(
+                    index.producer(wire),
+                    Some(WireProducer::Input(_) | WireProducer::Constant(_))
+                );
+
+                (wire, fixed || !moved.insert(wire))
+            })
+            .collect::<Vec<_>>();
+
+        for wire in self.used_wires() {
+            if !mapping.contains_key(&wire) && !moved.contains(&wire) {
+                mapping.insert(wire, next);
+                next += 1;
+            }
+        }
+
+        let first_output = next;
+        let mut copies = Vec::new();
+
+        for (i, &(wire, copy)) in slots.iter().enumerate() {
+            if copy {
+                copies.push((wire, first_output + i));
+            } else {
+                mapping.insert(wire, first_output + i);
+            }
+        }
+
+        let mut result = self.clone();
+        result.map_wires(|wire| mapping[&wire]);
+
+        let mut slot = first_output;
+
+        for bus in &outputs {
+            *result
+                .info
+                .output_name_to_wire_index
+                .get_mut(bus.name)
+                .expect("output buses come from the info") = slot;
+            slot += bus.width;
+        }
+
+        result
+            .gates
+            .extend(copies.into_iter().map(|(wire, copy)| Gate {
+                inputs: smallvec![mapping[&wire]],
+                outputs: smallvec![copy],
+                op: "EQW".into(),
+            }));
+        result.wire_count = first_output + slots.len();
+
+        result
+    }
+
+    /// Checks the usual Bristol layout: the named inputs fill the first wires, each starting
+    /// where the one before ends, and the named outputs likewise fill the last wires, without
+    /// overlapping the inputs. [`BristolCircuit::normalize_wire_layout`] arranges this.
+    pub fn check_standard_layout(&self) -> Result<(), BristolCircuitError> {
+        let inconsistency = |message: String| BristolCircuitError::Inconsistency { message };
+
+        let mut expected = 0;
+
+        for bus in self.input_buses() {
+            if bus.wire != expected {
+                return Err(inconsistency(format!(
+                    "Input {} starts at wire {}, not {}",
+                    bus.name, bus.wire, expected
+                )));
+            }
+
+            expected += bus.width;
+        }
+
+        let outputs = self.output_buses();
+        let output_width = outputs.iter().map(|bus| bus.width).sum::<usize>();
+
+        let Some(first_output) = self
+            .wire_count
+            .checked_sub(output_width)
+            .filter(|&first| first >= expected)
+        else {
+            return Err(inconsistency(format!(
+                "The outputs' {} wires don't fit after the inputs' {} in {} wires",
+                output_width, expected, self.wire_count
+            )));
+        };
+
+        let mut expected = first_output;
+
+        for bus in outputs {
+            if bus.wire != expected {
+                return Err(inconsistency(format!(
+                    "Output {} starts at wire {}, not {}",
+                    bus.name, bus.wire, expected
+                )));
+            }
+
+            expected += bus.width;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{circuit_builder::CircuitBuilder, gadgets};
+
+    #[test]
+    fn test_gadgets_are_standard() {
+        let circuit = gadgets::adder(8, true);
+        assert!(circuit.check_standard_layout().is_ok());
+
+        let normalized = circuit.normalize_wire_layout();
+        assert!(normalized.check_standard_layout().is_ok());
+        assert_eq!(normalized.gates.len(), circuit.gates.len());
+    }
+
+    #[test]
+    fn test_shared_and_passed_through_outputs() {
+        // `x` is output directly, `and` twice, with a constant.
+        let mut b = CircuitBuilder::new();
+        let x = b.input("x");
+        let y = b.input("y");
+        let one = b.constant("one", "1");
+        let and = b.gate("AND", &[x, y]);
+        let out = b.gate("XOR", &[and, one]);
+        b.output("xor", out);
+        b.output("and", and);
+        b.output("x_copy", x);
+        b.output("also_and", and);
+        let mut circuit = b.build().unwrap();
+
+        // Move the inputs up, out of the standard place.
+        circuit.map_wires(|wire| wire + 10);
+        circuit.recompute_wire_count();
+
+        let err = circuit.check_standard_layout().unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Inconsistency: Input x starts at wire 10, not 0"
+        );
+
+        let normalized = circuit.normalize_wire_layout();
+        assert!(normalized.check_standard_layout().is_ok());
+        assert!(normalized.validate().is_ok());
+        assert!(normalized.is_topologically_sorted());
+        normalized.assert_wire_count_tight();
+
+        assert_eq!(normalized.info.input_name_to_wire_index["x"], 0);
+        assert_eq!(normalized.info.input_name_to_wire_index["y"], 1);
+        assert_eq!(normalized.info.constants["one"].wire_index, 2);
+        assert_eq!(normalized.wire_count, 3 + 4);
+
+        // The passed-through input and the second use of `and` are copies.
+        assert_eq!(normalized.gates.len(), circuit.gates.len() + 2);
+        assert!(normalized.gates[2..].iter().all(|gate| gate.op == "EQW"));
+
+        for (x, y) in [(false, false), (true, false), (true, true)] {
+            let inputs = HashMap::from([("x".to_string(), vec![x]), ("y".to_string(), vec![y])]);
+            assert_eq!(
+                normalized.eval_boolean(&inputs).unwrap(),
+                circuit.eval_boolean(&inputs).unwrap()
+            );
+        }
+    }
+
+    #[test]
+    fn test_check_standard_layout_errors() {
+        let mut circuit = gadgets::adder(2, true);
+        circuit.wire_count += 1;
+
+        let err = circuit.check_standard_layout().unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            format!(
+                "Inconsistency: Output sum starts at wire {}, not {}",
+                circuit.wire_count - 4,
+                circuit.wire_count - 3
+            )
+        );
+
+        circuit.wire_count = 4;
+        let err = circuit.check_standard_layout().unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Inconsistency: The outputs' 3 wires don't fit after the inputs' 4 in 4 wires"
+        );
+    }
+}
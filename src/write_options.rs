@@ -0,0 +1,5 @@
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct WriteOptions {
+    /// Written before the size line, each of its lines as a `#` comment.
+    pub header_comment: Option<String>,
+}